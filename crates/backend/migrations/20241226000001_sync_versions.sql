@@ -0,0 +1,50 @@
+-- Change tracking for incremental (delta) cloud sync.
+-- Every insert or real update stamps the row with the next value of a global
+-- sequence; clients pull rows whose sync_version is greater than their cursor.
+CREATE SEQUENCE IF NOT EXISTS sync_version_seq;
+
+ALTER TABLE user_games ADD COLUMN IF NOT EXISTS sync_version BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE user_achievements ADD COLUMN IF NOT EXISTS sync_version BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE run_history ADD COLUMN IF NOT EXISTS sync_version BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE achievement_history ADD COLUMN IF NOT EXISTS sync_version BIGINT NOT NULL DEFAULT 0;
+
+CREATE OR REPLACE FUNCTION bump_sync_version() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.sync_version := nextval('sync_version_seq');
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS user_games_sync_insert ON user_games;
+CREATE TRIGGER user_games_sync_insert BEFORE INSERT ON user_games
+    FOR EACH ROW EXECUTE FUNCTION bump_sync_version();
+DROP TRIGGER IF EXISTS user_games_sync_update ON user_games;
+CREATE TRIGGER user_games_sync_update BEFORE UPDATE ON user_games
+    FOR EACH ROW WHEN (OLD.* IS DISTINCT FROM NEW.*) EXECUTE FUNCTION bump_sync_version();
+
+DROP TRIGGER IF EXISTS user_achievements_sync_insert ON user_achievements;
+CREATE TRIGGER user_achievements_sync_insert BEFORE INSERT ON user_achievements
+    FOR EACH ROW EXECUTE FUNCTION bump_sync_version();
+DROP TRIGGER IF EXISTS user_achievements_sync_update ON user_achievements;
+CREATE TRIGGER user_achievements_sync_update BEFORE UPDATE ON user_achievements
+    FOR EACH ROW WHEN (OLD.* IS DISTINCT FROM NEW.*) EXECUTE FUNCTION bump_sync_version();
+
+DROP TRIGGER IF EXISTS run_history_sync_insert ON run_history;
+CREATE TRIGGER run_history_sync_insert BEFORE INSERT ON run_history
+    FOR EACH ROW EXECUTE FUNCTION bump_sync_version();
+DROP TRIGGER IF EXISTS run_history_sync_update ON run_history;
+CREATE TRIGGER run_history_sync_update BEFORE UPDATE ON run_history
+    FOR EACH ROW WHEN (OLD.* IS DISTINCT FROM NEW.*) EXECUTE FUNCTION bump_sync_version();
+
+DROP TRIGGER IF EXISTS achievement_history_sync_insert ON achievement_history;
+CREATE TRIGGER achievement_history_sync_insert BEFORE INSERT ON achievement_history
+    FOR EACH ROW EXECUTE FUNCTION bump_sync_version();
+DROP TRIGGER IF EXISTS achievement_history_sync_update ON achievement_history;
+CREATE TRIGGER achievement_history_sync_update BEFORE UPDATE ON achievement_history
+    FOR EACH ROW WHEN (OLD.* IS DISTINCT FROM NEW.*) EXECUTE FUNCTION bump_sync_version();
+
+-- Indexes for delta pulls
+CREATE INDEX IF NOT EXISTS idx_user_games_sync_version ON user_games(steam_id, sync_version);
+CREATE INDEX IF NOT EXISTS idx_user_achievements_sync_version ON user_achievements(steam_id, sync_version);
+CREATE INDEX IF NOT EXISTS idx_run_history_sync_version ON run_history(steam_id, sync_version);
+CREATE INDEX IF NOT EXISTS idx_achievement_history_sync_version ON achievement_history(steam_id, sync_version);
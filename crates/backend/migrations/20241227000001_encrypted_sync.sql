@@ -0,0 +1,12 @@
+-- Client-encrypted cloud backups. The server cannot decrypt these; the
+-- plaintext tables keep holding what the guest view needs.
+CREATE TABLE IF NOT EXISTS encrypted_sync_blobs (
+    steam_id BIGINT PRIMARY KEY REFERENCES users(steam_id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    salt TEXT NOT NULL,
+    key_check TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    ciphertext TEXT NOT NULL,
+    last_sync TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,40 @@
+-- Users following other users' public profiles
+CREATE TABLE IF NOT EXISTS follows (
+    follower_steam_id BIGINT NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    followed_steam_id BIGINT NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (follower_steam_id, followed_steam_id),
+    CHECK (follower_steam_id <> followed_steam_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_follows_followed ON follows(followed_steam_id);
+
+-- First time a game was played, for the activity feed
+CREATE TABLE IF NOT EXISTS first_plays (
+    steam_id BIGINT NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    appid BIGINT NOT NULL,
+    played_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (steam_id, appid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_first_plays_played_at ON first_plays(steam_id, played_at DESC);
+
+-- Record a first play whenever a game's playtime leaves zero, however the
+-- update arrives (server sync or cloud upload)
+CREATE OR REPLACE FUNCTION record_first_play() RETURNS TRIGGER AS $$
+BEGIN
+    IF OLD.playtime_forever = 0 AND NEW.playtime_forever > 0 THEN
+        INSERT INTO first_plays (steam_id, appid, played_at)
+        VALUES (
+            NEW.steam_id,
+            NEW.appid,
+            COALESCE(to_timestamp(NULLIF(NEW.rtime_last_played, 0)), NOW())
+        )
+        ON CONFLICT (steam_id, appid) DO NOTHING;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER user_games_first_play AFTER UPDATE OF playtime_forever ON user_games
+    FOR EACH ROW EXECUTE FUNCTION record_first_play();
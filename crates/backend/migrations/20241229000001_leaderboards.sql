@@ -0,0 +1,32 @@
+-- Users appear on global leaderboards only after opting in
+ALTER TABLE users ADD COLUMN IF NOT EXISTS leaderboard_opt_in BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Per-user totals for leaderboards, refreshed periodically by the server
+-- (REFRESH MATERIALIZED VIEW CONCURRENTLY needs the unique index below)
+CREATE MATERIALIZED VIEW IF NOT EXISTS leaderboard_stats AS
+SELECT
+    u.steam_id,
+    COALESCE(g.total_unlocks, 0)::BIGINT AS total_unlocks,
+    COALESCE(g.perfect_games, 0)::BIGINT AS perfect_games,
+    COALESCE(g.avg_completion, 0)::DOUBLE PRECISION AS avg_completion,
+    COALESCE(m.unlocks_this_month, 0)::BIGINT AS unlocks_this_month,
+    NOW() AS refreshed_at
+FROM users u
+LEFT JOIN (
+    SELECT
+        steam_id,
+        SUM(achievements_unlocked) FILTER (WHERE achievements_total > 0) AS total_unlocks,
+        COUNT(*) FILTER (WHERE achievements_total > 0 AND achievements_unlocked >= achievements_total) AS perfect_games,
+        AVG(achievements_unlocked::DOUBLE PRECISION / achievements_total * 100)
+            FILTER (WHERE achievements_total > 0 AND playtime_forever > 0) AS avg_completion
+    FROM user_games
+    GROUP BY steam_id
+) g ON g.steam_id = u.steam_id
+LEFT JOIN (
+    SELECT steam_id, COUNT(*) AS unlocks_this_month
+    FROM user_achievements
+    WHERE achieved = true AND unlocktime >= date_trunc('month', NOW())
+    GROUP BY steam_id
+) m ON m.steam_id = u.steam_id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_leaderboard_stats_steam_id ON leaderboard_stats(steam_id);
@@ -0,0 +1,18 @@
+-- Community unlock rates are aggregated per achievement
+CREATE INDEX IF NOT EXISTS idx_user_achievements_unlocked
+    ON user_achievements(appid, apiname) WHERE achieved = true;
+
+-- Cache of Steam's global unlock percentages
+CREATE TABLE IF NOT EXISTS steam_achievement_percentages (
+    appid BIGINT NOT NULL,
+    apiname TEXT NOT NULL,
+    percent REAL NOT NULL,
+    PRIMARY KEY (appid, apiname)
+);
+
+-- When Steam's percentages were last fetched for a game (also recorded for
+-- games Steam has no percentages for, so they aren't fetched every time)
+CREATE TABLE IF NOT EXISTS steam_percentages_fetched (
+    appid BIGINT PRIMARY KEY,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
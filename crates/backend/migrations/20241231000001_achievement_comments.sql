@@ -0,0 +1,20 @@
+-- Comments on achievements; one comment can tag several achievements
+CREATE TABLE IF NOT EXISTS achievement_comments (
+    id BIGSERIAL PRIMARY KEY,
+    steam_id BIGINT NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    comment TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    edited_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_achievement_comments_steam_id ON achievement_comments(steam_id);
+
+-- Achievements tagged by a comment
+CREATE TABLE IF NOT EXISTS achievement_comment_tags (
+    comment_id BIGINT NOT NULL REFERENCES achievement_comments(id) ON DELETE CASCADE,
+    appid BIGINT NOT NULL,
+    apiname TEXT NOT NULL,
+    PRIMARY KEY (comment_id, appid, apiname)
+);
+
+CREATE INDEX IF NOT EXISTS idx_achievement_comment_tags_achievement ON achievement_comment_tags(appid, apiname);
@@ -0,0 +1,11 @@
+-- Editing and voting on achievement tips
+ALTER TABLE achievement_tips ADD COLUMN IF NOT EXISTS edited_at TIMESTAMPTZ;
+
+-- One vote per user and tip: 1 (helpful) or -1 (not helpful)
+CREATE TABLE IF NOT EXISTS achievement_tip_votes (
+    tip_id INTEGER NOT NULL REFERENCES achievement_tips(id) ON DELETE CASCADE,
+    steam_id BIGINT NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    vote SMALLINT NOT NULL CHECK (vote IN (-1, 1)),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tip_id, steam_id)
+);
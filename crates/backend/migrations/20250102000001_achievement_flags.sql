@@ -0,0 +1,5 @@
+-- Structured achievement flags (missable, bugged, grind, online-only) as a
+-- bitmask next to the difficulty rating. A user may flag an achievement
+-- without rating it, so the rating becomes optional.
+ALTER TABLE achievement_ratings ALTER COLUMN rating DROP NOT NULL;
+ALTER TABLE achievement_ratings ADD COLUMN IF NOT EXISTS flags SMALLINT NOT NULL DEFAULT 0;
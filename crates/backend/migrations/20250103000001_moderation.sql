@@ -0,0 +1,26 @@
+-- Moderation of community content
+-- Admins are granted directly in the database:
+--   UPDATE users SET is_admin = TRUE WHERE steam_id = ...;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS banned_at TIMESTAMPTZ;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS ban_reason TEXT;
+
+-- Hidden content is kept for review but excluded from all community queries
+ALTER TABLE achievement_comments ADD COLUMN IF NOT EXISTS hidden_at TIMESTAMPTZ;
+ALTER TABLE achievement_tips ADD COLUMN IF NOT EXISTS hidden_at TIMESTAMPTZ;
+ALTER TABLE game_ratings ADD COLUMN IF NOT EXISTS hidden_at TIMESTAMPTZ;
+
+-- User reports of comments, tips and reviews; one open report per user and item
+CREATE TABLE IF NOT EXISTS content_reports (
+    id BIGSERIAL PRIMARY KEY,
+    reporter_steam_id BIGINT NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('comment', 'tip', 'review')),
+    content_id BIGINT NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    UNIQUE (reporter_steam_id, kind, content_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_reports_open ON content_reports(created_at) WHERE resolved_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_content_reports_content ON content_reports(kind, content_id);
@@ -0,0 +1,18 @@
+-- Login sessions. Access tokens are short-lived JWTs carrying the session id;
+-- they are renewed with a refresh token that is replaced on every use.
+CREATE TABLE IF NOT EXISTS sessions (
+    id BIGSERIAL PRIMARY KEY,
+    steam_id BIGINT NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    -- SHA-256 of the current refresh token and of the one it replaced
+    -- (presenting a replaced token again revokes the session)
+    refresh_token_hash TEXT NOT NULL UNIQUE,
+    previous_token_hash TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_steam_id ON sessions(steam_id) WHERE revoked_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_sessions_previous_token ON sessions(previous_token_hash);
+CREATE INDEX IF NOT EXISTS idx_sessions_revoked ON sessions(revoked_at) WHERE revoked_at IS NOT NULL;
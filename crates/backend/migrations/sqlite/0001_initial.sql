@@ -0,0 +1,325 @@
+-- Schema of the SQLite storage, equivalent to the PostgreSQL migrations up to
+-- 20250104000001_sessions. Timestamps are stored as text in the format
+-- written by rusqlite ('YYYY-MM-DD HH:MM:SS.fff+00:00'), so they sort and
+-- compare as text.
+
+CREATE TABLE users (
+    steam_id INTEGER PRIMARY KEY,
+    display_name TEXT NOT NULL,
+    avatar_url TEXT,
+    short_id TEXT UNIQUE,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    last_seen TEXT DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    leaderboard_opt_in INTEGER NOT NULL DEFAULT 0,
+    is_admin INTEGER NOT NULL DEFAULT 0,
+    banned_at TEXT,
+    ban_reason TEXT
+);
+
+CREATE TABLE user_games (
+    steam_id INTEGER REFERENCES users(steam_id) ON DELETE CASCADE,
+    appid INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    playtime_forever INTEGER NOT NULL DEFAULT 0,
+    rtime_last_played INTEGER,
+    img_icon_url TEXT,
+    added_at TEXT DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    achievements_total INTEGER,
+    achievements_unlocked INTEGER,
+    last_sync TEXT,
+    sync_version INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (steam_id, appid)
+);
+
+CREATE TABLE user_achievements (
+    steam_id INTEGER REFERENCES users(steam_id) ON DELETE CASCADE,
+    appid INTEGER NOT NULL,
+    apiname TEXT NOT NULL,
+    achieved INTEGER DEFAULT 0,
+    unlocktime TEXT,
+    sync_version INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (steam_id, appid, apiname)
+);
+
+CREATE TABLE achievement_schemas (
+    appid INTEGER NOT NULL,
+    apiname TEXT NOT NULL,
+    display_name TEXT NOT NULL,
+    description TEXT,
+    icon TEXT NOT NULL,
+    icon_gray TEXT NOT NULL,
+    cached_at TEXT DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    PRIMARY KEY (appid, apiname)
+);
+
+CREATE TABLE game_ratings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    steam_id INTEGER REFERENCES users(steam_id) ON DELETE CASCADE,
+    appid INTEGER NOT NULL,
+    rating INTEGER CHECK (rating >= 1 AND rating <= 5),
+    comment TEXT,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    hidden_at TEXT,
+    UNIQUE (steam_id, appid)
+);
+
+CREATE TABLE achievement_tips (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    steam_id INTEGER REFERENCES users(steam_id) ON DELETE CASCADE,
+    appid INTEGER NOT NULL,
+    apiname TEXT NOT NULL,
+    difficulty INTEGER CHECK (difficulty >= 1 AND difficulty <= 5),
+    tip TEXT NOT NULL,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    edited_at TEXT,
+    hidden_at TEXT,
+    UNIQUE (steam_id, appid, apiname)
+);
+
+CREATE TABLE achievement_tip_votes (
+    tip_id INTEGER NOT NULL REFERENCES achievement_tips(id) ON DELETE CASCADE,
+    steam_id INTEGER NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    vote INTEGER NOT NULL CHECK (vote IN (-1, 1)),
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    PRIMARY KEY (tip_id, steam_id)
+);
+
+CREATE TABLE run_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    steam_id INTEGER REFERENCES users(steam_id) ON DELETE CASCADE,
+    run_at TEXT DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    total_games INTEGER NOT NULL,
+    unplayed_games INTEGER NOT NULL DEFAULT 0,
+    unplayed_games_total INTEGER NOT NULL DEFAULT 0,
+    sync_version INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE achievement_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    steam_id INTEGER REFERENCES users(steam_id) ON DELETE CASCADE,
+    recorded_at TEXT DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    total_achievements INTEGER NOT NULL,
+    unlocked_achievements INTEGER NOT NULL,
+    games_with_achievements INTEGER NOT NULL,
+    avg_completion_percent REAL NOT NULL,
+    sync_version INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE achievement_ratings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    steam_id INTEGER REFERENCES users(steam_id) ON DELETE CASCADE,
+    appid INTEGER NOT NULL,
+    apiname TEXT NOT NULL,
+    rating INTEGER CHECK (rating >= 1 AND rating <= 5),
+    flags INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    UNIQUE (steam_id, appid, apiname)
+);
+
+CREATE TABLE encrypted_sync_blobs (
+    steam_id INTEGER PRIMARY KEY REFERENCES users(steam_id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    salt TEXT NOT NULL,
+    key_check TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    ciphertext TEXT NOT NULL,
+    last_sync TEXT,
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now'))
+);
+
+CREATE TABLE follows (
+    follower_steam_id INTEGER NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    followed_steam_id INTEGER NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    PRIMARY KEY (follower_steam_id, followed_steam_id),
+    CHECK (follower_steam_id <> followed_steam_id)
+);
+
+CREATE TABLE first_plays (
+    steam_id INTEGER NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    appid INTEGER NOT NULL,
+    played_at TEXT NOT NULL,
+    PRIMARY KEY (steam_id, appid)
+);
+
+-- A plain table instead of PostgreSQL's materialized view; the server
+-- rebuilds it periodically
+CREATE TABLE leaderboard_stats (
+    steam_id INTEGER PRIMARY KEY,
+    total_unlocks INTEGER NOT NULL,
+    perfect_games INTEGER NOT NULL,
+    avg_completion REAL NOT NULL,
+    unlocks_this_month INTEGER NOT NULL,
+    refreshed_at TEXT NOT NULL
+);
+
+CREATE TABLE steam_achievement_percentages (
+    appid INTEGER NOT NULL,
+    apiname TEXT NOT NULL,
+    percent REAL NOT NULL,
+    PRIMARY KEY (appid, apiname)
+);
+
+CREATE TABLE steam_percentages_fetched (
+    appid INTEGER PRIMARY KEY,
+    fetched_at TEXT NOT NULL
+);
+
+CREATE TABLE achievement_comments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    steam_id INTEGER NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    comment TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    edited_at TEXT,
+    hidden_at TEXT
+);
+
+CREATE TABLE achievement_comment_tags (
+    comment_id INTEGER NOT NULL REFERENCES achievement_comments(id) ON DELETE CASCADE,
+    appid INTEGER NOT NULL,
+    apiname TEXT NOT NULL,
+    PRIMARY KEY (comment_id, appid, apiname)
+);
+
+CREATE TABLE content_reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    reporter_steam_id INTEGER NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('comment', 'tip', 'review')),
+    content_id INTEGER NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    resolved_at TEXT,
+    UNIQUE (reporter_steam_id, kind, content_id)
+);
+
+CREATE TABLE sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    steam_id INTEGER NOT NULL REFERENCES users(steam_id) ON DELETE CASCADE,
+    refresh_token_hash TEXT NOT NULL UNIQUE,
+    previous_token_hash TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    last_used_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')),
+    expires_at TEXT NOT NULL,
+    revoked_at TEXT
+);
+
+CREATE INDEX idx_user_games_sync_version ON user_games(steam_id, sync_version);
+CREATE INDEX idx_user_achievements_sync_version ON user_achievements(steam_id, sync_version);
+CREATE INDEX idx_user_achievements_unlocked ON user_achievements(appid, apiname) WHERE achieved = 1;
+CREATE INDEX idx_game_ratings_appid ON game_ratings(appid);
+CREATE INDEX idx_achievement_tips_appid_apiname ON achievement_tips(appid, apiname);
+CREATE INDEX idx_run_history_sync_version ON run_history(steam_id, sync_version);
+CREATE INDEX idx_achievement_history_sync_version ON achievement_history(steam_id, sync_version);
+CREATE INDEX idx_achievement_ratings_appid_apiname ON achievement_ratings(appid, apiname);
+CREATE INDEX idx_achievement_ratings_steam_id ON achievement_ratings(steam_id);
+CREATE INDEX idx_follows_followed ON follows(followed_steam_id);
+CREATE INDEX idx_first_plays_played_at ON first_plays(steam_id, played_at DESC);
+CREATE INDEX idx_achievement_comments_steam_id ON achievement_comments(steam_id);
+CREATE INDEX idx_achievement_comment_tags_achievement ON achievement_comment_tags(appid, apiname);
+CREATE INDEX idx_content_reports_open ON content_reports(created_at) WHERE resolved_at IS NULL;
+CREATE INDEX idx_content_reports_content ON content_reports(kind, content_id);
+CREATE INDEX idx_sessions_steam_id ON sessions(steam_id) WHERE revoked_at IS NULL;
+CREATE INDEX idx_sessions_previous_token ON sessions(previous_token_hash);
+CREATE INDEX idx_sessions_revoked ON sessions(revoked_at) WHERE revoked_at IS NOT NULL;
+
+-- Change tracking for delta sync: every insert or real update stamps the row
+-- with the next value of a global counter. The triggers' own updates change
+-- sync_version, so they don't fire again.
+CREATE TABLE sync_version_seq (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    value INTEGER NOT NULL
+);
+INSERT INTO sync_version_seq (id, value) VALUES (1, 0);
+
+CREATE TRIGGER user_games_sync_insert AFTER INSERT ON user_games
+BEGIN
+    UPDATE sync_version_seq SET value = value + 1;
+    UPDATE user_games SET sync_version = (SELECT value FROM sync_version_seq) WHERE rowid = NEW.rowid;
+END;
+
+CREATE TRIGGER user_games_sync_update AFTER UPDATE ON user_games
+WHEN NEW.sync_version IS OLD.sync_version AND (
+    NEW.name IS NOT OLD.name
+    OR NEW.playtime_forever IS NOT OLD.playtime_forever
+    OR NEW.rtime_last_played IS NOT OLD.rtime_last_played
+    OR NEW.img_icon_url IS NOT OLD.img_icon_url
+    OR NEW.added_at IS NOT OLD.added_at
+    OR NEW.achievements_total IS NOT OLD.achievements_total
+    OR NEW.achievements_unlocked IS NOT OLD.achievements_unlocked
+    OR NEW.last_sync IS NOT OLD.last_sync
+)
+BEGIN
+    UPDATE sync_version_seq SET value = value + 1;
+    UPDATE user_games SET sync_version = (SELECT value FROM sync_version_seq) WHERE rowid = NEW.rowid;
+END;
+
+CREATE TRIGGER user_achievements_sync_insert AFTER INSERT ON user_achievements
+BEGIN
+    UPDATE sync_version_seq SET value = value + 1;
+    UPDATE user_achievements SET sync_version = (SELECT value FROM sync_version_seq) WHERE rowid = NEW.rowid;
+END;
+
+CREATE TRIGGER user_achievements_sync_update AFTER UPDATE ON user_achievements
+WHEN NEW.sync_version IS OLD.sync_version AND (
+    NEW.achieved IS NOT OLD.achieved
+    OR NEW.unlocktime IS NOT OLD.unlocktime
+)
+BEGIN
+    UPDATE sync_version_seq SET value = value + 1;
+    UPDATE user_achievements SET sync_version = (SELECT value FROM sync_version_seq) WHERE rowid = NEW.rowid;
+END;
+
+CREATE TRIGGER run_history_sync_insert AFTER INSERT ON run_history
+BEGIN
+    UPDATE sync_version_seq SET value = value + 1;
+    UPDATE run_history SET sync_version = (SELECT value FROM sync_version_seq) WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER run_history_sync_update AFTER UPDATE ON run_history
+WHEN NEW.sync_version IS OLD.sync_version AND (
+    NEW.run_at IS NOT OLD.run_at
+    OR NEW.total_games IS NOT OLD.total_games
+    OR NEW.unplayed_games IS NOT OLD.unplayed_games
+    OR NEW.unplayed_games_total IS NOT OLD.unplayed_games_total
+)
+BEGIN
+    UPDATE sync_version_seq SET value = value + 1;
+    UPDATE run_history SET sync_version = (SELECT value FROM sync_version_seq) WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER achievement_history_sync_insert AFTER INSERT ON achievement_history
+BEGIN
+    UPDATE sync_version_seq SET value = value + 1;
+    UPDATE achievement_history SET sync_version = (SELECT value FROM sync_version_seq) WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER achievement_history_sync_update AFTER UPDATE ON achievement_history
+WHEN NEW.sync_version IS OLD.sync_version AND (
+    NEW.recorded_at IS NOT OLD.recorded_at
+    OR NEW.total_achievements IS NOT OLD.total_achievements
+    OR NEW.unlocked_achievements IS NOT OLD.unlocked_achievements
+    OR NEW.games_with_achievements IS NOT OLD.games_with_achievements
+    OR NEW.avg_completion_percent IS NOT OLD.avg_completion_percent
+)
+BEGIN
+    UPDATE sync_version_seq SET value = value + 1;
+    UPDATE achievement_history SET sync_version = (SELECT value FROM sync_version_seq) WHERE id = NEW.id;
+END;
+
+-- Record a first play whenever a game's playtime leaves zero
+CREATE TRIGGER user_games_first_play AFTER UPDATE OF playtime_forever ON user_games
+WHEN OLD.playtime_forever = 0 AND NEW.playtime_forever > 0
+BEGIN
+    INSERT INTO first_plays (steam_id, appid, played_at)
+    VALUES (
+        NEW.steam_id,
+        NEW.appid,
+        COALESCE(
+            strftime('%Y-%m-%d %H:%M:%f+00:00', NULLIF(NEW.rtime_last_played, 0), 'unixepoch'),
+            strftime('%Y-%m-%d %H:%M:%f+00:00', 'now')
+        )
+    )
+    ON CONFLICT (steam_id, appid) DO NOTHING;
+END;
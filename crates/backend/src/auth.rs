@@ -1,13 +1,85 @@
-//! Authentication - Steam OpenID and JWT
+//! Authentication - Steam OpenID, JWT access tokens and session refresh
 
 use axum::{
     extract::{Query, State},
-    response::{IntoResponse, Redirect},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+    Json,
 };
 use jsonwebtoken::{encode, decode, Header, Validation, EncodingKey, DecodingKey};
+use overachiever_core::{AuthCodeExchangeRequest, AuthTokenResponse, RefreshTokenRequest, UserProfile};
+use overachiever_core::api::{ApiError, LogoutAllResponse, SuccessResponse};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use crate::AppState;
+use crate::config::AuthConfig;
+use crate::sessions::{self, RevokedSessions};
+
+const CLAIMED_ID_PREFIX: &str = "https://steamcommunity.com/openid/id/";
+
+/// One-time login codes expire after this long
+const AUTH_CODE_TTL: Duration = Duration::from_secs(60);
+const AUTH_CODE_LENGTH: usize = 32;
+
+/// "scheme://host[:port]" of an absolute URL
+fn url_origin(url: &str) -> Option<&str> {
+    let scheme_end = url.find("://")? + 3;
+    let end = url[scheme_end..].find(['/', '?', '#']).map_or(url.len(), |i| scheme_end + i);
+    (end > scheme_end).then(|| &url[..end])
+}
+
+/// Redirect URIs on the allowlist are allowed, and so are pages on the same
+/// origin as the Steam callback
+fn is_allowed_redirect(config: &AuthConfig, redirect_uri: &str) -> bool {
+    let without_query = redirect_uri.split(['?', '#']).next().unwrap_or_default();
+    if config.redirect_allowlist.iter().any(|allowed| allowed == without_query) {
+        return true;
+    }
+    matches!((url_origin(redirect_uri), url_origin(&config.steam_callback_url)), (Some(a), Some(b)) if a == b)
+}
+
+/// Append query parameters to a redirect URI that may already have some
+fn with_query(uri: &str, query: &str) -> String {
+    let separator = if uri.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", uri, separator, query)
+}
+
+struct PendingLogin {
+    tokens: AuthTokenResponse,
+    issued_at: Instant,
+}
+
+/// Short-lived one-time codes handed to the login redirect in place of the
+/// tokens. The receiver exchanges the code at `POST /auth/token`.
+#[derive(Default)]
+pub struct AuthCodes {
+    codes: Mutex<HashMap<String, PendingLogin>>,
+}
+
+impl AuthCodes {
+    fn issue(&self, tokens: AuthTokenResponse) -> String {
+        let code: String = {
+            let mut rng = rand::thread_rng();
+            (0..AUTH_CODE_LENGTH)
+                .map(|_| rng.sample(rand::distributions::Alphanumeric) as char)
+                .collect()
+        };
+
+        let mut codes = self.codes.lock().unwrap();
+        codes.retain(|_, login| login.issued_at.elapsed() < AUTH_CODE_TTL);
+        codes.insert(code.clone(), PendingLogin { tokens, issued_at: Instant::now() });
+        code
+    }
+
+    /// Tokens for a code; every code can be redeemed once
+    fn redeem(&self, code: &str) -> Option<AuthTokenResponse> {
+        let login = self.codes.lock().unwrap().remove(code)?;
+        (login.issued_at.elapsed() < AUTH_CODE_TTL).then_some(login.tokens)
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
@@ -16,9 +88,28 @@ pub struct Claims {
     pub avatar_url: Option<String>,
     /// Short ID for shareable profile URLs
     pub short_id: Option<String>,
+    /// Login session the token belongs to
+    pub sid: i64,
     pub exp: usize,
 }
 
+#[derive(Debug)]
+pub enum TokenError {
+    Expired,
+    Revoked,
+    Invalid(jsonwebtoken::errors::Error),
+}
+
+impl std::fmt::Display for TokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenError::Expired => write!(f, "{}", overachiever_core::AUTH_TOKEN_EXPIRED),
+            TokenError::Revoked => write!(f, "Session revoked"),
+            TokenError::Invalid(e) => write!(f, "Invalid token: {}", e),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SteamLoginParams {
     /// For desktop app: localhost callback URL
@@ -26,104 +117,335 @@ pub struct SteamLoginParams {
 }
 
 pub async fn steam_login(
+    State(state): State<Arc<AppState>>,
     Query(params): Query<SteamLoginParams>,
-) -> impl IntoResponse {
+) -> Response {
+    let config = &state.auth;
     // Use custom redirect_uri for desktop, or default for web
     let return_url = if let Some(redirect_uri) = params.redirect_uri {
+        if !is_allowed_redirect(config, &redirect_uri) {
+            return (StatusCode::BAD_REQUEST, "redirect_uri is not allowed").into_response();
+        }
         // Desktop flow: callback to localhost, but we need to go through our server first
-        let base_callback = std::env::var("STEAM_CALLBACK_URL")
-            .unwrap_or_else(|_| "http://localhost:8080/auth/steam/callback".to_string());
-        format!("{}?redirect_uri={}", base_callback, urlencoding::encode(&redirect_uri))
+        format!("{}?redirect_uri={}", config.steam_callback_url, urlencoding::encode(&redirect_uri))
     } else {
-        std::env::var("STEAM_CALLBACK_URL")
-            .unwrap_or_else(|_| "http://localhost:8080/auth/steam/callback".to_string())
+        config.steam_callback_url.clone()
     };
     
     let realm = return_url.split("/auth").next().unwrap_or(&return_url);
     
     let steam_openid_url = format!(
-        "https://steamcommunity.com/openid/login?openid.ns=http://specs.openid.net/auth/2.0&openid.mode=checkid_setup&openid.return_to={}&openid.realm={}&openid.identity=http://specs.openid.net/auth/2.0/identifier_select&openid.claimed_id=http://specs.openid.net/auth/2.0/identifier_select",
+        "{}?openid.ns=http://specs.openid.net/auth/2.0&openid.mode=checkid_setup&openid.return_to={}&openid.realm={}&openid.identity=http://specs.openid.net/auth/2.0/identifier_select&openid.claimed_id=http://specs.openid.net/auth/2.0/identifier_select",
+        config.steam_openid_url,
         urlencoding::encode(&return_url),
         urlencoding::encode(realm)
     );
     
-    Redirect::temporary(&steam_openid_url)
+    Redirect::temporary(&steam_openid_url).into_response()
 }
 
-#[derive(Debug, Deserialize)]
-pub struct SteamCallbackFullParams {
-    #[serde(rename = "openid.claimed_id")]
-    claimed_id: Option<String>,
-    /// For desktop app: where to redirect with the token
-    redirect_uri: Option<String>,
+/// Verify a positive OpenID assertion by sending it back to the provider
+/// (`check_authentication`) and return the asserted SteamID.
+async fn verify_openid_assertion(config: &AuthConfig, params: &HashMap<String, String>) -> Result<String, String> {
+    let param = |key: &str| params.get(key).map(String::as_str).unwrap_or_default();
+    
+    if param("openid.mode") != "id_res" {
+        return Err(format!("unexpected openid.mode {:?}", param("openid.mode")));
+    }
+    
+    let endpoint = &config.steam_openid_url;
+    if param("openid.op_endpoint") != endpoint {
+        return Err(format!("unexpected openid.op_endpoint {:?}", param("openid.op_endpoint")));
+    }
+    
+    // The assertion must have been issued for our callback, not another site's
+    if !param("openid.return_to").starts_with(&config.steam_callback_url) {
+        return Err(format!("unexpected openid.return_to {:?}", param("openid.return_to")));
+    }
+    
+    let steam_id = param("openid.claimed_id")
+        .strip_prefix(CLAIMED_ID_PREFIX)
+        .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        .ok_or_else(|| format!("invalid openid.claimed_id {:?}", param("openid.claimed_id")))?
+        .to_string();
+    
+    // Echo the signed fields back with mode=check_authentication
+    let mut form: Vec<(&str, &str)> = params.iter()
+        .filter(|(key, _)| key.starts_with("openid.") && key.as_str() != "openid.mode")
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+    form.push(("openid.mode", "check_authentication"));
+    
+    let response = reqwest::Client::new()
+        .post(endpoint)
+        .form(&form)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("check_authentication request failed: {}", e))?;
+    let body = response.text().await
+        .map_err(|e| format!("check_authentication response unreadable: {}", e))?;
+    
+    // Key-value form: one "key:value" pair per line
+    if body.lines().any(|line| line.trim() == "is_valid:true") {
+        Ok(steam_id)
+    } else {
+        Err("provider rejected the assertion".to_string())
+    }
 }
 
 pub async fn steam_callback(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<SteamCallbackFullParams>,
+    Query(params): Query<HashMap<String, String>>,
 ) -> impl IntoResponse {
-    // Extract Steam ID from claimed_id
-    let steam_id = params.claimed_id
-        .and_then(|id| id.rsplit('/').next().map(String::from))
-        .unwrap_or_default();
-    
-    if steam_id.is_empty() {
-        if let Some(redirect_uri) = params.redirect_uri {
-            return Redirect::temporary(&format!("{}?error=auth_failed", redirect_uri));
+    // For desktop app: where to redirect with the login code
+    let redirect_uri = params.get("redirect_uri").cloned();
+    if let Some(ref uri) = redirect_uri {
+        if !is_allowed_redirect(&state.auth, uri) {
+            tracing::warn!("Steam login with disallowed redirect_uri {:?}", uri);
+            return Redirect::temporary("/?error=invalid_redirect");
         }
-        return Redirect::temporary("/?error=auth_failed");
     }
     
-    // TODO: Verify the OpenID response with Steam
-    // TODO: Fetch user profile from Steam API
+    let steam_id = match verify_openid_assertion(&state.auth, &params).await {
+        Ok(steam_id) => steam_id,
+        Err(e) => {
+            tracing::warn!("Steam login rejected: {}", e);
+            if let Some(redirect_uri) = redirect_uri {
+                return Redirect::temporary(&with_query(&redirect_uri, "error=auth_failed"));
+            }
+            return Redirect::temporary("/?error=auth_failed");
+        }
+    };
     
-    let display_name = format!("User {}", &steam_id[..8.min(steam_id.len())]);
+    // Persona name and avatar; without them the stored profile is kept
+    let summary = match (&state.steam_api_key, steam_id.parse::<u64>()) {
+        (Some(api_key), Ok(steam_id_u64)) => {
+            match crate::steam_api::fetch_player_summary(api_key, steam_id_u64).await {
+                Ok(summary) => summary,
+                Err(e) => {
+                    tracing::warn!("Failed to fetch player summary for {}: {:?}", steam_id, e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
     
-    // Create/update user in database and get short_id
-    let short_id = match crate::db::get_or_create_user(&state.db_pool, &steam_id, &display_name, None).await {
-        Ok(short_id) => short_id,
+    // Create/update user in database
+    let profile = match state.db.get_or_create_user(
+        &steam_id,
+        summary.as_ref().map(|s| s.persona_name.as_str()),
+        summary.as_ref().and_then(|s| s.avatar_url.as_deref()),
+    ).await {
+        Ok(profile) => profile,
         Err(e) => {
             tracing::error!("Failed to create user {}: {:?}", steam_id, e);
             let error_str = format!("{:?}", e);
             let error_msg = urlencoding::encode(&error_str);
-            if let Some(redirect_uri) = params.redirect_uri {
-                return Redirect::temporary(&format!("{}?error=db_error&details={}", redirect_uri, error_msg));
+            if let Some(redirect_uri) = redirect_uri {
+                return Redirect::temporary(&with_query(&redirect_uri, &format!("error=db_error&details={}", error_msg)));
             }
             return Redirect::temporary(&format!("/?error=db_error&details={}", error_msg));
         }
     };
-    tracing::info!("User {} created/updated successfully with short_id {}", steam_id, short_id);
+    tracing::info!("User {} created/updated successfully with short_id {:?}", steam_id, profile.short_id);
+    
+    let tokens = match start_session(&state, profile).await {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            tracing::error!("Failed to start session for {}: {}", steam_id, e);
+            if let Some(redirect_uri) = redirect_uri {
+                return Redirect::temporary(&with_query(&redirect_uri, "error=auth_failed"));
+            }
+            return Redirect::temporary("/?error=auth_failed");
+        }
+    };
     
-    // Create JWT token (30 days for desktop, 7 days for web)
-    let expiry_days = if params.redirect_uri.is_some() { 30 } else { 7 };
+    // Redirect to desktop callback or web frontend with a code to exchange
+    let code = state.auth_codes.issue(tokens);
+    let target = redirect_uri.unwrap_or_else(|| "/".to_string());
+    Redirect::temporary(&with_query(&target, &format!("code={}", code)))
+}
+
+/// Access token for a session, and when it expires
+fn issue_access_token(secret: &str, profile: UserProfile, sid: i64) -> Result<(String, i64), jsonwebtoken::errors::Error> {
+    let expires_at = (chrono::Utc::now()
+        + chrono::Duration::from_std(sessions::ACCESS_TOKEN_TTL).unwrap_or_default()).timestamp();
     let claims = Claims {
-        steam_id: steam_id.clone(),
-        display_name,
-        avatar_url: None,
-        short_id: Some(short_id),
-        exp: (chrono::Utc::now() + chrono::Duration::days(expiry_days)).timestamp() as usize,
+        steam_id: profile.steam_id,
+        display_name: profile.display_name,
+        avatar_url: profile.avatar_url,
+        short_id: profile.short_id,
+        sid,
+        exp: expires_at as usize,
     };
+    Ok((create_jwt(&claims, secret)?, expires_at))
+}
+
+/// Start a session for a user who just logged in
+async fn start_session(state: &AppState, profile: UserProfile) -> Result<AuthTokenResponse, String> {
+    let refresh_token = sessions::generate_refresh_token();
+    let sid = state.db.create_session(
+        &profile.steam_id,
+        &sessions::hash_refresh_token(&refresh_token),
+        sessions::session_expiry(),
+    ).await.map_err(|e| format!("{:?}", e))?;
     
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(state.jwt_secret.as_bytes()),
-    ).unwrap_or_default();
+    let steam_id = profile.steam_id.clone();
+    let (token, expires_at) = issue_access_token(&state.jwt_secret, profile, sid)
+        .map_err(|e| e.to_string())?;
+    Ok(AuthTokenResponse { token, steam_id, refresh_token, expires_at })
+}
+
+/// Exchange a one-time login code for the session tokens it was issued for
+#[utoipa::path(
+    post,
+    path = "/auth/token",
+    tag = "auth",
+    request_body = AuthCodeExchangeRequest,
+    responses(
+        (status = 200, body = AuthTokenResponse),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn exchange_auth_code(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AuthCodeExchangeRequest>,
+) -> Result<Json<AuthTokenResponse>, (StatusCode, Json<serde_json::Value>)> {
+    match state.auth_codes.redeem(&req.code) {
+        Some(tokens) => Ok(Json(tokens)),
+        None => Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Invalid or expired login code"})),
+        )),
+    }
+}
+
+/// Get a new access token and replace the refresh token. Presenting a
+/// refresh token that was already replaced revokes its session.
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, body = AuthTokenResponse),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn refresh_session(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RefreshTokenRequest>,
+) -> Result<Json<AuthTokenResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let token_hash = sessions::hash_refresh_token(&req.refresh_token);
+    let refresh_token = sessions::generate_refresh_token();
     
-    // Redirect to desktop callback or web frontend
-    if let Some(redirect_uri) = params.redirect_uri {
-        Redirect::temporary(&format!("{}?token={}&steam_id={}", redirect_uri, token, steam_id))
-    } else {
-        Redirect::temporary(&format!("/?token={}", token))
+    let rotated = state.db.rotate_session(
+        &token_hash,
+        &sessions::hash_refresh_token(&refresh_token),
+        sessions::session_expiry(),
+    ).await.map_err(|e| (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({"error": format!("Failed to refresh session: {:?}", e)}))
+    ))?;
+    
+    let Some((sid, profile)) = rotated else {
+        match state.db.revoke_session_by_token(&token_hash).await {
+            Ok(Some(sid)) => {
+                tracing::warn!(session = %sid, "Replaced refresh token used again, session revoked");
+                state.revoked_sessions.insert(&[sid]);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::error!("Failed to revoke session of reused refresh token: {:?}", e),
+        }
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({"error": "Invalid or expired refresh token"}))
+        ));
+    };
+    
+    let steam_id = profile.steam_id.clone();
+    let (token, expires_at) = issue_access_token(&state.jwt_secret, profile, sid).map_err(|e| (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({"error": format!("Failed to create token: {}", e)}))
+    ))?;
+    Ok(Json(AuthTokenResponse { token, steam_id, refresh_token, expires_at }))
+}
+
+/// Log out a session by its refresh token
+#[utoipa::path(
+    post,
+    path = "/auth/revoke",
+    tag = "auth",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, body = SuccessResponse),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn revoke_session(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RefreshTokenRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let token_hash = sessions::hash_refresh_token(&req.refresh_token);
+    match state.db.revoke_session_by_token(&token_hash).await {
+        Ok(sid) => {
+            if let Some(sid) = sid {
+                state.revoked_sessions.insert(&[sid]);
+            }
+            Ok(Json(SuccessResponse { success: true }))
+        }
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to revoke session: {:?}", e)}))
+        )),
+    }
+}
+
+/// Log out all sessions of the authenticated user
+#[utoipa::path(
+    post,
+    path = "/auth/logout-all",
+    tag = "auth",
+    responses(
+        (status = 200, body = LogoutAllResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn logout_all_sessions(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<LogoutAllResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = crate::routes::extract_user(&headers, &state)?;
+    
+    match state.db.revoke_user_sessions(&claims.steam_id).await {
+        Ok(sids) => {
+            state.revoked_sessions.insert(&sids);
+            tracing::info!(steam_id = %claims.steam_id, sessions = sids.len(), "Logged out all sessions");
+            Ok(Json(LogoutAllResponse { success: true, revoked: sids.len() }))
+        }
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to revoke sessions: {:?}", e)}))
+        )),
     }
 }
 
-pub fn verify_jwt(token: &str, secret: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+/// Decode an access token and check that its session was not revoked
+pub fn verify_jwt(token: &str, secret: &str, revoked: &RevokedSessions) -> Result<Claims, TokenError> {
     let token_data = decode::<Claims>(
         token,
         &DecodingKey::from_secret(secret.as_bytes()),
         &Validation::default(),
-    )?;
+    ).map_err(|e| match e.kind() {
+        jsonwebtoken::errors::ErrorKind::ExpiredSignature => TokenError::Expired,
+        _ => TokenError::Invalid(e),
+    })?;
+    if revoked.contains(token_data.claims.sid) {
+        return Err(TokenError::Revoked);
+    }
     Ok(token_data.claims)
 }
 
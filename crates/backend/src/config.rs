@@ -0,0 +1,540 @@
+//! Server configuration
+//!
+//! Settings are read from an optional TOML file (`--config <path>` or
+//! `OVERACHIEVER_CONFIG`) and then overridden by environment variables, so an
+//! existing `.env` keeps working. See `scripts/server/overachiever.env.example`
+//! for the variables.
+//!
+//! `overachiever-server --check-config` validates the configuration and exits.
+//! With `profile = "production"` the server refuses to start on development
+//! defaults: the built-in JWT secret, CORS open to any origin or a plain-http
+//! login callback.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// JWT secret used when none is configured; refused in production
+const DEV_JWT_SECRET: &str = "dev-secret-change-in-production";
+
+/// Shortest JWT secret accepted in production
+const MIN_JWT_SECRET_LENGTH: usize = 32;
+
+/// Placeholders from `overachiever.env.example` that were never filled in
+const PLACEHOLDER_SECRETS: &[&str] = &["CHANGE_THIS_TO_A_RANDOM_SECRET", "CHANGE_THIS_PASSWORD"];
+
+/// Default limit for request bodies (axum's own default)
+pub const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Default limit for sync uploads (100MB)
+pub const DEFAULT_UPLOAD_BODY_LIMIT: usize = 100 * 1024 * 1024;
+
+/// Steam's OpenID provider
+pub const DEFAULT_OPENID_ENDPOINT: &str = "https://steamcommunity.com/openid/login";
+const DEFAULT_CALLBACK_URL: &str = "http://localhost:8080/auth/steam/callback";
+
+/// Port of the desktop app's local login callback server
+const DESKTOP_CALLBACK_PORT: u16 = 23847;
+
+const USAGE: &str = "Usage: overachiever-server [--config <path>] [--check-config] [migrate [status | baseline <version>]]
+
+Commands:
+  (none)                      Apply pending migrations (unless DB_AUTO_MIGRATE=false) and serve
+  migrate                     Apply pending migrations and exit
+  migrate status              List migrations and when they were applied
+  migrate baseline <version>  Record migrations up to <version> as applied without running them,
+                              for databases set up by applying the SQL files by hand (PostgreSQL only)
+
+Options:
+  --config <path>   Read settings from a TOML file (default: $OVERACHIEVER_CONFIG)
+  --check-config    Validate the configuration, print it and exit
+  -h, --help        Show this help";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    #[default]
+    Development,
+    Production,
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "development" | "dev" => Ok(Profile::Development),
+            "production" | "prod" => Ok(Profile::Production),
+            other => Err(format!("unknown profile {:?} (expected development or production)", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub profile: Profile,
+    pub bind_address: String,
+    pub jwt_secret: Option<String>,
+    pub steam_api_key: Option<String>,
+    /// Origins allowed to call the API from a browser; empty allows any
+    pub cors_origins: Vec<String>,
+    /// Largest accepted request body, in bytes
+    pub body_limit: usize,
+    /// Largest accepted sync upload, in bytes
+    pub upload_body_limit: usize,
+    pub database: DatabaseConfig,
+    pub auth: AuthConfig,
+    pub moderation: ModerationConfig,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            profile: Profile::default(),
+            bind_address: "0.0.0.0:8080".to_string(),
+            jwt_secret: None,
+            steam_api_key: None,
+            cors_origins: Vec::new(),
+            body_limit: DEFAULT_BODY_LIMIT,
+            upload_body_limit: DEFAULT_UPLOAD_BODY_LIMIT,
+            database: DatabaseConfig::default(),
+            auth: AuthConfig::default(),
+            moderation: ModerationConfig::default(),
+        }
+    }
+}
+
+/// Where the server keeps its data
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseBackend {
+    #[default]
+    Postgres,
+    /// A single database file, for small self-hosted servers
+    Sqlite,
+}
+
+impl FromStr for DatabaseBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "postgres" | "postgresql" => Ok(DatabaseBackend::Postgres),
+            "sqlite" => Ok(DatabaseBackend::Sqlite),
+            other => Err(format!("unknown database backend {:?} (expected postgres or sqlite)", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub backend: DatabaseBackend,
+    /// Database file of the SQLite backend; `:memory:` keeps it in memory
+    pub sqlite_path: PathBuf,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub name: Option<String>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    /// Apply pending migrations at startup; otherwise refuse to start with any
+    pub auto_migrate: bool,
+    /// Connection attempts at startup before giving up; 0 keeps trying
+    pub connect_attempts: u32,
+    /// Longest wait between connection attempts, in seconds
+    pub connect_retry_max_secs: u64,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            backend: DatabaseBackend::default(),
+            sqlite_path: PathBuf::from("overachiever.db"),
+            host: None,
+            port: None,
+            name: None,
+            user: None,
+            password: None,
+            auto_migrate: true,
+            connect_attempts: 0,
+            connect_retry_max_secs: 30,
+        }
+    }
+}
+
+impl DatabaseConfig {
+    pub fn connect_retry_max(&self) -> Duration {
+        Duration::from_secs(self.connect_retry_max_secs.max(1))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Where Steam sends users back after logging in; must match the public domain
+    pub steam_callback_url: String,
+    /// OpenID provider; point at a local stand-in provider for testing logins
+    pub steam_openid_url: String,
+    /// Redirect URIs a login may return to, besides pages on the callback's origin
+    pub redirect_allowlist: Vec<String>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            steam_callback_url: DEFAULT_CALLBACK_URL.to_string(),
+            steam_openid_url: DEFAULT_OPENID_ENDPOINT.to_string(),
+            redirect_allowlist: vec![
+                format!("http://localhost:{}/callback", DESKTOP_CALLBACK_PORT),
+                format!("http://127.0.0.1:{}/callback", DESKTOP_CALLBACK_PORT),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModerationConfig {
+    /// Blocked words
+    pub word_filter: Vec<String>,
+    /// File with one blocked word per line
+    pub word_filter_file: Option<PathBuf>,
+    /// Comments, tips, reviews and reports allowed per user within the window
+    pub submission_rate_limit: usize,
+    pub submission_rate_window_secs: u64,
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            word_filter: Vec::new(),
+            word_filter_file: None,
+            submission_rate_limit: 10,
+            submission_rate_window_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Env { name: &'static str, reason: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "Failed to read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "Invalid config file {}: {}", path.display(), e),
+            ConfigError::Env { name, reason } => write!(f, "Invalid {}: {}", name, reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Problems found by [`ServerConfig::check`]
+#[derive(Debug, Default)]
+pub struct ConfigReport {
+    /// The server refuses to start with any of these
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl ConfigReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// What to do once the configuration is loaded
+#[derive(Debug, Default, PartialEq, Eq)]
+pub enum Command {
+    #[default]
+    Serve,
+    /// Apply pending migrations and exit
+    Migrate,
+    MigrateStatus,
+    /// Record migrations up to this version as applied without running them
+    MigrateBaseline(i64),
+}
+
+/// Command line arguments
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    pub config_path: Option<PathBuf>,
+    pub check_config: bool,
+    pub command: Command,
+}
+
+impl CliArgs {
+    /// Parse arguments (without the program name); `Err` holds the message to print
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut positional = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--check-config" => parsed.check_config = true,
+                "--config" => {
+                    let path = args.next().ok_or_else(|| format!("--config needs a path\n\n{}", USAGE))?;
+                    parsed.config_path = Some(PathBuf::from(path));
+                }
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                other => match other.strip_prefix("--config=") {
+                    Some(path) => parsed.config_path = Some(PathBuf::from(path)),
+                    None if other.starts_with('-') => return Err(format!("Unknown argument {:?}\n\n{}", other, USAGE)),
+                    None => positional.push(arg),
+                },
+            }
+        }
+        parsed.command = match positional.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+            [] => Command::Serve,
+            ["migrate"] => Command::Migrate,
+            ["migrate", "status"] => Command::MigrateStatus,
+            ["migrate", "baseline", version] => Command::MigrateBaseline(
+                version.parse().map_err(|_| format!("Invalid migration version {:?}\n\n{}", version, USAGE))?,
+            ),
+            other => return Err(format!("Unknown command {:?}\n\n{}", other.join(" "), USAGE)),
+        };
+        if parsed.config_path.is_none() {
+            parsed.config_path = env_var("OVERACHIEVER_CONFIG").map(PathBuf::from);
+        }
+        Ok(parsed)
+    }
+}
+
+impl ServerConfig {
+    /// Defaults, then the config file if given, then environment variables
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut config = match path {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+        toml::from_str(&contents).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        if let Some(profile) = env_parse("SERVER_PROFILE")? {
+            self.profile = profile;
+        }
+        if let Some(addr) = env_var("BIND_ADDRESS") {
+            self.bind_address = addr;
+        }
+        if let Some(secret) = env_var("JWT_SECRET") {
+            self.jwt_secret = Some(secret);
+        }
+        if let Some(key) = env_var("STEAM_API_KEY") {
+            self.steam_api_key = Some(key);
+        }
+        if let Some(origins) = env_list("CORS_ORIGINS") {
+            self.cors_origins = origins;
+        }
+        if let Some(limit) = env_parse("BODY_LIMIT")? {
+            self.body_limit = limit;
+        }
+        if let Some(limit) = env_parse("UPLOAD_BODY_LIMIT")? {
+            self.upload_body_limit = limit;
+        }
+
+        let db = &mut self.database;
+        if let Some(backend) = env_parse("DB_BACKEND")? {
+            db.backend = backend;
+        }
+        if let Some(path) = env_var("DB_SQLITE_PATH") {
+            db.sqlite_path = PathBuf::from(path);
+        }
+        if let Some(host) = env_var("DB_HOST") {
+            db.host = Some(host);
+        }
+        if let Some(port) = env_parse("DB_PORT")? {
+            db.port = Some(port);
+        }
+        if let Some(name) = env_var("DB_NAME") {
+            db.name = Some(name);
+        }
+        if let Some(user) = env_var("DB_USER") {
+            db.user = Some(user);
+        }
+        if let Some(password) = env_var("DB_PASSWORD") {
+            db.password = Some(password);
+        }
+        if let Some(auto_migrate) = env_parse("DB_AUTO_MIGRATE")? {
+            db.auto_migrate = auto_migrate;
+        }
+        if let Some(attempts) = env_parse("DB_CONNECT_ATTEMPTS")? {
+            db.connect_attempts = attempts;
+        }
+        if let Some(secs) = env_parse("DB_CONNECT_RETRY_MAX_SECS")? {
+            db.connect_retry_max_secs = secs;
+        }
+
+        let auth = &mut self.auth;
+        if let Some(url) = env_var("STEAM_CALLBACK_URL") {
+            auth.steam_callback_url = url;
+        }
+        if let Some(url) = env_var("STEAM_OPENID_URL") {
+            auth.steam_openid_url = url;
+        }
+        if let Some(allowlist) = env_list("AUTH_REDIRECT_ALLOWLIST") {
+            auth.redirect_allowlist = allowlist;
+        }
+
+        let moderation = &mut self.moderation;
+        if let Some(words) = env_list("WORD_FILTER") {
+            moderation.word_filter = words;
+        }
+        if let Some(path) = env_var("WORD_FILTER_FILE") {
+            moderation.word_filter_file = Some(PathBuf::from(path));
+        }
+        if let Some(limit) = env_parse("SUBMISSION_RATE_LIMIT")? {
+            moderation.submission_rate_limit = limit;
+        }
+        if let Some(secs) = env_parse("SUBMISSION_RATE_WINDOW_SECS")? {
+            moderation.submission_rate_window_secs = secs;
+        }
+        Ok(())
+    }
+
+    /// The configured JWT secret, or the development one
+    pub fn jwt_secret(&self) -> &str {
+        self.jwt_secret.as_deref().unwrap_or(DEV_JWT_SECRET)
+    }
+
+    pub fn is_production(&self) -> bool {
+        self.profile == Profile::Production
+    }
+
+    /// Find settings the server can't run with, and development defaults.
+    /// In production the latter are errors too.
+    pub fn check(&self) -> ConfigReport {
+        let mut report = ConfigReport::default();
+        let production = self.is_production();
+        // Development defaults: errors in production, warnings otherwise
+        let unsafe_default = |report: &mut ConfigReport, message: String| {
+            if production {
+                report.errors.push(message);
+            } else {
+                report.warnings.push(message);
+            }
+        };
+
+        if self.bind_address.parse::<SocketAddr>().is_err() {
+            report.errors.push(format!("bind_address {:?} is not a valid socket address", self.bind_address));
+        }
+        if self.body_limit == 0 || self.upload_body_limit == 0 {
+            report.errors.push("body_limit and upload_body_limit must be above 0".to_string());
+        }
+
+        match self.jwt_secret.as_deref() {
+            None | Some(DEV_JWT_SECRET) => {
+                unsafe_default(&mut report, "JWT_SECRET is not set, using the development secret".to_string());
+            }
+            Some(secret) if PLACEHOLDER_SECRETS.contains(&secret) => {
+                unsafe_default(&mut report, "JWT_SECRET is still the example placeholder".to_string());
+            }
+            Some(secret) if secret.len() < MIN_JWT_SECRET_LENGTH => {
+                unsafe_default(&mut report, format!("JWT_SECRET is shorter than {} characters", MIN_JWT_SECRET_LENGTH));
+            }
+            Some(_) => {}
+        }
+        if self.database.backend == DatabaseBackend::Postgres
+            && self.database.password.as_deref().is_some_and(|p| PLACEHOLDER_SECRETS.contains(&p)) {
+            unsafe_default(&mut report, "DB_PASSWORD is still the example placeholder".to_string());
+        }
+        if self.database.backend == DatabaseBackend::Sqlite && self.database.sqlite_path == Path::new(":memory:") {
+            unsafe_default(&mut report, "DB_SQLITE_PATH is :memory:, all data is lost on restart".to_string());
+        }
+        if self.steam_api_key.is_none() {
+            report.warnings.push("STEAM_API_KEY not set - Steam sync will be disabled".to_string());
+        }
+
+        if self.cors_origins.is_empty() || self.cors_origins.iter().any(|o| o == "*") {
+            unsafe_default(&mut report, "CORS allows any origin; set CORS_ORIGINS".to_string());
+        }
+        for origin in self.cors_origins.iter().filter(|o| o.as_str() != "*") {
+            if !is_origin(origin) {
+                report.errors.push(format!("CORS origin {:?} is not of the form scheme://host[:port]", origin));
+            }
+        }
+
+        let callback = &self.auth.steam_callback_url;
+        if !callback.starts_with("http://") && !callback.starts_with("https://") {
+            report.errors.push(format!("STEAM_CALLBACK_URL {:?} is not an http(s) URL", callback));
+        } else if !callback.starts_with("https://") {
+            unsafe_default(&mut report, format!("STEAM_CALLBACK_URL {:?} is not https", callback));
+        }
+        if self.auth.steam_openid_url != DEFAULT_OPENID_ENDPOINT {
+            unsafe_default(&mut report, format!("STEAM_OPENID_URL points at {:?} instead of Steam", self.auth.steam_openid_url));
+        }
+
+        if self.moderation.submission_rate_limit == 0 || self.moderation.submission_rate_window_secs == 0 {
+            report.errors.push("SUBMISSION_RATE_LIMIT and SUBMISSION_RATE_WINDOW_SECS must be above 0".to_string());
+        }
+        if let Some(path) = &self.moderation.word_filter_file {
+            if let Err(e) = std::fs::metadata(path) {
+                report.errors.push(format!("WORD_FILTER_FILE {}: {}", path.display(), e));
+            }
+        }
+
+        report
+    }
+
+    /// The configuration as TOML, with secrets masked
+    pub fn to_masked_toml(&self) -> String {
+        let mask = |secret: &Option<String>| secret.as_ref().map(|_| "********".to_string());
+        let mut masked = self.clone();
+        masked.jwt_secret = mask(&self.jwt_secret);
+        masked.steam_api_key = mask(&self.steam_api_key);
+        masked.database.password = mask(&self.database.password);
+        toml::to_string_pretty(&masked).unwrap_or_else(|e| format!("# Failed to print config: {}", e))
+    }
+}
+
+/// "scheme://host[:port]" without a path
+fn is_origin(origin: &str) -> bool {
+    let Some((scheme, host)) = origin.split_once("://") else {
+        return false;
+    };
+    matches!(scheme, "http" | "https")
+        && !host.is_empty()
+        && !host.contains(['/', '?', '#'])
+        && origin.parse::<axum::http::HeaderValue>().is_ok()
+}
+
+/// Environment variable, if set and not blank
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+/// Comma-separated environment variable
+fn env_list(name: &str) -> Option<Vec<String>> {
+    env_var(name).map(|list| {
+        list.split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    })
+}
+
+fn env_parse<T>(name: &'static str) -> Result<Option<T>, ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    env_var(name)
+        .map(|value| value.trim().parse().map_err(|e| ConfigError::Env { name, reason: format!("{:?}: {}", value, e) }))
+        .transpose()
+}
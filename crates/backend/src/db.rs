@@ -1,9 +1,12 @@
 //! Database operations for the backend using tokio-postgres
 
 use deadpool_postgres::{Pool, PoolError};
-use overachiever_core::{Game, GameAchievement, GameRating, AchievementTip, LogEntry, CloudSyncData, CloudSyncStatus, SyncAchievement};
+use overachiever_core::{Game, GameAchievement, GameRating, GameRatingAverage, AchievementTip, LogEntry, FeedEntry, Leaderboard, LeaderboardEntry, LeaderboardMetric, LeaderboardScope, GameRarity, AchievementRarity, AchievementDifficulty, AchievementFlag, AchievementFlags, AchievementFlagVotes, FLAG_VOTE_THRESHOLD, AchievementComment, ContentKind, ContentReport, CloudSyncData, CloudSyncStatus, SyncAchievement, SyncMergePreview, CloudSyncDelta, SyncCursor, EncryptedSyncBlob};
 use chrono::{DateTime, Utc};
 use rand::Rng;
+use tokio_postgres::IsolationLevel;
+
+use crate::pagination::{ListVersion, LogCursor, UserList};
 
 /// Characters used for generating short IDs (URL-safe, case-sensitive)
 /// Similar to YouTube's video ID format
@@ -25,6 +28,7 @@ pub fn generate_short_id() -> String {
 pub enum DbError {
     Pool(PoolError),
     Postgres(tokio_postgres::Error),
+    Sqlite(rusqlite::Error),
 }
 
 impl From<PoolError> for DbError {
@@ -39,11 +43,18 @@ impl From<tokio_postgres::Error> for DbError {
     }
 }
 
+impl From<rusqlite::Error> for DbError {
+    fn from(e: rusqlite::Error) -> Self {
+        DbError::Sqlite(e)
+    }
+}
+
 impl std::fmt::Display for DbError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             DbError::Pool(e) => write!(f, "Pool error: {}", e),
             DbError::Postgres(e) => write!(f, "Postgres error: {}", e),
+            DbError::Sqlite(e) => write!(f, "SQLite error: {}", e),
         }
     }
 }
@@ -257,43 +268,59 @@ pub async fn get_game_achievements(
     Ok(achievements)
 }
 
+/// Ratings of a game, newest first. `viewer` marks the requesting user's own rating.
 pub async fn get_community_ratings(
     pool: &Pool,
     appid: u64,
+    viewer: Option<&str>,
 ) -> Result<Vec<GameRating>, DbError> {
     let client = pool.get().await?;
+    let viewer_steam_id: i64 = viewer.and_then(|s| s.parse().ok()).unwrap_or(0);
     
     let rows = client.query(
         r#"
-        SELECT id, steam_id, appid, rating, comment, created_at, updated_at
-        FROM game_ratings
-        WHERE appid = $1
-        ORDER BY created_at DESC
+        SELECT r.id::BIGINT AS id, r.appid, r.rating, r.comment, r.created_at, r.updated_at,
+               u.steam_id, u.display_name, u.avatar_url, u.short_id
+        FROM game_ratings r
+        JOIN users u ON u.steam_id = r.steam_id
+        WHERE r.appid = $1 AND r.hidden_at IS NULL
+        ORDER BY r.updated_at DESC
         "#,
         &[&(appid as i64)]
     ).await?;
     
     let ratings = rows.into_iter().map(|row| {
+        let author_steam_id: i64 = row.get("steam_id");
         GameRating {
-            id: Some(row.get::<_, i64>("id")),
-            steam_id: row.get::<_, i64>("steam_id").to_string(),
+            id: row.get("id"),
+            author: overachiever_core::UserProfile {
+                steam_id: author_steam_id.to_string(),
+                display_name: row.get("display_name"),
+                avatar_url: row.get("avatar_url"),
+                short_id: row.get("short_id"),
+            },
             appid: row.get::<_, i64>("appid") as u64,
             rating: row.get::<_, i16>("rating") as u8,
             comment: row.get("comment"),
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
+            is_yours: author_steam_id == viewer_steam_id,
         }
     }).collect();
     
     Ok(ratings)
 }
 
+/// Store a user's rating and review of a game, replacing their earlier one
 pub async fn upsert_rating(
     pool: &Pool,
-    rating: &GameRating,
+    steam_id: &str,
+    appid: u64,
+    rating: u8,
+    comment: Option<&str>,
 ) -> Result<(), DbError> {
     let client = pool.get().await?;
-    let steam_id_int: i64 = rating.steam_id.parse().unwrap_or(0);
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
     let now = Utc::now();
     
     client.execute(
@@ -307,9 +334,9 @@ pub async fn upsert_rating(
         "#,
         &[
             &steam_id_int,
-            &(rating.appid as i64),
-            &(rating.rating as i16),
-            &rating.comment,
+            &(appid as i64),
+            &(rating as i16),
+            &comment,
             &now,
         ]
     ).await?;
@@ -317,94 +344,221 @@ pub async fn upsert_rating(
     Ok(())
 }
 
+/// Community rating averages of the rated games among some games
+pub async fn get_game_rating_averages(pool: &Pool, appids: &[u64]) -> Result<Vec<GameRatingAverage>, DbError> {
+    let client = pool.get().await?;
+    let appids: Vec<i64> = appids.iter().map(|&appid| appid as i64).collect();
+    
+    let rows = client.query(
+        r#"
+        SELECT appid, AVG(rating)::REAL AS avg_rating, COUNT(*)::INT AS rating_count
+        FROM game_ratings
+        WHERE appid = ANY($1) AND hidden_at IS NULL
+        GROUP BY appid
+        "#,
+        &[&appids]
+    ).await?;
+    
+    Ok(rows.into_iter().map(|row| {
+        let appid: i64 = row.get("appid");
+        GameRatingAverage {
+            appid: appid as u64,
+            avg_rating: row.get("avg_rating"),
+            rating_count: row.get("rating_count"),
+        }
+    }).collect())
+}
+
+const TIP_SELECT: &str = r#"
+    SELECT t.id::BIGINT AS id, t.appid, t.apiname, t.difficulty, t.tip, t.created_at, t.edited_at,
+           u.steam_id, u.display_name, u.avatar_url, u.short_id,
+           COALESCE(SUM(v.vote), 0)::INT AS score,
+           COALESCE(MAX(v.vote) FILTER (WHERE v.steam_id = $3), 0)::SMALLINT AS your_vote
+    FROM achievement_tips t
+    JOIN users u ON u.steam_id = t.steam_id
+    LEFT JOIN achievement_tip_votes v ON v.tip_id = t.id
+"#;
+
+fn tip_from_row(row: &tokio_postgres::Row, viewer_steam_id: i64) -> AchievementTip {
+    let author_steam_id: i64 = row.get("steam_id");
+    AchievementTip {
+        id: row.get("id"),
+        author: overachiever_core::UserProfile {
+            steam_id: author_steam_id.to_string(),
+            display_name: row.get("display_name"),
+            avatar_url: row.get("avatar_url"),
+            short_id: row.get("short_id"),
+        },
+        appid: row.get::<_, i64>("appid") as u64,
+        apiname: row.get("apiname"),
+        difficulty: row.get::<_, Option<i16>>("difficulty").unwrap_or(0) as u8,
+        tip: row.get("tip"),
+        created_at: row.get("created_at"),
+        edited_at: row.get("edited_at"),
+        score: row.get("score"),
+        your_vote: row.get::<_, i16>("your_vote") as i8,
+        is_yours: author_steam_id == viewer_steam_id,
+    }
+}
+
+/// Tips for an achievement, highest score first. `viewer` marks the
+/// requesting user's own tip and votes.
 pub async fn get_achievement_tips(
     pool: &Pool,
     appid: u64,
     apiname: &str,
+    viewer: Option<&str>,
 ) -> Result<Vec<AchievementTip>, DbError> {
     let client = pool.get().await?;
+    let viewer_steam_id: i64 = viewer.and_then(|s| s.parse().ok()).unwrap_or(0);
     
-    let rows = client.query(
+    let query = format!(
+        r#"{}
+        WHERE t.appid = $1 AND t.apiname = $2 AND t.hidden_at IS NULL
+        GROUP BY t.id, u.steam_id
+        ORDER BY score DESC, t.created_at DESC
+        "#,
+        TIP_SELECT
+    );
+    let rows = client.query(&query, &[&(appid as i64), &apiname, &viewer_steam_id]).await?;
+    
+    Ok(rows.iter().map(|row| tip_from_row(row, viewer_steam_id)).collect())
+}
+
+/// Store a user's tip for an achievement, replacing (editing) their earlier one
+pub async fn upsert_achievement_tip(
+    pool: &Pool,
+    steam_id: &str,
+    appid: u64,
+    apiname: &str,
+    difficulty: u8,
+    tip: &str,
+) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    client.execute(
         r#"
-        SELECT id, steam_id, appid, apiname, difficulty, tip, created_at
-        FROM achievement_tips
-        WHERE appid = $1 AND apiname = $2
-        ORDER BY created_at DESC
+        INSERT INTO achievement_tips (steam_id, appid, apiname, difficulty, tip)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (steam_id, appid, apiname)
+        DO UPDATE SET difficulty = EXCLUDED.difficulty, tip = EXCLUDED.tip, edited_at = NOW()
         "#,
-        &[&(appid as i64), &apiname]
+        &[&steam_id_int, &(appid as i64), &apiname, &(difficulty as i16), &tip]
     ).await?;
     
-    let tips = rows.into_iter().map(|row| {
-        AchievementTip {
-            id: Some(row.get::<_, i64>("id")),
-            steam_id: row.get::<_, i64>("steam_id").to_string(),
-            appid: row.get::<_, i64>("appid") as u64,
-            apiname: row.get("apiname"),
-            difficulty: row.get::<_, i16>("difficulty") as u8,
-            tip: row.get("tip"),
-            created_at: row.get("created_at"),
-        }
-    }).collect();
+    Ok(())
+}
+
+/// Vote on another user's tip (1 or -1), or take the vote back (0). Returns
+/// the tip's achievement, or None if there is no such tip by another user.
+pub async fn vote_achievement_tip(
+    pool: &Pool,
+    steam_id: &str,
+    tip_id: i64,
+    vote: i8,
+) -> Result<Option<(u64, String)>, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let row = client.query_opt(
+        "SELECT appid, apiname FROM achievement_tips WHERE id = $1 AND steam_id <> $2 AND hidden_at IS NULL",
+        &[&(tip_id as i32), &steam_id_int]
+    ).await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    
+    if vote == 0 {
+        client.execute(
+            "DELETE FROM achievement_tip_votes WHERE tip_id = $1 AND steam_id = $2",
+            &[&(tip_id as i32), &steam_id_int]
+        ).await?;
+    } else {
+        client.execute(
+            r#"
+            INSERT INTO achievement_tip_votes (tip_id, steam_id, vote)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (tip_id, steam_id) DO UPDATE SET vote = EXCLUDED.vote, created_at = NOW()
+            "#,
+            &[&(tip_id as i32), &steam_id_int, &(vote.signum() as i16)]
+        ).await?;
+    }
     
-    Ok(tips)
+    Ok(Some((row.get::<_, i64>("appid") as u64, row.get("apiname"))))
 }
 
+/// Create or update a user at login and return the stored profile. Without a
+/// Steam profile (`display_name` and `avatar_url` None), existing users keep
+/// their stored name and avatar and new users get a placeholder name.
 pub async fn get_or_create_user(
     pool: &Pool,
     steam_id: &str,
-    display_name: &str,
+    display_name: Option<&str>,
     avatar_url: Option<&str>,
-) -> Result<String, DbError> {
+) -> Result<overachiever_core::UserProfile, DbError> {
     let client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
     let now = Utc::now();
-    let avatar: Option<String> = avatar_url.map(|s| s.to_string());
     
     // First, check if user exists and has a short_id
     let existing = client.query_opt(
-        "SELECT short_id FROM users WHERE steam_id = $1",
+        "SELECT display_name, avatar_url, short_id FROM users WHERE steam_id = $1",
         &[&steam_id_int]
     ).await?;
     
     if let Some(row) = existing {
-        // User exists, update last_seen and return existing short_id
+        // User exists, update profile and last_seen
         client.execute(
             r#"
             UPDATE users SET
-                display_name = $2,
-                avatar_url = $3,
+                display_name = COALESCE($2, display_name),
+                avatar_url = COALESCE($3, avatar_url),
                 last_seen = $4
             WHERE steam_id = $1
             "#,
-            &[&steam_id_int, &display_name, &avatar, &now]
+            &[&steam_id_int, &display_name, &avatar_url, &now]
         ).await?;
         
-        // Return existing short_id or generate one if missing
-        if let Some(short_id) = row.get::<_, Option<String>>("short_id") {
-            return Ok(short_id);
-        }
-        
-        // Generate short_id for existing user (migration case)
-        let short_id = generate_unique_short_id(&client).await?;
-        client.execute(
-            "UPDATE users SET short_id = $2 WHERE steam_id = $1",
-            &[&steam_id_int, &short_id]
-        ).await?;
-        return Ok(short_id);
+        // Keep the existing short_id or generate one if missing (migration case)
+        let short_id = match row.get::<_, Option<String>>("short_id") {
+            Some(short_id) => short_id,
+            None => {
+                let short_id = generate_unique_short_id(&client).await?;
+                client.execute(
+                    "UPDATE users SET short_id = $2 WHERE steam_id = $1",
+                    &[&steam_id_int, &short_id]
+                ).await?;
+                short_id
+            }
+        };
+        return Ok(overachiever_core::UserProfile {
+            steam_id: steam_id.to_string(),
+            display_name: display_name.map(str::to_string).unwrap_or_else(|| row.get("display_name")),
+            avatar_url: avatar_url.map(str::to_string).or_else(|| row.get("avatar_url")),
+            short_id: Some(short_id),
+        });
     }
     
     // New user - generate unique short_id
     let short_id = generate_unique_short_id(&client).await?;
+    let display_name = display_name.map(str::to_string)
+        .unwrap_or_else(|| format!("User {}", &steam_id[..8.min(steam_id.len())]));
     
     client.execute(
         r#"
         INSERT INTO users (steam_id, display_name, avatar_url, short_id, created_at, last_seen)
         VALUES ($1, $2, $3, $4, $5, $5)
         "#,
-        &[&steam_id_int, &display_name, &avatar, &short_id, &now]
+        &[&steam_id_int, &display_name, &avatar_url, &short_id, &now]
     ).await?;
     
-    Ok(short_id)
+    Ok(overachiever_core::UserProfile {
+        steam_id: steam_id.to_string(),
+        display_name,
+        avatar_url: avatar_url.map(str::to_string),
+        short_id: Some(short_id),
+    })
 }
 
 /// Generate a unique short_id by checking for collisions
@@ -423,19 +577,32 @@ async fn generate_unique_short_id(client: &deadpool_postgres::Client) -> Result<
     }
 }
 
+/// Start a transaction that writes rows pulled by delta sync. The user's row is
+/// locked first, so one user's rows get their sync versions in commit order and
+/// a pull can't move its cursor past a write that hasn't committed yet.
+pub async fn begin_sync_write(
+    client: &mut deadpool_postgres::Client,
+    steam_id_int: i64,
+) -> Result<deadpool_postgres::Transaction<'_>, DbError> {
+    let transaction = client.transaction().await?;
+    transaction.execute("SELECT 1 FROM users WHERE steam_id = $1 FOR NO KEY UPDATE", &[&steam_id_int]).await?;
+    Ok(transaction)
+}
+
 /// Insert or update games for a user
 pub async fn upsert_games(
     pool: &Pool,
     steam_id: &str,
     games: &[overachiever_core::SteamGame],
 ) -> Result<usize, DbError> {
-    let client = pool.get().await?;
+    let mut client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    let transaction = begin_sync_write(&mut client, steam_id_int).await?;
     let now = Utc::now();
     
     let mut count = 0;
     for game in games {
-        client.execute(
+        transaction.execute(
             r#"
             INSERT INTO user_games (steam_id, appid, name, playtime_forever, rtime_last_played, img_icon_url, added_at)
             VALUES ($1, $2, $3, $4, $5, $6, $7)
@@ -458,6 +625,7 @@ pub async fn upsert_games(
         count += 1;
     }
     
+    transaction.commit().await?;
     Ok(count)
 }
 
@@ -469,11 +637,12 @@ pub async fn update_game_achievements(
     total: i32,
     unlocked: i32,
 ) -> Result<(), DbError> {
-    let client = pool.get().await?;
+    let mut client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    let transaction = begin_sync_write(&mut client, steam_id_int).await?;
     let now = Utc::now();
     
-    client.execute(
+    transaction.execute(
         r#"
         UPDATE user_games
         SET achievements_total = $3, achievements_unlocked = $4, last_sync = $5
@@ -488,6 +657,7 @@ pub async fn update_game_achievements(
         ]
     ).await?;
     
+    transaction.commit().await?;
     Ok(())
 }
 
@@ -529,8 +699,9 @@ pub async fn upsert_user_achievement(
     appid: u64,
     achievement: &overachiever_core::Achievement,
 ) -> Result<(), DbError> {
-    let client = pool.get().await?;
+    let mut client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    let transaction = begin_sync_write(&mut client, steam_id_int).await?;
     let achieved = achievement.achieved == 1;
     let unlocktime: Option<DateTime<Utc>> = if achievement.unlocktime > 0 {
         chrono::DateTime::from_timestamp(achievement.unlocktime as i64, 0)
@@ -538,7 +709,7 @@ pub async fn upsert_user_achievement(
         None
     };
     
-    client.execute(
+    transaction.execute(
         r#"
         INSERT INTO user_achievements (steam_id, appid, apiname, achieved, unlocktime)
         VALUES ($1, $2, $3, $4, $5)
@@ -555,6 +726,7 @@ pub async fn upsert_user_achievement(
         ]
     ).await?;
     
+    transaction.commit().await?;
     Ok(())
 }
 
@@ -617,11 +789,12 @@ pub async fn get_achievement_history(pool: &Pool, steam_id: &str) -> Result<Vec<
 
 /// Record a run history entry
 pub async fn insert_run_history(pool: &Pool, steam_id: &str, total_games: i32, unplayed_games_total: i32) -> Result<(), DbError> {
-    let client = pool.get().await?;
+    let mut client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    let transaction = begin_sync_write(&mut client, steam_id_int).await?;
     let now = Utc::now();
     
-    client.execute(
+    transaction.execute(
         r#"
         INSERT INTO run_history (steam_id, run_at, total_games, unplayed_games, unplayed_games_total)
         VALUES ($1, $2, $3, 0, $4)
@@ -629,15 +802,17 @@ pub async fn insert_run_history(pool: &Pool, steam_id: &str, total_games: i32, u
         &[&steam_id_int, &now, &total_games, &unplayed_games_total]
     ).await?;
     
+    transaction.commit().await?;
     Ok(())
 }
 
 /// Update the unplayed_games count for the most recent run_history entry
 pub async fn update_latest_run_history_unplayed(pool: &Pool, steam_id: &str, unplayed_games: i32) -> Result<(), DbError> {
-    let client = pool.get().await?;
+    let mut client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    let transaction = begin_sync_write(&mut client, steam_id_int).await?;
     
-    client.execute(
+    transaction.execute(
         r#"
         UPDATE run_history 
         SET unplayed_games = $1 
@@ -646,16 +821,18 @@ pub async fn update_latest_run_history_unplayed(pool: &Pool, steam_id: &str, unp
         &[&unplayed_games, &steam_id_int]
     ).await?;
     
+    transaction.commit().await?;
     Ok(())
 }
 
 /// Update the total_games count for the most recent run_history entry
 /// Used when recently played games add new games not in GetOwnedGames (e.g., some F2P games)
 pub async fn update_run_history_total(pool: &Pool, steam_id: &str, total_games: i32) -> Result<(), DbError> {
-    let client = pool.get().await?;
+    let mut client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    let transaction = begin_sync_write(&mut client, steam_id_int).await?;
     
-    client.execute(
+    transaction.execute(
         r#"
         UPDATE run_history 
         SET total_games = $1 
@@ -664,16 +841,18 @@ pub async fn update_run_history_total(pool: &Pool, steam_id: &str, total_games:
         &[&total_games, &steam_id_int]
     ).await?;
     
+    transaction.commit().await?;
     Ok(())
 }
 
 /// Backfill unplayed_games for run_history entries that still have 0
 /// Only updates entries with unplayed_games = 0 (from before this feature was added)
 pub async fn backfill_run_history_unplayed(pool: &Pool, steam_id: &str, current_unplayed: i32) -> Result<(), DbError> {
-    let client = pool.get().await?;
+    let mut client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    let transaction = begin_sync_write(&mut client, steam_id_int).await?;
     
-    client.execute(
+    transaction.execute(
         r#"
         UPDATE run_history 
         SET unplayed_games = $1 
@@ -682,6 +861,7 @@ pub async fn backfill_run_history_unplayed(pool: &Pool, steam_id: &str, current_
         &[&current_unplayed, &steam_id_int]
     ).await?;
     
+    transaction.commit().await?;
     Ok(())
 }
 
@@ -694,11 +874,12 @@ pub async fn insert_achievement_history(
     games_with_achievements: i32,
     avg_completion_percent: f32,
 ) -> Result<(), DbError> {
-    let client = pool.get().await?;
+    let mut client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    let transaction = begin_sync_write(&mut client, steam_id_int).await?;
     let now = Utc::now();
     
-    client.execute(
+    transaction.execute(
         r#"
         INSERT INTO achievement_history (steam_id, recorded_at, total_achievements, unlocked_achievements, games_with_achievements, avg_completion_percent)
         VALUES ($1, $2, $3, $4, $5, $6)
@@ -706,6 +887,7 @@ pub async fn insert_achievement_history(
         &[&steam_id_int, &now, &total_achievements, &unlocked_achievements, &games_with_achievements, &(avg_completion_percent as f64)]
     ).await?;
     
+    transaction.commit().await?;
     Ok(())
 }
 
@@ -744,167 +926,1331 @@ pub async fn get_log_entries(pool: &Pool, steam_id: &str, limit: i32) -> Result<
     Ok(entries)
 }
 
-/// Upsert an achievement rating for a user
-pub async fn upsert_achievement_rating(
-    pool: &Pool,
-    steam_id: &str,
-    appid: u64,
-    apiname: &str,
-    rating: u8,
-) -> Result<(), DbError> {
+/// Row count and highest sync version of one of the user's lists
+pub async fn get_list_version(pool: &Pool, steam_id: &str, list: UserList) -> Result<ListVersion, DbError> {
     let client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
     
-    client.execute(
-        r#"
-        INSERT INTO achievement_ratings (steam_id, appid, apiname, rating)
-        VALUES ($1, $2, $3, $4)
-        ON CONFLICT (steam_id, appid, apiname)
-        DO UPDATE SET rating = $4, updated_at = NOW()
-        "#,
-        &[&steam_id_int, &(appid as i64), &apiname, &(rating as i16)]
-    ).await?;
+    let query = match list {
+        UserList::Games => "SELECT COUNT(*), COALESCE(MAX(sync_version), 0) FROM user_games WHERE steam_id = $1",
+        UserList::RunHistory => "SELECT COUNT(*), COALESCE(MAX(sync_version), 0) FROM run_history WHERE steam_id = $1",
+        UserList::AchievementHistory => "SELECT COUNT(*), COALESCE(MAX(sync_version), 0) FROM achievement_history WHERE steam_id = $1",
+        // Entries come from unlocked achievements joined with their game
+        UserList::Log => r#"
+            SELECT
+                (SELECT COUNT(*) FROM user_achievements WHERE steam_id = $1 AND achieved = true AND unlocktime IS NOT NULL)
+                + (SELECT COUNT(*) FROM user_games WHERE steam_id = $1),
+                GREATEST(
+                    (SELECT COALESCE(MAX(sync_version), 0) FROM user_achievements WHERE steam_id = $1),
+                    (SELECT COALESCE(MAX(sync_version), 0) FROM user_games WHERE steam_id = $1)
+                )
+            "#,
+    };
+    let row = client.query_one(query, &[&steam_id_int]).await?;
     
-    Ok(())
+    Ok(ListVersion { rows: row.get(0), sync_version: row.get(1) })
 }
 
-/// Get all achievement ratings for a user
-pub async fn get_user_achievement_ratings(
-    pool: &Pool,
-    steam_id: &str,
-) -> Result<Vec<(u64, String, u8)>, DbError> {
+/// Up to `limit` of the user's games by appid, after `after`
+pub async fn get_user_games_page(pool: &Pool, steam_id: &str, after: Option<u64>, limit: i32) -> Result<Vec<Game>, DbError> {
     let client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
     
     let rows = client.query(
         r#"
-        SELECT appid, apiname, rating
-        FROM achievement_ratings
-        WHERE steam_id = $1
+        SELECT appid, name, playtime_forever, rtime_last_played, img_icon_url,
+               added_at, achievements_total, achievements_unlocked, last_sync
+        FROM user_games
+        WHERE steam_id = $1 AND ($2::BIGINT IS NULL OR appid > $2)
+        ORDER BY appid
+        LIMIT $3
         "#,
-        &[&steam_id_int]
+        &[&steam_id_int, &after.map(|appid| appid as i64), &(limit as i64)]
     ).await?;
     
-    let ratings = rows.into_iter().map(|row| {
-        (
-            row.get::<_, i64>("appid") as u64,
-            row.get::<_, String>("apiname"),
-            row.get::<_, i16>("rating") as u8,
-        )
+    let games = rows.into_iter().map(|row| {
+        Game {
+            appid: row.get::<_, i64>("appid") as u64,
+            name: row.get("name"),
+            playtime_forever: row.get::<_, i32>("playtime_forever") as u32,
+            rtime_last_played: row.get::<_, Option<i32>>("rtime_last_played").map(|t| t as u32),
+            img_icon_url: row.get("img_icon_url"),
+            added_at: row.get::<_, Option<DateTime<Utc>>>("added_at").unwrap_or_else(Utc::now),
+            achievements_total: row.get("achievements_total"),
+            achievements_unlocked: row.get("achievements_unlocked"),
+            last_achievement_scrape: row.get("last_sync"),
+        }
     }).collect();
     
-    Ok(ratings)
+    Ok(games)
 }
 
-// ============================================================================
-// Cloud Sync Functions
-// ============================================================================
-
-/// Get cloud sync status for a user
-pub async fn get_cloud_sync_status(pool: &Pool, steam_id: &str) -> Result<CloudSyncStatus, DbError> {
+/// Up to `limit` run history entries by id, after `after`
+pub async fn get_run_history_page(pool: &Pool, steam_id: &str, after: Option<i64>, limit: i32) -> Result<Vec<overachiever_core::RunHistory>, DbError> {
     let client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
     
-    let game_count: i64 = client.query_one(
-        "SELECT COUNT(*) FROM user_games WHERE steam_id = $1",
-        &[&steam_id_int]
-    ).await?.get(0);
-    
-    let achievement_count: i64 = client.query_one(
-        "SELECT COUNT(*) FROM user_achievements WHERE steam_id = $1",
-        &[&steam_id_int]
-    ).await?.get(0);
-    
-    let last_sync: Option<chrono::DateTime<Utc>> = client.query_opt(
-        "SELECT MAX(run_at) FROM run_history WHERE steam_id = $1",
-        &[&steam_id_int]
-    ).await?.and_then(|row| row.get(0));
+    let rows = client.query(
+        r#"
+        SELECT id::bigint as id, run_at, total_games, COALESCE(unplayed_games, 0) as unplayed_games, COALESCE(unplayed_games_total, 0) as unplayed_games_total
+        FROM run_history
+        WHERE steam_id = $1 AND ($2::BIGINT IS NULL OR id > $2)
+        ORDER BY id
+        LIMIT $3
+        "#,
+        &[&steam_id_int, &after, &(limit as i64)]
+    ).await?;
     
-    Ok(CloudSyncStatus {
-        has_data: game_count > 0,
-        game_count: game_count as i32,
-        achievement_count: achievement_count as i32,
-        last_sync,
-    })
-}
-
-/// Get all user data for cloud download
-pub async fn get_cloud_sync_data(pool: &Pool, steam_id: &str) -> Result<CloudSyncData, DbError> {
-    let games = get_user_games(pool, steam_id).await?;
-    let achievements = get_all_user_achievements(pool, steam_id).await?;
-    let run_history = get_run_history(pool, steam_id).await?;
-    let achievement_history = get_achievement_history(pool, steam_id).await?;
+    let history = rows.into_iter().map(|row| {
+        overachiever_core::RunHistory {
+            id: row.get::<_, i64>("id"),
+            run_at: row.get("run_at"),
+            total_games: row.get("total_games"),
+            unplayed_games: row.get("unplayed_games"),
+            unplayed_games_total: row.get("unplayed_games_total"),
+        }
+    }).collect();
     
-    Ok(CloudSyncData {
-        steam_id: steam_id.to_string(),
-        games,
-        achievements,
-        run_history,
-        achievement_history,
-        exported_at: Utc::now(),
-    })
+    Ok(history)
 }
 
-/// Get all achievements for a user (across all games) - lightweight for sync
-async fn get_all_user_achievements(pool: &Pool, steam_id: &str) -> Result<Vec<SyncAchievement>, DbError> {
+/// Up to `limit` achievement history entries by id, after `after`
+pub async fn get_achievement_history_page(pool: &Pool, steam_id: &str, after: Option<i64>, limit: i32) -> Result<Vec<overachiever_core::AchievementHistory>, DbError> {
     let client = pool.get().await?;
     let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
     
     let rows = client.query(
         r#"
-        SELECT appid, apiname, achieved, unlocktime
-        FROM user_achievements
-        WHERE steam_id = $1
-        ORDER BY appid, apiname
+        SELECT id::bigint as id, recorded_at, total_achievements, unlocked_achievements, games_with_achievements, avg_completion_percent
+        FROM achievement_history
+        WHERE steam_id = $1 AND ($2::BIGINT IS NULL OR id > $2)
+        ORDER BY id
+        LIMIT $3
         "#,
-        &[&steam_id_int]
+        &[&steam_id_int, &after, &(limit as i64)]
     ).await?;
     
-    let achievements = rows.into_iter().map(|row| {
-        let unlocktime: Option<i32> = row.get("unlocktime");
-        SyncAchievement {
-            appid: row.get::<_, i64>("appid") as u64,
-            apiname: row.get("apiname"),
-            achieved: row.get("achieved"),
-            unlocktime: unlocktime.and_then(|t| {
-                if t > 0 {
-                    chrono::DateTime::from_timestamp(t as i64, 0)
-                } else {
-                    None
-                }
-            }),
+    let history = rows.into_iter().map(|row| {
+        overachiever_core::AchievementHistory {
+            id: row.get::<_, i64>("id"),
+            recorded_at: row.get("recorded_at"),
+            total_achievements: row.get("total_achievements"),
+            unlocked_achievements: row.get("unlocked_achievements"),
+            games_with_achievements: row.get("games_with_achievements"),
+            avg_completion_percent: row.get::<_, f64>("avg_completion_percent") as f32,
         }
     }).collect();
     
-    Ok(achievements)
+    Ok(history)
 }
 
-/// Upload cloud sync data (overwrites all existing data for user)
-pub async fn upload_cloud_sync_data(pool: &Pool, data: &CloudSyncData) -> Result<(), DbError> {
-    let mut client = pool.get().await?;
-    let steam_id_int: i64 = data.steam_id.parse().unwrap_or(0);
-    
-    // Use transaction for atomicity
+/// Up to `limit` log entries, newest first, after `after`
+pub async fn get_log_page(pool: &Pool, steam_id: &str, after: Option<&LogCursor>, limit: i32) -> Result<Vec<LogEntry>, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let rows = client.query(
+        r#"
+        SELECT ua.appid, g.name as game_name, ua.apiname, s.display_name as achievement_name, 
+               ua.unlocktime, s.icon as achievement_icon, g.img_icon_url as game_icon_url
+        FROM user_achievements ua
+        JOIN user_games g ON ua.steam_id = g.steam_id AND ua.appid = g.appid
+        LEFT JOIN achievement_schemas s ON ua.appid = s.appid AND ua.apiname = s.apiname
+        WHERE ua.steam_id = $1 AND ua.achieved = true AND ua.unlocktime IS NOT NULL
+          AND ($2::TIMESTAMPTZ IS NULL
+               OR ua.unlocktime < $2
+               OR (ua.unlocktime = $2 AND (ua.appid, ua.apiname) > ($3::BIGINT, $4::TEXT)))
+        ORDER BY ua.unlocktime DESC, ua.appid, ua.apiname
+        LIMIT $5
+        "#,
+        &[
+            &steam_id_int,
+            &after.map(|cursor| cursor.unlocktime),
+            &after.map(|cursor| cursor.appid as i64),
+            &after.map(|cursor| cursor.apiname.as_str()),
+            &(limit as i64),
+        ]
+    ).await?;
+    
+    let entries = rows.into_iter().map(|row| {
+        LogEntry::Achievement {
+            appid: row.get::<_, i64>("appid") as u64,
+            game_name: row.get("game_name"),
+            apiname: row.get("apiname"),
+            achievement_name: row.get::<_, Option<String>>("achievement_name").unwrap_or_else(|| "Unknown".to_string()),
+            timestamp: row.get("unlocktime"),
+            achievement_icon: row.get::<_, Option<String>>("achievement_icon").unwrap_or_default(),
+            game_icon_url: row.get("game_icon_url"),
+        }
+    }).collect();
+    
+    Ok(entries)
+}
+
+/// Follow a user. Following an already followed user is a no-op.
+pub async fn follow_user(pool: &Pool, steam_id: &str, followed_steam_id: &str) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    let followed_int: i64 = followed_steam_id.parse().unwrap_or(0);
+    
+    client.execute(
+        r#"
+        INSERT INTO follows (follower_steam_id, followed_steam_id)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING
+        "#,
+        &[&steam_id_int, &followed_int]
+    ).await?;
+    
+    Ok(())
+}
+
+/// Stop following a user
+pub async fn unfollow_user(pool: &Pool, steam_id: &str, followed_steam_id: &str) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    let followed_int: i64 = followed_steam_id.parse().unwrap_or(0);
+    
+    client.execute(
+        "DELETE FROM follows WHERE follower_steam_id = $1 AND followed_steam_id = $2",
+        &[&steam_id_int, &followed_int]
+    ).await?;
+    
+    Ok(())
+}
+
+/// Get the users a user follows, sorted by display name
+pub async fn get_following(pool: &Pool, steam_id: &str) -> Result<Vec<overachiever_core::UserProfile>, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let rows = client.query(
+        r#"
+        SELECT u.steam_id, u.display_name, u.avatar_url, u.short_id
+        FROM follows f
+        JOIN users u ON u.steam_id = f.followed_steam_id
+        WHERE f.follower_steam_id = $1
+        ORDER BY LOWER(u.display_name)
+        "#,
+        &[&steam_id_int]
+    ).await?;
+    
+    Ok(rows.into_iter().map(|row| {
+        overachiever_core::UserProfile {
+            steam_id: row.get::<_, i64>("steam_id").to_string(),
+            display_name: row.get("display_name"),
+            avatar_url: row.get("avatar_url"),
+            short_id: row.get("short_id"),
+        }
+    }).collect())
+}
+
+/// Get recent achievements and first plays of followed users, newest first
+pub async fn get_follow_feed(pool: &Pool, steam_id: &str, limit: i32) -> Result<Vec<FeedEntry>, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let rows = client.query(
+        r#"
+        SELECT * FROM (
+            SELECT 'achievement' AS kind, ua.steam_id, ua.appid, g.name AS game_name, ua.apiname,
+                   s.display_name AS achievement_name, ua.unlocktime AS timestamp,
+                   s.icon AS achievement_icon, g.img_icon_url AS game_icon_url
+            FROM follows f
+            JOIN user_achievements ua ON ua.steam_id = f.followed_steam_id
+            JOIN user_games g ON ua.steam_id = g.steam_id AND ua.appid = g.appid
+            LEFT JOIN achievement_schemas s ON ua.appid = s.appid AND ua.apiname = s.apiname
+            WHERE f.follower_steam_id = $1 AND ua.achieved = true AND ua.unlocktime IS NOT NULL
+            UNION ALL
+            SELECT 'first_play' AS kind, fp.steam_id, fp.appid, g.name AS game_name, NULL AS apiname,
+                   NULL AS achievement_name, fp.played_at AS timestamp,
+                   NULL AS achievement_icon, g.img_icon_url AS game_icon_url
+            FROM follows f
+            JOIN first_plays fp ON fp.steam_id = f.followed_steam_id
+            JOIN user_games g ON fp.steam_id = g.steam_id AND fp.appid = g.appid
+            WHERE f.follower_steam_id = $1
+        ) activity
+        JOIN users u ON u.steam_id = activity.steam_id
+        ORDER BY activity.timestamp DESC
+        LIMIT $2
+        "#,
+        &[&steam_id_int, &(limit as i64)]
+    ).await?;
+    
+    let entries = rows.into_iter().map(|row| {
+        let user = overachiever_core::UserProfile {
+            steam_id: row.get::<_, i64>("steam_id").to_string(),
+            display_name: row.get("display_name"),
+            avatar_url: row.get("avatar_url"),
+            short_id: row.get("short_id"),
+        };
+        let kind: String = row.get("kind");
+        let entry = if kind == "first_play" {
+            LogEntry::FirstPlay {
+                appid: row.get::<_, i64>("appid") as u64,
+                game_name: row.get("game_name"),
+                timestamp: row.get("timestamp"),
+                game_icon_url: row.get("game_icon_url"),
+            }
+        } else {
+            LogEntry::Achievement {
+                appid: row.get::<_, i64>("appid") as u64,
+                game_name: row.get("game_name"),
+                apiname: row.get::<_, Option<String>>("apiname").unwrap_or_default(),
+                achievement_name: row.get::<_, Option<String>>("achievement_name").unwrap_or_else(|| "Unknown".to_string()),
+                timestamp: row.get("timestamp"),
+                achievement_icon: row.get::<_, Option<String>>("achievement_icon").unwrap_or_default(),
+                game_icon_url: row.get("game_icon_url"),
+            }
+        };
+        FeedEntry { user, entry }
+    }).collect();
+    
+    Ok(entries)
+}
+
+/// Whether Steam's global percentages for a game are missing or older than `max_age_hours`
+pub async fn steam_percentages_stale(pool: &Pool, appid: u64, max_age_hours: i64) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    
+    let row = client.query_opt(
+        r#"
+        SELECT 1 FROM steam_percentages_fetched
+        WHERE appid = $1 AND fetched_at > NOW() - make_interval(hours => $2::INT)
+        "#,
+        &[&(appid as i64), &(max_age_hours as i32)]
+    ).await?;
+    
+    Ok(row.is_none())
+}
+
+/// Replace the cached Steam global percentages for a game
+pub async fn store_steam_percentages(pool: &Pool, appid: u64, percentages: &[(String, f32)]) -> Result<(), DbError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let appid_int = appid as i64;
+    
+    transaction.execute("DELETE FROM steam_achievement_percentages WHERE appid = $1", &[&appid_int]).await?;
+    for (apiname, percent) in percentages {
+        transaction.execute(
+            "INSERT INTO steam_achievement_percentages (appid, apiname, percent) VALUES ($1, $2, $3)",
+            &[&appid_int, apiname, percent]
+        ).await?;
+    }
+    transaction.execute(
+        r#"
+        INSERT INTO steam_percentages_fetched (appid, fetched_at) VALUES ($1, NOW())
+        ON CONFLICT (appid) DO UPDATE SET fetched_at = NOW()
+        "#,
+        &[&appid_int]
+    ).await?;
+    
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Record a failed fetch of Steam's global percentages, keeping the cached ones
+pub async fn mark_steam_percentages_fetched(pool: &Pool, appid: u64) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client.execute(
+        r#"
+        INSERT INTO steam_percentages_fetched (appid, fetched_at) VALUES ($1, NOW())
+        ON CONFLICT (appid) DO UPDATE SET fetched_at = NOW()
+        "#,
+        &[&(appid as i64)]
+    ).await?;
+    Ok(())
+}
+
+/// Community unlock rates for a game's achievements, with cached Steam percentages.
+/// Only owners whose achievements have been scanned count towards the share.
+pub async fn get_game_rarity(pool: &Pool, appid: u64) -> Result<GameRarity, DbError> {
+    let client = pool.get().await?;
+    let appid_int = appid as i64;
+    
+    let owners: i64 = client.query_one(
+        "SELECT COUNT(*) FROM user_games WHERE appid = $1 AND achievements_total IS NOT NULL",
+        &[&appid_int]
+    ).await?.get(0);
+    
+    let rows = client.query(
+        r#"
+        SELECT names.apiname, COALESCE(c.unlocked, 0) AS unlocked, sp.percent AS steam_percent
+        FROM (
+            SELECT apiname FROM achievement_schemas WHERE appid = $1
+            UNION
+            SELECT apiname FROM steam_achievement_percentages WHERE appid = $1
+        ) names
+        LEFT JOIN (
+            SELECT ua.apiname, COUNT(*) AS unlocked
+            FROM user_achievements ua
+            JOIN user_games g ON g.steam_id = ua.steam_id AND g.appid = ua.appid
+            WHERE ua.appid = $1 AND ua.achieved = true AND g.achievements_total IS NOT NULL
+            GROUP BY ua.apiname
+        ) c ON c.apiname = names.apiname
+        LEFT JOIN steam_achievement_percentages sp ON sp.appid = $1 AND sp.apiname = names.apiname
+        ORDER BY names.apiname
+        "#,
+        &[&appid_int]
+    ).await?;
+    
+    let achievements = rows.into_iter().map(|row| {
+        let unlocked: i64 = row.get("unlocked");
+        AchievementRarity {
+            apiname: row.get("apiname"),
+            community_percent: if owners > 0 { unlocked as f32 / owners as f32 * 100.0 } else { 0.0 },
+            steam_percent: row.get("steam_percent"),
+        }
+    }).collect();
+    
+    Ok(GameRarity { appid, community_owners: owners as u32, achievements })
+}
+
+/// Columns of a comment with its author and tagged achievements; callers add
+/// WHERE on `c`, and GROUP BY / ORDER BY
+const COMMENT_SELECT: &str = r#"
+    SELECT c.id, c.comment, c.created_at, c.edited_at,
+           u.steam_id, u.display_name, u.avatar_url, u.short_id,
+           ARRAY_AGG(t.appid ORDER BY t.appid, t.apiname) AS tag_appids,
+           ARRAY_AGG(t.apiname ORDER BY t.appid, t.apiname) AS tag_apinames
+    FROM achievement_comments c
+    JOIN users u ON u.steam_id = c.steam_id
+    JOIN achievement_comment_tags t ON t.comment_id = c.id
+"#;
+
+fn comment_from_row(row: &tokio_postgres::Row, viewer_steam_id: i64) -> AchievementComment {
+    let author_steam_id: i64 = row.get("steam_id");
+    let appids: Vec<i64> = row.get("tag_appids");
+    let apinames: Vec<String> = row.get("tag_apinames");
+    AchievementComment {
+        id: row.get("id"),
+        author: overachiever_core::UserProfile {
+            steam_id: author_steam_id.to_string(),
+            display_name: row.get("display_name"),
+            avatar_url: row.get("avatar_url"),
+            short_id: row.get("short_id"),
+        },
+        achievements: appids.into_iter().map(|appid| appid as u64).zip(apinames).collect(),
+        comment: row.get("comment"),
+        created_at: row.get("created_at"),
+        edited_at: row.get("edited_at"),
+        is_yours: author_steam_id == viewer_steam_id,
+    }
+}
+
+/// Get a comment by id, as seen by a user
+async fn get_achievement_comment(client: &deadpool_postgres::Client, id: i64, viewer_steam_id: i64) -> Result<Option<AchievementComment>, DbError> {
+    let query = format!("{} WHERE c.id = $1 GROUP BY c.id, u.steam_id", COMMENT_SELECT);
+    let row = client.query_opt(&query, &[&id]).await?;
+    Ok(row.map(|row| comment_from_row(&row, viewer_steam_id)))
+}
+
+/// Store a comment tagging one or more achievements
+pub async fn insert_achievement_comment(
+    pool: &Pool,
+    steam_id: &str,
+    achievements: &[(u64, String)],
+    comment: &str,
+) -> Result<AchievementComment, DbError> {
+    let mut client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
     let transaction = client.transaction().await?;
+    let id: i64 = transaction.query_one(
+        "INSERT INTO achievement_comments (steam_id, comment) VALUES ($1, $2) RETURNING id",
+        &[&steam_id_int, &comment]
+    ).await?.get(0);
+    for (appid, apiname) in achievements {
+        transaction.execute(
+            r#"
+            INSERT INTO achievement_comment_tags (comment_id, appid, apiname)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+            &[&id, &(*appid as i64), apiname]
+        ).await?;
+    }
+    let query = format!("{} WHERE c.id = $1 GROUP BY c.id, u.steam_id", COMMENT_SELECT);
+    let row = transaction.query_one(&query, &[&id]).await?;
+    transaction.commit().await?;
+    
+    Ok(comment_from_row(&row, steam_id_int))
+}
+
+/// Comments tagging an achievement, oldest first. `viewer` marks the
+/// requesting user's own comments.
+pub async fn get_achievement_comments(
+    pool: &Pool,
+    appid: u64,
+    apiname: &str,
+    viewer: Option<&str>,
+    limit: i64,
+) -> Result<Vec<AchievementComment>, DbError> {
+    let client = pool.get().await?;
+    let viewer_steam_id: i64 = viewer.and_then(|s| s.parse().ok()).unwrap_or(0);
+    
+    let query = format!(
+        r#"{}
+        WHERE c.id IN (SELECT comment_id FROM achievement_comment_tags WHERE appid = $1 AND apiname = $2)
+          AND c.hidden_at IS NULL
+        GROUP BY c.id, u.steam_id
+        ORDER BY c.created_at
+        LIMIT $3
+        "#,
+        COMMENT_SELECT
+    );
+    let rows = client.query(&query, &[&(appid as i64), &apiname, &limit]).await?;
+    
+    Ok(rows.iter().map(|row| comment_from_row(row, viewer_steam_id)).collect())
+}
+
+/// Change the text of a user's own comment. Returns None if the user has no
+/// visible comment with this id.
+pub async fn update_achievement_comment(
+    pool: &Pool,
+    steam_id: &str,
+    id: i64,
+    comment: &str,
+) -> Result<Option<AchievementComment>, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let updated = client.execute(
+        "UPDATE achievement_comments SET comment = $3, edited_at = NOW() WHERE id = $1 AND steam_id = $2 AND hidden_at IS NULL",
+        &[&id, &steam_id_int, &comment]
+    ).await?;
+    if updated == 0 {
+        return Ok(None);
+    }
+    
+    get_achievement_comment(&client, id, steam_id_int).await
+}
+
+/// Delete a user's own comment. Returns false if the user has no comment with this id.
+pub async fn delete_achievement_comment(pool: &Pool, steam_id: &str, id: i64) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let deleted = client.execute(
+        "DELETE FROM achievement_comments WHERE id = $1 AND steam_id = $2",
+        &[&id, &steam_id_int]
+    ).await?;
+    Ok(deleted > 0)
+}
+
+/// Community difficulty averages of the rated achievements of some games
+pub async fn get_achievement_averages(pool: &Pool, appids: &[u64]) -> Result<Vec<AchievementDifficulty>, DbError> {
+    let client = pool.get().await?;
+    let appids: Vec<i64> = appids.iter().map(|&appid| appid as i64).collect();
+    
+    let rows = client.query(
+        r#"
+        SELECT appid, apiname, AVG(rating)::REAL AS avg_rating, COUNT(rating)::INT AS rating_count
+        FROM achievement_ratings
+        WHERE appid = ANY($1) AND rating IS NOT NULL
+        GROUP BY appid, apiname
+        "#,
+        &[&appids]
+    ).await?;
+    
+    Ok(rows.into_iter().map(|row| {
+        let appid: i64 = row.get("appid");
+        AchievementDifficulty {
+            appid: appid as u64,
+            apiname: row.get("apiname"),
+            avg_rating: row.get("avg_rating"),
+            rating_count: row.get("rating_count"),
+        }
+    }).collect())
+}
+
+/// Community flags of the achievements of some games. Only flags set by at
+/// least [`FLAG_VOTE_THRESHOLD`] users are returned.
+pub async fn get_achievement_flag_votes(pool: &Pool, appids: &[u64]) -> Result<Vec<AchievementFlagVotes>, DbError> {
+    let client = pool.get().await?;
+    let appids: Vec<i64> = appids.iter().map(|&appid| appid as i64).collect();
+    
+    let rows = client.query(
+        r#"
+        SELECT appid, apiname,
+               COUNT(*) FILTER (WHERE flags & 1 <> 0)::INT AS missable,
+               COUNT(*) FILTER (WHERE flags & 2 <> 0)::INT AS bugged,
+               COUNT(*) FILTER (WHERE flags & 4 <> 0)::INT AS grind,
+               COUNT(*) FILTER (WHERE flags & 8 <> 0)::INT AS online_only
+        FROM achievement_ratings
+        WHERE appid = ANY($1) AND flags <> 0
+        GROUP BY appid, apiname
+        "#,
+        &[&appids]
+    ).await?;
+    
+    let mut votes = Vec::new();
+    for row in rows {
+        let appid: i64 = row.get("appid");
+        let apiname: String = row.get("apiname");
+        for (flag, column) in [
+            (AchievementFlag::Missable, "missable"),
+            (AchievementFlag::Bugged, "bugged"),
+            (AchievementFlag::Grind, "grind"),
+            (AchievementFlag::OnlineOnly, "online_only"),
+        ] {
+            let count: i32 = row.get(column);
+            if count >= FLAG_VOTE_THRESHOLD {
+                votes.push(AchievementFlagVotes { appid: appid as u64, apiname: apiname.clone(), flag, votes: count });
+            }
+        }
+    }
+    Ok(votes)
+}
+
+/// Recompute the cached leaderboard totals
+pub async fn refresh_leaderboard_stats(pool: &Pool) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    client.execute("REFRESH MATERIALIZED VIEW CONCURRENTLY leaderboard_stats", &[]).await?;
+    Ok(())
+}
+
+/// Show or hide a user on global leaderboards
+pub async fn set_leaderboard_opt_in(pool: &Pool, steam_id: &str, enabled: bool) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    client.execute(
+        "UPDATE users SET leaderboard_opt_in = $2 WHERE steam_id = $1",
+        &[&steam_id_int, &enabled]
+    ).await?;
+    
+    Ok(())
+}
+
+/// Rank users by a metric from the cached totals. Returns the top `limit`
+/// entries plus the requesting user's own entry if it ranks lower.
+pub async fn get_leaderboard(
+    pool: &Pool,
+    steam_id: &str,
+    metric: LeaderboardMetric,
+    scope: LeaderboardScope,
+    limit: i32,
+) -> Result<Leaderboard, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    // Column names come from a fixed list, never from user input
+    let (value, column) = match metric {
+        LeaderboardMetric::TotalUnlocks => ("ls.total_unlocks::DOUBLE PRECISION", "total_unlocks"),
+        LeaderboardMetric::PerfectGames => ("ls.perfect_games::DOUBLE PRECISION", "perfect_games"),
+        LeaderboardMetric::AvgCompletion => ("ls.avg_completion", "avg_completion"),
+        LeaderboardMetric::UnlocksThisMonth => ("ls.unlocks_this_month::DOUBLE PRECISION", "unlocks_this_month"),
+    };
+    let scope_filter = match scope {
+        LeaderboardScope::Following => {
+            "ls.steam_id = $1 OR ls.steam_id IN (SELECT followed_steam_id FROM follows WHERE follower_steam_id = $1)"
+        }
+        LeaderboardScope::Global => "u.leaderboard_opt_in",
+    };
+    
+    let rows = client.query(
+        &format!(
+            r#"
+            WITH ranked AS (
+                SELECT ls.steam_id, u.display_name, u.avatar_url, u.short_id, {value} AS value,
+                       RANK() OVER (ORDER BY ls.{column} DESC) AS rank
+                FROM leaderboard_stats ls
+                JOIN users u ON u.steam_id = ls.steam_id
+                WHERE {scope_filter}
+            )
+            SELECT * FROM ranked
+            WHERE rank <= $2 OR steam_id = $1
+            ORDER BY rank, LOWER(display_name)
+            "#
+        ),
+        &[&steam_id_int, &(limit as i64)]
+    ).await?;
+    
+    let entries = rows.into_iter().map(|row| {
+        let row_steam_id: i64 = row.get("steam_id");
+        LeaderboardEntry {
+            rank: row.get::<_, i64>("rank") as u32,
+            user: overachiever_core::UserProfile {
+                steam_id: row_steam_id.to_string(),
+                display_name: row.get("display_name"),
+                avatar_url: row.get("avatar_url"),
+                short_id: row.get("short_id"),
+            },
+            value: row.get("value"),
+            is_you: row_steam_id == steam_id_int,
+        }
+    }).collect();
+    
+    let refreshed_at: Option<DateTime<Utc>> = client.query_one(
+        "SELECT MAX(refreshed_at) FROM leaderboard_stats",
+        &[]
+    ).await?.get(0);
+    
+    let opted_in: bool = client.query_opt(
+        "SELECT leaderboard_opt_in FROM users WHERE steam_id = $1",
+        &[&steam_id_int]
+    ).await?.map(|row| row.get(0)).unwrap_or(false);
+    
+    Ok(Leaderboard { metric, scope, entries, refreshed_at, opted_in })
+}
+
+/// Upsert an achievement rating for a user
+pub async fn upsert_achievement_rating(
+    pool: &Pool,
+    steam_id: &str,
+    appid: u64,
+    apiname: &str,
+    rating: u8,
+) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    client.execute(
+        r#"
+        INSERT INTO achievement_ratings (steam_id, appid, apiname, rating)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (steam_id, appid, apiname)
+        DO UPDATE SET rating = $4, updated_at = NOW()
+        "#,
+        &[&steam_id_int, &(appid as i64), &apiname, &(rating as i16)]
+    ).await?;
+    
+    Ok(())
+}
+
+/// Set a user's flags for an achievement, keeping any difficulty rating
+pub async fn upsert_achievement_flags(
+    pool: &Pool,
+    steam_id: &str,
+    appid: u64,
+    apiname: &str,
+    flags: AchievementFlags,
+) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    client.execute(
+        r#"
+        INSERT INTO achievement_ratings (steam_id, appid, apiname, rating, flags)
+        VALUES ($1, $2, $3, NULL, $4)
+        ON CONFLICT (steam_id, appid, apiname)
+        DO UPDATE SET flags = $4, updated_at = NOW()
+        "#,
+        &[&steam_id_int, &(appid as i64), &apiname, &(flags.bits() as i16)]
+    ).await?;
+    
+    Ok(())
+}
+
+/// Get all achievement flags a user has set
+pub async fn get_user_achievement_flags(
+    pool: &Pool,
+    steam_id: &str,
+) -> Result<Vec<(u64, String, AchievementFlags)>, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let rows = client.query(
+        r#"
+        SELECT appid, apiname, flags
+        FROM achievement_ratings
+        WHERE steam_id = $1 AND flags <> 0
+        "#,
+        &[&steam_id_int]
+    ).await?;
+    
+    let flags = rows.into_iter().map(|row| {
+        (
+            row.get::<_, i64>("appid") as u64,
+            row.get::<_, String>("apiname"),
+            AchievementFlags::from_bits(row.get::<_, i16>("flags") as u8),
+        )
+    }).collect();
+    
+    Ok(flags)
+}
+
+/// Get all achievement ratings for a user
+pub async fn get_user_achievement_ratings(
+    pool: &Pool,
+    steam_id: &str,
+) -> Result<Vec<(u64, String, u8)>, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let rows = client.query(
+        r#"
+        SELECT appid, apiname, rating
+        FROM achievement_ratings
+        WHERE steam_id = $1 AND rating IS NOT NULL
+        "#,
+        &[&steam_id_int]
+    ).await?;
+    
+    let ratings = rows.into_iter().map(|row| {
+        (
+            row.get::<_, i64>("appid") as u64,
+            row.get::<_, String>("apiname"),
+            row.get::<_, i16>("rating") as u8,
+        )
+    }).collect();
+    
+    Ok(ratings)
+}
+
+// ============================================================================
+// Moderation Functions
+// ============================================================================
+
+/// Table holding a kind of community content
+fn content_table(kind: ContentKind) -> &'static str {
+    match kind {
+        ContentKind::Comment => "achievement_comments",
+        ContentKind::Tip => "achievement_tips",
+        ContentKind::Review => "game_ratings",
+    }
+}
+
+/// Whether a user may use the admin routes
+pub async fn is_admin(pool: &Pool, steam_id: &str) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let row = client.query_opt("SELECT is_admin FROM users WHERE steam_id = $1", &[&steam_id_int]).await?;
+    Ok(row.map(|row| row.get(0)).unwrap_or(false))
+}
+
+/// Whether a user is banned from submitting community content
+pub async fn is_banned(pool: &Pool, steam_id: &str) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let row = client.query_opt(
+        "SELECT banned_at IS NOT NULL FROM users WHERE steam_id = $1",
+        &[&steam_id_int]
+    ).await?;
+    Ok(row.map(|row| row.get(0)).unwrap_or(false))
+}
+
+/// Ban or unban a user. Returns false if there is no such user.
+pub async fn set_user_banned(pool: &Pool, steam_id: &str, banned: bool, reason: Option<&str>) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let updated = client.execute(
+        r#"
+        UPDATE users
+        SET banned_at = CASE WHEN $2 THEN COALESCE(banned_at, NOW()) END,
+            ban_reason = CASE WHEN $2 THEN $3 END
+        WHERE steam_id = $1
+        "#,
+        &[&steam_id_int, &banned, &reason]
+    ).await?;
+    Ok(updated > 0)
+}
+
+/// Report visible content of another user. Reporting the same item again
+/// replaces the reason and reopens the report. Returns false if there is no
+/// such visible content by another user.
+pub async fn insert_content_report(
+    pool: &Pool,
+    steam_id: &str,
+    kind: ContentKind,
+    content_id: i64,
+    reason: &str,
+) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    // Table names come from a fixed list, never from user input
+    let exists = client.query_opt(
+        &format!(
+            "SELECT 1 FROM {} WHERE id = $1::BIGINT AND steam_id <> $2 AND hidden_at IS NULL",
+            content_table(kind)
+        ),
+        &[&content_id, &steam_id_int]
+    ).await?;
+    if exists.is_none() {
+        return Ok(false);
+    }
+    
+    client.execute(
+        r#"
+        INSERT INTO content_reports (reporter_steam_id, kind, content_id, reason)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (reporter_steam_id, kind, content_id)
+        DO UPDATE SET reason = EXCLUDED.reason, created_at = NOW(), resolved_at = NULL
+        "#,
+        &[&steam_id_int, &kind.as_str(), &content_id, &reason]
+    ).await?;
+    Ok(true)
+}
+
+/// Open reports with the reported content, oldest first
+pub async fn get_open_reports(pool: &Pool) -> Result<Vec<ContentReport>, DbError> {
+    let client = pool.get().await?;
+    
+    let rows = client.query(
+        r#"
+        SELECT r.id, r.kind, r.content_id, r.reason, r.created_at,
+               ru.steam_id AS reporter_steam_id, ru.display_name AS reporter_name,
+               ru.avatar_url AS reporter_avatar, ru.short_id AS reporter_short_id,
+               au.steam_id AS author_steam_id, au.display_name AS author_name,
+               au.avatar_url AS author_avatar, au.short_id AS author_short_id,
+               c.text, c.hidden
+        FROM content_reports r
+        JOIN users ru ON ru.steam_id = r.reporter_steam_id
+        JOIN (
+            SELECT 'comment' AS kind, id AS id, steam_id, comment AS text, hidden_at IS NOT NULL AS hidden
+            FROM achievement_comments
+            UNION ALL
+            SELECT 'tip', id::BIGINT, steam_id, tip, hidden_at IS NOT NULL
+            FROM achievement_tips
+            UNION ALL
+            SELECT 'review', id::BIGINT, steam_id, COALESCE(comment, ''), hidden_at IS NOT NULL
+            FROM game_ratings
+        ) c ON c.kind = r.kind AND c.id = r.content_id
+        JOIN users au ON au.steam_id = c.steam_id
+        WHERE r.resolved_at IS NULL
+        ORDER BY r.created_at
+        "#,
+        &[]
+    ).await?;
+    
+    Ok(rows.into_iter().filter_map(|row| {
+        let kind = ContentKind::from_name(row.get("kind"))?;
+        Some(ContentReport {
+            id: row.get("id"),
+            kind,
+            content_id: row.get("content_id"),
+            reporter: overachiever_core::UserProfile {
+                steam_id: row.get::<_, i64>("reporter_steam_id").to_string(),
+                display_name: row.get("reporter_name"),
+                avatar_url: row.get("reporter_avatar"),
+                short_id: row.get("reporter_short_id"),
+            },
+            reason: row.get("reason"),
+            created_at: row.get("created_at"),
+            author: overachiever_core::UserProfile {
+                steam_id: row.get::<_, i64>("author_steam_id").to_string(),
+                display_name: row.get("author_name"),
+                avatar_url: row.get("author_avatar"),
+                short_id: row.get("author_short_id"),
+            },
+            content: row.get("text"),
+            hidden: row.get("hidden"),
+        })
+    }).collect())
+}
+
+/// Close a report without acting on the content. Returns false if there is
+/// no such open report.
+pub async fn dismiss_report(pool: &Pool, id: i64) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    
+    let updated = client.execute(
+        "UPDATE content_reports SET resolved_at = NOW() WHERE id = $1 AND resolved_at IS NULL",
+        &[&id]
+    ).await?;
+    Ok(updated > 0)
+}
+
+/// Close all open reports of an item after an admin acted on it
+async fn resolve_reports(client: &deadpool_postgres::Client, kind: ContentKind, content_id: i64) -> Result<(), DbError> {
+    client.execute(
+        "UPDATE content_reports SET resolved_at = NOW() WHERE kind = $1 AND content_id = $2 AND resolved_at IS NULL",
+        &[&kind.as_str(), &content_id]
+    ).await?;
+    Ok(())
+}
+
+/// Hide or show community content. Hiding resolves its open reports.
+/// Returns false if there is no such content.
+pub async fn set_content_hidden(pool: &Pool, kind: ContentKind, content_id: i64, hidden: bool) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    
+    let updated = client.execute(
+        &format!(
+            "UPDATE {} SET hidden_at = CASE WHEN $2 THEN COALESCE(hidden_at, NOW()) END WHERE id = $1::BIGINT",
+            content_table(kind)
+        ),
+        &[&content_id, &hidden]
+    ).await?;
+    if updated > 0 && hidden {
+        resolve_reports(&client, kind, content_id).await?;
+    }
+    Ok(updated > 0)
+}
+
+/// Delete community content of any user and resolve its open reports.
+/// Returns false if there is no such content.
+pub async fn delete_content(pool: &Pool, kind: ContentKind, content_id: i64) -> Result<bool, DbError> {
+    let client = pool.get().await?;
+    
+    let deleted = client.execute(
+        &format!("DELETE FROM {} WHERE id = $1::BIGINT", content_table(kind)),
+        &[&content_id]
+    ).await?;
+    if deleted > 0 {
+        resolve_reports(&client, kind, content_id).await?;
+    }
+    Ok(deleted > 0)
+}
+
+// ============================================================================
+// Session Functions
+// ============================================================================
+
+/// Start a login session and return its id
+pub async fn create_session(
+    pool: &Pool,
+    steam_id: &str,
+    refresh_token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<i64, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let row = client.query_one(
+        r#"
+        INSERT INTO sessions (steam_id, refresh_token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        RETURNING id
+        "#,
+        &[&steam_id_int, &refresh_token_hash, &expires_at]
+    ).await?;
+    Ok(row.get(0))
+}
+
+/// Replace the refresh token of an active session and extend it. Returns the
+/// session id and its user, or None if the token is unknown, expired or revoked.
+pub async fn rotate_session(
+    pool: &Pool,
+    refresh_token_hash: &str,
+    new_refresh_token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<Option<(i64, overachiever_core::UserProfile)>, DbError> {
+    let client = pool.get().await?;
+    
+    let row = client.query_opt(
+        r#"
+        WITH rotated AS (
+            UPDATE sessions SET
+                refresh_token_hash = $2,
+                previous_token_hash = $1,
+                last_used_at = NOW(),
+                expires_at = $3
+            WHERE refresh_token_hash = $1
+              AND revoked_at IS NULL
+              AND expires_at > NOW()
+            RETURNING id, steam_id
+        )
+        SELECT r.id, u.steam_id, u.display_name, u.avatar_url, u.short_id
+        FROM rotated r
+        JOIN users u ON u.steam_id = r.steam_id
+        "#,
+        &[&refresh_token_hash, &new_refresh_token_hash, &expires_at]
+    ).await?;
+    
+    Ok(row.map(|row| {
+        let profile = overachiever_core::UserProfile {
+            steam_id: row.get::<_, i64>("steam_id").to_string(),
+            display_name: row.get("display_name"),
+            avatar_url: row.get("avatar_url"),
+            short_id: row.get("short_id"),
+        };
+        (row.get("id"), profile)
+    }))
+}
+
+/// Revoke the session a refresh token belongs to, either as its current
+/// token or as the one it replaced. Returns the session id if one was revoked.
+pub async fn revoke_session_by_token(pool: &Pool, refresh_token_hash: &str) -> Result<Option<i64>, DbError> {
+    let client = pool.get().await?;
+    
+    let row = client.query_opt(
+        r#"
+        UPDATE sessions SET revoked_at = NOW()
+        WHERE (refresh_token_hash = $1 OR previous_token_hash = $1)
+          AND revoked_at IS NULL
+        RETURNING id
+        "#,
+        &[&refresh_token_hash]
+    ).await?;
+    Ok(row.map(|row| row.get(0)))
+}
+
+/// Revoke all active sessions of a user ("log out all devices")
+pub async fn revoke_user_sessions(pool: &Pool, steam_id: &str) -> Result<Vec<i64>, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
     
-    // Ensure user exists
-    transaction.execute(
-        "INSERT INTO users (steam_id, display_name) VALUES ($1, $2) ON CONFLICT (steam_id) DO NOTHING",
-        &[&steam_id_int, &format!("User {}", &data.steam_id[..8.min(data.steam_id.len())])]
+    let rows = client.query(
+        r#"
+        UPDATE sessions SET revoked_at = NOW()
+        WHERE steam_id = $1 AND revoked_at IS NULL
+        RETURNING id
+        "#,
+        &[&steam_id_int]
     ).await?;
+    Ok(rows.iter().map(|row| row.get(0)).collect())
+}
+
+/// Ids of sessions revoked since `since`
+pub async fn get_revoked_session_ids(pool: &Pool, since: DateTime<Utc>) -> Result<Vec<i64>, DbError> {
+    let client = pool.get().await?;
     
-    // Delete existing data for this user
-    transaction.execute("DELETE FROM user_achievements WHERE steam_id = $1", &[&steam_id_int]).await?;
-    transaction.execute("DELETE FROM user_games WHERE steam_id = $1", &[&steam_id_int]).await?;
-    transaction.execute("DELETE FROM run_history WHERE steam_id = $1", &[&steam_id_int]).await?;
-    transaction.execute("DELETE FROM achievement_history WHERE steam_id = $1", &[&steam_id_int]).await?;
+    let rows = client.query(
+        "SELECT id FROM sessions WHERE revoked_at >= $1",
+        &[&since]
+    ).await?;
+    Ok(rows.iter().map(|row| row.get(0)).collect())
+}
+
+// ============================================================================
+// Cloud Sync Functions
+// ============================================================================
+
+/// Get cloud sync status for a user
+pub async fn get_cloud_sync_status(pool: &Pool, steam_id: &str) -> Result<CloudSyncStatus, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let game_count: i64 = client.query_one(
+        "SELECT COUNT(*) FROM user_games WHERE steam_id = $1",
+        &[&steam_id_int]
+    ).await?.get(0);
+    
+    let achievement_count: i64 = client.query_one(
+        "SELECT COUNT(*) FROM user_achievements WHERE steam_id = $1",
+        &[&steam_id_int]
+    ).await?.get(0);
+    
+    let last_sync: Option<chrono::DateTime<Utc>> = client.query_opt(
+        "SELECT MAX(run_at) FROM run_history WHERE steam_id = $1",
+        &[&steam_id_int]
+    ).await?.and_then(|row| row.get(0));
+    
+    // Encrypted backups keep their history out of run_history
+    let encrypted_row = client.query_opt(
+        "SELECT last_sync FROM encrypted_sync_blobs WHERE steam_id = $1",
+        &[&steam_id_int]
+    ).await?;
+    let encrypted_last_sync: Option<DateTime<Utc>> = encrypted_row.as_ref().and_then(|row| row.get(0));
+    
+    Ok(CloudSyncStatus {
+        has_data: game_count > 0 || encrypted_row.is_some(),
+        game_count: game_count as i32,
+        achievement_count: achievement_count as i32,
+        last_sync: last_sync.max(encrypted_last_sync),
+        encrypted: encrypted_row.is_some(),
+    })
+}
+
+/// Get all user data for cloud download
+pub async fn get_cloud_sync_data(pool: &Pool, steam_id: &str) -> Result<CloudSyncData, DbError> {
+    let games = get_user_games(pool, steam_id).await?;
+    let achievements = get_all_user_achievements(pool, steam_id).await?;
+    let run_history = get_run_history(pool, steam_id).await?;
+    let achievement_history = get_achievement_history(pool, steam_id).await?;
+    
+    Ok(CloudSyncData {
+        steam_id: steam_id.to_string(),
+        games,
+        achievements,
+        run_history,
+        achievement_history,
+        exported_at: Utc::now(),
+    })
+}
+
+/// Get all achievements for a user (across all games) - lightweight for sync
+async fn get_all_user_achievements(pool: &Pool, steam_id: &str) -> Result<Vec<SyncAchievement>, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let rows = client.query(
+        r#"
+        SELECT appid, apiname, achieved, unlocktime
+        FROM user_achievements
+        WHERE steam_id = $1
+        ORDER BY appid, apiname
+        "#,
+        &[&steam_id_int]
+    ).await?;
+    
+    let achievements = rows.into_iter().map(|row| {
+        SyncAchievement {
+            appid: row.get::<_, i64>("appid") as u64,
+            apiname: row.get("apiname"),
+            achieved: row.get("achieved"),
+            unlocktime: row.get("unlocktime"),
+        }
+    }).collect();
+    
+    Ok(achievements)
+}
+
+/// The stored rows a sync bundle touches: its games and achievements by key and
+/// its history entries by timestamp. With `lock` the rows stay locked until the
+/// transaction ends.
+async fn get_stored_sync_rows(
+    transaction: &deadpool_postgres::Transaction<'_>,
+    steam_id_int: i64,
+    data: &CloudSyncData,
+    lock: bool,
+) -> Result<CloudSyncData, DbError> {
+    let for_update = if lock { " FOR UPDATE" } else { "" };
+    let mut stored = CloudSyncData {
+        steam_id: data.steam_id.clone(),
+        games: Vec::new(),
+        achievements: Vec::new(),
+        run_history: Vec::new(),
+        achievement_history: Vec::new(),
+        exported_at: Utc::now(),
+    };
+    
+    if !data.games.is_empty() {
+        let appids: Vec<i64> = data.games.iter().map(|g| g.appid as i64).collect();
+        let rows = transaction.query(
+            &format!(
+                r#"
+                SELECT appid, name, playtime_forever, rtime_last_played, img_icon_url,
+                       added_at, achievements_total, achievements_unlocked, last_sync
+                FROM user_games
+                WHERE steam_id = $1 AND appid = ANY($2){for_update}
+                "#
+            ),
+            &[&steam_id_int, &appids]
+        ).await?;
+        stored.games = rows.into_iter().map(|row| Game {
+            appid: row.get::<_, i64>("appid") as u64,
+            name: row.get("name"),
+            playtime_forever: row.get::<_, i32>("playtime_forever") as u32,
+            rtime_last_played: row.get::<_, Option<i32>>("rtime_last_played").map(|t| t as u32),
+            img_icon_url: row.get("img_icon_url"),
+            added_at: row.get::<_, Option<DateTime<Utc>>>("added_at").unwrap_or_else(Utc::now),
+            achievements_total: row.get("achievements_total"),
+            achievements_unlocked: row.get("achievements_unlocked"),
+            last_achievement_scrape: row.get("last_sync"),
+        }).collect();
+    }
+    
+    if !data.achievements.is_empty() {
+        let appids: Vec<i64> = data.achievements.iter().map(|a| a.appid as i64).collect();
+        let apinames: Vec<&str> = data.achievements.iter().map(|a| a.apiname.as_str()).collect();
+        let rows = transaction.query(
+            &format!(
+                r#"
+                SELECT ua.appid, ua.apiname, ua.achieved, ua.unlocktime
+                FROM user_achievements ua
+                JOIN UNNEST($2::bigint[], $3::text[]) AS k(appid, apiname)
+                    ON ua.appid = k.appid AND ua.apiname = k.apiname
+                WHERE ua.steam_id = $1{}
+                "#,
+                if lock { " FOR UPDATE OF ua" } else { "" }
+            ),
+            &[&steam_id_int, &appids, &apinames]
+        ).await?;
+        stored.achievements = rows.into_iter().map(|row| SyncAchievement {
+            appid: row.get::<_, i64>("appid") as u64,
+            apiname: row.get("apiname"),
+            achieved: row.get::<_, Option<bool>>("achieved").unwrap_or(false),
+            unlocktime: row.get("unlocktime"),
+        }).collect();
+    }
+    
+    if !data.run_history.is_empty() {
+        let times: Vec<DateTime<Utc>> = data.run_history.iter().map(|r| r.run_at).collect();
+        let rows = transaction.query(
+            r#"
+            SELECT id::bigint as id, run_at, total_games, COALESCE(unplayed_games, 0) as unplayed_games, COALESCE(unplayed_games_total, 0) as unplayed_games_total
+            FROM run_history
+            WHERE steam_id = $1 AND run_at = ANY($2)
+            "#,
+            &[&steam_id_int, &times]
+        ).await?;
+        stored.run_history = rows.into_iter().map(|row| overachiever_core::RunHistory {
+            id: row.get::<_, i64>("id"),
+            run_at: row.get("run_at"),
+            total_games: row.get("total_games"),
+            unplayed_games: row.get("unplayed_games"),
+            unplayed_games_total: row.get("unplayed_games_total"),
+        }).collect();
+    }
+    
+    if !data.achievement_history.is_empty() {
+        let times: Vec<DateTime<Utc>> = data.achievement_history.iter().map(|a| a.recorded_at).collect();
+        let rows = transaction.query(
+            r#"
+            SELECT id::bigint as id, recorded_at, total_achievements, unlocked_achievements, games_with_achievements, avg_completion_percent
+            FROM achievement_history
+            WHERE steam_id = $1 AND recorded_at = ANY($2)
+            "#,
+            &[&steam_id_int, &times]
+        ).await?;
+        stored.achievement_history = rows.into_iter().map(|row| overachiever_core::AchievementHistory {
+            id: row.get::<_, i64>("id"),
+            recorded_at: row.get("recorded_at"),
+            total_achievements: row.get("total_achievements"),
+            unlocked_achievements: row.get("unlocked_achievements"),
+            games_with_achievements: row.get("games_with_achievements"),
+            avg_completion_percent: row.get::<_, f64>("avg_completion_percent") as f32,
+        }).collect();
+    }
+    
+    Ok(stored)
+}
+
+/// Upload cloud sync data, merging it with what is already stored for the user.
+/// History is unioned, the earliest unlock wins and the newest game metadata wins.
+/// Also used for delta pushes, which are simply partial bundles: only the stored
+/// rows the bundle touches are read, inside the write transaction.
+/// With `dry_run` nothing is written and only the merge summary is returned.
+pub async fn upload_cloud_sync_data(pool: &Pool, data: &CloudSyncData, dry_run: bool) -> Result<SyncMergePreview, DbError> {
+    let mut client = pool.get().await?;
+    let steam_id_int: i64 = data.steam_id.parse().unwrap_or(0);
+    
+    // Use transaction for atomicity; returning early rolls it back
+    let transaction = client.transaction().await?;
+    
+    if !dry_run {
+        // Ensure user exists
+        transaction.execute(
+            "INSERT INTO users (steam_id, display_name) VALUES ($1, $2) ON CONFLICT (steam_id) DO NOTHING",
+            &[&steam_id_int, &format!("User {}", &data.steam_id[..8.min(data.steam_id.len())])]
+        ).await?;
+        
+        // Merges into the same user's data run one after another, so each one
+        // sees the rows the previous one wrote
+        transaction.execute(
+            "SELECT 1 FROM users WHERE steam_id = $1 FOR NO KEY UPDATE",
+            &[&steam_id_int]
+        ).await?;
+    }
     
-    // Insert games
-    for game in &data.games {
+    let existing = get_stored_sync_rows(&transaction, steam_id_int, data, !dry_run).await?;
+    let changes = existing.merge_changes(data);
+    if dry_run || changes.is_empty() {
+        return Ok(changes.preview);
+    }
+    
+    // Upsert new or newer games
+    for game in &changes.games {
         transaction.execute(
             r#"
             INSERT INTO user_games (steam_id, appid, name, playtime_forever, rtime_last_played, img_icon_url, added_at, achievements_total, achievements_unlocked, last_sync)
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (steam_id, appid) DO UPDATE SET
+                name = EXCLUDED.name,
+                playtime_forever = EXCLUDED.playtime_forever,
+                rtime_last_played = EXCLUDED.rtime_last_played,
+                img_icon_url = EXCLUDED.img_icon_url,
+                added_at = EXCLUDED.added_at,
+                achievements_total = EXCLUDED.achievements_total,
+                achievements_unlocked = EXCLUDED.achievements_unlocked,
+                last_sync = EXCLUDED.last_sync
             "#,
             &[
                 &steam_id_int,
@@ -921,15 +2267,15 @@ pub async fn upload_cloud_sync_data(pool: &Pool, data: &CloudSyncData) -> Result
         ).await?;
     }
     
-    // Insert achievements (lightweight - only sync user progress, not schema)
-    for ach in &data.achievements {
+    // Upsert changed achievements (lightweight - only sync user progress, not schema)
+    for ach in &changes.achievements {
         transaction.execute(
             r#"
             INSERT INTO user_achievements (steam_id, appid, apiname, achieved, unlocktime)
             VALUES ($1, $2, $3, $4, $5)
             ON CONFLICT (steam_id, appid, apiname) DO UPDATE SET
-                achieved = EXCLUDED.achieved,
-                unlocktime = EXCLUDED.unlocktime
+                achieved = user_achievements.achieved OR EXCLUDED.achieved,
+                unlocktime = LEAST(user_achievements.unlocktime, EXCLUDED.unlocktime)
             "#,
             &[
                 &steam_id_int,
@@ -941,16 +2287,16 @@ pub async fn upload_cloud_sync_data(pool: &Pool, data: &CloudSyncData) -> Result
         ).await?;
     }
     
-    // Insert run history
-    for rh in &data.run_history {
+    // Insert run history entries the server doesn't have yet
+    for rh in &changes.run_history {
         transaction.execute(
             "INSERT INTO run_history (steam_id, run_at, total_games, unplayed_games, unplayed_games_total) VALUES ($1, $2, $3, $4, $5)",
             &[&steam_id_int, &rh.run_at, &rh.total_games, &rh.unplayed_games, &rh.unplayed_games_total]
         ).await?;
     }
     
-    // Insert achievement history
-    for ah in &data.achievement_history {
+    // Insert achievement history entries the server doesn't have yet
+    for ah in &changes.achievement_history {
         transaction.execute(
             "INSERT INTO achievement_history (steam_id, recorded_at, total_achievements, unlocked_achievements, games_with_achievements, avg_completion_percent) VALUES ($1, $2, $3, $4, $5, $6)",
             &[&steam_id_int, &ah.recorded_at, &ah.total_achievements, &ah.unlocked_achievements, &ah.games_with_achievements, &(ah.avg_completion_percent as f64)]
@@ -959,7 +2305,117 @@ pub async fn upload_cloud_sync_data(pool: &Pool, data: &CloudSyncData) -> Result
     
     transaction.commit().await?;
     
-    Ok(())
+    Ok(changes.preview)
+}
+
+/// Get all rows changed after `since` (or every row if `since` is None),
+/// along with the cursor to use for the next pull
+pub async fn get_sync_delta(pool: &Pool, steam_id: &str, since: Option<SyncCursor>) -> Result<CloudSyncDelta, DbError> {
+    let mut client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    let since = since.unwrap_or(-1);
+    
+    // All four tables and the cursor come from one snapshot, so the cursor
+    // covers exactly the rows returned. Writers take the user's row lock
+    // (`begin_sync_write`), so no uncommitted row has a lower version.
+    let transaction = client
+        .build_transaction()
+        .isolation_level(IsolationLevel::RepeatableRead)
+        .read_only(true)
+        .start()
+        .await?;
+    
+    let mut delta = CloudSyncDelta::new(steam_id);
+    let mut cursor = since.max(0);
+    
+    let rows = transaction.query(
+        r#"
+        SELECT appid, name, playtime_forever, rtime_last_played, img_icon_url,
+               added_at, achievements_total, achievements_unlocked, last_sync, sync_version
+        FROM user_games
+        WHERE steam_id = $1 AND sync_version > $2
+        "#,
+        &[&steam_id_int, &since]
+    ).await?;
+    for row in rows {
+        cursor = cursor.max(row.get("sync_version"));
+        delta.games.push(Game {
+            appid: row.get::<_, i64>("appid") as u64,
+            name: row.get("name"),
+            playtime_forever: row.get::<_, i32>("playtime_forever") as u32,
+            rtime_last_played: row.get::<_, Option<i32>>("rtime_last_played").map(|t| t as u32),
+            img_icon_url: row.get("img_icon_url"),
+            added_at: row.get::<_, Option<DateTime<Utc>>>("added_at").unwrap_or_else(Utc::now),
+            achievements_total: row.get("achievements_total"),
+            achievements_unlocked: row.get("achievements_unlocked"),
+            last_achievement_scrape: row.get("last_sync"),
+        });
+    }
+    
+    let rows = transaction.query(
+        r#"
+        SELECT appid, apiname, achieved, unlocktime, sync_version
+        FROM user_achievements
+        WHERE steam_id = $1 AND sync_version > $2
+        "#,
+        &[&steam_id_int, &since]
+    ).await?;
+    for row in rows {
+        cursor = cursor.max(row.get("sync_version"));
+        delta.achievements.push(SyncAchievement {
+            appid: row.get::<_, i64>("appid") as u64,
+            apiname: row.get("apiname"),
+            achieved: row.get("achieved"),
+            unlocktime: row.get("unlocktime"),
+        });
+    }
+    
+    let rows = transaction.query(
+        r#"
+        SELECT id::bigint as id, run_at, total_games, COALESCE(unplayed_games, 0) as unplayed_games,
+               COALESCE(unplayed_games_total, 0) as unplayed_games_total, sync_version
+        FROM run_history
+        WHERE steam_id = $1 AND sync_version > $2
+        ORDER BY run_at
+        "#,
+        &[&steam_id_int, &since]
+    ).await?;
+    for row in rows {
+        cursor = cursor.max(row.get("sync_version"));
+        delta.run_history.push(overachiever_core::RunHistory {
+            id: row.get::<_, i64>("id"),
+            run_at: row.get("run_at"),
+            total_games: row.get("total_games"),
+            unplayed_games: row.get("unplayed_games"),
+            unplayed_games_total: row.get("unplayed_games_total"),
+        });
+    }
+    
+    let rows = transaction.query(
+        r#"
+        SELECT id::bigint as id, recorded_at, total_achievements, unlocked_achievements,
+               games_with_achievements, avg_completion_percent, sync_version
+        FROM achievement_history
+        WHERE steam_id = $1 AND sync_version > $2
+        ORDER BY recorded_at
+        "#,
+        &[&steam_id_int, &since]
+    ).await?;
+    for row in rows {
+        cursor = cursor.max(row.get("sync_version"));
+        delta.achievement_history.push(overachiever_core::AchievementHistory {
+            id: row.get::<_, i64>("id"),
+            recorded_at: row.get("recorded_at"),
+            total_achievements: row.get("total_achievements"),
+            unlocked_achievements: row.get("unlocked_achievements"),
+            games_with_achievements: row.get("games_with_achievements"),
+            avg_completion_percent: row.get::<_, f64>("avg_completion_percent") as f32,
+        });
+    }
+    
+    transaction.commit().await?;
+    delta.cursor = Some(cursor);
+    Ok(delta)
 }
 
 /// Delete all cloud data for a user
@@ -974,6 +2430,81 @@ pub async fn delete_cloud_sync_data(pool: &Pool, steam_id: &str) -> Result<(), D
     client.execute("DELETE FROM achievement_history WHERE steam_id = $1", &[&steam_id_int]).await?;
     client.execute("DELETE FROM achievement_ratings WHERE steam_id = $1", &[&steam_id_int]).await?;
     client.execute("DELETE FROM game_ratings WHERE steam_id = $1", &[&steam_id_int]).await?;
+    client.execute("DELETE FROM achievement_comments WHERE steam_id = $1", &[&steam_id_int]).await?;
+    client.execute("DELETE FROM achievement_tip_votes WHERE steam_id = $1", &[&steam_id_int]).await?;
+    client.execute("DELETE FROM achievement_tips WHERE steam_id = $1", &[&steam_id_int]).await?;
+    client.execute("DELETE FROM content_reports WHERE reporter_steam_id = $1", &[&steam_id_int]).await?;
+    client.execute("DELETE FROM encrypted_sync_blobs WHERE steam_id = $1", &[&steam_id_int]).await?;
+    
+    Ok(())
+}
+
+/// Get the user's encrypted backup, if they have one
+pub async fn get_encrypted_sync_blob(pool: &Pool, steam_id: &str) -> Result<Option<EncryptedSyncBlob>, DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    let row = client.query_opt(
+        r#"
+        SELECT version, salt, key_check, nonce, ciphertext, last_sync
+        FROM encrypted_sync_blobs
+        WHERE steam_id = $1
+        "#,
+        &[&steam_id_int]
+    ).await?;
+    
+    Ok(row.map(|row| EncryptedSyncBlob {
+        version: row.get::<_, i32>("version") as u32,
+        salt: row.get("salt"),
+        key_check: row.get("key_check"),
+        nonce: row.get("nonce"),
+        ciphertext: row.get("ciphertext"),
+        last_sync: row.get("last_sync"),
+    }))
+}
+
+/// Store the user's encrypted backup, replacing the previous one. The backup
+/// holds the history from now on, so the plaintext history is deleted with it.
+pub async fn store_encrypted_sync_blob(pool: &Pool, steam_id: &str, blob: &EncryptedSyncBlob) -> Result<(), DbError> {
+    let mut client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    let transaction = client.transaction().await?;
+    
+    // Ensure user exists
+    transaction.execute(
+        "INSERT INTO users (steam_id, display_name) VALUES ($1, $2) ON CONFLICT (steam_id) DO NOTHING",
+        &[&steam_id_int, &format!("User {}", &steam_id[..8.min(steam_id.len())])]
+    ).await?;
+    
+    transaction.execute(
+        r#"
+        INSERT INTO encrypted_sync_blobs (steam_id, version, salt, key_check, nonce, ciphertext, last_sync, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+        ON CONFLICT (steam_id) DO UPDATE SET
+            version = EXCLUDED.version,
+            salt = EXCLUDED.salt,
+            key_check = EXCLUDED.key_check,
+            nonce = EXCLUDED.nonce,
+            ciphertext = EXCLUDED.ciphertext,
+            last_sync = EXCLUDED.last_sync,
+            updated_at = NOW()
+        "#,
+        &[&steam_id_int, &(blob.version as i32), &blob.salt, &blob.key_check, &blob.nonce, &blob.ciphertext, &blob.last_sync]
+    ).await?;
+    
+    transaction.execute("DELETE FROM run_history WHERE steam_id = $1", &[&steam_id_int]).await?;
+    transaction.execute("DELETE FROM achievement_history WHERE steam_id = $1", &[&steam_id_int]).await?;
+    
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Delete the user's encrypted backup, leaving the plaintext tables alone
+pub async fn delete_encrypted_sync_blob(pool: &Pool, steam_id: &str) -> Result<(), DbError> {
+    let client = pool.get().await?;
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    
+    client.execute("DELETE FROM encrypted_sync_blobs WHERE steam_id = $1", &[&steam_id_int]).await?;
     
     Ok(())
 }
@@ -0,0 +1,176 @@
+//! Overachiever Backend Server
+//! 
+//! Provides:
+//! - WebSocket API for real-time sync
+//! - REST API for initial data load, described by an OpenAPI document
+//! - Steam API proxy for WASM clients
+//! - PostgreSQL (or single-file SQLite) storage for user data
+//!
+//! The `overachiever-server` binary connects the database and serves [`app`];
+//! the integration tests serve it on an in-memory SQLite database.
+
+pub mod config;
+pub mod db;
+pub mod migrations;
+mod steam_api;
+mod ws_handler;
+mod auth;
+mod routes;
+mod upload_sessions;
+mod sessions;
+mod rarity;
+mod moderation;
+mod pagination;
+mod openapi;
+pub mod repository;
+pub mod sqlite;
+
+use axum::{
+    routing::get,
+    Json, Router,
+};
+use tower_http::cors::{AllowOrigin, CorsLayer, Any};
+use tower_http::trace::TraceLayer;
+use utoipa::OpenApi;
+use utoipa_axum::{router::{OpenApiRouter, UtoipaMethodRouterExt}, routes};
+use axum::extract::DefaultBodyLimit;
+use std::sync::Arc;
+
+use db::DbError;
+use repository::Repository;
+
+pub struct AppState {
+    pub db: Arc<dyn Repository>,
+    pub jwt_secret: String,
+    pub steam_api_key: Option<String>,
+    pub upload_sessions: upload_sessions::UploadSessions,
+    pub moderation: moderation::Moderation,
+    pub auth_codes: auth::AuthCodes,
+    pub revoked_sessions: sessions::RevokedSessions,
+    pub auth: config::AuthConfig,
+    pub upload_body_limit: usize,
+}
+
+impl AppState {
+    /// State of a server with `config` on `db`. Access tokens of sessions
+    /// revoked before a restart are still rejected.
+    pub async fn new(db: Arc<dyn Repository>, config: &config::ServerConfig) -> Result<Self, DbError> {
+        let moderation = moderation::Moderation::from_config(&config.moderation);
+        tracing::info!("Word filter has {} entries", moderation.word_filter.len());
+        let revoked_sessions = sessions::RevokedSessions::load(db.as_ref()).await?;
+        tracing::info!("{} recently revoked sessions", revoked_sessions.len());
+        Ok(Self {
+            db,
+            jwt_secret: config.jwt_secret().to_string(),
+            steam_api_key: config.steam_api_key.clone(),
+            upload_sessions: upload_sessions::UploadSessions::default(),
+            moderation,
+            auth_codes: auth::AuthCodes::default(),
+            revoked_sessions,
+            auth: config.auth.clone(),
+            upload_body_limit: config.upload_body_limit,
+        })
+    }
+}
+
+/// Browsers may call the API from the configured origins, or from anywhere if none are set
+fn cors_layer(origins: &[String]) -> CorsLayer {
+    let layer = CorsLayer::new()
+        .allow_methods(Any)
+        .allow_headers(Any);
+    if origins.is_empty() || origins.iter().any(|o| o == "*") {
+        layer.allow_origin(Any)
+    } else {
+        layer.allow_origin(AllowOrigin::list(origins.iter().filter_map(|o| o.parse().ok())))
+    }
+}
+
+/// The REST routes and the OpenAPI document describing them. Routes are
+/// collected into the document as they are added; routes with their own
+/// body limit layer it per route.
+pub fn rest_api(config: &config::ServerConfig) -> (Router<Arc<AppState>>, utoipa::openapi::OpenApi) {
+    OpenApiRouter::with_openapi(openapi::ApiDoc::openapi())
+        // REST API
+        .routes(routes!(routes::get_games))
+        .routes(routes!(routes::get_achievements))
+        .routes(routes!(routes::get_run_history))
+        .routes(routes!(routes::get_achievement_history))
+        .routes(routes!(routes::get_log))
+        // Guest links (public)
+        .routes(routes!(routes::get_guest_profile))
+        .routes(routes!(routes::get_guest_games))
+        .routes(routes!(routes::get_guest_achievements))
+        .routes(routes!(routes::get_guest_run_history))
+        .routes(routes!(routes::get_guest_achievement_history))
+        .routes(routes!(routes::get_guest_log))
+        // Community data (public)
+        .routes(routes!(routes::get_achievement_rarity))
+        .routes(routes!(routes::get_game_achievement_averages))
+        .routes(routes!(routes::get_achievement_averages))
+        .routes(routes!(routes::get_ratings))
+        .routes(routes!(routes::submit_rating))
+        .routes(routes!(routes::get_game_rating_averages))
+        // Achievement rating/comment endpoints
+        .routes(routes!(routes::submit_achievement_rating))
+        .routes(routes!(routes::get_user_achievement_ratings))
+        .routes(routes!(routes::submit_achievement_flags))
+        .routes(routes!(routes::get_achievement_flags))
+        .routes(routes!(routes::submit_achievement_comment))
+        .routes(routes!(routes::edit_achievement_comment, routes::delete_achievement_comment))
+        .routes(routes!(routes::get_achievement_comments))
+        .routes(routes!(routes::submit_achievement_tip))
+        .routes(routes!(routes::vote_achievement_tip))
+        .routes(routes!(routes::get_achievement_tips))
+        .routes(routes!(routes::report_content))
+        // Moderation (admins only)
+        .routes(routes!(routes::get_reports))
+        .routes(routes!(routes::dismiss_report))
+        .routes(routes!(routes::delete_content))
+        .routes(routes!(routes::set_content_hidden))
+        .routes(routes!(routes::set_user_banned))
+        // Cloud sync endpoints
+        .routes(routes!(routes::get_sync_status))
+        .routes(routes!(routes::download_sync_data))
+        .routes(routes!(routes::upload_sync_data)
+            .layer(DefaultBodyLimit::max(config.upload_body_limit)))
+        .routes(routes!(routes::delete_sync_data))
+        .routes(routes!(routes::pull_sync_delta, routes::push_sync_delta)
+            .layer(DefaultBodyLimit::max(config.upload_body_limit)))
+        .routes(routes!(routes::create_upload_session))
+        .routes(routes!(routes::get_upload_session))
+        .routes(routes!(routes::put_upload_chunk)
+            .layer(DefaultBodyLimit::max(upload_sessions::CHUNK_SIZE)))
+        .routes(routes!(routes::complete_upload_session))
+        .routes(routes!(routes::get_encrypted_sync, routes::store_encrypted_sync, routes::delete_encrypted_sync)
+            .layer(DefaultBodyLimit::max(config.upload_body_limit)))
+        .routes(routes!(routes::get_following))
+        .routes(routes!(routes::follow_user, routes::unfollow_user))
+        .routes(routes!(routes::get_feed))
+        .routes(routes!(routes::get_leaderboard))
+        .routes(routes!(routes::set_leaderboard_opt_in))
+        // Auth
+        .routes(routes!(auth::exchange_auth_code))
+        .routes(routes!(auth::refresh_session))
+        .routes(routes!(auth::revoke_session))
+        .routes(routes!(auth::logout_all_sessions))
+        .split_for_parts()
+}
+
+/// The whole server: REST API, OpenAPI document, WebSocket and Steam login
+pub fn app(state: Arc<AppState>, config: &config::ServerConfig) -> Router {
+    let (api, openapi) = rest_api(config);
+    Router::new()
+        // Health check
+        .route("/health", get(|| async { "OK" }))
+        // WebSocket endpoint
+        .route("/ws", get(ws_handler::ws_handler))
+        .merge(api)
+        .route("/api/openapi.json", get(move || async move { Json(openapi) }))
+        // Steam login (browser redirects)
+        .route("/auth/steam", get(auth::steam_login))
+        .route("/auth/steam/callback", get(auth::steam_callback))
+        .with_state(state)
+        .layer(DefaultBodyLimit::max(config.body_limit))
+        .layer(cors_layer(&config.cors_origins))
+        .layer(TraceLayer::new_for_http())
+}
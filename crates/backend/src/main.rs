@@ -1,33 +1,155 @@
-//! Overachiever Backend Server
-//! 
-//! Provides:
-//! - WebSocket API for real-time sync
-//! - REST API for initial data load
-//! - Steam API proxy for WASM clients
-//! - PostgreSQL storage for user data
+//! `overachiever-server`: loads the configuration, opens the database and
+//! serves [`overachiever_backend::app`]
 
-mod db;
-mod steam_api;
-mod ws_handler;
-mod auth;
-mod routes;
-
-use axum::{
-    routing::{get, post, delete},
-    Router,
-};
 use deadpool_postgres::{Config, Runtime, Pool};
 use tokio_postgres::NoTls;
-use tower_http::cors::{CorsLayer, Any};
-use tower_http::trace::TraceLayer;
-use axum::extract::DefaultBodyLimit;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use std::sync::Arc;
+use std::time::Duration;
+
+use overachiever_backend::{config, migrations, repository, sqlite, AppState};
+use overachiever_backend::repository::Repository;
+
+/// How often the cached leaderboard totals are recomputed
+const LEADERBOARD_REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// First wait between database connection attempts; doubles up to the configured maximum
+const DB_CONNECT_INITIAL_DELAY: Duration = Duration::from_secs(1);
+
+/// Log a startup failure and exit
+fn fatal(message: impl std::fmt::Display) -> ! {
+    tracing::error!("{}", message);
+    std::process::exit(1);
+}
+
+/// Create the pool and wait until the database accepts connections
+async fn connect_database(config: &config::DatabaseConfig) -> Result<Pool, String> {
+    let mut cfg = Config::new();
+    cfg.host = config.host.clone();
+    cfg.port = config.port;
+    cfg.dbname = config.name.clone();
+    cfg.user = config.user.clone();
+    cfg.password = config.password.clone();
+    
+    let db_pool = cfg.create_pool(Some(Runtime::Tokio1), NoTls)
+        .map_err(|e| format!("Failed to create database pool: {}", e))?;
+    
+    let mut delay = DB_CONNECT_INITIAL_DELAY;
+    let mut attempt = 1;
+    loop {
+        match db_pool.get().await {
+            Ok(_) => return Ok(db_pool),
+            Err(e) if config.connect_attempts == 0 || attempt < config.connect_attempts => {
+                tracing::warn!("Failed to connect to database (attempt {}): {}; retrying in {}s", attempt, e, delay.as_secs());
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(config.connect_retry_max());
+                attempt += 1;
+            }
+            Err(e) => return Err(format!("Failed to connect to database after {} attempts: {}", attempt, e)),
+        }
+    }
+}
+
+/// `overachiever-server migrate ...`
+async fn run_migrate_command(db_pool: &Pool, command: config::Command) -> Result<(), migrations::MigrationError> {
+    match command {
+        config::Command::Serve => {}
+        config::Command::Migrate => {
+            let applied = migrations::run(db_pool).await?;
+            for migration in &applied {
+                println!("Applied {}_{}", migration.version, migration.name);
+            }
+            println!("{} migrations applied, schema is up to date", applied.len());
+        }
+        config::Command::MigrateStatus => {
+            let (statuses, unknown) = migrations::status(db_pool).await?;
+            for status in &statuses {
+                let applied = status.applied_at
+                    .map(|at| format!("applied {}", at.format("%Y-%m-%d %H:%M:%S UTC")))
+                    .unwrap_or_else(|| "pending".to_string());
+                println!("{}_{}  {}", status.migration.version, status.migration.name, applied);
+            }
+            for version in &unknown {
+                println!("{}  applied, unknown to this server", version);
+            }
+        }
+        config::Command::MigrateBaseline(version) => {
+            let recorded = migrations::baseline(db_pool, version).await?;
+            println!("Recorded {} migrations up to {} as applied", recorded, version);
+        }
+    }
+    Ok(())
+}
+
+/// Connect to PostgreSQL and bring the schema up to date, or run a migrate
+/// command and return `None`
+async fn open_postgres(config: &config::DatabaseConfig, command: config::Command) -> Option<Arc<dyn Repository>> {
+    let db_pool = connect_database(config).await
+        .unwrap_or_else(|e| fatal(e));
+    tracing::info!("Connected to database");
+    
+    if command != config::Command::Serve {
+        if let Err(e) = run_migrate_command(&db_pool, command).await {
+            fatal(e);
+        }
+        return None;
+    }
+    
+    // Bring the schema up to date before serving
+    if config.auto_migrate {
+        match migrations::run(&db_pool).await {
+            Ok(applied) => {
+                for migration in &applied {
+                    tracing::info!("Applied migration {}_{}", migration.version, migration.name);
+                }
+            }
+            Err(e) => fatal(e),
+        }
+    } else if let Err(e) = migrations::check(&db_pool).await {
+        fatal(e);
+    }
+    Some(Arc::new(repository::PostgresRepository::new(db_pool)))
+}
 
-pub struct AppState {
-    pub db_pool: Pool,
-    pub jwt_secret: String,
-    pub steam_api_key: Option<String>,
+/// Open the SQLite database file and bring its schema up to date, or run a
+/// migrate command and return `None`
+fn open_sqlite(config: &config::DatabaseConfig, command: config::Command) -> Option<Arc<dyn Repository>> {
+    let db = sqlite::SqliteRepository::open(&config.sqlite_path)
+        .unwrap_or_else(|e| fatal(format!("Failed to open {}: {}", config.sqlite_path.display(), e)));
+    tracing::info!("Opened SQLite database {}", config.sqlite_path.display());
+    
+    let migrate = match command {
+        config::Command::Serve => config.auto_migrate,
+        config::Command::Migrate => true,
+        config::Command::MigrateStatus => {
+            match db.schema_version() {
+                Ok(version) => println!("Schema version {} of {}", version, sqlite::SCHEMA_VERSION),
+                Err(e) => fatal(e),
+            }
+            return None;
+        }
+        config::Command::MigrateBaseline(_) => {
+            fatal("migrate baseline only applies to PostgreSQL; SQLite databases always record their schema version")
+        }
+    };
+    
+    if migrate {
+        match db.migrate() {
+            Ok(applied) => {
+                for version in &applied {
+                    tracing::info!("Applied SQLite migration {}", version);
+                }
+            }
+            Err(e) => fatal(e),
+        }
+    } else if let Err(e) = db.check_schema() {
+        fatal(e);
+    }
+    if command == config::Command::Migrate {
+        println!("SQLite schema is up to date (version {})", sqlite::SCHEMA_VERSION);
+        return None;
+    }
+    Some(Arc::new(db))
 }
 
 #[tokio::main]
@@ -38,76 +160,80 @@ async fn main() {
     // Initialize tracing
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::try_from_default_env()
-            .unwrap_or_else(|_| "overachiever_backend=debug,tower_http=debug".into()))
+            .unwrap_or_else(|_| "overachiever_server=debug,overachiever_backend=debug,tower_http=debug".into()))
         .with(tracing_subscriber::fmt::layer())
         .init();
     
-    // Database connection pool
-    let mut cfg = Config::new();
-    cfg.host = std::env::var("DB_HOST").ok();
-    cfg.port = std::env::var("DB_PORT").ok().and_then(|p| p.parse().ok());
-    cfg.dbname = std::env::var("DB_NAME").ok();
-    cfg.user = std::env::var("DB_USER").ok();
-    cfg.password = std::env::var("DB_PASSWORD").ok();
+    let args = match config::CliArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+    let config = config::ServerConfig::load(args.config_path.as_deref())
+        .unwrap_or_else(|e| fatal(e));
     
-    let db_pool = cfg.create_pool(Some(Runtime::Tokio1), NoTls)
-        .expect("Failed to create database pool");
-    
-    // Test connection
-    let _ = db_pool.get().await.expect("Failed to connect to database");
-    tracing::info!("Connected to database");
+    let report = config.check();
+    if args.check_config {
+        println!("{}", config.to_masked_toml());
+        for warning in &report.warnings {
+            println!("# warning: {}", warning);
+        }
+        for error in &report.errors {
+            println!("# error: {}", error);
+        }
+        if report.is_ok() {
+            println!("# Configuration OK");
+            return;
+        }
+        std::process::exit(1);
+    }
+    for warning in &report.warnings {
+        tracing::warn!("{}", warning);
+    }
+    for error in &report.errors {
+        tracing::error!("{}", error);
+    }
+    if !report.is_ok() {
+        fatal(format!("Refusing to start with {} configuration errors", report.errors.len()));
+    }
+    tracing::info!("Using {:?} profile", config.profile);
     
-    let jwt_secret = std::env::var("JWT_SECRET")
-        .unwrap_or_else(|_| "dev-secret-change-in-production".to_string());
+    let db = match config.database.backend {
+        config::DatabaseBackend::Postgres => open_postgres(&config.database, args.command).await,
+        config::DatabaseBackend::Sqlite => open_sqlite(&config.database, args.command),
+    };
+    // A migrate command ran instead
+    let Some(db) = db else {
+        return;
+    };
     
-    let steam_api_key = std::env::var("STEAM_API_KEY").ok();
-    if steam_api_key.is_none() {
-        tracing::warn!("STEAM_API_KEY not set - Steam sync will be disabled");
-    }
+    let state = Arc::new(AppState::new(db, &config).await
+        .unwrap_or_else(|e| fatal(format!("Failed to load revoked sessions: {}", e))));
     
-    let state = Arc::new(AppState {
-        db_pool,
-        jwt_secret,
-        steam_api_key,
+    // Refresh cached leaderboard totals in the background
+    let leaderboard_db = state.db.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(LEADERBOARD_REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            match leaderboard_db.refresh_leaderboard_stats().await {
+                Ok(()) => tracing::debug!("Leaderboards refreshed"),
+                Err(e) => tracing::warn!("Failed to refresh leaderboards: {}", e),
+            }
+        }
     });
     
-    // Build router
-    let app = Router::new()
-        // Health check
-        .route("/health", get(|| async { "OK" }))
-        // WebSocket endpoint
-        .route("/ws", get(ws_handler::ws_handler))
-        // REST API
-        .route("/api/games", get(routes::get_games))
-        .route("/api/games/{appid}/achievements", get(routes::get_achievements))
-        .route("/api/community/ratings/{appid}", get(routes::get_ratings))
-        .route("/api/community/ratings", post(routes::submit_rating))
-        // Achievement rating/comment endpoints
-        .route("/api/achievement/rating", post(routes::submit_achievement_rating))
-        .route("/api/achievement/ratings", get(routes::get_user_achievement_ratings))
-        .route("/api/achievement/comment", post(routes::submit_achievement_comment))
-        // Cloud sync endpoints
-        .route("/api/sync/status", get(routes::get_sync_status))
-        .route("/api/sync/download", get(routes::download_sync_data))
-        .route("/api/sync/upload", post(routes::upload_sync_data)
-            .layer(DefaultBodyLimit::max(routes::UPLOAD_BODY_LIMIT)))
-        .route("/api/sync/data", delete(routes::delete_sync_data))
-        // Auth
-        .route("/auth/steam", get(auth::steam_login))
-        .route("/auth/steam/callback", get(auth::steam_callback))
-        .with_state(state)
-        .layer(CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods(Any)
-            .allow_headers(Any))
-        .layer(TraceLayer::new_for_http());
+    let app = overachiever_backend::app(state, &config);
     
     // Start server
-    let addr = std::env::var("BIND_ADDRESS")
-        .unwrap_or_else(|_| "0.0.0.0:8080".to_string());
-    
+    let addr = &config.bind_address;
     tracing::info!("Starting server on {}", addr);
     
-    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    let listener = tokio::net::TcpListener::bind(addr).await
+        .unwrap_or_else(|e| fatal(format!("Failed to bind {}: {}", addr, e)));
+    if let Err(e) = axum::serve(listener, app).await {
+        fatal(format!("Server error: {}", e));
+    }
 }
@@ -0,0 +1,300 @@
+//! Embedded schema migrations
+//!
+//! The SQL files in `migrations/` are compiled into the server. Applied
+//! versions are recorded in `schema_migrations` with a checksum of their SQL.
+//! Pending migrations run oldest first, each in its own transaction together
+//! with its record, at startup or with `overachiever-server migrate`.
+//!
+//! The server refuses to start when the database has migrations this binary
+//! doesn't know (it is older than the schema) or when an applied migration's
+//! SQL has changed since. Databases set up by applying the files by hand have
+//! no records yet; mark what was applied with
+//! `overachiever-server migrate baseline <version>`.
+
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use crate::db::DbError;
+
+/// Advisory lock held by the transaction applying a migration, so servers
+/// starting at the same time don't apply one twice
+const MIGRATION_LOCK_KEY: i64 = 0x6f76_6572_6163_6869;
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+impl Migration {
+    pub fn checksum(&self) -> String {
+        format!("{:x}", Sha256::digest(self.sql.as_bytes()))
+    }
+}
+
+macro_rules! migration {
+    ($version:literal, $name:literal) => {
+        Migration {
+            version: $version,
+            name: $name,
+            sql: include_str!(concat!("../migrations/", $version, "_", $name, ".sql")),
+        }
+    };
+}
+
+/// Every file in `migrations/`, oldest first. New files must be added here.
+pub const MIGRATIONS: &[Migration] = &[
+    migration!(20241219000001, "initial"),
+    migration!(20241219000002, "history_tables"),
+    migration!(20241220000001, "achievement_ratings"),
+    migration!(20241222000001, "run_history_unplayed"),
+    migration!(20241224000001, "run_history_unplayed_total"),
+    migration!(20241225000001, "user_short_id"),
+    migration!(20241226000001, "sync_versions"),
+    migration!(20241227000001, "encrypted_sync"),
+    migration!(20241228000001, "follows"),
+    migration!(20241229000001, "leaderboards"),
+    migration!(20241230000001, "achievement_rarity"),
+    migration!(20241231000001, "achievement_comments"),
+    migration!(20250101000001, "tip_votes"),
+    migration!(20250102000001, "achievement_flags"),
+    migration!(20250103000001, "moderation"),
+    migration!(20250104000001, "sessions"),
+];
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Db(DbError),
+    /// Versions applied to the database that this binary doesn't have
+    Unknown(Vec<i64>),
+    /// Applied migrations whose SQL differs from this binary's
+    Modified(Vec<i64>),
+    /// The schema exists but no migrations are recorded
+    NotBaselined,
+    /// Migrations not applied yet, with automatic migration turned off
+    Pending(Vec<i64>),
+    Failed { version: i64, name: &'static str, error: tokio_postgres::Error },
+    InvalidBaseline(i64),
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::Db(e) => write!(f, "{}", e),
+            MigrationError::Unknown(versions) => write!(
+                f,
+                "Database has migrations this server doesn't know: {:?}. Deploy a newer server.",
+                versions
+            ),
+            MigrationError::Modified(versions) => write!(
+                f,
+                "Applied migrations were modified since: {:?}. Add a new migration instead of editing one.",
+                versions
+            ),
+            MigrationError::NotBaselined => write!(
+                f,
+                "Database has tables but no recorded migrations. Record the last migration applied by hand with \
+                 `overachiever-server migrate baseline <version>`."
+            ),
+            MigrationError::Pending(versions) => write!(
+                f,
+                "{} pending migrations {:?}. Run `overachiever-server migrate`.",
+                versions.len(),
+                versions
+            ),
+            MigrationError::Failed { version, name, error } => {
+                write!(f, "Migration {}_{} failed: {}", version, name, error)
+            }
+            MigrationError::InvalidBaseline(version) => write!(f, "No migration has version {}", version),
+        }
+    }
+}
+
+impl From<DbError> for MigrationError {
+    fn from(e: DbError) -> Self {
+        MigrationError::Db(e)
+    }
+}
+
+impl From<deadpool_postgres::PoolError> for MigrationError {
+    fn from(e: deadpool_postgres::PoolError) -> Self {
+        MigrationError::Db(e.into())
+    }
+}
+
+impl From<tokio_postgres::Error> for MigrationError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        MigrationError::Db(e.into())
+    }
+}
+
+/// A migration of this binary and when it was applied
+pub struct MigrationStatus {
+    pub migration: &'static Migration,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+struct AppliedMigration {
+    checksum: String,
+    applied_at: DateTime<Utc>,
+}
+
+const CREATE_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS schema_migrations (
+        version BIGINT PRIMARY KEY,
+        name TEXT NOT NULL,
+        checksum TEXT NOT NULL,
+        applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )
+"#;
+
+/// Recorded migrations; none if the table doesn't exist yet
+async fn applied(pool: &Pool) -> Result<HashMap<i64, AppliedMigration>, MigrationError> {
+    let client = pool.get().await?;
+    let exists: bool = client
+        .query_one("SELECT to_regclass('schema_migrations') IS NOT NULL", &[])
+        .await?
+        .get(0);
+    if !exists {
+        return Ok(HashMap::new());
+    }
+
+    let rows = client
+        .query("SELECT version, checksum, applied_at FROM schema_migrations", &[])
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let applied = AppliedMigration { checksum: row.get("checksum"), applied_at: row.get("applied_at") };
+            (row.get("version"), applied)
+        })
+        .collect())
+}
+
+/// Whether tables exist that the first migration creates
+async fn has_schema(pool: &Pool) -> Result<bool, MigrationError> {
+    let client = pool.get().await?;
+    let row = client.query_one("SELECT to_regclass('users') IS NOT NULL", &[]).await?;
+    Ok(row.get(0))
+}
+
+/// Check the recorded migrations against this binary's and return the pending ones
+async fn pending_migrations(pool: &Pool) -> Result<Vec<&'static Migration>, MigrationError> {
+    let applied = applied(pool).await?;
+    if applied.is_empty() && has_schema(pool).await? {
+        return Err(MigrationError::NotBaselined);
+    }
+
+    let mut unknown: Vec<i64> = applied
+        .keys()
+        .filter(|version| !MIGRATIONS.iter().any(|m| m.version == **version))
+        .copied()
+        .collect();
+    if !unknown.is_empty() {
+        unknown.sort_unstable();
+        return Err(MigrationError::Unknown(unknown));
+    }
+
+    let modified: Vec<i64> = MIGRATIONS
+        .iter()
+        .filter(|m| applied.get(&m.version).is_some_and(|a| a.checksum != m.checksum()))
+        .map(|m| m.version)
+        .collect();
+    if !modified.is_empty() {
+        return Err(MigrationError::Modified(modified));
+    }
+
+    Ok(MIGRATIONS.iter().filter(|m| !applied.contains_key(&m.version)).collect())
+}
+
+/// Fail if any migrations are pending, without applying them
+pub async fn check(pool: &Pool) -> Result<(), MigrationError> {
+    let pending = pending_migrations(pool).await?;
+    if pending.is_empty() {
+        Ok(())
+    } else {
+        Err(MigrationError::Pending(pending.iter().map(|m| m.version).collect()))
+    }
+}
+
+/// Apply pending migrations and return the ones applied
+pub async fn run(pool: &Pool) -> Result<Vec<&'static Migration>, MigrationError> {
+    let pending = pending_migrations(pool).await?;
+    let mut client = pool.get().await?;
+    let mut applied = Vec::new();
+
+    for migration in pending {
+        let tx = client.transaction().await?;
+        tx.execute("SELECT pg_advisory_xact_lock($1)", &[&MIGRATION_LOCK_KEY]).await?;
+        tx.batch_execute(CREATE_TABLE).await?;
+        // Another server may have applied it while we waited for the lock
+        let done = tx
+            .query_opt("SELECT 1 FROM schema_migrations WHERE version = $1", &[&migration.version])
+            .await?
+            .is_some();
+        if done {
+            continue;
+        }
+
+        tx.batch_execute(migration.sql).await.map_err(|error| MigrationError::Failed {
+            version: migration.version,
+            name: migration.name,
+            error,
+        })?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version, name, checksum) VALUES ($1, $2, $3)",
+            &[&migration.version, &migration.name, &migration.checksum()],
+        )
+        .await?;
+        tx.commit().await?;
+        applied.push(migration);
+    }
+
+    Ok(applied)
+}
+
+/// Record every migration up to `version` as applied without running it.
+/// Returns how many were recorded.
+pub async fn baseline(pool: &Pool, version: i64) -> Result<u64, MigrationError> {
+    if !MIGRATIONS.iter().any(|m| m.version == version) {
+        return Err(MigrationError::InvalidBaseline(version));
+    }
+
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+    tx.execute("SELECT pg_advisory_xact_lock($1)", &[&MIGRATION_LOCK_KEY]).await?;
+    tx.batch_execute(CREATE_TABLE).await?;
+    let mut recorded = 0;
+    for migration in MIGRATIONS.iter().filter(|m| m.version <= version) {
+        recorded += tx
+            .execute(
+                "INSERT INTO schema_migrations (version, name, checksum) VALUES ($1, $2, $3) ON CONFLICT (version) DO NOTHING",
+                &[&migration.version, &migration.name, &migration.checksum()],
+            )
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(recorded)
+}
+
+/// This binary's migrations and when each was applied, and the versions
+/// applied to the database that this binary doesn't know
+pub async fn status(pool: &Pool) -> Result<(Vec<MigrationStatus>, Vec<i64>), MigrationError> {
+    let applied = applied(pool).await?;
+    let statuses = MIGRATIONS
+        .iter()
+        .map(|migration| MigrationStatus {
+            migration,
+            applied_at: applied.get(&migration.version).map(|a| a.applied_at),
+        })
+        .collect();
+    let mut unknown: Vec<i64> = applied
+        .keys()
+        .filter(|version| !MIGRATIONS.iter().any(|m| m.version == **version))
+        .copied()
+        .collect();
+    unknown.sort_unstable();
+    Ok((statuses, unknown))
+}
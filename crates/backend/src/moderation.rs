@@ -0,0 +1,154 @@
+//! Safeguards for community content (comments, tips, reviews and reports)
+//!
+//! Every submission passes [`Moderation::check_submission`], which rejects
+//! banned users, text containing a filtered word, and users who submitted too
+//! much recently. The word filter and rate limit come from
+//! [`ModerationConfig`]:
+//! - `WORD_FILTER`: comma-separated blocked words
+//! - `WORD_FILTER_FILE`: file with one blocked word per line
+//! - `SUBMISSION_RATE_LIMIT`: submissions allowed per window (default 10)
+//! - `SUBMISSION_RATE_WINDOW_SECS`: window length in seconds (default 60)
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::ModerationConfig;
+use crate::db::DbError;
+use crate::repository::Repository;
+
+/// Above this many tracked users, idle ones are dropped on the next check
+const MAX_TRACKED_USERS: usize = 10_000;
+
+#[derive(Debug)]
+pub enum ModerationError {
+    Banned,
+    RateLimited { retry_after: Duration },
+    BlockedWord,
+    Db(DbError),
+}
+
+impl std::fmt::Display for ModerationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModerationError::Banned => write!(f, "Your account is banned from posting"),
+            ModerationError::RateLimited { retry_after } => {
+                write!(f, "Too many submissions, try again in {} seconds", retry_after.as_secs().max(1))
+            }
+            ModerationError::BlockedWord => write!(f, "Text contains a blocked word"),
+            ModerationError::Db(e) => write!(f, "Failed to check submission: {}", e),
+        }
+    }
+}
+
+impl From<DbError> for ModerationError {
+    fn from(e: DbError) -> Self {
+        ModerationError::Db(e)
+    }
+}
+
+/// Case-insensitive list of blocked words
+#[derive(Default)]
+pub struct WordFilter {
+    words: HashSet<String>,
+}
+
+impl WordFilter {
+    pub fn new<I: IntoIterator<Item = S>, S: AsRef<str>>(words: I) -> Self {
+        let words = words.into_iter()
+            .map(|w| w.as_ref().trim().to_lowercase())
+            .filter(|w| !w.is_empty())
+            .collect();
+        Self { words }
+    }
+
+    /// Configured words and the words of the word filter file
+    pub fn from_config(config: &ModerationConfig) -> Self {
+        let mut words = config.word_filter.clone();
+        if let Some(path) = &config.word_filter_file {
+            match std::fs::read_to_string(path) {
+                Ok(contents) => words.extend(contents.lines().map(str::to_string)),
+                Err(e) => tracing::warn!("Failed to read WORD_FILTER_FILE {}: {}", path.display(), e),
+            }
+        }
+        Self::new(words)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Whether any whole word of the text is blocked
+    pub fn is_blocked(&self, text: &str) -> bool {
+        if self.words.is_empty() {
+            return false;
+        }
+        text.to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| self.words.contains(word))
+    }
+}
+
+/// Sliding-window limit of submissions per user
+pub struct RateLimiter {
+    limit: usize,
+    window: Duration,
+    submissions: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self { limit, window, submissions: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn from_config(config: &ModerationConfig) -> Self {
+        Self::new(config.submission_rate_limit, Duration::from_secs(config.submission_rate_window_secs))
+    }
+
+    /// Record a submission, or return how long the user has to wait
+    pub fn check(&self, steam_id: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut submissions = self.submissions.lock().unwrap();
+        if submissions.len() > MAX_TRACKED_USERS {
+            submissions.retain(|_, times| times.back().is_some_and(|t| now.duration_since(*t) < self.window));
+        }
+
+        let times = submissions.entry(steam_id.to_string()).or_default();
+        while times.front().is_some_and(|t| now.duration_since(*t) >= self.window) {
+            times.pop_front();
+        }
+        if times.len() >= self.limit {
+            let oldest = times.front().copied().unwrap_or(now);
+            return Err(self.window.saturating_sub(now.duration_since(oldest)));
+        }
+        times.push_back(now);
+        Ok(())
+    }
+}
+
+pub struct Moderation {
+    pub word_filter: WordFilter,
+    pub rate_limiter: RateLimiter,
+}
+
+impl Moderation {
+    pub fn from_config(config: &ModerationConfig) -> Self {
+        Self {
+            word_filter: WordFilter::from_config(config),
+            rate_limiter: RateLimiter::from_config(config),
+        }
+    }
+
+    /// Check a submission of one or more texts by a user. Only accepted
+    /// submissions count towards the rate limit.
+    pub async fn check_submission(&self, db: &dyn Repository, steam_id: &str, texts: &[&str]) -> Result<(), ModerationError> {
+        if db.is_banned(steam_id).await? {
+            return Err(ModerationError::Banned);
+        }
+        if texts.iter().any(|text| self.word_filter.is_blocked(text)) {
+            return Err(ModerationError::BlockedWord);
+        }
+        self.rate_limiter.check(steam_id)
+            .map_err(|retry_after| ModerationError::RateLimited { retry_after })
+    }
+}
@@ -0,0 +1,49 @@
+//! OpenAPI document of the REST API
+//!
+//! Paths and schemas are collected from the handlers' `#[utoipa::path]`
+//! annotations as the routes are added to the router; this only adds the
+//! document's info, tags and the bearer token scheme. Served at
+//! `/api/openapi.json`.
+
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Overachiever API",
+        description = "REST API of the Overachiever server. Errors are answered with an `ApiError` body.",
+        license(name = "MIT"),
+    ),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "library", description = "The authenticated user's games, achievements and history"),
+        (name = "guest", description = "A user's library through their guest link"),
+        (name = "community", description = "Rarity, difficulty and game ratings of all users"),
+        (name = "achievements", description = "Achievement ratings, flags, comments and tips"),
+        (name = "admin", description = "Moderation (admins only)"),
+        (name = "sync", description = "Cloud sync and encrypted backups"),
+        (name = "social", description = "Follows, feed and leaderboards"),
+        (name = "auth", description = "Login sessions"),
+    ),
+)]
+pub struct ApiDoc;
+
+/// Access tokens are sent as `Authorization: Bearer <token>`
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("Access token from /auth/token or /auth/refresh"))
+                    .build(),
+            ),
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Paging and conditional requests for the REST lists
+//!
+//! Pages are keyset paged: the cursor is the sort key of the last item sent
+//! rather than an offset, so rows added or removed between requests don't
+//! shift later pages. The library, the histories and the log are paged in
+//! the repository (`WHERE key > cursor ORDER BY key LIMIT n + 1`), and their
+//! `ETag` is derived from the list's [`ListVersion`] and the page asked for,
+//! so a request whose `If-None-Match` matches gets `304 Not Modified` before
+//! anything is loaded. A game's achievements are bounded by its schema, so
+//! they are cut from the whole list with [`paginate`] and tagged by body.
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use overachiever_core::api::PageQuery;
+use overachiever_core::Page;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+type ApiError = (StatusCode, Json<serde_json::Value>);
+
+/// Items per page when the request doesn't say
+pub const DEFAULT_PAGE_LIMIT: usize = 100;
+
+/// Most items a single page may hold
+pub const MAX_PAGE_LIMIT: usize = 1000;
+
+/// The user's lists that are paged in the repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserList {
+    Games,
+    RunHistory,
+    AchievementHistory,
+    Log,
+}
+
+/// Row count and highest sync version of a list. Adding or changing a row
+/// raises the version and removing one lowers the count, so any change to
+/// the list changes one of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListVersion {
+    pub rows: i64,
+    pub sync_version: i64,
+}
+
+/// Position in the log, which is ordered newest first with ties broken by
+/// game and API name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogCursor {
+    pub unlocktime: DateTime<Utc>,
+    pub appid: u64,
+    pub apiname: String,
+}
+
+impl LogCursor {
+    /// Cursor `<unix nanos>:<appid>:<apiname>`; API names may contain colons.
+    /// Nanoseconds, since SQLite keeps unlock times at full precision and a
+    /// truncated cursor would skip the rows tied with it.
+    pub fn parse(cursor: &str) -> Option<Self> {
+        let mut parts = cursor.splitn(3, ':');
+        let unlocktime = DateTime::from_timestamp_nanos(parts.next()?.parse().ok()?);
+        let appid = parts.next()?.parse().ok()?;
+        let apiname = parts.next()?.to_string();
+        Some(Self { unlocktime, appid, apiname })
+    }
+
+    pub fn format(&self) -> String {
+        let nanos = self.unlocktime.timestamp_nanos_opt().unwrap_or(i64::MAX);
+        format!("{}:{}:{}", nanos, self.appid, self.apiname)
+    }
+}
+
+/// Items per page asked for, within bounds
+pub fn page_limit(query: &PageQuery) -> usize {
+    query.limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT)
+}
+
+/// The query's cursor; a cursor that doesn't parse is a client error
+pub fn page_cursor<K>(query: &PageQuery, parse: impl Fn(&str) -> Option<K>) -> Result<Option<K>, ApiError> {
+    query.cursor.as_deref().map(|cursor| {
+        parse(cursor).ok_or_else(|| {
+            (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "Invalid cursor"})))
+        })
+    }).transpose()
+}
+
+/// Page from up to `limit + 1` items loaded after the cursor; the extra item
+/// only tells that there is a next page
+pub fn finish_page<T>(mut items: Vec<T>, limit: usize, cursor: impl Fn(&T) -> String) -> Page<T> {
+    let next_cursor = if items.len() > limit {
+        items.truncate(limit);
+        items.last().map(cursor)
+    } else {
+        None
+    };
+    Page { items, next_cursor }
+}
+
+/// `ETag` of a page of a list at `version`
+pub fn list_etag(list: UserList, version: ListVersion, query: &PageQuery) -> String {
+    let key = format!(
+        "{:?}:{}:{}:{}:{}",
+        list,
+        version.rows,
+        version.sync_version,
+        query.cursor.as_deref().unwrap_or(""),
+        page_limit(query),
+    );
+    format!("\"{:x}\"", Sha256::digest(key.as_bytes()))
+}
+
+/// `304 Not Modified` when the request's `If-None-Match` holds `etag`
+pub fn not_modified(headers: &HeaderMap, etag: &str) -> Option<Response> {
+    let matches = headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == etag || tag == "*");
+    matches.then(|| (StatusCode::NOT_MODIFIED, [(header::ETAG, etag_header(etag))]).into_response())
+}
+
+/// JSON response carrying `etag`
+pub fn json_with_etag<T: Serialize>(etag: &str, value: &T) -> Response {
+    match serde_json::to_vec(value) {
+        Ok(body) => (
+            [(header::ETAG, etag_header(etag)), (header::CONTENT_TYPE, HeaderValue::from_static("application/json"))],
+            body,
+        ).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to encode response: {}", e)})),
+        ).into_response(),
+    }
+}
+
+fn etag_header(etag: &str) -> HeaderValue {
+    HeaderValue::from_str(etag).expect("hex digest is a valid header value")
+}
+
+/// Sort `items` by `key` and return the page after the query's cursor.
+/// `parse` and `format` turn a key into a cursor and back; a cursor that
+/// doesn't parse is a client error.
+pub fn paginate<T, K: Ord>(
+    mut items: Vec<T>,
+    query: &PageQuery,
+    key: impl Fn(&T) -> K,
+    parse: impl Fn(&str) -> Option<K>,
+    format: impl Fn(&K) -> String,
+) -> Result<Page<T>, ApiError> {
+    items.sort_by_key(|item| key(item));
+
+    if let Some(after) = page_cursor(query, parse)? {
+        items.retain(|item| key(item) > after);
+    }
+
+    let limit = page_limit(query);
+    items.truncate(limit + 1);
+    Ok(finish_page(items, limit, |item| format(&key(item))))
+}
+
+/// JSON response with an `ETag` of its body, or `304 Not Modified` when the
+/// client already has this version
+pub fn etag_json<T: Serialize>(headers: &HeaderMap, value: &T) -> Response {
+    let body = match serde_json::to_vec(value) {
+        Ok(body) => body,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Failed to encode response: {}", e)})),
+            ).into_response();
+        }
+    };
+    let etag = format!("\"{:x}\"", Sha256::digest(&body));
+
+    if let Some(response) = not_modified(headers, &etag) {
+        return response;
+    }
+    (
+        [(header::ETAG, etag_header(&etag)), (header::CONTENT_TYPE, HeaderValue::from_static("application/json"))],
+        body,
+    ).into_response()
+}
@@ -0,0 +1,29 @@
+//! Achievement unlock rates
+//!
+//! The community share is aggregated from synced users' achievements on
+//! every request. Steam's global percentages are fetched from the Steam API
+//! and cached, since they change slowly.
+
+use overachiever_core::GameRarity;
+
+use crate::db::DbError;
+use crate::repository::Repository;
+
+/// Steam's global percentages are refetched after this many hours
+const STEAM_PERCENTAGES_MAX_AGE_HOURS: i64 = 24;
+
+/// Unlock rates for a game's achievements, refreshing Steam's percentages if stale
+pub async fn game_rarity(db: &dyn Repository, appid: u64) -> Result<GameRarity, DbError> {
+    if db.steam_percentages_stale(appid, STEAM_PERCENTAGES_MAX_AGE_HOURS).await? {
+        match crate::steam_api::fetch_global_achievement_percentages(appid).await {
+            Ok(percentages) => db.store_steam_percentages(appid, &percentages).await?,
+            // Serve whatever is cached and don't ask Steam again until it goes stale
+            Err(e) => {
+                tracing::warn!("Failed to fetch Steam achievement percentages for {}: {}", appid, e);
+                db.mark_steam_percentages_fetched(appid).await?;
+            }
+        }
+    }
+    
+    db.get_game_rarity(appid).await
+}
@@ -0,0 +1,584 @@
+//! Storage behind the server
+//!
+//! Routes and handlers go through [`Repository`] rather than a database
+//! driver. [`PostgresRepository`] is the default and runs the queries in
+//! [`crate::db`]; [`crate::sqlite::SqliteRepository`] keeps everything in a
+//! single SQLite file (or in memory) for small self-hosted servers and tests.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use overachiever_core::{Game, GameAchievement, GameRating, GameRatingAverage, AchievementTip, LogEntry, FeedEntry, Leaderboard, LeaderboardMetric, LeaderboardScope, GameRarity, AchievementDifficulty, AchievementFlags, AchievementFlagVotes, AchievementComment, ContentKind, ContentReport, CloudSyncData, CloudSyncStatus, SyncMergePreview, CloudSyncDelta, SyncCursor, EncryptedSyncBlob};
+
+use crate::db::{self, DbError};
+use crate::pagination::{ListVersion, LogCursor, UserList};
+
+#[async_trait]
+pub trait Repository: Send + Sync {
+    /// Get user info (steam_id, display_name, avatar_url) by short_id
+    async fn get_user_by_short_id(&self, short_id: &str) -> Result<Option<overachiever_core::UserProfile>, DbError>;
+
+    /// Get games for a user by their short_id
+    async fn get_user_games_by_short_id(&self, short_id: &str) -> Result<Option<Vec<Game>>, DbError>;
+
+    /// Get achievements for a game by short_id (for guest viewing)
+    async fn get_game_achievements_by_short_id(&self, short_id: &str, appid: u64) -> Result<Option<Vec<GameAchievement>>, DbError>;
+
+    /// Get history data for a user by short_id (for guest viewing)
+    async fn get_history_by_short_id(&self, short_id: &str) -> Result<Option<(Vec<overachiever_core::RunHistory>, Vec<overachiever_core::AchievementHistory>, Vec<overachiever_core::LogEntry>)>, DbError>;
+
+    async fn get_user_games(&self, steam_id: &str) -> Result<Vec<Game>, DbError>;
+
+    async fn get_game_achievements(&self, steam_id: &str, appid: u64) -> Result<Vec<GameAchievement>, DbError>;
+
+    /// Ratings of a game, newest first. `viewer` marks the requesting user's own rating.
+    async fn get_community_ratings(&self, appid: u64, viewer: Option<&str>) -> Result<Vec<GameRating>, DbError>;
+
+    /// Store a user's rating and review of a game, replacing their earlier one
+    async fn upsert_rating(&self, steam_id: &str, appid: u64, rating: u8, comment: Option<&str>) -> Result<(), DbError>;
+
+    /// Community rating averages of the rated games among some games
+    async fn get_game_rating_averages(&self, appids: &[u64]) -> Result<Vec<GameRatingAverage>, DbError>;
+
+    /// Tips for an achievement, highest score first. `viewer` marks the
+    /// requesting user's own tip and votes.
+    async fn get_achievement_tips(&self, appid: u64, apiname: &str, viewer: Option<&str>) -> Result<Vec<AchievementTip>, DbError>;
+
+    /// Store a user's tip for an achievement, replacing (editing) their earlier one
+    async fn upsert_achievement_tip(&self, steam_id: &str, appid: u64, apiname: &str, difficulty: u8, tip: &str) -> Result<(), DbError>;
+
+    /// Vote on another user's tip (1 or -1), or take the vote back (0). Returns
+    /// the tip's achievement, or None if there is no such tip by another user.
+    async fn vote_achievement_tip(&self, steam_id: &str, tip_id: i64, vote: i8) -> Result<Option<(u64, String)>, DbError>;
+
+    /// Create or update a user at login and return the stored profile. Without a
+    /// Steam profile (`display_name` and `avatar_url` None), existing users keep
+    /// their stored name and avatar and new users get a placeholder name.
+    async fn get_or_create_user(&self, steam_id: &str, display_name: Option<&str>, avatar_url: Option<&str>) -> Result<overachiever_core::UserProfile, DbError>;
+
+    /// Insert or update games for a user
+    async fn upsert_games(&self, steam_id: &str, games: &[overachiever_core::SteamGame]) -> Result<usize, DbError>;
+
+    /// Update achievement counts for a game
+    async fn update_game_achievements(&self, steam_id: &str, appid: u64, total: i32, unlocked: i32) -> Result<(), DbError>;
+
+    /// Store achievement schema
+    async fn upsert_achievement_schema(&self, appid: u64, schema: &overachiever_core::AchievementSchema) -> Result<(), DbError>;
+
+    /// Store user achievement progress
+    async fn upsert_user_achievement(&self, steam_id: &str, appid: u64, achievement: &overachiever_core::Achievement) -> Result<(), DbError>;
+
+    /// Get run history for a user
+    async fn get_run_history(&self, steam_id: &str) -> Result<Vec<overachiever_core::RunHistory>, DbError>;
+
+    /// Get achievement history for a user  
+    async fn get_achievement_history(&self, steam_id: &str) -> Result<Vec<overachiever_core::AchievementHistory>, DbError>;
+
+    /// Record a run history entry
+    async fn insert_run_history(&self, steam_id: &str, total_games: i32, unplayed_games_total: i32) -> Result<(), DbError>;
+
+    /// Update the unplayed_games count for the most recent run_history entry
+    async fn update_latest_run_history_unplayed(&self, steam_id: &str, unplayed_games: i32) -> Result<(), DbError>;
+
+    /// Update the total_games count for the most recent run_history entry
+    /// Used when recently played games add new games not in GetOwnedGames (e.g., some F2P games)
+    async fn update_run_history_total(&self, steam_id: &str, total_games: i32) -> Result<(), DbError>;
+
+    /// Backfill unplayed_games for run_history entries that still have 0
+    /// Only updates entries with unplayed_games = 0 (from before this feature was added)
+    async fn backfill_run_history_unplayed(&self, steam_id: &str, current_unplayed: i32) -> Result<(), DbError>;
+
+    /// Record achievement history snapshot
+    async fn insert_achievement_history(&self, steam_id: &str, total_achievements: i32, unlocked_achievements: i32, games_with_achievements: i32, avg_completion_percent: f32) -> Result<(), DbError>;
+
+    /// Get log entries (recently unlocked achievements) for a user
+    async fn get_log_entries(&self, steam_id: &str, limit: i32) -> Result<Vec<LogEntry>, DbError>;
+
+    /// Row count and highest sync version of one of the user's lists, for
+    /// tagging pages without loading them. The log's game and achievement
+    /// names are not versioned; they change with the user's own rows.
+    async fn get_list_version(&self, steam_id: &str, list: UserList) -> Result<ListVersion, DbError>;
+
+    /// Up to `limit` of the user's games by appid, after `after`
+    async fn get_user_games_page(&self, steam_id: &str, after: Option<u64>, limit: i32) -> Result<Vec<Game>, DbError>;
+
+    /// Up to `limit` run history entries by id, after `after`
+    async fn get_run_history_page(&self, steam_id: &str, after: Option<i64>, limit: i32) -> Result<Vec<overachiever_core::RunHistory>, DbError>;
+
+    /// Up to `limit` achievement history entries by id, after `after`
+    async fn get_achievement_history_page(&self, steam_id: &str, after: Option<i64>, limit: i32) -> Result<Vec<overachiever_core::AchievementHistory>, DbError>;
+
+    /// Up to `limit` log entries, newest first, after `after`
+    async fn get_log_page(&self, steam_id: &str, after: Option<&LogCursor>, limit: i32) -> Result<Vec<LogEntry>, DbError>;
+
+    /// Follow a user. Following an already followed user is a no-op.
+    async fn follow_user(&self, steam_id: &str, followed_steam_id: &str) -> Result<(), DbError>;
+
+    /// Stop following a user
+    async fn unfollow_user(&self, steam_id: &str, followed_steam_id: &str) -> Result<(), DbError>;
+
+    /// Get the users a user follows, sorted by display name
+    async fn get_following(&self, steam_id: &str) -> Result<Vec<overachiever_core::UserProfile>, DbError>;
+
+    /// Get recent achievements and first plays of followed users, newest first
+    async fn get_follow_feed(&self, steam_id: &str, limit: i32) -> Result<Vec<FeedEntry>, DbError>;
+
+    /// Whether Steam's global percentages for a game are missing or older than `max_age_hours`
+    async fn steam_percentages_stale(&self, appid: u64, max_age_hours: i64) -> Result<bool, DbError>;
+
+    /// Replace the cached Steam global percentages for a game
+    async fn store_steam_percentages(&self, appid: u64, percentages: &[(String, f32)]) -> Result<(), DbError>;
+
+    /// Record a failed fetch of Steam's global percentages, keeping the cached
+    /// ones, so the game isn't refetched before they go stale again
+    async fn mark_steam_percentages_fetched(&self, appid: u64) -> Result<(), DbError>;
+
+    /// Community unlock rates for a game's achievements, with cached Steam percentages.
+    /// Only owners whose achievements have been scanned count towards the share.
+    async fn get_game_rarity(&self, appid: u64) -> Result<GameRarity, DbError>;
+
+    /// Store a comment tagging one or more achievements
+    async fn insert_achievement_comment(&self, steam_id: &str, achievements: &[(u64, String)], comment: &str) -> Result<AchievementComment, DbError>;
+
+    /// Comments tagging an achievement, oldest first. `viewer` marks the
+    /// requesting user's own comments.
+    async fn get_achievement_comments(&self, appid: u64, apiname: &str, viewer: Option<&str>, limit: i64) -> Result<Vec<AchievementComment>, DbError>;
+
+    /// Change the text of a user's own comment. Returns None if the user has no
+    /// visible comment with this id.
+    async fn update_achievement_comment(&self, steam_id: &str, id: i64, comment: &str) -> Result<Option<AchievementComment>, DbError>;
+
+    /// Delete a user's own comment. Returns false if the user has no comment with this id.
+    async fn delete_achievement_comment(&self, steam_id: &str, id: i64) -> Result<bool, DbError>;
+
+    /// Community difficulty averages of the rated achievements of some games
+    async fn get_achievement_averages(&self, appids: &[u64]) -> Result<Vec<AchievementDifficulty>, DbError>;
+
+    /// Community flags of the achievements of some games. Only flags set by at
+    /// least [`FLAG_VOTE_THRESHOLD`] users are returned.
+    async fn get_achievement_flag_votes(&self, appids: &[u64]) -> Result<Vec<AchievementFlagVotes>, DbError>;
+
+    /// Recompute the cached leaderboard totals
+    async fn refresh_leaderboard_stats(&self) -> Result<(), DbError>;
+
+    /// Show or hide a user on global leaderboards
+    async fn set_leaderboard_opt_in(&self, steam_id: &str, enabled: bool) -> Result<(), DbError>;
+
+    /// Rank users by a metric from the cached totals. Returns the top `limit`
+    /// entries plus the requesting user's own entry if it ranks lower.
+    async fn get_leaderboard(&self, steam_id: &str, metric: LeaderboardMetric, scope: LeaderboardScope, limit: i32) -> Result<Leaderboard, DbError>;
+
+    /// Upsert an achievement rating for a user
+    async fn upsert_achievement_rating(&self, steam_id: &str, appid: u64, apiname: &str, rating: u8) -> Result<(), DbError>;
+
+    /// Set a user's flags for an achievement, keeping any difficulty rating
+    async fn upsert_achievement_flags(&self, steam_id: &str, appid: u64, apiname: &str, flags: AchievementFlags) -> Result<(), DbError>;
+
+    /// Get all achievement flags a user has set
+    async fn get_user_achievement_flags(&self, steam_id: &str) -> Result<Vec<(u64, String, AchievementFlags)>, DbError>;
+
+    /// Get all achievement ratings for a user
+    async fn get_user_achievement_ratings(&self, steam_id: &str) -> Result<Vec<(u64, String, u8)>, DbError>;
+
+    // ========================================================================
+    // Moderation
+    // ========================================================================
+
+    /// Whether a user may use the admin routes
+    async fn is_admin(&self, steam_id: &str) -> Result<bool, DbError>;
+
+    /// Whether a user is banned from submitting community content
+    async fn is_banned(&self, steam_id: &str) -> Result<bool, DbError>;
+
+    /// Ban or unban a user. Returns false if there is no such user.
+    async fn set_user_banned(&self, steam_id: &str, banned: bool, reason: Option<&str>) -> Result<bool, DbError>;
+
+    /// Report visible content of another user. Reporting the same item again
+    /// replaces the reason and reopens the report. Returns false if there is no
+    /// such visible content by another user.
+    async fn insert_content_report(&self, steam_id: &str, kind: ContentKind, content_id: i64, reason: &str) -> Result<bool, DbError>;
+
+    /// Open reports with the reported content, oldest first
+    async fn get_open_reports(&self) -> Result<Vec<ContentReport>, DbError>;
+
+    /// Close a report without acting on the content. Returns false if there is
+    /// no such open report.
+    async fn dismiss_report(&self, id: i64) -> Result<bool, DbError>;
+
+    /// Hide or show community content. Hiding resolves its open reports.
+    /// Returns false if there is no such content.
+    async fn set_content_hidden(&self, kind: ContentKind, content_id: i64, hidden: bool) -> Result<bool, DbError>;
+
+    /// Delete community content of any user and resolve its open reports.
+    /// Returns false if there is no such content.
+    async fn delete_content(&self, kind: ContentKind, content_id: i64) -> Result<bool, DbError>;
+
+    // ========================================================================
+    // Sessions
+    // ========================================================================
+
+    /// Start a login session and return its id
+    async fn create_session(&self, steam_id: &str, refresh_token_hash: &str, expires_at: DateTime<Utc>) -> Result<i64, DbError>;
+
+    /// Replace the refresh token of an active session and extend it. Returns the
+    /// session id and its user, or None if the token is unknown, expired or revoked.
+    async fn rotate_session(&self, refresh_token_hash: &str, new_refresh_token_hash: &str, expires_at: DateTime<Utc>) -> Result<Option<(i64, overachiever_core::UserProfile)>, DbError>;
+
+    /// Revoke the session a refresh token belongs to, either as its current
+    /// token or as the one it replaced. Returns the session id if one was revoked.
+    async fn revoke_session_by_token(&self, refresh_token_hash: &str) -> Result<Option<i64>, DbError>;
+
+    /// Revoke all active sessions of a user ("log out all devices")
+    async fn revoke_user_sessions(&self, steam_id: &str) -> Result<Vec<i64>, DbError>;
+
+    /// Ids of sessions revoked since `since`
+    async fn get_revoked_session_ids(&self, since: DateTime<Utc>) -> Result<Vec<i64>, DbError>;
+
+    // ========================================================================
+    // Cloud Sync
+    // ========================================================================
+
+    /// Get cloud sync status for a user
+    async fn get_cloud_sync_status(&self, steam_id: &str) -> Result<CloudSyncStatus, DbError>;
+
+    /// Get all user data for cloud download
+    async fn get_cloud_sync_data(&self, steam_id: &str) -> Result<CloudSyncData, DbError>;
+
+    /// Upload cloud sync data, merging it with what is already stored for the user.
+    /// History is unioned, the earliest unlock wins and the newest game metadata wins.
+    /// Also used for delta pushes, which are simply partial bundles; only the
+    /// stored rows the bundle touches are read.
+    /// With `dry_run` nothing is written and only the merge summary is returned.
+    async fn upload_cloud_sync_data(&self, data: &CloudSyncData, dry_run: bool) -> Result<SyncMergePreview, DbError>;
+
+    /// Get all rows changed after `since` (or every row if `since` is None),
+    /// along with the cursor to use for the next pull
+    async fn get_sync_delta(&self, steam_id: &str, since: Option<SyncCursor>) -> Result<CloudSyncDelta, DbError>;
+
+    /// Delete all cloud data for a user
+    async fn delete_cloud_sync_data(&self, steam_id: &str) -> Result<(), DbError>;
+
+    /// Get the user's encrypted backup, if they have one
+    async fn get_encrypted_sync_blob(&self, steam_id: &str) -> Result<Option<EncryptedSyncBlob>, DbError>;
+
+    /// Store the user's encrypted backup, replacing the previous one, and delete
+    /// the plaintext history the backup now holds
+    async fn store_encrypted_sync_blob(&self, steam_id: &str, blob: &EncryptedSyncBlob) -> Result<(), DbError>;
+
+    /// Delete the user's encrypted backup, leaving the plaintext tables alone
+    async fn delete_encrypted_sync_blob(&self, steam_id: &str) -> Result<(), DbError>;
+}
+
+/// PostgreSQL storage, with the schema managed by [`crate::migrations`]
+pub struct PostgresRepository {
+    pool: Pool,
+}
+
+impl PostgresRepository {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl Repository for PostgresRepository {
+    async fn get_user_by_short_id(&self, short_id: &str) -> Result<Option<overachiever_core::UserProfile>, DbError> {
+        db::get_user_by_short_id(&self.pool, short_id).await
+    }
+
+    async fn get_user_games_by_short_id(&self, short_id: &str) -> Result<Option<Vec<Game>>, DbError> {
+        db::get_user_games_by_short_id(&self.pool, short_id).await
+    }
+
+    async fn get_game_achievements_by_short_id(&self, short_id: &str, appid: u64) -> Result<Option<Vec<GameAchievement>>, DbError> {
+        db::get_game_achievements_by_short_id(&self.pool, short_id, appid).await
+    }
+
+    async fn get_history_by_short_id(&self, short_id: &str) -> Result<Option<(Vec<overachiever_core::RunHistory>, Vec<overachiever_core::AchievementHistory>, Vec<overachiever_core::LogEntry>)>, DbError> {
+        db::get_history_by_short_id(&self.pool, short_id).await
+    }
+
+    async fn get_user_games(&self, steam_id: &str) -> Result<Vec<Game>, DbError> {
+        db::get_user_games(&self.pool, steam_id).await
+    }
+
+    async fn get_game_achievements(&self, steam_id: &str, appid: u64) -> Result<Vec<GameAchievement>, DbError> {
+        db::get_game_achievements(&self.pool, steam_id, appid).await
+    }
+
+    async fn get_community_ratings(&self, appid: u64, viewer: Option<&str>) -> Result<Vec<GameRating>, DbError> {
+        db::get_community_ratings(&self.pool, appid, viewer).await
+    }
+
+    async fn upsert_rating(&self, steam_id: &str, appid: u64, rating: u8, comment: Option<&str>) -> Result<(), DbError> {
+        db::upsert_rating(&self.pool, steam_id, appid, rating, comment).await
+    }
+
+    async fn get_game_rating_averages(&self, appids: &[u64]) -> Result<Vec<GameRatingAverage>, DbError> {
+        db::get_game_rating_averages(&self.pool, appids).await
+    }
+
+    async fn get_achievement_tips(&self, appid: u64, apiname: &str, viewer: Option<&str>) -> Result<Vec<AchievementTip>, DbError> {
+        db::get_achievement_tips(&self.pool, appid, apiname, viewer).await
+    }
+
+    async fn upsert_achievement_tip(&self, steam_id: &str, appid: u64, apiname: &str, difficulty: u8, tip: &str) -> Result<(), DbError> {
+        db::upsert_achievement_tip(&self.pool, steam_id, appid, apiname, difficulty, tip).await
+    }
+
+    async fn vote_achievement_tip(&self, steam_id: &str, tip_id: i64, vote: i8) -> Result<Option<(u64, String)>, DbError> {
+        db::vote_achievement_tip(&self.pool, steam_id, tip_id, vote).await
+    }
+
+    async fn get_or_create_user(&self, steam_id: &str, display_name: Option<&str>, avatar_url: Option<&str>) -> Result<overachiever_core::UserProfile, DbError> {
+        db::get_or_create_user(&self.pool, steam_id, display_name, avatar_url).await
+    }
+
+    async fn upsert_games(&self, steam_id: &str, games: &[overachiever_core::SteamGame]) -> Result<usize, DbError> {
+        db::upsert_games(&self.pool, steam_id, games).await
+    }
+
+    async fn update_game_achievements(&self, steam_id: &str, appid: u64, total: i32, unlocked: i32) -> Result<(), DbError> {
+        db::update_game_achievements(&self.pool, steam_id, appid, total, unlocked).await
+    }
+
+    async fn upsert_achievement_schema(&self, appid: u64, schema: &overachiever_core::AchievementSchema) -> Result<(), DbError> {
+        db::upsert_achievement_schema(&self.pool, appid, schema).await
+    }
+
+    async fn upsert_user_achievement(&self, steam_id: &str, appid: u64, achievement: &overachiever_core::Achievement) -> Result<(), DbError> {
+        db::upsert_user_achievement(&self.pool, steam_id, appid, achievement).await
+    }
+
+    async fn get_run_history(&self, steam_id: &str) -> Result<Vec<overachiever_core::RunHistory>, DbError> {
+        db::get_run_history(&self.pool, steam_id).await
+    }
+
+    async fn get_achievement_history(&self, steam_id: &str) -> Result<Vec<overachiever_core::AchievementHistory>, DbError> {
+        db::get_achievement_history(&self.pool, steam_id).await
+    }
+
+    async fn insert_run_history(&self, steam_id: &str, total_games: i32, unplayed_games_total: i32) -> Result<(), DbError> {
+        db::insert_run_history(&self.pool, steam_id, total_games, unplayed_games_total).await
+    }
+
+    async fn update_latest_run_history_unplayed(&self, steam_id: &str, unplayed_games: i32) -> Result<(), DbError> {
+        db::update_latest_run_history_unplayed(&self.pool, steam_id, unplayed_games).await
+    }
+
+    async fn update_run_history_total(&self, steam_id: &str, total_games: i32) -> Result<(), DbError> {
+        db::update_run_history_total(&self.pool, steam_id, total_games).await
+    }
+
+    async fn backfill_run_history_unplayed(&self, steam_id: &str, current_unplayed: i32) -> Result<(), DbError> {
+        db::backfill_run_history_unplayed(&self.pool, steam_id, current_unplayed).await
+    }
+
+    async fn insert_achievement_history(&self, steam_id: &str, total_achievements: i32, unlocked_achievements: i32, games_with_achievements: i32, avg_completion_percent: f32) -> Result<(), DbError> {
+        db::insert_achievement_history(&self.pool, steam_id, total_achievements, unlocked_achievements, games_with_achievements, avg_completion_percent).await
+    }
+
+    async fn get_log_entries(&self, steam_id: &str, limit: i32) -> Result<Vec<LogEntry>, DbError> {
+        db::get_log_entries(&self.pool, steam_id, limit).await
+    }
+
+    async fn get_list_version(&self, steam_id: &str, list: UserList) -> Result<ListVersion, DbError> {
+        db::get_list_version(&self.pool, steam_id, list).await
+    }
+
+    async fn get_user_games_page(&self, steam_id: &str, after: Option<u64>, limit: i32) -> Result<Vec<Game>, DbError> {
+        db::get_user_games_page(&self.pool, steam_id, after, limit).await
+    }
+
+    async fn get_run_history_page(&self, steam_id: &str, after: Option<i64>, limit: i32) -> Result<Vec<overachiever_core::RunHistory>, DbError> {
+        db::get_run_history_page(&self.pool, steam_id, after, limit).await
+    }
+
+    async fn get_achievement_history_page(&self, steam_id: &str, after: Option<i64>, limit: i32) -> Result<Vec<overachiever_core::AchievementHistory>, DbError> {
+        db::get_achievement_history_page(&self.pool, steam_id, after, limit).await
+    }
+
+    async fn get_log_page(&self, steam_id: &str, after: Option<&LogCursor>, limit: i32) -> Result<Vec<LogEntry>, DbError> {
+        db::get_log_page(&self.pool, steam_id, after, limit).await
+    }
+
+    async fn follow_user(&self, steam_id: &str, followed_steam_id: &str) -> Result<(), DbError> {
+        db::follow_user(&self.pool, steam_id, followed_steam_id).await
+    }
+
+    async fn unfollow_user(&self, steam_id: &str, followed_steam_id: &str) -> Result<(), DbError> {
+        db::unfollow_user(&self.pool, steam_id, followed_steam_id).await
+    }
+
+    async fn get_following(&self, steam_id: &str) -> Result<Vec<overachiever_core::UserProfile>, DbError> {
+        db::get_following(&self.pool, steam_id).await
+    }
+
+    async fn get_follow_feed(&self, steam_id: &str, limit: i32) -> Result<Vec<FeedEntry>, DbError> {
+        db::get_follow_feed(&self.pool, steam_id, limit).await
+    }
+
+    async fn steam_percentages_stale(&self, appid: u64, max_age_hours: i64) -> Result<bool, DbError> {
+        db::steam_percentages_stale(&self.pool, appid, max_age_hours).await
+    }
+
+    async fn store_steam_percentages(&self, appid: u64, percentages: &[(String, f32)]) -> Result<(), DbError> {
+        db::store_steam_percentages(&self.pool, appid, percentages).await
+    }
+
+    async fn mark_steam_percentages_fetched(&self, appid: u64) -> Result<(), DbError> {
+        db::mark_steam_percentages_fetched(&self.pool, appid).await
+    }
+
+    async fn get_game_rarity(&self, appid: u64) -> Result<GameRarity, DbError> {
+        db::get_game_rarity(&self.pool, appid).await
+    }
+
+    async fn insert_achievement_comment(&self, steam_id: &str, achievements: &[(u64, String)], comment: &str) -> Result<AchievementComment, DbError> {
+        db::insert_achievement_comment(&self.pool, steam_id, achievements, comment).await
+    }
+
+    async fn get_achievement_comments(&self, appid: u64, apiname: &str, viewer: Option<&str>, limit: i64) -> Result<Vec<AchievementComment>, DbError> {
+        db::get_achievement_comments(&self.pool, appid, apiname, viewer, limit).await
+    }
+
+    async fn update_achievement_comment(&self, steam_id: &str, id: i64, comment: &str) -> Result<Option<AchievementComment>, DbError> {
+        db::update_achievement_comment(&self.pool, steam_id, id, comment).await
+    }
+
+    async fn delete_achievement_comment(&self, steam_id: &str, id: i64) -> Result<bool, DbError> {
+        db::delete_achievement_comment(&self.pool, steam_id, id).await
+    }
+
+    async fn get_achievement_averages(&self, appids: &[u64]) -> Result<Vec<AchievementDifficulty>, DbError> {
+        db::get_achievement_averages(&self.pool, appids).await
+    }
+
+    async fn get_achievement_flag_votes(&self, appids: &[u64]) -> Result<Vec<AchievementFlagVotes>, DbError> {
+        db::get_achievement_flag_votes(&self.pool, appids).await
+    }
+
+    async fn refresh_leaderboard_stats(&self) -> Result<(), DbError> {
+        db::refresh_leaderboard_stats(&self.pool).await
+    }
+
+    async fn set_leaderboard_opt_in(&self, steam_id: &str, enabled: bool) -> Result<(), DbError> {
+        db::set_leaderboard_opt_in(&self.pool, steam_id, enabled).await
+    }
+
+    async fn get_leaderboard(&self, steam_id: &str, metric: LeaderboardMetric, scope: LeaderboardScope, limit: i32) -> Result<Leaderboard, DbError> {
+        db::get_leaderboard(&self.pool, steam_id, metric, scope, limit).await
+    }
+
+    async fn upsert_achievement_rating(&self, steam_id: &str, appid: u64, apiname: &str, rating: u8) -> Result<(), DbError> {
+        db::upsert_achievement_rating(&self.pool, steam_id, appid, apiname, rating).await
+    }
+
+    async fn upsert_achievement_flags(&self, steam_id: &str, appid: u64, apiname: &str, flags: AchievementFlags) -> Result<(), DbError> {
+        db::upsert_achievement_flags(&self.pool, steam_id, appid, apiname, flags).await
+    }
+
+    async fn get_user_achievement_flags(&self, steam_id: &str) -> Result<Vec<(u64, String, AchievementFlags)>, DbError> {
+        db::get_user_achievement_flags(&self.pool, steam_id).await
+    }
+
+    async fn get_user_achievement_ratings(&self, steam_id: &str) -> Result<Vec<(u64, String, u8)>, DbError> {
+        db::get_user_achievement_ratings(&self.pool, steam_id).await
+    }
+
+    // ========================================================================
+    // Moderation
+    // ========================================================================
+
+    async fn is_admin(&self, steam_id: &str) -> Result<bool, DbError> {
+        db::is_admin(&self.pool, steam_id).await
+    }
+
+    async fn is_banned(&self, steam_id: &str) -> Result<bool, DbError> {
+        db::is_banned(&self.pool, steam_id).await
+    }
+
+    async fn set_user_banned(&self, steam_id: &str, banned: bool, reason: Option<&str>) -> Result<bool, DbError> {
+        db::set_user_banned(&self.pool, steam_id, banned, reason).await
+    }
+
+    async fn insert_content_report(&self, steam_id: &str, kind: ContentKind, content_id: i64, reason: &str) -> Result<bool, DbError> {
+        db::insert_content_report(&self.pool, steam_id, kind, content_id, reason).await
+    }
+
+    async fn get_open_reports(&self) -> Result<Vec<ContentReport>, DbError> {
+        db::get_open_reports(&self.pool).await
+    }
+
+    async fn dismiss_report(&self, id: i64) -> Result<bool, DbError> {
+        db::dismiss_report(&self.pool, id).await
+    }
+
+    async fn set_content_hidden(&self, kind: ContentKind, content_id: i64, hidden: bool) -> Result<bool, DbError> {
+        db::set_content_hidden(&self.pool, kind, content_id, hidden).await
+    }
+
+    async fn delete_content(&self, kind: ContentKind, content_id: i64) -> Result<bool, DbError> {
+        db::delete_content(&self.pool, kind, content_id).await
+    }
+
+    // ========================================================================
+    // Sessions
+    // ========================================================================
+
+    async fn create_session(&self, steam_id: &str, refresh_token_hash: &str, expires_at: DateTime<Utc>) -> Result<i64, DbError> {
+        db::create_session(&self.pool, steam_id, refresh_token_hash, expires_at).await
+    }
+
+    async fn rotate_session(&self, refresh_token_hash: &str, new_refresh_token_hash: &str, expires_at: DateTime<Utc>) -> Result<Option<(i64, overachiever_core::UserProfile)>, DbError> {
+        db::rotate_session(&self.pool, refresh_token_hash, new_refresh_token_hash, expires_at).await
+    }
+
+    async fn revoke_session_by_token(&self, refresh_token_hash: &str) -> Result<Option<i64>, DbError> {
+        db::revoke_session_by_token(&self.pool, refresh_token_hash).await
+    }
+
+    async fn revoke_user_sessions(&self, steam_id: &str) -> Result<Vec<i64>, DbError> {
+        db::revoke_user_sessions(&self.pool, steam_id).await
+    }
+
+    async fn get_revoked_session_ids(&self, since: DateTime<Utc>) -> Result<Vec<i64>, DbError> {
+        db::get_revoked_session_ids(&self.pool, since).await
+    }
+
+    // ========================================================================
+    // Cloud Sync
+    // ========================================================================
+
+    async fn get_cloud_sync_status(&self, steam_id: &str) -> Result<CloudSyncStatus, DbError> {
+        db::get_cloud_sync_status(&self.pool, steam_id).await
+    }
+
+    async fn get_cloud_sync_data(&self, steam_id: &str) -> Result<CloudSyncData, DbError> {
+        db::get_cloud_sync_data(&self.pool, steam_id).await
+    }
+
+    async fn upload_cloud_sync_data(&self, data: &CloudSyncData, dry_run: bool) -> Result<SyncMergePreview, DbError> {
+        db::upload_cloud_sync_data(&self.pool, data, dry_run).await
+    }
+
+    async fn get_sync_delta(&self, steam_id: &str, since: Option<SyncCursor>) -> Result<CloudSyncDelta, DbError> {
+        db::get_sync_delta(&self.pool, steam_id, since).await
+    }
+
+    async fn delete_cloud_sync_data(&self, steam_id: &str) -> Result<(), DbError> {
+        db::delete_cloud_sync_data(&self.pool, steam_id).await
+    }
+
+    async fn get_encrypted_sync_blob(&self, steam_id: &str) -> Result<Option<EncryptedSyncBlob>, DbError> {
+        db::get_encrypted_sync_blob(&self.pool, steam_id).await
+    }
+
+    async fn store_encrypted_sync_blob(&self, steam_id: &str, blob: &EncryptedSyncBlob) -> Result<(), DbError> {
+        db::store_encrypted_sync_blob(&self.pool, steam_id, blob).await
+    }
+
+    async fn delete_encrypted_sync_blob(&self, steam_id: &str) -> Result<(), DbError> {
+        db::delete_encrypted_sync_blob(&self.pool, steam_id).await
+    }
+}
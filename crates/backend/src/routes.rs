@@ -1,17 +1,29 @@
 //! REST API routes
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{StatusCode, HeaderMap},
+    response::Response,
     Json,
 };
 use std::sync::Arc;
-use overachiever_core::{Game, GameAchievement, GameRating};
+use overachiever_core::{Game, GameAchievement, CommunityGameRating, GameRatingAverage, GameRarity, AchievementDifficulty, AchievementFlagVotes, AchievementComment, AchievementTip, ContentKind, ContentReport, UserProfile, FeedEntry, LogEntry, Leaderboard, Page, RunHistory, AchievementHistory};
+use overachiever_core::api::{
+    AchievementCommentRequest, AchievementCommentResponse, ApiError, AchievementFlagsEntry, AchievementFlagsRequest,
+    AchievementQuery, AchievementRatingEntry, AchievementRatingRequest, AchievementRatingResponse, AppidsRequest,
+    BanRequest, EditCommentRequest, LeaderboardOptIn, LeaderboardQuery, PageQuery, PullDeltaQuery, PushDeltaQuery,
+    ReportRequest, SetHiddenRequest, StoreEncryptedQuery, SubmitRatingRequest, SubmitTipRequest, SuccessResponse,
+    SyncUploadResponse, UserAchievementRatingsResponse, VoteTipRequest,
+};
 use crate::AppState;
 use crate::auth::{verify_jwt, Claims};
+use crate::moderation::ModerationError;
+use crate::pagination::{
+    etag_json, finish_page, json_with_etag, list_etag, not_modified, page_cursor, page_limit, paginate, LogCursor, UserList,
+};
 
 /// Extract authenticated user from Authorization header
-fn extract_user(headers: &HeaderMap, jwt_secret: &str) -> Result<Claims, (StatusCode, Json<serde_json::Value>)> {
+pub(crate) fn extract_user(headers: &HeaderMap, state: &AppState) -> Result<Claims, (StatusCode, Json<serde_json::Value>)> {
     let auth_header = headers
         .get("Authorization")
         .and_then(|h| h.to_str().ok())
@@ -25,75 +37,680 @@ fn extract_user(headers: &HeaderMap, jwt_secret: &str) -> Result<Claims, (Status
             (StatusCode::UNAUTHORIZED, Json(serde_json::json!({"error": "Invalid Authorization header format"})))
         })?;
     
-    verify_jwt(token, jwt_secret).map_err(|e| {
-        (StatusCode::UNAUTHORIZED, Json(serde_json::json!({"error": format!("Invalid token: {}", e)})))
+    verify_jwt(token, &state.jwt_secret, &state.revoked_sessions).map_err(|e| {
+        (StatusCode::UNAUTHORIZED, Json(serde_json::json!({"error": e.to_string()})))
+    })
+}
+
+/// Extract an authenticated admin from Authorization header
+async fn extract_admin(state: &AppState, headers: &HeaderMap) -> Result<Claims, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(headers, state)?;
+    match state.db.is_admin(&claims.steam_id).await {
+        Ok(true) => Ok(claims),
+        Ok(false) => Err((StatusCode::FORBIDDEN, Json(serde_json::json!({"error": "Admins only"})))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to check admin role: {:?}", e)}))
+        )),
+    }
+}
+
+/// Run the moderation checks for a submission of community content
+async fn check_submission(state: &AppState, steam_id: &str, texts: &[&str]) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    state.moderation.check_submission(state.db.as_ref(), steam_id, texts).await.map_err(|e| {
+        let status = match e {
+            ModerationError::Banned => StatusCode::FORBIDDEN,
+            ModerationError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ModerationError::BlockedWord => StatusCode::BAD_REQUEST,
+            ModerationError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(serde_json::json!({"error": e.to_string()})))
     })
 }
 
+// ============================================================================
+// Library & History Endpoints
+// ============================================================================
+//
+// Each list is paged (see `crate::pagination`) and served with an ETag. The
+// `/api/users/{short_id}/...` variants are public and show the same data as a
+// shared guest link.
+
+/// Steam ID of the user behind a guest link
+async fn guest_steam_id(state: &AppState, short_id: &str) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    match state.db.get_user_by_short_id(short_id).await {
+        Ok(Some(user)) => Ok(user.steam_id),
+        Ok(None) => Err(guest_not_found(short_id)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to look up user: {:?}", e)}))
+        )),
+    }
+}
+
+fn guest_not_found(short_id: &str) -> (StatusCode, Json<serde_json::Value>) {
+    (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": format!("User not found: {}", short_id)})))
+}
+
+/// ETag of a page of one of the user's lists, derived without loading it
+async fn page_etag(state: &AppState, steam_id: &str, list: UserList, query: &PageQuery) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    match state.db.get_list_version(steam_id, list).await {
+        Ok(version) => Ok(list_etag(list, version, query)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get list version: {:?}", e)}))
+        )),
+    }
+}
+
+/// Games page, ordered by appid
+async fn games_page(state: &AppState, headers: &HeaderMap, steam_id: &str, query: &PageQuery) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let after = page_cursor(query, |cursor| cursor.parse().ok())?;
+    let etag = page_etag(state, steam_id, UserList::Games, query).await?;
+    if let Some(response) = not_modified(headers, &etag) {
+        return Ok(response);
+    }
+    
+    let limit = page_limit(query);
+    match state.db.get_user_games_page(steam_id, after, limit as i32 + 1).await {
+        Ok(games) => Ok(json_with_etag(&etag, &finish_page(games, limit, |game| game.appid.to_string()))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get games: {:?}", e)}))
+        ))
+    }
+}
+
+/// Achievements page, ordered by API name
+fn achievements_page(headers: &HeaderMap, achievements: Vec<GameAchievement>, query: &PageQuery) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let page = paginate(
+        achievements,
+        query,
+        |achievement| achievement.apiname.clone(),
+        |cursor| Some(cursor.to_string()),
+        String::clone,
+    )?;
+    Ok(etag_json(headers, &page))
+}
+
+/// Library of the authenticated user
+#[utoipa::path(
+    get,
+    path = "/api/games",
+    tag = "library",
+    params(PageQuery),
+    responses(
+        (status = 200, body = Page<Game>),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
 pub async fn get_games(
-    State(_state): State<Arc<AppState>>,
-) -> Json<Vec<Game>> {
-    // TODO: Get authenticated user and fetch their games
-    Json(vec![])
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<PageQuery>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    games_page(&state, &headers, &claims.steam_id, &query).await
 }
 
-pub async fn get_achievements(
-    State(_state): State<Arc<AppState>>,
-    Path(_appid): Path<u64>,
-) -> Json<Vec<GameAchievement>> {
-    // TODO: Get authenticated user and fetch achievements
-    Json(vec![])
+/// Library of a guest link's user (public)
+#[utoipa::path(
+    get,
+    path = "/api/users/{short_id}/games",
+    tag = "guest",
+    params(
+        ("short_id" = String, Path, description = "Guest link id of the user"),
+        PageQuery,
+    ),
+    responses(
+        (status = 200, body = Page<Game>),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn get_guest_games(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(short_id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let steam_id = guest_steam_id(&state, &short_id).await?;
+    games_page(&state, &headers, &steam_id, &query).await
 }
 
-pub async fn get_ratings(
+/// The authenticated user's achievements in a game
+#[utoipa::path(
+    get,
+    path = "/api/games/{appid}/achievements",
+    tag = "library",
+    params(
+        ("appid" = u64, Path, description = "Steam app id"),
+        PageQuery,
+    ),
+    responses(
+        (status = 200, body = Page<GameAchievement>),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_achievements(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Path(appid): Path<u64>,
-) -> Json<Vec<GameRating>> {
-    match crate::db::get_community_ratings(&state.db_pool, appid).await {
-        Ok(ratings) => Json(ratings),
-        Err(_) => Json(vec![]),
+    Query(query): Query<PageQuery>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    match state.db.get_game_achievements(&claims.steam_id, appid).await {
+        Ok(achievements) => achievements_page(&headers, achievements, &query),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get achievements: {:?}", e)}))
+        ))
     }
 }
 
-#[derive(serde::Deserialize)]
-pub struct SubmitRatingRequest {
-    pub appid: u64,
-    pub rating: u8,
-    pub comment: Option<String>,
+/// A guest link's user's achievements in a game (public)
+#[utoipa::path(
+    get,
+    path = "/api/users/{short_id}/games/{appid}/achievements",
+    tag = "guest",
+    params(
+        ("short_id" = String, Path, description = "Guest link id of the user"),
+        ("appid" = u64, Path, description = "Steam app id"),
+        PageQuery,
+    ),
+    responses(
+        (status = 200, body = Page<GameAchievement>),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn get_guest_achievements(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path((short_id, appid)): Path<(String, u64)>,
+    Query(query): Query<PageQuery>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    match state.db.get_game_achievements_by_short_id(&short_id, appid).await {
+        Ok(Some(achievements)) => achievements_page(&headers, achievements, &query),
+        Ok(None) => Err(guest_not_found(&short_id)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get achievements: {:?}", e)}))
+        ))
+    }
 }
 
-pub async fn submit_rating(
-    State(_state): State<Arc<AppState>>,
-    Json(_body): Json<SubmitRatingRequest>,
-) -> Json<serde_json::Value> {
-    // TODO: Get authenticated user and submit rating
-    Json(serde_json::json!({"error": "Not implemented"}))
+/// Profile behind a guest link (public)
+#[utoipa::path(
+    get,
+    path = "/api/users/{short_id}",
+    tag = "guest",
+    params(("short_id" = String, Path, description = "Guest link id of the user")),
+    responses(
+        (status = 200, body = UserProfile),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn get_guest_profile(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(short_id): Path<String>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    match state.db.get_user_by_short_id(&short_id).await {
+        Ok(Some(user)) => Ok(etag_json(&headers, &user)),
+        Ok(None) => Err(guest_not_found(&short_id)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to look up user: {:?}", e)}))
+        ))
+    }
+}
+
+/// Game count history, oldest first
+async fn run_history_page(state: &AppState, headers: &HeaderMap, steam_id: &str, query: &PageQuery) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let after = page_cursor(query, |cursor| cursor.parse().ok())?;
+    let etag = page_etag(state, steam_id, UserList::RunHistory, query).await?;
+    if let Some(response) = not_modified(headers, &etag) {
+        return Ok(response);
+    }
+    
+    let limit = page_limit(query);
+    match state.db.get_run_history_page(steam_id, after, limit as i32 + 1).await {
+        Ok(history) => Ok(json_with_etag(&etag, &finish_page(history, limit, |run| run.id.to_string()))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get run history: {:?}", e)}))
+        ))
+    }
+}
+
+/// Achievement progress history, oldest first
+async fn achievement_history_page(state: &AppState, headers: &HeaderMap, steam_id: &str, query: &PageQuery) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let after = page_cursor(query, |cursor| cursor.parse().ok())?;
+    let etag = page_etag(state, steam_id, UserList::AchievementHistory, query).await?;
+    if let Some(response) = not_modified(headers, &etag) {
+        return Ok(response);
+    }
+    
+    let limit = page_limit(query);
+    match state.db.get_achievement_history_page(steam_id, after, limit as i32 + 1).await {
+        Ok(history) => Ok(json_with_etag(&etag, &finish_page(history, limit, |entry| entry.id.to_string()))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get achievement history: {:?}", e)}))
+        ))
+    }
+}
+
+/// Position of a log entry, the cursor of the page it ends
+fn log_cursor(entry: &LogEntry) -> String {
+    let (appid, apiname) = match entry {
+        LogEntry::Achievement { appid, apiname, .. } => (*appid, apiname.clone()),
+        LogEntry::FirstPlay { appid, .. } => (*appid, String::new()),
+    };
+    LogCursor { unlocktime: entry.timestamp(), appid, apiname }.format()
+}
+
+/// Achievement unlocks, newest first
+async fn log_page(state: &AppState, headers: &HeaderMap, steam_id: &str, query: &PageQuery) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let after = page_cursor(query, LogCursor::parse)?;
+    let etag = page_etag(state, steam_id, UserList::Log, query).await?;
+    if let Some(response) = not_modified(headers, &etag) {
+        return Ok(response);
+    }
+    
+    let limit = page_limit(query);
+    match state.db.get_log_page(steam_id, after.as_ref(), limit as i32 + 1).await {
+        Ok(entries) => Ok(json_with_etag(&etag, &finish_page(entries, limit, log_cursor))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get log entries: {:?}", e)}))
+        ))
+    }
+}
+
+/// Game count history of the authenticated user
+#[utoipa::path(
+    get,
+    path = "/api/history/runs",
+    tag = "library",
+    params(PageQuery),
+    responses(
+        (status = 200, body = Page<RunHistory>),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_run_history(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<PageQuery>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    run_history_page(&state, &headers, &claims.steam_id, &query).await
+}
+
+/// Game count history of a guest link's user (public)
+#[utoipa::path(
+    get,
+    path = "/api/users/{short_id}/history/runs",
+    tag = "guest",
+    params(
+        ("short_id" = String, Path, description = "Guest link id of the user"),
+        PageQuery,
+    ),
+    responses(
+        (status = 200, body = Page<RunHistory>),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn get_guest_run_history(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(short_id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let steam_id = guest_steam_id(&state, &short_id).await?;
+    run_history_page(&state, &headers, &steam_id, &query).await
+}
+
+/// Achievement progress history of the authenticated user
+#[utoipa::path(
+    get,
+    path = "/api/history/achievements",
+    tag = "library",
+    params(PageQuery),
+    responses(
+        (status = 200, body = Page<AchievementHistory>),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_achievement_history(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<PageQuery>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    achievement_history_page(&state, &headers, &claims.steam_id, &query).await
+}
+
+/// Achievement progress history of a guest link's user (public)
+#[utoipa::path(
+    get,
+    path = "/api/users/{short_id}/history/achievements",
+    tag = "guest",
+    params(
+        ("short_id" = String, Path, description = "Guest link id of the user"),
+        PageQuery,
+    ),
+    responses(
+        (status = 200, body = Page<AchievementHistory>),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn get_guest_achievement_history(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(short_id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let steam_id = guest_steam_id(&state, &short_id).await?;
+    achievement_history_page(&state, &headers, &steam_id, &query).await
+}
+
+/// Achievement unlocks of the authenticated user
+#[utoipa::path(
+    get,
+    path = "/api/log",
+    tag = "library",
+    params(PageQuery),
+    responses(
+        (status = 200, body = Page<LogEntry>),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_log(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<PageQuery>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    log_page(&state, &headers, &claims.steam_id, &query).await
+}
+
+/// Achievement unlocks of a guest link's user (public)
+#[utoipa::path(
+    get,
+    path = "/api/users/{short_id}/log",
+    tag = "guest",
+    params(
+        ("short_id" = String, Path, description = "Guest link id of the user"),
+        PageQuery,
+    ),
+    responses(
+        (status = 200, body = Page<LogEntry>),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn get_guest_log(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(short_id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let steam_id = guest_steam_id(&state, &short_id).await?;
+    log_page(&state, &headers, &steam_id, &query).await
 }
 
 // ============================================================================
-// Achievement Rating & Comment Endpoints
+// Community Endpoints
 // ============================================================================
 
-#[derive(serde::Deserialize)]
-pub struct AchievementRatingRequest {
-    pub appid: u64,
-    pub apiname: String,
-    pub rating: u8,
+/// Most games a single averages request may ask for
+pub const MAX_AVERAGES_APPIDS: usize = 10_000;
+
+/// Community difficulty averages for one game's achievements (public)
+#[utoipa::path(
+    get,
+    path = "/api/games/{appid}/achievement-averages",
+    tag = "community",
+    params(("appid" = u64, Path, description = "Steam app id")),
+    responses(
+        (status = 200, body = Vec<AchievementDifficulty>),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn get_game_achievement_averages(
+    State(state): State<Arc<AppState>>,
+    Path(appid): Path<u64>,
+) -> Result<Json<Vec<AchievementDifficulty>>, (StatusCode, Json<serde_json::Value>)> {
+    achievement_averages(&state, &[appid]).await
+}
+
+/// Community difficulty averages for a whole library (public)
+#[utoipa::path(
+    post,
+    path = "/api/achievement/averages",
+    tag = "community",
+    request_body = AppidsRequest,
+    responses(
+        (status = 200, body = Vec<AchievementDifficulty>),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn get_achievement_averages(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<AppidsRequest>,
+) -> Result<Json<Vec<AchievementDifficulty>>, (StatusCode, Json<serde_json::Value>)> {
+    if body.appids.len() > MAX_AVERAGES_APPIDS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("At most {} games per request", MAX_AVERAGES_APPIDS)}))
+        ));
+    }
+    achievement_averages(&state, &body.appids).await
+}
+
+async fn achievement_averages(
+    state: &AppState,
+    appids: &[u64],
+) -> Result<Json<Vec<AchievementDifficulty>>, (StatusCode, Json<serde_json::Value>)> {
+    match state.db.get_achievement_averages(appids).await {
+        Ok(averages) => Ok(Json(averages)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get difficulty averages: {:?}", e)}))
+        ))
+    }
 }
 
-#[derive(serde::Serialize)]
-pub struct AchievementRatingResponse {
-    pub success: bool,
-    pub appid: u64,
-    pub apiname: String,
+/// Community and Steam unlock rates for a game's achievements (public)
+#[utoipa::path(
+    get,
+    path = "/api/games/{appid}/rarity",
+    tag = "community",
+    params(("appid" = u64, Path, description = "Steam app id")),
+    responses(
+        (status = 200, body = GameRarity),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn get_achievement_rarity(
+    State(state): State<Arc<AppState>>,
+    Path(appid): Path<u64>,
+) -> Result<Json<GameRarity>, (StatusCode, Json<serde_json::Value>)> {
+    match crate::rarity::game_rarity(state.db.as_ref(), appid).await {
+        Ok(rarity) => Ok(Json(rarity)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get unlock rates: {:?}", e)}))
+        ))
+    }
 }
 
+/// Ratings and reviews of a game, newest first (public; a token marks the own rating)
+#[utoipa::path(
+    get,
+    path = "/api/community/ratings/{appid}",
+    tag = "community",
+    params(("appid" = u64, Path, description = "Steam app id")),
+    responses(
+        (status = 200, body = CommunityGameRating),
+        (status = "default", body = ApiError),
+    ),
+    security((), ("bearer" = [])),
+)]
+pub async fn get_ratings(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(appid): Path<u64>,
+) -> Result<Json<CommunityGameRating>, (StatusCode, Json<serde_json::Value>)> {
+    let viewer = extract_user(&headers, &state).ok();
+    let viewer = viewer.as_ref().map(|claims| claims.steam_id.as_str());
+    
+    match state.db.get_community_ratings(appid, viewer).await {
+        Ok(ratings) => Ok(Json(CommunityGameRating::from_ratings(appid, ratings))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get ratings: {:?}", e)}))
+        ))
+    }
+}
+
+/// Rate a game, replacing the user's earlier rating. Returns the game's ratings.
+#[utoipa::path(
+    post,
+    path = "/api/community/ratings",
+    tag = "community",
+    request_body = SubmitRatingRequest,
+    responses(
+        (status = 200, body = CommunityGameRating),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn submit_rating(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<SubmitRatingRequest>,
+) -> Result<Json<CommunityGameRating>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    let comment = body.comment.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    if let Err(e) = overachiever_core::validate_game_rating(body.rating, comment) {
+        return Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e}))));
+    }
+    check_submission(&state, &claims.steam_id, comment.as_slice()).await?;
+    
+    let result = match state.db.upsert_rating(&claims.steam_id, body.appid, body.rating, comment).await {
+        Ok(()) => state.db.get_community_ratings(body.appid, Some(&claims.steam_id)).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(ratings) => Ok(Json(CommunityGameRating::from_ratings(body.appid, ratings))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to submit rating: {:?}", e)}))
+        ))
+    }
+}
+
+/// Community rating averages for a whole library (public)
+#[utoipa::path(
+    post,
+    path = "/api/community/ratings/averages",
+    tag = "community",
+    request_body = AppidsRequest,
+    responses(
+        (status = 200, body = Vec<GameRatingAverage>),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn get_game_rating_averages(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<AppidsRequest>,
+) -> Result<Json<Vec<GameRatingAverage>>, (StatusCode, Json<serde_json::Value>)> {
+    if body.appids.len() > MAX_AVERAGES_APPIDS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("At most {} games per request", MAX_AVERAGES_APPIDS)}))
+        ));
+    }
+    match state.db.get_game_rating_averages(&body.appids).await {
+        Ok(averages) => Ok(Json(averages)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get rating averages: {:?}", e)}))
+        ))
+    }
+}
+
+/// Community-confirmed achievement flags for a whole library (public)
+#[utoipa::path(
+    post,
+    path = "/api/achievement/flags",
+    tag = "community",
+    request_body = AppidsRequest,
+    responses(
+        (status = 200, body = Vec<AchievementFlagVotes>),
+        (status = "default", body = ApiError),
+    ),
+)]
+pub async fn get_achievement_flags(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<AppidsRequest>,
+) -> Result<Json<Vec<AchievementFlagVotes>>, (StatusCode, Json<serde_json::Value>)> {
+    if body.appids.len() > MAX_AVERAGES_APPIDS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("At most {} games per request", MAX_AVERAGES_APPIDS)}))
+        ));
+    }
+    match state.db.get_achievement_flag_votes(&body.appids).await {
+        Ok(flags) => Ok(Json(flags)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get achievement flags: {:?}", e)}))
+        ))
+    }
+}
+
+// ============================================================================
+// Achievement Rating & Comment Endpoints
+// ============================================================================
+
+#[utoipa::path(
+    post,
+    path = "/api/achievement/rating",
+    tag = "achievements",
+    request_body = AchievementRatingRequest,
+    responses(
+        (status = 200, body = AchievementRatingResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
 pub async fn submit_achievement_rating(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
     Json(body): Json<AchievementRatingRequest>,
 ) -> Result<Json<AchievementRatingResponse>, (StatusCode, Json<serde_json::Value>)> {
-    let claims = extract_user(&headers, &state.jwt_secret)?;
+    let claims = extract_user(&headers, &state)?;
     
     // Validate rating is 1-5
     if body.rating < 1 || body.rating > 5 {
@@ -112,8 +729,7 @@ pub async fn submit_achievement_rating(
     );
     
     // Store rating in database
-    if let Err(e) = crate::db::upsert_achievement_rating(
-        &state.db_pool,
+    if let Err(e) = state.db.upsert_achievement_rating(
         &claims.steam_id,
         body.appid,
         &body.apiname,
@@ -133,33 +749,72 @@ pub async fn submit_achievement_rating(
     }))
 }
 
-/// Response format for user's achievement ratings
-#[derive(serde::Serialize)]
-pub struct UserAchievementRatingsResponse {
-    pub ratings: Vec<AchievementRatingEntry>,
-}
-
-#[derive(serde::Serialize)]
-pub struct AchievementRatingEntry {
-    pub appid: u64,
-    pub apiname: String,
-    pub rating: u8,
+/// Set the authenticated user's flags for an achievement (empty flags clear them)
+#[utoipa::path(
+    post,
+    path = "/api/achievement/flag",
+    tag = "achievements",
+    request_body = AchievementFlagsRequest,
+    responses(
+        (status = 200, body = AchievementRatingResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn submit_achievement_flags(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<AchievementFlagsRequest>,
+) -> Result<Json<AchievementRatingResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    match state.db.upsert_achievement_flags(&claims.steam_id, body.appid, &body.apiname, body.flags).await {
+        Ok(()) => Ok(Json(AchievementRatingResponse {
+            success: true,
+            appid: body.appid,
+            apiname: body.apiname,
+        })),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to store flags: {:?}", e)}))
+        ))
+    }
 }
 
+/// Response format for user's achievement ratings
 /// Get all achievement ratings for the authenticated user
+#[utoipa::path(
+    get,
+    path = "/api/achievement/ratings",
+    tag = "achievements",
+    responses(
+        (status = 200, body = UserAchievementRatingsResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
 pub async fn get_user_achievement_ratings(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<UserAchievementRatingsResponse>, (StatusCode, Json<serde_json::Value>)> {
-    let claims = extract_user(&headers, &state.jwt_secret)?;
+    let claims = extract_user(&headers, &state)?;
     
-    match crate::db::get_user_achievement_ratings(&state.db_pool, &claims.steam_id).await {
-        Ok(ratings) => {
+    let result = match state.db.get_user_achievement_ratings(&claims.steam_id).await {
+        Ok(ratings) => state.db.get_user_achievement_flags(&claims.steam_id).await
+            .map(|flags| (ratings, flags)),
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok((ratings, flags)) => {
             let entries: Vec<AchievementRatingEntry> = ratings
                 .into_iter()
                 .map(|(appid, apiname, rating)| AchievementRatingEntry { appid, apiname, rating })
                 .collect();
-            Ok(Json(UserAchievementRatingsResponse { ratings: entries }))
+            let flags = flags
+                .into_iter()
+                .map(|(appid, apiname, flags)| AchievementFlagsEntry { appid, apiname, flags })
+                .collect();
+            Ok(Json(UserAchievementRatingsResponse { ratings: entries, flags }))
         }
         Err(e) => {
             tracing::error!("Failed to fetch user achievement ratings: {:?}", e);
@@ -171,25 +826,23 @@ pub async fn get_user_achievement_ratings(
     }
 }
 
-#[derive(serde::Deserialize)]
-pub struct AchievementCommentRequest {
-    /// List of (appid, apiname) pairs
-    pub achievements: Vec<(u64, String)>,
-    pub comment: String,
-}
-
-#[derive(serde::Serialize)]
-pub struct AchievementCommentResponse {
-    pub success: bool,
-    pub count: usize,
-}
-
+#[utoipa::path(
+    post,
+    path = "/api/achievement/comment",
+    tag = "achievements",
+    request_body = AchievementCommentRequest,
+    responses(
+        (status = 200, body = AchievementCommentResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
 pub async fn submit_achievement_comment(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
     Json(body): Json<AchievementCommentRequest>,
 ) -> Result<Json<AchievementCommentResponse>, (StatusCode, Json<serde_json::Value>)> {
-    let claims = extract_user(&headers, &state.jwt_secret)?;
+    let claims = extract_user(&headers, &state)?;
     
     if body.achievements.is_empty() {
         return Err((
@@ -198,46 +851,472 @@ pub async fn submit_achievement_comment(
         ));
     }
     
-    if body.comment.trim().is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({"error": "Comment cannot be empty"}))
-        ));
+    if let Err(e) = overachiever_core::validate_comment(&body.comment) {
+        return Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e}))));
     }
+    check_submission(&state, &claims.steam_id, &[&body.comment]).await?;
     
-    tracing::info!(
-        steam_id = %claims.steam_id,
-        achievements = ?body.achievements,
-        comment = %body.comment,
-        "Achievement comment submitted via REST"
-    );
+    match state.db.insert_achievement_comment(&claims.steam_id, &body.achievements, &body.comment).await {
+        Ok(comment) => Ok(Json(AchievementCommentResponse {
+            success: true,
+            count: comment.achievements.len(),
+            comment,
+        })),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to store comment: {:?}", e)}))
+        ))
+    }
+}
+
+/// Most comments returned for one achievement
+pub const COMMENTS_LIMIT: i64 = 200;
+
+/// Comments tagging an achievement, oldest first (public; a token marks own comments)
+#[utoipa::path(
+    get,
+    path = "/api/achievement/comments",
+    tag = "achievements",
+    params(AchievementQuery),
+    responses(
+        (status = 200, body = Vec<AchievementComment>),
+        (status = "default", body = ApiError),
+    ),
+    security((), ("bearer" = [])),
+)]
+pub async fn get_achievement_comments(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<AchievementQuery>,
+) -> Result<Json<Vec<AchievementComment>>, (StatusCode, Json<serde_json::Value>)> {
+    let viewer = extract_user(&headers, &state).ok();
+    let viewer = viewer.as_ref().map(|claims| claims.steam_id.as_str());
     
-    // TODO: Store comment in database
-    // For now, just log and return success
+    match state.db.get_achievement_comments(query.appid, &query.apiname, viewer, COMMENTS_LIMIT).await {
+        Ok(comments) => Ok(Json(comments)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get comments: {:?}", e)}))
+        ))
+    }
+}
+
+/// Tips for an achievement, highest score first (public; a token marks own
+/// tips and votes)
+#[utoipa::path(
+    get,
+    path = "/api/achievement/tips",
+    tag = "achievements",
+    params(AchievementQuery),
+    responses(
+        (status = 200, body = Vec<AchievementTip>),
+        (status = "default", body = ApiError),
+    ),
+    security((), ("bearer" = [])),
+)]
+pub async fn get_achievement_tips(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<AchievementQuery>,
+) -> Result<Json<Vec<AchievementTip>>, (StatusCode, Json<serde_json::Value>)> {
+    let viewer = extract_user(&headers, &state).ok();
+    let viewer = viewer.as_ref().map(|claims| claims.steam_id.as_str());
     
-    Ok(Json(AchievementCommentResponse {
-        success: true,
-        count: body.achievements.len(),
-    }))
+    match state.db.get_achievement_tips(query.appid, &query.apiname, viewer).await {
+        Ok(tips) => Ok(Json(tips)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get tips: {:?}", e)}))
+        ))
+    }
+}
+
+/// Submit a tip, replacing the user's earlier tip for the achievement.
+/// Returns the achievement's tips.
+#[utoipa::path(
+    post,
+    path = "/api/achievement/tip",
+    tag = "achievements",
+    request_body = SubmitTipRequest,
+    responses(
+        (status = 200, body = Vec<AchievementTip>),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn submit_achievement_tip(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<SubmitTipRequest>,
+) -> Result<Json<Vec<AchievementTip>>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    if let Err(e) = overachiever_core::validate_tip(body.difficulty, &body.tip) {
+        return Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e}))));
+    }
+    check_submission(&state, &claims.steam_id, &[&body.tip]).await?;
+    
+    let result = match state.db.upsert_achievement_tip(&claims.steam_id, body.appid, &body.apiname, body.difficulty, body.tip.trim()).await {
+        Ok(()) => state.db.get_achievement_tips(body.appid, &body.apiname, Some(&claims.steam_id)).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(tips) => Ok(Json(tips)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to submit tip: {:?}", e)}))
+        ))
+    }
+}
+
+/// Vote on another user's tip (1, -1, or 0 to take the vote back). Returns
+/// the tips of the tip's achievement.
+#[utoipa::path(
+    put,
+    path = "/api/achievement/tip/{id}/vote",
+    tag = "achievements",
+    params(("id" = i64, Path, description = "Tip id")),
+    request_body = VoteTipRequest,
+    responses(
+        (status = 200, body = Vec<AchievementTip>),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn vote_achievement_tip(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+    Json(body): Json<VoteTipRequest>,
+) -> Result<Json<Vec<AchievementTip>>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    let result = match state.db.vote_achievement_tip(&claims.steam_id, id, body.vote).await {
+        Ok(Some((appid, apiname))) => state.db.get_achievement_tips(appid, &apiname, Some(&claims.steam_id)).await,
+        Ok(None) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Tip not found (own tips cannot be voted on)"}))
+        )),
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(tips) => Ok(Json(tips)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to vote on tip: {:?}", e)}))
+        ))
+    }
+}
+
+/// Change the text of an own comment
+#[utoipa::path(
+    put,
+    path = "/api/achievement/comment/{id}",
+    tag = "achievements",
+    params(("id" = i64, Path, description = "Comment id")),
+    request_body = EditCommentRequest,
+    responses(
+        (status = 200, body = AchievementComment),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn edit_achievement_comment(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+    Json(body): Json<EditCommentRequest>,
+) -> Result<Json<AchievementComment>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    if let Err(e) = overachiever_core::validate_comment(&body.comment) {
+        return Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e}))));
+    }
+    check_submission(&state, &claims.steam_id, &[&body.comment]).await?;
+    
+    match state.db.update_achievement_comment(&claims.steam_id, id, &body.comment).await {
+        Ok(Some(comment)) => Ok(Json(comment)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Comment not found"}))
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to edit comment: {:?}", e)}))
+        ))
+    }
+}
+
+/// Delete an own comment
+#[utoipa::path(
+    delete,
+    path = "/api/achievement/comment/{id}",
+    tag = "achievements",
+    params(("id" = i64, Path, description = "Comment id")),
+    responses(
+        (status = 200, body = SuccessResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn delete_achievement_comment(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    match state.db.delete_achievement_comment(&claims.steam_id, id).await {
+        Ok(true) => Ok(Json(SuccessResponse { success: true })),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Comment not found"}))
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to delete comment: {:?}", e)}))
+        ))
+    }
 }
 
 // ============================================================================
-// Cloud Sync Endpoints
+// Reporting & Moderation Endpoints
 // ============================================================================
 
-use overachiever_core::{CloudSyncData, CloudSyncStatus};
+/// Report another user's comment, tip or review to the admins
+#[utoipa::path(
+    post,
+    path = "/api/report",
+    tag = "achievements",
+    request_body = ReportRequest,
+    responses(
+        (status = 200, body = SuccessResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn report_content(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<ReportRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    if let Err(e) = overachiever_core::validate_report_reason(&body.reason) {
+        return Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e}))));
+    }
+    check_submission(&state, &claims.steam_id, &[&body.reason]).await?;
+    
+    match state.db.insert_content_report(&claims.steam_id, body.kind, body.content_id, body.reason.trim()).await {
+        Ok(true) => Ok(Json(SuccessResponse { success: true })),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Content not found (own content cannot be reported)"}))
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to store report: {:?}", e)}))
+        ))
+    }
+}
+
+/// Open reports, oldest first (admins only)
+#[utoipa::path(
+    get,
+    path = "/api/admin/reports",
+    tag = "admin",
+    responses(
+        (status = 200, body = Vec<ContentReport>),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_reports(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ContentReport>>, (StatusCode, Json<serde_json::Value>)> {
+    extract_admin(&state, &headers).await?;
+    
+    match state.db.get_open_reports().await {
+        Ok(reports) => Ok(Json(reports)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get reports: {:?}", e)}))
+        ))
+    }
+}
+
+/// Close a report without acting on the content (admins only)
+#[utoipa::path(
+    delete,
+    path = "/api/admin/reports/{id}",
+    tag = "admin",
+    params(("id" = i64, Path, description = "Report id")),
+    responses(
+        (status = 200, body = SuccessResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn dismiss_report(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_admin(&state, &headers).await?;
+    
+    match state.db.dismiss_report(id).await {
+        Ok(true) => {
+            tracing::info!(admin = %claims.steam_id, report = %id, "Report dismissed");
+            Ok(Json(SuccessResponse { success: true }))
+        }
+        Ok(false) => Err((StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "Report not found"})))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to dismiss report: {:?}", e)}))
+        ))
+    }
+}
+
+/// Hide content from all community queries, or show it again (admins only)
+#[utoipa::path(
+    put,
+    path = "/api/admin/content/{kind}/{id}/hidden",
+    tag = "admin",
+    params(
+        ("kind" = ContentKind, Path, description = "Kind of content"),
+        ("id" = i64, Path, description = "Id of the content"),
+    ),
+    request_body = SetHiddenRequest,
+    responses(
+        (status = 200, body = SuccessResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn set_content_hidden(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path((kind, id)): Path<(ContentKind, i64)>,
+    Json(body): Json<SetHiddenRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_admin(&state, &headers).await?;
+    
+    match state.db.set_content_hidden(kind, id, body.hidden).await {
+        Ok(true) => {
+            tracing::info!(admin = %claims.steam_id, kind = kind.as_str(), id = %id, hidden = body.hidden, "Content visibility changed");
+            Ok(Json(SuccessResponse { success: true }))
+        }
+        Ok(false) => Err((StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "Content not found"})))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to change visibility: {:?}", e)}))
+        ))
+    }
+}
+
+/// Delete any user's content (admins only)
+#[utoipa::path(
+    delete,
+    path = "/api/admin/content/{kind}/{id}",
+    tag = "admin",
+    params(
+        ("kind" = ContentKind, Path, description = "Kind of content"),
+        ("id" = i64, Path, description = "Id of the content"),
+    ),
+    responses(
+        (status = 200, body = SuccessResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn delete_content(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path((kind, id)): Path<(ContentKind, i64)>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_admin(&state, &headers).await?;
+    
+    match state.db.delete_content(kind, id).await {
+        Ok(true) => {
+            tracing::info!(admin = %claims.steam_id, kind = kind.as_str(), id = %id, "Content deleted");
+            Ok(Json(SuccessResponse { success: true }))
+        }
+        Ok(false) => Err((StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "Content not found"})))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to delete content: {:?}", e)}))
+        ))
+    }
+}
 
-/// Body limit for large uploads (100MB)
-pub const UPLOAD_BODY_LIMIT: usize = 100 * 1024 * 1024;
+/// Ban a user from submitting community content, or lift the ban (admins only)
+#[utoipa::path(
+    put,
+    path = "/api/admin/users/{steam_id}/ban",
+    tag = "admin",
+    params(("steam_id" = String, Path, description = "Steam id of the user")),
+    request_body = BanRequest,
+    responses(
+        (status = 200, body = SuccessResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn set_user_banned(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(steam_id): Path<String>,
+    Json(body): Json<BanRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_admin(&state, &headers).await?;
+    if steam_id == claims.steam_id {
+        return Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "Admins cannot ban themselves"}))));
+    }
+    
+    let reason = body.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    match state.db.set_user_banned(&steam_id, body.banned, reason).await {
+        Ok(true) => {
+            tracing::info!(admin = %claims.steam_id, user = %steam_id, banned = body.banned, "User ban changed");
+            Ok(Json(SuccessResponse { success: true }))
+        }
+        Ok(false) => Err((StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "User not found"})))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to change ban: {:?}", e)}))
+        ))
+    }
+}
+
+// ============================================================================
+// Cloud Sync Endpoints
+// ============================================================================
+
+use axum::body::Bytes;
+use overachiever_core::{
+    CloudSyncData, CloudSyncStatus, CloudSyncDelta, SyncPushResponse, SYNC_DELTA_VERSION,
+    UploadSessionRequest, UploadSessionInfo, UploadCompleteRequest, EncryptedSyncBlob, ENCRYPTED_SYNC_VERSION,
+};
+use crate::upload_sessions::UploadError;
 
 /// Check if user has data in the cloud
+#[utoipa::path(
+    get,
+    path = "/api/sync/status",
+    tag = "sync",
+    responses(
+        (status = 200, body = CloudSyncStatus),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
 pub async fn get_sync_status(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<CloudSyncStatus>, (StatusCode, Json<serde_json::Value>)> {
-    let claims = extract_user(&headers, &state.jwt_secret)?;
+    let claims = extract_user(&headers, &state)?;
     
-    match crate::db::get_cloud_sync_status(&state.db_pool, &claims.steam_id).await {
+    match state.db.get_cloud_sync_status(&claims.steam_id).await {
         Ok(status) => Ok(Json(status)),
         Err(e) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -247,13 +1326,23 @@ pub async fn get_sync_status(
 }
 
 /// Download all user data from cloud
+#[utoipa::path(
+    get,
+    path = "/api/sync/download",
+    tag = "sync",
+    responses(
+        (status = 200, body = CloudSyncData),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
 pub async fn download_sync_data(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<CloudSyncData>, (StatusCode, Json<serde_json::Value>)> {
-    let claims = extract_user(&headers, &state.jwt_secret)?;
+    let claims = extract_user(&headers, &state)?;
     
-    match crate::db::get_cloud_sync_data(&state.db_pool, &claims.steam_id).await {
+    match state.db.get_cloud_sync_data(&claims.steam_id).await {
         Ok(data) => Ok(Json(data)),
         Err(e) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -262,13 +1351,24 @@ pub async fn download_sync_data(
     }
 }
 
-/// Upload all user data to cloud (overwrites existing)
+/// Upload all user data to cloud (merged with existing cloud data)
+#[utoipa::path(
+    post,
+    path = "/api/sync/upload",
+    tag = "sync",
+    request_body = CloudSyncData,
+    responses(
+        (status = 200, body = SyncUploadResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
 pub async fn upload_sync_data(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
     Json(data): Json<CloudSyncData>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
-    let claims = extract_user(&headers, &state.jwt_secret)?;
+) -> Result<Json<SyncUploadResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
     
     // Verify the uploaded data belongs to the authenticated user
     if data.steam_id != claims.steam_id {
@@ -278,19 +1378,22 @@ pub async fn upload_sync_data(
         ));
     }
     
-    match crate::db::upload_cloud_sync_data(&state.db_pool, &data).await {
-        Ok(_) => {
+    match state.db.upload_cloud_sync_data(&data, false).await {
+        Ok(merged) => {
             tracing::info!(
                 steam_id = %claims.steam_id,
                 games = data.games.len(),
                 achievements = data.achievements.len(),
-                "Cloud sync data uploaded"
+                games_added = merged.games_added,
+                run_history_added = merged.run_history_added,
+                "Cloud sync data merged"
             );
-            Ok(Json(serde_json::json!({
-                "success": true,
-                "games_uploaded": data.games.len(),
-                "achievements_uploaded": data.achievements.len()
-            })))
+            Ok(Json(SyncUploadResponse {
+                success: true,
+                games_uploaded: data.games.len(),
+                achievements_uploaded: data.achievements.len(),
+                merged,
+            }))
         }
         Err(e) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -299,17 +1402,363 @@ pub async fn upload_sync_data(
     }
 }
 
+/// Pull rows changed since a sync cursor
+#[utoipa::path(
+    get,
+    path = "/api/sync/delta",
+    tag = "sync",
+    params(PullDeltaQuery),
+    responses(
+        (status = 200, body = CloudSyncDelta),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn pull_sync_delta(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<PullDeltaQuery>,
+) -> Result<Json<CloudSyncDelta>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    match state.db.get_sync_delta(&claims.steam_id, query.since).await {
+        Ok(delta) => Ok(Json(delta)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to pull changes: {:?}", e)}))
+        ))
+    }
+}
+
+/// Push locally changed rows; they are merged like a full upload
+#[utoipa::path(
+    post,
+    path = "/api/sync/delta",
+    tag = "sync",
+    params(PushDeltaQuery),
+    request_body = CloudSyncDelta,
+    responses(
+        (status = 200, body = SyncPushResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn push_sync_delta(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<PushDeltaQuery>,
+    Json(delta): Json<CloudSyncDelta>,
+) -> Result<Json<SyncPushResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    merge_pushed_delta(&state, &claims, delta, query.dry_run).await.map(Json)
+}
+
+/// Validate a pushed delta and merge it into the cloud data
+async fn merge_pushed_delta(
+    state: &AppState,
+    claims: &Claims,
+    delta: CloudSyncDelta,
+    dry_run: bool,
+) -> Result<SyncPushResponse, (StatusCode, Json<serde_json::Value>)> {
+    if !delta.is_supported() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!(
+                "Unsupported sync format version {} (server supports {})",
+                delta.version, SYNC_DELTA_VERSION
+            )}))
+        ));
+    }
+    if delta.steam_id != claims.steam_id {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": "Cannot upload data for a different user"}))
+        ));
+    }
+    
+    let rows = delta.row_count();
+    match state.db.upload_cloud_sync_data(&delta.into_data(), dry_run).await {
+        Ok(merged) => {
+            if !dry_run {
+                tracing::info!(steam_id = %claims.steam_id, rows, "Cloud sync delta pushed");
+            }
+            Ok(SyncPushResponse { merged })
+        }
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to push changes: {:?}", e)}))
+        ))
+    }
+}
+
+fn upload_error(e: UploadError) -> (StatusCode, Json<serde_json::Value>) {
+    match e {
+        UploadError::NotFound => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": e.to_string()}))
+        ),
+        UploadError::OffsetMismatch { received_bytes } => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({"error": e.to_string(), "received_bytes": received_bytes}))
+        ),
+        UploadError::TooLarge => (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(serde_json::json!({"error": e.to_string()}))
+        ),
+        UploadError::Busy => (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(serde_json::json!({"error": e.to_string()}))
+        ),
+    }
+}
+
+/// Open a chunked upload session and negotiate the payload encoding
+#[utoipa::path(
+    post,
+    path = "/api/sync/upload/sessions",
+    tag = "sync",
+    request_body = UploadSessionRequest,
+    responses(
+        (status = 200, body = UploadSessionInfo),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn create_upload_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<UploadSessionRequest>,
+) -> Result<Json<UploadSessionInfo>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    let info = state.upload_sessions.create(&claims.steam_id, &request.encodings, request.dry_run);
+    Ok(Json(info))
+}
+
+/// Get the state of an upload session, used to resume after a failed chunk
+#[utoipa::path(
+    get,
+    path = "/api/sync/upload/sessions/{session_id}",
+    tag = "sync",
+    params(("session_id" = String, Path, description = "Upload session id")),
+    responses(
+        (status = 200, body = UploadSessionInfo),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_upload_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Result<Json<UploadSessionInfo>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    state.upload_sessions.info(&session_id, &claims.steam_id)
+        .map(Json)
+        .map_err(upload_error)
+}
+
+/// Append one chunk of the encoded payload at the given byte offset
+#[utoipa::path(
+    put,
+    path = "/api/sync/upload/sessions/{session_id}/chunks/{offset}",
+    tag = "sync",
+    params(
+        ("session_id" = String, Path, description = "Upload session id"),
+        ("offset" = usize, Path, description = "Offset of the chunk in the encoded payload"),
+    ),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, body = UploadSessionInfo),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn put_upload_chunk(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path((session_id, offset)): Path<(String, usize)>,
+    body: Bytes,
+) -> Result<Json<UploadSessionInfo>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    state.upload_sessions.append(&session_id, &claims.steam_id, offset, &body, state.upload_body_limit)
+        .map(Json)
+        .map_err(upload_error)
+}
+
+/// Finish an upload session: decode the payload and merge it like a delta push
+#[utoipa::path(
+    post,
+    path = "/api/sync/upload/sessions/{session_id}/complete",
+    tag = "sync",
+    params(("session_id" = String, Path, description = "Upload session id")),
+    request_body = UploadCompleteRequest,
+    responses(
+        (status = 200, body = SyncPushResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn complete_upload_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+    Json(request): Json<UploadCompleteRequest>,
+) -> Result<Json<SyncPushResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    let upload = state.upload_sessions.complete(&session_id, &claims.steam_id, request.total_bytes)
+        .map_err(upload_error)?;
+    
+    let bad_request = |error: String| (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": error})));
+    let decoded = crate::upload_sessions::decode_payload(upload.encoding, &upload.data, state.upload_body_limit)
+        .map_err(bad_request)?;
+    let delta: CloudSyncDelta = serde_json::from_slice(&decoded)
+        .map_err(|e| bad_request(format!("Invalid sync data: {}", e)))?;
+    
+    tracing::debug!(
+        steam_id = %claims.steam_id,
+        encoding = upload.encoding.label(),
+        encoded_bytes = upload.data.len(),
+        decoded_bytes = decoded.len(),
+        "Upload session completed"
+    );
+    merge_pushed_delta(&state, &claims, delta, upload.dry_run).await.map(Json)
+}
+
+/// Get the user's encrypted backup (null if there is none)
+#[utoipa::path(
+    get,
+    path = "/api/sync/encrypted",
+    tag = "sync",
+    responses(
+        (status = 200, body = Option<EncryptedSyncBlob>),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_encrypted_sync(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Option<EncryptedSyncBlob>>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    match state.db.get_encrypted_sync_blob(&claims.steam_id).await {
+        Ok(blob) => Ok(Json(blob)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get encrypted backup: {:?}", e)}))
+        ))
+    }
+}
+
+/// Store the user's encrypted backup; the server never sees its contents and
+/// deletes the plaintext history it replaces
+#[utoipa::path(
+    put,
+    path = "/api/sync/encrypted",
+    tag = "sync",
+    params(StoreEncryptedQuery),
+    request_body = EncryptedSyncBlob,
+    responses(
+        (status = 200, body = SuccessResponse),
+        (status = 409, description = "The stored backup has a different key check", body = ApiError),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn store_encrypted_sync(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<StoreEncryptedQuery>,
+    Json(blob): Json<EncryptedSyncBlob>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    if blob.version != ENCRYPTED_SYNC_VERSION {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!(
+                "Unsupported encrypted backup version {} (server supports {})",
+                blob.version, ENCRYPTED_SYNC_VERSION
+            )}))
+        ));
+    }
+    
+    let internal_error = |e: crate::db::DbError| (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({"error": format!("Failed to store encrypted backup: {:?}", e)}))
+    );
+    
+    if let Some(expected) = &query.expected_key_check {
+        let existing = state.db.get_encrypted_sync_blob(&claims.steam_id).await
+            .map_err(internal_error)?;
+        if let Some(existing) = existing.filter(|b| &b.key_check != expected) {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(serde_json::json!({
+                    "error": "Encrypted backup was re-encrypted with a different passphrase",
+                    "key_check": existing.key_check
+                }))
+            ));
+        }
+    }
+    
+    state.db.store_encrypted_sync_blob(&claims.steam_id, &blob).await
+        .map_err(internal_error)?;
+    tracing::info!(steam_id = %claims.steam_id, bytes = blob.ciphertext.len(), "Encrypted backup stored");
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// Delete the user's encrypted backup (used when turning encryption off)
+#[utoipa::path(
+    delete,
+    path = "/api/sync/encrypted",
+    tag = "sync",
+    responses(
+        (status = 200, body = SuccessResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn delete_encrypted_sync(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    match state.db.delete_encrypted_sync_blob(&claims.steam_id).await {
+        Ok(_) => Ok(Json(SuccessResponse { success: true })),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to delete encrypted backup: {:?}", e)}))
+        ))
+    }
+}
+
 /// Delete all user data from cloud
+#[utoipa::path(
+    delete,
+    path = "/api/sync/data",
+    tag = "sync",
+    responses(
+        (status = 200, body = SuccessResponse),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
 pub async fn delete_sync_data(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
-    let claims = extract_user(&headers, &state.jwt_secret)?;
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
     
-    match crate::db::delete_cloud_sync_data(&state.db_pool, &claims.steam_id).await {
+    match state.db.delete_cloud_sync_data(&claims.steam_id).await {
         Ok(_) => {
             tracing::info!(steam_id = %claims.steam_id, "Cloud sync data deleted");
-            Ok(Json(serde_json::json!({"success": true})))
+            Ok(Json(SuccessResponse { success: true }))
         }
         Err(e) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -317,3 +1766,192 @@ pub async fn delete_sync_data(
         ))
     }
 }
+
+/// Resolve a short_id to a user other than the caller, for follow changes
+async fn followed_user(
+    state: &AppState,
+    claims: &Claims,
+    short_id: &str,
+) -> Result<UserProfile, (StatusCode, Json<serde_json::Value>)> {
+    match state.db.get_user_by_short_id(short_id).await {
+        Ok(Some(user)) if user.steam_id == claims.steam_id => Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "You can't follow yourself"}))
+        )),
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "User not found"}))
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to look up user: {:?}", e)}))
+        ))
+    }
+}
+
+/// List the users the caller follows
+#[utoipa::path(
+    get,
+    path = "/api/follows",
+    tag = "social",
+    responses(
+        (status = 200, body = Vec<UserProfile>),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_following(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<UserProfile>>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    match state.db.get_following(&claims.steam_id).await {
+        Ok(users) => Ok(Json(users)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get followed users: {:?}", e)}))
+        ))
+    }
+}
+
+/// Follow a user by short_id; returns the updated list of followed users
+#[utoipa::path(
+    put,
+    path = "/api/follows/{short_id}",
+    tag = "social",
+    params(("short_id" = String, Path, description = "Guest link id of the user")),
+    responses(
+        (status = 200, body = Vec<UserProfile>),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn follow_user(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(short_id): Path<String>,
+) -> Result<Json<Vec<UserProfile>>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    let user = followed_user(&state, &claims, &short_id).await?;
+    
+    if let Err(e) = state.db.follow_user(&claims.steam_id, &user.steam_id).await {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to follow user: {:?}", e)}))
+        ));
+    }
+    get_following(State(state), headers).await
+}
+
+/// Stop following a user by short_id; returns the updated list of followed users
+#[utoipa::path(
+    delete,
+    path = "/api/follows/{short_id}",
+    tag = "social",
+    params(("short_id" = String, Path, description = "Guest link id of the user")),
+    responses(
+        (status = 200, body = Vec<UserProfile>),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn unfollow_user(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(short_id): Path<String>,
+) -> Result<Json<Vec<UserProfile>>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    let user = followed_user(&state, &claims, &short_id).await?;
+    
+    if let Err(e) = state.db.unfollow_user(&claims.steam_id, &user.steam_id).await {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to unfollow user: {:?}", e)}))
+        ));
+    }
+    get_following(State(state), headers).await
+}
+
+/// Recent achievements and first plays of followed users, newest first
+#[utoipa::path(
+    get,
+    path = "/api/feed",
+    tag = "social",
+    responses(
+        (status = 200, body = Vec<FeedEntry>),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_feed(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<FeedEntry>>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    match state.db.get_follow_feed(&claims.steam_id, crate::ws_handler::FEED_LIMIT).await {
+        Ok(entries) => Ok(Json(entries)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get feed: {:?}", e)}))
+        ))
+    }
+}
+
+/// Leaderboard among followed users or site-wide, from cached totals
+#[utoipa::path(
+    get,
+    path = "/api/leaderboards",
+    tag = "social",
+    params(LeaderboardQuery),
+    responses(
+        (status = 200, body = Leaderboard),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_leaderboard(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<Leaderboard>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    match state.db.get_leaderboard(&claims.steam_id, query.metric, query.scope, crate::ws_handler::LEADERBOARD_LIMIT).await {
+        Ok(leaderboard) => Ok(Json(leaderboard)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to get leaderboard: {:?}", e)}))
+        ))
+    }
+}
+
+/// Show or hide the caller on global leaderboards
+#[utoipa::path(
+    put,
+    path = "/api/leaderboards/opt-in",
+    tag = "social",
+    request_body = LeaderboardOptIn,
+    responses(
+        (status = 200, body = LeaderboardOptIn),
+        (status = "default", body = ApiError),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn set_leaderboard_opt_in(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<LeaderboardOptIn>,
+) -> Result<Json<LeaderboardOptIn>, (StatusCode, Json<serde_json::Value>)> {
+    let claims = extract_user(&headers, &state)?;
+    
+    match state.db.set_leaderboard_opt_in(&claims.steam_id, body.enabled).await {
+        Ok(()) => Ok(Json(LeaderboardOptIn { enabled: body.enabled })),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to update leaderboard opt-in: {:?}", e)}))
+        ))
+    }
+}
@@ -0,0 +1,75 @@
+//! Login sessions
+//!
+//! Logging in starts a session and hands out a short-lived access token (a
+//! JWT carrying the session id) and a refresh token. Every refresh replaces
+//! the refresh token; presenting a replaced one again revokes the session, as
+//! the token must have been copied. Revoked sessions are remembered in memory
+//! until their access tokens have expired, so [`crate::auth::verify_jwt`] can
+//! reject them without a database query.
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::db::DbError;
+use crate::repository::Repository;
+
+/// Lifetime of access tokens
+pub const ACCESS_TOKEN_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Sessions end after this many days without a refresh
+pub const SESSION_TTL_DAYS: i64 = 30;
+
+const REFRESH_TOKEN_LENGTH: usize = 48;
+
+pub fn generate_refresh_token() -> String {
+    let mut rng = rand::thread_rng();
+    (0..REFRESH_TOKEN_LENGTH)
+        .map(|_| rng.sample(rand::distributions::Alphanumeric) as char)
+        .collect()
+}
+
+/// Refresh tokens are only stored as hashes
+pub fn hash_refresh_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// When a session started or refreshed now ends
+pub fn session_expiry() -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::days(SESSION_TTL_DAYS)
+}
+
+/// Sessions revoked while their access tokens may still be valid
+#[derive(Default)]
+pub struct RevokedSessions {
+    revoked: Mutex<HashMap<i64, Instant>>,
+}
+
+impl RevokedSessions {
+    /// Sessions revoked recently enough to have unexpired access tokens
+    pub async fn load(db: &dyn Repository) -> Result<Self, DbError> {
+        let since = Utc::now() - chrono::Duration::from_std(ACCESS_TOKEN_TTL).unwrap_or_default();
+        let ids = db.get_revoked_session_ids(since).await?;
+        let revoked = Self::default();
+        revoked.insert(&ids);
+        Ok(revoked)
+    }
+
+    pub fn len(&self) -> usize {
+        self.revoked.lock().unwrap().len()
+    }
+
+    pub fn insert(&self, session_ids: &[i64]) {
+        let now = Instant::now();
+        let mut revoked = self.revoked.lock().unwrap();
+        revoked.retain(|_, at| now.duration_since(*at) < ACCESS_TOKEN_TTL);
+        revoked.extend(session_ids.iter().map(|id| (*id, now)));
+    }
+
+    pub fn contains(&self, session_id: i64) -> bool {
+        self.revoked.lock().unwrap().contains_key(&session_id)
+    }
+}
@@ -0,0 +1,1917 @@
+//! SQLite storage
+//!
+//! Keeps everything in a single database file, for self-hosted servers that
+//! don't want to run PostgreSQL, or in memory (`:memory:`) for tests. The
+//! queries mirror [`crate::db`]; the schema lives in `migrations/sqlite/` and
+//! its version in `PRAGMA user_version`.
+//!
+//! All queries share one connection behind a mutex and run on the calling
+//! task. They are short, and SQLite serializes writes anyway, so this is
+//! meant for small installs rather than busy public servers.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use overachiever_core::{Game, GameAchievement, GameRating, GameRatingAverage, AchievementTip, LogEntry, FeedEntry, Leaderboard, LeaderboardEntry, LeaderboardMetric, LeaderboardScope, GameRarity, AchievementRarity, AchievementDifficulty, AchievementFlag, AchievementFlags, AchievementFlagVotes, FLAG_VOTE_THRESHOLD, AchievementComment, ContentKind, ContentReport, CloudSyncData, CloudSyncStatus, SyncAchievement, SyncMergePreview, CloudSyncDelta, SyncCursor, EncryptedSyncBlob, UserProfile};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::db::{generate_short_id, DbError};
+use crate::migrations::MigrationError;
+use crate::pagination::{ListVersion, LogCursor, UserList};
+use crate::repository::Repository;
+
+/// Schema files, oldest first; `PRAGMA user_version` counts the applied ones
+const SQLITE_MIGRATIONS: &[&str] = &[
+    include_str!("../migrations/sqlite/0001_initial.sql"),
+];
+
+/// Schema version of an up-to-date database
+pub const SCHEMA_VERSION: i64 = SQLITE_MIGRATIONS.len() as i64;
+
+/// How long a query waits for another process holding the database file
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct SqliteRepository {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteRepository {
+    /// Open (or create) the database file; `:memory:` keeps it in memory
+    pub fn open(path: &Path) -> Result<Self, DbError> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        // In-memory databases answer "memory" and stay that way
+        conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap()
+    }
+
+    /// Number of schema files applied to the database
+    pub fn schema_version(&self) -> Result<i64, DbError> {
+        let version = self.conn().query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        Ok(version)
+    }
+
+    /// Versions of the schema files not applied yet
+    fn pending_migrations(&self) -> Result<Vec<i64>, MigrationError> {
+        let version = self.schema_version()?;
+        if version > SCHEMA_VERSION {
+            return Err(MigrationError::Unknown(((SCHEMA_VERSION + 1)..=version).collect()));
+        }
+        Ok(((version + 1)..=SCHEMA_VERSION).collect())
+    }
+
+    /// Fail if the schema is not up to date, without changing it
+    pub fn check_schema(&self) -> Result<(), MigrationError> {
+        let pending = self.pending_migrations()?;
+        if pending.is_empty() {
+            Ok(())
+        } else {
+            Err(MigrationError::Pending(pending))
+        }
+    }
+
+    /// Apply pending schema files and return their versions
+    pub fn migrate(&self) -> Result<Vec<i64>, MigrationError> {
+        let pending = self.pending_migrations()?;
+        let mut conn = self.conn();
+        for version in &pending {
+            let tx = conn.transaction().map_err(DbError::from)?;
+            tx.execute_batch(SQLITE_MIGRATIONS[*version as usize - 1]).map_err(DbError::from)?;
+            tx.pragma_update(None, "user_version", version).map_err(DbError::from)?;
+            tx.commit().map_err(DbError::from)?;
+        }
+        Ok(pending)
+    }
+}
+
+fn profile_from_row(row: &Row) -> rusqlite::Result<UserProfile> {
+    Ok(UserProfile {
+        steam_id: row.get::<_, i64>("steam_id")?.to_string(),
+        display_name: row.get("display_name")?,
+        avatar_url: row.get("avatar_url")?,
+        short_id: row.get("short_id")?,
+    })
+}
+
+/// Author of a rating, tip or comment, marked when it is the viewer
+fn author_from_row(row: &Row, viewer_steam_id: i64) -> rusqlite::Result<(UserProfile, bool)> {
+    let author = profile_from_row(row)?;
+    let is_yours = row.get::<_, i64>("steam_id")? == viewer_steam_id;
+    Ok((author, is_yours))
+}
+
+const GAME_COLUMNS: &str = r#"
+    appid, name, playtime_forever, rtime_last_played, img_icon_url,
+    added_at, achievements_total, achievements_unlocked, last_sync
+"#;
+
+fn game_from_row(row: &Row) -> rusqlite::Result<Game> {
+    Ok(Game {
+        appid: row.get::<_, i64>("appid")? as u64,
+        name: row.get("name")?,
+        playtime_forever: row.get::<_, i64>("playtime_forever")? as u32,
+        rtime_last_played: row.get::<_, Option<i64>>("rtime_last_played")?.map(|t| t as u32),
+        img_icon_url: row.get("img_icon_url")?,
+        added_at: row.get::<_, Option<DateTime<Utc>>>("added_at")?.unwrap_or_else(Utc::now),
+        achievements_total: row.get("achievements_total")?,
+        achievements_unlocked: row.get("achievements_unlocked")?,
+        last_achievement_scrape: row.get("last_sync")?,
+    })
+}
+
+fn run_history_from_row(row: &Row) -> rusqlite::Result<overachiever_core::RunHistory> {
+    Ok(overachiever_core::RunHistory {
+        id: row.get("id")?,
+        run_at: row.get("run_at")?,
+        total_games: row.get("total_games")?,
+        unplayed_games: row.get("unplayed_games")?,
+        unplayed_games_total: row.get("unplayed_games_total")?,
+    })
+}
+
+fn achievement_history_from_row(row: &Row) -> rusqlite::Result<overachiever_core::AchievementHistory> {
+    Ok(overachiever_core::AchievementHistory {
+        id: row.get("id")?,
+        recorded_at: row.get("recorded_at")?,
+        total_achievements: row.get("total_achievements")?,
+        unlocked_achievements: row.get("unlocked_achievements")?,
+        games_with_achievements: row.get("games_with_achievements")?,
+        avg_completion_percent: row.get::<_, f64>("avg_completion_percent")? as f32,
+    })
+}
+
+fn sync_achievement_from_row(row: &Row) -> rusqlite::Result<SyncAchievement> {
+    Ok(SyncAchievement {
+        appid: row.get::<_, i64>("appid")? as u64,
+        apiname: row.get("apiname")?,
+        achieved: row.get::<_, Option<bool>>("achieved")?.unwrap_or(false),
+        unlocktime: row.get("unlocktime")?,
+    })
+}
+
+/// Appids as a JSON array, for `IN (SELECT value FROM json_each(?))`
+fn appids_json(appids: &[u64]) -> String {
+    serde_json::to_string(appids).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn user_steam_id(conn: &Connection, short_id: &str) -> rusqlite::Result<Option<i64>> {
+    conn.query_row("SELECT steam_id FROM users WHERE short_id = ?1", params![short_id], |row| row.get(0))
+        .optional()
+}
+
+fn user_games(conn: &Connection, steam_id_int: i64) -> rusqlite::Result<Vec<Game>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM user_games WHERE steam_id = ?1 ORDER BY name",
+        GAME_COLUMNS
+    ))?;
+    let games = stmt.query_map(params![steam_id_int], game_from_row)?.collect();
+    games
+}
+
+fn game_achievements(conn: &Connection, steam_id_int: i64, appid: u64) -> rusqlite::Result<Vec<(GameAchievement, bool)>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT ua.appid, ua.apiname, s.display_name AS name, s.description,
+               s.icon, s.icon_gray, ua.achieved, ua.unlocktime
+        FROM user_achievements ua
+        LEFT JOIN achievement_schemas s ON ua.appid = s.appid AND ua.apiname = s.apiname
+        WHERE ua.steam_id = ?1 AND ua.appid = ?2
+        ORDER BY s.display_name
+        "#,
+    )?;
+    let achievements = stmt.query_map(params![steam_id_int, appid as i64], |row| {
+        let name: Option<String> = row.get("name")?;
+        let has_schema = name.is_some();
+        let achievement = GameAchievement {
+            appid: row.get::<_, i64>("appid")? as u64,
+            apiname: row.get("apiname")?,
+            name: name.unwrap_or_default(),
+            description: row.get("description")?,
+            icon: row.get::<_, Option<String>>("icon")?.unwrap_or_default(),
+            icon_gray: row.get::<_, Option<String>>("icon_gray")?.unwrap_or_default(),
+            achieved: row.get::<_, Option<bool>>("achieved")?.unwrap_or(false),
+            unlocktime: row.get("unlocktime")?,
+        };
+        Ok((achievement, has_schema))
+    })?.collect();
+    achievements
+}
+
+fn run_history(conn: &Connection, steam_id_int: i64) -> rusqlite::Result<Vec<overachiever_core::RunHistory>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, run_at, total_games, unplayed_games, unplayed_games_total
+        FROM run_history
+        WHERE steam_id = ?1
+        ORDER BY run_at
+        "#,
+    )?;
+    let history = stmt.query_map(params![steam_id_int], run_history_from_row)?.collect();
+    history
+}
+
+fn achievement_history(conn: &Connection, steam_id_int: i64) -> rusqlite::Result<Vec<overachiever_core::AchievementHistory>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, recorded_at, total_achievements, unlocked_achievements, games_with_achievements, avg_completion_percent
+        FROM achievement_history
+        WHERE steam_id = ?1
+        ORDER BY recorded_at
+        "#,
+    )?;
+    let history = stmt.query_map(params![steam_id_int], achievement_history_from_row)?.collect();
+    history
+}
+
+fn log_entry_from_row(row: &Row) -> rusqlite::Result<LogEntry> {
+    Ok(LogEntry::Achievement {
+        appid: row.get::<_, i64>("appid")? as u64,
+        game_name: row.get("game_name")?,
+        apiname: row.get("apiname")?,
+        achievement_name: row.get::<_, Option<String>>("achievement_name")?.unwrap_or_else(|| "Unknown".to_string()),
+        timestamp: row.get("unlocktime")?,
+        achievement_icon: row.get::<_, Option<String>>("achievement_icon")?.unwrap_or_default(),
+        game_icon_url: row.get("game_icon_url")?,
+    })
+}
+
+fn log_entries(conn: &Connection, steam_id_int: i64, limit: i32) -> rusqlite::Result<Vec<LogEntry>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT ua.appid, g.name AS game_name, ua.apiname, s.display_name AS achievement_name,
+               ua.unlocktime, s.icon AS achievement_icon, g.img_icon_url AS game_icon_url
+        FROM user_achievements ua
+        JOIN user_games g ON ua.steam_id = g.steam_id AND ua.appid = g.appid
+        LEFT JOIN achievement_schemas s ON ua.appid = s.appid AND ua.apiname = s.apiname
+        WHERE ua.steam_id = ?1 AND ua.achieved = 1 AND ua.unlocktime IS NOT NULL
+        ORDER BY ua.unlocktime DESC
+        LIMIT ?2
+        "#,
+    )?;
+    let entries = stmt.query_map(params![steam_id_int, limit as i64], log_entry_from_row)?.collect();
+    entries
+}
+
+fn all_user_achievements(conn: &Connection, steam_id_int: i64) -> rusqlite::Result<Vec<SyncAchievement>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT appid, apiname, achieved, unlocktime
+        FROM user_achievements
+        WHERE steam_id = ?1
+        ORDER BY appid, apiname
+        "#,
+    )?;
+    let achievements = stmt.query_map(params![steam_id_int], sync_achievement_from_row)?.collect();
+    achievements
+}
+
+fn cloud_sync_data(conn: &Connection, steam_id: &str) -> rusqlite::Result<CloudSyncData> {
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    Ok(CloudSyncData {
+        steam_id: steam_id.to_string(),
+        games: user_games(conn, steam_id_int)?,
+        achievements: all_user_achievements(conn, steam_id_int)?,
+        run_history: run_history(conn, steam_id_int)?,
+        achievement_history: achievement_history(conn, steam_id_int)?,
+        exported_at: Utc::now(),
+    })
+}
+
+/// The stored rows a sync bundle touches: its games and achievements by key and
+/// its history entries by timestamp
+fn stored_sync_rows(conn: &Connection, steam_id_int: i64, data: &CloudSyncData) -> rusqlite::Result<CloudSyncData> {
+    let mut stored = CloudSyncData {
+        steam_id: data.steam_id.clone(),
+        games: Vec::new(),
+        achievements: Vec::new(),
+        run_history: Vec::new(),
+        achievement_history: Vec::new(),
+        exported_at: Utc::now(),
+    };
+
+    let mut stmt = conn.prepare_cached(&format!("SELECT {GAME_COLUMNS} FROM user_games WHERE steam_id = ?1 AND appid = ?2"))?;
+    for game in &data.games {
+        stored.games.extend(stmt.query_row(params![steam_id_int, game.appid as i64], game_from_row).optional()?);
+    }
+
+    let mut stmt = conn.prepare_cached(
+        "SELECT appid, apiname, achieved, unlocktime FROM user_achievements WHERE steam_id = ?1 AND appid = ?2 AND apiname = ?3",
+    )?;
+    for ach in &data.achievements {
+        stored.achievements.extend(stmt.query_row(params![steam_id_int, ach.appid as i64, ach.apiname], sync_achievement_from_row).optional()?);
+    }
+
+    let mut stmt = conn.prepare_cached(
+        r#"
+        SELECT id, run_at, total_games, unplayed_games, unplayed_games_total
+        FROM run_history
+        WHERE steam_id = ?1 AND run_at = ?2
+        "#,
+    )?;
+    for rh in &data.run_history {
+        stored.run_history.extend(stmt.query_row(params![steam_id_int, rh.run_at], run_history_from_row).optional()?);
+    }
+
+    let mut stmt = conn.prepare_cached(
+        r#"
+        SELECT id, recorded_at, total_achievements, unlocked_achievements, games_with_achievements, avg_completion_percent
+        FROM achievement_history
+        WHERE steam_id = ?1 AND recorded_at = ?2
+        "#,
+    )?;
+    for ah in &data.achievement_history {
+        stored.achievement_history.extend(stmt.query_row(params![steam_id_int, ah.recorded_at], achievement_history_from_row).optional()?);
+    }
+
+    Ok(stored)
+}
+
+/// Generate a unique short_id by checking for collisions
+fn generate_unique_short_id(conn: &Connection) -> rusqlite::Result<String> {
+    loop {
+        let short_id = generate_short_id();
+        let exists = conn
+            .query_row("SELECT 1 FROM users WHERE short_id = ?1", params![short_id], |_| Ok(()))
+            .optional()?;
+        if exists.is_none() {
+            return Ok(short_id);
+        }
+    }
+}
+
+/// Make sure a user row exists before storing data that references it
+fn ensure_user(conn: &Connection, steam_id: &str) -> rusqlite::Result<()> {
+    let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+    conn.execute(
+        "INSERT INTO users (steam_id, display_name) VALUES (?1, ?2) ON CONFLICT (steam_id) DO NOTHING",
+        params![steam_id_int, format!("User {}", &steam_id[..8.min(steam_id.len())])],
+    )?;
+    Ok(())
+}
+
+const TIP_SELECT: &str = r#"
+    SELECT t.id, t.appid, t.apiname, t.difficulty, t.tip, t.created_at, t.edited_at,
+           u.steam_id, u.display_name, u.avatar_url, u.short_id,
+           COALESCE(SUM(v.vote), 0) AS score,
+           COALESCE(MAX(v.vote) FILTER (WHERE v.steam_id = ?3), 0) AS your_vote
+    FROM achievement_tips t
+    JOIN users u ON u.steam_id = t.steam_id
+    LEFT JOIN achievement_tip_votes v ON v.tip_id = t.id
+"#;
+
+fn tip_from_row(row: &Row, viewer_steam_id: i64) -> rusqlite::Result<AchievementTip> {
+    let (author, is_yours) = author_from_row(row, viewer_steam_id)?;
+    Ok(AchievementTip {
+        id: row.get("id")?,
+        author,
+        appid: row.get::<_, i64>("appid")? as u64,
+        apiname: row.get("apiname")?,
+        difficulty: row.get::<_, Option<i64>>("difficulty")?.unwrap_or(0) as u8,
+        tip: row.get("tip")?,
+        created_at: row.get("created_at")?,
+        edited_at: row.get("edited_at")?,
+        score: row.get("score")?,
+        your_vote: row.get::<_, i64>("your_vote")? as i8,
+        is_yours,
+    })
+}
+
+/// Columns of a comment with its author and tagged achievements (as a JSON
+/// array of `[appid, apiname]` pairs); callers add WHERE on `c`, and GROUP BY
+/// / ORDER BY
+const COMMENT_SELECT: &str = r#"
+    SELECT c.id, c.comment, c.created_at, c.edited_at,
+           u.steam_id, u.display_name, u.avatar_url, u.short_id,
+           json_group_array(json_array(t.appid, t.apiname) ORDER BY t.appid, t.apiname) AS tags
+    FROM achievement_comments c
+    JOIN users u ON u.steam_id = c.steam_id
+    JOIN achievement_comment_tags t ON t.comment_id = c.id
+"#;
+
+fn comment_from_row(row: &Row, viewer_steam_id: i64) -> rusqlite::Result<AchievementComment> {
+    let (author, is_yours) = author_from_row(row, viewer_steam_id)?;
+    let tags: String = row.get("tags")?;
+    let achievements: Vec<(u64, String)> = serde_json::from_str(&tags)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
+    Ok(AchievementComment {
+        id: row.get("id")?,
+        author,
+        achievements,
+        comment: row.get("comment")?,
+        created_at: row.get("created_at")?,
+        edited_at: row.get("edited_at")?,
+        is_yours,
+    })
+}
+
+/// Get a comment by id, as seen by a user
+fn achievement_comment(conn: &Connection, id: i64, viewer_steam_id: i64) -> rusqlite::Result<Option<AchievementComment>> {
+    conn.query_row(
+        &format!("{} WHERE c.id = ?1 GROUP BY c.id", COMMENT_SELECT),
+        params![id],
+        |row| comment_from_row(row, viewer_steam_id),
+    ).optional()
+}
+
+/// Table holding a kind of community content
+fn content_table(kind: ContentKind) -> &'static str {
+    match kind {
+        ContentKind::Comment => "achievement_comments",
+        ContentKind::Tip => "achievement_tips",
+        ContentKind::Review => "game_ratings",
+    }
+}
+
+/// Close all open reports of an item after an admin acted on it
+fn resolve_reports(conn: &Connection, kind: ContentKind, content_id: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE content_reports SET resolved_at = ?3 WHERE kind = ?1 AND content_id = ?2 AND resolved_at IS NULL",
+        params![kind.as_str(), content_id, Utc::now()],
+    )?;
+    Ok(())
+}
+
+/// Start of the current month, for the monthly leaderboard
+fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0).single().unwrap_or(now)
+}
+
+#[async_trait]
+impl Repository for SqliteRepository {
+    async fn get_user_by_short_id(&self, short_id: &str) -> Result<Option<UserProfile>, DbError> {
+        let conn = self.conn();
+        let profile = conn.query_row(
+            "SELECT steam_id, display_name, avatar_url, short_id FROM users WHERE short_id = ?1",
+            params![short_id],
+            profile_from_row,
+        ).optional()?;
+        Ok(profile)
+    }
+
+    async fn get_user_games_by_short_id(&self, short_id: &str) -> Result<Option<Vec<Game>>, DbError> {
+        let conn = self.conn();
+        let Some(steam_id_int) = user_steam_id(&conn, short_id)? else {
+            return Ok(None);
+        };
+        Ok(Some(user_games(&conn, steam_id_int)?))
+    }
+
+    async fn get_game_achievements_by_short_id(&self, short_id: &str, appid: u64) -> Result<Option<Vec<GameAchievement>>, DbError> {
+        let conn = self.conn();
+        let Some(steam_id_int) = user_steam_id(&conn, short_id)? else {
+            return Ok(None);
+        };
+        // Guests see the API name of achievements without a cached schema
+        let achievements = game_achievements(&conn, steam_id_int, appid)?
+            .into_iter()
+            .map(|(mut achievement, has_schema)| {
+                if !has_schema {
+                    achievement.name = achievement.apiname.clone();
+                }
+                achievement
+            })
+            .collect();
+        Ok(Some(achievements))
+    }
+
+    async fn get_history_by_short_id(&self, short_id: &str) -> Result<Option<(Vec<overachiever_core::RunHistory>, Vec<overachiever_core::AchievementHistory>, Vec<LogEntry>)>, DbError> {
+        let conn = self.conn();
+        let Some(steam_id_int) = user_steam_id(&conn, short_id)? else {
+            return Ok(None);
+        };
+        Ok(Some((
+            run_history(&conn, steam_id_int)?,
+            achievement_history(&conn, steam_id_int)?,
+            log_entries(&conn, steam_id_int, 100)?,
+        )))
+    }
+
+    async fn get_user_games(&self, steam_id: &str) -> Result<Vec<Game>, DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        Ok(user_games(&self.conn(), steam_id_int)?)
+    }
+
+    async fn get_game_achievements(&self, steam_id: &str, appid: u64) -> Result<Vec<GameAchievement>, DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let achievements = game_achievements(&self.conn(), steam_id_int, appid)?;
+        Ok(achievements.into_iter().map(|(achievement, _)| achievement).collect())
+    }
+
+    async fn get_community_ratings(&self, appid: u64, viewer: Option<&str>) -> Result<Vec<GameRating>, DbError> {
+        let conn = self.conn();
+        let viewer_steam_id: i64 = viewer.and_then(|s| s.parse().ok()).unwrap_or(0);
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT r.id, r.appid, r.rating, r.comment, r.created_at, r.updated_at,
+                   u.steam_id, u.display_name, u.avatar_url, u.short_id
+            FROM game_ratings r
+            JOIN users u ON u.steam_id = r.steam_id
+            WHERE r.appid = ?1 AND r.hidden_at IS NULL
+            ORDER BY r.updated_at DESC
+            "#,
+        )?;
+        let ratings = stmt.query_map(params![appid as i64], |row| {
+            let (author, is_yours) = author_from_row(row, viewer_steam_id)?;
+            Ok(GameRating {
+                id: row.get("id")?,
+                author,
+                appid: row.get::<_, i64>("appid")? as u64,
+                rating: row.get::<_, i64>("rating")? as u8,
+                comment: row.get("comment")?,
+                created_at: row.get("created_at")?,
+                updated_at: row.get("updated_at")?,
+                is_yours,
+            })
+        })?.collect::<Result<_, _>>()?;
+        Ok(ratings)
+    }
+
+    async fn upsert_rating(&self, steam_id: &str, appid: u64, rating: u8, comment: Option<&str>) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            r#"
+            INSERT INTO game_ratings (steam_id, appid, rating, comment, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+            ON CONFLICT (steam_id, appid) DO UPDATE SET
+                rating = excluded.rating,
+                comment = excluded.comment,
+                updated_at = excluded.updated_at
+            "#,
+            params![steam_id_int, appid as i64, rating as i64, comment, Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    async fn get_game_rating_averages(&self, appids: &[u64]) -> Result<Vec<GameRatingAverage>, DbError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT appid, AVG(rating) AS avg_rating, COUNT(*) AS rating_count
+            FROM game_ratings
+            WHERE appid IN (SELECT value FROM json_each(?1)) AND hidden_at IS NULL
+            GROUP BY appid
+            "#,
+        )?;
+        let averages = stmt.query_map(params![appids_json(appids)], |row| {
+            Ok(GameRatingAverage {
+                appid: row.get::<_, i64>("appid")? as u64,
+                avg_rating: row.get::<_, f64>("avg_rating")? as f32,
+                rating_count: row.get("rating_count")?,
+            })
+        })?.collect::<Result<_, _>>()?;
+        Ok(averages)
+    }
+
+    async fn get_achievement_tips(&self, appid: u64, apiname: &str, viewer: Option<&str>) -> Result<Vec<AchievementTip>, DbError> {
+        let conn = self.conn();
+        let viewer_steam_id: i64 = viewer.and_then(|s| s.parse().ok()).unwrap_or(0);
+
+        let mut stmt = conn.prepare(&format!(
+            r#"{}
+            WHERE t.appid = ?1 AND t.apiname = ?2 AND t.hidden_at IS NULL
+            GROUP BY t.id
+            ORDER BY score DESC, t.created_at DESC
+            "#,
+            TIP_SELECT
+        ))?;
+        let tips = stmt
+            .query_map(params![appid as i64, apiname, viewer_steam_id], |row| tip_from_row(row, viewer_steam_id))?
+            .collect::<Result<_, _>>()?;
+        Ok(tips)
+    }
+
+    async fn upsert_achievement_tip(&self, steam_id: &str, appid: u64, apiname: &str, difficulty: u8, tip: &str) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            r#"
+            INSERT INTO achievement_tips (steam_id, appid, apiname, difficulty, tip, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT (steam_id, appid, apiname)
+            DO UPDATE SET difficulty = excluded.difficulty, tip = excluded.tip, edited_at = excluded.created_at
+            "#,
+            params![steam_id_int, appid as i64, apiname, difficulty as i64, tip, Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    async fn vote_achievement_tip(&self, steam_id: &str, tip_id: i64, vote: i8) -> Result<Option<(u64, String)>, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+
+        let tip = conn.query_row(
+            "SELECT appid, apiname FROM achievement_tips WHERE id = ?1 AND steam_id <> ?2 AND hidden_at IS NULL",
+            params![tip_id, steam_id_int],
+            |row| Ok((row.get::<_, i64>(0)? as u64, row.get::<_, String>(1)?)),
+        ).optional()?;
+        let Some(tip) = tip else {
+            return Ok(None);
+        };
+
+        if vote == 0 {
+            conn.execute(
+                "DELETE FROM achievement_tip_votes WHERE tip_id = ?1 AND steam_id = ?2",
+                params![tip_id, steam_id_int],
+            )?;
+        } else {
+            conn.execute(
+                r#"
+                INSERT INTO achievement_tip_votes (tip_id, steam_id, vote, created_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT (tip_id, steam_id) DO UPDATE SET vote = excluded.vote, created_at = excluded.created_at
+                "#,
+                params![tip_id, steam_id_int, vote.signum() as i64, Utc::now()],
+            )?;
+        }
+        Ok(Some(tip))
+    }
+
+    async fn get_or_create_user(&self, steam_id: &str, display_name: Option<&str>, avatar_url: Option<&str>) -> Result<UserProfile, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let now = Utc::now();
+
+        let existing = conn.query_row(
+            "SELECT display_name, avatar_url, short_id FROM users WHERE steam_id = ?1",
+            params![steam_id_int],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, Option<String>>(2)?)),
+        ).optional()?;
+
+        if let Some((stored_name, stored_avatar, short_id)) = existing {
+            conn.execute(
+                r#"
+                UPDATE users SET
+                    display_name = COALESCE(?2, display_name),
+                    avatar_url = COALESCE(?3, avatar_url),
+                    last_seen = ?4
+                WHERE steam_id = ?1
+                "#,
+                params![steam_id_int, display_name, avatar_url, now],
+            )?;
+
+            let short_id = match short_id {
+                Some(short_id) => short_id,
+                None => {
+                    let short_id = generate_unique_short_id(&conn)?;
+                    conn.execute("UPDATE users SET short_id = ?2 WHERE steam_id = ?1", params![steam_id_int, short_id])?;
+                    short_id
+                }
+            };
+            return Ok(UserProfile {
+                steam_id: steam_id.to_string(),
+                display_name: display_name.map(str::to_string).unwrap_or(stored_name),
+                avatar_url: avatar_url.map(str::to_string).or(stored_avatar),
+                short_id: Some(short_id),
+            });
+        }
+
+        let short_id = generate_unique_short_id(&conn)?;
+        let display_name = display_name.map(str::to_string)
+            .unwrap_or_else(|| format!("User {}", &steam_id[..8.min(steam_id.len())]));
+        conn.execute(
+            r#"
+            INSERT INTO users (steam_id, display_name, avatar_url, short_id, created_at, last_seen)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+            "#,
+            params![steam_id_int, display_name, avatar_url, short_id, now],
+        )?;
+
+        Ok(UserProfile {
+            steam_id: steam_id.to_string(),
+            display_name,
+            avatar_url: avatar_url.map(str::to_string),
+            short_id: Some(short_id),
+        })
+    }
+
+    async fn upsert_games(&self, steam_id: &str, games: &[overachiever_core::SteamGame]) -> Result<usize, DbError> {
+        let mut conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let now = Utc::now();
+
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                r#"
+                INSERT INTO user_games (steam_id, appid, name, playtime_forever, rtime_last_played, img_icon_url, added_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                ON CONFLICT (steam_id, appid) DO UPDATE SET
+                    name = excluded.name,
+                    playtime_forever = excluded.playtime_forever,
+                    rtime_last_played = excluded.rtime_last_played,
+                    img_icon_url = excluded.img_icon_url
+                "#,
+            )?;
+            for game in games {
+                stmt.execute(params![
+                    steam_id_int,
+                    game.appid as i64,
+                    game.name,
+                    game.playtime_forever as i64,
+                    game.rtime_last_played.map(|t| t as i64),
+                    game.img_icon_url,
+                    now,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(games.len())
+    }
+
+    async fn update_game_achievements(&self, steam_id: &str, appid: u64, total: i32, unlocked: i32) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            r#"
+            UPDATE user_games
+            SET achievements_total = ?3, achievements_unlocked = ?4, last_sync = ?5
+            WHERE steam_id = ?1 AND appid = ?2
+            "#,
+            params![steam_id_int, appid as i64, total, unlocked, Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    async fn upsert_achievement_schema(&self, appid: u64, schema: &overachiever_core::AchievementSchema) -> Result<(), DbError> {
+        self.conn().execute(
+            r#"
+            INSERT INTO achievement_schemas (appid, apiname, display_name, description, icon, icon_gray)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT (appid, apiname) DO UPDATE SET
+                display_name = excluded.display_name,
+                description = excluded.description,
+                icon = excluded.icon,
+                icon_gray = excluded.icon_gray
+            "#,
+            params![appid as i64, schema.name, schema.display_name, schema.description, schema.icon, schema.icongray],
+        )?;
+        Ok(())
+    }
+
+    async fn upsert_user_achievement(&self, steam_id: &str, appid: u64, achievement: &overachiever_core::Achievement) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let unlocktime: Option<DateTime<Utc>> = if achievement.unlocktime > 0 {
+            DateTime::from_timestamp(achievement.unlocktime as i64, 0)
+        } else {
+            None
+        };
+        self.conn().execute(
+            r#"
+            INSERT INTO user_achievements (steam_id, appid, apiname, achieved, unlocktime)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT (steam_id, appid, apiname) DO UPDATE SET
+                achieved = excluded.achieved,
+                unlocktime = COALESCE(excluded.unlocktime, user_achievements.unlocktime)
+            "#,
+            params![steam_id_int, appid as i64, achievement.apiname, achievement.achieved == 1, unlocktime],
+        )?;
+        Ok(())
+    }
+
+    async fn get_run_history(&self, steam_id: &str) -> Result<Vec<overachiever_core::RunHistory>, DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        Ok(run_history(&self.conn(), steam_id_int)?)
+    }
+
+    async fn get_achievement_history(&self, steam_id: &str) -> Result<Vec<overachiever_core::AchievementHistory>, DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        Ok(achievement_history(&self.conn(), steam_id_int)?)
+    }
+
+    async fn insert_run_history(&self, steam_id: &str, total_games: i32, unplayed_games_total: i32) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            r#"
+            INSERT INTO run_history (steam_id, run_at, total_games, unplayed_games, unplayed_games_total)
+            VALUES (?1, ?2, ?3, 0, ?4)
+            "#,
+            params![steam_id_int, Utc::now(), total_games, unplayed_games_total],
+        )?;
+        Ok(())
+    }
+
+    async fn update_latest_run_history_unplayed(&self, steam_id: &str, unplayed_games: i32) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            r#"
+            UPDATE run_history
+            SET unplayed_games = ?1
+            WHERE steam_id = ?2 AND id = (SELECT MAX(id) FROM run_history WHERE steam_id = ?2)
+            "#,
+            params![unplayed_games, steam_id_int],
+        )?;
+        Ok(())
+    }
+
+    async fn update_run_history_total(&self, steam_id: &str, total_games: i32) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            r#"
+            UPDATE run_history
+            SET total_games = ?1
+            WHERE steam_id = ?2 AND id = (SELECT MAX(id) FROM run_history WHERE steam_id = ?2)
+            "#,
+            params![total_games, steam_id_int],
+        )?;
+        Ok(())
+    }
+
+    async fn backfill_run_history_unplayed(&self, steam_id: &str, current_unplayed: i32) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            "UPDATE run_history SET unplayed_games = ?1 WHERE steam_id = ?2 AND unplayed_games = 0",
+            params![current_unplayed, steam_id_int],
+        )?;
+        Ok(())
+    }
+
+    async fn insert_achievement_history(&self, steam_id: &str, total_achievements: i32, unlocked_achievements: i32, games_with_achievements: i32, avg_completion_percent: f32) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            r#"
+            INSERT INTO achievement_history (steam_id, recorded_at, total_achievements, unlocked_achievements, games_with_achievements, avg_completion_percent)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+            params![steam_id_int, Utc::now(), total_achievements, unlocked_achievements, games_with_achievements, avg_completion_percent as f64],
+        )?;
+        Ok(())
+    }
+
+    async fn get_log_entries(&self, steam_id: &str, limit: i32) -> Result<Vec<LogEntry>, DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        Ok(log_entries(&self.conn(), steam_id_int, limit)?)
+    }
+
+    async fn get_list_version(&self, steam_id: &str, list: UserList) -> Result<ListVersion, DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let query = match list {
+            UserList::Games => "SELECT COUNT(*), COALESCE(MAX(sync_version), 0) FROM user_games WHERE steam_id = ?1",
+            UserList::RunHistory => "SELECT COUNT(*), COALESCE(MAX(sync_version), 0) FROM run_history WHERE steam_id = ?1",
+            UserList::AchievementHistory => "SELECT COUNT(*), COALESCE(MAX(sync_version), 0) FROM achievement_history WHERE steam_id = ?1",
+            // Entries come from unlocked achievements joined with their game
+            UserList::Log => r#"
+                SELECT
+                    (SELECT COUNT(*) FROM user_achievements WHERE steam_id = ?1 AND achieved = 1 AND unlocktime IS NOT NULL)
+                    + (SELECT COUNT(*) FROM user_games WHERE steam_id = ?1),
+                    MAX(
+                        (SELECT COALESCE(MAX(sync_version), 0) FROM user_achievements WHERE steam_id = ?1),
+                        (SELECT COALESCE(MAX(sync_version), 0) FROM user_games WHERE steam_id = ?1)
+                    )
+                "#,
+        };
+        let version = self.conn().query_row(query, params![steam_id_int], |row| {
+            Ok(ListVersion { rows: row.get(0)?, sync_version: row.get(1)? })
+        })?;
+        Ok(version)
+    }
+
+    async fn get_user_games_page(&self, steam_id: &str, after: Option<u64>, limit: i32) -> Result<Vec<Game>, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM user_games WHERE steam_id = ?1 AND (?2 IS NULL OR appid > ?2) ORDER BY appid LIMIT ?3",
+            GAME_COLUMNS
+        ))?;
+        let games = stmt.query_map(params![steam_id_int, after.map(|appid| appid as i64), limit as i64], game_from_row)?
+            .collect::<Result<_, _>>()?;
+        Ok(games)
+    }
+
+    async fn get_run_history_page(&self, steam_id: &str, after: Option<i64>, limit: i32) -> Result<Vec<overachiever_core::RunHistory>, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, run_at, total_games, unplayed_games, unplayed_games_total
+            FROM run_history
+            WHERE steam_id = ?1 AND (?2 IS NULL OR id > ?2)
+            ORDER BY id
+            LIMIT ?3
+            "#,
+        )?;
+        let history = stmt.query_map(params![steam_id_int, after, limit as i64], run_history_from_row)?
+            .collect::<Result<_, _>>()?;
+        Ok(history)
+    }
+
+    async fn get_achievement_history_page(&self, steam_id: &str, after: Option<i64>, limit: i32) -> Result<Vec<overachiever_core::AchievementHistory>, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, recorded_at, total_achievements, unlocked_achievements, games_with_achievements, avg_completion_percent
+            FROM achievement_history
+            WHERE steam_id = ?1 AND (?2 IS NULL OR id > ?2)
+            ORDER BY id
+            LIMIT ?3
+            "#,
+        )?;
+        let history = stmt.query_map(params![steam_id_int, after, limit as i64], achievement_history_from_row)?
+            .collect::<Result<_, _>>()?;
+        Ok(history)
+    }
+
+    async fn get_log_page(&self, steam_id: &str, after: Option<&LogCursor>, limit: i32) -> Result<Vec<LogEntry>, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        // Timestamps are stored as UTC text of one format, which sorts by time
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT ua.appid, g.name AS game_name, ua.apiname, s.display_name AS achievement_name,
+                   ua.unlocktime, s.icon AS achievement_icon, g.img_icon_url AS game_icon_url
+            FROM user_achievements ua
+            JOIN user_games g ON ua.steam_id = g.steam_id AND ua.appid = g.appid
+            LEFT JOIN achievement_schemas s ON ua.appid = s.appid AND ua.apiname = s.apiname
+            WHERE ua.steam_id = ?1 AND ua.achieved = 1 AND ua.unlocktime IS NOT NULL
+              AND (?2 IS NULL
+                   OR ua.unlocktime < ?2
+                   OR (ua.unlocktime = ?2 AND (ua.appid, ua.apiname) > (?3, ?4)))
+            ORDER BY ua.unlocktime DESC, ua.appid, ua.apiname
+            LIMIT ?5
+            "#,
+        )?;
+        let entries = stmt.query_map(
+            params![
+                steam_id_int,
+                after.map(|cursor| cursor.unlocktime),
+                after.map(|cursor| cursor.appid as i64),
+                after.map(|cursor| cursor.apiname.as_str()),
+                limit as i64,
+            ],
+            log_entry_from_row,
+        )?.collect::<Result<_, _>>()?;
+        Ok(entries)
+    }
+
+    async fn follow_user(&self, steam_id: &str, followed_steam_id: &str) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let followed_int: i64 = followed_steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            "INSERT INTO follows (follower_steam_id, followed_steam_id) VALUES (?1, ?2) ON CONFLICT DO NOTHING",
+            params![steam_id_int, followed_int],
+        )?;
+        Ok(())
+    }
+
+    async fn unfollow_user(&self, steam_id: &str, followed_steam_id: &str) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let followed_int: i64 = followed_steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            "DELETE FROM follows WHERE follower_steam_id = ?1 AND followed_steam_id = ?2",
+            params![steam_id_int, followed_int],
+        )?;
+        Ok(())
+    }
+
+    async fn get_following(&self, steam_id: &str) -> Result<Vec<UserProfile>, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT u.steam_id, u.display_name, u.avatar_url, u.short_id
+            FROM follows f
+            JOIN users u ON u.steam_id = f.followed_steam_id
+            WHERE f.follower_steam_id = ?1
+            ORDER BY LOWER(u.display_name)
+            "#,
+        )?;
+        let following = stmt.query_map(params![steam_id_int], profile_from_row)?.collect::<Result<_, _>>()?;
+        Ok(following)
+    }
+
+    async fn get_follow_feed(&self, steam_id: &str, limit: i32) -> Result<Vec<FeedEntry>, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT activity.*, u.display_name, u.avatar_url, u.short_id FROM (
+                SELECT 'achievement' AS kind, ua.steam_id, ua.appid, g.name AS game_name, ua.apiname,
+                       s.display_name AS achievement_name, ua.unlocktime AS timestamp,
+                       s.icon AS achievement_icon, g.img_icon_url AS game_icon_url
+                FROM follows f
+                JOIN user_achievements ua ON ua.steam_id = f.followed_steam_id
+                JOIN user_games g ON ua.steam_id = g.steam_id AND ua.appid = g.appid
+                LEFT JOIN achievement_schemas s ON ua.appid = s.appid AND ua.apiname = s.apiname
+                WHERE f.follower_steam_id = ?1 AND ua.achieved = 1 AND ua.unlocktime IS NOT NULL
+                UNION ALL
+                SELECT 'first_play' AS kind, fp.steam_id, fp.appid, g.name AS game_name, NULL AS apiname,
+                       NULL AS achievement_name, fp.played_at AS timestamp,
+                       NULL AS achievement_icon, g.img_icon_url AS game_icon_url
+                FROM follows f
+                JOIN first_plays fp ON fp.steam_id = f.followed_steam_id
+                JOIN user_games g ON fp.steam_id = g.steam_id AND fp.appid = g.appid
+                WHERE f.follower_steam_id = ?1
+            ) activity
+            JOIN users u ON u.steam_id = activity.steam_id
+            ORDER BY activity.timestamp DESC
+            LIMIT ?2
+            "#,
+        )?;
+        let entries = stmt.query_map(params![steam_id_int, limit as i64], |row| {
+            let user = profile_from_row(row)?;
+            let kind: String = row.get("kind")?;
+            let entry = if kind == "first_play" {
+                LogEntry::FirstPlay {
+                    appid: row.get::<_, i64>("appid")? as u64,
+                    game_name: row.get("game_name")?,
+                    timestamp: row.get("timestamp")?,
+                    game_icon_url: row.get("game_icon_url")?,
+                }
+            } else {
+                LogEntry::Achievement {
+                    appid: row.get::<_, i64>("appid")? as u64,
+                    game_name: row.get("game_name")?,
+                    apiname: row.get::<_, Option<String>>("apiname")?.unwrap_or_default(),
+                    achievement_name: row.get::<_, Option<String>>("achievement_name")?.unwrap_or_else(|| "Unknown".to_string()),
+                    timestamp: row.get("timestamp")?,
+                    achievement_icon: row.get::<_, Option<String>>("achievement_icon")?.unwrap_or_default(),
+                    game_icon_url: row.get("game_icon_url")?,
+                }
+            };
+            Ok(FeedEntry { user, entry })
+        })?.collect::<Result<_, _>>()?;
+        Ok(entries)
+    }
+
+    async fn steam_percentages_stale(&self, appid: u64, max_age_hours: i64) -> Result<bool, DbError> {
+        let fresh_since = Utc::now() - chrono::Duration::hours(max_age_hours);
+        let row = self.conn().query_row(
+            "SELECT 1 FROM steam_percentages_fetched WHERE appid = ?1 AND fetched_at > ?2",
+            params![appid as i64, fresh_since],
+            |_| Ok(()),
+        ).optional()?;
+        Ok(row.is_none())
+    }
+
+    async fn store_steam_percentages(&self, appid: u64, percentages: &[(String, f32)]) -> Result<(), DbError> {
+        let mut conn = self.conn();
+        let appid_int = appid as i64;
+
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM steam_achievement_percentages WHERE appid = ?1", params![appid_int])?;
+        for (apiname, percent) in percentages {
+            tx.execute(
+                "INSERT INTO steam_achievement_percentages (appid, apiname, percent) VALUES (?1, ?2, ?3)",
+                params![appid_int, apiname, *percent as f64],
+            )?;
+        }
+        tx.execute(
+            r#"
+            INSERT INTO steam_percentages_fetched (appid, fetched_at) VALUES (?1, ?2)
+            ON CONFLICT (appid) DO UPDATE SET fetched_at = excluded.fetched_at
+            "#,
+            params![appid_int, Utc::now()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn mark_steam_percentages_fetched(&self, appid: u64) -> Result<(), DbError> {
+        self.conn().execute(
+            r#"
+            INSERT INTO steam_percentages_fetched (appid, fetched_at) VALUES (?1, ?2)
+            ON CONFLICT (appid) DO UPDATE SET fetched_at = excluded.fetched_at
+            "#,
+            params![appid as i64, Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    async fn get_game_rarity(&self, appid: u64) -> Result<GameRarity, DbError> {
+        let conn = self.conn();
+        let appid_int = appid as i64;
+
+        let owners: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM user_games WHERE appid = ?1 AND achievements_total IS NOT NULL",
+            params![appid_int],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT names.apiname, COALESCE(c.unlocked, 0) AS unlocked, sp.percent AS steam_percent
+            FROM (
+                SELECT apiname FROM achievement_schemas WHERE appid = ?1
+                UNION
+                SELECT apiname FROM steam_achievement_percentages WHERE appid = ?1
+            ) names
+            LEFT JOIN (
+                SELECT ua.apiname, COUNT(*) AS unlocked
+                FROM user_achievements ua
+                JOIN user_games g ON g.steam_id = ua.steam_id AND g.appid = ua.appid
+                WHERE ua.appid = ?1 AND ua.achieved = 1 AND g.achievements_total IS NOT NULL
+                GROUP BY ua.apiname
+            ) c ON c.apiname = names.apiname
+            LEFT JOIN steam_achievement_percentages sp ON sp.appid = ?1 AND sp.apiname = names.apiname
+            ORDER BY names.apiname
+            "#,
+        )?;
+        let achievements = stmt.query_map(params![appid_int], |row| {
+            let unlocked: i64 = row.get("unlocked")?;
+            Ok(AchievementRarity {
+                apiname: row.get("apiname")?,
+                community_percent: if owners > 0 { unlocked as f32 / owners as f32 * 100.0 } else { 0.0 },
+                steam_percent: row.get::<_, Option<f64>>("steam_percent")?.map(|p| p as f32),
+            })
+        })?.collect::<Result<_, _>>()?;
+
+        Ok(GameRarity { appid, community_owners: owners as u32, achievements })
+    }
+
+    async fn insert_achievement_comment(&self, steam_id: &str, achievements: &[(u64, String)], comment: &str) -> Result<AchievementComment, DbError> {
+        let mut conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO achievement_comments (steam_id, comment, created_at) VALUES (?1, ?2, ?3)",
+            params![steam_id_int, comment, Utc::now()],
+        )?;
+        let id = tx.last_insert_rowid();
+        for (appid, apiname) in achievements {
+            tx.execute(
+                "INSERT INTO achievement_comment_tags (comment_id, appid, apiname) VALUES (?1, ?2, ?3) ON CONFLICT DO NOTHING",
+                params![id, *appid as i64, apiname],
+            )?;
+        }
+        let comment = achievement_comment(&tx, id, steam_id_int)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        tx.commit()?;
+        Ok(comment)
+    }
+
+    async fn get_achievement_comments(&self, appid: u64, apiname: &str, viewer: Option<&str>, limit: i64) -> Result<Vec<AchievementComment>, DbError> {
+        let conn = self.conn();
+        let viewer_steam_id: i64 = viewer.and_then(|s| s.parse().ok()).unwrap_or(0);
+
+        let mut stmt = conn.prepare(&format!(
+            r#"{}
+            WHERE c.id IN (SELECT comment_id FROM achievement_comment_tags WHERE appid = ?1 AND apiname = ?2)
+              AND c.hidden_at IS NULL
+            GROUP BY c.id
+            ORDER BY c.created_at
+            LIMIT ?3
+            "#,
+            COMMENT_SELECT
+        ))?;
+        let comments = stmt
+            .query_map(params![appid as i64, apiname, limit], |row| comment_from_row(row, viewer_steam_id))?
+            .collect::<Result<_, _>>()?;
+        Ok(comments)
+    }
+
+    async fn update_achievement_comment(&self, steam_id: &str, id: i64, comment: &str) -> Result<Option<AchievementComment>, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+
+        let updated = conn.execute(
+            "UPDATE achievement_comments SET comment = ?3, edited_at = ?4 WHERE id = ?1 AND steam_id = ?2 AND hidden_at IS NULL",
+            params![id, steam_id_int, comment, Utc::now()],
+        )?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        Ok(achievement_comment(&conn, id, steam_id_int)?)
+    }
+
+    async fn delete_achievement_comment(&self, steam_id: &str, id: i64) -> Result<bool, DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let deleted = self.conn().execute(
+            "DELETE FROM achievement_comments WHERE id = ?1 AND steam_id = ?2",
+            params![id, steam_id_int],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    async fn get_achievement_averages(&self, appids: &[u64]) -> Result<Vec<AchievementDifficulty>, DbError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT appid, apiname, AVG(rating) AS avg_rating, COUNT(rating) AS rating_count
+            FROM achievement_ratings
+            WHERE appid IN (SELECT value FROM json_each(?1)) AND rating IS NOT NULL
+            GROUP BY appid, apiname
+            "#,
+        )?;
+        let averages = stmt.query_map(params![appids_json(appids)], |row| {
+            Ok(AchievementDifficulty {
+                appid: row.get::<_, i64>("appid")? as u64,
+                apiname: row.get("apiname")?,
+                avg_rating: row.get::<_, f64>("avg_rating")? as f32,
+                rating_count: row.get("rating_count")?,
+            })
+        })?.collect::<Result<_, _>>()?;
+        Ok(averages)
+    }
+
+    async fn get_achievement_flag_votes(&self, appids: &[u64]) -> Result<Vec<AchievementFlagVotes>, DbError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT appid, apiname,
+                   COUNT(*) FILTER (WHERE flags & 1 <> 0) AS missable,
+                   COUNT(*) FILTER (WHERE flags & 2 <> 0) AS bugged,
+                   COUNT(*) FILTER (WHERE flags & 4 <> 0) AS grind,
+                   COUNT(*) FILTER (WHERE flags & 8 <> 0) AS online_only
+            FROM achievement_ratings
+            WHERE appid IN (SELECT value FROM json_each(?1)) AND flags <> 0
+            GROUP BY appid, apiname
+            "#,
+        )?;
+        let mut rows = stmt.query(params![appids_json(appids)])?;
+
+        let mut votes = Vec::new();
+        while let Some(row) = rows.next()? {
+            let appid: i64 = row.get("appid")?;
+            let apiname: String = row.get("apiname")?;
+            for (flag, column) in [
+                (AchievementFlag::Missable, "missable"),
+                (AchievementFlag::Bugged, "bugged"),
+                (AchievementFlag::Grind, "grind"),
+                (AchievementFlag::OnlineOnly, "online_only"),
+            ] {
+                let count: i32 = row.get(column)?;
+                if count >= FLAG_VOTE_THRESHOLD {
+                    votes.push(AchievementFlagVotes { appid: appid as u64, apiname: apiname.clone(), flag, votes: count });
+                }
+            }
+        }
+        Ok(votes)
+    }
+
+    async fn refresh_leaderboard_stats(&self) -> Result<(), DbError> {
+        let mut conn = self.conn();
+        let now = Utc::now();
+
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM leaderboard_stats", [])?;
+        tx.execute(
+            r#"
+            INSERT INTO leaderboard_stats (steam_id, total_unlocks, perfect_games, avg_completion, unlocks_this_month, refreshed_at)
+            SELECT
+                u.steam_id,
+                COALESCE(g.total_unlocks, 0),
+                COALESCE(g.perfect_games, 0),
+                COALESCE(g.avg_completion, 0.0),
+                COALESCE(m.unlocks_this_month, 0),
+                ?2
+            FROM users u
+            LEFT JOIN (
+                SELECT
+                    steam_id,
+                    SUM(achievements_unlocked) FILTER (WHERE achievements_total > 0) AS total_unlocks,
+                    COUNT(*) FILTER (WHERE achievements_total > 0 AND achievements_unlocked >= achievements_total) AS perfect_games,
+                    AVG(CAST(achievements_unlocked AS REAL) / achievements_total * 100)
+                        FILTER (WHERE achievements_total > 0 AND playtime_forever > 0) AS avg_completion
+                FROM user_games
+                GROUP BY steam_id
+            ) g ON g.steam_id = u.steam_id
+            LEFT JOIN (
+                SELECT steam_id, COUNT(*) AS unlocks_this_month
+                FROM user_achievements
+                WHERE achieved = 1 AND unlocktime >= ?1
+                GROUP BY steam_id
+            ) m ON m.steam_id = u.steam_id
+            "#,
+            params![month_start(now), now],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn set_leaderboard_opt_in(&self, steam_id: &str, enabled: bool) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            "UPDATE users SET leaderboard_opt_in = ?2 WHERE steam_id = ?1",
+            params![steam_id_int, enabled],
+        )?;
+        Ok(())
+    }
+
+    async fn get_leaderboard(&self, steam_id: &str, metric: LeaderboardMetric, scope: LeaderboardScope, limit: i32) -> Result<Leaderboard, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+
+        // Column names come from a fixed list, never from user input
+        let column = match metric {
+            LeaderboardMetric::TotalUnlocks => "total_unlocks",
+            LeaderboardMetric::PerfectGames => "perfect_games",
+            LeaderboardMetric::AvgCompletion => "avg_completion",
+            LeaderboardMetric::UnlocksThisMonth => "unlocks_this_month",
+        };
+        let scope_filter = match scope {
+            LeaderboardScope::Following => {
+                "ls.steam_id = ?1 OR ls.steam_id IN (SELECT followed_steam_id FROM follows WHERE follower_steam_id = ?1)"
+            }
+            LeaderboardScope::Global => "u.leaderboard_opt_in",
+        };
+
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            WITH ranked AS (
+                SELECT ls.steam_id, u.display_name, u.avatar_url, u.short_id, CAST(ls.{column} AS REAL) AS value,
+                       RANK() OVER (ORDER BY ls.{column} DESC) AS rank
+                FROM leaderboard_stats ls
+                JOIN users u ON u.steam_id = ls.steam_id
+                WHERE {scope_filter}
+            )
+            SELECT * FROM ranked
+            WHERE rank <= ?2 OR steam_id = ?1
+            ORDER BY rank, LOWER(display_name)
+            "#
+        ))?;
+        let entries = stmt.query_map(params![steam_id_int, limit as i64], |row| {
+            let user = profile_from_row(row)?;
+            Ok(LeaderboardEntry {
+                rank: row.get::<_, i64>("rank")? as u32,
+                is_you: row.get::<_, i64>("steam_id")? == steam_id_int,
+                user,
+                value: row.get("value")?,
+            })
+        })?.collect::<Result<_, _>>()?;
+
+        let refreshed_at: Option<DateTime<Utc>> = conn.query_row(
+            "SELECT MAX(refreshed_at) FROM leaderboard_stats",
+            [],
+            |row| row.get(0),
+        )?;
+
+        let opted_in: bool = conn.query_row(
+            "SELECT leaderboard_opt_in FROM users WHERE steam_id = ?1",
+            params![steam_id_int],
+            |row| row.get(0),
+        ).optional()?.unwrap_or(false);
+
+        Ok(Leaderboard { metric, scope, entries, refreshed_at, opted_in })
+    }
+
+    async fn upsert_achievement_rating(&self, steam_id: &str, appid: u64, apiname: &str, rating: u8) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            r#"
+            INSERT INTO achievement_ratings (steam_id, appid, apiname, rating)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (steam_id, appid, apiname)
+            DO UPDATE SET rating = ?4, updated_at = ?5
+            "#,
+            params![steam_id_int, appid as i64, apiname, rating as i64, Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    async fn upsert_achievement_flags(&self, steam_id: &str, appid: u64, apiname: &str, flags: AchievementFlags) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        self.conn().execute(
+            r#"
+            INSERT INTO achievement_ratings (steam_id, appid, apiname, rating, flags)
+            VALUES (?1, ?2, ?3, NULL, ?4)
+            ON CONFLICT (steam_id, appid, apiname)
+            DO UPDATE SET flags = ?4, updated_at = ?5
+            "#,
+            params![steam_id_int, appid as i64, apiname, flags.bits() as i64, Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    async fn get_user_achievement_flags(&self, steam_id: &str) -> Result<Vec<(u64, String, AchievementFlags)>, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let mut stmt = conn.prepare(
+            "SELECT appid, apiname, flags FROM achievement_ratings WHERE steam_id = ?1 AND flags <> 0",
+        )?;
+        let flags = stmt.query_map(params![steam_id_int], |row| {
+            Ok((
+                row.get::<_, i64>("appid")? as u64,
+                row.get::<_, String>("apiname")?,
+                AchievementFlags::from_bits(row.get::<_, i64>("flags")? as u8),
+            ))
+        })?.collect::<Result<_, _>>()?;
+        Ok(flags)
+    }
+
+    async fn get_user_achievement_ratings(&self, steam_id: &str) -> Result<Vec<(u64, String, u8)>, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let mut stmt = conn.prepare(
+            "SELECT appid, apiname, rating FROM achievement_ratings WHERE steam_id = ?1 AND rating IS NOT NULL",
+        )?;
+        let ratings = stmt.query_map(params![steam_id_int], |row| {
+            Ok((
+                row.get::<_, i64>("appid")? as u64,
+                row.get::<_, String>("apiname")?,
+                row.get::<_, i64>("rating")? as u8,
+            ))
+        })?.collect::<Result<_, _>>()?;
+        Ok(ratings)
+    }
+
+    // ========================================================================
+    // Moderation
+    // ========================================================================
+
+    async fn is_admin(&self, steam_id: &str) -> Result<bool, DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let is_admin = self.conn().query_row(
+            "SELECT is_admin FROM users WHERE steam_id = ?1",
+            params![steam_id_int],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(is_admin.unwrap_or(false))
+    }
+
+    async fn is_banned(&self, steam_id: &str) -> Result<bool, DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let banned = self.conn().query_row(
+            "SELECT banned_at IS NOT NULL FROM users WHERE steam_id = ?1",
+            params![steam_id_int],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(banned.unwrap_or(false))
+    }
+
+    async fn set_user_banned(&self, steam_id: &str, banned: bool, reason: Option<&str>) -> Result<bool, DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let updated = self.conn().execute(
+            r#"
+            UPDATE users
+            SET banned_at = CASE WHEN ?2 THEN COALESCE(banned_at, ?4) END,
+                ban_reason = CASE WHEN ?2 THEN ?3 END
+            WHERE steam_id = ?1
+            "#,
+            params![steam_id_int, banned, reason, Utc::now()],
+        )?;
+        Ok(updated > 0)
+    }
+
+    async fn insert_content_report(&self, steam_id: &str, kind: ContentKind, content_id: i64, reason: &str) -> Result<bool, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+
+        // Table names come from a fixed list, never from user input
+        let exists = conn.query_row(
+            &format!(
+                "SELECT 1 FROM {} WHERE id = ?1 AND steam_id <> ?2 AND hidden_at IS NULL",
+                content_table(kind)
+            ),
+            params![content_id, steam_id_int],
+            |_| Ok(()),
+        ).optional()?;
+        if exists.is_none() {
+            return Ok(false);
+        }
+
+        conn.execute(
+            r#"
+            INSERT INTO content_reports (reporter_steam_id, kind, content_id, reason, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT (reporter_steam_id, kind, content_id)
+            DO UPDATE SET reason = excluded.reason, created_at = excluded.created_at, resolved_at = NULL
+            "#,
+            params![steam_id_int, kind.as_str(), content_id, reason, Utc::now()],
+        )?;
+        Ok(true)
+    }
+
+    async fn get_open_reports(&self) -> Result<Vec<ContentReport>, DbError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT r.id, r.kind, r.content_id, r.reason, r.created_at,
+                   ru.steam_id AS reporter_steam_id, ru.display_name AS reporter_name,
+                   ru.avatar_url AS reporter_avatar, ru.short_id AS reporter_short_id,
+                   au.steam_id AS author_steam_id, au.display_name AS author_name,
+                   au.avatar_url AS author_avatar, au.short_id AS author_short_id,
+                   c.text, c.hidden
+            FROM content_reports r
+            JOIN users ru ON ru.steam_id = r.reporter_steam_id
+            JOIN (
+                SELECT 'comment' AS kind, id, steam_id, comment AS text, hidden_at IS NOT NULL AS hidden
+                FROM achievement_comments
+                UNION ALL
+                SELECT 'tip', id, steam_id, tip, hidden_at IS NOT NULL
+                FROM achievement_tips
+                UNION ALL
+                SELECT 'review', id, steam_id, COALESCE(comment, ''), hidden_at IS NOT NULL
+                FROM game_ratings
+            ) c ON c.kind = r.kind AND c.id = r.content_id
+            JOIN users au ON au.steam_id = c.steam_id
+            WHERE r.resolved_at IS NULL
+            ORDER BY r.created_at
+            "#,
+        )?;
+        let mut rows = stmt.query([])?;
+
+        let mut reports = Vec::new();
+        while let Some(row) = rows.next()? {
+            let kind: String = row.get("kind")?;
+            let Some(kind) = ContentKind::from_name(&kind) else {
+                continue;
+            };
+            reports.push(ContentReport {
+                id: row.get("id")?,
+                kind,
+                content_id: row.get("content_id")?,
+                reporter: UserProfile {
+                    steam_id: row.get::<_, i64>("reporter_steam_id")?.to_string(),
+                    display_name: row.get("reporter_name")?,
+                    avatar_url: row.get("reporter_avatar")?,
+                    short_id: row.get("reporter_short_id")?,
+                },
+                reason: row.get("reason")?,
+                created_at: row.get("created_at")?,
+                author: UserProfile {
+                    steam_id: row.get::<_, i64>("author_steam_id")?.to_string(),
+                    display_name: row.get("author_name")?,
+                    avatar_url: row.get("author_avatar")?,
+                    short_id: row.get("author_short_id")?,
+                },
+                content: row.get("text")?,
+                hidden: row.get("hidden")?,
+            });
+        }
+        Ok(reports)
+    }
+
+    async fn dismiss_report(&self, id: i64) -> Result<bool, DbError> {
+        let updated = self.conn().execute(
+            "UPDATE content_reports SET resolved_at = ?2 WHERE id = ?1 AND resolved_at IS NULL",
+            params![id, Utc::now()],
+        )?;
+        Ok(updated > 0)
+    }
+
+    async fn set_content_hidden(&self, kind: ContentKind, content_id: i64, hidden: bool) -> Result<bool, DbError> {
+        let conn = self.conn();
+        let updated = conn.execute(
+            &format!(
+                "UPDATE {} SET hidden_at = CASE WHEN ?2 THEN COALESCE(hidden_at, ?3) END WHERE id = ?1",
+                content_table(kind)
+            ),
+            params![content_id, hidden, Utc::now()],
+        )?;
+        if updated > 0 && hidden {
+            resolve_reports(&conn, kind, content_id)?;
+        }
+        Ok(updated > 0)
+    }
+
+    async fn delete_content(&self, kind: ContentKind, content_id: i64) -> Result<bool, DbError> {
+        let conn = self.conn();
+        let deleted = conn.execute(
+            &format!("DELETE FROM {} WHERE id = ?1", content_table(kind)),
+            params![content_id],
+        )?;
+        if deleted > 0 {
+            resolve_reports(&conn, kind, content_id)?;
+        }
+        Ok(deleted > 0)
+    }
+
+    // ========================================================================
+    // Sessions
+    // ========================================================================
+
+    async fn create_session(&self, steam_id: &str, refresh_token_hash: &str, expires_at: DateTime<Utc>) -> Result<i64, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let now = Utc::now();
+        conn.execute(
+            r#"
+            INSERT INTO sessions (steam_id, refresh_token_hash, created_at, last_used_at, expires_at)
+            VALUES (?1, ?2, ?3, ?3, ?4)
+            "#,
+            params![steam_id_int, refresh_token_hash, now, expires_at],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    async fn rotate_session(&self, refresh_token_hash: &str, new_refresh_token_hash: &str, expires_at: DateTime<Utc>) -> Result<Option<(i64, UserProfile)>, DbError> {
+        let mut conn = self.conn();
+        let now = Utc::now();
+
+        let tx = conn.transaction()?;
+        let rotated = tx.query_row(
+            r#"
+            UPDATE sessions SET
+                refresh_token_hash = ?2,
+                previous_token_hash = ?1,
+                last_used_at = ?4,
+                expires_at = ?3
+            WHERE refresh_token_hash = ?1
+              AND revoked_at IS NULL
+              AND expires_at > ?4
+            RETURNING id, steam_id
+            "#,
+            params![refresh_token_hash, new_refresh_token_hash, expires_at, now],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+        ).optional()?;
+        let Some((id, steam_id_int)) = rotated else {
+            return Ok(None);
+        };
+        let profile = tx.query_row(
+            "SELECT steam_id, display_name, avatar_url, short_id FROM users WHERE steam_id = ?1",
+            params![steam_id_int],
+            profile_from_row,
+        )?;
+        tx.commit()?;
+        Ok(Some((id, profile)))
+    }
+
+    async fn revoke_session_by_token(&self, refresh_token_hash: &str) -> Result<Option<i64>, DbError> {
+        let id = self.conn().query_row(
+            r#"
+            UPDATE sessions SET revoked_at = ?2
+            WHERE (refresh_token_hash = ?1 OR previous_token_hash = ?1)
+              AND revoked_at IS NULL
+            RETURNING id
+            "#,
+            params![refresh_token_hash, Utc::now()],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(id)
+    }
+
+    async fn revoke_user_sessions(&self, steam_id: &str) -> Result<Vec<i64>, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let mut stmt = conn.prepare(
+            r#"
+            UPDATE sessions SET revoked_at = ?2
+            WHERE steam_id = ?1 AND revoked_at IS NULL
+            RETURNING id
+            "#,
+        )?;
+        let ids = stmt.query_map(params![steam_id_int, Utc::now()], |row| row.get(0))?.collect::<Result<_, _>>()?;
+        Ok(ids)
+    }
+
+    async fn get_revoked_session_ids(&self, since: DateTime<Utc>) -> Result<Vec<i64>, DbError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT id FROM sessions WHERE revoked_at >= ?1")?;
+        let ids = stmt.query_map(params![since], |row| row.get(0))?.collect::<Result<_, _>>()?;
+        Ok(ids)
+    }
+
+    // ========================================================================
+    // Cloud Sync
+    // ========================================================================
+
+    async fn get_cloud_sync_status(&self, steam_id: &str) -> Result<CloudSyncStatus, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+
+        let game_count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM user_games WHERE steam_id = ?1",
+            params![steam_id_int],
+            |row| row.get(0),
+        )?;
+        let achievement_count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM user_achievements WHERE steam_id = ?1",
+            params![steam_id_int],
+            |row| row.get(0),
+        )?;
+        let last_sync: Option<DateTime<Utc>> = conn.query_row(
+            "SELECT MAX(run_at) FROM run_history WHERE steam_id = ?1",
+            params![steam_id_int],
+            |row| row.get(0),
+        )?;
+
+        // Encrypted backups keep their history out of run_history
+        let encrypted_row: Option<Option<DateTime<Utc>>> = conn.query_row(
+            "SELECT last_sync FROM encrypted_sync_blobs WHERE steam_id = ?1",
+            params![steam_id_int],
+            |row| row.get(0),
+        ).optional()?;
+        let encrypted_last_sync = encrypted_row.flatten();
+
+        Ok(CloudSyncStatus {
+            has_data: game_count > 0 || encrypted_row.is_some(),
+            game_count: game_count as i32,
+            achievement_count: achievement_count as i32,
+            last_sync: last_sync.max(encrypted_last_sync),
+            encrypted: encrypted_row.is_some(),
+        })
+    }
+
+    async fn get_cloud_sync_data(&self, steam_id: &str) -> Result<CloudSyncData, DbError> {
+        Ok(cloud_sync_data(&self.conn(), steam_id)?)
+    }
+
+    async fn upload_cloud_sync_data(&self, data: &CloudSyncData, dry_run: bool) -> Result<SyncMergePreview, DbError> {
+        let mut conn = self.conn();
+        let steam_id_int: i64 = data.steam_id.parse().unwrap_or(0);
+        let tx = conn.transaction()?;
+        let existing = stored_sync_rows(&tx, steam_id_int, data)?;
+        let changes = existing.merge_changes(data);
+        if dry_run || changes.is_empty() {
+            return Ok(changes.preview);
+        }
+
+        ensure_user(&tx, &data.steam_id)?;
+
+        for game in &changes.games {
+            tx.execute(
+                r#"
+                INSERT INTO user_games (steam_id, appid, name, playtime_forever, rtime_last_played, img_icon_url, added_at, achievements_total, achievements_unlocked, last_sync)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                ON CONFLICT (steam_id, appid) DO UPDATE SET
+                    name = excluded.name,
+                    playtime_forever = excluded.playtime_forever,
+                    rtime_last_played = excluded.rtime_last_played,
+                    img_icon_url = excluded.img_icon_url,
+                    added_at = excluded.added_at,
+                    achievements_total = excluded.achievements_total,
+                    achievements_unlocked = excluded.achievements_unlocked,
+                    last_sync = excluded.last_sync
+                "#,
+                params![
+                    steam_id_int,
+                    game.appid as i64,
+                    game.name,
+                    game.playtime_forever as i64,
+                    game.rtime_last_played.map(|t| t as i64),
+                    game.img_icon_url,
+                    game.added_at,
+                    game.achievements_total,
+                    game.achievements_unlocked,
+                    game.last_achievement_scrape,
+                ],
+            )?;
+        }
+
+        for ach in &changes.achievements {
+            tx.execute(
+                r#"
+                INSERT INTO user_achievements (steam_id, appid, apiname, achieved, unlocktime)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT (steam_id, appid, apiname) DO UPDATE SET
+                    achieved = excluded.achieved,
+                    unlocktime = excluded.unlocktime
+                "#,
+                params![steam_id_int, ach.appid as i64, ach.apiname, ach.achieved, ach.unlocktime],
+            )?;
+        }
+
+        for rh in &changes.run_history {
+            tx.execute(
+                "INSERT INTO run_history (steam_id, run_at, total_games, unplayed_games, unplayed_games_total) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![steam_id_int, rh.run_at, rh.total_games, rh.unplayed_games, rh.unplayed_games_total],
+            )?;
+        }
+
+        for ah in &changes.achievement_history {
+            tx.execute(
+                "INSERT INTO achievement_history (steam_id, recorded_at, total_achievements, unlocked_achievements, games_with_achievements, avg_completion_percent) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![steam_id_int, ah.recorded_at, ah.total_achievements, ah.unlocked_achievements, ah.games_with_achievements, ah.avg_completion_percent as f64],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(changes.preview)
+    }
+
+    async fn get_sync_delta(&self, steam_id: &str, since: Option<SyncCursor>) -> Result<CloudSyncDelta, DbError> {
+        let conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let since = since.unwrap_or(-1);
+
+        let mut delta = CloudSyncDelta::new(steam_id);
+        let mut cursor = since.max(0);
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, sync_version FROM user_games WHERE steam_id = ?1 AND sync_version > ?2",
+            GAME_COLUMNS
+        ))?;
+        let mut rows = stmt.query(params![steam_id_int, since])?;
+        while let Some(row) = rows.next()? {
+            cursor = cursor.max(row.get("sync_version")?);
+            delta.games.push(game_from_row(row)?);
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT appid, apiname, achieved, unlocktime, sync_version FROM user_achievements WHERE steam_id = ?1 AND sync_version > ?2",
+        )?;
+        let mut rows = stmt.query(params![steam_id_int, since])?;
+        while let Some(row) = rows.next()? {
+            cursor = cursor.max(row.get("sync_version")?);
+            delta.achievements.push(sync_achievement_from_row(row)?);
+        }
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, run_at, total_games, unplayed_games, unplayed_games_total, sync_version
+            FROM run_history
+            WHERE steam_id = ?1 AND sync_version > ?2
+            ORDER BY run_at
+            "#,
+        )?;
+        let mut rows = stmt.query(params![steam_id_int, since])?;
+        while let Some(row) = rows.next()? {
+            cursor = cursor.max(row.get("sync_version")?);
+            delta.run_history.push(run_history_from_row(row)?);
+        }
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, recorded_at, total_achievements, unlocked_achievements,
+                   games_with_achievements, avg_completion_percent, sync_version
+            FROM achievement_history
+            WHERE steam_id = ?1 AND sync_version > ?2
+            ORDER BY recorded_at
+            "#,
+        )?;
+        let mut rows = stmt.query(params![steam_id_int, since])?;
+        while let Some(row) = rows.next()? {
+            cursor = cursor.max(row.get("sync_version")?);
+            delta.achievement_history.push(achievement_history_from_row(row)?);
+        }
+
+        delta.cursor = Some(cursor);
+        Ok(delta)
+    }
+
+    async fn delete_cloud_sync_data(&self, steam_id: &str) -> Result<(), DbError> {
+        let mut conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+
+        let tx = conn.transaction()?;
+        for table in [
+            "user_achievements",
+            "user_games",
+            "run_history",
+            "achievement_history",
+            "achievement_ratings",
+            "game_ratings",
+            "achievement_comments",
+            "achievement_tip_votes",
+            "achievement_tips",
+            "encrypted_sync_blobs",
+        ] {
+            tx.execute(&format!("DELETE FROM {} WHERE steam_id = ?1", table), params![steam_id_int])?;
+        }
+        tx.execute("DELETE FROM content_reports WHERE reporter_steam_id = ?1", params![steam_id_int])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn get_encrypted_sync_blob(&self, steam_id: &str) -> Result<Option<EncryptedSyncBlob>, DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let blob = self.conn().query_row(
+            r#"
+            SELECT version, salt, key_check, nonce, ciphertext, last_sync
+            FROM encrypted_sync_blobs
+            WHERE steam_id = ?1
+            "#,
+            params![steam_id_int],
+            |row| {
+                Ok(EncryptedSyncBlob {
+                    version: row.get::<_, i64>("version")? as u32,
+                    salt: row.get("salt")?,
+                    key_check: row.get("key_check")?,
+                    nonce: row.get("nonce")?,
+                    ciphertext: row.get("ciphertext")?,
+                    last_sync: row.get("last_sync")?,
+                })
+            },
+        ).optional()?;
+        Ok(blob)
+    }
+
+    async fn store_encrypted_sync_blob(&self, steam_id: &str, blob: &EncryptedSyncBlob) -> Result<(), DbError> {
+        let mut conn = self.conn();
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        let tx = conn.transaction()?;
+
+        ensure_user(&tx, steam_id)?;
+        tx.execute(
+            r#"
+            INSERT INTO encrypted_sync_blobs (steam_id, version, salt, key_check, nonce, ciphertext, last_sync, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT (steam_id) DO UPDATE SET
+                version = excluded.version,
+                salt = excluded.salt,
+                key_check = excluded.key_check,
+                nonce = excluded.nonce,
+                ciphertext = excluded.ciphertext,
+                last_sync = excluded.last_sync,
+                updated_at = excluded.updated_at
+            "#,
+            params![steam_id_int, blob.version as i64, blob.salt, blob.key_check, blob.nonce, blob.ciphertext, blob.last_sync, Utc::now()],
+        )?;
+        tx.execute("DELETE FROM run_history WHERE steam_id = ?1", params![steam_id_int])?;
+        tx.execute("DELETE FROM achievement_history WHERE steam_id = ?1", params![steam_id_int])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn delete_encrypted_sync_blob(&self, steam_id: &str) -> Result<(), DbError> {
+        let steam_id_int: i64 = steam_id.parse().unwrap_or(0);
+        self.conn().execute("DELETE FROM encrypted_sync_blobs WHERE steam_id = ?1", params![steam_id_int])?;
+        Ok(())
+    }
+}
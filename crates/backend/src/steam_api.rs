@@ -6,6 +6,40 @@ const API_OWNED_GAMES: &str = "https://api.steampowered.com/IPlayerService/GetOw
 const API_RECENTLY_PLAYED: &str = "https://api.steampowered.com/IPlayerService/GetRecentlyPlayedGames/v1/";
 const API_ACHIEVEMENTS: &str = "http://api.steampowered.com/ISteamUserStats/GetPlayerAchievements/v0001/";
 const API_SCHEMA: &str = "http://api.steampowered.com/ISteamUserStats/GetSchemaForGame/v2/";
+const API_GLOBAL_PERCENTAGES: &str = "https://api.steampowered.com/ISteamUserStats/GetGlobalAchievementPercentagesForApp/v0002/";
+const API_PLAYER_SUMMARIES: &str = "https://api.steampowered.com/ISteamUser/GetPlayerSummaries/v2/";
+
+/// Public profile data of a Steam user
+pub struct PlayerSummary {
+    pub persona_name: String,
+    /// Full-size (184x184) avatar
+    pub avatar_url: Option<String>,
+}
+
+/// Persona name and avatar of a Steam user, or None if Steam doesn't know the user
+pub async fn fetch_player_summary(
+    steam_key: &str,
+    steam_id: u64,
+) -> Result<Option<PlayerSummary>, Box<dyn std::error::Error + Send + Sync>> {
+    let url = format!("{}?key={}&steamids={}&format=json", API_PLAYER_SUMMARIES, steam_key, steam_id);
+    
+    let client = reqwest::Client::new();
+    let response = client.get(&url).send().await?.error_for_status()?;
+    let body: serde_json::Value = response.json().await?;
+    
+    let summary = body["response"]["players"]
+        .as_array()
+        .and_then(|players| players.first())
+        .and_then(|player| {
+            let persona_name = player["personaname"].as_str()?.to_string();
+            let avatar_url = player["avatarfull"].as_str()
+                .filter(|url| !url.is_empty())
+                .map(str::to_string);
+            Some(PlayerSummary { persona_name, avatar_url })
+        });
+    
+    Ok(summary)
+}
 
 pub async fn fetch_owned_games(
     steam_key: &str,
@@ -123,3 +157,32 @@ pub async fn fetch_achievement_schema(
     
     Ok(schema)
 }
+
+/// Steam's global unlock percentage per achievement (apiname, percent).
+/// Public data, no API key needed.
+pub async fn fetch_global_achievement_percentages(
+    appid: u64,
+) -> Result<Vec<(String, f32)>, Box<dyn std::error::Error + Send + Sync>> {
+    let url = format!("{}?gameid={}&format=json", API_GLOBAL_PERCENTAGES, appid);
+    
+    let client = reqwest::Client::new();
+    let response = client.get(&url).send().await?;
+    let body: serde_json::Value = response.json().await?;
+    
+    // `percent` is a number in older responses and a string in newer ones
+    let percentages = body["achievementpercentages"]["achievements"]
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|a| {
+                    let name = a["name"].as_str()?.to_string();
+                    let percent = a["percent"].as_f64()
+                        .or_else(|| a["percent"].as_str().and_then(|p| p.parse().ok()))?;
+                    Some((name, percent as f32))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    
+    Ok(percentages)
+}
@@ -0,0 +1,202 @@
+//! In-memory sessions for chunked, resumable cloud sync uploads
+//!
+//! The client opens a session, sends the (optionally compressed) payload in
+//! chunks at increasing offsets, and completes the session once everything
+//! has arrived. After a dropped connection it asks for the session state and
+//! continues from `received_bytes` instead of starting over.
+//!
+//! Each user has at most one session; opening another replaces it. The total
+//! buffered across all sessions is capped so uploads can't exhaust memory.
+
+use overachiever_core::{SyncEncoding, UploadSessionInfo};
+use rand::Rng;
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Maximum size of a single chunk
+pub const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Sessions without activity for this long are discarded
+const SESSION_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Maximum bytes buffered by all sessions together
+const MAX_BUFFERED_BYTES: usize = 256 * 1024 * 1024;
+
+/// Encodings the server can decode, in order of preference
+const SUPPORTED_ENCODINGS: [SyncEncoding; 3] = [SyncEncoding::Zstd, SyncEncoding::Gzip, SyncEncoding::Identity];
+
+const SESSION_ID_LENGTH: usize = 32;
+
+#[derive(Debug)]
+pub enum UploadError {
+    NotFound,
+    /// Chunk does not start where the previous one ended
+    OffsetMismatch { received_bytes: usize },
+    TooLarge,
+    /// Other uploads already buffer as much as the server allows
+    Busy,
+}
+
+impl std::fmt::Display for UploadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UploadError::NotFound => write!(f, "Upload session not found or expired"),
+            UploadError::OffsetMismatch { received_bytes } => {
+                write!(f, "Chunk offset mismatch, server has {} bytes", received_bytes)
+            }
+            UploadError::TooLarge => write!(f, "Upload exceeds the maximum size"),
+            UploadError::Busy => write!(f, "Too many uploads in progress, try again later"),
+        }
+    }
+}
+
+/// A finished upload, ready to be decoded and merged
+pub struct CompletedUpload {
+    pub encoding: SyncEncoding,
+    pub dry_run: bool,
+    pub data: Vec<u8>,
+}
+
+struct UploadSession {
+    steam_id: String,
+    encoding: SyncEncoding,
+    dry_run: bool,
+    data: Vec<u8>,
+    last_activity: Instant,
+}
+
+impl UploadSession {
+    fn info(&self, session_id: &str) -> UploadSessionInfo {
+        UploadSessionInfo {
+            session_id: session_id.to_string(),
+            encoding: self.encoding,
+            chunk_size: CHUNK_SIZE,
+            received_bytes: self.data.len(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct UploadSessions {
+    sessions: Mutex<HashMap<String, UploadSession>>,
+}
+
+impl UploadSessions {
+    /// Open a new session, picking the first client encoding the server supports.
+    /// Replaces the user's previous session, if any.
+    pub fn create(&self, steam_id: &str, encodings: &[SyncEncoding], dry_run: bool) -> UploadSessionInfo {
+        let encoding = encodings.iter()
+            .copied()
+            .find(|e| SUPPORTED_ENCODINGS.contains(e))
+            .unwrap_or(SyncEncoding::Identity);
+
+        let session_id: String = {
+            let mut rng = rand::thread_rng();
+            (0..SESSION_ID_LENGTH)
+                .map(|_| rng.sample(rand::distributions::Alphanumeric) as char)
+                .collect()
+        };
+
+        let session = UploadSession {
+            steam_id: steam_id.to_string(),
+            encoding,
+            dry_run,
+            data: Vec::new(),
+            last_activity: Instant::now(),
+        };
+        let info = session.info(&session_id);
+
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, s| s.steam_id != steam_id && s.last_activity.elapsed() < SESSION_TTL);
+        sessions.insert(session_id, session);
+        info
+    }
+
+    /// Current state of a session owned by `steam_id`
+    pub fn info(&self, session_id: &str, steam_id: &str) -> Result<UploadSessionInfo, UploadError> {
+        let sessions = self.sessions.lock().unwrap();
+        match sessions.get(session_id) {
+            Some(s) if s.steam_id == steam_id => Ok(s.info(session_id)),
+            _ => Err(UploadError::NotFound),
+        }
+    }
+
+    /// Append a chunk at `offset`. Chunks that were already received
+    /// (e.g. resent after a lost response) are accepted and ignored.
+    pub fn append(&self, session_id: &str, steam_id: &str, offset: usize, chunk: &[u8], max_bytes: usize) -> Result<UploadSessionInfo, UploadError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, s| s.last_activity.elapsed() < SESSION_TTL);
+        let buffered: usize = sessions.values().map(|s| s.data.len()).sum();
+        let session = match sessions.get_mut(session_id) {
+            Some(s) if s.steam_id == steam_id => s,
+            _ => return Err(UploadError::NotFound),
+        };
+
+        let received_bytes = session.data.len();
+        if chunk.len() > CHUNK_SIZE {
+            return Err(UploadError::TooLarge);
+        }
+        let Some(end) = offset.checked_add(chunk.len()) else {
+            return Err(UploadError::OffsetMismatch { received_bytes });
+        };
+        if end <= received_bytes {
+            // Duplicate of data we already have
+        } else if offset == received_bytes {
+            if end > max_bytes {
+                return Err(UploadError::TooLarge);
+            }
+            if buffered + chunk.len() > MAX_BUFFERED_BYTES {
+                return Err(UploadError::Busy);
+            }
+            session.data.extend_from_slice(chunk);
+        } else {
+            return Err(UploadError::OffsetMismatch { received_bytes });
+        }
+
+        session.last_activity = Instant::now();
+        Ok(session.info(session_id))
+    }
+
+    /// Remove a session once all `total_bytes` have been received
+    pub fn complete(&self, session_id: &str, steam_id: &str, total_bytes: usize) -> Result<CompletedUpload, UploadError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        match sessions.get(session_id) {
+            Some(s) if s.steam_id == steam_id => {
+                if s.data.len() != total_bytes {
+                    return Err(UploadError::OffsetMismatch { received_bytes: s.data.len() });
+                }
+            }
+            _ => return Err(UploadError::NotFound),
+        }
+
+        let session = sessions.remove(session_id).ok_or(UploadError::NotFound)?;
+        Ok(CompletedUpload {
+            encoding: session.encoding,
+            dry_run: session.dry_run,
+            data: session.data,
+        })
+    }
+}
+
+/// Decode an uploaded payload, refusing to inflate it beyond `max_bytes`
+pub fn decode_payload(encoding: SyncEncoding, data: &[u8], max_bytes: usize) -> Result<Vec<u8>, String> {
+    let reader: Box<dyn Read + '_> = match encoding {
+        SyncEncoding::Identity => return Ok(data.to_vec()),
+        SyncEncoding::Gzip => Box::new(flate2::read::GzDecoder::new(data)),
+        SyncEncoding::Zstd => Box::new(
+            zstd::stream::read::Decoder::new(data).map_err(|e| format!("Invalid zstd data: {}", e))?
+        ),
+    };
+
+    let mut decoded = Vec::new();
+    reader
+        .take(max_bytes as u64 + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|e| format!("Failed to decompress {} data: {}", encoding.label(), e))?;
+    if decoded.len() > max_bytes {
+        return Err("Decompressed upload exceeds the maximum size".to_string());
+    }
+    Ok(decoded)
+}
@@ -12,6 +12,18 @@ use std::sync::Arc;
 use overachiever_core::{ClientMessage, ServerMessage};
 use crate::AppState;
 
+/// Number of activity entries sent for the friends feed
+pub const FEED_LIMIT: i32 = 100;
+
+/// Number of top entries sent for a leaderboard
+pub const LEADERBOARD_LIMIT: i32 = 50;
+
+/// Reply to a message that needs authentication. After the token of the
+/// connection lapsed, the reason tells the client why.
+fn not_authenticated(lapsed: Option<&str>) -> ServerMessage {
+    ServerMessage::AuthError { reason: lapsed.unwrap_or("Not authenticated").to_string() }
+}
+
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
@@ -24,6 +36,10 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
     
     // Track authenticated user
     let mut authenticated_steam_id: Option<String> = None;
+    // Session id and expiry of the access token the connection authenticated with
+    let mut session: Option<(i64, usize)> = None;
+    // Why the connection is no longer authenticated
+    let mut lapsed_reason: Option<&'static str> = None;
     
     while let Some(msg) = receiver.next().await {
         let msg = match msg {
@@ -48,12 +64,25 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
             }
         };
         
+        // Access tokens are short-lived: once the token expires or its session
+        // is revoked, the client has to authenticate again with a fresh token
+        if let Some((sid, exp)) = session {
+            let revoked = state.revoked_sessions.contains(sid);
+            if revoked || exp <= chrono::Utc::now().timestamp() as usize {
+                authenticated_steam_id = None;
+                session = None;
+                lapsed_reason = Some(if revoked { "Session revoked" } else { overachiever_core::AUTH_TOKEN_EXPIRED });
+            }
+        }
+        
         // Handle message
         let response = match client_msg {
             ClientMessage::Authenticate { token } => {
-                match crate::auth::verify_jwt(&token, &state.jwt_secret) {
+                match crate::auth::verify_jwt(&token, &state.jwt_secret, &state.revoked_sessions) {
                     Ok(claims) => {
                         authenticated_steam_id = Some(claims.steam_id.clone());
+                        session = Some((claims.sid, claims.exp));
+                        lapsed_reason = None;
                         ServerMessage::Authenticated {
                             user: overachiever_core::UserProfile {
                                 steam_id: claims.steam_id,
@@ -72,7 +101,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
             ClientMessage::FetchGames => {
                 if let Some(ref steam_id) = authenticated_steam_id {
                     tracing::debug!("Fetching games for steam_id: {}", steam_id);
-                    match crate::db::get_user_games(&state.db_pool, steam_id).await {
+                    match state.db.get_user_games(steam_id).await {
                         Ok(games) => {
                             tracing::info!("Returning {} games for steam_id: {}", games.len(), steam_id);
                             ServerMessage::Games { games }
@@ -83,31 +112,31 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                         }
                     }
                 } else {
-                    ServerMessage::AuthError { reason: "Not authenticated".to_string() }
+                    not_authenticated(lapsed_reason)
                 }
             }
             
             ClientMessage::FetchAchievements { appid } => {
                 if let Some(ref steam_id) = authenticated_steam_id {
-                    match crate::db::get_game_achievements(&state.db_pool, steam_id, appid).await {
+                    match state.db.get_game_achievements(steam_id, appid).await {
                         Ok(achievements) => ServerMessage::Achievements { appid, achievements },
                         Err(e) => ServerMessage::Error { message: e.to_string() }
                     }
                 } else {
-                    ServerMessage::AuthError { reason: "Not authenticated".to_string() }
+                    not_authenticated(lapsed_reason)
                 }
             }
             
             ClientMessage::GetCommunityRatings { appid } => {
-                match crate::db::get_community_ratings(&state.db_pool, appid).await {
+                match state.db.get_community_ratings(appid, authenticated_steam_id.as_deref()).await {
                     Ok(ratings) => {
-                        let rating_count = ratings.len() as i32;
-                        let avg_rating = if rating_count > 0 {
-                            ratings.iter().map(|r| r.rating as f32).sum::<f32>() / rating_count as f32
-                        } else {
-                            0.0
-                        };
-                        ServerMessage::CommunityRatings { appid, avg_rating, rating_count, ratings }
+                        let community = overachiever_core::CommunityGameRating::from_ratings(appid, ratings);
+                        ServerMessage::CommunityRatings {
+                            appid,
+                            avg_rating: community.avg_rating,
+                            rating_count: community.rating_count,
+                            ratings: community.ratings,
+                        }
                     }
                     Err(e) => ServerMessage::Error { message: e.to_string() }
                 }
@@ -115,26 +144,36 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
             
             ClientMessage::SubmitRating { appid, rating, comment } => {
                 if let Some(ref steam_id) = authenticated_steam_id {
-                    let game_rating = overachiever_core::GameRating {
-                        id: None,
-                        steam_id: steam_id.clone(),
-                        appid,
-                        rating,
-                        comment,
-                        created_at: chrono::Utc::now(),
-                        updated_at: chrono::Utc::now(),
-                    };
-                    match crate::db::upsert_rating(&state.db_pool, &game_rating).await {
-                        Ok(_) => ServerMessage::RatingSubmitted { appid },
-                        Err(e) => ServerMessage::Error { message: e.to_string() }
+                    let comment = comment.as_deref().map(str::trim).filter(|c| !c.is_empty());
+                    if let Err(e) = overachiever_core::validate_game_rating(rating, comment) {
+                        ServerMessage::Error { message: e }
+                    } else if let Err(e) = state.moderation.check_submission(state.db.as_ref(), steam_id, comment.as_slice()).await {
+                        ServerMessage::Error { message: e.to_string() }
+                    } else {
+                        let result = match state.db.upsert_rating(steam_id, appid, rating, comment).await {
+                            Ok(()) => state.db.get_community_ratings(appid, Some(steam_id)).await,
+                            Err(e) => Err(e),
+                        };
+                        match result {
+                            Ok(ratings) => {
+                                let community = overachiever_core::CommunityGameRating::from_ratings(appid, ratings);
+                                ServerMessage::CommunityRatings {
+                                    appid,
+                                    avg_rating: community.avg_rating,
+                                    rating_count: community.rating_count,
+                                    ratings: community.ratings,
+                                }
+                            }
+                            Err(e) => ServerMessage::Error { message: format!("Failed to submit rating: {:?}", e) }
+                        }
                     }
                 } else {
-                    ServerMessage::AuthError { reason: "Not authenticated".to_string() }
+                    not_authenticated(lapsed_reason)
                 }
             }
             
             ClientMessage::GetCommunityTips { appid, apiname } => {
-                match crate::db::get_achievement_tips(&state.db_pool, appid, &apiname).await {
+                match state.db.get_achievement_tips(appid, &apiname, authenticated_steam_id.as_deref()).await {
                     Ok(tips) => ServerMessage::CommunityTips { appid, apiname, tips },
                     Err(e) => ServerMessage::Error { message: e.to_string() }
                 }
@@ -143,9 +182,9 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
             // Guest viewing - no authentication required
             ClientMessage::ViewGuestLibrary { short_id } => {
                 tracing::info!("Guest viewing library for short_id: {}", short_id);
-                match crate::db::get_user_by_short_id(&state.db_pool, &short_id).await {
+                match state.db.get_user_by_short_id(&short_id).await {
                     Ok(Some(user)) => {
-                        match crate::db::get_user_games_by_short_id(&state.db_pool, &short_id).await {
+                        match state.db.get_user_games_by_short_id(&short_id).await {
                             Ok(Some(games)) => {
                                 tracing::info!("Returning {} games for guest view of {}", games.len(), short_id);
                                 ServerMessage::GuestLibrary { user, games }
@@ -160,7 +199,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
             }
             
             ClientMessage::FetchGuestAchievements { short_id, appid } => {
-                match crate::db::get_game_achievements_by_short_id(&state.db_pool, &short_id, appid).await {
+                match state.db.get_game_achievements_by_short_id(&short_id, appid).await {
                     Ok(Some(achievements)) => ServerMessage::Achievements { appid, achievements },
                     Ok(None) => ServerMessage::GuestNotFound { short_id },
                     Err(e) => ServerMessage::Error { message: e.to_string() }
@@ -168,7 +207,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
             }
             
             ClientMessage::FetchGuestHistory { short_id } => {
-                match crate::db::get_history_by_short_id(&state.db_pool, &short_id).await {
+                match state.db.get_history_by_short_id(&short_id).await {
                     Ok(Some((run_history, achievement_history, log_entries))) => {
                         ServerMessage::History { run_history, achievement_history, log_entries }
                     }
@@ -177,6 +216,159 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                 }
             }
             
+            ClientMessage::CompareLibraries { short_id } => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    let other = state.db.get_user_by_short_id(&short_id).await;
+                    let theirs = state.db.get_user_games_by_short_id(&short_id).await;
+                    let mine = state.db.get_user_games(steam_id).await;
+                    match (other, theirs, mine) {
+                        (Ok(Some(other)), Ok(Some(theirs)), Ok(mine)) => ServerMessage::LibraryComparison {
+                            comparison: overachiever_core::LibraryComparison::new(other, &mine, &theirs),
+                        },
+                        (Ok(_), Ok(_), Ok(_)) => ServerMessage::GuestNotFound { short_id },
+                        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => ServerMessage::Error { message: e.to_string() }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
+            ClientMessage::CompareGameAchievements { short_id, appid } => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    let mine = state.db.get_game_achievements(steam_id, appid).await;
+                    let theirs = state.db.get_game_achievements_by_short_id(&short_id, appid).await;
+                    match (mine, theirs) {
+                        (Ok(mine), Ok(Some(theirs))) => ServerMessage::GameAchievementComparison {
+                            comparison: overachiever_core::GameAchievementComparison::new(appid, &mine, &theirs),
+                            short_id,
+                        },
+                        (Ok(_), Ok(None)) => ServerMessage::GuestNotFound { short_id },
+                        (Err(e), _) | (_, Err(e)) => ServerMessage::Error { message: e.to_string() }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
+            ClientMessage::FollowUser { short_id } => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    match state.db.get_user_by_short_id(&short_id).await {
+                        Ok(Some(user)) if &user.steam_id == steam_id => ServerMessage::Error {
+                            message: "You can't follow yourself".to_string(),
+                        },
+                        Ok(Some(user)) => {
+                            match state.db.follow_user(steam_id, &user.steam_id).await {
+                                Ok(()) => following_message(&state, steam_id).await,
+                                Err(e) => ServerMessage::Error { message: e.to_string() }
+                            }
+                        }
+                        Ok(None) => ServerMessage::Error { message: format!("User not found: {}", short_id) },
+                        Err(e) => ServerMessage::Error { message: e.to_string() }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
+            ClientMessage::UnfollowUser { short_id } => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    match state.db.get_user_by_short_id(&short_id).await {
+                        Ok(Some(user)) => {
+                            match state.db.unfollow_user(steam_id, &user.steam_id).await {
+                                Ok(()) => following_message(&state, steam_id).await,
+                                Err(e) => ServerMessage::Error { message: e.to_string() }
+                            }
+                        }
+                        Ok(None) => ServerMessage::Error { message: format!("User not found: {}", short_id) },
+                        Err(e) => ServerMessage::Error { message: e.to_string() }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
+            ClientMessage::FetchFollowing => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    following_message(&state, steam_id).await
+                } else {
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
+            ClientMessage::FetchFeed => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    match state.db.get_follow_feed(steam_id, FEED_LIMIT).await {
+                        Ok(entries) => ServerMessage::Feed { entries },
+                        Err(e) => ServerMessage::Error { message: e.to_string() }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
+            ClientMessage::FetchAchievementRarity { appid } => {
+                // Aggregated data, available without authentication
+                match crate::rarity::game_rarity(state.db.as_ref(), appid).await {
+                    Ok(rarity) => ServerMessage::AchievementRarity { rarity },
+                    Err(e) => ServerMessage::Error { message: e.to_string() }
+                }
+            }
+            
+            ClientMessage::FetchAchievementAverages { appids } => {
+                if appids.len() > crate::routes::MAX_AVERAGES_APPIDS {
+                    ServerMessage::Error { message: format!("At most {} games per request", crate::routes::MAX_AVERAGES_APPIDS) }
+                } else {
+                    match state.db.get_achievement_averages(&appids).await {
+                        Ok(averages) => ServerMessage::AchievementAverages { averages },
+                        Err(e) => ServerMessage::Error { message: e.to_string() }
+                    }
+                }
+            }
+            
+            ClientMessage::FetchGameRatingAverages { appids } => {
+                if appids.len() > crate::routes::MAX_AVERAGES_APPIDS {
+                    ServerMessage::Error { message: format!("At most {} games per request", crate::routes::MAX_AVERAGES_APPIDS) }
+                } else {
+                    match state.db.get_game_rating_averages(&appids).await {
+                        Ok(averages) => ServerMessage::GameRatingAverages { averages },
+                        Err(e) => ServerMessage::Error { message: e.to_string() }
+                    }
+                }
+            }
+            
+            ClientMessage::FetchAchievementFlags { appids } => {
+                if appids.len() > crate::routes::MAX_AVERAGES_APPIDS {
+                    ServerMessage::Error { message: format!("At most {} games per request", crate::routes::MAX_AVERAGES_APPIDS) }
+                } else {
+                    match state.db.get_achievement_flag_votes(&appids).await {
+                        Ok(flags) => ServerMessage::AchievementFlags { flags },
+                        Err(e) => ServerMessage::Error { message: e.to_string() }
+                    }
+                }
+            }
+            
+            ClientMessage::FetchLeaderboard { metric, scope } => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    match state.db.get_leaderboard(steam_id, metric, scope, LEADERBOARD_LIMIT).await {
+                        Ok(leaderboard) => ServerMessage::Leaderboard { leaderboard },
+                        Err(e) => ServerMessage::Error { message: e.to_string() }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
+            ClientMessage::SetLeaderboardOptIn { enabled } => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    match state.db.set_leaderboard_opt_in(steam_id, enabled).await {
+                        Ok(()) => ServerMessage::LeaderboardOptIn { enabled },
+                        Err(e) => ServerMessage::Error { message: e.to_string() }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
             ClientMessage::SyncFromSteam => {
                 if let Some(ref steam_id) = authenticated_steam_id {
                     if let Some(ref api_key) = state.steam_api_key {
@@ -199,7 +391,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                         let game_count = games.len() as i32;
                         let unplayed_count = games.iter().filter(|g| g.playtime_forever == 0).count() as i32;
                         
-                        match crate::db::upsert_games(&state.db_pool, steam_id, &games).await {
+                        match state.db.upsert_games(steam_id, &games).await {
                             Ok(count) => tracing::info!("Saved {} games for user {}", count, steam_id),
                             Err(e) => {
                                 let _ = sender.send(Message::Text(serde_json::to_string(&ServerMessage::Error { 
@@ -210,7 +402,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                         }
                         
                         // Record run history
-                        if let Err(e) = crate::db::insert_run_history(&state.db_pool, steam_id, game_count, unplayed_count).await {
+                        if let Err(e) = state.db.insert_run_history(steam_id, game_count, unplayed_count).await {
                             tracing::error!("Failed to insert run_history: {:?}", e);
                         }
                         
@@ -223,7 +415,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                         
                         if recent_games.is_empty() {
                             // No recently played games, return sync complete with just the games
-                            match crate::db::get_user_games(&state.db_pool, steam_id).await {
+                            match state.db.get_user_games(steam_id).await {
                                 Ok(user_games) => {
                                     let result = overachiever_core::SyncResult {
                                         games_updated: game_count,
@@ -236,15 +428,15 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                             }
                         } else {
                             // Upsert recently played games (in case any are missing from owned games)
-                            if let Err(e) = crate::db::upsert_games(&state.db_pool, steam_id, &recent_games).await {
+                            if let Err(e) = state.db.upsert_games(steam_id, &recent_games).await {
                                 tracing::warn!("Failed to upsert recently played games: {:?}", e);
                             }
                             
                             // Recalculate total games after adding recently played (some F2P games might not be in GetOwnedGames)
-                            if let Ok(all_games_after) = crate::db::get_user_games(&state.db_pool, steam_id).await {
+                            if let Ok(all_games_after) = state.db.get_user_games(steam_id).await {
                                 let new_total = all_games_after.len() as i32;
                                 if new_total > game_count {
-                                    if let Err(e) = crate::db::update_run_history_total(&state.db_pool, steam_id, new_total).await {
+                                    if let Err(e) = state.db.update_run_history_total(steam_id, new_total).await {
                                         tracing::warn!("Failed to update run_history total: {:?}", e);
                                     }
                                 }
@@ -254,7 +446,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                             let recent_appids: Vec<u64> = recent_games.iter().map(|g| g.appid).collect();
                             
                             // Step 3: Scrape achievements for recently played games
-                            let all_games = match crate::db::get_user_games(&state.db_pool, steam_id).await {
+                            let all_games = match state.db.get_user_games(steam_id).await {
                                 Ok(g) => g,
                                 Err(e) => {
                                     let _ = sender.send(Message::Text(serde_json::to_string(&ServerMessage::Error { 
@@ -297,7 +489,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                                 
                                 // Store schema
                                 for s in &schema {
-                                    let _ = crate::db::upsert_achievement_schema(&state.db_pool, game.appid, s).await;
+                                    let _ = state.db.upsert_achievement_schema(game.appid, s).await;
                                 }
                                 
                                 // Store achievements and count
@@ -305,14 +497,14 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                                 let mut ach_unlocked = 0i32;
                                 
                                 for ach in &achievements {
-                                    let _ = crate::db::upsert_user_achievement(&state.db_pool, steam_id, game.appid, ach).await;
+                                    let _ = state.db.upsert_user_achievement(steam_id, game.appid, ach).await;
                                     if ach.achieved == 1 {
                                         ach_unlocked += 1;
                                     }
                                 }
                                 
                                 // Update game achievement counts
-                                let _ = crate::db::update_game_achievements(&state.db_pool, steam_id, game.appid, ach_total, ach_unlocked).await;
+                                let _ = state.db.update_game_achievements(steam_id, game.appid, ach_total, ach_unlocked).await;
                                 
                                 // Track totals
                                 if ach_total > 0 {
@@ -327,20 +519,20 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                             }
                             
                             // Calculate unplayed games with achievements
-                            let user_games = crate::db::get_user_games(&state.db_pool, steam_id).await.unwrap_or_default();
+                            let user_games = state.db.get_user_games(steam_id).await.unwrap_or_default();
                             let unplayed_with_ach = user_games.iter()
                                 .filter(|g| g.achievements_total.map(|t| t > 0).unwrap_or(false))
                                 .filter(|g| g.playtime_forever == 0)
                                 .count() as i32;
                             
                             // Update unplayed count in run_history and backfill historical data
-                            let _ = crate::db::update_latest_run_history_unplayed(&state.db_pool, steam_id, unplayed_with_ach).await;
-                            let _ = crate::db::backfill_run_history_unplayed(&state.db_pool, steam_id, unplayed_with_ach).await;
+                            let _ = state.db.update_latest_run_history_unplayed(steam_id, unplayed_with_ach).await;
+                            let _ = state.db.backfill_run_history_unplayed(steam_id, unplayed_with_ach).await;
                             
                             // Record achievement history if we scanned any games with achievements
                             if games_with_ach > 0 {
                                 let avg_completion = completion_sum / games_with_ach as f32;
-                                let _ = crate::db::insert_achievement_history(&state.db_pool, steam_id, total_achievements, total_unlocked, games_with_ach, avg_completion).await;
+                                let _ = state.db.insert_achievement_history(steam_id, total_achievements, total_unlocked, games_with_ach, avg_completion).await;
                             }
                             
                             // Return with updated games
@@ -355,7 +547,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                         ServerMessage::Error { message: "Steam API key not configured on server".to_string() }
                     }
                 } else {
-                    ServerMessage::AuthError { reason: "Not authenticated".to_string() }
+                    not_authenticated(lapsed_reason)
                 }
             }
             
@@ -366,7 +558,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                         let steam_id_u64: u64 = steam_id.parse().unwrap_or(0);
                         
                         // Get games that need scanning
-                        let games = match crate::db::get_user_games(&state.db_pool, steam_id).await {
+                        let games = match state.db.get_user_games(steam_id).await {
                             Ok(g) => g,
                             Err(e) => {
                                 let _ = sender.send(Message::Text(serde_json::to_string(&ServerMessage::Error { 
@@ -411,7 +603,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                             
                             // Store schema
                             for s in &schema {
-                                let _ = crate::db::upsert_achievement_schema(&state.db_pool, game.appid, s).await;
+                                let _ = state.db.upsert_achievement_schema(game.appid, s).await;
                             }
                             
                             // Store achievements and count
@@ -419,14 +611,14 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                             let mut ach_unlocked = 0i32;
                             
                             for ach in &achievements {
-                                let _ = crate::db::upsert_user_achievement(&state.db_pool, steam_id, game.appid, ach).await;
+                                let _ = state.db.upsert_user_achievement(steam_id, game.appid, ach).await;
                                 if ach.achieved == 1 {
                                     ach_unlocked += 1;
                                 }
                             }
                             
                             // Update game achievement counts
-                            let _ = crate::db::update_game_achievements(&state.db_pool, steam_id, game.appid, ach_total, ach_unlocked).await;
+                            let _ = state.db.update_game_achievements(steam_id, game.appid, ach_total, ach_unlocked).await;
                             
                             // Track totals
                             if ach_total > 0 {
@@ -441,19 +633,19 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                         }
                         
                         // Calculate unplayed games with achievements
-                        let user_games = crate::db::get_user_games(&state.db_pool, steam_id).await.unwrap_or_default();
+                        let user_games = state.db.get_user_games(steam_id).await.unwrap_or_default();
                         let unplayed_with_ach = user_games.iter()
                             .filter(|g| g.achievements_total.map(|t| t > 0).unwrap_or(false))
                             .filter(|g| g.playtime_forever == 0)
                             .count() as i32;
                         
                         // Update unplayed count in run_history and backfill historical data
-                        let _ = crate::db::update_latest_run_history_unplayed(&state.db_pool, steam_id, unplayed_with_ach).await;
-                        let _ = crate::db::backfill_run_history_unplayed(&state.db_pool, steam_id, unplayed_with_ach).await;
+                        let _ = state.db.update_latest_run_history_unplayed(steam_id, unplayed_with_ach).await;
+                        let _ = state.db.backfill_run_history_unplayed(steam_id, unplayed_with_ach).await;
                         
                         // Record achievement history
                         let avg_completion = if games_with_ach > 0 { completion_sum / games_with_ach as f32 } else { 0.0 };
-                        let _ = crate::db::insert_achievement_history(&state.db_pool, steam_id, total_achievements, total_unlocked, games_with_ach, avg_completion).await;
+                        let _ = state.db.insert_achievement_history(steam_id, total_achievements, total_unlocked, games_with_ach, avg_completion).await;
                         
                         // Return with updated games
                         let result = overachiever_core::SyncResult {
@@ -466,22 +658,22 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                         ServerMessage::Error { message: "Steam API key not configured on server".to_string() }
                     }
                 } else {
-                    ServerMessage::AuthError { reason: "Not authenticated".to_string() }
+                    not_authenticated(lapsed_reason)
                 }
             }
             
             ClientMessage::FetchHistory => {
                 if let Some(ref steam_id) = authenticated_steam_id {
-                    let run_history = crate::db::get_run_history(&state.db_pool, steam_id).await.unwrap_or_default();
-                    let achievement_history = crate::db::get_achievement_history(&state.db_pool, steam_id).await.unwrap_or_default();
-                    let log_entries = crate::db::get_log_entries(&state.db_pool, steam_id, 50).await.unwrap_or_default();
+                    let run_history = state.db.get_run_history(steam_id).await.unwrap_or_default();
+                    let achievement_history = state.db.get_achievement_history(steam_id).await.unwrap_or_default();
+                    let log_entries = state.db.get_log_entries(steam_id, 50).await.unwrap_or_default();
                     ServerMessage::History {
                         run_history,
                         achievement_history,
                         log_entries,
                     }
                 } else {
-                    ServerMessage::AuthError { reason: "Not authenticated".to_string() }
+                    not_authenticated(lapsed_reason)
                 }
             }
             
@@ -497,7 +689,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                         
                         // Store schema
                         for s in &schema {
-                            let _ = crate::db::upsert_achievement_schema(&state.db_pool, appid, s).await;
+                            let _ = state.db.upsert_achievement_schema(appid, s).await;
                         }
                         
                         // Store achievements and count
@@ -505,19 +697,19 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                         let mut ach_unlocked = 0i32;
                         
                         for ach in &achievements {
-                            let _ = crate::db::upsert_user_achievement(&state.db_pool, steam_id, appid, ach).await;
+                            let _ = state.db.upsert_user_achievement(steam_id, appid, ach).await;
                             if ach.achieved == 1 {
                                 ach_unlocked += 1;
                             }
                         }
                         
                         // Update game achievement counts
-                        let _ = crate::db::update_game_achievements(&state.db_pool, steam_id, appid, ach_total, ach_unlocked).await;
+                        let _ = state.db.update_game_achievements(steam_id, appid, ach_total, ach_unlocked).await;
                         
                         // Get the updated game and achievements
-                        let user_games = crate::db::get_user_games(&state.db_pool, steam_id).await.unwrap_or_default();
+                        let user_games = state.db.get_user_games(steam_id).await.unwrap_or_default();
                         let game = user_games.into_iter().find(|g| g.appid == appid);
-                        let game_achievements = crate::db::get_game_achievements(&state.db_pool, steam_id, appid).await.unwrap_or_default();
+                        let game_achievements = state.db.get_game_achievements(steam_id, appid).await.unwrap_or_default();
                         
                         if let Some(game) = game {
                             ServerMessage::SingleGameRefreshComplete { appid, game, achievements: game_achievements }
@@ -528,13 +720,46 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                         ServerMessage::Error { message: "Steam API key not configured on server".to_string() }
                     }
                 } else {
-                    ServerMessage::AuthError { reason: "Not authenticated".to_string() }
+                    not_authenticated(lapsed_reason)
                 }
             }
             
-            ClientMessage::SubmitAchievementTip { .. } => {
-                // TODO: Implement tip submission
-                ServerMessage::Error { message: "Tip submission not yet implemented".to_string() }
+            ClientMessage::SubmitAchievementTip { appid, apiname, difficulty, tip } => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    if let Err(e) = overachiever_core::validate_tip(difficulty, &tip) {
+                        ServerMessage::Error { message: e }
+                    } else if let Err(e) = state.moderation.check_submission(state.db.as_ref(), steam_id, &[&tip]).await {
+                        ServerMessage::Error { message: e.to_string() }
+                    } else {
+                        let result = match state.db.upsert_achievement_tip(steam_id, appid, &apiname, difficulty, tip.trim()).await {
+                            Ok(()) => state.db.get_achievement_tips(appid, &apiname, Some(steam_id)).await,
+                            Err(e) => Err(e),
+                        };
+                        match result {
+                            Ok(tips) => ServerMessage::CommunityTips { appid, apiname, tips },
+                            Err(e) => ServerMessage::Error { message: format!("Failed to submit tip: {:?}", e) }
+                        }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
+            ClientMessage::VoteAchievementTip { tip_id, vote } => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    match state.db.vote_achievement_tip(steam_id, tip_id, vote).await {
+                        Ok(Some((appid, apiname))) => {
+                            match state.db.get_achievement_tips(appid, &apiname, Some(steam_id)).await {
+                                Ok(tips) => ServerMessage::CommunityTips { appid, apiname, tips },
+                                Err(e) => ServerMessage::Error { message: format!("Failed to get tips: {:?}", e) }
+                            }
+                        }
+                        Ok(None) => ServerMessage::Error { message: "Tip not found (own tips cannot be voted on)".to_string() },
+                        Err(e) => ServerMessage::Error { message: format!("Failed to vote on tip: {:?}", e) }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
+                }
             }
             
             ClientMessage::SubmitAchievementRating { appid, apiname, rating } => {
@@ -543,17 +768,93 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
                     // TODO: Store rating in database
                     ServerMessage::AchievementRatingSubmitted { appid, apiname }
                 } else {
-                    ServerMessage::AuthError { reason: "Not authenticated".to_string() }
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
+            ClientMessage::SubmitAchievementFlags { appid, apiname, flags } => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    match state.db.upsert_achievement_flags(steam_id, appid, &apiname, flags).await {
+                        Ok(()) => ServerMessage::AchievementFlagsSubmitted { appid, apiname },
+                        Err(e) => ServerMessage::Error { message: e.to_string() }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
+            ClientMessage::ReportContent { kind, content_id, reason } => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    if let Err(message) = overachiever_core::validate_report_reason(&reason) {
+                        ServerMessage::Error { message }
+                    } else if let Err(e) = state.moderation.check_submission(state.db.as_ref(), steam_id, &[&reason]).await {
+                        ServerMessage::Error { message: e.to_string() }
+                    } else {
+                        match state.db.insert_content_report(steam_id, kind, content_id, reason.trim()).await {
+                            Ok(true) => ServerMessage::ContentReported { kind, content_id },
+                            Ok(false) => ServerMessage::Error { message: "Content not found (own content cannot be reported)".to_string() },
+                            Err(e) => ServerMessage::Error { message: e.to_string() }
+                        }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
                 }
             }
             
             ClientMessage::SubmitAchievementComment { achievements, comment } => {
                 if let Some(ref steam_id) = authenticated_steam_id {
-                    tracing::info!(steam_id = %steam_id, achievements = ?achievements, comment = %comment, "Achievement comment submitted");
-                    // TODO: Store comment in database
-                    ServerMessage::AchievementCommentSubmitted { count: achievements.len() }
+                    if achievements.is_empty() {
+                        ServerMessage::Error { message: "No achievements specified".to_string() }
+                    } else if let Err(message) = overachiever_core::validate_comment(&comment) {
+                        ServerMessage::Error { message }
+                    } else if let Err(e) = state.moderation.check_submission(state.db.as_ref(), steam_id, &[&comment]).await {
+                        ServerMessage::Error { message: e.to_string() }
+                    } else {
+                        match state.db.insert_achievement_comment(steam_id, &achievements, &comment).await {
+                            Ok(comment) => ServerMessage::AchievementCommentSubmitted { comment },
+                            Err(e) => ServerMessage::Error { message: e.to_string() }
+                        }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
+            ClientMessage::FetchAchievementComments { appid, apiname } => {
+                // Public; own comments are marked when authenticated
+                match state.db.get_achievement_comments(appid, &apiname, authenticated_steam_id.as_deref(), crate::routes::COMMENTS_LIMIT).await {
+                    Ok(comments) => ServerMessage::AchievementComments { appid, apiname, comments },
+                    Err(e) => ServerMessage::Error { message: e.to_string() }
+                }
+            }
+            
+            ClientMessage::EditAchievementComment { id, comment } => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    if let Err(message) = overachiever_core::validate_comment(&comment) {
+                        ServerMessage::Error { message }
+                    } else if let Err(e) = state.moderation.check_submission(state.db.as_ref(), steam_id, &[&comment]).await {
+                        ServerMessage::Error { message: e.to_string() }
+                    } else {
+                        match state.db.update_achievement_comment(steam_id, id, &comment).await {
+                            Ok(Some(comment)) => ServerMessage::AchievementCommentUpdated { comment },
+                            Ok(None) => ServerMessage::Error { message: "Comment not found".to_string() },
+                            Err(e) => ServerMessage::Error { message: e.to_string() }
+                        }
+                    }
                 } else {
-                    ServerMessage::AuthError { reason: "Not authenticated".to_string() }
+                    not_authenticated(lapsed_reason)
+                }
+            }
+            
+            ClientMessage::DeleteAchievementComment { id } => {
+                if let Some(ref steam_id) = authenticated_steam_id {
+                    match state.db.delete_achievement_comment(steam_id, id).await {
+                        Ok(true) => ServerMessage::AchievementCommentDeleted { id },
+                        Ok(false) => ServerMessage::Error { message: "Comment not found".to_string() },
+                        Err(e) => ServerMessage::Error { message: e.to_string() }
+                    }
+                } else {
+                    not_authenticated(lapsed_reason)
                 }
             }
         };
@@ -564,3 +865,11 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
         }
     }
 }
+
+/// Current list of followed users, sent after follow changes too
+async fn following_message(state: &AppState, steam_id: &str) -> ServerMessage {
+    match state.db.get_following(steam_id).await {
+        Ok(users) => ServerMessage::Following { users },
+        Err(e) => ServerMessage::Error { message: e.to_string() }
+    }
+}
@@ -0,0 +1,149 @@
+//! Every typed endpoint in `overachiever_core::api` must name a route the
+//! server has, and every REST route must have an endpoint. The routes are
+//! read from the OpenAPI document the router collects as routes are added.
+
+use overachiever_backend::config::ServerConfig;
+use overachiever_core::api::*;
+use overachiever_core::{
+    AchievementFlags, AuthCodeExchangeRequest, CloudSyncDelta, ContentKind, EncryptedSyncBlob, LeaderboardMetric,
+    LeaderboardScope, RefreshTokenRequest, UploadCompleteRequest, UploadSessionRequest,
+};
+use utoipa::openapi::PathItem;
+
+fn request<E: Endpoint>(endpoint: E) -> (Method, String) {
+    (E::METHOD, endpoint.path())
+}
+
+/// Method and path (without the query) of a request to every endpoint
+fn all_requests() -> Vec<(Method, String)> {
+    let page = || PageQuery { cursor: Some("1".into()), limit: Some(2) };
+    let short_id = || "abcdefg".to_string();
+    let appids = || AppidsRequest { appids: vec![440] };
+    let achievement = || AchievementQuery { appid: 440, apiname: "TF_PLAY_GAME".into() };
+    let data = CloudSyncDelta::new("76561198000000001").into_data();
+    let delta = CloudSyncDelta::new("76561198000000001");
+    let blob = EncryptedSyncBlob {
+        version: 1,
+        salt: String::new(),
+        key_check: String::new(),
+        nonce: String::new(),
+        ciphertext: String::new(),
+        last_sync: None,
+    };
+    let refresh = || RefreshTokenRequest { refresh_token: "token".into() };
+
+    vec![
+        request(GetGames { page: page() }),
+        request(GetAchievements { appid: 440, page: page() }),
+        request(GetRunHistory { page: page() }),
+        request(GetAchievementHistory { page: page() }),
+        request(GetLog { page: page() }),
+        request(GetGuestProfile { short_id: short_id() }),
+        request(GetGuestGames { short_id: short_id(), page: page() }),
+        request(GetGuestAchievements { short_id: short_id(), appid: 440, page: page() }),
+        request(GetGuestRunHistory { short_id: short_id(), page: page() }),
+        request(GetGuestAchievementHistory { short_id: short_id(), page: page() }),
+        request(GetGuestLog { short_id: short_id(), page: page() }),
+        request(GetGameRarity { appid: 440 }),
+        request(GetGameAchievementAverages { appid: 440 }),
+        request(GetAchievementAverages(appids())),
+        request(GetGameRatingAverages(appids())),
+        request(GetAchievementFlags(appids())),
+        request(GetGameRatings { appid: 440 }),
+        request(SubmitRatingRequest { appid: 440, rating: 5, comment: None }),
+        request(AchievementRatingRequest { appid: 440, apiname: "TF_PLAY_GAME".into(), rating: 3 }),
+        request(AchievementFlagsRequest { appid: 440, apiname: "TF_PLAY_GAME".into(), flags: AchievementFlags::default() }),
+        request(GetUserAchievementRatings),
+        request(AchievementCommentRequest { achievements: vec![], comment: "comment".into() }),
+        request(GetAchievementComments(achievement())),
+        request(EditAchievementComment { id: 1, body: EditCommentRequest { comment: "comment".into() } }),
+        request(DeleteAchievementComment { id: 1 }),
+        request(GetAchievementTips(achievement())),
+        request(SubmitTipRequest { appid: 440, apiname: "TF_PLAY_GAME".into(), difficulty: 1, tip: "tip".into() }),
+        request(VoteAchievementTip { id: 1, body: VoteTipRequest { vote: 1 } }),
+        request(ReportRequest { kind: ContentKind::Tip, content_id: 1, reason: "spam".into() }),
+        request(GetReports),
+        request(DismissReport { id: 1 }),
+        request(DeleteContent { kind: ContentKind::Review, id: 1 }),
+        request(SetContentHidden { kind: ContentKind::Comment, id: 1, body: SetHiddenRequest { hidden: true } }),
+        request(SetUserBanned { steam_id: "76561198000000001".into(), body: BanRequest { banned: true, reason: None } }),
+        request(GetSyncStatus),
+        request(DownloadSyncData),
+        request(UploadSyncData(&data)),
+        request(DeleteSyncData),
+        request(PullSyncDelta::default()),
+        request(PushSyncDelta { query: PushDeltaQuery { dry_run: true }, body: &delta }),
+        request(UploadSessionRequest { encodings: vec![], dry_run: false }),
+        request(GetUploadSession { session_id: "session".into() }),
+        (Method::Put, upload_chunk_path("session", 0)),
+        request(CompleteUploadSession { session_id: "session".into(), body: UploadCompleteRequest { total_bytes: 1 } }),
+        request(GetEncryptedBackup),
+        request(StoreEncryptedBackup { query: StoreEncryptedQuery::default(), body: &blob }),
+        request(DeleteEncryptedBackup),
+        request(GetFollowing),
+        request(FollowUser { short_id: short_id() }),
+        request(UnfollowUser { short_id: short_id() }),
+        request(GetFeed),
+        request(GetLeaderboard(LeaderboardQuery { metric: LeaderboardMetric::AvgCompletion, scope: LeaderboardScope::Global })),
+        request(LeaderboardOptIn { enabled: true }),
+        request(AuthCodeExchangeRequest { code: "code".into() }),
+        request(RefreshSession(refresh())),
+        request(RevokeSession(refresh())),
+        request(LogoutAllSessions),
+    ]
+    .into_iter()
+    .map(|(method, path)| (method, path.split('?').next().unwrap_or_default().to_string()))
+    .collect()
+}
+
+/// Methods of the operations under one path of the document
+fn methods(item: &PathItem) -> Vec<Method> {
+    [
+        (Method::Get, item.get.is_some()),
+        (Method::Post, item.post.is_some()),
+        (Method::Put, item.put.is_some()),
+        (Method::Delete, item.delete.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(method, present)| present.then_some(method))
+    .collect()
+}
+
+/// Whether a concrete path fills in a template like `/api/users/{short_id}`
+fn matches_template(template: &str, path: &str) -> bool {
+    let template: Vec<&str> = template.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    template.len() == path.len()
+        && template.iter().zip(&path).all(|(t, p)| {
+            if t.starts_with('{') && t.ends_with('}') { !p.is_empty() } else { t == p }
+        })
+}
+
+#[test]
+fn endpoints_match_documented_routes() {
+    let (_, openapi) = overachiever_backend::rest_api(&ServerConfig::default());
+    let operations: Vec<(Method, &str)> = openapi
+        .paths
+        .paths
+        .iter()
+        .flat_map(|(template, item)| methods(item).into_iter().map(move |method| (method, template.as_str())))
+        .collect();
+    let requests = all_requests();
+
+    for (method, path) in &requests {
+        assert!(
+            operations.iter().any(|(m, template)| m == method && matches_template(template, path)),
+            "{:?} {} is not a route of the server",
+            method,
+            path
+        );
+    }
+    for (method, template) in &operations {
+        assert!(
+            requests.iter().any(|(m, path)| m == method && matches_template(template, path)),
+            "{:?} {} has no endpoint in overachiever_core::api",
+            method,
+            template
+        );
+    }
+}
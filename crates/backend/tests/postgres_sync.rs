@@ -0,0 +1,72 @@
+//! Delta pulls against PostgreSQL, where writers run concurrently. Runs when
+//! `TEST_DATABASE_URL` names a scratch database, e.g.
+//! `postgres://user@localhost/overachiever_test`; skipped otherwise.
+
+use std::time::Duration;
+
+use deadpool_postgres::{Config, Pool, PoolConfig, Runtime};
+use overachiever_backend::{db, migrations};
+use tokio_postgres::NoTls;
+
+async fn test_pool() -> Option<Pool> {
+    let url = std::env::var("TEST_DATABASE_URL").ok()?;
+    let mut config = Config::new();
+    config.url = Some(url);
+    // Writers wait on each other while holding connections; leave room for the pulls
+    config.pool = Some(PoolConfig::new(8));
+    let pool = config.create_pool(Some(Runtime::Tokio1), NoTls).unwrap();
+    migrations::run(&pool).await.unwrap();
+    Some(pool)
+}
+
+/// A user of its own, so reruns against the same database don't interfere
+async fn fresh_user(pool: &Pool) -> String {
+    let suffix = chrono::Utc::now().timestamp_micros() % 1_000_000_000;
+    let steam_id = format!("7656119{:010}", suffix);
+    db::get_or_create_user(pool, &steam_id, None, None).await.unwrap();
+    steam_id
+}
+
+#[tokio::test]
+async fn pull_never_skips_a_write_that_commits_late() {
+    let Some(pool) = test_pool().await else {
+        eprintln!("TEST_DATABASE_URL is not set; skipping");
+        return;
+    };
+    let steam_id = fresh_user(&pool).await;
+    let steam_id_int: i64 = steam_id.parse().unwrap();
+    let start = db::get_sync_delta(&pool, &steam_id, None).await.unwrap().cursor;
+
+    // The first writer stamps its row, then stays open
+    let mut client = pool.get().await.unwrap();
+    let first = db::begin_sync_write(&mut client, steam_id_int).await.unwrap();
+    first.execute(
+        "INSERT INTO run_history (steam_id, run_at, total_games, unplayed_games, unplayed_games_total) VALUES ($1, NOW(), 1, 0, 0)",
+        &[&steam_id_int],
+    ).await.unwrap();
+
+    // The second writer starts later and would commit first
+    let second = tokio::spawn({
+        let pool = pool.clone();
+        let steam_id = steam_id.clone();
+        async move { db::insert_run_history(&pool, &steam_id, 2, 0).await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!second.is_finished(), "second writer must wait for the first");
+
+    let during = db::get_sync_delta(&pool, &steam_id, start).await.unwrap();
+    assert!(during.run_history.is_empty());
+    assert_eq!(during.cursor, start);
+
+    first.commit().await.unwrap();
+    second.await.unwrap();
+
+    let after = db::get_sync_delta(&pool, &steam_id, during.cursor).await.unwrap();
+    let mut totals: Vec<i32> = after.run_history.iter().map(|run| run.total_games).collect();
+    totals.sort();
+    assert_eq!(totals, vec![1, 2]);
+
+    let caught_up = db::get_sync_delta(&pool, &steam_id, after.cursor).await.unwrap();
+    assert!(caught_up.run_history.is_empty());
+    assert_eq!(caught_up.cursor, after.cursor);
+}
@@ -0,0 +1,402 @@
+//! End-to-end tests of the REST API on an in-memory SQLite database
+//!
+//! Each test serves the whole router on a local port, next to a stand-in
+//! Steam OpenID provider, and talks to it through the typed endpoints of
+//! `overachiever_core::api`.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::{routing::post, Form, Router};
+use chrono::{DateTime, Duration, Utc};
+use overachiever_backend::config::ServerConfig;
+use overachiever_backend::sqlite::SqliteRepository;
+use overachiever_backend::AppState;
+use overachiever_core::api::*;
+use overachiever_core::{
+    AuthCodeExchangeRequest, AuthTokenResponse, CloudSyncDelta, EncryptedSyncBlob, Game, LogEntry,
+    RefreshTokenRequest, RunHistory, SyncAchievement, ENCRYPTED_SYNC_VERSION,
+};
+use reqwest::{header, StatusCode};
+use tokio::net::TcpListener;
+
+const ALICE: &str = "76561198000000001";
+const BOB: &str = "76561198000000002";
+
+/// `openid.sig` the stand-in provider vouches for
+const VALID_SIGNATURE: &str = "valid";
+
+/// Answer `check_authentication` like Steam, for assertions signed with
+/// [`VALID_SIGNATURE`]
+async fn check_authentication(Form(params): Form<HashMap<String, String>>) -> String {
+    let valid = params.get("openid.mode").map(String::as_str) == Some("check_authentication")
+        && params.get("openid.sig").map(String::as_str) == Some(VALID_SIGNATURE);
+    format!("ns:http://specs.openid.net/auth/2.0\nis_valid:{}\n", valid)
+}
+
+async fn serve(router: Router) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{}", address)
+}
+
+struct TestServer {
+    base: String,
+    config: ServerConfig,
+    http: reqwest::Client,
+}
+
+impl TestServer {
+    async fn start() -> Self {
+        let openid = serve(Router::new().route("/openid/login", post(check_authentication))).await;
+
+        // Bind first: the callback URL has to name the server's port
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let mut config = ServerConfig::default();
+        config.auth.steam_openid_url = format!("{}/openid/login", openid);
+        config.auth.steam_callback_url = format!("{}/auth/steam/callback", base);
+
+        let db = SqliteRepository::open(Path::new(":memory:")).unwrap();
+        db.migrate().unwrap();
+        let state = Arc::new(AppState::new(Arc::new(db), &config).await.unwrap());
+        let app = overachiever_backend::app(state, &config);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let http = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap();
+        Self { base, config, http }
+    }
+
+    /// Come back from the OpenID provider with an assertion for `steam_id`;
+    /// returns where the server redirects the desktop app to
+    async fn steam_callback(&self, steam_id: &str, signature: &str) -> String {
+        let claimed_id = format!("https://steamcommunity.com/openid/id/{}", steam_id);
+        let response = self.http
+            .get(&self.config.auth.steam_callback_url)
+            .query(&[
+                ("openid.mode", "id_res"),
+                ("openid.op_endpoint", &self.config.auth.steam_openid_url),
+                ("openid.return_to", &self.config.auth.steam_callback_url),
+                ("openid.claimed_id", &claimed_id),
+                ("openid.sig", signature),
+                ("redirect_uri", &self.config.auth.redirect_allowlist[0]),
+            ])
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_redirection(), "callback answered {}", response.status());
+        response.headers()[header::LOCATION].to_str().unwrap().to_string()
+    }
+
+    /// One-time login code handed to the desktop app
+    async fn login_code(&self, steam_id: &str) -> String {
+        let location = self.steam_callback(steam_id, VALID_SIGNATURE).await;
+        let (_, code) = location.split_once("code=").expect("redirect has no login code");
+        code.to_string()
+    }
+
+    async fn login(&self, steam_id: &str) -> AuthTokenResponse {
+        let code = self.login_code(steam_id).await;
+        self.send(None, &AuthCodeExchangeRequest { code }).await.unwrap()
+    }
+
+    async fn send<E: Endpoint>(&self, token: Option<&str>, endpoint: &E) -> Result<E::Response, StatusCode> {
+        let method = match E::METHOD {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Delete => reqwest::Method::DELETE,
+        };
+        let mut request = self.http.request(method, format!("{}{}", self.base, endpoint.path()));
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(body) = endpoint.body() {
+            request = request.json(body);
+        }
+        let response = request.send().await.unwrap();
+        if !response.status().is_success() {
+            return Err(response.status());
+        }
+        Ok(response.json().await.unwrap())
+    }
+
+    /// GET with `If-None-Match`; returns the status and the `ETag`
+    async fn get_tagged(&self, token: &str, path: &str, if_none_match: Option<&str>) -> (StatusCode, String) {
+        let mut request = self.http.get(format!("{}{}", self.base, path)).bearer_auth(token);
+        if let Some(etag) = if_none_match {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        let response = request.send().await.unwrap();
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+        (response.status(), etag)
+    }
+
+    async fn push(&self, token: &str, delta: &CloudSyncDelta) {
+        let push = PushSyncDelta { query: PushDeltaQuery { dry_run: false }, body: delta };
+        self.send(Some(token), &push).await.unwrap();
+    }
+}
+
+/// Guest link id of the user an access token belongs to
+fn short_id(config: &ServerConfig, token: &str) -> String {
+    let claims = jsonwebtoken::decode::<serde_json::Value>(
+        token,
+        &jsonwebtoken::DecodingKey::from_secret(config.jwt_secret().as_bytes()),
+        &jsonwebtoken::Validation::default(),
+    ).unwrap().claims;
+    claims["short_id"].as_str().unwrap().to_string()
+}
+
+fn game(appid: u64) -> Game {
+    Game {
+        appid,
+        name: format!("Game {}", appid),
+        playtime_forever: 60,
+        rtime_last_played: None,
+        img_icon_url: None,
+        added_at: Utc::now(),
+        achievements_total: Some(1),
+        achievements_unlocked: Some(1),
+        last_achievement_scrape: None,
+    }
+}
+
+fn unlock(appid: u64, apiname: &str, at: DateTime<Utc>) -> SyncAchievement {
+    SyncAchievement { appid, apiname: apiname.to_string(), achieved: true, unlocktime: Some(at) }
+}
+
+fn run(at: DateTime<Utc>, total_games: i32) -> RunHistory {
+    RunHistory { id: 0, run_at: at, total_games, unplayed_games: 0, unplayed_games_total: 0 }
+}
+
+fn blob(key_check: &str) -> EncryptedSyncBlob {
+    EncryptedSyncBlob {
+        version: ENCRYPTED_SYNC_VERSION,
+        salt: "salt".to_string(),
+        key_check: key_check.to_string(),
+        nonce: "nonce".to_string(),
+        ciphertext: "ciphertext".to_string(),
+        last_sync: None,
+    }
+}
+
+/// Follow `next_cursor` until the last page
+async fn all_pages<T, E: Endpoint<Response = overachiever_core::Page<T>>>(
+    server: &TestServer,
+    token: &str,
+    endpoint: impl Fn(PageQuery) -> E,
+    limit: usize,
+) -> Vec<Vec<T>> {
+    let mut pages = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = server.send(Some(token), &endpoint(PageQuery { cursor, limit: Some(limit) })).await.unwrap();
+        pages.push(page.items);
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            return pages;
+        }
+    }
+}
+
+#[tokio::test]
+async fn login_code_is_exchanged_once() {
+    let server = TestServer::start().await;
+
+    let code = server.login_code(ALICE).await;
+    let tokens = server.send(None, &AuthCodeExchangeRequest { code: code.clone() }).await.unwrap();
+    assert_eq!(tokens.steam_id, ALICE);
+    assert!(server.send(Some(&tokens.token), &GetSyncStatus).await.is_ok());
+
+    let reused = server.send(None, &AuthCodeExchangeRequest { code }).await;
+    assert_eq!(reused.unwrap_err(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn assertion_rejected_by_provider_fails_login() {
+    let server = TestServer::start().await;
+
+    let location = server.steam_callback(ALICE, "forged").await;
+    assert!(location.ends_with("error=auth_failed"), "redirected to {}", location);
+    assert!(!location.contains("code="));
+}
+
+#[tokio::test]
+async fn refresh_rotates_and_reuse_revokes_the_session() {
+    let server = TestServer::start().await;
+    let first = server.login(ALICE).await;
+
+    let refresh = |token: &str| RefreshSession(RefreshTokenRequest { refresh_token: token.to_string() });
+    let second = server.send(None, &refresh(&first.refresh_token)).await.unwrap();
+    assert_ne!(second.refresh_token, first.refresh_token);
+    assert!(server.send(Some(&second.token), &GetSyncStatus).await.is_ok());
+
+    // A replaced refresh token coming back means it leaked: end the session
+    let reused = server.send(None, &refresh(&first.refresh_token)).await;
+    assert_eq!(reused.unwrap_err(), StatusCode::UNAUTHORIZED);
+    assert_eq!(server.send(Some(&second.token), &GetSyncStatus).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+    assert_eq!(server.send(None, &refresh(&second.refresh_token)).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn revoked_sessions_are_logged_out() {
+    let server = TestServer::start().await;
+
+    let tokens = server.login(ALICE).await;
+    let request = RefreshTokenRequest { refresh_token: tokens.refresh_token.clone() };
+    assert!(server.send(None, &RevokeSession(request.clone())).await.unwrap().success);
+    assert_eq!(server.send(Some(&tokens.token), &GetSyncStatus).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+    assert_eq!(server.send(None, &RefreshSession(request)).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+
+    let laptop = server.login(ALICE).await;
+    let desktop = server.login(ALICE).await;
+    let other_user = server.login(BOB).await;
+    let logout = server.send(Some(&laptop.token), &LogoutAllSessions).await.unwrap();
+    assert_eq!(logout.revoked, 2);
+    assert_eq!(server.send(Some(&desktop.token), &GetSyncStatus).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+    assert!(server.send(Some(&other_user.token), &GetSyncStatus).await.is_ok());
+}
+
+#[tokio::test]
+async fn delta_push_and_pull() {
+    let server = TestServer::start().await;
+    let tokens = server.login(ALICE).await;
+    let token = tokens.token.as_str();
+    let unlocked_at = Utc::now() - Duration::days(3);
+
+    let mut delta = CloudSyncDelta::new(ALICE);
+    delta.games = vec![game(10), game(20)];
+    delta.achievements = vec![unlock(10, "WIN", unlocked_at)];
+    delta.run_history = vec![run(unlocked_at, 2)];
+
+    // A dry run only reports the merge
+    let dry_run = PushSyncDelta { query: PushDeltaQuery { dry_run: true }, body: &delta };
+    let preview = server.send(Some(token), &dry_run).await.unwrap().merged;
+    assert_eq!((preview.games_added, preview.unlocks_added, preview.run_history_added), (2, 1, 1));
+    assert!(server.send(Some(token), &PullSyncDelta::default()).await.unwrap().games.is_empty());
+
+    server.push(token, &delta).await;
+    let full = server.send(Some(token), &PullSyncDelta::default()).await.unwrap();
+    assert_eq!(full.games.len(), 2);
+    assert_eq!(full.achievements.len(), 1);
+    assert_eq!(full.run_history.len(), 1);
+    let cursor = full.cursor.expect("pull returns a cursor");
+
+    // Only rows changed after the cursor come back; the earlier unlock wins
+    let mut later = CloudSyncDelta::new(ALICE);
+    later.games = vec![game(30)];
+    later.achievements = vec![unlock(10, "WIN", unlocked_at - Duration::days(1))];
+    server.push(token, &later).await;
+    let changes = server.send(Some(token), &PullSyncDelta(PullDeltaQuery { since: Some(cursor) })).await.unwrap();
+    assert_eq!(changes.games.iter().map(|g| g.appid).collect::<Vec<_>>(), vec![30]);
+    assert_eq!(changes.achievements.len(), 1);
+    assert_eq!(changes.achievements[0].unlocktime, Some(unlocked_at - Duration::days(1)));
+    assert!(changes.run_history.is_empty());
+
+    // A later unlock from another device doesn't overwrite the earliest one
+    let mut stale = CloudSyncDelta::new(ALICE);
+    stale.achievements = vec![unlock(10, "WIN", unlocked_at)];
+    server.push(token, &stale).await;
+    let after_stale = server.send(Some(token), &PullSyncDelta(PullDeltaQuery { since: changes.cursor })).await.unwrap();
+    assert!(after_stale.achievements.is_empty());
+
+    // Rows of another user are refused
+    let foreign = CloudSyncDelta::new(BOB);
+    let push = PushSyncDelta { query: PushDeltaQuery { dry_run: false }, body: &foreign };
+    assert_eq!(server.send(Some(token), &push).await.unwrap_err(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn encrypted_backup_replaces_history_and_detects_passphrase_changes() {
+    let server = TestServer::start().await;
+    let tokens = server.login(ALICE).await;
+    let token = tokens.token.as_str();
+
+    let mut delta = CloudSyncDelta::new(ALICE);
+    delta.games = vec![game(10)];
+    delta.run_history = vec![run(Utc::now(), 1)];
+    server.push(token, &delta).await;
+
+    let (first_key, second_key) = (blob("key-1"), blob("key-2"));
+    let store = |body, expected: Option<&str>| StoreEncryptedBackup {
+        query: StoreEncryptedQuery { expected_key_check: expected.map(str::to_string) },
+        body,
+    };
+    server.send(Some(token), &store(&first_key, None)).await.unwrap();
+    let stored = server.send(Some(token), &GetEncryptedBackup).await.unwrap().expect("backup stored");
+    assert_eq!(stored.key_check, "key-1");
+
+    // The backup holds the history now; the library stays for the guest view
+    let pulled = server.send(Some(token), &PullSyncDelta::default()).await.unwrap();
+    assert!(pulled.run_history.is_empty());
+    assert_eq!(pulled.games.len(), 1);
+
+    // Changing the passphrase names the key check it replaces
+    server.send(Some(token), &store(&second_key, Some("key-1"))).await.unwrap();
+
+    // A device still on the old passphrase must not overwrite the new backup
+    let conflict = server.send(Some(token), &store(&first_key, Some("key-1"))).await;
+    assert_eq!(conflict.unwrap_err(), StatusCode::CONFLICT);
+    let kept = server.send(Some(token), &GetEncryptedBackup).await.unwrap().unwrap();
+    assert_eq!(kept.key_check, "key-2");
+}
+
+#[tokio::test]
+async fn lists_are_paged_and_tagged() {
+    let server = TestServer::start().await;
+    let tokens = server.login(ALICE).await;
+    let token = tokens.token.as_str();
+    let start = Utc::now() - Duration::days(30);
+
+    let mut delta = CloudSyncDelta::new(ALICE);
+    delta.games = (1..=25).map(|appid| game(appid * 10)).collect();
+    // Pairs of unlocks share a timestamp, so pages have to break ties
+    delta.achievements = (0..7)
+        .map(|i| unlock(10 + (i % 2) * 10, &format!("ACH_{}", i), start + Duration::hours(i as i64 / 2)))
+        .collect();
+    delta.run_history = (0..5).map(|i| run(start + Duration::days(i), 10 + i as i32)).collect();
+    server.push(token, &delta).await;
+
+    let pages = all_pages(&server, token, |page| GetGames { page }, 10).await;
+    assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), vec![10, 10, 5]);
+    let appids: Vec<u64> = pages.into_iter().flatten().map(|g| g.appid).collect();
+    assert_eq!(appids, (1..=25).map(|appid| appid * 10).collect::<Vec<_>>());
+
+    let runs: Vec<i32> = all_pages(&server, token, |page| GetRunHistory { page }, 2).await
+        .into_iter().flatten().map(|r| r.total_games).collect();
+    assert_eq!(runs, vec![10, 11, 12, 13, 14]);
+
+    let log: Vec<LogEntry> = all_pages(&server, token, |page| GetLog { page }, 3).await.into_iter().flatten().collect();
+    assert_eq!(log.len(), 7);
+    assert!(log.windows(2).all(|w| w[0].timestamp() >= w[1].timestamp()), "log is newest first");
+
+    // Guests page through the same library
+    let guest = GetGuestGames { short_id: short_id(&server.config, token), page: PageQuery { cursor: None, limit: Some(10) } };
+    let guest_page = server.send(None, &guest).await.unwrap();
+    assert_eq!(guest_page.items.len(), 10);
+    assert_eq!(guest_page.next_cursor.as_deref(), Some("100"));
+
+    let invalid = GetLog { page: PageQuery { cursor: Some("not a cursor".into()), limit: None } };
+    assert_eq!(server.send(Some(token), &invalid).await.unwrap_err(), StatusCode::BAD_REQUEST);
+
+    // Unchanged pages are answered with 304 until the list changes
+    let (status, etag) = server.get_tagged(token, "/api/games?limit=10", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = server.get_tagged(token, "/api/games?limit=10", Some(&etag)).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    let (_, other_page) = server.get_tagged(token, "/api/games?limit=5", None).await;
+    assert_ne!(other_page, etag);
+
+    let mut change = CloudSyncDelta::new(ALICE);
+    change.games = vec![game(999)];
+    server.push(token, &change).await;
+    let (status, changed) = server.get_tagged(token, "/api/games?limit=10", Some(&etag)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(changed, etag);
+}
@@ -0,0 +1,1263 @@
+//! Typed REST API
+//!
+//! Request and response bodies of the server's REST endpoints, and an
+//! [`Endpoint`] for every route. The server extracts the same types the
+//! clients send, so a request that no longer matches its route is a compile
+//! error instead of a 400 at runtime. Clients only supply the transport
+//! (blocking reqwest on the desktop, the fetch API in the browser).
+//!
+//! A request type that is the whole request implements [`Endpoint`] itself;
+//! routes with path or query parameters have their own endpoint struct.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::leaderboard::{Leaderboard, LeaderboardMetric, LeaderboardScope};
+use crate::models::{
+    AchievementComment, AchievementDifficulty, AchievementFlagVotes, AchievementFlags, AchievementHistory,
+    AchievementTip, AuthCodeExchangeRequest, AuthTokenResponse, CloudSyncData, CloudSyncStatus,
+    CommunityGameRating, ContentKind, ContentReport, FeedEntry, Game, GameAchievement, GameRarity,
+    GameRatingAverage, LogEntry, Page, RefreshTokenRequest, RunHistory, UserProfile,
+};
+use crate::sync::{
+    CloudSyncDelta, EncryptedSyncBlob, SyncCursor, SyncMergePreview, SyncPushResponse, UploadCompleteRequest,
+    UploadSessionInfo, UploadSessionRequest,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+/// A REST route with its request and response types
+pub trait Endpoint {
+    /// JSON request body; `()` for routes without one
+    type Body: Serialize;
+    /// JSON response body
+    type Response: DeserializeOwned;
+
+    const METHOD: Method;
+
+    /// Path and query string, relative to the server URL
+    fn path(&self) -> String;
+
+    fn body(&self) -> Option<&Self::Body> {
+        None
+    }
+}
+
+/// Body of every error response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiError {
+    pub error: String,
+}
+
+/// Body of requests that only change something
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SuccessResponse {
+    pub success: bool,
+}
+
+/// Append the present parameters as a query string
+fn with_query(path: String, params: &[(&str, Option<String>)]) -> String {
+    let query: Vec<String> = params
+        .iter()
+        .filter_map(|(name, value)| value.as_ref().map(|v| format!("{}={}", name, urlencoding::encode(v))))
+        .collect();
+    if query.is_empty() {
+        path
+    } else {
+        format!("{}?{}", path, query.join("&"))
+    }
+}
+
+/// A value as it appears in a query string (enums by their serde name)
+fn query_value<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        Ok(other) => other.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+// ============================================================================
+// Library & history
+// ============================================================================
+
+/// Which page of a list to get
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct PageQuery {
+    /// `next_cursor` of the previous page; omit for the first page
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl PageQuery {
+    fn append_to(&self, path: String) -> String {
+        with_query(path, &[
+            ("cursor", self.cursor.clone()),
+            ("limit", self.limit.map(|l| l.to_string())),
+        ])
+    }
+}
+
+/// The authenticated user's games
+#[derive(Debug, Clone, Default)]
+pub struct GetGames {
+    pub page: PageQuery,
+}
+
+impl Endpoint for GetGames {
+    type Body = ();
+    type Response = Page<Game>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        self.page.append_to("/api/games".to_string())
+    }
+}
+
+/// The authenticated user's achievements of a game
+#[derive(Debug, Clone)]
+pub struct GetAchievements {
+    pub appid: u64,
+    pub page: PageQuery,
+}
+
+impl Endpoint for GetAchievements {
+    type Body = ();
+    type Response = Page<GameAchievement>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        self.page.append_to(format!("/api/games/{}/achievements", self.appid))
+    }
+}
+
+/// The authenticated user's run history
+#[derive(Debug, Clone, Default)]
+pub struct GetRunHistory {
+    pub page: PageQuery,
+}
+
+impl Endpoint for GetRunHistory {
+    type Body = ();
+    type Response = Page<RunHistory>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        self.page.append_to("/api/history/runs".to_string())
+    }
+}
+
+/// The authenticated user's achievement history
+#[derive(Debug, Clone, Default)]
+pub struct GetAchievementHistory {
+    pub page: PageQuery,
+}
+
+impl Endpoint for GetAchievementHistory {
+    type Body = ();
+    type Response = Page<AchievementHistory>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        self.page.append_to("/api/history/achievements".to_string())
+    }
+}
+
+/// The authenticated user's activity log, newest first
+#[derive(Debug, Clone, Default)]
+pub struct GetLog {
+    pub page: PageQuery,
+}
+
+impl Endpoint for GetLog {
+    type Body = ();
+    type Response = Page<LogEntry>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        self.page.append_to("/api/log".to_string())
+    }
+}
+
+/// Profile behind a guest link
+#[derive(Debug, Clone)]
+pub struct GetGuestProfile {
+    pub short_id: String,
+}
+
+impl Endpoint for GetGuestProfile {
+    type Body = ();
+    type Response = UserProfile;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        format!("/api/users/{}", urlencoding::encode(&self.short_id))
+    }
+}
+
+/// Games of a guest link's user
+#[derive(Debug, Clone)]
+pub struct GetGuestGames {
+    pub short_id: String,
+    pub page: PageQuery,
+}
+
+impl Endpoint for GetGuestGames {
+    type Body = ();
+    type Response = Page<Game>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        self.page.append_to(format!("/api/users/{}/games", urlencoding::encode(&self.short_id)))
+    }
+}
+
+/// Achievements of a game of a guest link's user
+#[derive(Debug, Clone)]
+pub struct GetGuestAchievements {
+    pub short_id: String,
+    pub appid: u64,
+    pub page: PageQuery,
+}
+
+impl Endpoint for GetGuestAchievements {
+    type Body = ();
+    type Response = Page<GameAchievement>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        self.page.append_to(format!("/api/users/{}/games/{}/achievements", urlencoding::encode(&self.short_id), self.appid))
+    }
+}
+
+/// Run history of a guest link's user
+#[derive(Debug, Clone)]
+pub struct GetGuestRunHistory {
+    pub short_id: String,
+    pub page: PageQuery,
+}
+
+impl Endpoint for GetGuestRunHistory {
+    type Body = ();
+    type Response = Page<RunHistory>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        self.page.append_to(format!("/api/users/{}/history/runs", urlencoding::encode(&self.short_id)))
+    }
+}
+
+/// Achievement history of a guest link's user
+#[derive(Debug, Clone)]
+pub struct GetGuestAchievementHistory {
+    pub short_id: String,
+    pub page: PageQuery,
+}
+
+impl Endpoint for GetGuestAchievementHistory {
+    type Body = ();
+    type Response = Page<AchievementHistory>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        self.page.append_to(format!("/api/users/{}/history/achievements", urlencoding::encode(&self.short_id)))
+    }
+}
+
+/// Activity log of a guest link's user
+#[derive(Debug, Clone)]
+pub struct GetGuestLog {
+    pub short_id: String,
+    pub page: PageQuery,
+}
+
+impl Endpoint for GetGuestLog {
+    type Body = ();
+    type Response = Page<LogEntry>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        self.page.append_to(format!("/api/users/{}/log", urlencoding::encode(&self.short_id)))
+    }
+}
+
+// ============================================================================
+// Community data
+// ============================================================================
+
+/// Games to get community data for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AppidsRequest {
+    pub appids: Vec<u64>,
+}
+
+/// Community and Steam unlock rates of a game's achievements
+#[derive(Debug, Clone)]
+pub struct GetGameRarity {
+    pub appid: u64,
+}
+
+impl Endpoint for GetGameRarity {
+    type Body = ();
+    type Response = GameRarity;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        format!("/api/games/{}/rarity", self.appid)
+    }
+}
+
+/// Community difficulty averages of a game's achievements
+#[derive(Debug, Clone)]
+pub struct GetGameAchievementAverages {
+    pub appid: u64,
+}
+
+impl Endpoint for GetGameAchievementAverages {
+    type Body = ();
+    type Response = Vec<AchievementDifficulty>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        format!("/api/games/{}/achievement-averages", self.appid)
+    }
+}
+
+/// Community difficulty averages for a whole library
+#[derive(Debug, Clone)]
+pub struct GetAchievementAverages(pub AppidsRequest);
+
+impl Endpoint for GetAchievementAverages {
+    type Body = AppidsRequest;
+    type Response = Vec<AchievementDifficulty>;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/api/achievement/averages".to_string()
+    }
+
+    fn body(&self) -> Option<&AppidsRequest> {
+        Some(&self.0)
+    }
+}
+
+/// Community rating averages for a whole library
+#[derive(Debug, Clone)]
+pub struct GetGameRatingAverages(pub AppidsRequest);
+
+impl Endpoint for GetGameRatingAverages {
+    type Body = AppidsRequest;
+    type Response = Vec<GameRatingAverage>;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/api/community/ratings/averages".to_string()
+    }
+
+    fn body(&self) -> Option<&AppidsRequest> {
+        Some(&self.0)
+    }
+}
+
+/// Community-confirmed achievement flags for a whole library
+#[derive(Debug, Clone)]
+pub struct GetAchievementFlags(pub AppidsRequest);
+
+impl Endpoint for GetAchievementFlags {
+    type Body = AppidsRequest;
+    type Response = Vec<AchievementFlagVotes>;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/api/achievement/flags".to_string()
+    }
+
+    fn body(&self) -> Option<&AppidsRequest> {
+        Some(&self.0)
+    }
+}
+
+/// Ratings and reviews of a game; with a token, the own rating is marked
+#[derive(Debug, Clone)]
+pub struct GetGameRatings {
+    pub appid: u64,
+}
+
+impl Endpoint for GetGameRatings {
+    type Body = ();
+    type Response = CommunityGameRating;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        format!("/api/community/ratings/{}", self.appid)
+    }
+}
+
+/// Rate a game with an optional review, replacing an earlier rating.
+/// Returns the game's ratings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SubmitRatingRequest {
+    pub appid: u64,
+    pub rating: u8,
+    pub comment: Option<String>,
+}
+
+impl Endpoint for SubmitRatingRequest {
+    type Body = Self;
+    type Response = CommunityGameRating;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/api/community/ratings".to_string()
+    }
+
+    fn body(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
+
+// ============================================================================
+// Achievement ratings, comments & tips
+// ============================================================================
+
+/// Rate the difficulty of an achievement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AchievementRatingRequest {
+    pub appid: u64,
+    pub apiname: String,
+    pub rating: u8,
+}
+
+impl Endpoint for AchievementRatingRequest {
+    type Body = Self;
+    type Response = AchievementRatingResponse;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/api/achievement/rating".to_string()
+    }
+
+    fn body(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AchievementRatingResponse {
+    pub success: bool,
+    pub appid: u64,
+    pub apiname: String,
+}
+
+/// Set the user's flags for an achievement (empty flags clear them)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AchievementFlagsRequest {
+    pub appid: u64,
+    pub apiname: String,
+    pub flags: AchievementFlags,
+}
+
+impl Endpoint for AchievementFlagsRequest {
+    type Body = Self;
+    type Response = AchievementRatingResponse;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/api/achievement/flag".to_string()
+    }
+
+    fn body(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
+
+/// All achievement ratings and flags of the authenticated user
+#[derive(Debug, Clone)]
+pub struct GetUserAchievementRatings;
+
+impl Endpoint for GetUserAchievementRatings {
+    type Body = ();
+    type Response = UserAchievementRatingsResponse;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        "/api/achievement/ratings".to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UserAchievementRatingsResponse {
+    pub ratings: Vec<AchievementRatingEntry>,
+    #[serde(default)]
+    pub flags: Vec<AchievementFlagsEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AchievementFlagsEntry {
+    pub appid: u64,
+    pub apiname: String,
+    pub flags: AchievementFlags,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AchievementRatingEntry {
+    pub appid: u64,
+    pub apiname: String,
+    pub rating: u8,
+}
+
+/// Post a comment tagging one or more achievements
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AchievementCommentRequest {
+    /// List of (appid, apiname) pairs
+    pub achievements: Vec<(u64, String)>,
+    pub comment: String,
+}
+
+impl Endpoint for AchievementCommentRequest {
+    type Body = Self;
+    type Response = AchievementCommentResponse;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/api/achievement/comment".to_string()
+    }
+
+    fn body(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AchievementCommentResponse {
+    pub success: bool,
+    pub count: usize,
+    pub comment: AchievementComment,
+}
+
+/// An achievement, for the comment and tip lists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct AchievementQuery {
+    pub appid: u64,
+    pub apiname: String,
+}
+
+impl AchievementQuery {
+    fn append_to(&self, path: &str) -> String {
+        with_query(path.to_string(), &[
+            ("appid", Some(self.appid.to_string())),
+            ("apiname", Some(self.apiname.clone())),
+        ])
+    }
+}
+
+/// Comments tagging an achievement, oldest first; with a token, own
+/// comments are marked
+#[derive(Debug, Clone)]
+pub struct GetAchievementComments(pub AchievementQuery);
+
+impl Endpoint for GetAchievementComments {
+    type Body = ();
+    type Response = Vec<AchievementComment>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        self.0.append_to("/api/achievement/comments")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EditCommentRequest {
+    pub comment: String,
+}
+
+/// Change the text of an own comment
+#[derive(Debug, Clone)]
+pub struct EditAchievementComment {
+    pub id: i64,
+    pub body: EditCommentRequest,
+}
+
+impl Endpoint for EditAchievementComment {
+    type Body = EditCommentRequest;
+    type Response = AchievementComment;
+    const METHOD: Method = Method::Put;
+
+    fn path(&self) -> String {
+        format!("/api/achievement/comment/{}", self.id)
+    }
+
+    fn body(&self) -> Option<&EditCommentRequest> {
+        Some(&self.body)
+    }
+}
+
+/// Delete an own comment
+#[derive(Debug, Clone)]
+pub struct DeleteAchievementComment {
+    pub id: i64,
+}
+
+impl Endpoint for DeleteAchievementComment {
+    type Body = ();
+    type Response = SuccessResponse;
+    const METHOD: Method = Method::Delete;
+
+    fn path(&self) -> String {
+        format!("/api/achievement/comment/{}", self.id)
+    }
+}
+
+/// Tips for an achievement, highest score first; with a token, own tips and
+/// votes are marked
+#[derive(Debug, Clone)]
+pub struct GetAchievementTips(pub AchievementQuery);
+
+impl Endpoint for GetAchievementTips {
+    type Body = ();
+    type Response = Vec<AchievementTip>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        self.0.append_to("/api/achievement/tips")
+    }
+}
+
+/// Submit a tip, replacing the user's earlier tip for the achievement.
+/// Returns the achievement's tips.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SubmitTipRequest {
+    pub appid: u64,
+    pub apiname: String,
+    pub difficulty: u8,
+    pub tip: String,
+}
+
+impl Endpoint for SubmitTipRequest {
+    type Body = Self;
+    type Response = Vec<AchievementTip>;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/api/achievement/tip".to_string()
+    }
+
+    fn body(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VoteTipRequest {
+    /// 1, -1, or 0 to take the vote back
+    pub vote: i8,
+}
+
+/// Vote on another user's tip. Returns the tips of the tip's achievement.
+#[derive(Debug, Clone)]
+pub struct VoteAchievementTip {
+    pub id: i64,
+    pub body: VoteTipRequest,
+}
+
+impl Endpoint for VoteAchievementTip {
+    type Body = VoteTipRequest;
+    type Response = Vec<AchievementTip>;
+    const METHOD: Method = Method::Put;
+
+    fn path(&self) -> String {
+        format!("/api/achievement/tip/{}/vote", self.id)
+    }
+
+    fn body(&self) -> Option<&VoteTipRequest> {
+        Some(&self.body)
+    }
+}
+
+/// Report another user's comment, tip or review to the admins
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ReportRequest {
+    pub kind: ContentKind,
+    pub content_id: i64,
+    pub reason: String,
+}
+
+impl Endpoint for ReportRequest {
+    type Body = Self;
+    type Response = SuccessResponse;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/api/report".to_string()
+    }
+
+    fn body(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
+
+// ============================================================================
+// Moderation (admins only)
+// ============================================================================
+
+/// Open reports, oldest first
+#[derive(Debug, Clone)]
+pub struct GetReports;
+
+impl Endpoint for GetReports {
+    type Body = ();
+    type Response = Vec<ContentReport>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        "/api/admin/reports".to_string()
+    }
+}
+
+/// Close a report without acting on the content
+#[derive(Debug, Clone)]
+pub struct DismissReport {
+    pub id: i64,
+}
+
+impl Endpoint for DismissReport {
+    type Body = ();
+    type Response = SuccessResponse;
+    const METHOD: Method = Method::Delete;
+
+    fn path(&self) -> String {
+        format!("/api/admin/reports/{}", self.id)
+    }
+}
+
+/// Delete any user's content
+#[derive(Debug, Clone)]
+pub struct DeleteContent {
+    pub kind: ContentKind,
+    pub id: i64,
+}
+
+impl Endpoint for DeleteContent {
+    type Body = ();
+    type Response = SuccessResponse;
+    const METHOD: Method = Method::Delete;
+
+    fn path(&self) -> String {
+        format!("/api/admin/content/{}/{}", self.kind.as_str(), self.id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SetHiddenRequest {
+    pub hidden: bool,
+}
+
+/// Hide content from all community queries, or show it again
+#[derive(Debug, Clone)]
+pub struct SetContentHidden {
+    pub kind: ContentKind,
+    pub id: i64,
+    pub body: SetHiddenRequest,
+}
+
+impl Endpoint for SetContentHidden {
+    type Body = SetHiddenRequest;
+    type Response = SuccessResponse;
+    const METHOD: Method = Method::Put;
+
+    fn path(&self) -> String {
+        format!("/api/admin/content/{}/{}/hidden", self.kind.as_str(), self.id)
+    }
+
+    fn body(&self) -> Option<&SetHiddenRequest> {
+        Some(&self.body)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BanRequest {
+    pub banned: bool,
+    pub reason: Option<String>,
+}
+
+/// Ban a user from submitting community content, or lift the ban
+#[derive(Debug, Clone)]
+pub struct SetUserBanned {
+    pub steam_id: String,
+    pub body: BanRequest,
+}
+
+impl Endpoint for SetUserBanned {
+    type Body = BanRequest;
+    type Response = SuccessResponse;
+    const METHOD: Method = Method::Put;
+
+    fn path(&self) -> String {
+        format!("/api/admin/users/{}/ban", urlencoding::encode(&self.steam_id))
+    }
+
+    fn body(&self) -> Option<&BanRequest> {
+        Some(&self.body)
+    }
+}
+
+// ============================================================================
+// Cloud sync
+// ============================================================================
+
+/// Whether the authenticated user has data in the cloud
+#[derive(Debug, Clone)]
+pub struct GetSyncStatus;
+
+impl Endpoint for GetSyncStatus {
+    type Body = ();
+    type Response = CloudSyncStatus;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        "/api/sync/status".to_string()
+    }
+}
+
+/// All of the authenticated user's cloud data
+#[derive(Debug, Clone)]
+pub struct DownloadSyncData;
+
+impl Endpoint for DownloadSyncData {
+    type Body = ();
+    type Response = CloudSyncData;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        "/api/sync/download".to_string()
+    }
+}
+
+/// Upload all user data, merged with the existing cloud data
+#[derive(Debug, Clone)]
+pub struct UploadSyncData<'a>(pub &'a CloudSyncData);
+
+impl Endpoint for UploadSyncData<'_> {
+    type Body = CloudSyncData;
+    type Response = SyncUploadResponse;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/api/sync/upload".to_string()
+    }
+
+    fn body(&self) -> Option<&CloudSyncData> {
+        Some(self.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SyncUploadResponse {
+    pub success: bool,
+    pub games_uploaded: usize,
+    pub achievements_uploaded: usize,
+    pub merged: SyncMergePreview,
+}
+
+/// Delete all of the authenticated user's cloud data
+#[derive(Debug, Clone)]
+pub struct DeleteSyncData;
+
+impl Endpoint for DeleteSyncData {
+    type Body = ();
+    type Response = SuccessResponse;
+    const METHOD: Method = Method::Delete;
+
+    fn path(&self) -> String {
+        "/api/sync/data".to_string()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct PullDeltaQuery {
+    /// Cursor from the previous pull; omit to pull everything
+    #[cfg_attr(feature = "openapi", param(value_type = Option<i64>))]
+    pub since: Option<SyncCursor>,
+}
+
+/// Rows changed in the cloud since a sync cursor
+#[derive(Debug, Clone, Default)]
+pub struct PullSyncDelta(pub PullDeltaQuery);
+
+impl Endpoint for PullSyncDelta {
+    type Body = ();
+    type Response = CloudSyncDelta;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        with_query("/api/sync/delta".to_string(), &[("since", self.0.since.map(|s| s.to_string()))])
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct PushDeltaQuery {
+    /// Only report what would change, without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Push locally changed rows; they are merged like a full upload
+#[derive(Debug, Clone)]
+pub struct PushSyncDelta<'a> {
+    pub query: PushDeltaQuery,
+    pub body: &'a CloudSyncDelta,
+}
+
+impl Endpoint for PushSyncDelta<'_> {
+    type Body = CloudSyncDelta;
+    type Response = SyncPushResponse;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        with_query("/api/sync/delta".to_string(), &[("dry_run", self.query.dry_run.then(|| "true".to_string()))])
+    }
+
+    fn body(&self) -> Option<&CloudSyncDelta> {
+        Some(self.body)
+    }
+}
+
+/// Open a chunked upload session
+impl Endpoint for UploadSessionRequest {
+    type Body = Self;
+    type Response = UploadSessionInfo;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/api/sync/upload/sessions".to_string()
+    }
+
+    fn body(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
+
+/// State of an upload session, to resume it
+#[derive(Debug, Clone)]
+pub struct GetUploadSession {
+    pub session_id: String,
+}
+
+impl Endpoint for GetUploadSession {
+    type Body = ();
+    type Response = UploadSessionInfo;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        format!("/api/sync/upload/sessions/{}", urlencoding::encode(&self.session_id))
+    }
+}
+
+/// Path of an upload session's chunk. Chunks are sent as raw bytes
+/// (`application/octet-stream`) and answered with [`UploadSessionInfo`].
+pub fn upload_chunk_path(session_id: &str, offset: usize) -> String {
+    format!("/api/sync/upload/sessions/{}/chunks/{}", urlencoding::encode(session_id), offset)
+}
+
+/// Finish an upload session and merge its payload
+#[derive(Debug, Clone)]
+pub struct CompleteUploadSession {
+    pub session_id: String,
+    pub body: UploadCompleteRequest,
+}
+
+impl Endpoint for CompleteUploadSession {
+    type Body = UploadCompleteRequest;
+    type Response = SyncPushResponse;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        format!("/api/sync/upload/sessions/{}/complete", urlencoding::encode(&self.session_id))
+    }
+
+    fn body(&self) -> Option<&UploadCompleteRequest> {
+        Some(&self.body)
+    }
+}
+
+/// The user's encrypted backup (null if there is none)
+#[derive(Debug, Clone)]
+pub struct GetEncryptedBackup;
+
+impl Endpoint for GetEncryptedBackup {
+    type Body = ();
+    type Response = Option<EncryptedSyncBlob>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        "/api/sync/encrypted".to_string()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct StoreEncryptedQuery {
+    /// Key check of the backup being replaced; the upload is rejected if the
+    /// stored backup was re-encrypted with a different key in the meantime
+    pub expected_key_check: Option<String>,
+}
+
+/// Store the encrypted backup, replacing the previous one. Answered with
+/// `409 Conflict` if `expected_key_check` no longer matches.
+#[derive(Debug, Clone)]
+pub struct StoreEncryptedBackup<'a> {
+    pub query: StoreEncryptedQuery,
+    pub body: &'a EncryptedSyncBlob,
+}
+
+impl Endpoint for StoreEncryptedBackup<'_> {
+    type Body = EncryptedSyncBlob;
+    type Response = SuccessResponse;
+    const METHOD: Method = Method::Put;
+
+    fn path(&self) -> String {
+        with_query("/api/sync/encrypted".to_string(), &[("expected_key_check", self.query.expected_key_check.clone())])
+    }
+
+    fn body(&self) -> Option<&EncryptedSyncBlob> {
+        Some(self.body)
+    }
+}
+
+/// Delete the encrypted backup, keeping the rest of the cloud data
+#[derive(Debug, Clone)]
+pub struct DeleteEncryptedBackup;
+
+impl Endpoint for DeleteEncryptedBackup {
+    type Body = ();
+    type Response = SuccessResponse;
+    const METHOD: Method = Method::Delete;
+
+    fn path(&self) -> String {
+        "/api/sync/encrypted".to_string()
+    }
+}
+
+// ============================================================================
+// Follows & leaderboards
+// ============================================================================
+
+/// Users the authenticated user follows
+#[derive(Debug, Clone)]
+pub struct GetFollowing;
+
+impl Endpoint for GetFollowing {
+    type Body = ();
+    type Response = Vec<UserProfile>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        "/api/follows".to_string()
+    }
+}
+
+/// Follow a user by short_id. Returns the followed users.
+#[derive(Debug, Clone)]
+pub struct FollowUser {
+    pub short_id: String,
+}
+
+impl Endpoint for FollowUser {
+    type Body = ();
+    type Response = Vec<UserProfile>;
+    const METHOD: Method = Method::Put;
+
+    fn path(&self) -> String {
+        format!("/api/follows/{}", urlencoding::encode(&self.short_id))
+    }
+}
+
+/// Unfollow a user by short_id. Returns the followed users.
+#[derive(Debug, Clone)]
+pub struct UnfollowUser {
+    pub short_id: String,
+}
+
+impl Endpoint for UnfollowUser {
+    type Body = ();
+    type Response = Vec<UserProfile>;
+    const METHOD: Method = Method::Delete;
+
+    fn path(&self) -> String {
+        format!("/api/follows/{}", urlencoding::encode(&self.short_id))
+    }
+}
+
+/// Recent activity of followed users
+#[derive(Debug, Clone)]
+pub struct GetFeed;
+
+impl Endpoint for GetFeed {
+    type Body = ();
+    type Response = Vec<FeedEntry>;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        "/api/feed".to_string()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct LeaderboardQuery {
+    #[serde(default)]
+    pub metric: LeaderboardMetric,
+    #[serde(default)]
+    pub scope: LeaderboardScope,
+}
+
+/// Leaderboard among followed users or site-wide
+#[derive(Debug, Clone, Default)]
+pub struct GetLeaderboard(pub LeaderboardQuery);
+
+impl Endpoint for GetLeaderboard {
+    type Body = ();
+    type Response = Leaderboard;
+    const METHOD: Method = Method::Get;
+
+    fn path(&self) -> String {
+        with_query("/api/leaderboards".to_string(), &[
+            ("metric", Some(query_value(&self.0.metric))),
+            ("scope", Some(query_value(&self.0.scope))),
+        ])
+    }
+}
+
+/// Show or hide the authenticated user on global leaderboards. The response
+/// repeats the new setting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LeaderboardOptIn {
+    pub enabled: bool,
+}
+
+impl Endpoint for LeaderboardOptIn {
+    type Body = Self;
+    type Response = LeaderboardOptIn;
+    const METHOD: Method = Method::Put;
+
+    fn path(&self) -> String {
+        "/api/leaderboards/opt-in".to_string()
+    }
+
+    fn body(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
+
+// ============================================================================
+// Sessions
+// ============================================================================
+
+/// Exchange a one-time login code for the session tokens it was issued for
+impl Endpoint for AuthCodeExchangeRequest {
+    type Body = Self;
+    type Response = AuthTokenResponse;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/auth/token".to_string()
+    }
+
+    fn body(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
+
+/// Get a new access token and replace the refresh token
+#[derive(Debug, Clone)]
+pub struct RefreshSession(pub RefreshTokenRequest);
+
+impl Endpoint for RefreshSession {
+    type Body = RefreshTokenRequest;
+    type Response = AuthTokenResponse;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/auth/refresh".to_string()
+    }
+
+    fn body(&self) -> Option<&RefreshTokenRequest> {
+        Some(&self.0)
+    }
+}
+
+/// Log out a session by its refresh token
+#[derive(Debug, Clone)]
+pub struct RevokeSession(pub RefreshTokenRequest);
+
+impl Endpoint for RevokeSession {
+    type Body = RefreshTokenRequest;
+    type Response = SuccessResponse;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/auth/revoke".to_string()
+    }
+
+    fn body(&self) -> Option<&RefreshTokenRequest> {
+        Some(&self.0)
+    }
+}
+
+/// Log out all sessions of the authenticated user
+#[derive(Debug, Clone)]
+pub struct LogoutAllSessions;
+
+impl Endpoint for LogoutAllSessions {
+    type Body = ();
+    type Response = LogoutAllResponse;
+    const METHOD: Method = Method::Post;
+
+    fn path(&self) -> String {
+        "/auth/logout-all".to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LogoutAllResponse {
+    pub success: bool,
+    /// Number of sessions that were ended
+    pub revoked: usize,
+}
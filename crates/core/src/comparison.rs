@@ -0,0 +1,180 @@
+//! Library comparison between two users
+//!
+//! The overview is built from both users' game lists; achievement differences
+//! are compared one game at a time, when the user opens that game.
+
+use crate::models::{Game, GameAchievement, UserProfile};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Totals for one side of a comparison
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LibraryScore {
+    pub games_owned: usize,
+    /// Games with at least one achievement
+    pub games_with_achievements: usize,
+    pub achievements_unlocked: i32,
+    pub achievements_total: i32,
+    /// Games with every achievement unlocked
+    pub perfect_games: usize,
+    /// Average completion of played games with achievements (0-100)
+    pub avg_completion: f32,
+}
+
+impl LibraryScore {
+    pub fn from_games(games: &[Game]) -> Self {
+        let with_achievements: Vec<&Game> = games.iter()
+            .filter(|g| g.achievements_total.map(|t| t > 0).unwrap_or(false))
+            .collect();
+        let played_percents: Vec<f32> = with_achievements.iter()
+            .filter(|g| g.playtime_forever > 0)
+            .filter_map(|g| g.completion_percent())
+            .collect();
+
+        Self {
+            games_owned: games.len(),
+            games_with_achievements: with_achievements.len(),
+            achievements_unlocked: with_achievements.iter().filter_map(|g| g.achievements_unlocked).sum(),
+            achievements_total: with_achievements.iter().filter_map(|g| g.achievements_total).sum(),
+            perfect_games: games.iter()
+                .filter(|g| g.completion_percent().map(|p| p >= 100.0).unwrap_or(false))
+                .count(),
+            avg_completion: if played_percents.is_empty() {
+                0.0
+            } else {
+                played_percents.iter().sum::<f32>() / played_percents.len() as f32
+            },
+        }
+    }
+}
+
+/// One user's progress in a shared game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameProgress {
+    pub playtime_forever: u32,
+    pub achievements_unlocked: Option<i32>,
+    pub achievements_total: Option<i32>,
+}
+
+impl GameProgress {
+    fn from_game(game: &Game) -> Self {
+        Self {
+            playtime_forever: game.playtime_forever,
+            achievements_unlocked: game.achievements_unlocked,
+            achievements_total: game.achievements_total,
+        }
+    }
+
+    pub fn completion_percent(&self) -> Option<f32> {
+        match (self.achievements_unlocked, self.achievements_total) {
+            (Some(unlocked), Some(total)) if total > 0 => Some(unlocked as f32 / total as f32 * 100.0),
+            _ => None,
+        }
+    }
+}
+
+/// A game owned by both users
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedGame {
+    pub appid: u64,
+    pub name: String,
+    pub img_icon_url: Option<String>,
+    pub mine: GameProgress,
+    pub theirs: GameProgress,
+}
+
+/// Overview comparing the requesting user's library with another user's
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryComparison {
+    /// The user being compared against
+    pub other: UserProfile,
+    pub mine: LibraryScore,
+    pub theirs: LibraryScore,
+    /// Games owned by both, sorted by name
+    pub shared_games: Vec<SharedGame>,
+    /// Number of games only the requesting user owns
+    pub only_mine: usize,
+    /// Number of games only the other user owns
+    pub only_theirs: usize,
+}
+
+impl LibraryComparison {
+    pub fn new(other: UserProfile, mine: &[Game], theirs: &[Game]) -> Self {
+        let theirs_by_appid: HashMap<u64, &Game> = theirs.iter().map(|g| (g.appid, g)).collect();
+
+        let mut shared_games: Vec<SharedGame> = mine.iter()
+            .filter_map(|game| {
+                let other_game = theirs_by_appid.get(&game.appid)?;
+                Some(SharedGame {
+                    appid: game.appid,
+                    name: game.name.clone(),
+                    img_icon_url: game.img_icon_url.clone(),
+                    mine: GameProgress::from_game(game),
+                    theirs: GameProgress::from_game(other_game),
+                })
+            })
+            .collect();
+        shared_games.sort_by_key(|g| g.name.to_lowercase());
+
+        Self {
+            other,
+            mine: LibraryScore::from_games(mine),
+            theirs: LibraryScore::from_games(theirs),
+            only_mine: mine.len() - shared_games.len(),
+            only_theirs: theirs.len() - shared_games.len(),
+            shared_games,
+        }
+    }
+}
+
+/// An achievement unlocked by only one of the two users
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AchievementDifference {
+    pub apiname: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub icon: String,
+    pub unlocktime: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Achievement differences within one shared game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameAchievementComparison {
+    pub appid: u64,
+    /// Unlocked by the requesting user only
+    pub only_mine: Vec<AchievementDifference>,
+    /// Unlocked by the other user only
+    pub only_theirs: Vec<AchievementDifference>,
+    /// Unlocked by both
+    pub both: usize,
+}
+
+impl GameAchievementComparison {
+    pub fn new(appid: u64, mine: &[GameAchievement], theirs: &[GameAchievement]) -> Self {
+        let unlocked = |achievements: &[GameAchievement]| -> HashSet<String> {
+            achievements.iter().filter(|a| a.achieved).map(|a| a.apiname.clone()).collect()
+        };
+        let mine_unlocked = unlocked(mine);
+        let theirs_unlocked = unlocked(theirs);
+
+        let differences = |achievements: &[GameAchievement], other: &HashSet<String>| -> Vec<AchievementDifference> {
+            achievements.iter()
+                .filter(|a| a.achieved && !other.contains(&a.apiname))
+                .map(|a| AchievementDifference {
+                    apiname: a.apiname.clone(),
+                    name: a.name.clone(),
+                    description: a.description.clone(),
+                    icon: a.icon.clone(),
+                    unlocktime: a.unlocktime,
+                })
+                .collect()
+        };
+
+        Self {
+            appid,
+            only_mine: differences(mine, &theirs_unlocked),
+            only_theirs: differences(theirs, &mine_unlocked),
+            both: mine_unlocked.intersection(&theirs_unlocked).count(),
+        }
+    }
+}
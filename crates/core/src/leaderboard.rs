@@ -0,0 +1,92 @@
+//! Leaderboards among followed users and site-wide
+//!
+//! Rankings are computed by the server from cached per-user totals, which
+//! are refreshed periodically rather than on every request.
+
+use crate::models::UserProfile;
+use serde::{Deserialize, Serialize};
+
+/// What users are ranked by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardMetric {
+    #[default]
+    TotalUnlocks,
+    PerfectGames,
+    /// Average completion of played games with achievements (0-100)
+    AvgCompletion,
+    /// Achievements unlocked since the start of the current month
+    UnlocksThisMonth,
+}
+
+impl LeaderboardMetric {
+    pub const ALL: [LeaderboardMetric; 4] = [
+        LeaderboardMetric::TotalUnlocks,
+        LeaderboardMetric::PerfectGames,
+        LeaderboardMetric::AvgCompletion,
+        LeaderboardMetric::UnlocksThisMonth,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LeaderboardMetric::TotalUnlocks => "Total unlocks",
+            LeaderboardMetric::PerfectGames => "Perfect games",
+            LeaderboardMetric::AvgCompletion => "Avg. completion",
+            LeaderboardMetric::UnlocksThisMonth => "Unlocks this month",
+        }
+    }
+
+    /// Format a value of this metric for display
+    pub fn format_value(&self, value: f64) -> String {
+        match self {
+            LeaderboardMetric::AvgCompletion => format!("{:.1}%", value),
+            _ => format!("{}", value as i64),
+        }
+    }
+}
+
+/// Who is ranked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardScope {
+    /// The requesting user and the users they follow
+    #[default]
+    Following,
+    /// All users who opted in to public leaderboards
+    Global,
+}
+
+impl LeaderboardScope {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LeaderboardScope::Following => "Following",
+            LeaderboardScope::Global => "Everyone",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LeaderboardEntry {
+    /// 1-based rank; tied values share a rank
+    pub rank: u32,
+    pub user: UserProfile,
+    pub value: f64,
+    /// This entry is the requesting user
+    pub is_you: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Leaderboard {
+    pub metric: LeaderboardMetric,
+    pub scope: LeaderboardScope,
+    /// Top entries, plus the requesting user's entry if ranked lower
+    pub entries: Vec<LeaderboardEntry>,
+    /// When the cached totals were last refreshed
+    pub refreshed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Whether the requesting user appears on the global leaderboard
+    pub opted_in: bool,
+}
@@ -4,11 +4,19 @@
 //! - Data models shared between desktop, WASM, and backend
 //! - WebSocket message types for client-server communication
 //! - Error types
+//! - Cloud sync merge logic
+//! - Library comparison between users
+//! - Leaderboards
+//! - Typed REST API endpoints (`api`)
 //! - Shared UI components (with `ui` feature)
 
 pub mod models;
 pub mod messages;
 pub mod error;
+pub mod sync;
+pub mod comparison;
+pub mod leaderboard;
+pub mod api;
 
 #[cfg(feature = "ui")]
 pub mod ui;
@@ -16,6 +24,9 @@ pub mod ui;
 pub use models::*;
 pub use messages::*;
 pub use error::*;
+pub use sync::*;
+pub use comparison::*;
+pub use leaderboard::*;
 
 #[cfg(feature = "ui")]
 pub use ui::*;
@@ -2,12 +2,18 @@
 
 use serde::{Deserialize, Serialize};
 use crate::models::*;
+use crate::comparison::{LibraryComparison, GameAchievementComparison};
+use crate::leaderboard::{Leaderboard, LeaderboardMetric, LeaderboardScope};
+
+/// `AuthError` reason for an expired access token. The connection stays open
+/// and can be authenticated again with a refreshed token.
+pub const AUTH_TOKEN_EXPIRED: &str = "Token expired";
 
 /// Messages sent from client to server
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ClientMessage {
-    /// Authenticate with JWT token
+    /// Authenticate with JWT token; sent again after refreshing the token
     Authenticate { token: String },
     
     /// Request user's games list
@@ -28,14 +34,15 @@ pub enum ClientMessage {
     /// Request history data
     FetchHistory,
     
-    /// Submit a game rating
+    /// Submit a game rating with an optional review, replacing the user's
+    /// earlier rating of the game
     SubmitRating { 
         appid: u64, 
         rating: u8, 
         comment: Option<String> 
     },
     
-    /// Submit an achievement tip
+    /// Submit an achievement tip, replacing the user's earlier tip for it
     SubmitAchievementTip { 
         appid: u64, 
         apiname: String, 
@@ -50,6 +57,20 @@ pub enum ClientMessage {
         rating: u8,
     },
     
+    /// Set own flags for an achievement (empty flags clear them)
+    SubmitAchievementFlags {
+        appid: u64,
+        apiname: String,
+        flags: AchievementFlags,
+    },
+    
+    /// Report a comment, tip or review to the admins (requires authentication)
+    ReportContent {
+        kind: ContentKind,
+        content_id: i64,
+        reason: String,
+    },
+    
     /// Submit a comment for multiple achievements
     SubmitAchievementComment {
         /// List of (appid, apiname) tuples
@@ -57,12 +78,24 @@ pub enum ClientMessage {
         comment: String,
     },
     
+    /// Get the comments tagging an achievement, oldest first
+    FetchAchievementComments { appid: u64, apiname: String },
+    
+    /// Change the text of an own comment
+    EditAchievementComment { id: i64, comment: String },
+    
+    /// Delete an own comment
+    DeleteAchievementComment { id: i64 },
+    
     /// Get community ratings for a game
     GetCommunityRatings { appid: u64 },
     
-    /// Get community tips for an achievement
+    /// Get community tips for an achievement, highest score first
     GetCommunityTips { appid: u64, apiname: String },
     
+    /// Vote on a tip: 1 (helpful), -1 (not helpful) or 0 to take the vote back
+    VoteAchievementTip { tip_id: i64, vote: i8 },
+    
     /// View another user's library by short_id (no authentication required)
     ViewGuestLibrary { short_id: String },
     
@@ -72,6 +105,42 @@ pub enum ClientMessage {
     /// Request history data when viewing as guest
     FetchGuestHistory { short_id: String },
     
+    /// Compare own library with another user's (requires authentication)
+    CompareLibraries { short_id: String },
+    
+    /// Compare unlocked achievements in a game both users own (requires authentication)
+    CompareGameAchievements { short_id: String, appid: u64 },
+    
+    /// Follow another user's public profile (requires authentication)
+    FollowUser { short_id: String },
+    
+    /// Stop following a user (requires authentication)
+    UnfollowUser { short_id: String },
+    
+    /// Request the list of followed users (requires authentication)
+    FetchFollowing,
+    
+    /// Request recent activity of followed users (requires authentication)
+    FetchFeed,
+    
+    /// Request community and Steam unlock rates for a game's achievements
+    FetchAchievementRarity { appid: u64 },
+    
+    /// Request community difficulty averages for the achievements of some games
+    FetchAchievementAverages { appids: Vec<u64> },
+    
+    /// Request community rating averages of some games
+    FetchGameRatingAverages { appids: Vec<u64> },
+    
+    /// Request community-confirmed flags for the achievements of some games
+    FetchAchievementFlags { appids: Vec<u64> },
+    
+    /// Request a leaderboard (requires authentication)
+    FetchLeaderboard { metric: LeaderboardMetric, scope: LeaderboardScope },
+    
+    /// Show or hide the user on global leaderboards (requires authentication)
+    SetLeaderboardOptIn { enabled: bool },
+    
     /// Ping to keep connection alive
     Ping,
 }
@@ -85,7 +154,8 @@ pub enum ServerMessage {
         user: UserProfile 
     },
     
-    /// Authentication failed
+    /// Authentication failed. [`AUTH_TOKEN_EXPIRED`] means the access token
+    /// has to be refreshed and sent again with `Authenticate`.
     AuthError { 
         reason: String 
     },
@@ -112,7 +182,8 @@ pub enum ServerMessage {
         games: Vec<Game>,
     },
     
-    /// Community ratings for a game
+    /// Community ratings and reviews for a game, newest first (also the
+    /// reply to submitting a rating)
     CommunityRatings { 
         appid: u64,
         avg_rating: f32,
@@ -120,7 +191,8 @@ pub enum ServerMessage {
         ratings: Vec<GameRating> 
     },
     
-    /// Community tips for an achievement
+    /// Community tips for an achievement, highest score first (also the
+    /// reply to submitting or voting on a tip)
     CommunityTips { 
         appid: u64,
         apiname: String,
@@ -136,8 +208,23 @@ pub enum ServerMessage {
     /// Achievement rating submitted successfully
     AchievementRatingSubmitted { appid: u64, apiname: String },
     
-    /// Achievement comment submitted successfully
-    AchievementCommentSubmitted { count: usize },
+    /// Achievement flags stored
+    AchievementFlagsSubmitted { appid: u64, apiname: String },
+    
+    /// Report stored
+    ContentReported { kind: ContentKind, content_id: i64 },
+    
+    /// Achievement comment stored
+    AchievementCommentSubmitted { comment: AchievementComment },
+    
+    /// Comments tagging an achievement, oldest first
+    AchievementComments { appid: u64, apiname: String, comments: Vec<AchievementComment> },
+    
+    /// An own comment was edited
+    AchievementCommentUpdated { comment: AchievementComment },
+    
+    /// An own comment was deleted
+    AchievementCommentDeleted { id: i64 },
     
     /// Single game refresh completed
     SingleGameRefreshComplete {
@@ -162,6 +249,36 @@ pub enum ServerMessage {
     /// Guest library not found (invalid short_id)
     GuestNotFound { short_id: String },
     
+    /// Own library compared with another user's
+    LibraryComparison { comparison: LibraryComparison },
+    
+    /// Achievement differences in a shared game
+    GameAchievementComparison { short_id: String, comparison: GameAchievementComparison },
+    
+    /// Users the authenticated user follows
+    Following { users: Vec<UserProfile> },
+    
+    /// Recent activity of followed users, newest first
+    Feed { entries: Vec<FeedEntry> },
+    
+    /// Unlock rates for a game's achievements
+    AchievementRarity { rarity: GameRarity },
+    
+    /// Community difficulty averages (only rated achievements are included)
+    AchievementAverages { averages: Vec<AchievementDifficulty> },
+    
+    /// Community rating averages (only rated games are included)
+    GameRatingAverages { averages: Vec<GameRatingAverage> },
+    
+    /// Community flags (only flags that reached the vote threshold are included)
+    AchievementFlags { flags: Vec<AchievementFlagVotes> },
+    
+    /// Requested leaderboard
+    Leaderboard { leaderboard: Leaderboard },
+    
+    /// Global leaderboard opt-in changed
+    LeaderboardOptIn { enabled: bool },
+    
     /// Generic error
     Error { 
         message: String 
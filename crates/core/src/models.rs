@@ -1,5 +1,7 @@
 //! Shared data models used across all platforms
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -25,6 +27,7 @@ pub struct SteamGame {
 
 /// Game with tracked data
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Game {
     pub appid: u64,
     pub name: String,
@@ -78,6 +81,7 @@ pub struct AchievementSchema {
 
 /// Achievement stored in database with display info
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct GameAchievement {
     pub appid: u64,
     pub apiname: String,
@@ -89,8 +93,245 @@ pub struct GameAchievement {
     pub unlocktime: Option<DateTime<Utc>>,
 }
 
+/// Unlock rates of one achievement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AchievementRarity {
+    pub apiname: String,
+    /// Share of Overachiever users owning the game who unlocked it (0-100)
+    pub community_percent: f32,
+    /// Steam's global unlock percentage (0-100), if Steam reports one
+    pub steam_percent: Option<f32>,
+}
+
+/// Unlock rates of all achievements in a game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GameRarity {
+    pub appid: u64,
+    /// Overachiever users who own the game and have had its achievements scanned
+    pub community_owners: u32,
+    pub achievements: Vec<AchievementRarity>,
+}
+
+impl GameRarity {
+    pub fn get(&self, apiname: &str) -> Option<&AchievementRarity> {
+        self.achievements.iter().find(|a| a.apiname == apiname)
+    }
+}
+
+/// Community difficulty average of one achievement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AchievementDifficulty {
+    pub appid: u64,
+    pub apiname: String,
+    /// Average difficulty rating (1-5)
+    pub avg_rating: f32,
+    pub rating_count: i32,
+}
+
+/// Structured warning players can attach to an achievement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AchievementFlag {
+    /// Can be permanently missed in a playthrough
+    Missable,
+    /// Currently doesn't unlock when it should
+    Bugged,
+    /// Takes a long, repetitive effort
+    Grind,
+    /// Needs other players or official servers
+    OnlineOnly,
+}
+
+impl AchievementFlag {
+    pub const ALL: [AchievementFlag; 4] = [
+        AchievementFlag::Missable,
+        AchievementFlag::Bugged,
+        AchievementFlag::Grind,
+        AchievementFlag::OnlineOnly,
+    ];
+
+    fn bit(self) -> u8 {
+        match self {
+            AchievementFlag::Missable => 1,
+            AchievementFlag::Bugged => 2,
+            AchievementFlag::Grind => 4,
+            AchievementFlag::OnlineOnly => 8,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AchievementFlag::Missable => "Missable",
+            AchievementFlag::Bugged => "Bugged",
+            AchievementFlag::Grind => "Grind",
+            AchievementFlag::OnlineOnly => "Online only",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            AchievementFlag::Missable => "Can be permanently missed in a playthrough",
+            AchievementFlag::Bugged => "Currently unobtainable or doesn't unlock reliably",
+            AchievementFlag::Grind => "Takes a long, repetitive effort",
+            AchievementFlag::OnlineOnly => "Needs other players or official servers",
+        }
+    }
+}
+
+/// A user's flags for one achievement, stored as a bitmask
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(transparent)]
+pub struct AchievementFlags(u8);
+
+impl AchievementFlags {
+    /// Flags from a stored bitmask; unknown bits are dropped
+    pub fn from_bits(bits: u8) -> Self {
+        Self(bits & AchievementFlag::ALL.iter().fold(0, |mask, flag| mask | flag.bit()))
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, flag: AchievementFlag) -> bool {
+        self.0 & flag.bit() != 0
+    }
+
+    pub fn set(&mut self, flag: AchievementFlag, on: bool) {
+        if on {
+            self.0 |= flag.bit();
+        } else {
+            self.0 &= !flag.bit();
+        }
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = AchievementFlag> {
+        AchievementFlag::ALL.into_iter().filter(move |flag| self.contains(*flag))
+    }
+}
+
+/// Players needed before the server reports a flag for an achievement
+pub const FLAG_VOTE_THRESHOLD: i32 = 3;
+
+/// Community votes for one flag of an achievement (only reported once
+/// [`FLAG_VOTE_THRESHOLD`] is reached)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AchievementFlagVotes {
+    pub appid: u64,
+    pub apiname: String,
+    pub flag: AchievementFlag,
+    pub votes: i32,
+}
+
+/// Community rating average of one game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GameRatingAverage {
+    pub appid: u64,
+    /// Average star rating (1-5)
+    pub avg_rating: f32,
+    pub rating_count: i32,
+}
+
+/// Client-side cache of community averages: difficulty per achievement and
+/// per game, game ratings, and confirmed achievement flags
+#[derive(Debug, Clone, Default)]
+pub struct CommunityAverages {
+    achievements: HashMap<(u64, String), (f32, i32)>,
+    games: HashMap<u64, f32>,
+    game_ratings: HashMap<u64, (f32, i32)>,
+    flags: HashMap<(u64, String), Vec<(AchievementFlag, i32)>>,
+}
+
+impl CommunityAverages {
+    /// Store averages from the server; a response covers all rated
+    /// achievements of each game in it
+    pub fn insert(&mut self, averages: Vec<AchievementDifficulty>) {
+        let mut sums: HashMap<u64, (f32, u32)> = HashMap::new();
+        for avg in averages {
+            let sum = sums.entry(avg.appid).or_default();
+            sum.0 += avg.avg_rating;
+            sum.1 += 1;
+            self.achievements.insert((avg.appid, avg.apiname), (avg.avg_rating, avg.rating_count));
+        }
+        for (appid, (total, count)) in sums {
+            self.games.insert(appid, total / count as f32);
+        }
+    }
+    
+    /// Average rating and vote count of an achievement
+    pub fn achievement(&self, appid: u64, apiname: &str) -> Option<(f32, i32)> {
+        self.achievements.get(&(appid, apiname.to_string())).copied()
+    }
+    
+    /// Mean of the averages of a game's rated achievements
+    pub fn game(&self, appid: u64) -> Option<f32> {
+        self.games.get(&appid).copied()
+    }
+    
+    /// Store game rating averages from the server
+    pub fn insert_game_ratings(&mut self, averages: Vec<GameRatingAverage>) {
+        for avg in averages {
+            self.set_game_rating(avg.appid, avg.avg_rating, avg.rating_count);
+        }
+    }
+    
+    /// Update one game's rating average (e.g. after the user rated it)
+    pub fn set_game_rating(&mut self, appid: u64, avg_rating: f32, rating_count: i32) {
+        if rating_count > 0 {
+            self.game_ratings.insert(appid, (avg_rating, rating_count));
+        } else {
+            self.game_ratings.remove(&appid);
+        }
+    }
+    
+    /// Average star rating and rating count of a game
+    pub fn game_rating(&self, appid: u64) -> Option<(f32, i32)> {
+        self.game_ratings.get(&appid).copied()
+    }
+    
+    /// Store confirmed flags from the server
+    pub fn insert_flags(&mut self, votes: Vec<AchievementFlagVotes>) {
+        for vote in votes {
+            let flags = self.flags.entry((vote.appid, vote.apiname)).or_default();
+            flags.retain(|(flag, _)| *flag != vote.flag);
+            flags.push((vote.flag, vote.votes));
+        }
+    }
+    
+    /// Confirmed flags of an achievement with their vote counts
+    pub fn achievement_flags(&self, appid: u64, apiname: &str) -> &[(AchievementFlag, i32)] {
+        self.flags.get(&(appid, apiname.to_string())).map(|f| f.as_slice()).unwrap_or(&[])
+    }
+    
+    /// Whether any achievement of a game is confirmed as missable
+    pub fn game_has_missables(&self, appid: u64) -> bool {
+        self.flags.iter().any(|((flag_appid, _), flags)| {
+            *flag_appid == appid && flags.iter().any(|(flag, _)| *flag == AchievementFlag::Missable)
+        })
+    }
+    
+    pub fn clear(&mut self) {
+        self.achievements.clear();
+        self.games.clear();
+        self.game_ratings.clear();
+        self.flags.clear();
+    }
+}
+
 /// Run history entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct RunHistory {
     pub id: i64,
     pub run_at: DateTime<Utc>,
@@ -104,6 +345,7 @@ pub struct RunHistory {
 
 /// History of achievement progress over time
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct AchievementHistory {
     pub id: i64,
     pub recorded_at: DateTime<Utc>,
@@ -136,6 +378,7 @@ pub struct FirstPlay {
 
 /// A log entry that can be either an achievement or first play
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(tag = "type")]
 pub enum LogEntry {
     Achievement {
@@ -168,28 +411,74 @@ impl LogEntry {
 // Community features (for Hybrid and Remote modes)
 // ============================================================================
 
-/// Game rating submitted by a user
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub const MAX_REVIEW_LENGTH: usize = 1000;
+
+/// Check a game rating and its optional review before they are stored
+pub fn validate_game_rating(rating: u8, review: Option<&str>) -> Result<(), String> {
+    if !(1..=5).contains(&rating) {
+        return Err("Rating must be between 1 and 5".to_string());
+    }
+    if review.map(|r| r.chars().count() > MAX_REVIEW_LENGTH).unwrap_or(false) {
+        return Err(format!("Review is longer than {} characters", MAX_REVIEW_LENGTH));
+    }
+    Ok(())
+}
+
+/// Game rating submitted by a user (one per user and game), with an
+/// optional short review
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct GameRating {
-    pub id: Option<i64>,
-    pub steam_id: String,
+    pub id: i64,
+    pub author: UserProfile,
     pub appid: u64,
     pub rating: u8, // 1-5 stars
+    /// Review text
     pub comment: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// The rating was given by the requesting user
+    #[serde(default)]
+    pub is_yours: bool,
 }
 
-/// Achievement tip/guide submitted by a user
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub const MAX_TIP_LENGTH: usize = 2000;
+
+/// Check an achievement tip before it is stored
+pub fn validate_tip(difficulty: u8, tip: &str) -> Result<(), String> {
+    if !(1..=5).contains(&difficulty) {
+        return Err("Difficulty must be between 1 and 5".to_string());
+    }
+    if tip.trim().is_empty() {
+        return Err("Tip cannot be empty".to_string());
+    }
+    if tip.chars().count() > MAX_TIP_LENGTH {
+        return Err(format!("Tip is longer than {} characters", MAX_TIP_LENGTH));
+    }
+    Ok(())
+}
+
+/// Achievement tip/guide submitted by a user (one per user and achievement)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct AchievementTip {
-    pub id: Option<i64>,
-    pub steam_id: String,
+    pub id: i64,
+    pub author: UserProfile,
     pub appid: u64,
     pub apiname: String,
     pub difficulty: u8, // 1-5
     pub tip: String,
     pub created_at: DateTime<Utc>,
+    /// Set when the author changed the tip after posting
+    pub edited_at: Option<DateTime<Utc>>,
+    /// Upvotes minus downvotes
+    pub score: i32,
+    /// The requesting user's vote: 1, -1 or 0 for none
+    #[serde(default)]
+    pub your_vote: i8,
+    /// The tip was written by the requesting user (may be edited)
+    #[serde(default)]
+    pub is_yours: bool,
 }
 
 /// Achievement rating submitted by a user
@@ -203,19 +492,103 @@ pub struct AchievementRating {
     pub created_at: DateTime<Utc>,
 }
 
+/// Longest accepted achievement comment, in characters
+pub const MAX_COMMENT_LENGTH: usize = 2000;
+
+/// Check the text of an achievement comment before it is stored
+pub fn validate_comment(comment: &str) -> Result<(), String> {
+    if comment.trim().is_empty() {
+        return Err("Comment cannot be empty".to_string());
+    }
+    if comment.chars().count() > MAX_COMMENT_LENGTH {
+        return Err(format!("Comment is longer than {} characters", MAX_COMMENT_LENGTH));
+    }
+    Ok(())
+}
+
 /// Achievement comment that can tag multiple achievements
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct AchievementComment {
-    pub id: Option<i64>,
-    pub steam_id: String,
+    pub id: i64,
+    pub author: UserProfile,
     /// List of (appid, apiname) tuples for tagged achievements
     pub achievements: Vec<(u64, String)>,
     pub comment: String,
     pub created_at: DateTime<Utc>,
+    /// Set when the author changed the text after posting
+    pub edited_at: Option<DateTime<Utc>>,
+    /// The comment was written by the requesting user (may be edited or deleted)
+    #[serde(default)]
+    pub is_yours: bool,
+}
+
+/// Kind of user-written community content that can be reported and moderated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ContentKind {
+    /// An [`AchievementComment`]
+    Comment,
+    /// An [`AchievementTip`]
+    Tip,
+    /// The review text of a [`GameRating`]
+    Review,
+}
+
+impl ContentKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContentKind::Comment => "comment",
+            ContentKind::Tip => "tip",
+            ContentKind::Review => "review",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "comment" => Some(ContentKind::Comment),
+            "tip" => Some(ContentKind::Tip),
+            "review" => Some(ContentKind::Review),
+            _ => None,
+        }
+    }
+}
+
+/// Longest accepted report reason, in characters
+pub const MAX_REPORT_REASON_LENGTH: usize = 500;
+
+/// Check the reason of a content report before it is stored
+pub fn validate_report_reason(reason: &str) -> Result<(), String> {
+    if reason.trim().is_empty() {
+        return Err("Please describe the problem".to_string());
+    }
+    if reason.chars().count() > MAX_REPORT_REASON_LENGTH {
+        return Err(format!("Reason is longer than {} characters", MAX_REPORT_REASON_LENGTH));
+    }
+    Ok(())
+}
+
+/// An open report of community content, as listed for admins
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ContentReport {
+    pub id: i64,
+    pub kind: ContentKind,
+    pub content_id: i64,
+    pub reporter: UserProfile,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+    /// Author and text of the reported content
+    pub author: UserProfile,
+    pub content: String,
+    /// The content is already hidden from community queries
+    pub hidden: bool,
 }
 
 /// Aggregated community rating for a game
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CommunityGameRating {
     pub appid: u64,
     pub avg_rating: f32,
@@ -223,8 +596,21 @@ pub struct CommunityGameRating {
     pub ratings: Vec<GameRating>,
 }
 
+impl CommunityGameRating {
+    pub fn from_ratings(appid: u64, ratings: Vec<GameRating>) -> Self {
+        let rating_count = ratings.len() as i32;
+        let avg_rating = if rating_count > 0 {
+            ratings.iter().map(|r| r.rating as f32).sum::<f32>() / rating_count as f32
+        } else {
+            0.0
+        };
+        Self { appid, avg_rating, rating_count, ratings }
+    }
+}
+
 /// User profile from Steam
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct UserProfile {
     pub steam_id: String,
     pub display_name: String,
@@ -233,6 +619,70 @@ pub struct UserProfile {
     pub short_id: Option<String>,
 }
 
+/// Clients refresh their access token this many seconds before it expires
+pub const TOKEN_REFRESH_MARGIN_SECS: i64 = 120;
+
+/// Exchange of the one-time login code handed to a `redirect_uri` for tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AuthCodeExchangeRequest {
+    pub code: String,
+}
+
+/// Refresh token of a session, to renew or revoke it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+/// Tokens of a login session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AuthTokenResponse {
+    /// Short-lived access token (JWT)
+    pub token: String,
+    pub steam_id: String,
+    /// One-time token for the next refresh; replaced on every refresh
+    pub refresh_token: String,
+    /// When the access token expires (Unix timestamp)
+    pub expires_at: i64,
+}
+
+impl AuthTokenResponse {
+    /// Whether an access token expiring at `expires_at` is due for a refresh
+    pub fn needs_refresh(expires_at: i64) -> bool {
+        chrono::Utc::now().timestamp() >= expires_at - TOKEN_REFRESH_MARGIN_SECS
+    }
+}
+
+/// Extract a short_id from a profile link (https://overachiever.space/<short_id>)
+/// or a bare short_id
+pub fn short_id_from_input(input: &str) -> Option<String> {
+    let id = input.trim().trim_end_matches('/').rsplit('/').next()?.trim();
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return None;
+    }
+    Some(id.to_string())
+}
+
+/// Activity of a followed user, for the friends feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedEntry {
+    pub user: UserProfile,
+    pub entry: LogEntry,
+}
+
+/// One page of a REST list. Pass `next_cursor` back as `cursor` to get the
+/// next page; it is `None` on the last one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
 /// Sync result after updating from Steam
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncResult {
@@ -247,15 +697,20 @@ pub struct SyncResult {
 
 /// Cloud sync status for a user
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CloudSyncStatus {
     pub has_data: bool,
     pub game_count: i32,
     pub achievement_count: i32,
     pub last_sync: Option<DateTime<Utc>>,
+    /// The cloud holds an encrypted backup (see `EncryptedSyncBlob`)
+    #[serde(default)]
+    pub encrypted: bool,
 }
 
 /// Lightweight achievement data for cloud sync (no icons/descriptions)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SyncAchievement {
     pub appid: u64,
     pub apiname: String,
@@ -265,6 +720,7 @@ pub struct SyncAchievement {
 
 /// Full cloud sync data bundle for upload/download
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CloudSyncData {
     pub steam_id: String,
     pub games: Vec<Game>,
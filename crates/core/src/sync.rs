@@ -0,0 +1,404 @@
+//! Cloud sync merge logic shared by the backend and the desktop app
+//!
+//! Syncing from several machines must never lose history, so instead of
+//! replacing one side with the other, both sides are merged:
+//! - `run_history` and `achievement_history` are unioned by timestamp
+//! - the earliest known unlock is kept per achievement
+//! - the newest metadata is kept per game
+//!
+//! After the first upload, clients exchange [`CloudSyncDelta`]s that carry
+//! only the rows changed since a server-issued [`SyncCursor`].
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{AchievementHistory, CloudSyncData, CloudSyncStatus, Game, RunHistory, SyncAchievement};
+
+/// Current version of the [`CloudSyncDelta`] wire format
+pub const SYNC_DELTA_VERSION: u32 = 1;
+
+/// Server-issued position in a user's change stream.
+/// Pulling with a cursor returns only rows changed after it.
+pub type SyncCursor = i64;
+
+/// Rows changed on one side of a sync.
+///
+/// Pushed by clients with the rows changed locally since their last push,
+/// and pulled from the server with the rows changed since a cursor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CloudSyncDelta {
+    /// Format version, see [`SYNC_DELTA_VERSION`]
+    pub version: u32,
+    pub steam_id: String,
+    /// On pull: the cursor to pass as `since` next time (unused on push)
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<i64>))]
+    pub cursor: Option<SyncCursor>,
+    #[serde(default)]
+    pub games: Vec<Game>,
+    #[serde(default)]
+    pub achievements: Vec<SyncAchievement>,
+    #[serde(default)]
+    pub run_history: Vec<RunHistory>,
+    #[serde(default)]
+    pub achievement_history: Vec<AchievementHistory>,
+}
+
+impl CloudSyncDelta {
+    /// Create an empty delta in the current format version
+    pub fn new(steam_id: impl Into<String>) -> Self {
+        Self {
+            version: SYNC_DELTA_VERSION,
+            steam_id: steam_id.into(),
+            cursor: None,
+            games: Vec::new(),
+            achievements: Vec::new(),
+            run_history: Vec::new(),
+            achievement_history: Vec::new(),
+        }
+    }
+
+    /// Returns true if this delta was produced by a compatible format version
+    pub fn is_supported(&self) -> bool {
+        self.version == SYNC_DELTA_VERSION
+    }
+
+    /// Total number of rows carried by this delta
+    pub fn row_count(&self) -> usize {
+        self.games.len() + self.achievements.len() + self.run_history.len() + self.achievement_history.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.row_count() == 0
+    }
+
+    /// View the delta as a (partial) sync bundle so it can be merged
+    pub fn into_data(self) -> CloudSyncData {
+        CloudSyncData {
+            steam_id: self.steam_id,
+            games: self.games,
+            achievements: self.achievements,
+            run_history: self.run_history,
+            achievement_history: self.achievement_history,
+            exported_at: Utc::now(),
+        }
+    }
+
+    /// The part of the delta that is uploaded in plaintext when backups are
+    /// encrypted: the library shown in the guest view, without the history
+    pub fn without_history(&self) -> CloudSyncDelta {
+        CloudSyncDelta {
+            run_history: Vec::new(),
+            achievement_history: Vec::new(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Server response to a delta push
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SyncPushResponse {
+    /// What the push changed (or would change, for a dry run) on the server
+    pub merged: SyncMergePreview,
+}
+
+/// Summary of what a merge changes on the receiving side
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SyncMergePreview {
+    pub games_added: usize,
+    pub games_updated: usize,
+    pub achievements_added: usize,
+    /// Achievements that become unlocked
+    pub unlocks_added: usize,
+    /// Achievements that were already unlocked but get an earlier unlock time
+    pub unlocks_backdated: usize,
+    pub run_history_added: usize,
+    pub achievement_history_added: usize,
+}
+
+impl SyncMergePreview {
+    /// Returns true if the merge would not change anything
+    pub fn is_empty(&self) -> bool {
+        *self == SyncMergePreview::default()
+    }
+
+    /// Human-readable lines describing the changes (empty if nothing changes)
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.games_added > 0 {
+            lines.push(format!("{} new games", self.games_added));
+        }
+        if self.games_updated > 0 {
+            lines.push(format!("{} games with newer data", self.games_updated));
+        }
+        if self.achievements_added > 0 {
+            lines.push(format!("{} new achievements", self.achievements_added));
+        }
+        if self.unlocks_added > 0 {
+            lines.push(format!("{} newly unlocked achievements", self.unlocks_added));
+        }
+        if self.unlocks_backdated > 0 {
+            lines.push(format!("{} unlock times corrected to an earlier date", self.unlocks_backdated));
+        }
+        if self.run_history_added > 0 {
+            lines.push(format!("{} run history entries", self.run_history_added));
+        }
+        if self.achievement_history_added > 0 {
+            lines.push(format!("{} achievement history entries", self.achievement_history_added));
+        }
+        lines
+    }
+}
+
+/// Rows that must be written to one side of a sync to bring it up to date
+/// with the merged result. Games and achievements hold the merged values.
+#[derive(Debug, Clone, Default)]
+pub struct SyncChanges {
+    pub games: Vec<Game>,
+    pub achievements: Vec<SyncAchievement>,
+    pub run_history: Vec<RunHistory>,
+    pub achievement_history: Vec<AchievementHistory>,
+    pub preview: SyncMergePreview,
+}
+
+impl SyncChanges {
+    pub fn is_empty(&self) -> bool {
+        self.preview.is_empty()
+    }
+}
+
+/// Ordering key used to decide which copy of a game is newer
+fn game_freshness(game: &Game) -> (Option<DateTime<Utc>>, Option<u32>, u32) {
+    (game.last_achievement_scrape, game.rtime_last_played, game.playtime_forever)
+}
+
+/// Merge two copies of the same game, keeping the newest metadata
+/// but the earliest `added_at`
+fn merge_game(existing: &Game, incoming: &Game) -> Game {
+    let mut merged = if game_freshness(incoming) > game_freshness(existing) {
+        incoming.clone()
+    } else {
+        existing.clone()
+    };
+    merged.added_at = existing.added_at.min(incoming.added_at);
+    merged
+}
+
+/// Earliest of two optional unlock times
+fn earliest(a: Option<DateTime<Utc>>, b: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+impl CloudSyncData {
+    /// Compute the changes needed to merge `incoming` into `self`.
+    /// `self` is left untouched; use [`CloudSyncData::merge`] to apply them.
+    pub fn merge_changes(&self, incoming: &CloudSyncData) -> SyncChanges {
+        let mut changes = SyncChanges::default();
+
+        // Games: keep the newest metadata
+        let games: HashMap<u64, &Game> = self.games.iter().map(|g| (g.appid, g)).collect();
+        for game in &incoming.games {
+            match games.get(&game.appid) {
+                None => {
+                    changes.preview.games_added += 1;
+                    changes.games.push(game.clone());
+                }
+                Some(existing) => {
+                    let merged = merge_game(existing, game);
+                    if game_freshness(&merged) != game_freshness(existing) || merged.added_at != existing.added_at {
+                        changes.preview.games_updated += 1;
+                        changes.games.push(merged);
+                    }
+                }
+            }
+        }
+
+        // Achievements: unlocked on either side wins, earliest unlock time wins
+        let achievements: HashMap<(u64, &str), &SyncAchievement> = self.achievements.iter()
+            .map(|a| ((a.appid, a.apiname.as_str()), a))
+            .collect();
+        for ach in &incoming.achievements {
+            match achievements.get(&(ach.appid, ach.apiname.as_str())) {
+                None => {
+                    changes.preview.achievements_added += 1;
+                    if ach.achieved {
+                        changes.preview.unlocks_added += 1;
+                    }
+                    changes.achievements.push(ach.clone());
+                }
+                Some(existing) => {
+                    let achieved = existing.achieved || ach.achieved;
+                    let unlocktime = earliest(existing.unlocktime, ach.unlocktime);
+                    if achieved == existing.achieved && unlocktime == existing.unlocktime {
+                        continue;
+                    }
+                    if achieved && !existing.achieved {
+                        changes.preview.unlocks_added += 1;
+                    } else {
+                        changes.preview.unlocks_backdated += 1;
+                    }
+                    changes.achievements.push(SyncAchievement {
+                        appid: ach.appid,
+                        apiname: ach.apiname.clone(),
+                        achieved,
+                        unlocktime,
+                    });
+                }
+            }
+        }
+
+        // History: union by timestamp
+        let run_times: HashSet<DateTime<Utc>> = self.run_history.iter().map(|r| r.run_at).collect();
+        let mut seen = HashSet::new();
+        for rh in &incoming.run_history {
+            if !run_times.contains(&rh.run_at) && seen.insert(rh.run_at) {
+                changes.run_history.push(rh.clone());
+            }
+        }
+        changes.preview.run_history_added = changes.run_history.len();
+
+        let ach_times: HashSet<DateTime<Utc>> = self.achievement_history.iter().map(|a| a.recorded_at).collect();
+        let mut seen = HashSet::new();
+        for ah in &incoming.achievement_history {
+            if !ach_times.contains(&ah.recorded_at) && seen.insert(ah.recorded_at) {
+                changes.achievement_history.push(ah.clone());
+            }
+        }
+        changes.preview.achievement_history_added = changes.achievement_history.len();
+
+        changes
+    }
+
+    /// Merge `incoming` into `self`, returning a summary of what changed
+    pub fn merge(&mut self, incoming: &CloudSyncData) -> SyncMergePreview {
+        let changes = self.merge_changes(incoming);
+        self.apply_changes(&changes);
+        changes.preview
+    }
+
+    /// Apply previously computed changes to this bundle
+    pub fn apply_changes(&mut self, changes: &SyncChanges) {
+        for game in &changes.games {
+            match self.games.iter_mut().find(|g| g.appid == game.appid) {
+                Some(existing) => *existing = game.clone(),
+                None => self.games.push(game.clone()),
+            }
+        }
+        for ach in &changes.achievements {
+            match self.achievements.iter_mut().find(|a| a.appid == ach.appid && a.apiname == ach.apiname) {
+                Some(existing) => *existing = ach.clone(),
+                None => self.achievements.push(ach.clone()),
+            }
+        }
+
+        self.run_history.extend(changes.run_history.iter().cloned());
+        self.run_history.sort_by_key(|r| r.run_at);
+        self.achievement_history.extend(changes.achievement_history.iter().cloned());
+        self.achievement_history.sort_by_key(|a| a.recorded_at);
+    }
+
+    /// Timestamp of the most recent run in this bundle
+    /// (matches how the server reports `CloudSyncStatus.last_sync`)
+    pub fn last_sync(&self) -> Option<DateTime<Utc>> {
+        self.run_history.iter().map(|r| r.run_at).max()
+    }
+}
+
+impl CloudSyncStatus {
+    /// Returns true if the cloud has received data since `last_known_sync`,
+    /// i.e. another device uploaded after this one last synced
+    pub fn has_remote_changes(&self, last_known_sync: Option<DateTime<Utc>>) -> bool {
+        match (self.last_sync, last_known_sync) {
+            (Some(remote), Some(local)) => remote > local,
+            (Some(_), None) => self.has_data,
+            (None, _) => false,
+        }
+    }
+}
+
+// ============================================================================
+// Chunked uploads
+// ============================================================================
+
+/// Compression applied to an upload payload before it is split into chunks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum SyncEncoding {
+    Identity,
+    Gzip,
+    Zstd,
+}
+
+impl SyncEncoding {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SyncEncoding::Identity => "uncompressed",
+            SyncEncoding::Gzip => "gzip",
+            SyncEncoding::Zstd => "zstd",
+        }
+    }
+}
+
+/// Request to open a chunked upload session for a [`CloudSyncDelta`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UploadSessionRequest {
+    /// Encodings the client can produce, most preferred first
+    pub encodings: Vec<SyncEncoding>,
+    /// Only report what the upload would change, without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// State of a chunked upload session, returned by every session endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UploadSessionInfo {
+    pub session_id: String,
+    /// Encoding chosen by the server; the payload must be compressed with it
+    pub encoding: SyncEncoding,
+    /// Maximum size of a single chunk in bytes
+    pub chunk_size: usize,
+    /// Bytes received so far; the next chunk must start at this offset
+    pub received_bytes: usize,
+}
+
+/// Request to finish a chunked upload and merge its payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UploadCompleteRequest {
+    /// Total size of the encoded payload, checked against what the server received
+    pub total_bytes: usize,
+}
+
+// ============================================================================
+// Encrypted backups
+// ============================================================================
+
+/// Format version of [`EncryptedSyncBlob`]
+pub const ENCRYPTED_SYNC_VERSION: u32 = 1;
+
+/// A [`CloudSyncData`] encrypted on the client with a passphrase-derived key.
+/// The server stores it as-is and cannot read it; binary fields are base64.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EncryptedSyncBlob {
+    pub version: u32,
+    /// Salt used to derive the key from the passphrase
+    pub salt: String,
+    /// Identifies the key the blob was encrypted with, so a wrong passphrase or
+    /// one changed on another device is detected without attempting to decrypt
+    pub key_check: String,
+    pub nonce: String,
+    pub ciphertext: String,
+    /// Latest run in the encrypted data, kept readable for the sync status
+    pub last_sync: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,249 @@
+//! Achievement comment thread - shared between desktop and WASM
+//!
+//! Renders: Comments tagging one achievement below its row in the expanded
+//! games table, with posting for logged-in users and editing/deleting of
+//! their own comments
+
+use std::collections::{HashMap, HashSet};
+
+use egui::{self, Color32, RichText, Ui};
+use egui_phosphor::regular;
+
+use crate::{AchievementComment, AchievementTip, validate_comment};
+use super::GamesTablePlatform;
+
+/// Open thread and drafts, owned by the platform
+#[derive(Clone, Default)]
+pub struct CommentThreadState {
+    /// Achievement whose thread is shown (appid, apiname)
+    pub open: Option<(u64, String)>,
+    /// Text of a new comment
+    pub draft: String,
+    /// Comment being edited (id, text)
+    pub editing: Option<(i64, String)>,
+    /// Own tip being written or edited (difficulty, text)
+    pub tip_draft: Option<(u8, String)>,
+}
+
+impl CommentThreadState {
+    pub fn is_open(&self, appid: u64, apiname: &str) -> bool {
+        self.open.as_ref().map(|(a, n)| *a == appid && n == apiname).unwrap_or(false)
+    }
+
+    /// Show an achievement's thread, or hide it if it is shown
+    pub fn toggle(&mut self, appid: u64, apiname: &str) {
+        self.open = if self.is_open(appid, apiname) {
+            None
+        } else {
+            Some((appid, apiname.to_string()))
+        };
+        self.draft.clear();
+        self.editing = None;
+        self.tip_draft = None;
+    }
+}
+
+/// Loaded comment threads and tips and the thread UI state, owned by the platform
+#[derive(Clone, Default)]
+pub struct CommentCache {
+    pub thread: CommentThreadState,
+    threads: HashMap<(u64, String), Vec<AchievementComment>>,
+    requested: HashSet<(u64, String)>,
+    tips: HashMap<(u64, String), Vec<AchievementTip>>,
+    tips_requested: HashSet<(u64, String)>,
+}
+
+impl CommentCache {
+    pub fn get(&self, appid: u64, apiname: &str) -> Option<&[AchievementComment]> {
+        self.threads.get(&(appid, apiname.to_string())).map(|c| c.as_slice())
+    }
+
+    /// Mark a thread as requested; returns false if it already was
+    pub fn start_request(&mut self, appid: u64, apiname: &str) -> bool {
+        self.requested.insert((appid, apiname.to_string()))
+    }
+
+    pub fn set_thread(&mut self, appid: u64, apiname: String, comments: Vec<AchievementComment>) {
+        self.threads.insert((appid, apiname), comments);
+    }
+
+    /// Add a new comment to the loaded threads of the achievements it tags
+    pub fn add(&mut self, comment: AchievementComment) {
+        for key in &comment.achievements {
+            if let Some(thread) = self.threads.get_mut(key) {
+                thread.push(comment.clone());
+            }
+        }
+    }
+
+    /// Replace an edited comment wherever it is loaded
+    pub fn update(&mut self, comment: AchievementComment) {
+        for existing in self.threads.values_mut().flatten().filter(|c| c.id == comment.id) {
+            *existing = comment.clone();
+        }
+    }
+
+    pub fn remove(&mut self, id: i64) {
+        for thread in self.threads.values_mut() {
+            thread.retain(|c| c.id != id);
+        }
+    }
+
+    pub fn tips(&self, appid: u64, apiname: &str) -> Option<&[AchievementTip]> {
+        self.tips.get(&(appid, apiname.to_string())).map(|t| t.as_slice())
+    }
+
+    /// Mark an achievement's tips as requested; returns false if they already were
+    pub fn start_tips_request(&mut self, appid: u64, apiname: &str) -> bool {
+        self.tips_requested.insert((appid, apiname.to_string()))
+    }
+
+    /// Store an achievement's tips (the server sends the whole sorted list
+    /// after every submission or vote)
+    pub fn set_tips(&mut self, appid: u64, apiname: String, tips: Vec<AchievementTip>) {
+        self.tips.insert((appid, apiname), tips);
+    }
+
+    /// Forget loaded threads and tips (e.g. after logging in or out, which
+    /// changes which comments and votes are marked as own)
+    pub fn clear(&mut self) {
+        self.threads.clear();
+        self.requested.clear();
+        self.tips.clear();
+        self.tips_requested.clear();
+    }
+}
+
+enum CommentAction {
+    Post(String),
+    Edit(i64, String),
+    Delete(i64),
+}
+
+const AUTHOR_COLOR: Color32 = Color32::from_rgb(180, 140, 255);
+
+/// Render the comments of an achievement (call only when the platform has a
+/// [`CommentThreadState`])
+pub fn render_comment_thread<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P, appid: u64, apiname: &str) {
+    let Some(mut state) = platform.comment_thread().cloned() else {
+        return;
+    };
+    let comments = platform.achievement_comments(appid, apiname).map(|c| c.to_vec());
+    let can_post = platform.is_authenticated();
+    let mut action = None;
+
+    ui.indent(("comment_thread", appid, apiname), |ui| {
+        ui.add_space(4.0);
+        ui.label(RichText::new(format!("{} Comments", regular::CHAT_CIRCLE_TEXT)).strong());
+        match &comments {
+            None => {
+                platform.request_achievement_comments(appid, apiname);
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(RichText::new("Loading comments...").color(Color32::GRAY).small());
+                });
+            }
+            Some(comments) if comments.is_empty() => {
+                ui.label(RichText::new("No comments yet.").color(Color32::GRAY).italics());
+            }
+            Some(comments) => {
+                for comment in comments {
+                    if let Some(a) = render_comment(ui, comment, &mut state) {
+                        action = Some(a);
+                    }
+                    ui.add_space(4.0);
+                }
+            }
+        }
+
+        ui.add_space(2.0);
+        if can_post {
+            ui.add(
+                egui::TextEdit::multiline(&mut state.draft)
+                    .hint_text("Share a tip or ask a question...")
+                    .desired_rows(2)
+                    .desired_width(f32::INFINITY)
+            );
+            let valid = validate_comment(&state.draft).err();
+            ui.horizontal(|ui| {
+                let post = ui.add_enabled(valid.is_none(), egui::Button::new(format!("{} Post", regular::PAPER_PLANE_TILT)));
+                if post.clicked() {
+                    action = Some(CommentAction::Post(state.draft.trim().to_string()));
+                    state.draft.clear();
+                }
+                // Only explain problems other than an empty draft
+                if let Some(e) = valid.as_ref().filter(|_| !state.draft.trim().is_empty()) {
+                    ui.label(RichText::new(e).color(Color32::from_rgb(230, 80, 80)).small());
+                }
+            });
+        } else {
+            ui.label(RichText::new("Link your Steam account to comment.").color(Color32::GRAY).small());
+        }
+        ui.add_space(4.0);
+    });
+
+    if let Some(thread) = platform.comment_thread() {
+        *thread = state;
+    }
+    match action {
+        Some(CommentAction::Post(text)) => platform.post_achievement_comment(appid, apiname.to_string(), text),
+        Some(CommentAction::Edit(id, text)) => platform.edit_achievement_comment(id, text),
+        Some(CommentAction::Delete(id)) => platform.delete_achievement_comment(id),
+        None => {}
+    }
+}
+
+/// One comment, or its editor when it is being edited
+fn render_comment(ui: &mut Ui, comment: &AchievementComment, state: &mut CommentThreadState) -> Option<CommentAction> {
+    let mut action = None;
+
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(&comment.author.display_name).color(AUTHOR_COLOR).strong());
+        let date = comment.created_at.format("%Y-%m-%d").to_string();
+        ui.label(RichText::new(date).color(Color32::GRAY).small());
+        if let Some(edited_at) = comment.edited_at {
+            ui.label(RichText::new("(edited)").color(Color32::GRAY).small())
+                .on_hover_text(edited_at.format("%Y-%m-%d %H:%M UTC").to_string());
+        }
+        if comment.is_yours && state.editing.is_none() {
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button(regular::TRASH.to_string()).on_hover_text("Delete").clicked() {
+                    action = Some(CommentAction::Delete(comment.id));
+                }
+                if ui.small_button(regular::PENCIL_SIMPLE.to_string()).on_hover_text("Edit").clicked() {
+                    state.editing = Some((comment.id, comment.comment.clone()));
+                }
+            });
+        }
+    });
+
+    let others = comment.achievements.len().saturating_sub(1);
+    if others > 0 {
+        ui.label(RichText::new(format!("Also about {} other achievement(s)", others)).color(Color32::GRAY).small().italics());
+    }
+
+    match &mut state.editing {
+        Some((id, text)) if *id == comment.id => {
+            ui.add(egui::TextEdit::multiline(text).desired_rows(2).desired_width(f32::INFINITY));
+            let valid = validate_comment(text).is_ok();
+            let mut done = false;
+            ui.horizontal(|ui| {
+                if ui.add_enabled(valid, egui::Button::new("Save")).clicked() {
+                    action = Some(CommentAction::Edit(*id, text.trim().to_string()));
+                    done = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    done = true;
+                }
+            });
+            if done {
+                state.editing = None;
+            }
+        }
+        _ => {
+            ui.label(&comment.comment);
+        }
+    }
+
+    action
+}
@@ -0,0 +1,327 @@
+//! Library comparison panel - shared between desktop and WASM
+//!
+//! Renders: Score deltas, games owned by both users with each user's
+//! completion, and per-game achievements one user has that the other lacks
+
+use std::collections::HashSet;
+
+use egui::{self, Color32, RichText, Ui};
+use egui_phosphor::regular;
+
+use crate::{LibraryComparison, GameAchievementComparison, AchievementDifference, SharedGame};
+use super::StatsPanelPlatform;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum ComparisonSort {
+    #[default]
+    Name,
+    /// Largest completion lead first
+    MyLead,
+    /// Largest completion deficit first
+    TheirLead,
+}
+
+impl ComparisonSort {
+    fn label(&self) -> &'static str {
+        match self {
+            ComparisonSort::Name => "Name",
+            ComparisonSort::MyLead => "Where I'm ahead",
+            ComparisonSort::TheirLead => "Where they're ahead",
+        }
+    }
+}
+
+/// UI state of the comparison panel, owned by the platform
+#[derive(Clone, Default)]
+pub struct ComparisonViewState {
+    pub filter_name: String,
+    pub sort: ComparisonSort,
+    /// Hide shared games without achievements
+    pub only_with_achievements: bool,
+    pub expanded: HashSet<u64>,
+}
+
+/// Platform-specific operations needed for the comparison panel
+/// (icons come from [`StatsPanelPlatform`])
+pub trait ComparisonPanelPlatform: StatsPanelPlatform {
+    /// The loaded comparison, if any
+    fn comparison(&self) -> Option<&LibraryComparison>;
+
+    /// Achievement differences for a shared game, if loaded
+    fn game_comparison(&self, appid: u64) -> Option<&GameAchievementComparison>;
+
+    /// Load achievement differences for a shared game
+    fn request_game_comparison(&mut self, appid: u64);
+
+    fn comparison_view(&self) -> &ComparisonViewState;
+
+    fn comparison_view_mut(&mut self) -> &mut ComparisonViewState;
+}
+
+// ============================================================================
+// Rendering Functions
+// ============================================================================
+
+const MINE_COLOR: Color32 = Color32::from_rgb(100, 200, 100);
+const THEIRS_COLOR: Color32 = Color32::from_rgb(90, 150, 230);
+
+/// Difference in completion (mine - theirs) for sorting; unknown counts as 0%
+fn completion_delta(game: &SharedGame) -> f32 {
+    game.mine.completion_percent().unwrap_or(0.0) - game.theirs.completion_percent().unwrap_or(0.0)
+}
+
+/// Indices of shared games matching the filters, in display order
+fn filtered_shared_games(comparison: &LibraryComparison, view: &ComparisonViewState) -> Vec<usize> {
+    let filter = view.filter_name.to_lowercase();
+    let mut indices: Vec<usize> = comparison.shared_games.iter()
+        .enumerate()
+        .filter(|(_, g)| filter.is_empty() || g.name.to_lowercase().contains(&filter))
+        .filter(|(_, g)| {
+            !view.only_with_achievements
+                || g.mine.achievements_total.unwrap_or(0) > 0
+                || g.theirs.achievements_total.unwrap_or(0) > 0
+        })
+        .map(|(i, _)| i)
+        .collect();
+
+    let games = &comparison.shared_games;
+    match view.sort {
+        ComparisonSort::Name => {}
+        ComparisonSort::MyLead => indices.sort_by(|a, b| {
+            completion_delta(&games[*b]).total_cmp(&completion_delta(&games[*a]))
+        }),
+        ComparisonSort::TheirLead => indices.sort_by(|a, b| {
+            completion_delta(&games[*a]).total_cmp(&completion_delta(&games[*b]))
+        }),
+    }
+    indices
+}
+
+/// Render the complete comparison (score deltas and shared games)
+pub fn render_comparison<P: ComparisonPanelPlatform>(ui: &mut Ui, platform: &mut P) {
+    let Some(comparison) = platform.comparison() else {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label("Comparing libraries...");
+        });
+        return;
+    };
+
+    render_score_deltas(ui, comparison);
+    ui.add_space(12.0);
+
+    ui.label(format!(
+        "{} games in common  ·  {} only you  ·  {} only {}",
+        comparison.shared_games.len(),
+        comparison.only_mine,
+        comparison.only_theirs,
+        comparison.other.display_name
+    ));
+    ui.add_space(4.0);
+
+    // Filters work on a copy so the comparison can stay borrowed while rendering
+    let mut view = platform.comparison_view().clone();
+    ui.horizontal(|ui| {
+        ui.label(regular::MAGNIFYING_GLASS);
+        ui.add(egui::TextEdit::singleline(&mut view.filter_name).hint_text("Filter by name...").desired_width(180.0));
+        egui::ComboBox::from_id_salt("comparison_sort")
+            .selected_text(view.sort.label())
+            .show_ui(ui, |ui| {
+                for sort in [ComparisonSort::Name, ComparisonSort::MyLead, ComparisonSort::TheirLead] {
+                    ui.selectable_value(&mut view.sort, sort, sort.label());
+                }
+            });
+        ui.checkbox(&mut view.only_with_achievements, "With achievements only");
+    });
+    ui.separator();
+
+    let mut toggled = None;
+    let indices = filtered_shared_games(comparison, &view);
+    egui::ScrollArea::vertical()
+        .id_salt("comparison_games")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            if indices.is_empty() {
+                ui.label("No games in common match the filters.");
+            }
+            for i in indices {
+                let game = &comparison.shared_games[i];
+                let expanded = view.expanded.contains(&game.appid);
+                if render_shared_game_row(ui, &*platform, game, expanded) {
+                    toggled = Some(game.appid);
+                }
+                if expanded {
+                    ui.indent(("comparison_details", game.appid), |ui| {
+                        match platform.game_comparison(game.appid) {
+                            Some(details) => render_achievement_differences(ui, &*platform, details, &comparison.other.display_name),
+                            None => {
+                                ui.horizontal(|ui| {
+                                    ui.spinner();
+                                    ui.label("Loading achievements...");
+                                });
+                            }
+                        }
+                    });
+                    ui.add_space(4.0);
+                }
+            }
+        });
+
+    if let Some(appid) = toggled {
+        if !view.expanded.remove(&appid) {
+            view.expanded.insert(appid);
+            if platform.game_comparison(appid).is_none() {
+                platform.request_game_comparison(appid);
+            }
+        }
+    }
+    *platform.comparison_view_mut() = view;
+}
+
+/// Grid of both users' totals with the difference
+pub fn render_score_deltas(ui: &mut Ui, comparison: &LibraryComparison) {
+    let mine = &comparison.mine;
+    let theirs = &comparison.theirs;
+
+    egui::Grid::new("comparison_scores")
+        .num_columns(4)
+        .spacing([24.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("");
+            ui.label(RichText::new("You").color(MINE_COLOR).strong());
+            ui.label(RichText::new(&comparison.other.display_name).color(THEIRS_COLOR).strong());
+            ui.label(RichText::new("Difference").strong());
+            ui.end_row();
+
+            let rows: [(&str, f64, f64, bool); 5] = [
+                ("Games owned", mine.games_owned as f64, theirs.games_owned as f64, false),
+                ("Games with achievements", mine.games_with_achievements as f64, theirs.games_with_achievements as f64, false),
+                ("Achievements unlocked", mine.achievements_unlocked as f64, theirs.achievements_unlocked as f64, false),
+                ("Perfect games", mine.perfect_games as f64, theirs.perfect_games as f64, false),
+                ("Avg. completion", mine.avg_completion as f64, theirs.avg_completion as f64, true),
+            ];
+            for (label, mine, theirs, is_percent) in rows {
+                let format = |v: f64| if is_percent { format!("{:.1}%", v) } else { format!("{}", v as i64) };
+                ui.label(label);
+                ui.label(format(mine));
+                ui.label(format(theirs));
+                ui.label(delta_text(mine - theirs, is_percent));
+                ui.end_row();
+            }
+        });
+}
+
+/// Signed difference, green when the user is ahead
+fn delta_text(delta: f64, is_percent: bool) -> RichText {
+    let text = if is_percent {
+        format!("{:+.1}%", delta)
+    } else {
+        format!("{:+}", delta as i64)
+    };
+    if delta > 0.0 {
+        RichText::new(text).color(MINE_COLOR)
+    } else if delta < 0.0 {
+        RichText::new(text).color(THEIRS_COLOR)
+    } else {
+        RichText::new(text).color(Color32::GRAY)
+    }
+}
+
+/// One shared game with both completion bars. Returns true when clicked.
+fn render_shared_game_row<P: ComparisonPanelPlatform>(ui: &mut Ui, platform: &P, game: &SharedGame, expanded: bool) -> bool {
+    let has_achievements = game.mine.achievements_total.unwrap_or(0) > 0 || game.theirs.achievements_total.unwrap_or(0) > 0;
+    let mut clicked = false;
+
+    ui.horizontal(|ui| {
+        if has_achievements {
+            let icon = if expanded { regular::CARET_DOWN } else { regular::CARET_RIGHT };
+            clicked = ui.small_button(icon.to_string()).clicked();
+        } else {
+            ui.add_space(20.0);
+        }
+
+        if let Some(icon_hash) = game.img_icon_url.as_ref().filter(|h| !h.is_empty()) {
+            ui.add(
+                egui::Image::new(platform.game_icon_source(ui, game.appid, icon_hash))
+                    .fit_to_exact_size(egui::vec2(20.0, 20.0))
+                    .corner_radius(2.0)
+            );
+        }
+        ui.add_sized([220.0, 20.0], egui::Label::new(RichText::new(&game.name).strong()).truncate());
+
+        for (progress, color) in [(&game.mine, MINE_COLOR), (&game.theirs, THEIRS_COLOR)] {
+            match progress.completion_percent() {
+                Some(pct) => {
+                    ui.add(
+                        egui::ProgressBar::new(pct / 100.0)
+                            .desired_width(110.0)
+                            .fill(color.gamma_multiply(0.6))
+                            .text(format!(
+                                "{}/{}",
+                                progress.achievements_unlocked.unwrap_or(0),
+                                progress.achievements_total.unwrap_or(0)
+                            ))
+                    );
+                }
+                None => {
+                    ui.add_sized([110.0, 20.0], egui::Label::new(RichText::new("—").color(Color32::GRAY)));
+                }
+            }
+        }
+
+        if has_achievements {
+            ui.label(delta_text(completion_delta(game) as f64, true));
+        }
+    });
+
+    clicked
+}
+
+/// Lists of achievements only one of the two users has unlocked
+pub fn render_achievement_differences<P: ComparisonPanelPlatform>(
+    ui: &mut Ui,
+    platform: &P,
+    details: &GameAchievementComparison,
+    other_name: &str,
+) {
+    ui.label(RichText::new(format!("{} unlocked by both", details.both)).color(Color32::GRAY));
+
+    let sections = [
+        ("Only you".to_string(), &details.only_mine, MINE_COLOR),
+        (format!("Only {}", other_name), &details.only_theirs, THEIRS_COLOR),
+    ];
+    for (title, achievements, color) in sections {
+        ui.label(RichText::new(format!("{} ({})", title, achievements.len())).color(color).strong());
+        if achievements.is_empty() {
+            ui.label(RichText::new("Nothing here").color(Color32::GRAY).italics());
+        }
+        for achievement in achievements {
+            render_achievement_difference(ui, platform, achievement);
+        }
+        ui.add_space(4.0);
+    }
+}
+
+fn render_achievement_difference<P: ComparisonPanelPlatform>(ui: &mut Ui, platform: &P, achievement: &AchievementDifference) {
+    ui.horizontal(|ui| {
+        if !achievement.icon.is_empty() {
+            ui.add(
+                egui::Image::new(platform.achievement_icon_source(ui, &achievement.icon))
+                    .fit_to_exact_size(egui::vec2(24.0, 24.0))
+                    .corner_radius(2.0)
+            );
+        }
+        let response = ui.label(&achievement.name);
+        if let Some(description) = achievement.description.as_ref().filter(|d| !d.is_empty()) {
+            response.on_hover_text(description);
+        }
+        if let Some(unlocktime) = achievement.unlocktime {
+            ui.label(RichText::new(unlocktime.format("%Y-%m-%d").to_string()).color(Color32::GRAY).small());
+        }
+    });
+}
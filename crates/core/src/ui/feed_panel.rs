@@ -0,0 +1,227 @@
+//! Friends panel - shared between desktop and WASM
+//!
+//! Renders: Followed users (follow by profile link, unfollow) and a combined
+//! activity feed of their achievements and first plays
+
+use egui::{self, Color32, RichText, Ui};
+use egui_phosphor::regular;
+
+use crate::{FeedEntry, LogEntry, UserProfile};
+use super::{StatsPanelPlatform, instant_tooltip};
+
+/// Platform-specific operations needed for the friends panel
+/// (icons come from [`StatsPanelPlatform`])
+pub trait FeedPanelPlatform: StatsPanelPlatform {
+    /// Users the current user follows
+    fn following(&self) -> &[UserProfile];
+
+    /// Recent activity of followed users, newest first
+    fn feed(&self) -> &[FeedEntry];
+
+    /// Whether the feed is being loaded
+    fn feed_loading(&self) -> bool;
+
+    /// Last follow or feed error, if any
+    fn feed_error(&self) -> Option<&str>;
+
+    /// Text of the "follow" input (profile link or short_id)
+    fn follow_input(&mut self) -> &mut String;
+
+    /// Follow a user by short_id
+    fn follow_user(&mut self, short_id: &str);
+
+    /// Stop following a user by short_id
+    fn unfollow_user(&mut self, short_id: &str);
+
+    /// Reload followed users and the feed
+    fn refresh_feed(&mut self);
+
+    /// Open a followed user's library (optional)
+    fn open_profile(&mut self, _short_id: &str) {}
+}
+
+// ============================================================================
+// Rendering Functions
+// ============================================================================
+
+const USER_COLOR: Color32 = Color32::from_rgb(180, 140, 255);
+
+/// Render the complete friends panel content (inside a scroll area)
+pub fn render_feed_content<P: FeedPanelPlatform>(ui: &mut Ui, platform: &mut P) {
+    ui.horizontal(|ui| {
+        ui.heading(format!("{} Friends", regular::USERS));
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let refresh = ui.add_enabled(!platform.feed_loading(), egui::Button::new(regular::ARROWS_CLOCKWISE.to_string()));
+            if refresh.on_hover_text("Refresh").clicked() {
+                platform.refresh_feed();
+            }
+            if platform.feed_loading() {
+                ui.spinner();
+            }
+        });
+    });
+    ui.separator();
+
+    if !platform.is_authenticated() {
+        ui.label("Link your Steam account to follow other users.");
+        return;
+    }
+
+    render_follow_input(ui, platform);
+    if let Some(error) = platform.feed_error() {
+        ui.label(RichText::new(error).color(Color32::from_rgb(230, 80, 80)).small());
+    }
+    ui.add_space(4.0);
+
+    render_following(ui, platform);
+    ui.add_space(8.0);
+
+    render_feed(ui, platform);
+}
+
+fn render_follow_input<P: FeedPanelPlatform>(ui: &mut Ui, platform: &mut P) {
+    ui.horizontal(|ui| {
+        let response = ui.add(
+            egui::TextEdit::singleline(platform.follow_input())
+                .hint_text("Profile link or ID...")
+                .desired_width(180.0)
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        let short_id = crate::short_id_from_input(platform.follow_input());
+        let clicked = ui.add_enabled(short_id.is_some(), egui::Button::new(format!("{} Follow", regular::USER_PLUS))).clicked();
+        if let Some(short_id) = short_id.filter(|_| clicked || submitted) {
+            platform.follow_user(&short_id);
+            platform.follow_input().clear();
+        }
+    });
+}
+
+fn render_following<P: FeedPanelPlatform>(ui: &mut Ui, platform: &mut P) {
+    let following = platform.following().to_vec();
+    let mut unfollow = None;
+    let mut open = None;
+
+    egui::CollapsingHeader::new(format!("Following ({})", following.len()))
+        .id_salt("feed_following")
+        .default_open(following.len() <= 5)
+        .show(ui, |ui| {
+            if following.is_empty() {
+                ui.label(RichText::new("Paste a profile link above to follow someone.").color(Color32::GRAY).italics());
+            }
+            for user in &following {
+                let Some(short_id) = user.short_id.as_deref() else { continue };
+                ui.horizontal(|ui| {
+                    let name = ui.add(
+                        egui::Label::new(RichText::new(&user.display_name).color(USER_COLOR))
+                            .selectable(false)
+                            .sense(egui::Sense::click())
+                    );
+                    if name.on_hover_text("Open library").clicked() {
+                        open = Some(short_id.to_string());
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button(regular::USER_MINUS.to_string()).on_hover_text("Unfollow").clicked() {
+                            unfollow = Some(short_id.to_string());
+                        }
+                    });
+                });
+            }
+        });
+
+    if let Some(short_id) = unfollow {
+        platform.unfollow_user(&short_id);
+    }
+    if let Some(short_id) = open {
+        platform.open_profile(&short_id);
+    }
+}
+
+/// Render the combined activity of followed users
+pub fn render_feed<P: FeedPanelPlatform>(ui: &mut Ui, platform: &mut P) {
+    let achievement_color = Color32::from_rgb(255, 215, 0);
+    let game_color = Color32::from_rgb(100, 180, 255);
+    let alt_bg = Color32::from_rgba_unmultiplied(255, 255, 255, 8);
+
+    let entries = platform.feed().to_vec();
+    if entries.is_empty() {
+        if !platform.feed_loading() {
+            ui.label("No activity from followed users yet.");
+        }
+        return;
+    }
+
+    let mut row_tops: Vec<f32> = Vec::with_capacity(entries.len() + 1);
+    let mut open = None;
+
+    for FeedEntry { user, entry } in &entries {
+        row_tops.push(ui.cursor().min.y);
+
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 4.0;
+
+            let name = ui.add(
+                egui::Label::new(RichText::new(&user.display_name).color(USER_COLOR).strong())
+                    .selectable(false)
+                    .sense(egui::Sense::click())
+            );
+            if name.clicked() {
+                open = user.short_id.clone();
+            }
+
+            match entry {
+                LogEntry::Achievement { appid, game_name, achievement_name, timestamp, achievement_icon, game_icon_url, .. } => {
+                    ui.label(RichText::new("unlocked").small());
+                    if !achievement_icon.is_empty() {
+                        let response = ui.add(
+                            egui::Image::new(platform.achievement_icon_source(ui, achievement_icon))
+                                .fit_to_exact_size(egui::vec2(18.0, 18.0))
+                                .corner_radius(2.0)
+                        );
+                        instant_tooltip(&response, timestamp.format("%Y-%m-%d").to_string());
+                    }
+                    ui.label(RichText::new(achievement_name).color(achievement_color));
+                    ui.label(RichText::new("in").small());
+                    feed_game_label(ui, &*platform, *appid, game_name, game_icon_url.as_deref(), game_color);
+                }
+                LogEntry::FirstPlay { appid, game_name, timestamp, game_icon_url } => {
+                    ui.label(RichText::new("started playing").small());
+                    feed_game_label(ui, &*platform, *appid, game_name, game_icon_url.as_deref(), game_color);
+                    ui.label(RichText::new(timestamp.format("%Y-%m-%d").to_string()).color(Color32::GRAY).small());
+                }
+            }
+        });
+    }
+
+    row_tops.push(ui.cursor().min.y);
+    for i in (1..row_tops.len()).step_by(2) {
+        let top = row_tops[i];
+        let bottom = row_tops.get(i + 1).copied().unwrap_or(top + 24.0);
+        let row_rect = egui::Rect::from_min_max(
+            egui::pos2(ui.min_rect().left(), top),
+            egui::pos2(ui.min_rect().right(), bottom)
+        );
+        ui.painter().rect_filled(row_rect, 2.0, alt_bg);
+    }
+
+    if let Some(short_id) = open {
+        platform.open_profile(&short_id);
+    }
+}
+
+fn feed_game_label<P: FeedPanelPlatform>(
+    ui: &mut Ui,
+    platform: &P,
+    appid: u64,
+    game_name: &str,
+    game_icon_url: Option<&str>,
+    color: Color32,
+) {
+    if let Some(icon_hash) = game_icon_url.filter(|h| !h.is_empty()) {
+        ui.add(
+            egui::Image::new(platform.game_icon_source(ui, appid, icon_hash))
+                .fit_to_exact_size(egui::vec2(18.0, 18.0))
+                .corner_radius(2.0)
+        );
+    }
+    ui.label(RichText::new(game_name).color(color));
+}
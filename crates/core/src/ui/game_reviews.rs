@@ -0,0 +1,161 @@
+//! Game ratings and reviews - shared between desktop and WASM
+//!
+//! Renders: A collapsible section at the top of an expanded game row with the
+//! community average, a star widget and short review for logged-in users, and
+//! the game's reviews
+
+use std::collections::{HashMap, HashSet};
+
+use egui::{self, Color32, RichText, Ui};
+use egui_phosphor::regular;
+
+use crate::{CommunityGameRating, validate_game_rating};
+use super::GamesTablePlatform;
+
+/// Loaded ratings and unsaved own ratings, owned by the platform
+#[derive(Clone, Default)]
+pub struct GameReviewCache {
+    games: HashMap<u64, CommunityGameRating>,
+    requested: HashSet<u64>,
+    /// Own rating being edited per game (stars, review)
+    drafts: HashMap<u64, (u8, String)>,
+}
+
+impl GameReviewCache {
+    pub fn get(&self, appid: u64) -> Option<&CommunityGameRating> {
+        self.games.get(&appid)
+    }
+
+    /// Mark a game's ratings as requested; returns false if they already were
+    pub fn start_request(&mut self, appid: u64) -> bool {
+        self.requested.insert(appid)
+    }
+
+    /// Store a game's ratings (the server sends them all again after the
+    /// user rated the game)
+    pub fn set(&mut self, community: CommunityGameRating) {
+        self.drafts.remove(&community.appid);
+        self.games.insert(community.appid, community);
+    }
+
+    /// Forget loaded ratings (e.g. after logging in or out, which changes
+    /// which rating is marked as own)
+    pub fn clear(&mut self) {
+        self.games.clear();
+        self.requested.clear();
+        self.drafts.clear();
+    }
+}
+
+const STAR_COLOR: Color32 = Color32::from_rgb(255, 200, 60);
+const AUTHOR_COLOR: Color32 = Color32::from_rgb(180, 140, 255);
+
+/// Compact "★ 4.2" label for a game's average rating
+pub fn render_game_rating(ui: &mut Ui, avg_rating: f32, rating_count: i32) {
+    ui.label(RichText::new(format!("{} {:.1}", regular::STAR, avg_rating)).color(STAR_COLOR))
+        .on_hover_text(format!("{:.1} / 5 from {} rating(s)", avg_rating, rating_count));
+}
+
+/// Five stars, the first `rating` of them lit; returns the clicked star
+fn render_stars(ui: &mut Ui, rating: u8, clickable: bool) -> Option<u8> {
+    let mut clicked = None;
+    ui.spacing_mut().item_spacing.x = 1.0;
+    for star in 1..=5u8 {
+        let color = if star <= rating { STAR_COLOR } else { Color32::DARK_GRAY };
+        let text = RichText::new(regular::STAR).color(color);
+        if clickable {
+            if ui.add(egui::Button::new(text).frame(false)).clicked() {
+                clicked = Some(star);
+            }
+        } else {
+            ui.label(text);
+        }
+    }
+    clicked
+}
+
+/// Render the ratings section of an expanded game (nothing if the platform
+/// has no [`GameReviewCache`])
+pub fn render_game_reviews<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P, appid: u64) {
+    let can_rate = platform.is_authenticated();
+    let Some(cache) = platform.game_reviews() else {
+        return;
+    };
+    let community = cache.get(appid).cloned();
+    let mut submit = None;
+
+    let title = match &community {
+        Some(c) if c.rating_count > 0 => format!("{} Rating {:.1} / 5 ({} ratings)", regular::STAR, c.avg_rating, c.rating_count),
+        _ => format!("{} Rating & reviews", regular::STAR),
+    };
+    egui::CollapsingHeader::new(title)
+        .id_salt(("game_reviews", appid))
+        .show(ui, |ui| {
+            let Some(community) = &community else {
+                platform.request_game_reviews(appid);
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(RichText::new("Loading reviews...").color(Color32::GRAY).small());
+                });
+                return;
+            };
+
+            if can_rate {
+                if let Some(cache) = platform.game_reviews() {
+                    let own = community.ratings.iter().find(|r| r.is_yours);
+                    let (stars, review) = cache.drafts.entry(appid).or_insert_with(|| {
+                        own.map(|r| (r.rating, r.comment.clone().unwrap_or_default())).unwrap_or_default()
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Your rating:");
+                        if let Some(star) = render_stars(ui, *stars, true) {
+                            *stars = star;
+                        }
+                    });
+                    ui.add(
+                        egui::TextEdit::singleline(review)
+                            .hint_text("Short review (optional)")
+                            .desired_width(f32::INFINITY)
+                    );
+                    let trimmed = review.trim();
+                    let review_opt = Some(trimmed).filter(|r| !r.is_empty());
+                    let valid = validate_game_rating(*stars, review_opt).err();
+                    let changed = own.map(|r| r.rating != *stars || r.comment.as_deref() != review_opt).unwrap_or(true);
+                    ui.horizontal(|ui| {
+                        let label = if own.is_some() { "Update rating" } else { "Rate game" };
+                        if ui.add_enabled(valid.is_none() && changed, egui::Button::new(label)).clicked() {
+                            submit = Some((*stars, review_opt.map(str::to_string)));
+                        }
+                        // Only explain problems other than no stars picked yet
+                        if let Some(e) = valid.as_ref().filter(|_| *stars > 0) {
+                            ui.label(RichText::new(e).color(Color32::from_rgb(230, 80, 80)).small());
+                        }
+                    });
+                    ui.add_space(4.0);
+                }
+            } else {
+                ui.label(RichText::new("Link your Steam account to rate games.").color(Color32::GRAY).small());
+            }
+
+            if community.ratings.is_empty() {
+                ui.label(RichText::new("No ratings yet.").color(Color32::GRAY).italics());
+            }
+            for rating in &community.ratings {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(&rating.author.display_name).color(AUTHOR_COLOR).strong());
+                    render_stars(ui, rating.rating, false);
+                    ui.add_space(4.0);
+                    let date = rating.updated_at.format("%Y-%m-%d").to_string();
+                    ui.label(RichText::new(date).color(Color32::GRAY).small());
+                });
+                if let Some(review) = &rating.comment {
+                    ui.label(review);
+                }
+                ui.add_space(2.0);
+            }
+        });
+
+    if let Some((rating, review)) = submit {
+        platform.submit_game_rating(appid, rating, review);
+    }
+}
@@ -7,8 +7,8 @@ use egui::{self, Color32, RichText, Ui};
 use egui_extras::{Column, TableBuilder};
 use egui_phosphor::regular;
 
-use crate::Game;
-use super::{StatsPanelPlatform, instant_tooltip};
+use crate::{Game, GameRarity, AchievementRarity, AchievementComment, AchievementTip, AchievementFlag, AchievementFlags, CommunityAverages};
+use super::{StatsPanelPlatform, CommentThreadState, GameReviewCache, instant_tooltip, render_comment_thread, render_tips_section, render_game_reviews, render_game_rating};
 
 // ============================================================================
 // Types
@@ -22,6 +22,10 @@ pub enum SortColumn {
     Playtime,
     AchievementsTotal,
     AchievementsPercent,
+    /// Community average difficulty of the game's achievements
+    Difficulty,
+    /// Community average rating of the game
+    Rating,
 }
 
 #[derive(Clone, Copy, PartialEq, Default)]
@@ -170,6 +174,97 @@ pub trait GamesTablePlatform: StatsPanelPlatform {
     
     /// Set installed games filter state
     fn set_filter_installed(&mut self, _filter: TriFilter) {}
+    
+    /// Get "has missables" filter state
+    fn filter_missables(&self) -> TriFilter { TriFilter::All }
+    
+    /// Set "has missables" filter state
+    fn set_filter_missables(&mut self, _filter: TriFilter) {}
+    
+    // ========================================================================
+    // Unlock rates (community and Steam global)
+    // ========================================================================
+    
+    /// Unlock rates for a game's achievements, if loaded
+    fn achievement_rarity(&self, _appid: u64) -> Option<&GameRarity> { None }
+    
+    /// Load unlock rates for a game; called while its achievements are shown
+    /// and none are loaded, so requests in flight should be ignored
+    fn request_achievement_rarity(&mut self, _appid: u64) {}
+    
+    /// Community average difficulty of a game's rated achievements (1-5)
+    fn game_avg_difficulty(&self, _appid: u64) -> Option<f32> { None }
+    
+    // ========================================================================
+    // Achievement flags (missable, bugged, grind, online-only)
+    // ========================================================================
+    
+    /// Community-confirmed flags of an achievement with their vote counts
+    fn community_achievement_flags(&self, _appid: u64, _apiname: &str) -> Vec<(AchievementFlag, i32)> { Vec::new() }
+    
+    /// Whether any achievement of a game is confirmed as missable
+    fn game_has_missables(&self, _appid: u64) -> bool { false }
+    
+    /// Whether the user can flag achievements here (needs own flags loaded,
+    /// so views of other users' libraries leave this off)
+    fn can_flag_achievements(&self) -> bool { false }
+    
+    // ========================================================================
+    // Game ratings and reviews
+    // ========================================================================
+    
+    /// Community average star rating and rating count of a game
+    fn game_rating(&self, _appid: u64) -> Option<(f32, i32)> { None }
+    
+    /// Loaded reviews and own rating drafts; None hides ratings entirely
+    fn game_reviews(&mut self) -> Option<&mut GameReviewCache> { None }
+    
+    /// Load a game's ratings; called while its ratings section is open and
+    /// none are loaded, so requests in flight should be ignored
+    fn request_game_reviews(&mut self, _appid: u64) {}
+    
+    /// Rate a game (1-5) with an optional review, replacing an earlier rating
+    fn submit_game_rating(&mut self, _appid: u64, _rating: u8, _review: Option<String>) {}
+    
+    // ========================================================================
+    // Achievement comments
+    // ========================================================================
+    
+    /// Open comment thread and drafts; None hides comments entirely
+    fn comment_thread(&mut self) -> Option<&mut CommentThreadState> { None }
+    
+    /// Loaded comments of an achievement, oldest first
+    fn achievement_comments(&self, _appid: u64, _apiname: &str) -> Option<&[AchievementComment]> { None }
+    
+    /// Load the comments of an achievement; called while its thread is open
+    /// and none are loaded, so requests in flight should be ignored
+    fn request_achievement_comments(&mut self, _appid: u64, _apiname: &str) {}
+    
+    /// Post a comment on one achievement
+    fn post_achievement_comment(&mut self, _appid: u64, _apiname: String, _comment: String) {}
+    
+    /// Change the text of an own comment
+    fn edit_achievement_comment(&mut self, _id: i64, _comment: String) {}
+    
+    /// Delete an own comment
+    fn delete_achievement_comment(&mut self, _id: i64) {}
+    
+    // ========================================================================
+    // Achievement tips (shown above the comments of an open thread)
+    // ========================================================================
+    
+    /// Loaded tips of an achievement, highest score first
+    fn achievement_tips(&self, _appid: u64, _apiname: &str) -> Option<&[AchievementTip]> { None }
+    
+    /// Load the tips of an achievement; called while its thread is open and
+    /// none are loaded, so requests in flight should be ignored
+    fn request_achievement_tips(&mut self, _appid: u64, _apiname: &str) {}
+    
+    /// Submit the own tip for an achievement, replacing an earlier one
+    fn submit_achievement_tip(&mut self, _appid: u64, _apiname: String, _difficulty: u8, _tip: String) {}
+    
+    /// Vote on another user's tip: 1, -1 or 0 to take the vote back
+    fn vote_achievement_tip(&mut self, _tip_id: i64, _vote: i8) {}
 }
 
 // ============================================================================
@@ -220,6 +315,13 @@ pub fn get_filtered_indices(platform: &impl GamesTablePlatform) -> Vec<usize> {
                 TriFilter::With => if !has_playtime { return false; }
                 TriFilter::Without => if has_playtime { return false; }
             }
+            // Missables filter
+            let has_missables = platform.game_has_missables(g.appid);
+            match platform.filter_missables() {
+                TriFilter::All => {}
+                TriFilter::With => if !has_missables { return false; }
+                TriFilter::Without => if has_missables { return false; }
+            }
             // Installed filter (desktop only - if platform can detect installed games)
             if platform.can_detect_installed() {
                 let is_installed = platform.is_game_installed(g.appid);
@@ -235,8 +337,26 @@ pub fn get_filtered_indices(platform: &impl GamesTablePlatform) -> Vec<usize> {
         .collect()
 }
 
+/// Sort by a community average, highest first in ascending order. Games
+/// without one sort last in either order; equal averages fall back to the name.
+fn sort_by_community_average(games: &mut [Game], sort_order: SortOrder, average: impl Fn(u64) -> Option<f32>) {
+    games.sort_by(|a, b| {
+        let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase());
+        match (average(a.appid), average(b.appid)) {
+            (Some(a_avg), Some(b_avg)) => {
+                let cmp = b_avg.partial_cmp(&a_avg).unwrap_or(std::cmp::Ordering::Equal);
+                let cmp = if sort_order == SortOrder::Descending { cmp.reverse() } else { cmp };
+                cmp.then_with(by_name)
+            }
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => by_name(),
+        }
+    });
+}
+
 /// Sort games in place based on current sort settings
-pub fn sort_games(games: &mut [Game], sort_column: SortColumn, sort_order: SortOrder) {
+pub fn sort_games(games: &mut [Game], sort_column: SortColumn, sort_order: SortOrder, community: &CommunityAverages) {
     match sort_column {
         SortColumn::Name => {
             games.sort_by(|a, b| {
@@ -270,6 +390,12 @@ pub fn sort_games(games: &mut [Game], sort_column: SortColumn, sort_order: SortO
                 if sort_order == SortOrder::Descending { cmp.reverse() } else { cmp }
             });
         }
+        SortColumn::Difficulty => {
+            sort_by_community_average(games, sort_order, |appid| community.game(appid));
+        }
+        SortColumn::Rating => {
+            sort_by_community_average(games, sort_order, |appid| community.game_rating(appid).map(|(avg, _)| avg));
+        }
     }
 }
 
@@ -306,6 +432,13 @@ pub fn render_filter_bar<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P) {
             platform.set_filter_playtime(next);
         }
         
+        // Missables filter - games with achievements the community flagged as missable
+        let miss_label = format!("Missables: {}", platform.filter_missables().label("Has", "None"));
+        if ui.button(&miss_label).on_hover_text("Games with achievements that can be permanently missed").clicked() {
+            let next = platform.filter_missables().cycle();
+            platform.set_filter_missables(next);
+        }
+        
         // Installed filter - only show on desktop (platform that can detect installed games)
         if platform.can_detect_installed() {
             let inst_label = format!("Installed: {}", platform.filter_installed().label("Yes", "No"));
@@ -319,6 +452,7 @@ pub fn render_filter_bar<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P) {
         let has_filters = !platform.filter_name().is_empty() 
             || platform.filter_achievements() != TriFilter::All 
             || platform.filter_playtime() != TriFilter::All
+            || platform.filter_missables() != TriFilter::All
             || (platform.can_detect_installed() && platform.filter_installed() != TriFilter::All);
         
         if !has_filters {
@@ -327,6 +461,7 @@ pub fn render_filter_bar<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P) {
             platform.set_filter_name(String::new());
             platform.set_filter_achievements(TriFilter::All);
             platform.set_filter_playtime(TriFilter::All);
+            platform.set_filter_missables(TriFilter::All);
             if platform.can_detect_installed() {
                 platform.set_filter_installed(TriFilter::All);
             }
@@ -381,6 +516,8 @@ pub fn render_games_table<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P,
         .column(Column::exact(80.0))  // Playtime - fixed
         .column(Column::exact(100.0)) // Achievements - fixed
         .column(Column::exact(60.0))  // Percent - fixed
+        .column(Column::exact(80.0))  // Difficulty - fixed
+        .column(Column::exact(60.0))  // Rating - fixed
         .min_scrolled_height(0.0)
         .max_scroll_height(available_height);
     
@@ -427,6 +564,22 @@ pub fn render_games_table<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P,
                     platform.set_sort(SortColumn::AchievementsPercent);
                 }
             });
+            header.col(|ui| {
+                let indicator = sort_indicator(platform, SortColumn::Difficulty);
+                let label = if indicator.is_empty() { "Difficulty".to_string() } else { format!("Difficulty {}", indicator) };
+                let response = ui.selectable_label(platform.sort_column() == SortColumn::Difficulty, label);
+                if response.on_hover_text("Community average difficulty of rated achievements").clicked() {
+                    platform.set_sort(SortColumn::Difficulty);
+                }
+            });
+            header.col(|ui| {
+                let indicator = sort_indicator(platform, SortColumn::Rating);
+                let label = if indicator.is_empty() { "Rating".to_string() } else { format!("Rating {}", indicator) };
+                let response = ui.selectable_label(platform.sort_column() == SortColumn::Rating, label);
+                if response.on_hover_text("Community average rating of the game").clicked() {
+                    platform.set_sort(SortColumn::Rating);
+                }
+            });
         })
         .body(|body| {
             body.heterogeneous_rows(row_heights.into_iter(), |mut row| {
@@ -613,6 +766,34 @@ pub fn render_games_table<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P,
                         }
                     }
                 });
+                
+                row.col(|ui| {
+                    if let Some(color) = flash_color {
+                        ui.painter().rect_filled(ui.available_rect_before_wrap(), 0.0, color);
+                    }
+                    if !is_expanded {
+                        if let Some(avg) = platform.game_avg_difficulty(appid) {
+                            let rating = avg.round() as u8;
+                            ui.label(RichText::new(difficulty_label(rating).trim_end()).color(difficulty_color(rating)))
+                                .on_hover_text(format!("{:.1} / 5", avg));
+                        } else {
+                            ui.label("—");
+                        }
+                    }
+                });
+                
+                row.col(|ui| {
+                    if let Some(color) = flash_color {
+                        ui.painter().rect_filled(ui.available_rect_before_wrap(), 0.0, color);
+                    }
+                    if !is_expanded {
+                        if let Some((avg, count)) = platform.game_rating(appid) {
+                            render_game_rating(ui, avg, count);
+                        } else {
+                            ui.label("—");
+                        }
+                    }
+                });
             });
         });
     
@@ -628,6 +809,12 @@ fn render_achievements_list<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P
         .filter(|(nav_appid, _)| *nav_appid == appid)
         .map(|(_, apiname)| apiname.clone());
     
+    if platform.achievement_rarity(appid).is_none() {
+        platform.request_achievement_rarity(appid);
+    }
+    let rarity = platform.achievement_rarity(appid).cloned();
+    let has_comments = platform.comment_thread().is_some();
+    
     if let Some(achievements) = platform.get_cached_achievements(appid) {
         ui.add_space(4.0);
         ui.separator();
@@ -657,6 +844,7 @@ fn render_achievements_list<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P
         
         egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
             ui.set_width(ui.available_width());
+            render_game_reviews(ui, platform, appid);
             let is_authenticated = platform.is_authenticated();
             for (i, (apiname, name, achieved, icon_url, description, unlocktime)) in ach_data.iter().enumerate() {
                 // Check if this is the navigation target
@@ -671,6 +859,15 @@ fn render_achievements_list<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P
                 };
                 // Get community average rating
                 let avg_rating_data = platform.get_achievement_avg_rating(appid, apiname);
+                // Community-confirmed flags, plus the user's own
+                let community_flags = platform.community_achievement_flags(appid, apiname);
+                let can_flag = is_authenticated && platform.can_flag_achievements();
+                let mut user_flags = if can_flag {
+                    platform.get_user_achievement_flags(appid, apiname)
+                } else {
+                    AchievementFlags::default()
+                };
+                let mut flags_changed = false;
                 
                 // Alternate row background, or highlight if target
                 let row_rect = ui.available_rect_before_wrap();
@@ -704,6 +901,11 @@ fn render_achievements_list<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P
                     );
                 }
                 
+                let thread_open = platform.comment_thread()
+                    .map(|thread| thread.is_open(appid, apiname))
+                    .unwrap_or(false);
+                let mut toggle_thread = false;
+                
                 // Add top padding for the row content
                 ui.add_space(2.0);
                 ui.horizontal(|ui| {
@@ -739,7 +941,23 @@ fn render_achievements_list<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P
                         // Top row: name and date/stars
                         ui.horizontal(|ui| {
                             ui.label(name_text);
+                            // Warnings only matter while the achievement is still locked
+                            if !*achieved {
+                                render_flag_badges(ui, &community_flags, user_flags);
+                            }
                             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if has_comments {
+                                    let icon = if thread_open { regular::CHAT_CIRCLE_TEXT } else { regular::CHAT_CIRCLE };
+                                    let button = ui.add(egui::Button::new(icon.to_string()).small().selected(thread_open));
+                                    if button.on_hover_text("Tips and comments").clicked() {
+                                        toggle_thread = true;
+                                    }
+                                    ui.add_space(4.0);
+                                }
+                                if can_flag {
+                                    flags_changed = render_flag_menu(ui, &mut user_flags);
+                                    ui.add_space(4.0);
+                                }
                                 // Show compact average rating (read-only)
                                 // Use average if available, otherwise show user's own rating
                                 let (display_rating, count) = if let Some((avg, cnt)) = avg_rating_data {
@@ -748,6 +966,12 @@ fn render_achievements_list<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P
                                     (user_rating, None)
                                 };
                                 render_compact_avg_rating(ui, display_rating, count);
+                                if let Some(rarity) = &rarity {
+                                    if let Some(achievement_rarity) = rarity.get(apiname) {
+                                        ui.add_space(8.0);
+                                        render_rarity(ui, achievement_rarity, rarity.community_owners);
+                                    }
+                                }
                             });
                         });
                         // Description below, full width
@@ -756,6 +980,19 @@ fn render_achievements_list<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P
                         }
                     });
                 });
+                
+                if flags_changed {
+                    platform.set_user_achievement_flags(appid, apiname.clone(), user_flags);
+                }
+                if toggle_thread {
+                    if let Some(thread) = platform.comment_thread() {
+                        thread.toggle(appid, apiname);
+                    }
+                }
+                if thread_open && !toggle_thread {
+                    render_tips_section(ui, platform, appid, apiname);
+                    render_comment_thread(ui, platform, appid, apiname);
+                }
             }
         });
     } else {
@@ -764,8 +1001,58 @@ fn render_achievements_list<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P
     }
 }
 
+fn flag_icon(flag: AchievementFlag) -> &'static str {
+    match flag {
+        AchievementFlag::Missable => regular::WARNING,
+        AchievementFlag::Bugged => regular::BUG,
+        AchievementFlag::Grind => regular::HOURGLASS,
+        AchievementFlag::OnlineOnly => regular::GLOBE,
+    }
+}
+
+fn flag_color(flag: AchievementFlag) -> Color32 {
+    match flag {
+        AchievementFlag::Missable => Color32::from_rgb(255, 170, 50),
+        AchievementFlag::Bugged => Color32::from_rgb(230, 80, 80),
+        AchievementFlag::Grind => Color32::from_rgb(180, 180, 100),
+        AchievementFlag::OnlineOnly => Color32::from_rgb(100, 170, 230),
+    }
+}
+
+/// Warning badges for confirmed and own flags of a locked achievement
+fn render_flag_badges(ui: &mut Ui, community: &[(AchievementFlag, i32)], own: AchievementFlags) {
+    for flag in AchievementFlag::ALL {
+        let votes = community.iter().find(|(f, _)| *f == flag).map(|(_, v)| *v);
+        let flagged_by_you = own.contains(flag);
+        let tooltip = match (votes, flagged_by_you) {
+            (Some(votes), true) => format!("{}: {}\nFlagged by {} players, including you", flag.label(), flag.description(), votes),
+            (Some(votes), false) => format!("{}: {}\nFlagged by {} players", flag.label(), flag.description(), votes),
+            (None, true) => format!("{}: {}\nFlagged by you", flag.label(), flag.description()),
+            (None, false) => continue,
+        };
+        let color = if votes.is_some() { flag_color(flag) } else { Color32::GRAY };
+        ui.label(RichText::new(flag_icon(flag)).color(color)).on_hover_text(tooltip);
+    }
+}
+
+/// Menu for setting own flags on an achievement; returns true if they changed
+fn render_flag_menu(ui: &mut Ui, flags: &mut AchievementFlags) -> bool {
+    let mut changed = false;
+    ui.menu_button(regular::FLAG.to_string(), |ui| {
+        for flag in AchievementFlag::ALL {
+            let mut on = flags.contains(flag);
+            let label = format!("{} {}", flag_icon(flag), flag.label());
+            if ui.checkbox(&mut on, label).on_hover_text(flag.description()).changed() {
+                flags.set(flag, on);
+                changed = true;
+            }
+        }
+    }).response.on_hover_text("Flag this achievement");
+    changed
+}
+
 /// Get difficulty label for rating (with trailing space to avoid border clipping)
-fn difficulty_label(rating: u8) -> &'static str {
+pub(super) fn difficulty_label(rating: u8) -> &'static str {
     match rating {
         1 => "Very easy  ",
         2 => "Easy  ",
@@ -777,7 +1064,7 @@ fn difficulty_label(rating: u8) -> &'static str {
 }
 
 /// Get icon for difficulty rating (single icon per level)
-fn difficulty_icon(rating: u8) -> &'static str {
+pub(super) fn difficulty_icon(rating: u8) -> &'static str {
     match rating {
         1 => "🐢",  // Turtle - Very easy
         2 => "🐇",  // Rabbit - Easy
@@ -820,3 +1107,22 @@ fn render_compact_avg_rating(ui: &mut Ui, avg_rating: Option<u8>, rating_count:
     // Single difficulty icon
     ui.label(RichText::new(difficulty_icon(rating)).color(difficulty_color(rating)).size(12.0));
 }
+
+/// Render community and Steam unlock rates (right-to-left)
+fn render_rarity(ui: &mut Ui, rarity: &AchievementRarity, community_owners: u32) {
+    if let Some(steam_percent) = rarity.steam_percent {
+        let response = ui.label(
+            RichText::new(format!("{} {:.1}%", regular::GLOBE, steam_percent)).color(Color32::GRAY).size(10.0)
+        );
+        instant_tooltip(&response, "Steam global unlock rate");
+    }
+    if community_owners > 0 {
+        let response = ui.label(
+            RichText::new(format!("{} {:.1}%", regular::USERS_THREE, rarity.community_percent))
+                .color(Color32::from_rgb(120, 180, 255))
+                .size(10.0)
+        );
+        let owners = if community_owners == 1 { "1 Overachiever user".to_string() } else { format!("{} Overachiever users", community_owners) };
+        instant_tooltip(&response, format!("Unlocked by {:.1}% of {} who own this game", rarity.community_percent, owners));
+    }
+}
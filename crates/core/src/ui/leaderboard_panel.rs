@@ -0,0 +1,166 @@
+//! Leaderboard panel - shared between desktop and WASM
+//!
+//! Renders: Rankings by metric, among followed users or everyone who opted
+//! in, with the user's own row highlighted
+
+use egui::{self, Color32, RichText, Ui};
+use egui_phosphor::regular;
+
+use crate::{Leaderboard, LeaderboardEntry, LeaderboardMetric, LeaderboardScope};
+use super::StatsPanelPlatform;
+
+/// Which leaderboard is shown, owned by the platform
+#[derive(Clone, Copy, PartialEq, Default)]
+pub struct LeaderboardViewState {
+    pub metric: LeaderboardMetric,
+    pub scope: LeaderboardScope,
+}
+
+/// Platform-specific operations needed for the leaderboard panel
+pub trait LeaderboardPanelPlatform: StatsPanelPlatform {
+    /// The loaded leaderboard, if any (may be for a previous selection while loading)
+    fn leaderboard(&self) -> Option<&Leaderboard>;
+
+    fn leaderboard_loading(&self) -> bool;
+
+    /// Last leaderboard error, if any
+    fn leaderboard_error(&self) -> Option<&str>;
+
+    fn leaderboard_view(&self) -> LeaderboardViewState;
+
+    /// Load the leaderboard for a selection (and remember the selection)
+    fn request_leaderboard(&mut self, view: LeaderboardViewState);
+
+    /// Show or hide the user on global leaderboards
+    fn set_leaderboard_opt_in(&mut self, enabled: bool);
+
+    /// Open a ranked user's library (optional)
+    fn open_profile(&mut self, _short_id: &str) {}
+}
+
+// ============================================================================
+// Rendering Functions
+// ============================================================================
+
+const YOU_COLOR: Color32 = Color32::from_rgb(255, 215, 0);
+
+fn rank_text(rank: u32) -> RichText {
+    match rank {
+        1 => RichText::new("1").color(Color32::from_rgb(255, 215, 0)).strong(),
+        2 => RichText::new("2").color(Color32::from_rgb(200, 200, 210)).strong(),
+        3 => RichText::new("3").color(Color32::from_rgb(205, 127, 50)).strong(),
+        _ => RichText::new(rank.to_string()).color(Color32::GRAY),
+    }
+}
+
+/// Render the complete leaderboard panel content (inside a scroll area)
+pub fn render_leaderboard_content<P: LeaderboardPanelPlatform>(ui: &mut Ui, platform: &mut P) {
+    ui.heading(format!("{} Leaderboards", regular::TROPHY));
+    ui.separator();
+
+    if !platform.is_authenticated() {
+        ui.label("Link your Steam account to see leaderboards.");
+        return;
+    }
+
+    let current = platform.leaderboard_view();
+    let mut view = current;
+    ui.horizontal(|ui| {
+        for scope in [LeaderboardScope::Following, LeaderboardScope::Global] {
+            ui.selectable_value(&mut view.scope, scope, scope.label());
+        }
+        ui.separator();
+        egui::ComboBox::from_id_salt("leaderboard_metric")
+            .selected_text(view.metric.label())
+            .show_ui(ui, |ui| {
+                for metric in LeaderboardMetric::ALL {
+                    ui.selectable_value(&mut view.metric, metric, metric.label());
+                }
+            });
+        if platform.leaderboard_loading() {
+            ui.spinner();
+        } else if ui.small_button(regular::ARROWS_CLOCKWISE.to_string()).on_hover_text("Refresh").clicked() {
+            platform.request_leaderboard(view);
+        }
+    });
+    if view != current {
+        platform.request_leaderboard(view);
+    }
+
+    if let Some(error) = platform.leaderboard_error() {
+        ui.label(RichText::new(error).color(Color32::from_rgb(230, 80, 80)).small());
+    }
+
+    let Some(leaderboard) = platform.leaderboard().cloned() else {
+        if !platform.leaderboard_loading() && platform.leaderboard_error().is_none() {
+            platform.request_leaderboard(view);
+        }
+        return;
+    };
+
+    if view.scope == LeaderboardScope::Global {
+        let mut opted_in = leaderboard.opted_in;
+        if ui.checkbox(&mut opted_in, "Show me on the public leaderboard").changed() {
+            platform.set_leaderboard_opt_in(opted_in);
+        }
+    }
+    if let Some(refreshed_at) = leaderboard.refreshed_at {
+        ui.label(
+            RichText::new(format!("Updated {}", refreshed_at.format("%Y-%m-%d %H:%M UTC")))
+                .color(Color32::GRAY)
+                .small()
+        );
+    }
+    ui.add_space(4.0);
+
+    if leaderboard.entries.is_empty() {
+        let hint = match leaderboard.scope {
+            LeaderboardScope::Following => "Follow other users to compare with them here.",
+            LeaderboardScope::Global => "Nobody has joined the public leaderboard yet.",
+        };
+        ui.label(RichText::new(hint).color(Color32::GRAY).italics());
+        return;
+    }
+
+    let mut open = None;
+    egui::Grid::new("leaderboard_entries")
+        .num_columns(3)
+        .spacing([12.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            for (i, entry) in leaderboard.entries.iter().enumerate() {
+                // Ranks never exceed list positions unless entries were left
+                // out, which only happens before the user's own entry
+                if entry.rank as usize > i + 1 {
+                    ui.label(RichText::new("…").color(Color32::GRAY));
+                    ui.end_row();
+                }
+                if render_entry(ui, leaderboard.metric, entry) {
+                    open = entry.user.short_id.clone();
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(short_id) = open {
+        platform.open_profile(&short_id);
+    }
+}
+
+/// One ranked row. Returns true when the name was clicked.
+fn render_entry(ui: &mut Ui, metric: LeaderboardMetric, entry: &LeaderboardEntry) -> bool {
+    ui.label(rank_text(entry.rank));
+
+    let name = if entry.is_you {
+        RichText::new(format!("{} (you)", entry.user.display_name)).color(YOU_COLOR).strong()
+    } else {
+        RichText::new(&entry.user.display_name)
+    };
+    let response = ui.add(egui::Label::new(name).selectable(false).sense(egui::Sense::click()));
+    ui.label(RichText::new(metric.format_value(entry.value)).strong());
+
+    if !entry.is_you && entry.user.short_id.is_some() {
+        return response.on_hover_text("Open library").clicked();
+    }
+    false
+}
@@ -6,10 +6,22 @@
 mod stats_panel;
 mod log_panel;
 mod games_table;
+mod comparison_panel;
+mod feed_panel;
+mod leaderboard_panel;
+mod comment_thread;
+mod tips_section;
+mod game_reviews;
 
 pub use stats_panel::*;
 pub use log_panel::*;
 pub use games_table::*;
+pub use comparison_panel::*;
+pub use feed_panel::*;
+pub use leaderboard_panel::*;
+pub use comment_thread::*;
+pub use tips_section::*;
+pub use game_reviews::*;
 
 use egui::{Response, RectAlign};
 use egui::containers::Popup;
@@ -31,4 +43,6 @@ pub enum SidebarPanel {
     #[default]
     Stats,
     Log,
+    Friends,
+    Leaderboards,
 }
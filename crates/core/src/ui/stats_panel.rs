@@ -6,7 +6,7 @@ use egui::{self, Color32, RichText, Ui};
 use egui_plot::{Line, Plot, PlotPoints};
 use egui_phosphor::regular;
 
-use crate::{Game, RunHistory, AchievementHistory, LogEntry};
+use crate::{Game, RunHistory, AchievementHistory, AchievementFlags, LogEntry};
 
 /// Platform-specific operations needed for the stats panel
 pub trait StatsPanelPlatform {
@@ -81,6 +81,14 @@ pub trait StatsPanelPlatform {
     /// Set the user's rating for an achievement (stores locally and submits to server)
     fn set_user_achievement_rating(&mut self, _appid: u64, _apiname: String, _rating: u8) {}
     
+    /// Get the user's flags for an achievement (missable, bugged, ...)
+    fn get_user_achievement_flags(&self, _appid: u64, _apiname: &str) -> AchievementFlags {
+        AchievementFlags::default()
+    }
+    
+    /// Set the user's flags for an achievement (stores locally and submits to server)
+    fn set_user_achievement_flags(&mut self, _appid: u64, _apiname: String, _flags: AchievementFlags) {}
+    
     /// Submit a comment for selected achievements
     fn submit_achievement_comment(&mut self, _comment: String) {}
     
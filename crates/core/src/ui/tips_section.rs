@@ -0,0 +1,167 @@
+//! Achievement tips section - shared between desktop and WASM
+//!
+//! Renders: Community tips for one achievement above its comment thread,
+//! highest score first, with up/down voting and writing or editing the own
+//! tip for logged-in users
+
+use egui::{self, Color32, RichText, Ui};
+use egui_phosphor::regular;
+
+use crate::{AchievementTip, validate_tip};
+use super::{CommentThreadState, GamesTablePlatform};
+use super::games_table::{difficulty_icon, difficulty_label};
+
+enum TipAction {
+    Submit(u8, String),
+    Vote(i64, i8),
+}
+
+const AUTHOR_COLOR: Color32 = Color32::from_rgb(180, 140, 255);
+const UPVOTE_COLOR: Color32 = Color32::from_rgb(100, 200, 100);
+const DOWNVOTE_COLOR: Color32 = Color32::from_rgb(230, 80, 80);
+
+/// Render the tips of an achievement (call only when the platform has a
+/// [`CommentThreadState`])
+pub fn render_tips_section<P: GamesTablePlatform>(ui: &mut Ui, platform: &mut P, appid: u64, apiname: &str) {
+    let Some(mut state) = platform.comment_thread().cloned() else {
+        return;
+    };
+    let tips = platform.achievement_tips(appid, apiname).map(|t| t.to_vec());
+    let can_post = platform.is_authenticated();
+    let mut action = None;
+
+    ui.indent(("tips_section", appid, apiname), |ui| {
+        ui.add_space(4.0);
+        ui.label(RichText::new(format!("{} Tips", regular::LIGHTBULB)).strong());
+        match &tips {
+            None => {
+                platform.request_achievement_tips(appid, apiname);
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(RichText::new("Loading tips...").color(Color32::GRAY).small());
+                });
+            }
+            Some(tips) if tips.is_empty() => {
+                ui.label(RichText::new("No tips yet.").color(Color32::GRAY).italics());
+            }
+            Some(tips) => {
+                for tip in tips {
+                    if let Some(a) = render_tip(ui, tip, can_post, &mut state) {
+                        action = Some(a);
+                    }
+                    ui.add_space(4.0);
+                }
+            }
+        }
+
+        let has_own_tip = tips.as_ref().map(|t| t.iter().any(|tip| tip.is_yours)).unwrap_or(false);
+        if !can_post {
+            ui.label(RichText::new("Link your Steam account to write tips.").color(Color32::GRAY).small());
+        } else if state.tip_draft.is_some() {
+            if let Some(a) = render_tip_editor(ui, &mut state) {
+                action = Some(a);
+            }
+        } else if !has_own_tip && tips.is_some()
+            && ui.small_button(format!("{} Write a tip", regular::PENCIL_SIMPLE)).clicked()
+        {
+            state.tip_draft = Some((3, String::new()));
+        }
+        ui.add_space(4.0);
+    });
+
+    if let Some(thread) = platform.comment_thread() {
+        *thread = state;
+    }
+    match action {
+        Some(TipAction::Submit(difficulty, text)) => platform.submit_achievement_tip(appid, apiname.to_string(), difficulty, text),
+        Some(TipAction::Vote(id, vote)) => platform.vote_achievement_tip(id, vote),
+        None => {}
+    }
+}
+
+/// One tip with its score and vote buttons
+fn render_tip(ui: &mut Ui, tip: &AchievementTip, can_vote: bool, state: &mut CommentThreadState) -> Option<TipAction> {
+    let mut action = None;
+
+    ui.horizontal(|ui| {
+        // Voting on own tips isn't allowed, so they only show the score
+        let can_vote = can_vote && !tip.is_yours;
+        let up_color = if tip.your_vote > 0 { UPVOTE_COLOR } else { Color32::GRAY };
+        let up = ui.add_enabled(can_vote, egui::Button::new(RichText::new(regular::THUMBS_UP).color(up_color)).small().frame(false));
+        if up.on_hover_text("Helpful").clicked() {
+            action = Some(TipAction::Vote(tip.id, if tip.your_vote > 0 { 0 } else { 1 }));
+        }
+        let score_color = match tip.score {
+            s if s > 0 => UPVOTE_COLOR,
+            s if s < 0 => DOWNVOTE_COLOR,
+            _ => Color32::GRAY,
+        };
+        ui.label(RichText::new(tip.score.to_string()).color(score_color).strong());
+        let down_color = if tip.your_vote < 0 { DOWNVOTE_COLOR } else { Color32::GRAY };
+        let down = ui.add_enabled(can_vote, egui::Button::new(RichText::new(regular::THUMBS_DOWN).color(down_color)).small().frame(false));
+        if down.on_hover_text("Not helpful").clicked() {
+            action = Some(TipAction::Vote(tip.id, if tip.your_vote < 0 { 0 } else { -1 }));
+        }
+
+        ui.add_space(4.0);
+        ui.label(RichText::new(&tip.author.display_name).color(AUTHOR_COLOR).strong());
+        ui.label(format!("{} {}", difficulty_icon(tip.difficulty), difficulty_label(tip.difficulty).trim_end()))
+            .on_hover_text("Difficulty according to this tip");
+        let date = tip.created_at.format("%Y-%m-%d").to_string();
+        ui.label(RichText::new(date).color(Color32::GRAY).small());
+        if let Some(edited_at) = tip.edited_at {
+            ui.label(RichText::new("(edited)").color(Color32::GRAY).small())
+                .on_hover_text(edited_at.format("%Y-%m-%d %H:%M UTC").to_string());
+        }
+        if tip.is_yours && state.tip_draft.is_none() {
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button(regular::PENCIL_SIMPLE.to_string()).on_hover_text("Edit").clicked() {
+                    state.tip_draft = Some((tip.difficulty.clamp(1, 5), tip.tip.clone()));
+                }
+            });
+        }
+    });
+    ui.label(&tip.tip);
+
+    action
+}
+
+/// Editor for the own tip; submitting replaces an earlier tip
+fn render_tip_editor(ui: &mut Ui, state: &mut CommentThreadState) -> Option<TipAction> {
+    let (difficulty, text) = state.tip_draft.as_mut()?;
+    let mut action = None;
+    let mut done = false;
+
+    ui.horizontal(|ui| {
+        ui.label("Difficulty:");
+        for rating in 1..=5 {
+            ui.selectable_value(difficulty, rating, difficulty_icon(rating))
+                .on_hover_text(difficulty_label(rating).trim_end());
+        }
+    });
+    ui.add(
+        egui::TextEdit::multiline(text)
+            .hint_text("How did you get this achievement?")
+            .desired_rows(3)
+            .desired_width(f32::INFINITY)
+    );
+    let valid = validate_tip(*difficulty, text).err();
+    ui.horizontal(|ui| {
+        if ui.add_enabled(valid.is_none(), egui::Button::new(format!("{} Save tip", regular::PAPER_PLANE_TILT))).clicked() {
+            action = Some(TipAction::Submit(*difficulty, text.trim().to_string()));
+            done = true;
+        }
+        if ui.button("Cancel").clicked() {
+            done = true;
+        }
+        // Only explain problems other than an empty draft
+        if let Some(e) = valid.as_ref().filter(|_| !text.trim().is_empty()) {
+            ui.label(RichText::new(e).color(DOWNVOTE_COLOR).small());
+        }
+    });
+    if done {
+        state.tip_draft = None;
+    }
+
+    action
+}
@@ -0,0 +1,126 @@
+//! Automatic cloud upload after updates and full scans
+//!
+//! When enabled, every completed update or scan schedules an upload. The
+//! upload waits until nothing has changed for a short while, so back-to-back
+//! updates produce a single upload, and failed uploads are retried with an
+//! exponential backoff.
+
+use std::time::{Duration, Instant};
+
+use super::SteamOverachieverApp;
+
+/// Quiet period after the last change before uploading
+const AUTO_SYNC_DEBOUNCE: Duration = Duration::from_secs(10);
+
+/// Delay before the first retry; doubled after every further failure
+const AUTO_SYNC_RETRY_BASE: Duration = Duration::from_secs(30);
+
+/// Longest delay between retries
+const AUTO_SYNC_RETRY_MAX: Duration = Duration::from_secs(30 * 60);
+
+/// State of automatic cloud uploads
+#[derive(Debug, Default)]
+pub(crate) struct AutoSync {
+    /// When the next automatic upload is due
+    pub due_at: Option<Instant>,
+    /// The running cloud operation was started by auto-sync
+    pub running: bool,
+    /// Consecutive failed attempts
+    pub failures: u32,
+    pub last_error: Option<String>,
+}
+
+impl AutoSync {
+    fn retry_delay(&self) -> Duration {
+        let exponent = self.failures.saturating_sub(1).min(16);
+        (AUTO_SYNC_RETRY_BASE * 2u32.pow(exponent)).min(AUTO_SYNC_RETRY_MAX)
+    }
+}
+
+impl SteamOverachieverApp {
+    fn auto_sync_enabled(&self) -> bool {
+        self.config.cloud_auto_sync && self.config.cloud_token.is_some()
+    }
+
+    /// Schedule an automatic upload after local data changed
+    pub(crate) fn schedule_auto_sync(&mut self) {
+        if !self.auto_sync_enabled() {
+            return;
+        }
+        // New changes restart the debounce, but don't cut a retry backoff short
+        let debounced = Instant::now() + AUTO_SYNC_DEBOUNCE;
+        self.auto_sync.due_at = Some(match self.auto_sync.due_at {
+            Some(due_at) if self.auto_sync.failures > 0 => due_at.max(debounced),
+            _ => debounced,
+        });
+    }
+
+    /// Start the automatic upload once it is due and nothing else is running
+    pub(crate) fn run_auto_sync(&mut self, ctx: &eframe::egui::Context) {
+        let Some(due_at) = self.auto_sync.due_at else {
+            return;
+        };
+        if !self.auto_sync_enabled() {
+            self.auto_sync = AutoSync::default();
+            return;
+        }
+
+        let now = Instant::now();
+        if now < due_at {
+            ctx.request_repaint_after(due_at - now);
+            return;
+        }
+
+        // Wait for updates, other cloud operations and open cloud dialogs
+        let blocked = self.state.is_busy()
+            || self.cloud_op_receiver.is_some()
+            || self.pending_cloud_action.is_some()
+            || self.encryption_dialog.is_some();
+        if blocked {
+            ctx.request_repaint_after(Duration::from_secs(1));
+            return;
+        }
+
+        self.auto_sync.due_at = None;
+        self.auto_sync.running = true;
+        self.upload_to_cloud();
+
+        // Reading local data can fail before anything is sent
+        if self.cloud_op_receiver.is_none() {
+            let error = match &self.cloud_sync_state {
+                crate::cloud_sync::CloudSyncState::Error(e) => e.clone(),
+                _ => "Upload could not be started".to_string(),
+            };
+            self.auto_sync_finished(Err(error));
+        }
+    }
+
+    /// Record the outcome of an upload. Failed automatic uploads are retried.
+    pub(crate) fn auto_sync_finished(&mut self, result: Result<(), String>) {
+        let was_auto = std::mem::take(&mut self.auto_sync.running);
+        match result {
+            Ok(()) => {
+                self.auto_sync.failures = 0;
+                self.auto_sync.last_error = None;
+                self.config.last_cloud_upload = Some(chrono::Utc::now());
+                let _ = self.config.save();
+            }
+            Err(e) if was_auto => {
+                self.auto_sync.failures += 1;
+                self.auto_sync.last_error = Some(e);
+                if self.auto_sync_enabled() {
+                    self.auto_sync.due_at = Some(Instant::now() + self.auto_sync.retry_delay());
+                }
+            }
+            Err(_) => {}
+        }
+    }
+
+    /// Time until the next automatic retry, if one is scheduled after a failure
+    pub(crate) fn auto_sync_retry_in(&self) -> Option<Duration> {
+        if self.auto_sync.failures == 0 {
+            return None;
+        }
+        self.auto_sync.due_at.map(|due_at| due_at.saturating_duration_since(Instant::now()))
+    }
+}
@@ -0,0 +1,195 @@
+//! Achievement comments and tips, game ratings and reviews
+//!
+//! All are loaded from the server over REST, in local and remote mode alike;
+//! the cloud token is needed to post, rate or vote and marks own comments,
+//! tips, ratings and votes.
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+use overachiever_core::{AchievementComment, AchievementTip, CommunityGameRating, GameReviewCache, CommentCache, CommentThreadState, GdprConsent};
+
+use super::SteamOverachieverApp;
+use crate::cloud_sync::{
+    delete_achievement_comment, edit_achievement_comment, fetch_achievement_comments, post_achievement_comment,
+    fetch_achievement_tips, submit_achievement_tip, vote_achievement_tip, fetch_game_ratings, submit_game_rating,
+};
+use crate::ui::SortColumn;
+
+pub(crate) enum CommentsUpdate {
+    Thread { appid: u64, apiname: String, comments: Vec<AchievementComment> },
+    Posted(AchievementComment),
+    Updated(AchievementComment),
+    Deleted(i64),
+    /// All tips of an achievement, also the result of submitting or voting
+    Tips { appid: u64, apiname: String, tips: Vec<AchievementTip> },
+    /// All ratings of a game, also the result of rating it
+    Ratings(CommunityGameRating),
+}
+
+pub(crate) struct Comments {
+    pub cache: CommentCache,
+    pub reviews: GameReviewCache,
+    /// Requests still running
+    pub in_flight: usize,
+    sender: Sender<Result<CommentsUpdate, String>>,
+    receiver: Receiver<Result<CommentsUpdate, String>>,
+}
+
+impl Default for Comments {
+    fn default() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            cache: CommentCache::default(),
+            reviews: GameReviewCache::default(),
+            in_flight: 0,
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl SteamOverachieverApp {
+    /// Apply finished comment requests; called every frame
+    pub(crate) fn check_comments(&mut self) {
+        while let Ok(result) = self.comments.receiver.try_recv() {
+            self.comments.in_flight = self.comments.in_flight.saturating_sub(1);
+            match result {
+                Ok(CommentsUpdate::Thread { appid, apiname, comments }) => {
+                    self.comments.cache.set_thread(appid, apiname, comments);
+                }
+                Ok(CommentsUpdate::Posted(comment)) => self.comments.cache.add(comment),
+                Ok(CommentsUpdate::Updated(comment)) => self.comments.cache.update(comment),
+                Ok(CommentsUpdate::Deleted(id)) => self.comments.cache.remove(id),
+                Ok(CommentsUpdate::Tips { appid, apiname, tips }) => {
+                    self.comments.cache.set_tips(appid, apiname, tips);
+                }
+                Ok(CommentsUpdate::Ratings(community)) => {
+                    self.difficulty.averages.set_game_rating(community.appid, community.avg_rating, community.rating_count);
+                    self.comments.reviews.set(community);
+                    if self.sort_column == SortColumn::Rating {
+                        self.sort_games();
+                    }
+                }
+                Err(e) => self.status = format!("Comments: {}", e),
+            }
+        }
+    }
+
+    /// Run a comment request in the background
+    fn spawn_comments_request(&mut self, request: impl FnOnce() -> Result<CommentsUpdate, String> + Send + 'static) {
+        self.comments.in_flight += 1;
+        let sender = self.comments.sender.clone();
+        thread::spawn(move || {
+            let _ = sender.send(request());
+        });
+    }
+
+    pub(crate) fn comment_thread_state(&mut self) -> Option<&mut CommentThreadState> {
+        // Comments live on the server, so they are hidden for users who
+        // declined data processing
+        if self.config.gdpr_consent == GdprConsent::Declined {
+            return None;
+        }
+        Some(&mut self.comments.cache.thread)
+    }
+
+    pub(crate) fn game_reviews_cache(&mut self) -> Option<&mut GameReviewCache> {
+        // Ratings live on the server too
+        if self.config.gdpr_consent == GdprConsent::Declined {
+            return None;
+        }
+        Some(&mut self.comments.reviews)
+    }
+
+    pub(crate) fn request_comments(&mut self, appid: u64, apiname: &str) {
+        if !self.comments.cache.start_request(appid, apiname) {
+            return;
+        }
+        let server = self.config.server_base_url().to_string();
+        let token = self.config.cloud_token.clone();
+        let apiname = apiname.to_string();
+        self.spawn_comments_request(move || {
+            fetch_achievement_comments(&server, token.as_deref(), appid, &apiname)
+                .map(|comments| CommentsUpdate::Thread { appid, apiname, comments })
+        });
+    }
+
+    pub(crate) fn post_comment(&mut self, achievements: Vec<(u64, String)>, comment: String) {
+        let Some(token) = self.config.cloud_token.clone() else { return };
+        let server = self.config.server_base_url().to_string();
+        self.spawn_comments_request(move || {
+            post_achievement_comment(&server, &token, achievements, &comment).map(CommentsUpdate::Posted)
+        });
+    }
+
+    pub(crate) fn edit_comment(&mut self, id: i64, comment: String) {
+        let Some(token) = self.config.cloud_token.clone() else { return };
+        let server = self.config.server_base_url().to_string();
+        self.spawn_comments_request(move || {
+            edit_achievement_comment(&server, &token, id, &comment).map(CommentsUpdate::Updated)
+        });
+    }
+
+    pub(crate) fn delete_comment(&mut self, id: i64) {
+        let Some(token) = self.config.cloud_token.clone() else { return };
+        let server = self.config.server_base_url().to_string();
+        self.spawn_comments_request(move || {
+            delete_achievement_comment(&server, &token, id).map(|()| CommentsUpdate::Deleted(id))
+        });
+    }
+
+    pub(crate) fn request_game_ratings(&mut self, appid: u64) {
+        if !self.comments.reviews.start_request(appid) {
+            return;
+        }
+        let server = self.config.server_base_url().to_string();
+        let token = self.config.cloud_token.clone();
+        self.spawn_comments_request(move || {
+            fetch_game_ratings(&server, token.as_deref(), appid).map(CommentsUpdate::Ratings)
+        });
+    }
+
+    pub(crate) fn rate_game(&mut self, appid: u64, rating: u8, review: Option<String>) {
+        let Some(token) = self.config.cloud_token.clone() else { return };
+        let server = self.config.server_base_url().to_string();
+        self.spawn_comments_request(move || {
+            submit_game_rating(&server, &token, appid, rating, review.as_deref()).map(CommentsUpdate::Ratings)
+        });
+    }
+
+    pub(crate) fn request_tips(&mut self, appid: u64, apiname: &str) {
+        if !self.comments.cache.start_tips_request(appid, apiname) {
+            return;
+        }
+        let server = self.config.server_base_url().to_string();
+        let token = self.config.cloud_token.clone();
+        let apiname = apiname.to_string();
+        self.spawn_comments_request(move || {
+            fetch_achievement_tips(&server, token.as_deref(), appid, &apiname)
+                .map(|tips| CommentsUpdate::Tips { appid, apiname, tips })
+        });
+    }
+
+    pub(crate) fn submit_tip(&mut self, appid: u64, apiname: String, difficulty: u8, tip: String) {
+        let Some(token) = self.config.cloud_token.clone() else { return };
+        let server = self.config.server_base_url().to_string();
+        self.spawn_comments_request(move || {
+            submit_achievement_tip(&server, &token, appid, &apiname, difficulty, &tip)
+                .map(|tips| CommentsUpdate::Tips { appid, apiname, tips })
+        });
+    }
+
+    pub(crate) fn vote_tip(&mut self, tip_id: i64, vote: i8) {
+        let Some(token) = self.config.cloud_token.clone() else { return };
+        let server = self.config.server_base_url().to_string();
+        self.spawn_comments_request(move || {
+            let tips = vote_achievement_tip(&server, &token, tip_id, vote)?;
+            // The server replies with the tips of the voted tip's achievement
+            let (appid, apiname) = tips.first()
+                .map(|t| (t.appid, t.apiname.clone()))
+                .ok_or_else(|| "Empty tip list".to_string())?;
+            Ok(CommentsUpdate::Tips { appid, apiname, tips })
+        });
+    }
+}
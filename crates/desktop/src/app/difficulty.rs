@@ -0,0 +1,76 @@
+//! Community difficulty and rating averages, and confirmed achievement flags
+//!
+//! Loaded for the whole library from the server's public endpoints, in local
+//! and remote mode alike, and reloaded when the number of games changes.
+
+use std::sync::mpsc::Receiver;
+use std::thread;
+
+use overachiever_core::{AchievementDifficulty, AchievementFlagVotes, CommunityAverages, GameRatingAverage, GdprConsent};
+
+use super::SteamOverachieverApp;
+use crate::cloud_sync::{fetch_achievement_averages, fetch_achievement_flags, fetch_game_rating_averages};
+use crate::ui::SortColumn;
+
+/// Difficulty averages of rated achievements, rating averages of rated games
+/// and confirmed achievement flags
+type Averages = (Vec<AchievementDifficulty>, Vec<GameRatingAverage>, Vec<AchievementFlagVotes>);
+
+#[derive(Default)]
+pub(crate) struct DifficultyCache {
+    pub averages: CommunityAverages,
+    /// Number of games the last request covered
+    requested_for: Option<usize>,
+    pub receiver: Option<Receiver<Result<Averages, String>>>,
+}
+
+impl SteamOverachieverApp {
+    /// Start loading averages when the library changed and store finished
+    /// requests; called every frame
+    pub(crate) fn check_difficulty(&mut self) {
+        if let Some(receiver) = &self.difficulty.receiver {
+            match receiver.try_recv() {
+                Ok(Ok((averages, game_ratings, flags))) => {
+                    self.difficulty.averages.insert(averages);
+                    self.difficulty.averages.insert_game_ratings(game_ratings);
+                    self.difficulty.averages.insert_flags(flags);
+                    self.difficulty.receiver = None;
+                    if matches!(self.sort_column, SortColumn::Difficulty | SortColumn::Rating) {
+                        self.sort_games();
+                    }
+                }
+                Ok(Err(e)) => {
+                    eprintln!("Failed to load community averages: {}", e);
+                    self.difficulty.receiver = None;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {}
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.difficulty.receiver = None;
+                }
+            }
+            return;
+        }
+
+        // Don't contact the server for users who declined data processing
+        if self.games.is_empty() || self.config.gdpr_consent == GdprConsent::Declined
+            || self.difficulty.requested_for == Some(self.games.len())
+        {
+            return;
+        }
+        self.difficulty.requested_for = Some(self.games.len());
+
+        let all_appids: Vec<u64> = self.games.iter().map(|g| g.appid).collect();
+        let appids: Vec<u64> = self.games.iter()
+            .filter(|g| g.achievements_total.map(|t| t > 0).unwrap_or(false))
+            .map(|g| g.appid)
+            .collect();
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.difficulty.receiver = Some(rx);
+        let server = self.config.server_base_url().to_string();
+        thread::spawn(move || {
+            let result = fetch_achievement_averages(&server, &appids)
+                .and_then(|averages| Ok((averages, fetch_game_rating_averages(&server, &all_appids)?, fetch_achievement_flags(&server, &appids)?)));
+            let _ = tx.send(result);
+        });
+    }
+}
@@ -0,0 +1,107 @@
+//! Friends feed
+//!
+//! Followed users and their recent activity are loaded from the server over
+//! REST with the cloud token, in local and remote mode alike.
+
+use std::sync::mpsc::Receiver;
+
+use eframe::egui;
+use overachiever_core::{FeedEntry, FeedPanelPlatform, UserProfile};
+
+use super::SteamOverachieverApp;
+use crate::cloud_sync::{start_follow_change, start_friends_refresh, FriendsUpdate};
+
+#[derive(Default)]
+pub(crate) struct FriendsFeed {
+    pub following: Vec<UserProfile>,
+    pub entries: Vec<FeedEntry>,
+    pub error: Option<String>,
+    /// Profile link input for following someone
+    pub input: String,
+    /// Set once the feed has been requested, so opening the tab loads it once
+    pub loaded: bool,
+    pub receiver: Option<Receiver<Result<FriendsUpdate, String>>>,
+    /// Library to open in the guest view on the next frame
+    pub open_profile: Option<String>,
+}
+
+impl SteamOverachieverApp {
+    /// Apply finished friends requests and open requested profiles; called every frame
+    pub(crate) fn check_friends(&mut self, ctx: &egui::Context) {
+        if let Some(short_id) = self.friends.open_profile.take() {
+            self.open_guest_view(ctx, short_id);
+        }
+
+        let Some(receiver) = &self.friends.receiver else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(Ok(update)) => {
+                self.friends.following = update.following;
+                self.friends.entries = update.feed;
+                self.friends.error = None;
+                self.friends.receiver = None;
+            }
+            Ok(Err(e)) => {
+                self.friends.error = Some(e);
+                self.friends.receiver = None;
+            }
+            Err(std::sync::mpsc::TryRecvError::Empty) => {}
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                self.friends.receiver = None;
+            }
+        }
+    }
+
+    /// Load the feed the first time the Friends tab is shown
+    pub(crate) fn ensure_friends_loaded(&mut self) {
+        if !self.friends.loaded {
+            self.refresh_feed();
+        }
+    }
+}
+
+impl FeedPanelPlatform for SteamOverachieverApp {
+    fn following(&self) -> &[UserProfile] {
+        &self.friends.following
+    }
+
+    fn feed(&self) -> &[FeedEntry] {
+        &self.friends.entries
+    }
+
+    fn feed_loading(&self) -> bool {
+        self.friends.receiver.is_some()
+    }
+
+    fn feed_error(&self) -> Option<&str> {
+        self.friends.error.as_deref()
+    }
+
+    fn follow_input(&mut self) -> &mut String {
+        &mut self.friends.input
+    }
+
+    fn follow_user(&mut self, short_id: &str) {
+        if let Some(token) = self.config.cloud_token.clone() {
+            self.friends.receiver = Some(start_follow_change(self.config.server_base_url().to_string(), token, short_id.to_string(), true));
+        }
+    }
+
+    fn unfollow_user(&mut self, short_id: &str) {
+        if let Some(token) = self.config.cloud_token.clone() {
+            self.friends.receiver = Some(start_follow_change(self.config.server_base_url().to_string(), token, short_id.to_string(), false));
+        }
+    }
+
+    fn refresh_feed(&mut self) {
+        if let Some(token) = self.config.cloud_token.clone() {
+            self.friends.loaded = true;
+            self.friends.receiver = Some(start_friends_refresh(self.config.server_base_url().to_string(), token));
+        }
+    }
+
+    fn open_profile(&mut self, short_id: &str) {
+        self.friends.open_profile = Some(short_id.to_string());
+    }
+}
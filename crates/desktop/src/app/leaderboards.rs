@@ -0,0 +1,78 @@
+//! Leaderboards
+//!
+//! Loaded from the server over REST with the cloud token, like the friends feed.
+
+use std::sync::mpsc::Receiver;
+
+use overachiever_core::{Leaderboard, LeaderboardPanelPlatform, LeaderboardViewState};
+
+use super::SteamOverachieverApp;
+use crate::cloud_sync::{start_leaderboard_fetch, start_leaderboard_opt_in};
+
+#[derive(Default)]
+pub(crate) struct Leaderboards {
+    pub view: LeaderboardViewState,
+    pub leaderboard: Option<Leaderboard>,
+    pub error: Option<String>,
+    pub receiver: Option<Receiver<Result<Leaderboard, String>>>,
+}
+
+impl SteamOverachieverApp {
+    /// Apply a finished leaderboard request; called every frame
+    pub(crate) fn check_leaderboards(&mut self) {
+        let Some(receiver) = &self.leaderboards.receiver else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(Ok(leaderboard)) => {
+                self.leaderboards.leaderboard = Some(leaderboard);
+                self.leaderboards.error = None;
+                self.leaderboards.receiver = None;
+            }
+            Ok(Err(e)) => {
+                self.leaderboards.error = Some(e);
+                self.leaderboards.receiver = None;
+            }
+            Err(std::sync::mpsc::TryRecvError::Empty) => {}
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                self.leaderboards.receiver = None;
+            }
+        }
+    }
+}
+
+impl LeaderboardPanelPlatform for SteamOverachieverApp {
+    fn leaderboard(&self) -> Option<&Leaderboard> {
+        self.leaderboards.leaderboard.as_ref()
+    }
+
+    fn leaderboard_loading(&self) -> bool {
+        self.leaderboards.receiver.is_some()
+    }
+
+    fn leaderboard_error(&self) -> Option<&str> {
+        self.leaderboards.error.as_deref()
+    }
+
+    fn leaderboard_view(&self) -> LeaderboardViewState {
+        self.leaderboards.view
+    }
+
+    fn request_leaderboard(&mut self, view: LeaderboardViewState) {
+        self.leaderboards.view = view;
+        if let Some(token) = self.config.cloud_token.clone() {
+            self.leaderboards.receiver = Some(start_leaderboard_fetch(self.config.server_base_url().to_string(), token, view.metric, view.scope));
+        }
+    }
+
+    fn set_leaderboard_opt_in(&mut self, enabled: bool) {
+        if let Some(token) = self.config.cloud_token.clone() {
+            let view = self.leaderboards.view;
+            self.leaderboards.receiver = Some(start_leaderboard_opt_in(self.config.server_base_url().to_string(), token, enabled, view.metric, view.scope));
+        }
+    }
+
+    fn open_profile(&mut self, short_id: &str) {
+        self.friends.open_profile = Some(short_id.to_string());
+    }
+}
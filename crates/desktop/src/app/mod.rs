@@ -2,14 +2,31 @@
 
 mod state;
 mod panels;
+mod auto_sync;
+mod session;
+mod remote;
+mod friends;
+mod comments;
+mod difficulty;
+mod leaderboards;
+mod rarity;
 
 use crate::config::Config;
-use crate::db::{get_all_games, get_run_history, get_achievement_history, get_log_entries, open_connection, get_last_update, finalize_migration, ensure_user, get_all_achievement_ratings};
+use auto_sync::AutoSync;
+use session::CloudSession;
+use remote::RemoteSession;
+use friends::FriendsFeed;
+use comments::Comments;
+use difficulty::DifficultyCache;
+use leaderboards::Leaderboards;
+use rarity::RarityCache;
+use panels::GuestView;
+use crate::db::{get_all_games, get_run_history, get_achievement_history, get_log_entries, open_connection, get_last_update, finalize_migration, ensure_user, get_all_achievement_ratings, get_all_achievement_flags};
 use crate::icon_cache::IconCache;
 use crate::steam_library::get_installed_games;
 use crate::ui::{AppState, SortColumn, SortOrder, TriFilter, ProgressReceiver};
-use crate::cloud_sync::{CloudSyncState, AuthResult, CloudOpResult};
-use overachiever_core::{Game, RunHistory, AchievementHistory, GameAchievement, LogEntry, SidebarPanel, CloudSyncStatus};
+use crate::cloud_sync::{CloudSyncState, AuthResult, CloudOpResult, CloudPreview};
+use overachiever_core::{Game, RunHistory, AchievementHistory, GameAchievement, LogEntry, SidebarPanel, CloudSyncStatus, AchievementFlags};
 
 use eframe::egui;
 use std::collections::{HashMap, HashSet};
@@ -39,10 +56,17 @@ pub struct SteamOverachieverApp {
     pub(crate) expanded_rows: HashSet<u64>,
     // Cache loaded achievements for expanded games
     pub(crate) achievements_cache: HashMap<u64, Vec<GameAchievement>>,
+    // Community and Steam unlock rates for expanded games
+    pub(crate) rarity: RarityCache,
+    // Community difficulty averages for the library
+    pub(crate) difficulty: DifficultyCache,
+    // Achievement comment threads
+    pub(crate) comments: Comments,
     // Icon cache for achievement icons
     pub(crate) icon_cache: IconCache,
     // User achievement ratings: (appid, apiname) -> rating
     pub(crate) user_achievement_ratings: HashMap<(u64, String), u8>,
+    pub(crate) user_achievement_flags: HashMap<(u64, String), AchievementFlags>,
     // Filters
     pub(crate) filter_name: String,
     pub(crate) filter_achievements: TriFilter,
@@ -66,6 +90,24 @@ pub struct SteamOverachieverApp {
     pub(crate) cloud_op_receiver: Option<Receiver<Result<CloudOpResult, String>>>,
     // Pending cloud action (for confirmation dialog)
     pub(crate) pending_cloud_action: Option<CloudAction>,
+    // Merge preview shown in the confirmation dialog
+    pub(crate) cloud_preview: Option<CloudPreview>,
+    // Passphrase dialog for cloud backup encryption
+    pub(crate) encryption_dialog: Option<EncryptionDialog>,
+    // Automatic uploads after updates
+    pub(crate) auto_sync: AutoSync,
+    // Access token refresh and logout of the cloud login session
+    pub(crate) cloud_session: CloudSession,
+    // Server connection in remote mode
+    pub(crate) remote: RemoteSession,
+    // Another user's shared library, opened read-only
+    pub(crate) guest_view: Option<GuestView>,
+    // Profile link input for opening a shared library
+    pub(crate) guest_dialog: Option<String>,
+    // Followed users and their activity
+    pub(crate) friends: FriendsFeed,
+    // Leaderboard panel
+    pub(crate) leaderboards: Leaderboards,
     // Navigation target for scrolling to an achievement
     pub(crate) navigation_target: Option<(u64, String)>, // (appid, apiname)
     // Whether we need to scroll to the navigation target (one-time scroll)
@@ -80,6 +122,8 @@ pub struct SteamOverachieverApp {
     pub(crate) installed_games: HashSet<u64>,
     // Filter for installed games
     pub(crate) filter_installed: TriFilter,
+    // Filter for games with community-flagged missable achievements
+    pub(crate) filter_missables: TriFilter,
 }
 
 /// Cloud action pending confirmation
@@ -90,6 +134,37 @@ pub enum CloudAction {
     Delete,
 }
 
+/// What the encryption passphrase dialog is for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EncryptionAction {
+    /// Start encrypting backups with a new passphrase
+    Enable,
+    /// Enter the passphrase of a backup encrypted on another device
+    Unlock,
+    ChangePassphrase,
+    Disable,
+}
+
+/// State of the encryption passphrase dialog
+#[derive(Debug, Clone)]
+pub struct EncryptionDialog {
+    pub action: EncryptionAction,
+    pub passphrase: String,
+    pub confirm: String,
+    pub error: Option<String>,
+}
+
+impl EncryptionDialog {
+    pub fn new(action: EncryptionAction) -> Self {
+        Self {
+            action,
+            passphrase: String::new(),
+            confirm: String::new(),
+            error: None,
+        }
+    }
+}
+
 impl SteamOverachieverApp {
     pub fn new() -> Self {
         let config = Config::load();
@@ -111,35 +186,31 @@ impl SteamOverachieverApp {
         let is_cloud_linked = config.cloud_token.is_some();
         
         // Load user achievement ratings - prefer server data if authenticated, fallback to local
-        let user_achievement_ratings: HashMap<(u64, String), u8> = if let Some(token) = &config.cloud_token {
-            // Try to fetch from server
-            match crate::cloud_sync::fetch_user_achievement_ratings(token) {
-                Ok(server_ratings) => {
-                    // Update local cache with server data
-                    for (appid, apiname, rating) in &server_ratings {
-                        let _ = crate::db::set_achievement_rating(&conn, steam_id, *appid, apiname, *rating);
-                    }
-                    server_ratings.into_iter()
-                        .map(|(appid, apiname, rating)| ((appid, apiname), rating))
-                        .collect()
-                }
-                Err(_) => {
-                    // Fallback to local cache
-                    get_all_achievement_ratings(&conn, steam_id)
-                        .unwrap_or_default()
-                        .into_iter()
-                        .map(|(appid, apiname, rating)| ((appid, apiname), rating))
-                        .collect()
-                }
+        let server_ratings = config.cloud_token.as_ref()
+            .and_then(|token| crate::cloud_sync::fetch_user_achievement_ratings(config.server_base_url(), token).ok());
+        if let Some(server) = &server_ratings {
+            // Update local cache with server data
+            for (appid, apiname, rating) in &server.ratings {
+                let _ = crate::db::set_achievement_rating(&conn, steam_id, *appid, apiname, *rating);
             }
-        } else {
-            // Not authenticated, use local cache only
-            get_all_achievement_ratings(&conn, steam_id)
-                .unwrap_or_default()
-                .into_iter()
-                .map(|(appid, apiname, rating)| ((appid, apiname), rating))
-                .collect()
+            for (appid, apiname, flags) in &server.flags {
+                let _ = crate::db::set_achievement_flags(&conn, steam_id, *appid, apiname, *flags);
+            }
+        }
+        // Not authenticated or offline: fall back to the local cache
+        let (ratings, flags) = match server_ratings {
+            Some(server) => (server.ratings, server.flags),
+            None => (
+                get_all_achievement_ratings(&conn, steam_id).unwrap_or_default(),
+                get_all_achievement_flags(&conn, steam_id).unwrap_or_default(),
+            ),
         };
+        let user_achievement_ratings: HashMap<(u64, String), u8> = ratings.into_iter()
+            .map(|(appid, apiname, rating)| ((appid, apiname), rating))
+            .collect();
+        let user_achievement_flags: HashMap<(u64, String), AchievementFlags> = flags.into_iter()
+            .map(|(appid, apiname, flags)| ((appid, apiname), flags))
+            .collect();
         
         // Detect installed Steam games
         let installed_games = get_installed_games();
@@ -161,8 +232,12 @@ impl SteamOverachieverApp {
             include_unplayed_in_avg: false,
             expanded_rows: HashSet::new(),
             achievements_cache: HashMap::new(),
+            rarity: RarityCache::default(),
+            difficulty: DifficultyCache::default(),
+            comments: Comments::default(),
             icon_cache: IconCache::new(),
             user_achievement_ratings,
+            user_achievement_flags,
             filter_name: String::new(),
             filter_achievements: TriFilter::All,
             filter_playtime: TriFilter::All,
@@ -177,6 +252,15 @@ impl SteamOverachieverApp {
             auth_receiver: None,
             cloud_op_receiver: None,
             pending_cloud_action: None,
+            cloud_preview: None,
+            encryption_dialog: None,
+            auto_sync: AutoSync::default(),
+            cloud_session: CloudSession::default(),
+            remote: RemoteSession::default(),
+            guest_view: None,
+            guest_dialog: None,
+            friends: FriendsFeed::default(),
+            leaderboards: Leaderboards::default(),
             navigation_target: None,
             needs_scroll_to_target: false,
             log_selected_achievement: None,
@@ -184,13 +268,18 @@ impl SteamOverachieverApp {
             game_launch_times: HashMap::new(),
             installed_games,
             filter_installed: TriFilter::All,
+            filter_missables: TriFilter::All,
         };
         
         // Apply consistent sorting after loading from database
         app.sort_games();
         
-        // Auto-start update on launch
-        app.start_update();
+        // Auto-start update on launch (remote mode updates once connected)
+        if app.is_remote() {
+            app.clear_library();
+        } else {
+            app.start_update();
+        }
         
         app
     }
@@ -201,12 +290,23 @@ impl eframe::App for SteamOverachieverApp {
         self.check_progress();
         self.cleanup_expired_flashes();
         self.check_auth_callback();
+        self.check_cloud_session(ctx);
         self.check_cloud_operation();
+        self.check_remote(ctx);
+        self.check_friends(ctx);
+        self.check_leaderboards();
+        self.check_rarity();
+        self.check_difficulty();
+        self.check_comments();
+        self.run_auto_sync(ctx);
         
         let is_busy = self.state.is_busy();
         let has_flashing = !self.updated_games.is_empty();
         let is_linking = self.auth_receiver.is_some();
-        let is_cloud_op = self.cloud_op_receiver.is_some();
+        let is_cloud_op = self.cloud_op_receiver.is_some() || self.friends.receiver.is_some()
+            || self.leaderboards.receiver.is_some() || self.rarity.in_flight > 0
+            || self.difficulty.receiver.is_some() || self.comments.in_flight > 0
+            || self.cloud_session.in_flight();
         let has_launch_cooldowns = !self.game_launch_times.is_empty();
         
         // Request repaint while busy or while animations are active
@@ -221,6 +321,8 @@ impl eframe::App for SteamOverachieverApp {
         self.render_top_panel(ctx);
         self.render_history_panel(ctx);
         self.render_games_table_panel(ctx);
+        self.render_guest_dialog(ctx);
+        self.render_guest_view(ctx);
         
         // Show GDPR modal if needed (for hybrid/remote mode and consent not set)
         self.render_gdpr_modal(ctx);
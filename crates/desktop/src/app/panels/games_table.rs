@@ -6,7 +6,7 @@ use eframe::egui;
 use crate::app::SteamOverachieverApp;
 use crate::db::{open_connection, get_game_achievements};
 use crate::ui::{SortColumn, SortOrder, TriFilter};
-use overachiever_core::{GamesTablePlatform, GameAchievement, sort_games, get_filtered_indices, render_filter_bar, render_games_table};
+use overachiever_core::{GamesTablePlatform, GameAchievement, GameRarity, AchievementComment, AchievementTip, AchievementFlag, CommentThreadState, GameReviewCache, sort_games, get_filtered_indices, render_filter_bar, render_games_table};
 
 /// Implement GamesTablePlatform for the desktop app
 impl GamesTablePlatform for SteamOverachieverApp {
@@ -25,7 +25,7 @@ impl GamesTablePlatform for SteamOverachieverApp {
             self.sort_column = column;
             self.sort_order = SortOrder::Ascending;
         }
-        sort_games(&mut self.games, self.sort_column, self.sort_order);
+        sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty.averages);
     }
     
     fn filter_name(&self) -> &str {
@@ -69,6 +69,10 @@ impl GamesTablePlatform for SteamOverachieverApp {
     }
     
     fn request_achievements(&mut self, appid: u64) {
+        if self.is_remote() {
+            self.request_remote_achievements(appid);
+            return;
+        }
         // Desktop loads achievements synchronously from local SQLite
         if !self.achievements_cache.contains_key(&appid) {
             if let Ok(conn) = open_connection() {
@@ -79,6 +83,86 @@ impl GamesTablePlatform for SteamOverachieverApp {
         }
     }
     
+    fn achievement_rarity(&self, appid: u64) -> Option<&GameRarity> {
+        self.rarity.loaded.get(&appid)
+    }
+    
+    fn request_achievement_rarity(&mut self, appid: u64) {
+        self.request_rarity(appid);
+    }
+    
+    fn game_avg_difficulty(&self, appid: u64) -> Option<f32> {
+        self.difficulty.averages.game(appid)
+    }
+    
+    fn game_rating(&self, appid: u64) -> Option<(f32, i32)> {
+        self.difficulty.averages.game_rating(appid)
+    }
+    
+    fn community_achievement_flags(&self, appid: u64, apiname: &str) -> Vec<(AchievementFlag, i32)> {
+        self.difficulty.averages.achievement_flags(appid, apiname).to_vec()
+    }
+    
+    fn game_has_missables(&self, appid: u64) -> bool {
+        self.difficulty.averages.game_has_missables(appid)
+    }
+    
+    fn can_flag_achievements(&self) -> bool {
+        true
+    }
+    
+    fn game_reviews(&mut self) -> Option<&mut GameReviewCache> {
+        self.game_reviews_cache()
+    }
+    
+    fn request_game_reviews(&mut self, appid: u64) {
+        self.request_game_ratings(appid);
+    }
+    
+    fn submit_game_rating(&mut self, appid: u64, rating: u8, review: Option<String>) {
+        self.rate_game(appid, rating, review);
+    }
+    
+    fn comment_thread(&mut self) -> Option<&mut CommentThreadState> {
+        self.comment_thread_state()
+    }
+    
+    fn achievement_comments(&self, appid: u64, apiname: &str) -> Option<&[AchievementComment]> {
+        self.comments.cache.get(appid, apiname)
+    }
+    
+    fn request_achievement_comments(&mut self, appid: u64, apiname: &str) {
+        self.request_comments(appid, apiname);
+    }
+    
+    fn post_achievement_comment(&mut self, appid: u64, apiname: String, comment: String) {
+        self.post_comment(vec![(appid, apiname)], comment);
+    }
+    
+    fn edit_achievement_comment(&mut self, id: i64, comment: String) {
+        self.edit_comment(id, comment);
+    }
+    
+    fn delete_achievement_comment(&mut self, id: i64) {
+        self.delete_comment(id);
+    }
+    
+    fn achievement_tips(&self, appid: u64, apiname: &str) -> Option<&[AchievementTip]> {
+        self.comments.cache.tips(appid, apiname)
+    }
+    
+    fn request_achievement_tips(&mut self, appid: u64, apiname: &str) {
+        self.request_tips(appid, apiname);
+    }
+    
+    fn submit_achievement_tip(&mut self, appid: u64, apiname: String, difficulty: u8, tip: String) {
+        self.submit_tip(appid, apiname, difficulty, tip);
+    }
+    
+    fn vote_achievement_tip(&mut self, tip_id: i64, vote: i8) {
+        self.vote_tip(tip_id, vote);
+    }
+    
     fn get_flash_intensity(&self, appid: u64) -> Option<f32> {
         // Use the existing flash mechanism from desktop app
         SteamOverachieverApp::get_flash_intensity(self, appid)
@@ -103,7 +187,8 @@ impl GamesTablePlatform for SteamOverachieverApp {
     
     fn can_refresh_single_game(&self) -> bool {
         // Desktop can always refresh if we have valid config
-        self.config.is_valid()
+        // (and, in remote mode, a server connection)
+        self.config.is_valid() && (!self.is_remote() || self.is_remote_connected())
     }
     
     fn request_single_game_refresh(&mut self, appid: u64) -> bool {
@@ -154,6 +239,14 @@ impl GamesTablePlatform for SteamOverachieverApp {
     fn set_filter_installed(&mut self, filter: TriFilter) {
         self.filter_installed = filter;
     }
+    
+    fn filter_missables(&self) -> TriFilter {
+        self.filter_missables
+    }
+    
+    fn set_filter_missables(&mut self, filter: TriFilter) {
+        self.filter_missables = filter;
+    }
 }
 
 impl SteamOverachieverApp {
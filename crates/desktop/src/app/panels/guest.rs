@@ -0,0 +1,703 @@
+//! Read-only view of another user's shared library
+//!
+//! Opened by short_id or profile link from the top panel. The library is
+//! fetched with the guest WebSocket messages, which need no login, and shown
+//! with the shared games table and stats panel in a separate window. When a
+//! Steam account is linked, the window can also compare the library with the
+//! user's own library on the server.
+
+use std::collections::{HashMap, HashSet};
+
+use eframe::egui::{self, Ui};
+use egui_phosphor::regular;
+use overachiever_core::{
+    Game, GameAchievement, GameRarity, CommunityAverages, AchievementComment, AchievementTip, AchievementFlag, CommunityGameRating, GameReviewCache, CommentCache, CommentThreadState, RunHistory, AchievementHistory, LogEntry, UserProfile, ServerMessage, SidebarPanel,
+    LibraryComparison, GameAchievementComparison, short_id_from_input,
+    GamesTablePlatform, StatsPanelPlatform, StatsPanelConfig, ComparisonPanelPlatform, ComparisonViewState,
+    sort_games, get_filtered_indices, render_filter_bar, render_games_table, render_stats_content, render_log_content,
+    render_comparison,
+};
+
+use crate::app::SteamOverachieverApp;
+use crate::icon_cache::IconCache;
+use crate::ui::{SortColumn, SortOrder, TriFilter};
+use crate::ws_client::{WsClient, WsState};
+
+/// Accent used to mark the window as someone else's library
+const GUEST_ACCENT: egui::Color32 = egui::Color32::from_rgb(90, 150, 230);
+
+/// Tabs of the shared library window
+#[derive(Clone, Copy, PartialEq)]
+enum GuestTab {
+    Library,
+    Compare,
+}
+
+/// Another user's library, opened read-only
+pub(crate) struct GuestView {
+    short_id: String,
+    client: WsClient,
+    user: Option<UserProfile>,
+    error: Option<String>,
+    tab: GuestTab,
+    /// The connection is logged in, so comparing is possible
+    can_compare: bool,
+    comparison_requested: bool,
+    comparison: Option<LibraryComparison>,
+    comparison_error: Option<String>,
+    game_comparisons: HashMap<u64, GameAchievementComparison>,
+    pending_game_comparisons: HashSet<u64>,
+    comparison_view: ComparisonViewState,
+    games: Vec<Game>,
+    run_history: Vec<RunHistory>,
+    achievement_history: Vec<AchievementHistory>,
+    log_entries: Vec<LogEntry>,
+    achievements_cache: HashMap<u64, Vec<GameAchievement>>,
+    pending_achievements: HashSet<u64>,
+    expanded_rows: HashSet<u64>,
+    sort_column: SortColumn,
+    sort_order: SortOrder,
+    filter_name: String,
+    filter_achievements: TriFilter,
+    filter_playtime: TriFilter,
+    filter_missables: TriFilter,
+    include_unplayed_in_avg: bool,
+    games_graph_tab: usize,
+    achievements_graph_tab: usize,
+    sidebar_panel: SidebarPanel,
+    navigation_target: Option<(u64, String)>,
+    needs_scroll_to_target: bool,
+    rarity: HashMap<u64, GameRarity>,
+    pending_rarity: HashSet<u64>,
+    difficulty: CommunityAverages,
+    comments: CommentCache,
+    reviews: GameReviewCache,
+    icon_cache: IconCache,
+}
+
+impl GuestView {
+    /// Open a library; with a token the connection also logs in for comparing
+    fn open(short_id: String, ws_url: &str, token: Option<&str>, ctx: &egui::Context) -> Self {
+        let client = WsClient::connect(ws_url, ctx.clone());
+        if let Some(token) = token {
+            client.authenticate(token);
+        }
+        client.view_guest_library(&short_id);
+        Self {
+            short_id,
+            client,
+            user: None,
+            error: None,
+            tab: GuestTab::Library,
+            can_compare: token.is_some(),
+            comparison_requested: false,
+            comparison: None,
+            comparison_error: None,
+            game_comparisons: HashMap::new(),
+            pending_game_comparisons: HashSet::new(),
+            comparison_view: ComparisonViewState::default(),
+            games: Vec::new(),
+            run_history: Vec::new(),
+            achievement_history: Vec::new(),
+            log_entries: Vec::new(),
+            achievements_cache: HashMap::new(),
+            pending_achievements: HashSet::new(),
+            expanded_rows: HashSet::new(),
+            sort_column: SortColumn::Name,
+            sort_order: SortOrder::Ascending,
+            filter_name: String::new(),
+            filter_achievements: TriFilter::All,
+            filter_playtime: TriFilter::All,
+            filter_missables: TriFilter::All,
+            include_unplayed_in_avg: false,
+            games_graph_tab: 0,
+            achievements_graph_tab: 0,
+            sidebar_panel: SidebarPanel::Stats,
+            navigation_target: None,
+            needs_scroll_to_target: false,
+            rarity: HashMap::new(),
+            pending_rarity: HashSet::new(),
+            difficulty: CommunityAverages::default(),
+            comments: CommentCache::default(),
+            reviews: GameReviewCache::default(),
+            icon_cache: IconCache::new(),
+        }
+    }
+
+    fn check_messages(&mut self) {
+        for msg in self.client.poll_messages() {
+            match msg {
+                ServerMessage::GuestLibrary { user, games } => {
+                    self.user = Some(user);
+                    self.games = games;
+                    sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
+                    self.client.fetch_guest_history(&self.short_id);
+                    let appids: Vec<u64> = self.games.iter()
+                        .filter(|g| g.achievements_total.map(|t| t > 0).unwrap_or(false))
+                        .map(|g| g.appid)
+                        .collect();
+                    self.client.fetch_achievement_averages(appids.clone());
+                    self.client.fetch_achievement_flags(appids);
+                    self.client.fetch_game_rating_averages(self.games.iter().map(|g| g.appid).collect());
+                }
+                ServerMessage::GuestNotFound { short_id } => {
+                    self.error = Some(format!("No shared library found for '{}'", short_id));
+                }
+                ServerMessage::Achievements { appid, achievements } => {
+                    self.pending_achievements.remove(&appid);
+                    self.achievements_cache.insert(appid, achievements);
+                }
+                ServerMessage::AchievementComments { appid, apiname, comments } => {
+                    self.comments.set_thread(appid, apiname, comments);
+                }
+                ServerMessage::AchievementCommentSubmitted { comment } => self.comments.add(comment),
+                ServerMessage::AchievementCommentUpdated { comment } => self.comments.update(comment),
+                ServerMessage::AchievementCommentDeleted { id } => self.comments.remove(id),
+                ServerMessage::CommunityTips { appid, apiname, tips } => {
+                    self.comments.set_tips(appid, apiname, tips);
+                }
+                ServerMessage::CommunityRatings { appid, avg_rating, rating_count, ratings } => {
+                    self.difficulty.set_game_rating(appid, avg_rating, rating_count);
+                    self.reviews.set(CommunityGameRating { appid, avg_rating, rating_count, ratings });
+                    if self.sort_column == SortColumn::Rating {
+                        sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
+                    }
+                }
+                ServerMessage::GameRatingAverages { averages } => {
+                    self.difficulty.insert_game_ratings(averages);
+                    if self.sort_column == SortColumn::Rating {
+                        sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
+                    }
+                }
+                ServerMessage::AchievementFlags { flags } => self.difficulty.insert_flags(flags),
+                ServerMessage::AchievementAverages { averages } => {
+                    self.difficulty.insert(averages);
+                    if self.sort_column == SortColumn::Difficulty {
+                        sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
+                    }
+                }
+                ServerMessage::AchievementRarity { rarity } => {
+                    self.pending_rarity.remove(&rarity.appid);
+                    self.rarity.insert(rarity.appid, rarity);
+                }
+                ServerMessage::History { run_history, achievement_history, log_entries } => {
+                    self.run_history = run_history;
+                    self.achievement_history = achievement_history;
+                    self.log_entries = log_entries;
+                }
+                ServerMessage::LibraryComparison { comparison } => {
+                    self.comparison = Some(comparison);
+                }
+                ServerMessage::GameAchievementComparison { comparison, .. } => {
+                    self.pending_game_comparisons.remove(&comparison.appid);
+                    self.game_comparisons.insert(comparison.appid, comparison);
+                }
+                ServerMessage::AuthError { reason } => {
+                    // Only comparing needs the login; the library stays viewable
+                    self.can_compare = false;
+                    self.comparison_error = Some(format!("Login failed: {}", reason));
+                }
+                ServerMessage::Error { message } if self.tab == GuestTab::Compare => {
+                    self.comparison_error = Some(message);
+                }
+                ServerMessage::Error { message } if self.user.is_some() => {
+                    // A failed comment or similar request; keep the library shown
+                    eprintln!("Guest view: {}", message);
+                }
+                ServerMessage::Error { message } => {
+                    self.error = Some(message);
+                }
+                _ => {}
+            }
+        }
+
+        // A dropped connection only matters while something is still missing
+        if self.error.is_none() {
+            match self.client.state() {
+                WsState::Error(e) => self.error = Some(e),
+                WsState::Closed if self.user.is_none()
+                    || !self.pending_achievements.is_empty()
+                    || !self.pending_game_comparisons.is_empty() => {
+                    self.error = Some("Connection to the server closed".to_string());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn show_comparison(&mut self) {
+        self.tab = GuestTab::Compare;
+        if !self.comparison_requested {
+            self.comparison_requested = true;
+            self.client.compare_libraries(&self.short_id);
+        }
+    }
+
+    fn title(&self) -> String {
+        match &self.user {
+            Some(user) => format!("{}'s library", user.display_name),
+            None => format!("Library {}", self.short_id),
+        }
+    }
+}
+
+impl StatsPanelPlatform for GuestView {
+    fn games(&self) -> &[Game] {
+        &self.games
+    }
+
+    fn run_history(&self) -> &[RunHistory] {
+        &self.run_history
+    }
+
+    fn achievement_history(&self) -> &[AchievementHistory] {
+        &self.achievement_history
+    }
+
+    fn log_entries(&self) -> &[LogEntry] {
+        &self.log_entries
+    }
+
+    fn include_unplayed_in_avg(&self) -> bool {
+        self.include_unplayed_in_avg
+    }
+
+    fn set_include_unplayed_in_avg(&mut self, value: bool) {
+        self.include_unplayed_in_avg = value;
+    }
+
+    fn game_icon_source(&self, ui: &Ui, appid: u64, icon_hash: &str) -> egui::ImageSource<'static> {
+        let game_icon_url = format!(
+            "https://media.steampowered.com/steamcommunity/public/images/apps/{}/{}.jpg",
+            appid, icon_hash
+        );
+
+        if let Some(bytes) = self.icon_cache.get_icon_bytes(&game_icon_url) {
+            let cache_uri = format!("bytes://log_game/{}", appid);
+            ui.ctx().include_bytes(cache_uri.clone(), bytes);
+            egui::ImageSource::Uri(cache_uri.into())
+        } else {
+            egui::ImageSource::Uri(game_icon_url.into())
+        }
+    }
+
+    fn achievement_icon_source(&self, ui: &Ui, icon_url: &str) -> egui::ImageSource<'static> {
+        if let Some(bytes) = self.icon_cache.get_icon_bytes(icon_url) {
+            let cache_uri = format!("bytes://log_ach/{}", icon_url.replace(['/', ':', '.'], "_"));
+            ui.ctx().include_bytes(cache_uri.clone(), bytes);
+            egui::ImageSource::Uri(cache_uri.into())
+        } else {
+            egui::ImageSource::Uri(icon_url.to_string().into())
+        }
+    }
+
+    fn games_graph_tab(&self) -> usize {
+        self.games_graph_tab
+    }
+
+    fn set_games_graph_tab(&mut self, tab: usize) {
+        self.games_graph_tab = tab;
+    }
+
+    fn achievements_graph_tab(&self) -> usize {
+        self.achievements_graph_tab
+    }
+
+    fn set_achievements_graph_tab(&mut self, tab: usize) {
+        self.achievements_graph_tab = tab;
+    }
+
+    fn navigate_to_achievement(&mut self, appid: u64, apiname: String) {
+        self.filter_name.clear();
+        self.filter_achievements = TriFilter::All;
+        self.filter_playtime = TriFilter::All;
+        self.filter_missables = TriFilter::All;
+        self.expanded_rows.insert(appid);
+        self.request_achievements(appid);
+        self.navigation_target = Some((appid, apiname));
+        self.needs_scroll_to_target = true;
+    }
+    fn get_achievement_avg_rating(&self, appid: u64, apiname: &str) -> Option<(f32, i32)> {
+        self.difficulty.achievement(appid, apiname)
+    }
+
+    /// Logged in connections can post comments
+    fn is_authenticated(&self) -> bool {
+        self.can_compare
+    }
+}
+
+/// Read-only: no ratings, refreshes or launching
+impl GamesTablePlatform for GuestView {
+    fn sort_column(&self) -> SortColumn {
+        self.sort_column
+    }
+
+    fn sort_order(&self) -> SortOrder {
+        self.sort_order
+    }
+
+    fn set_sort(&mut self, column: SortColumn) {
+        if self.sort_column == column {
+            self.sort_order = self.sort_order.toggle();
+        } else {
+            self.sort_column = column;
+            self.sort_order = SortOrder::Ascending;
+        }
+        sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
+    }
+
+    fn filter_name(&self) -> &str {
+        &self.filter_name
+    }
+
+    fn set_filter_name(&mut self, name: String) {
+        self.filter_name = name;
+    }
+
+    fn filter_achievements(&self) -> TriFilter {
+        self.filter_achievements
+    }
+
+    fn set_filter_achievements(&mut self, filter: TriFilter) {
+        self.filter_achievements = filter;
+    }
+
+    fn filter_playtime(&self) -> TriFilter {
+        self.filter_playtime
+    }
+
+    fn set_filter_playtime(&mut self, filter: TriFilter) {
+        self.filter_playtime = filter;
+    }
+
+    fn filter_missables(&self) -> TriFilter {
+        self.filter_missables
+    }
+
+    fn set_filter_missables(&mut self, filter: TriFilter) {
+        self.filter_missables = filter;
+    }
+
+    fn is_expanded(&self, appid: u64) -> bool {
+        self.expanded_rows.contains(&appid)
+    }
+
+    fn toggle_expanded(&mut self, appid: u64) {
+        if !self.expanded_rows.remove(&appid) {
+            self.expanded_rows.insert(appid);
+        }
+    }
+
+    fn get_cached_achievements(&self, appid: u64) -> Option<&Vec<GameAchievement>> {
+        self.achievements_cache.get(&appid)
+    }
+
+    fn request_achievements(&mut self, appid: u64) {
+        if self.achievements_cache.contains_key(&appid) || self.pending_achievements.contains(&appid) {
+            return;
+        }
+        self.client.fetch_guest_achievements(&self.short_id, appid);
+        self.pending_achievements.insert(appid);
+    }
+
+    fn game_avg_difficulty(&self, appid: u64) -> Option<f32> {
+        self.difficulty.game(appid)
+    }
+
+    fn game_rating(&self, appid: u64) -> Option<(f32, i32)> {
+        self.difficulty.game_rating(appid)
+    }
+
+    fn community_achievement_flags(&self, appid: u64, apiname: &str) -> Vec<(AchievementFlag, i32)> {
+        self.difficulty.achievement_flags(appid, apiname).to_vec()
+    }
+
+    fn game_has_missables(&self, appid: u64) -> bool {
+        self.difficulty.game_has_missables(appid)
+    }
+
+    fn game_reviews(&mut self) -> Option<&mut GameReviewCache> {
+        Some(&mut self.reviews)
+    }
+
+    fn request_game_reviews(&mut self, appid: u64) {
+        if self.reviews.start_request(appid) {
+            self.client.fetch_game_ratings(appid);
+        }
+    }
+
+    fn submit_game_rating(&mut self, appid: u64, rating: u8, review: Option<String>) {
+        self.client.submit_game_rating(appid, rating, review);
+    }
+
+    fn comment_thread(&mut self) -> Option<&mut CommentThreadState> {
+        Some(&mut self.comments.thread)
+    }
+
+    fn achievement_comments(&self, appid: u64, apiname: &str) -> Option<&[AchievementComment]> {
+        self.comments.get(appid, apiname)
+    }
+
+    fn request_achievement_comments(&mut self, appid: u64, apiname: &str) {
+        if self.comments.start_request(appid, apiname) {
+            self.client.fetch_achievement_comments(appid, apiname);
+        }
+    }
+
+    fn post_achievement_comment(&mut self, appid: u64, apiname: String, comment: String) {
+        self.client.submit_achievement_comment(vec![(appid, apiname)], comment);
+    }
+
+    fn edit_achievement_comment(&mut self, id: i64, comment: String) {
+        self.client.edit_achievement_comment(id, comment);
+    }
+
+    fn delete_achievement_comment(&mut self, id: i64) {
+        self.client.delete_achievement_comment(id);
+    }
+
+    fn achievement_tips(&self, appid: u64, apiname: &str) -> Option<&[AchievementTip]> {
+        self.comments.tips(appid, apiname)
+    }
+
+    fn request_achievement_tips(&mut self, appid: u64, apiname: &str) {
+        if self.comments.start_tips_request(appid, apiname) {
+            self.client.fetch_achievement_tips(appid, apiname);
+        }
+    }
+
+    fn submit_achievement_tip(&mut self, appid: u64, apiname: String, difficulty: u8, tip: String) {
+        self.client.submit_achievement_tip(appid, apiname, difficulty, tip);
+    }
+
+    fn vote_achievement_tip(&mut self, tip_id: i64, vote: i8) {
+        self.client.vote_achievement_tip(tip_id, vote);
+    }
+
+    fn achievement_rarity(&self, appid: u64) -> Option<&GameRarity> {
+        self.rarity.get(&appid)
+    }
+
+    fn request_achievement_rarity(&mut self, appid: u64) {
+        if self.pending_rarity.insert(appid) {
+            self.client.fetch_achievement_rarity(appid);
+        }
+    }
+
+    fn get_navigation_target(&self) -> Option<(u64, String)> {
+        self.navigation_target.clone()
+    }
+
+    fn clear_navigation_target(&mut self) {
+        self.navigation_target = None;
+    }
+
+    fn needs_scroll_to_target(&self) -> bool {
+        self.needs_scroll_to_target
+    }
+
+    fn mark_scrolled_to_target(&mut self) {
+        self.needs_scroll_to_target = false;
+    }
+}
+
+impl ComparisonPanelPlatform for GuestView {
+    fn comparison(&self) -> Option<&LibraryComparison> {
+        self.comparison.as_ref()
+    }
+
+    fn game_comparison(&self, appid: u64) -> Option<&GameAchievementComparison> {
+        self.game_comparisons.get(&appid)
+    }
+
+    fn request_game_comparison(&mut self, appid: u64) {
+        if self.pending_game_comparisons.insert(appid) {
+            self.client.compare_game_achievements(&self.short_id, appid);
+        }
+    }
+
+    fn comparison_view(&self) -> &ComparisonViewState {
+        &self.comparison_view
+    }
+
+    fn comparison_view_mut(&mut self) -> &mut ComparisonViewState {
+        &mut self.comparison_view
+    }
+}
+
+impl SteamOverachieverApp {
+    /// Open a shared library by short_id, replacing any open one
+    pub(crate) fn open_guest_view(&mut self, ctx: &egui::Context, short_id: String) {
+        let token = self.config.cloud_token.as_deref();
+        self.guest_view = Some(GuestView::open(short_id, &self.config.websocket_url(), token, ctx));
+    }
+
+    /// Prompt for the profile link or short_id of the library to open
+    pub(crate) fn render_guest_dialog(&mut self, ctx: &egui::Context) {
+        let Some(mut input) = self.guest_dialog.take() else {
+            return;
+        };
+
+        let mut open = true;
+        let mut submitted = false;
+        egui::Window::new(format!("{} View a Friend's Library", regular::USERS))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("Paste their profile link or ID:");
+                ui.add_space(4.0);
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut input)
+                        .desired_width(320.0)
+                        .hint_text("https://overachiever.space/abc123")
+                );
+                let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                let short_id = short_id_from_input(&input);
+                if !input.trim().is_empty() && short_id.is_none() {
+                    ui.colored_label(egui::Color32::YELLOW, format!("{} Not a valid profile link", regular::WARNING));
+                }
+
+                ui.add_space(8.0);
+                let clicked = ui.add_enabled(short_id.is_some(), egui::Button::new(format!("{} Open", regular::EYE))).clicked();
+                if let Some(short_id) = short_id.filter(|_| clicked || entered) {
+                    self.open_guest_view(ctx, short_id);
+                    submitted = true;
+                }
+            });
+
+        if open && !submitted {
+            self.guest_dialog = Some(input);
+        }
+    }
+
+    /// Window showing the open shared library
+    pub(crate) fn render_guest_view(&mut self, ctx: &egui::Context) {
+        let Some(mut guest) = self.guest_view.take() else {
+            return;
+        };
+        guest.check_messages();
+
+        let mut open = true;
+        let mut retry = false;
+        let frame = egui::Frame::window(&ctx.style()).stroke(egui::Stroke::new(2.0, GUEST_ACCENT));
+        egui::Window::new(format!("{} {}", regular::EYE, guest.title()))
+            .id(egui::Id::new("guest_view"))
+            .open(&mut open)
+            .frame(frame)
+            .default_size([900.0, 600.0])
+            .min_size([600.0, 300.0])
+            .show(ctx, |ui| {
+                // Banner making clear whose data this is
+                egui::Frame::new()
+                    .fill(GUEST_ACCENT.gamma_multiply(0.25))
+                    .inner_margin(6.0)
+                    .corner_radius(4.0)
+                    .show(ui, |ui| {
+                        ui.set_width(ui.available_width());
+                        let name = guest.user.as_ref().map(|u| u.display_name.as_str()).unwrap_or("another user");
+                        ui.label(
+                            egui::RichText::new(format!("{} Viewing {}'s library (read-only)", regular::EYE, name))
+                                .color(GUEST_ACCENT)
+                                .strong()
+                        );
+                    });
+                ui.add_space(4.0);
+
+                if let Some(error) = &guest.error {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("{} {}", regular::WARNING, error));
+                    retry = ui.button(format!("{} Retry", regular::ARROWS_CLOCKWISE)).clicked();
+                    return;
+                }
+                if guest.user.is_none() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Loading library...");
+                    });
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.selectable_label(guest.tab == GuestTab::Library, format!("{} Library", regular::GAME_CONTROLLER)).clicked() {
+                        guest.tab = GuestTab::Library;
+                    }
+                    let compare = ui.add_enabled(
+                        guest.can_compare,
+                        egui::Button::selectable(guest.tab == GuestTab::Compare, format!("{} Compare with mine", regular::SCALES))
+                    );
+                    if compare.clicked() {
+                        guest.show_comparison();
+                    }
+                    compare.on_disabled_hover_text("Link your Steam account in Settings to compare libraries");
+                });
+                ui.separator();
+
+                if guest.tab == GuestTab::Compare {
+                    if let Some(error) = &guest.comparison_error {
+                        ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("{} {}", regular::WARNING, error));
+                        return;
+                    }
+                    ui.label(
+                        egui::RichText::new("Compared with your library on the server. Upload or use remote mode to keep it current.")
+                            .color(egui::Color32::GRAY)
+                    );
+                    ui.add_space(4.0);
+                    render_comparison(ui, &mut guest);
+                    return;
+                }
+
+                egui::SidePanel::right("guest_sidebar")
+                    .min_width(300.0)
+                    .show_inside(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            if ui.selectable_label(guest.sidebar_panel == SidebarPanel::Stats, format!("{} Stats", regular::CHART_LINE)).clicked() {
+                                guest.sidebar_panel = SidebarPanel::Stats;
+                            }
+                            if ui.selectable_label(guest.sidebar_panel == SidebarPanel::Log, format!("{} Log", regular::SCROLL)).clicked() {
+                                guest.sidebar_panel = SidebarPanel::Log;
+                            }
+                        });
+                        ui.separator();
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            match guest.sidebar_panel {
+                                SidebarPanel::Stats => render_stats_content(ui, &mut guest, &StatsPanelConfig::desktop()),
+                                // Guests have no friends or leaderboards tabs
+                                SidebarPanel::Log | SidebarPanel::Friends | SidebarPanel::Leaderboards => render_log_content(ui, &mut guest),
+                            }
+                        });
+                    });
+
+                egui::CentralPanel::default().show_inside(ui, |ui| {
+                    ui.heading(format!("Games ({} games)", guest.games.len()));
+                    ui.separator();
+
+                    if guest.games.is_empty() {
+                        ui.label("This library has no games yet.");
+                        return;
+                    }
+
+                    render_filter_bar(ui, &mut guest);
+                    ui.add_space(4.0);
+
+                    let filtered_indices = get_filtered_indices(&guest);
+                    if filtered_indices.len() != guest.games.len() {
+                        ui.label(format!("Showing {} of {} games", filtered_indices.len(), guest.games.len()));
+                    }
+
+                    for appid in render_games_table(ui, &mut guest, filtered_indices) {
+                        guest.request_achievements(appid);
+                    }
+                });
+            });
+
+        if retry {
+            let short_id = guest.short_id.clone();
+            self.open_guest_view(ctx, short_id);
+        } else if open {
+            self.guest_view = Some(guest);
+        }
+    }
+}
@@ -2,7 +2,7 @@
 
 use eframe::egui;
 use egui_phosphor::regular;
-use overachiever_core::{render_stats_content, render_log_content, StatsPanelConfig, SidebarPanel};
+use overachiever_core::{render_stats_content, render_log_content, render_feed_content, render_leaderboard_content, StatsPanelConfig, SidebarPanel};
 
 use crate::app::SteamOverachieverApp;
 
@@ -19,7 +19,7 @@ impl SteamOverachieverApp {
             .fill(darker_fill);
 
         if !self.show_stats_panel {
-            // Collapsed sidebar - show two buttons (Stats and Log)
+            // Collapsed sidebar - show buttons for each panel (Stats, Log, Friends and Leaderboards)
             egui::SidePanel::right("history_panel_collapsed")
                 .exact_width(36.0)
                 .resizable(false)
@@ -42,6 +42,22 @@ impl SteamOverachieverApp {
                         self.sidebar_panel = SidebarPanel::Log;
                         self.show_stats_panel = true;
                     }
+                    // Friends button
+                    if ui.button(regular::USERS.to_string())
+                        .on_hover_text("Open Friends Panel")
+                        .clicked()
+                    {
+                        self.sidebar_panel = SidebarPanel::Friends;
+                        self.show_stats_panel = true;
+                    }
+                    // Leaderboards button
+                    if ui.button(regular::TROPHY.to_string())
+                        .on_hover_text("Open Leaderboards Panel")
+                        .clicked()
+                    {
+                        self.sidebar_panel = SidebarPanel::Leaderboards;
+                        self.show_stats_panel = true;
+                    }
                 });
             return;
         }
@@ -65,6 +81,8 @@ impl SteamOverachieverApp {
                     // Panel navigation tabs
                     let stats_selected = self.sidebar_panel == SidebarPanel::Stats;
                     let log_selected = self.sidebar_panel == SidebarPanel::Log;
+                    let friends_selected = self.sidebar_panel == SidebarPanel::Friends;
+                    let leaderboards_selected = self.sidebar_panel == SidebarPanel::Leaderboards;
                     
                     if ui.selectable_label(stats_selected, format!("{} Stats", regular::CHART_LINE)).clicked() {
                         self.sidebar_panel = SidebarPanel::Stats;
@@ -72,6 +90,12 @@ impl SteamOverachieverApp {
                     if ui.selectable_label(log_selected, format!("{} Log", regular::SCROLL)).clicked() {
                         self.sidebar_panel = SidebarPanel::Log;
                     }
+                    if ui.selectable_label(friends_selected, format!("{} Friends", regular::USERS)).clicked() {
+                        self.sidebar_panel = SidebarPanel::Friends;
+                    }
+                    if ui.selectable_label(leaderboards_selected, format!("{} Leaderboards", regular::TROPHY)).clicked() {
+                        self.sidebar_panel = SidebarPanel::Leaderboards;
+                    }
                 });
                 ui.separator();
 
@@ -84,6 +108,13 @@ impl SteamOverachieverApp {
                         SidebarPanel::Log => {
                             render_log_content(ui, self);
                         }
+                        SidebarPanel::Friends => {
+                            self.ensure_friends_loaded();
+                            render_feed_content(ui, self);
+                        }
+                        SidebarPanel::Leaderboards => {
+                            render_leaderboard_content(ui, self);
+                        }
                     }
                 });
             });
@@ -4,3 +4,6 @@ mod top;
 mod history;
 mod games_table;
 mod stats_impl;
+mod guest;
+
+pub(crate) use guest::GuestView;
@@ -1,11 +1,11 @@
 //! Platform implementation for shared stats panel
 
 use eframe::egui::{self, Ui};
-use overachiever_core::{Game, RunHistory, AchievementHistory, LogEntry, StatsPanelPlatform};
+use overachiever_core::{Game, RunHistory, AchievementHistory, AchievementFlags, LogEntry, StatsPanelPlatform};
 
 use crate::app::SteamOverachieverApp;
-use crate::db::{open_connection, set_achievement_rating};
-use crate::cloud_sync::submit_achievement_rating;
+use crate::db::{open_connection, set_achievement_rating, set_achievement_flags};
+use crate::cloud_sync::{submit_achievement_rating, submit_achievement_flags};
 
 impl StatsPanelPlatform for SteamOverachieverApp {
     fn games(&self) -> &[Game] {
@@ -93,21 +93,49 @@ impl StatsPanelPlatform for SteamOverachieverApp {
         
         // Submit to remote server if authenticated
         if let Some(token) = &self.config.cloud_token {
-            submit_achievement_rating(token, appid, &apiname, rating);
+            submit_achievement_rating(self.config.server_base_url(), token, appid, &apiname, rating);
         }
     }
     
+    fn get_user_achievement_flags(&self, appid: u64, apiname: &str) -> AchievementFlags {
+        self.user_achievement_flags.get(&(appid, apiname.to_string())).copied().unwrap_or_default()
+    }
+    
+    fn set_user_achievement_flags(&mut self, appid: u64, apiname: String, flags: AchievementFlags) {
+        if flags.is_empty() {
+            self.user_achievement_flags.remove(&(appid, apiname.clone()));
+        } else {
+            self.user_achievement_flags.insert((appid, apiname.clone()), flags);
+        }
+        
+        let steam_id = self.config.steam_id.clone();
+        if let Ok(conn) = open_connection() {
+            let _ = set_achievement_flags(&conn, &steam_id, appid, &apiname, flags);
+        }
+        
+        if let Some(token) = &self.config.cloud_token {
+            submit_achievement_flags(self.config.server_base_url(), token, appid, &apiname, flags);
+        }
+    }
+    
+    fn get_achievement_avg_rating(&self, appid: u64, apiname: &str) -> Option<(f32, i32)> {
+        self.difficulty.averages.achievement(appid, apiname)
+    }
+    
     fn navigate_to_achievement(&mut self, appid: u64, apiname: String) {
         // Clear filters so the game is visible
         self.filter_name.clear();
         self.filter_achievements = crate::ui::TriFilter::All;
         self.filter_playtime = crate::ui::TriFilter::All;
+        self.filter_missables = crate::ui::TriFilter::All;
         
         // Expand the game row
         self.expanded_rows.insert(appid);
         
         // Load achievements if not cached
-        if !self.achievements_cache.contains_key(&appid) {
+        if self.is_remote() {
+            self.request_remote_achievements(appid);
+        } else if !self.achievements_cache.contains_key(&appid) {
             if let Ok(conn) = open_connection() {
                 if let Ok(achs) = crate::db::get_game_achievements(&conn, &self.config.steam_id, appid) {
                     self.achievements_cache.insert(appid, achs);
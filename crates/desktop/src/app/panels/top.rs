@@ -4,8 +4,9 @@ use eframe::egui;
 use egui_phosphor::regular;
 use overachiever_core::{GdprConsent, DATA_HANDLING_DESCRIPTION};
 
-use crate::app::SteamOverachieverApp;
+use crate::app::{SteamOverachieverApp, EncryptionAction, EncryptionDialog};
 use crate::cloud_sync::CloudSyncState;
+use crate::config::DataMode;
 
 // Build info embedded at compile time
 const BUILD_NUMBER: &str = env!("BUILD_NUMBER");
@@ -14,6 +15,8 @@ const BUILD_DATETIME: &str = env!("BUILD_DATETIME");
 impl SteamOverachieverApp {
     pub(crate) fn render_top_panel(&mut self, ctx: &egui::Context) {
         let is_busy = self.state.is_busy();
+        // Remote mode also needs the server connection
+        let can_run = self.config.is_valid() && (!self.is_remote() || self.is_remote_connected());
         
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
@@ -27,7 +30,7 @@ impl SteamOverachieverApp {
                 
                 // Update button - for recently played games
                 let update_button = egui::Button::new(format!("{} Update", regular::ARROWS_CLOCKWISE));
-                let update_response = ui.add_enabled(!is_busy && self.config.is_valid(), update_button);
+                let update_response = ui.add_enabled(!is_busy && can_run, update_button);
                 
                 // Show warning if update is stale
                 if self.is_update_stale() && !is_busy {
@@ -37,9 +40,13 @@ impl SteamOverachieverApp {
                 }
                 
                 if !self.config.is_valid() {
-                    update_response.clone().on_hover_text(
+                    update_response.clone().on_hover_text(if self.is_remote() {
+                        "Please link your Steam account in Settings (⚙)"
+                    } else {
                         "Please configure Steam API Key and Steam ID in Settings (⚙)"
-                    );
+                    });
+                } else if !can_run {
+                    update_response.clone().on_hover_text("Not connected to the server");
                 }
                 
                 if update_response.clicked() {
@@ -53,7 +60,7 @@ impl SteamOverachieverApp {
                 } else {
                     format!("{} Full Scan", regular::GAME_CONTROLLER)
                 };
-                let can_scan = (needs_scrape > 0 || self.force_full_scan) && self.config.is_valid();
+                let can_scan = (needs_scrape > 0 || self.force_full_scan) && can_run;
                 if ui.add_enabled(!is_busy && can_scan, egui::Button::new(full_scan_label)).clicked() {
                     self.start_scrape();
                 }
@@ -84,9 +91,13 @@ impl SteamOverachieverApp {
                         }
                     }
                     
+                    if ui.button(regular::USERS).on_hover_text("View a friend's library").clicked() {
+                        self.guest_dialog = Some(String::new());
+                    }
+                    
                     // User profile button - show shareable link if cloud linked
                     if let Some(short_id) = self.config.get_short_id() {
-                        let profile_url = format!("https://overachiever.space/{}", short_id);
+                        let profile_url = format!("{}/{}", self.config.server_base_url(), short_id);
                         if ui.button(regular::USER)
                             .on_hover_text_at_pointer(format!("Open profile: {}", profile_url))
                             .clicked() 
@@ -94,6 +105,11 @@ impl SteamOverachieverApp {
                             let _ = open::that(&profile_url);
                         }
                     }
+                    
+                    // Cloud sync indicator
+                    if self.config.cloud_token.is_some() {
+                        self.render_cloud_sync_indicator(ui);
+                    }
                 });
             });
         });
@@ -102,6 +118,52 @@ impl SteamOverachieverApp {
         self.render_settings_window(ctx);
     }
     
+    /// Last-synced indicator for the top panel (laid out right to left)
+    fn render_cloud_sync_indicator(&mut self, ui: &mut egui::Ui) {
+        if matches!(self.cloud_sync_state, CloudSyncState::Uploading(_)) {
+            ui.label(format!("{} Syncing...", regular::CLOUD_ARROW_UP));
+            ui.spinner();
+            return;
+        }
+        
+        let response = if let Some(error) = &self.auto_sync.last_error {
+            let retry = match self.auto_sync_retry_in() {
+                Some(delay) => format!("\nRetrying in {}", format_duration(delay.as_secs())),
+                None => String::new(),
+            };
+            ui.add(egui::Label::new(
+                egui::RichText::new(format!("{} Sync failed", regular::CLOUD_WARNING)).color(egui::Color32::from_rgb(255, 180, 50))
+            ).sense(egui::Sense::click()))
+                .on_hover_text(format!("Automatic upload failed: {}{}", error, retry))
+        } else if let Some(at) = self.config.last_cloud_upload {
+            let elapsed = (chrono::Utc::now() - at).num_seconds().max(0) as u64;
+            let text = if elapsed < 60 {
+                "Synced just now".to_string()
+            } else {
+                format!("Synced {} ago", format_duration(elapsed))
+            };
+            ui.ctx().request_repaint_after(std::time::Duration::from_secs(60));
+            let auto = if self.config.cloud_auto_sync { "on" } else { "off" };
+            ui.add(egui::Label::new(
+                egui::RichText::new(format!("{} {}", regular::CLOUD_CHECK, text)).color(egui::Color32::GRAY)
+            ).sense(egui::Sense::click()))
+                .on_hover_text(format!(
+                    "Last upload: {}\nAutomatic uploads: {}",
+                    at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                    auto
+                ))
+        } else {
+            ui.add(egui::Label::new(
+                egui::RichText::new(format!("{} Not synced yet", regular::CLOUD)).color(egui::Color32::GRAY)
+            ).sense(egui::Sense::click()))
+                .on_hover_text("Upload from Settings, or turn on automatic uploads")
+        };
+        
+        if response.clicked() {
+            self.show_settings = true;
+        }
+    }
+    
     fn render_settings_window(&mut self, ctx: &egui::Context) {
         let mut show_settings = self.show_settings;
         
@@ -131,6 +193,44 @@ impl SteamOverachieverApp {
                     ui.separator();
                     ui.add_space(8.0);
                     
+                    // Data source
+                    ui.heading(format!("{} Data Source", regular::DATABASE));
+                    
+                    ui.add_space(8.0);
+                    
+                    let mut mode = self.config.mode;
+                    ui.add_enabled_ui(!self.state.is_busy(), |ui| {
+                        ui.radio_value(&mut mode, DataMode::Local, "Local: your own Steam API key, data stays on this computer");
+                        ui.radio_value(&mut mode, DataMode::Remote, "Remote: updates run on the Overachiever server, no API key needed");
+                    });
+                    if mode != self.config.mode {
+                        self.set_data_mode(mode);
+                    }
+                    
+                    if self.is_remote() {
+                        ui.add_space(4.0);
+                        ui.label(
+                            egui::RichText::new(format!("Server: {}", self.config.server_base_url()))
+                                .color(egui::Color32::GRAY)
+                        );
+                        if let Some(user) = &self.remote.user {
+                            ui.colored_label(egui::Color32::GREEN, format!("{} Connected as {}", regular::CHECK, user.display_name));
+                        } else if self.config.cloud_token.is_none() {
+                            ui.colored_label(egui::Color32::YELLOW, format!("{} Link your Steam account under Cloud Sync", regular::WARNING));
+                        } else if self.remote.client.is_some() {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label("Connecting...");
+                            });
+                        } else {
+                            ui.colored_label(egui::Color32::YELLOW, format!("{} Not connected", regular::WARNING));
+                        }
+                    }
+                    
+                    ui.add_space(12.0);
+                    ui.separator();
+                    ui.add_space(8.0);
+                    
                     // Steam credentials
                     ui.heading("Steam Credentials");
                     
@@ -190,7 +290,12 @@ impl SteamOverachieverApp {
                     ui.add_space(12.0);
                     
                     // Validation status
-                    if !self.config.is_valid() {
+                    if self.is_remote() && !self.config.has_steam_credentials() {
+                        ui.label(
+                            egui::RichText::new("Not needed in remote mode")
+                                .color(egui::Color32::GRAY)
+                        );
+                    } else if !self.config.has_steam_credentials() {
                         ui.colored_label(egui::Color32::YELLOW, format!("{} Steam ID and API Key are required", regular::WARNING));
                     } else {
                         ui.colored_label(egui::Color32::GREEN, format!("{} Configuration valid", regular::CHECK));
@@ -238,7 +343,13 @@ impl SteamOverachieverApp {
                                         if progress.bytes_sent >= progress.total_bytes {
                                             ui.label(format!("Uploaded {:.2} MB", mb_total));
                                         } else {
-                                            ui.label(format!("Uploading {:.2} MB...", mb_total));
+                                            ui.label(format!(
+                                                "Uploading chunk {}/{} ({:.2} of {:.2} MB)...",
+                                                (progress.chunks_sent + 1).min(progress.chunk_count),
+                                                progress.chunk_count,
+                                                progress.bytes_sent as f64 / (1024.0 * 1024.0),
+                                                mb_total
+                                            ));
                                         }
                                     } else {
                                         ui.label("Preparing upload...");
@@ -247,6 +358,13 @@ impl SteamOverachieverApp {
                                 if progress.total_bytes > 0 {
                                     let fraction = progress.bytes_sent as f32 / progress.total_bytes as f32;
                                     ui.add(egui::ProgressBar::new(fraction).desired_width(200.0).animate(progress.bytes_sent < progress.total_bytes));
+                                    if progress.uncompressed_bytes > progress.total_bytes {
+                                        ui.label(egui::RichText::new(format!(
+                                            "{} compressed from {:.2} MB",
+                                            progress.encoding,
+                                            progress.uncompressed_bytes as f64 / (1024.0 * 1024.0)
+                                        )).small().color(egui::Color32::GRAY));
+                                    }
                                 }
                             });
                         }
@@ -262,6 +380,12 @@ impl SteamOverachieverApp {
                                 ui.label("Deleting...");
                             });
                         }
+                        CloudSyncState::UpdatingEncryption => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label("Updating encryption...");
+                            });
+                        }
                         CloudSyncState::Success(msg) => {
                             ui.colored_label(egui::Color32::GREEN, format!("{} {}", regular::CHECK, msg));
                         }
@@ -273,13 +397,15 @@ impl SteamOverachieverApp {
                     ui.add_space(8.0);
                     
                     // Buttons
-                    let is_busy = matches!(cloud_state, CloudSyncState::Checking | CloudSyncState::Uploading(_) | CloudSyncState::Downloading | CloudSyncState::Deleting | CloudSyncState::Linking);
+                    let is_busy = matches!(cloud_state, CloudSyncState::Checking | CloudSyncState::Uploading(_) | CloudSyncState::Downloading | CloudSyncState::Deleting | CloudSyncState::UpdatingEncryption | CloudSyncState::Linking);
                     
                     let mut link_clicked = false;
                     let mut unlink_clicked = false;
+                    let mut logout_all_clicked = false;
                     let mut upload_clicked = false;
                     let mut download_clicked = false;
                     let mut delete_clicked = false;
+                    let mut encryption_action = None;
                     
                     if !is_linked {
                         // Not linked - show link button
@@ -298,10 +424,55 @@ impl SteamOverachieverApp {
                             delete_clicked = true;
                         }
                         
+                        // Encryption of cloud backups
+                        ui.add_space(4.0);
+                        let cloud_encrypted = self.cloud_status.as_ref().is_some_and(|s| s.encrypted);
+                        if self.config.cloud_encryption.is_some() {
+                            ui.label(egui::RichText::new(format!("{} History is uploaded encrypted", regular::LOCK)).color(egui::Color32::GREEN));
+                            ui.horizontal(|ui| {
+                                if ui.add_enabled(!is_busy, egui::Button::new(format!("{} Change passphrase", regular::KEY))).clicked() {
+                                    encryption_action = Some(EncryptionAction::ChangePassphrase);
+                                }
+                                if ui.add_enabled(!is_busy, egui::Button::new(format!("{} Turn off", regular::LOCK_OPEN))).clicked() {
+                                    encryption_action = Some(EncryptionAction::Disable);
+                                }
+                            });
+                        } else if cloud_encrypted {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(255, 180, 50),
+                                format!("{} Your cloud history is encrypted on another device", regular::LOCK),
+                            );
+                            if ui.add_enabled(!is_busy, egui::Button::new(format!("{} Enter passphrase", regular::KEY))).clicked() {
+                                encryption_action = Some(EncryptionAction::Unlock);
+                            }
+                        } else if ui.add_enabled(!is_busy, egui::Button::new(format!("{} Encrypt cloud history", regular::LOCK)))
+                            .on_hover_text("Encrypt your history with a passphrase before it leaves this computer")
+                            .clicked()
+                        {
+                            encryption_action = Some(EncryptionAction::Enable);
+                        }
+                        
                         ui.add_space(4.0);
-                        if ui.add_enabled(!is_busy, egui::Button::new(format!("{} Unlink account", regular::LINK_BREAK))).clicked() {
-                            unlink_clicked = true;
+                        if ui.checkbox(&mut self.config.cloud_auto_sync, "Upload automatically after updates")
+                            .on_hover_text("Push changes to overachiever.space after every Update or Full Scan")
+                            .changed()
+                        {
+                            let _ = self.config.save();
                         }
+                        
+                        ui.add_space(4.0);
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(!is_busy, egui::Button::new(format!("{} Unlink account", regular::LINK_BREAK))).clicked() {
+                                unlink_clicked = true;
+                            }
+                            let logging_out = self.cloud_session.logout_all_receiver.is_some();
+                            if ui.add_enabled(!is_busy && !logging_out, egui::Button::new(format!("{} Log out all devices", regular::SIGN_OUT)))
+                                .on_hover_text("End every session of your account, e.g. on a lost computer, and unlink this one")
+                                .clicked()
+                            {
+                                logout_all_clicked = true;
+                            }
+                        });
                     }
                     
                     // Handle clicks - set pending action for confirmation
@@ -311,14 +482,20 @@ impl SteamOverachieverApp {
                     if unlink_clicked {
                         self.unlink_cloud();
                     }
+                    if logout_all_clicked {
+                        self.logout_all_devices();
+                    }
                     if upload_clicked {
-                        self.pending_cloud_action = Some(crate::app::CloudAction::Upload);
+                        self.request_cloud_action(crate::app::CloudAction::Upload);
                     }
                     if download_clicked {
-                        self.pending_cloud_action = Some(crate::app::CloudAction::Download);
+                        self.request_cloud_action(crate::app::CloudAction::Download);
                     }
                     if delete_clicked {
-                        self.pending_cloud_action = Some(crate::app::CloudAction::Delete);
+                        self.request_cloud_action(crate::app::CloudAction::Delete);
+                    }
+                    if let Some(action) = encryption_action {
+                        self.encryption_dialog = Some(EncryptionDialog::new(action));
                     }
                     
                     // Debug section
@@ -343,11 +520,13 @@ impl SteamOverachieverApp {
         
         // Render cloud action confirmation dialog
         self.render_cloud_confirm_dialog(ctx);
+        self.render_encryption_dialog(ctx);
     }
     
     /// Render confirmation dialog for cloud actions
     fn render_cloud_confirm_dialog(&mut self, ctx: &egui::Context) {
         use crate::app::CloudAction;
+        use crate::cloud_sync::CloudPreview;
         
         let pending = self.pending_cloud_action.clone();
         if pending.is_none() {
@@ -358,12 +537,12 @@ impl SteamOverachieverApp {
         let (title, message, confirm_text) = match &action {
             CloudAction::Upload => (
                 "Upload to Cloud",
-                "This will upload all your local data to overachiever.space.\nAny existing cloud data will be replaced.",
+                "This will merge your local data into overachiever.space.\nHistory already in the cloud is kept.",
                 "Upload"
             ),
             CloudAction::Download => (
                 "Download from Cloud", 
-                "This will download data from overachiever.space and replace your local data.",
+                "This will merge data from overachiever.space into your local data.\nYour local history is kept.",
                 "Download"
             ),
             CloudAction::Delete => (
@@ -373,6 +552,9 @@ impl SteamOverachieverApp {
             ),
         };
         
+        let preview = self.cloud_preview.clone();
+        let preview_loading = matches!(preview, Some(CloudPreview::Loading));
+        
         let mut confirmed = false;
         let mut cancelled = false;
         
@@ -383,13 +565,53 @@ impl SteamOverachieverApp {
             .show(ctx, |ui| {
                 ui.add_space(8.0);
                 ui.label(message);
+                
+                // Merge preview (uploads and downloads only)
+                match &preview {
+                    Some(CloudPreview::Loading) => {
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Comparing with cloud data...");
+                        });
+                    }
+                    Some(CloudPreview::Ready { upload, download, conflict }) => {
+                        ui.add_space(8.0);
+                        if *conflict {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(255, 180, 50),
+                                format!("{} The cloud has data from another device since your last sync. It will be merged, not overwritten.", regular::WARNING),
+                            );
+                            ui.add_space(4.0);
+                        }
+                        let (changes, target) = match action {
+                            CloudAction::Download => (download, "Your local data"),
+                            _ => (upload, "The cloud"),
+                        };
+                        let lines = changes.summary_lines();
+                        if lines.is_empty() {
+                            ui.label(format!("{} is already up to date.", target));
+                        } else {
+                            ui.label(format!("{} will receive:", target));
+                            for line in lines {
+                                ui.label(format!("  • {}", line));
+                            }
+                        }
+                    }
+                    Some(CloudPreview::Failed(e)) => {
+                        ui.add_space(8.0);
+                        ui.colored_label(egui::Color32::GRAY, format!("Preview unavailable: {}", e));
+                    }
+                    None => {}
+                }
+                
                 ui.add_space(16.0);
                 
                 ui.horizontal(|ui| {
                     if ui.button("Cancel").clicked() {
                         cancelled = true;
                     }
-                    if ui.button(confirm_text).clicked() {
+                    if ui.add_enabled(!preview_loading, egui::Button::new(confirm_text)).clicked() {
                         confirmed = true;
                     }
                 });
@@ -397,9 +619,11 @@ impl SteamOverachieverApp {
         
         if cancelled {
             self.pending_cloud_action = None;
+            self.cloud_preview = None;
         }
         if confirmed {
             self.pending_cloud_action = None;
+            self.cloud_preview = None;
             match action {
                 CloudAction::Upload => self.upload_to_cloud(),
                 CloudAction::Download => self.download_from_cloud(),
@@ -408,6 +632,100 @@ impl SteamOverachieverApp {
         }
     }
     
+    /// Render the passphrase dialog for cloud backup encryption
+    fn render_encryption_dialog(&mut self, ctx: &egui::Context) {
+        use crate::cloud_sync::CloudSyncState;
+        
+        let Some(dialog) = self.encryption_dialog.as_mut() else {
+            return;
+        };
+        let busy = matches!(self.cloud_sync_state, CloudSyncState::UpdatingEncryption);
+        
+        let (title, message, confirm_text) = match dialog.action {
+            EncryptionAction::Enable => (
+                "Encrypt Cloud History",
+                "Your history will be encrypted with this passphrase before it is uploaded.\n\
+                 Your library stays visible on your public profile.\n\
+                 The passphrase cannot be recovered: without it, the encrypted history is lost.\n\
+                 History uploaded before remains readable until you remove your cloud data.",
+                "Encrypt"
+            ),
+            EncryptionAction::Unlock => (
+                "Unlock Cloud History",
+                "Enter the passphrase used to encrypt your history on another device.",
+                "Unlock"
+            ),
+            EncryptionAction::ChangePassphrase => (
+                "Change Passphrase",
+                "Your cloud history will be re-encrypted with the new passphrase.\n\
+                 Your other devices will ask for it on their next sync.",
+                "Change"
+            ),
+            EncryptionAction::Disable => (
+                "Turn Off Encryption",
+                "The encrypted history is restored on this computer and removed from the cloud.\n\
+                 Your next upload includes history unencrypted.",
+                "Turn off"
+            ),
+        };
+        let needs_passphrase = dialog.action != EncryptionAction::Disable;
+        let needs_confirm = matches!(dialog.action, EncryptionAction::Enable | EncryptionAction::ChangePassphrase);
+        
+        let mut confirmed = false;
+        let mut cancelled = false;
+        
+        egui::Window::new(format!("{} {}", regular::LOCK, title))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.add_space(8.0);
+                ui.label(message);
+                ui.add_space(8.0);
+                
+                if needs_passphrase {
+                    let label = if dialog.action == EncryptionAction::ChangePassphrase { "New passphrase:" } else { "Passphrase:" };
+                    ui.label(label);
+                    let response = ui.add_enabled(!busy, egui::TextEdit::singleline(&mut dialog.passphrase).password(true).desired_width(250.0));
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) && !needs_confirm {
+                        confirmed = true;
+                    }
+                }
+                if needs_confirm {
+                    ui.label("Repeat passphrase:");
+                    let response = ui.add_enabled(!busy, egui::TextEdit::singleline(&mut dialog.confirm).password(true).desired_width(250.0));
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        confirmed = true;
+                    }
+                }
+                
+                if let Some(error) = &dialog.error {
+                    ui.add_space(4.0);
+                    ui.colored_label(egui::Color32::RED, format!("{} {}", regular::WARNING, error));
+                }
+                
+                ui.add_space(16.0);
+                
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!busy, egui::Button::new("Cancel")).clicked() {
+                        cancelled = true;
+                    }
+                    if ui.add_enabled(!busy, egui::Button::new(confirm_text)).clicked() {
+                        confirmed = true;
+                    }
+                    if busy {
+                        ui.spinner();
+                    }
+                });
+            });
+        
+        if cancelled {
+            self.encryption_dialog = None;
+        } else if confirmed && !busy {
+            self.submit_encryption_dialog();
+        }
+    }
+    
     /// Render GDPR modal
     pub(crate) fn render_gdpr_modal(&mut self, ctx: &egui::Context) {
         // If consent is already set and dialog not explicitly opened, don't show
@@ -525,3 +843,16 @@ impl SteamOverachieverApp {
             });
     }
 }
+
+/// Format a duration in seconds as a short human readable string
+fn format_duration(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else if secs < 86400 {
+        format!("{}h", secs / 3600)
+    } else {
+        format!("{}d", secs / 86400)
+    }
+}
@@ -0,0 +1,65 @@
+//! Achievement unlock rates
+//!
+//! Community and Steam unlock rates come from the server's public endpoint,
+//! in local and remote mode alike. Each game is fetched once per session.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+use overachiever_core::{GameRarity, GdprConsent};
+
+use super::SteamOverachieverApp;
+use crate::cloud_sync::fetch_achievement_rarity;
+
+pub(crate) struct RarityCache {
+    pub loaded: HashMap<u64, GameRarity>,
+    /// Games requested this session (including failed requests, which are not retried)
+    pub requested: HashSet<u64>,
+    /// Requests still running
+    pub in_flight: usize,
+    sender: Sender<(u64, Result<GameRarity, String>)>,
+    receiver: Receiver<(u64, Result<GameRarity, String>)>,
+}
+
+impl Default for RarityCache {
+    fn default() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            loaded: HashMap::new(),
+            requested: HashSet::new(),
+            in_flight: 0,
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl SteamOverachieverApp {
+    /// Store finished unlock rate requests; called every frame
+    pub(crate) fn check_rarity(&mut self) {
+        while let Ok((appid, result)) = self.rarity.receiver.try_recv() {
+            self.rarity.in_flight = self.rarity.in_flight.saturating_sub(1);
+            match result {
+                Ok(rarity) => {
+                    self.rarity.loaded.insert(appid, rarity);
+                }
+                Err(e) => eprintln!("Failed to load unlock rates for {}: {}", appid, e),
+            }
+        }
+    }
+
+    /// Fetch unlock rates for a game in the background (once per session)
+    pub(crate) fn request_rarity(&mut self, appid: u64) {
+        // Don't contact the server for users who declined data processing
+        if self.config.gdpr_consent == GdprConsent::Declined || !self.rarity.requested.insert(appid) {
+            return;
+        }
+        self.rarity.in_flight += 1;
+        let sender = self.rarity.sender.clone();
+        let server = self.config.server_base_url().to_string();
+        thread::spawn(move || {
+            let _ = sender.send((appid, fetch_achievement_rarity(&server, appid)));
+        });
+    }
+}
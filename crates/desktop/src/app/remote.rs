@@ -0,0 +1,327 @@
+//! Remote mode
+//!
+//! Library data lives on overachiever-server and is read over the WebSocket
+//! protocol instead of the local SQLite database, like the web client does.
+//! Updates and scans run on the server with its Steam API key, so users
+//! without a key of their own can use the desktop app.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+use overachiever_core::{GdprConsent, ServerMessage, SyncState, UserProfile, AUTH_TOKEN_EXPIRED};
+
+use super::SteamOverachieverApp;
+use crate::config::DataMode;
+use crate::db::{get_all_games, get_run_history, get_achievement_history, get_log_entries, open_connection, get_last_update};
+use crate::ui::AppState;
+use crate::ws_client::{WsClient, WsState};
+
+/// Wait before reconnecting after the connection dropped
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Connection to the server in remote mode
+#[derive(Default)]
+pub(crate) struct RemoteSession {
+    pub client: Option<WsClient>,
+    /// Set once the server accepted our token
+    pub user: Option<UserProfile>,
+    /// When to reconnect after the connection dropped
+    pub reconnect_at: Option<Instant>,
+    /// The server rejected our token; wait for a new login
+    pub auth_failed: bool,
+    /// Achievement lists requested but not received yet
+    pub pending_achievements: HashSet<u64>,
+    /// The update that runs on launch has been started
+    pub launch_update_started: bool,
+}
+
+impl SteamOverachieverApp {
+    pub(crate) fn is_remote(&self) -> bool {
+        self.config.mode == DataMode::Remote
+    }
+
+    /// Client of an authenticated connection
+    fn remote_client(&self) -> Option<&WsClient> {
+        self.remote.user.as_ref()?;
+        self.remote.client.as_ref()
+    }
+
+    pub(crate) fn is_remote_connected(&self) -> bool {
+        self.remote_client().is_some()
+    }
+
+    /// Keep the server connection up and apply incoming messages; called every frame
+    pub(crate) fn check_remote(&mut self, ctx: &egui::Context) {
+        if !self.is_remote() {
+            return;
+        }
+
+        let messages = self.remote.client.as_ref().map(|c| c.poll_messages()).unwrap_or_default();
+        for msg in messages {
+            self.handle_server_message(msg);
+        }
+
+        match self.remote.client.as_ref().map(|c| c.state()) {
+            None => self.connect_remote(ctx),
+            Some(WsState::Connecting) | Some(WsState::Open) => {}
+            Some(WsState::Closed) => self.remote_disconnected("Connection closed".to_string()),
+            Some(WsState::Error(e)) => self.remote_disconnected(e),
+        }
+    }
+
+    fn connect_remote(&mut self, ctx: &egui::Context) {
+        if self.remote.auth_failed {
+            return;
+        }
+        let Some(token) = self.config.cloud_token.clone() else {
+            return;
+        };
+        if self.config.gdpr_consent == GdprConsent::Declined {
+            self.status = "Remote mode needs consent to data processing (see Privacy Settings)".to_string();
+            return;
+        }
+        if let Some(at) = self.remote.reconnect_at {
+            let now = Instant::now();
+            if now < at {
+                ctx.request_repaint_after(at - now);
+                return;
+            }
+        }
+
+        self.remote.reconnect_at = None;
+        self.status = format!("Connecting to {}...", self.config.server_base_url());
+        // Messages are queued until the connection is open
+        let client = WsClient::connect(&self.config.websocket_url(), ctx.clone());
+        client.authenticate(&token);
+        self.remote.client = Some(client);
+    }
+
+    fn remote_disconnected(&mut self, reason: String) {
+        self.remote.client = None;
+        self.remote.user = None;
+        self.remote.pending_achievements.clear();
+        self.remote.reconnect_at = Some(Instant::now() + RECONNECT_DELAY);
+
+        // Anything running on the server won't report back on this connection
+        self.state = AppState::Idle;
+        self.single_game_refreshing = None;
+        self.status = format!("Disconnected from server: {}. Reconnecting...", reason);
+    }
+
+    fn handle_server_message(&mut self, msg: ServerMessage) {
+        match msg {
+            ServerMessage::Authenticated { user } => {
+                self.status = format!("Connected as {}", user.display_name);
+                // Authenticating again with a refreshed token keeps the loaded data
+                let reauthenticated = self.remote.user.replace(user).is_some();
+                if reauthenticated {
+                    return;
+                }
+                if let Some(client) = self.remote_client() {
+                    client.fetch_games();
+                    client.fetch_history();
+                }
+                // Same as local mode: update once on launch
+                if !self.remote.launch_update_started {
+                    self.remote.launch_update_started = true;
+                    self.start_update();
+                }
+            }
+            ServerMessage::AuthError { reason } if reason == AUTH_TOKEN_EXPIRED && self.config.cloud_refresh_token.is_some() => {
+                // The connection stays open; it is authenticated again after the refresh
+                self.remote.user = None;
+                self.request_token_refresh();
+            }
+            ServerMessage::AuthError { reason } => {
+                self.remote.client = None;
+                self.remote.user = None;
+                self.remote.auth_failed = true;
+                self.state = AppState::Idle;
+                self.status = format!("Server login failed: {}. Link your Steam account again in Settings.", reason);
+                self.show_settings = true;
+            }
+            ServerMessage::Games { games } => {
+                self.games = games;
+                self.sort_games();
+                if !self.state.is_busy() {
+                    self.status = format!("Loaded {} games", self.games.len());
+                }
+            }
+            ServerMessage::Achievements { appid, achievements } => {
+                self.remote.pending_achievements.remove(&appid);
+                self.achievements_cache.insert(appid, achievements);
+            }
+            ServerMessage::History { run_history, achievement_history, log_entries } => {
+                self.run_history = run_history;
+                self.achievement_history = achievement_history;
+                self.log_entries = log_entries;
+            }
+            ServerMessage::SyncProgress { state } => self.apply_sync_state(state),
+            ServerMessage::SyncComplete { result, games } => {
+                self.games = games;
+                self.sort_games();
+                // Achievement lists may have changed; expanded rows fetch them again
+                self.achievements_cache.clear();
+                self.remote.pending_achievements.clear();
+                self.last_update_time = Some(chrono::Utc::now());
+                self.state = AppState::Idle;
+                self.status = format!(
+                    "Update complete! {} games updated, {} achievements.",
+                    result.games_updated, result.achievements_updated
+                );
+                if let Some(client) = self.remote_client() {
+                    client.fetch_history();
+                }
+            }
+            ServerMessage::SingleGameRefreshComplete { appid, game, achievements } => {
+                if let Some(g) = self.games.iter_mut().find(|g| g.appid == appid) {
+                    *g = game;
+                }
+                self.achievements_cache.insert(appid, achievements);
+                self.updated_games.insert(appid, Instant::now());
+                self.sort_games();
+                self.single_game_refreshing = None;
+                self.status = "Refresh complete!".to_string();
+            }
+            ServerMessage::Error { message } => {
+                self.state = AppState::Idle;
+                self.single_game_refreshing = None;
+                self.status = format!("Server error: {}", message);
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_sync_state(&mut self, state: SyncState) {
+        let is_scan = matches!(self.state, AppState::Scraping { .. });
+        match state {
+            SyncState::Starting => {
+                self.status = "Starting on server...".to_string();
+            }
+            SyncState::FetchingGames => {
+                if !is_scan {
+                    self.state = AppState::UpdateFetchingGames;
+                }
+                self.status = "Fetching games...".to_string();
+            }
+            SyncState::FetchingRecentlyPlayed => {
+                self.state = AppState::UpdateFetchingRecentlyPlayed;
+                self.status = "Checking recently played...".to_string();
+            }
+            SyncState::ScrapingAchievements { current, total, game_name } => {
+                self.state = if is_scan {
+                    AppState::Scraping { current, total }
+                } else {
+                    AppState::UpdateScraping { current, total }
+                };
+                self.status = format!("Scraping {}/{}: {}", current, total, game_name);
+            }
+            SyncState::GameUpdated { appid, unlocked, total } => {
+                if let Some(game) = self.games.iter_mut().find(|g| g.appid == appid) {
+                    game.achievements_unlocked = Some(unlocked);
+                    game.achievements_total = Some(total);
+                }
+                self.updated_games.insert(appid, Instant::now());
+            }
+            SyncState::Done => {
+                // SyncComplete follows with the updated games
+            }
+            SyncState::Error { message } => {
+                self.state = AppState::Idle;
+                self.status = format!("Error: {}", message);
+            }
+        }
+    }
+
+    /// Ask the server to update recently played games
+    pub(crate) fn start_remote_update(&mut self) {
+        let Some(client) = self.remote_client() else {
+            self.status = "Not connected to the server".to_string();
+            return;
+        };
+        client.sync_from_steam();
+        self.state = AppState::UpdateFetchingGames;
+        self.status = "Starting update...".to_string();
+    }
+
+    /// Ask the server to scrape achievements for all games
+    pub(crate) fn start_remote_scrape(&mut self) {
+        let Some(client) = self.remote_client() else {
+            self.status = "Not connected to the server".to_string();
+            return;
+        };
+        client.full_scan(self.force_full_scan);
+        self.state = AppState::Scraping { current: 0, total: 0 };
+        self.status = "Starting achievement scrape...".to_string();
+    }
+
+    pub(crate) fn start_remote_single_game_refresh(&mut self, appid: u64) -> bool {
+        let Some(client) = self.remote_client() else {
+            return false;
+        };
+        client.refresh_single_game(appid);
+        self.single_game_refreshing = Some(appid);
+        self.status = format!("Refreshing game {}...", appid);
+        true
+    }
+
+    /// Fetch a game's achievements from the server unless already requested
+    pub(crate) fn request_remote_achievements(&mut self, appid: u64) {
+        if self.achievements_cache.contains_key(&appid) || self.remote.pending_achievements.contains(&appid) {
+            return;
+        }
+        if let Some(client) = self.remote_client() {
+            client.fetch_achievements(appid);
+            self.remote.pending_achievements.insert(appid);
+        }
+    }
+
+    /// Switch between local and remote data, replacing the loaded library
+    pub(crate) fn set_data_mode(&mut self, mode: DataMode) {
+        if self.config.mode == mode || self.state.is_busy() {
+            return;
+        }
+        self.config.mode = mode;
+        let _ = self.config.save();
+
+        self.remote = RemoteSession::default();
+        self.single_game_refreshing = None;
+        self.achievements_cache.clear();
+        self.updated_games.clear();
+        match mode {
+            DataMode::Local => {
+                self.load_local_library();
+                self.status = "Using local data".to_string();
+            }
+            DataMode::Remote => {
+                self.clear_library();
+                // Nothing to update locally; the server runs updates
+                self.remote.launch_update_started = true;
+            }
+        }
+    }
+
+    /// Clear data that belongs to the other mode
+    pub(crate) fn clear_library(&mut self) {
+        self.games.clear();
+        self.run_history.clear();
+        self.achievement_history.clear();
+        self.log_entries.clear();
+        self.last_update_time = None;
+    }
+
+    fn load_local_library(&mut self) {
+        let Ok(conn) = open_connection() else {
+            self.clear_library();
+            return;
+        };
+        let steam_id = self.config.steam_id.as_str();
+        self.games = get_all_games(&conn, steam_id).unwrap_or_default();
+        self.run_history = get_run_history(&conn, steam_id).unwrap_or_default();
+        self.achievement_history = get_achievement_history(&conn, steam_id).unwrap_or_default();
+        self.log_entries = get_log_entries(&conn, steam_id, 30).unwrap_or_default();
+        self.last_update_time = get_last_update(&conn).unwrap_or(None);
+        self.sort_games();
+    }
+}
@@ -0,0 +1,133 @@
+//! Cloud login session upkeep
+//!
+//! Access tokens from the server are short-lived. They are refreshed shortly
+//! before they expire, or right away when the server reports an expired
+//! token, and a remote-mode connection is authenticated again with the new
+//! token without reconnecting.
+
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+use overachiever_core::{AuthTokenResponse, TOKEN_REFRESH_MARGIN_SECS};
+
+use super::SteamOverachieverApp;
+use crate::cloud_sync::{start_logout_all_devices, start_session_refresh, CloudSyncState};
+
+/// Wait before retrying a refresh that failed without the server rejecting it
+const REFRESH_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Token refresh and logout requests of the cloud login session
+#[derive(Default)]
+pub(crate) struct CloudSession {
+    pub refresh_receiver: Option<Receiver<Result<AuthTokenResponse, String>>>,
+    /// The server reported an expired token; refresh regardless of the expiry
+    pub refresh_requested: bool,
+    /// When to retry after a failed refresh
+    pub retry_at: Option<Instant>,
+    pub logout_all_receiver: Option<Receiver<Result<(), String>>>,
+}
+
+impl CloudSession {
+    pub fn in_flight(&self) -> bool {
+        self.refresh_receiver.is_some() || self.logout_all_receiver.is_some()
+    }
+}
+
+impl SteamOverachieverApp {
+    /// Refresh the access token as soon as possible
+    pub(crate) fn request_token_refresh(&mut self) {
+        self.cloud_session.refresh_requested = true;
+    }
+
+    /// Log out every session of the account, then unlink this device
+    pub(crate) fn logout_all_devices(&mut self) {
+        if let Some(token) = self.config.cloud_token.clone() {
+            self.cloud_session.logout_all_receiver = Some(start_logout_all_devices(self.config.server_base_url().to_string(), token));
+        }
+    }
+
+    /// Refresh the access token when due and apply finished requests; called every frame
+    pub(crate) fn check_cloud_session(&mut self, ctx: &egui::Context) {
+        self.check_logout_all();
+
+        if let Some(receiver) = &self.cloud_session.refresh_receiver {
+            match receiver.try_recv() {
+                Ok(result) => {
+                    self.cloud_session.refresh_receiver = None;
+                    self.token_refreshed(result);
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.cloud_session.refresh_receiver = None,
+            }
+            return;
+        }
+
+        // Sessions from before refresh tokens can't be renewed
+        let Some(refresh_token) = self.config.cloud_refresh_token.clone() else {
+            return;
+        };
+        let expires_at = self.config.cloud_token_expires_at.unwrap_or(0);
+        if !self.cloud_session.refresh_requested && !AuthTokenResponse::needs_refresh(expires_at) {
+            let due_in = expires_at - TOKEN_REFRESH_MARGIN_SECS - chrono::Utc::now().timestamp();
+            ctx.request_repaint_after(Duration::from_secs(due_in.max(1) as u64));
+            return;
+        }
+        if let Some(at) = self.cloud_session.retry_at {
+            let now = Instant::now();
+            if now < at {
+                ctx.request_repaint_after(at - now);
+                return;
+            }
+        }
+
+        self.cloud_session.retry_at = None;
+        self.cloud_session.refresh_receiver = Some(start_session_refresh(self.config.server_base_url().to_string(), refresh_token));
+    }
+
+    fn token_refreshed(&mut self, result: Result<AuthTokenResponse, String>) {
+        match result {
+            Ok(tokens) => {
+                self.cloud_session.refresh_requested = false;
+                // Keep the connection and its state, just authenticate again
+                if let Some(client) = &self.remote.client {
+                    client.authenticate(&tokens.token);
+                }
+                self.config.set_cloud_session(tokens.token, tokens.refresh_token, tokens.expires_at);
+                let _ = self.config.save();
+            }
+            Err(e) if e.contains("401") => {
+                // Session expired or was logged out from another device
+                self.cloud_session = super::CloudSession::default();
+                self.config.clear_cloud_session();
+                let _ = self.config.save();
+                self.cloud_status = None;
+                self.cloud_sync_state = CloudSyncState::NotLinked;
+                self.status = "Cloud session ended. Link your Steam account again in Settings.".to_string();
+            }
+            Err(e) => {
+                eprintln!("Failed to refresh cloud session: {}", e);
+                self.cloud_session.retry_at = Some(Instant::now() + REFRESH_RETRY_DELAY);
+            }
+        }
+    }
+
+    fn check_logout_all(&mut self) {
+        let Some(receiver) = &self.cloud_session.logout_all_receiver else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(Ok(())) => {
+                self.cloud_session.logout_all_receiver = None;
+                self.unlink_cloud();
+                self.cloud_sync_state = CloudSyncState::Success("Logged out on all devices".to_string());
+            }
+            Ok(Err(e)) => {
+                self.cloud_session.logout_all_receiver = None;
+                self.cloud_sync_state = CloudSyncState::Error(format!("Failed to log out all devices: {}", e));
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => self.cloud_session.logout_all_receiver = None,
+        }
+    }
+}
@@ -47,6 +47,36 @@ impl SteamOverachieverApp {
                     if order == SortOrder::Descending { cmp.reverse() } else { cmp }
                 });
             }
+            SortColumn::Difficulty => {
+                let averages = &self.difficulty.averages;
+                self.games.sort_by(|a, b| {
+                    // Unrated games sort last in either order
+                    match (averages.game(a.appid), averages.game(b.appid)) {
+                        (Some(a_avg), Some(b_avg)) => {
+                            let cmp = a_avg.partial_cmp(&b_avg).unwrap_or(std::cmp::Ordering::Equal);
+                            if order == SortOrder::Descending { cmp.reverse() } else { cmp }
+                        }
+                        (Some(_), None) => std::cmp::Ordering::Less,
+                        (None, Some(_)) => std::cmp::Ordering::Greater,
+                        (None, None) => std::cmp::Ordering::Equal,
+                    }
+                });
+            }
+            SortColumn::Rating => {
+                let averages = &self.difficulty.averages;
+                self.games.sort_by(|a, b| {
+                    // Unrated games sort last in either order
+                    match (averages.game_rating(a.appid), averages.game_rating(b.appid)) {
+                        (Some((a_avg, _)), Some((b_avg, _))) => {
+                            let cmp = a_avg.partial_cmp(&b_avg).unwrap_or(std::cmp::Ordering::Equal);
+                            if order == SortOrder::Descending { cmp.reverse() } else { cmp }
+                        }
+                        (Some(_), None) => std::cmp::Ordering::Less,
+                        (None, Some(_)) => std::cmp::Ordering::Greater,
+                        (None, None) => std::cmp::Ordering::Equal,
+                    }
+                });
+            }
         }
     }
     
@@ -74,6 +104,11 @@ impl SteamOverachieverApp {
             return;
         }
         
+        if self.is_remote() {
+            self.start_remote_scrape();
+            return;
+        }
+        
         self.state = AppState::Scraping { current: 0, total: 0 };
         self.status = "Starting achievement scrape...".to_string();
         
@@ -93,6 +128,11 @@ impl SteamOverachieverApp {
             return;
         }
         
+        if self.is_remote() {
+            self.start_remote_update();
+            return;
+        }
+        
         self.state = AppState::UpdateFetchingGames;
         self.status = "Starting update...".to_string();
         
@@ -111,6 +151,9 @@ impl SteamOverachieverApp {
         if self.state.is_busy() || self.single_game_refreshing.is_some() {
             return false;
         }
+        if self.is_remote() {
+            return self.start_remote_single_game_refresh(appid);
+        }
         
         self.single_game_refreshing = Some(appid);
         self.state = AppState::Idle; // Keep idle state but track the refresh separately
@@ -228,6 +271,7 @@ impl SteamOverachieverApp {
                             
                             self.status = "Full scan complete!".to_string();
                             self.state = AppState::Idle;
+                            self.schedule_auto_sync();
                             return;
                         }
                         ScrapeProgress::Error(e) => {
@@ -284,6 +328,7 @@ impl SteamOverachieverApp {
                             
                             self.status = format!("Update complete! {} games updated.", updated_count);
                             self.state = AppState::Idle;
+                            self.schedule_auto_sync();
                             return;
                         }
                         UpdateProgress::Error(e) => {
@@ -447,7 +492,7 @@ impl SteamOverachieverApp {
         
         self.cloud_sync_state = CloudSyncState::Linking;
         
-        match start_steam_login() {
+        match start_steam_login(self.config.server_base_url()) {
             Ok(receiver) => {
                 self.auth_receiver = Some(receiver);
             }
@@ -464,9 +509,16 @@ impl SteamOverachieverApp {
         if let Some(ref receiver) = self.auth_receiver {
             match receiver.try_recv() {
                 Ok(Ok(result)) => {
-                    // Save token to config
-                    self.config.cloud_token = Some(result.token);
+                    // Save tokens to config
+                    self.config.set_cloud_session(result.token, result.refresh_token, result.expires_at);
                     let _ = self.config.save();
+                    self.cloud_session = super::CloudSession::default();
+                    // Reconnect with the new token in remote mode
+                    self.remote = super::RemoteSession::default();
+                    self.friends = super::FriendsFeed::default();
+                    self.leaderboards = super::Leaderboards::default();
+                    self.comments.cache.clear();
+                    self.comments.reviews.clear();
                     self.cloud_sync_state = CloudSyncState::Success("Linked to cloud successfully!".to_string());
                     self.auth_receiver = None;
                 }
@@ -485,20 +537,36 @@ impl SteamOverachieverApp {
         }
     }
     
-    /// Unlink from cloud (remove saved token)
+    /// Unlink from cloud (log out the session and remove saved tokens)
     pub(crate) fn unlink_cloud(&mut self) {
         use crate::cloud_sync::CloudSyncState;
         
-        self.config.cloud_token = None;
+        if let Some(refresh_token) = &self.config.cloud_refresh_token {
+            crate::cloud_sync::revoke_session(self.config.server_base_url(), refresh_token);
+        }
+        self.config.clear_cloud_session();
         let _ = self.config.save();
+        self.cloud_session = super::CloudSession::default();
         self.cloud_status = None;
         self.cloud_sync_state = CloudSyncState::NotLinked;
+        
+        // Remote data is no longer accessible
+        self.remote = super::RemoteSession::default();
+        self.friends = super::FriendsFeed::default();
+        self.leaderboards = super::Leaderboards::default();
+        self.comments.cache.clear();
+        self.comments.reviews.clear();
+        if self.is_remote() {
+            self.clear_library();
+            self.state = AppState::Idle;
+            self.single_game_refreshing = None;
+        }
     }
     
     /// Check for completed cloud operation results
     pub(crate) fn check_cloud_operation(&mut self) {
-        use crate::cloud_sync::{CloudSyncState, CloudOpResult};
-        use crate::db::import_cloud_sync_data;
+        use crate::cloud_sync::{CloudSyncState, CloudOpResult, CloudPreview};
+        use crate::db::{import_cloud_sync_data, get_local_sync_data, mark_delta_pushed, mark_all_cloud_dirty};
         
         if let Some(ref receiver) = self.cloud_op_receiver {
             match receiver.try_recv() {
@@ -509,16 +577,29 @@ impl SteamOverachieverApp {
                             self.cloud_sync_state = CloudSyncState::Uploading(progress);
                             return;
                         }
-                        CloudOpResult::UploadSuccess => {
-                            self.cloud_sync_state = CloudSyncState::Success("Data uploaded successfully!".to_string());
+                        CloudOpResult::UploadSuccess(pushed, merged) => {
+                            if let Err(e) = open_connection().and_then(|conn| mark_delta_pushed(&conn, &pushed)) {
+                                eprintln!("Failed to mark pushed rows as synced: {}", e);
+                            }
+                            let summary = merged.summary_lines();
+                            self.cloud_sync_state = CloudSyncState::Success(if summary.is_empty() {
+                                "Cloud is already up to date".to_string()
+                            } else {
+                                format!("Uploaded: {}", summary.join(", "))
+                            });
+                            self.mark_cloud_synced();
+                            self.auto_sync_finished(Ok(()));
                             // Start async status refresh
                             if let Some(token) = &self.config.cloud_token {
-                                self.cloud_op_receiver = Some(crate::cloud_sync::start_status_check(token.clone()));
+                                self.cloud_op_receiver = Some(crate::cloud_sync::start_status_check(
+                                    self.config.server_base_url().to_string(),
+                                    token.clone(),
+                                ));
                                 return; // Don't clear receiver yet
                             }
                         }
-                        CloudOpResult::DownloadSuccess(data) => {
-                            // Import into local database
+                        CloudOpResult::DownloadSuccess(delta) => {
+                            // Merge into local database
                             let conn = match open_connection() {
                                 Ok(c) => c,
                                 Err(e) => {
@@ -529,36 +610,65 @@ impl SteamOverachieverApp {
                             };
                             
                             // Update steam_id from downloaded data if different
-                            let steam_id = data.steam_id.clone();
+                            let steam_id = delta.steam_id.clone();
                             if self.config.steam_id != steam_id {
                                 self.config.steam_id = steam_id.clone();
                                 let _ = self.config.save();
                             }
                             
-                            let games_count = data.games.len();
-                            let achievements_count = data.achievements.len();
+                            let cursor = delta.cursor;
+                            let changes = match get_local_sync_data(&conn, &steam_id) {
+                                Ok(local) => local.merge_changes(&delta.into_data()),
+                                Err(e) => {
+                                    self.cloud_sync_state = CloudSyncState::Error(format!("Failed to read local data: {}", e));
+                                    self.cloud_op_receiver = None;
+                                    return;
+                                }
+                            };
                             
-                            if let Err(e) = import_cloud_sync_data(&conn, &data) {
+                            if let Err(e) = import_cloud_sync_data(&conn, &steam_id, &changes) {
                                 self.cloud_sync_state = CloudSyncState::Error(format!("Failed to import data: {}", e));
                                 self.cloud_op_receiver = None;
                                 return;
                             }
                             
+                            // Only advance the cursor once the changes are stored locally
+                            if cursor.is_some() {
+                                self.config.cloud_sync_cursor = cursor;
+                                let _ = self.config.save();
+                            }
+                            
                             // Reload data from database
                             self.games = crate::db::get_all_games(&conn, &steam_id).unwrap_or_default();
                             self.run_history = get_run_history(&conn, &steam_id).unwrap_or_default();
                             self.achievement_history = get_achievement_history(&conn, &steam_id).unwrap_or_default();
                             self.log_entries = get_log_entries(&conn, &steam_id, 30).unwrap_or_default();
+                            self.achievements_cache.clear();
                             
                             self.sort_games();
+                            self.mark_cloud_synced();
                             
-                            self.cloud_sync_state = CloudSyncState::Success(format!(
-                                "Downloaded {} games, {} achievements!", 
-                                games_count, 
-                                achievements_count
-                            ));
+                            let summary = changes.preview.summary_lines();
+                            let mut message = if summary.is_empty() {
+                                "Already up to date with the cloud".to_string()
+                            } else {
+                                format!("Merged from cloud: {}", summary.join(", "))
+                            };
+                            let history_locked = self.config.cloud_encryption.is_none()
+                                && self.cloud_status.as_ref().is_some_and(|s| s.encrypted);
+                            if history_locked {
+                                message.push_str(". History is in an encrypted backup; enter its passphrase to restore it");
+                            }
+                            self.cloud_sync_state = CloudSyncState::Success(message);
                         }
                         CloudOpResult::DeleteSuccess => {
+                            // The cloud is empty again: the next upload must push everything
+                            if let Err(e) = open_connection().and_then(|conn| mark_all_cloud_dirty(&conn, &self.config.steam_id)) {
+                                eprintln!("Failed to reset cloud sync state: {}", e);
+                            }
+                            self.config.cloud_sync_cursor = None;
+                            self.config.last_cloud_sync = None;
+                            let _ = self.config.save();
                             self.cloud_status = None;
                             self.cloud_sync_state = CloudSyncState::Success("Cloud data deleted successfully!".to_string());
                         }
@@ -569,15 +679,84 @@ impl SteamOverachieverApp {
                                 self.cloud_sync_state = CloudSyncState::Idle;
                             }
                         }
+                        CloudOpResult::EncryptionKeySet(key) => {
+                            let unlocked = self.cloud_status.as_ref().is_some_and(|s| s.encrypted);
+                            self.config.cloud_encryption = Some(key);
+                            let _ = self.config.save();
+                            self.encryption_dialog = None;
+                            self.cloud_sync_state = CloudSyncState::Success(if unlocked {
+                                "Encrypted backup unlocked. Download to restore its history.".to_string()
+                            } else {
+                                "Encryption enabled. Your next upload moves the cloud history into the encrypted backup.".to_string()
+                            });
+                        }
+                        CloudOpResult::PassphraseChanged(key) => {
+                            self.config.cloud_encryption = Some(key);
+                            let _ = self.config.save();
+                            self.encryption_dialog = None;
+                            self.cloud_sync_state = CloudSyncState::Success(
+                                "Passphrase changed. Your other devices will ask for the new one.".to_string()
+                            );
+                        }
+                        CloudOpResult::EncryptionDisabled(backup) => {
+                            // Keep the history that only existed in the backup
+                            let steam_id = self.config.steam_id.clone();
+                            let restored = open_connection().and_then(|conn| {
+                                if let Some(backup) = &backup {
+                                    let changes = get_local_sync_data(&conn, &steam_id)?.merge_changes(backup);
+                                    import_cloud_sync_data(&conn, &steam_id, &changes)?;
+                                    self.run_history = get_run_history(&conn, &steam_id).unwrap_or_default();
+                                    self.achievement_history = get_achievement_history(&conn, &steam_id).unwrap_or_default();
+                                }
+                                // History now has to be uploaded in plaintext
+                                mark_all_cloud_dirty(&conn, &steam_id)
+                            });
+                            self.config.cloud_encryption = None;
+                            let _ = self.config.save();
+                            self.encryption_dialog = None;
+                            self.cloud_sync_state = match restored {
+                                Ok(()) => CloudSyncState::Success(
+                                    "Encryption turned off. Your next upload includes history unencrypted.".to_string()
+                                ),
+                                Err(e) => CloudSyncState::Error(format!("Encryption turned off, but restoring the backup failed: {}", e)),
+                            };
+                        }
+                        CloudOpResult::PreviewFetched(status, remote, upload) => {
+                            let local = open_connection()
+                                .and_then(|conn| get_local_sync_data(&conn, &self.config.steam_id));
+                            self.cloud_preview = Some(match local {
+                                Ok(local) => CloudPreview::Ready {
+                                    upload,
+                                    download: local.merge_changes(&remote.into_data()).preview,
+                                    conflict: status.has_remote_changes(self.config.last_cloud_sync),
+                                },
+                                Err(e) => CloudPreview::Failed(format!("Failed to read local data: {}", e)),
+                            });
+                            self.cloud_status = Some(status);
+                            if matches!(self.cloud_sync_state, CloudSyncState::Checking) {
+                                self.cloud_sync_state = CloudSyncState::Idle;
+                            }
+                        }
                     }
                     self.cloud_op_receiver = None;
                 }
                 Ok(Err(e)) => {
+                    self.auto_sync_finished(Err(e.clone()));
                     // If 401, token expired - need to re-link
                     if e.contains("401") {
-                        self.config.cloud_token = None;
+                        self.config.clear_cloud_session();
                         let _ = self.config.save();
                         self.cloud_sync_state = CloudSyncState::NotLinked;
+                        self.pending_cloud_action = None;
+                        self.cloud_preview = None;
+                    } else if let Some(dialog) = self.encryption_dialog.as_mut() {
+                        // Encryption failures (e.g. a wrong passphrase) are shown in the dialog
+                        dialog.error = Some(e);
+                        self.cloud_sync_state = CloudSyncState::Idle;
+                    } else if matches!(self.cloud_preview, Some(CloudPreview::Loading)) {
+                        // Preview failures are shown in the confirmation dialog
+                        self.cloud_preview = Some(CloudPreview::Failed(e));
+                        self.cloud_sync_state = CloudSyncState::Idle;
                     } else {
                         self.cloud_sync_state = CloudSyncState::Error(e);
                     }
@@ -587,6 +766,7 @@ impl SteamOverachieverApp {
                     // Still waiting
                 }
                 Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.auto_sync_finished(Err("Operation failed unexpectedly".to_string()));
                     self.cloud_sync_state = CloudSyncState::Error("Operation failed unexpectedly".to_string());
                     self.cloud_op_receiver = None;
                 }
@@ -594,6 +774,115 @@ impl SteamOverachieverApp {
         }
     }
     
+    /// Ask for confirmation of a cloud action; uploads and downloads also
+    /// fetch the current cloud data so the dialog can preview the merge
+    pub(crate) fn request_cloud_action(&mut self, action: crate::app::CloudAction) {
+        use crate::app::CloudAction;
+        use crate::cloud_sync::{CloudSyncState, CloudPreview};
+        
+        self.cloud_preview = None;
+        if matches!(action, CloudAction::Upload | CloudAction::Download) {
+            if let Some(token) = &self.config.cloud_token {
+                let local = open_connection()
+                    .and_then(|conn| crate::db::get_local_sync_delta(&conn, &self.config.steam_id))
+                    .map_err(|e| e.to_string())
+                    .and_then(|delta| Ok((delta, self.encrypted_backup()?)));
+                match local {
+                    Ok((local, encrypted)) => {
+                        self.cloud_preview = Some(CloudPreview::Loading);
+                        self.cloud_sync_state = CloudSyncState::Checking;
+                        self.cloud_op_receiver = Some(crate::cloud_sync::start_preview(
+                            self.config.server_base_url().to_string(),
+                            token.clone(),
+                            self.config.cloud_sync_cursor,
+                            local,
+                            encrypted,
+                        ));
+                    }
+                    Err(e) => {
+                        self.cloud_preview = Some(CloudPreview::Failed(format!("Failed to read local data: {}", e)));
+                    }
+                }
+            }
+        }
+        self.pending_cloud_action = Some(action);
+    }
+    
+    /// Key and full local data for an encrypted upload, if encryption is enabled
+    fn encrypted_backup(&self) -> Result<Option<crate::cloud_sync::EncryptedBackup>, String> {
+        let key = match &self.config.cloud_encryption {
+            Some(key) => key.clone(),
+            None => return Ok(None),
+        };
+        let local = open_connection()
+            .and_then(|conn| crate::db::get_local_sync_data(&conn, &self.config.steam_id))
+            .map_err(|e| format!("Failed to read local data: {}", e))?;
+        Ok(Some(crate::cloud_sync::EncryptedBackup { key, local }))
+    }
+    
+    /// Validate the passphrase dialog and start the requested encryption change
+    pub(crate) fn submit_encryption_dialog(&mut self) {
+        use crate::app::EncryptionAction;
+        use crate::cloud_crypto::MIN_PASSPHRASE_LENGTH;
+        use crate::cloud_sync::{CloudSyncState, start_enable_encryption, start_change_passphrase, start_disable_encryption};
+        
+        let (token, dialog) = match (&self.config.cloud_token, &self.encryption_dialog) {
+            (Some(token), Some(dialog)) => (token.clone(), dialog.clone()),
+            _ => return,
+        };
+        
+        let new_passphrase = matches!(dialog.action, EncryptionAction::Enable | EncryptionAction::ChangePassphrase);
+        let error = if new_passphrase && dialog.passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+            Some(format!("The passphrase must be at least {} characters", MIN_PASSPHRASE_LENGTH))
+        } else if new_passphrase && dialog.passphrase != dialog.confirm {
+            Some("The passphrases do not match".to_string())
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            if let Some(dialog) = self.encryption_dialog.as_mut() {
+                dialog.error = Some(error);
+            }
+            return;
+        }
+        
+        let server = self.config.server_base_url().to_string();
+        let receiver = match dialog.action {
+            EncryptionAction::Enable | EncryptionAction::Unlock => {
+                start_enable_encryption(server, token, dialog.passphrase)
+            }
+            EncryptionAction::ChangePassphrase => match self.encrypted_backup() {
+                Ok(Some(current)) => start_change_passphrase(server, token, current, dialog.passphrase),
+                Ok(None) => return,
+                Err(e) => {
+                    if let Some(dialog) = self.encryption_dialog.as_mut() {
+                        dialog.error = Some(e);
+                    }
+                    return;
+                }
+            },
+            EncryptionAction::Disable => match &self.config.cloud_encryption {
+                Some(key) => start_disable_encryption(server, token, key.clone()),
+                None => return,
+            },
+        };
+        
+        if let Some(dialog) = self.encryption_dialog.as_mut() {
+            dialog.error = None;
+        }
+        self.cloud_sync_state = CloudSyncState::UpdatingEncryption;
+        self.cloud_op_receiver = Some(receiver);
+    }
+    
+    /// Remember the latest local run as synced, for conflict detection
+    fn mark_cloud_synced(&mut self) {
+        let latest = self.run_history.iter().map(|r| r.run_at).max();
+        if latest > self.config.last_cloud_sync {
+            self.config.last_cloud_sync = latest;
+            let _ = self.config.save();
+        }
+    }
+    
     #[allow(dead_code)]
     pub(crate) fn check_cloud_status(&mut self) {
         use crate::cloud_sync::CloudSyncState;
@@ -607,13 +896,12 @@ impl SteamOverachieverApp {
         };
         
         self.cloud_sync_state = CloudSyncState::Checking;
-        self.cloud_op_receiver = Some(crate::cloud_sync::start_status_check(token));
+        self.cloud_op_receiver = Some(crate::cloud_sync::start_status_check(self.config.server_base_url().to_string(), token));
     }
     
     pub(crate) fn upload_to_cloud(&mut self) {
         use crate::cloud_sync::CloudSyncState;
-        use crate::db::get_all_achievements_for_export;
-        use overachiever_core::CloudSyncData;
+        use crate::db::get_local_sync_delta;
         
         let token = match &self.config.cloud_token {
             Some(t) => t.clone(),
@@ -623,38 +911,26 @@ impl SteamOverachieverApp {
             }
         };
         
-        let steam_id = self.config.steam_id.clone();
-        
         self.cloud_sync_state = CloudSyncState::Uploading(crate::cloud_sync::UploadProgress::default());
         
-        // Gather data from local database (this is fast, so we do it synchronously)
-        let conn = match open_connection() {
-            Ok(c) => c,
+        // Gather rows changed since the last push (this is fast, so we do it synchronously)
+        let delta = match open_connection().and_then(|conn| get_local_sync_delta(&conn, &self.config.steam_id)) {
+            Ok(d) => d,
             Err(e) => {
-                self.cloud_sync_state = CloudSyncState::Error(format!("Failed to open database: {}", e));
+                self.cloud_sync_state = CloudSyncState::Error(format!("Failed to read local data: {}", e));
                 return;
             }
         };
-        
-        let achievements = match get_all_achievements_for_export(&conn, &steam_id) {
-            Ok(a) => a,
+        let encrypted = match self.encrypted_backup() {
+            Ok(e) => e,
             Err(e) => {
-                self.cloud_sync_state = CloudSyncState::Error(format!("Failed to get achievements: {}", e));
+                self.cloud_sync_state = CloudSyncState::Error(e);
                 return;
             }
         };
         
-        let data = CloudSyncData {
-            steam_id: steam_id.clone(),
-            games: self.games.clone(),
-            achievements,
-            run_history: self.run_history.clone(),
-            achievement_history: self.achievement_history.clone(),
-            exported_at: chrono::Utc::now(),
-        };
-        
         // Start async upload
-        self.cloud_op_receiver = Some(crate::cloud_sync::start_upload(token, data));
+        self.cloud_op_receiver = Some(crate::cloud_sync::start_upload(self.config.server_base_url().to_string(), token, delta, encrypted));
     }
     
     pub(crate) fn download_from_cloud(&mut self) {
@@ -670,8 +946,13 @@ impl SteamOverachieverApp {
         
         self.cloud_sync_state = CloudSyncState::Downloading;
         
-        // Start async download
-        self.cloud_op_receiver = Some(crate::cloud_sync::start_download(token));
+        // Start async download of changes since our last pull
+        self.cloud_op_receiver = Some(crate::cloud_sync::start_download(
+            self.config.server_base_url().to_string(),
+            token,
+            self.config.cloud_sync_cursor,
+            self.config.cloud_encryption.clone(),
+        ));
     }
     
     pub(crate) fn delete_from_cloud(&mut self) {
@@ -688,6 +969,6 @@ impl SteamOverachieverApp {
         self.cloud_sync_state = CloudSyncState::Deleting;
         
         // Start async delete
-        self.cloud_op_receiver = Some(crate::cloud_sync::start_delete(token));
+        self.cloud_op_receiver = Some(crate::cloud_sync::start_delete(self.config.server_base_url().to_string(), token));
     }
 }
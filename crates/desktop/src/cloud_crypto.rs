@@ -0,0 +1,151 @@
+//! Client-side encryption of cloud backups
+//!
+//! The key is derived from a passphrase with Argon2id and never leaves this
+//! machine. Backups are compressed with zstd and sealed with XChaCha20-Poly1305.
+//! The Argon2 output is split in two: the encryption key, and a key check that
+//! is stored next to the backup so a wrong or changed passphrase is recognised
+//! before decrypting.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use overachiever_core::{CloudSyncData, EncryptedSyncBlob, ENCRYPTED_SYNC_VERSION};
+use serde::{Deserialize, Serialize};
+
+/// Argon2id parameters; changing them changes every derived key
+const ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+const ARGON2_ITERATIONS: u32 = 2;
+const ARGON2_PARALLELISM: u32 = 1;
+
+const SALT_LENGTH: usize = 16;
+const KEY_LENGTH: usize = 32;
+const KEY_CHECK_LENGTH: usize = 16;
+
+/// Shortest passphrase accepted when enabling encryption
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+/// A key derived from the user's passphrase, stored in config.toml (readable
+/// only by the user on Unix). The passphrase itself is never stored.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct CloudEncryptionKey {
+    /// Salt the key was derived with (base64)
+    pub salt: String,
+    /// Identifies this key to the server and other devices (base64)
+    pub key_check: String,
+    /// The encryption key (base64)
+    key: String,
+}
+
+impl std::fmt::Debug for CloudEncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CloudEncryptionKey")
+            .field("key_check", &self.key_check)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CryptoError {
+    /// The backup was encrypted with a different passphrase
+    WrongKey,
+    UnsupportedVersion(u32),
+    /// The backup could not be decrypted or parsed
+    Corrupted(String),
+    Failed(String),
+}
+
+impl std::fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CryptoError::WrongKey => write!(
+                f,
+                "The cloud backup is encrypted with a different passphrase. If it was changed on another device, enter the new passphrase in Settings."
+            ),
+            CryptoError::UnsupportedVersion(v) => write!(
+                f,
+                "The cloud backup uses encryption format {}, this version of Overachiever supports {}. Please update.",
+                v, ENCRYPTED_SYNC_VERSION
+            ),
+            CryptoError::Corrupted(e) => write!(f, "The cloud backup could not be decrypted: {}", e),
+            CryptoError::Failed(e) => write!(f, "Encryption failed: {}", e),
+        }
+    }
+}
+
+impl CloudEncryptionKey {
+    /// Derive a key from a passphrase. Pass the salt of an existing backup to
+    /// unlock it, or None to start a new key with a random salt.
+    pub fn derive(passphrase: &str, salt: Option<&str>) -> Result<Self, CryptoError> {
+        let salt = match salt {
+            Some(salt) => BASE64.decode(salt).map_err(|e| CryptoError::Corrupted(format!("Invalid salt: {}", e)))?,
+            None => {
+                let mut salt = vec![0u8; SALT_LENGTH];
+                OsRng.fill_bytes(&mut salt);
+                salt
+            }
+        };
+
+        let params = Params::new(ARGON2_MEMORY_KIB, ARGON2_ITERATIONS, ARGON2_PARALLELISM, Some(KEY_LENGTH + KEY_CHECK_LENGTH))
+            .map_err(|e| CryptoError::Failed(e.to_string()))?;
+        let mut output = [0u8; KEY_LENGTH + KEY_CHECK_LENGTH];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut output)
+            .map_err(|e| CryptoError::Failed(e.to_string()))?;
+
+        Ok(Self {
+            salt: BASE64.encode(&salt),
+            key_check: BASE64.encode(&output[KEY_LENGTH..]),
+            key: BASE64.encode(&output[..KEY_LENGTH]),
+        })
+    }
+
+    fn cipher(&self) -> Result<XChaCha20Poly1305, CryptoError> {
+        let key = BASE64.decode(&self.key).map_err(|e| CryptoError::Failed(format!("Invalid stored key: {}", e)))?;
+        XChaCha20Poly1305::new_from_slice(&key).map_err(|e| CryptoError::Failed(e.to_string()))
+    }
+
+    /// Encrypt a full backup of the user's data
+    pub fn encrypt(&self, data: &CloudSyncData) -> Result<EncryptedSyncBlob, CryptoError> {
+        let json = serde_json::to_vec(data).map_err(|e| CryptoError::Failed(e.to_string()))?;
+        let compressed = zstd::encode_all(json.as_slice(), 0).map_err(|e| CryptoError::Failed(e.to_string()))?;
+
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self.cipher()?
+            .encrypt(&nonce, compressed.as_slice())
+            .map_err(|e| CryptoError::Failed(e.to_string()))?;
+
+        Ok(EncryptedSyncBlob {
+            version: ENCRYPTED_SYNC_VERSION,
+            salt: self.salt.clone(),
+            key_check: self.key_check.clone(),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+            last_sync: data.last_sync(),
+        })
+    }
+
+    /// Decrypt a backup, failing with [`CryptoError::WrongKey`] if it was
+    /// encrypted with another passphrase
+    pub fn decrypt(&self, blob: &EncryptedSyncBlob) -> Result<CloudSyncData, CryptoError> {
+        if blob.version != ENCRYPTED_SYNC_VERSION {
+            return Err(CryptoError::UnsupportedVersion(blob.version));
+        }
+        if blob.key_check != self.key_check {
+            return Err(CryptoError::WrongKey);
+        }
+
+        let nonce = BASE64.decode(&blob.nonce).map_err(|e| CryptoError::Corrupted(e.to_string()))?;
+        if nonce.len() != 24 {
+            return Err(CryptoError::Corrupted("Invalid nonce".to_string()));
+        }
+        let ciphertext = BASE64.decode(&blob.ciphertext).map_err(|e| CryptoError::Corrupted(e.to_string()))?;
+        let compressed = self.cipher()?
+            .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| CryptoError::Corrupted("Authentication failed".to_string()))?;
+        let json = zstd::decode_all(compressed.as_slice()).map_err(|e| CryptoError::Corrupted(e.to_string()))?;
+        serde_json::from_slice(&json).map_err(|e| CryptoError::Corrupted(e.to_string()))
+    }
+}
@@ -3,18 +3,39 @@
 //! Uses Steam OpenID for authentication:
 //! 1. User clicks "Link to Cloud" 
 //! 2. Browser opens Steam login
-//! 3. Steam redirects to localhost callback
-//! 4. Desktop captures JWT, saves to config
-//! 5. All sync operations use JWT
+//! 3. Steam redirects to localhost callback with a one-time code
+//! 4. Desktop exchanges the code for a JWT and refresh token, saves to config
+//! 5. All sync operations use JWT, which is refreshed before it expires
+//!
+//! Uploads push only rows changed locally since the last push, and downloads
+//! pull only rows changed in the cloud since the last pulled cursor. Uploads are
+//! compressed and sent in resumable chunks. With encryption enabled, history is
+//! only uploaded inside a passphrase-encrypted backup (see `cloud_crypto`).
 
-use overachiever_core::{CloudSyncData, CloudSyncStatus};
+use overachiever_core::{
+    CloudSyncDelta, CloudSyncStatus, SyncCursor, SyncMergePreview, SyncPushResponse, SYNC_DELTA_VERSION,
+    SyncEncoding, UploadSessionRequest, UploadSessionInfo, UploadCompleteRequest, CloudSyncData, EncryptedSyncBlob,
+    UserProfile, FeedEntry, Leaderboard, LeaderboardMetric, LeaderboardScope, GameRarity, AchievementDifficulty, AchievementComment, AchievementTip, CommunityGameRating, GameRatingAverage,
+    AchievementFlags, AchievementFlagVotes, AuthCodeExchangeRequest, AuthTokenResponse, RefreshTokenRequest,
+};
+use overachiever_core::api::{
+    upload_chunk_path, AchievementCommentRequest, AchievementFlagsRequest, AchievementQuery, AchievementRatingRequest,
+    ApiError, AppidsRequest, CompleteUploadSession, DeleteAchievementComment, DeleteEncryptedBackup, DeleteSyncData,
+    EditAchievementComment, EditCommentRequest, Endpoint, FollowUser, GetAchievementAverages, GetAchievementComments,
+    GetAchievementFlags, GetAchievementTips, GetEncryptedBackup, GetFeed, GetFollowing, GetGameRarity,
+    GetGameRatingAverages, GetGameRatings, GetLeaderboard, GetSyncStatus, GetUploadSession, GetUserAchievementRatings,
+    LeaderboardOptIn, LeaderboardQuery, LogoutAllSessions, Method, PullDeltaQuery, PullSyncDelta, RefreshSession,
+    RevokeSession, StoreEncryptedBackup, StoreEncryptedQuery, SubmitRatingRequest, SubmitTipRequest, SuccessResponse,
+    UnfollowUser, VoteAchievementTip, VoteTipRequest,
+};
+use serde::de::DeserializeOwned;
+use crate::cloud_crypto::{CloudEncryptionKey, CryptoError};
 use std::io::{BufRead, BufReader, Write};
 use std::net::TcpListener;
-use std::sync::mpsc;
+use std::sync::{mpsc, Mutex};
 use std::thread;
 use std::time::Duration;
 
-const DEFAULT_SERVER_URL: &str = "https://overachiever.space";
 const CALLBACK_PORT: u16 = 23847; // Random high port for OAuth callback
 
 #[derive(Debug, Clone, PartialEq)]
@@ -27,6 +48,7 @@ pub enum CloudSyncState {
     Uploading(UploadProgress),
     Downloading,
     Deleting,
+    UpdatingEncryption,
     Success(String),
     Error(String),
 }
@@ -34,10 +56,16 @@ pub enum CloudSyncState {
 /// Progress information for uploads
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct UploadProgress {
-    /// Bytes sent so far
+    /// Compressed bytes acknowledged by the server so far
     pub bytes_sent: usize,
-    /// Total bytes to send
+    /// Total compressed bytes to send
     pub total_bytes: usize,
+    /// Size of the payload before compression
+    pub uncompressed_bytes: usize,
+    pub chunks_sent: usize,
+    pub chunk_count: usize,
+    /// Compression negotiated with the server
+    pub encoding: &'static str,
 }
 
 /// Result from the Steam login callback
@@ -46,26 +74,61 @@ pub struct AuthResult {
     pub token: String,
     #[allow(dead_code)]
     pub steam_id: String,
+    pub refresh_token: String,
+    /// When `token` expires (Unix timestamp)
+    pub expires_at: i64,
 }
 
 /// Result from async cloud operations
 #[derive(Debug, Clone)]
 pub enum CloudOpResult {
-    UploadSuccess,
+    /// The pushed delta and what it changed on the server
+    UploadSuccess(CloudSyncDelta, SyncMergePreview),
     UploadProgress(UploadProgress),
-    DownloadSuccess(CloudSyncData),
+    DownloadSuccess(CloudSyncDelta),
     DeleteSuccess,
     StatusChecked(CloudSyncStatus),
+    /// Cloud status, cloud changes since our cursor, and the dry-run upload result
+    PreviewFetched(CloudSyncStatus, CloudSyncDelta, SyncMergePreview),
+    /// Encryption enabled, or unlocked with the passphrase of an existing backup
+    EncryptionKeySet(CloudEncryptionKey),
+    /// Backup re-encrypted with a new passphrase
+    PassphraseChanged(CloudEncryptionKey),
+    /// Encrypted backup removed; carries its contents so history is not lost
+    EncryptionDisabled(Option<CloudSyncData>),
+}
+
+/// What an upload needs when backups are encrypted
+pub struct EncryptedBackup {
+    pub key: CloudEncryptionKey,
+    /// Full local data; the backup always contains everything
+    pub local: CloudSyncData,
+}
+
+/// Preview of what an upload or download would change, shown before confirming
+#[derive(Debug, Clone, PartialEq)]
+pub enum CloudPreview {
+    Loading,
+    Ready {
+        /// Changes the cloud would receive on upload
+        upload: SyncMergePreview,
+        /// Changes the local database would receive on download
+        download: SyncMergePreview,
+        /// Another device has uploaded since this one last synced
+        conflict: bool,
+    },
+    Failed(String),
 }
 
 /// Start the Steam OpenID login flow
 /// Returns a channel that will receive the auth result
-pub fn start_steam_login() -> Result<mpsc::Receiver<Result<AuthResult, String>>, String> {
+pub fn start_steam_login(server: &str) -> Result<mpsc::Receiver<Result<AuthResult, String>>, String> {
     let (tx, rx) = mpsc::channel();
     
     // Start local callback server in background thread
+    let callback_server = server.to_string();
     thread::spawn(move || {
-        match run_callback_server() {
+        match run_callback_server(&callback_server) {
             Ok(result) => { let _ = tx.send(Ok(result)); }
             Err(e) => { let _ = tx.send(Err(e)); }
         }
@@ -78,7 +141,7 @@ pub fn start_steam_login() -> Result<mpsc::Receiver<Result<AuthResult, String>>,
     let callback_url = format!("http://localhost:{}/callback", CALLBACK_PORT);
     let login_url = format!(
         "{}/auth/steam?redirect_uri={}",
-        DEFAULT_SERVER_URL,
+        server,
         urlencoding::encode(&callback_url)
     );
     
@@ -90,7 +153,7 @@ pub fn start_steam_login() -> Result<mpsc::Receiver<Result<AuthResult, String>>,
 }
 
 /// Run a temporary local HTTP server to capture the OAuth callback
-fn run_callback_server() -> Result<AuthResult, String> {
+fn run_callback_server(server: &str) -> Result<AuthResult, String> {
     let listener = TcpListener::bind(format!("127.0.0.1:{}", CALLBACK_PORT))
         .map_err(|e| format!("Failed to start callback server: {}", e))?;
     
@@ -112,9 +175,10 @@ fn run_callback_server() -> Result<AuthResult, String> {
                 let mut request_line = String::new();
                 reader.read_line(&mut request_line).ok();
                 
-                // Parse the GET request to extract query params
-                // Format: GET /callback?token=xxx&steam_id=yyy HTTP/1.1
-                let result = parse_callback_request(&request_line);
+                // Parse the GET request to extract the login code
+                // Format: GET /callback?code=xxx HTTP/1.1
+                let result = parse_callback_request(&request_line)
+                    .and_then(|code| exchange_login_code(server, &code));
                 
                 // Send response to browser
                 let (status, body) = match &result {
@@ -143,8 +207,8 @@ fn run_callback_server() -> Result<AuthResult, String> {
     }
 }
 
-/// Parse the OAuth callback URL to extract token and steam_id
-fn parse_callback_request(request: &str) -> Result<AuthResult, String> {
+/// Parse the OAuth callback URL to extract the one-time login code
+fn parse_callback_request(request: &str) -> Result<String, String> {
     // Extract path from "GET /callback?params HTTP/1.1"
     let path = request
         .split_whitespace()
@@ -162,139 +226,400 @@ fn parse_callback_request(request: &str) -> Result<AuthResult, String> {
         return Err(format!("Steam login failed: {}", error));
     }
     
-    // Extract token and steam_id
     let query = path.split('?').nth(1).ok_or("Missing query params")?;
     
-    let mut token = None;
-    let mut steam_id = None;
+    query.split('&')
+        .find_map(|param| param.strip_prefix("code="))
+        .filter(|code| !code.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| "Missing code in callback".to_string())
+}
+
+/// Exchange the one-time login code from the callback for a JWT
+fn exchange_login_code(server: &str, code: &str) -> Result<AuthResult, String> {
+    let tokens = send(server, None, &AuthCodeExchangeRequest { code: code.to_string() })?;
+    Ok(AuthResult {
+        token: tokens.token,
+        steam_id: tokens.steam_id,
+        refresh_token: tokens.refresh_token,
+        expires_at: tokens.expires_at,
+    })
+}
+
+/// Get a new access token; the refresh token is replaced by the returned one
+pub fn refresh_session(server: &str, refresh_token: &str) -> Result<AuthTokenResponse, String> {
+    send(server, None, &RefreshSession(RefreshTokenRequest { refresh_token: refresh_token.to_string() }))
+}
+
+/// Refresh the access token in a background thread
+pub fn start_session_refresh(server: String, refresh_token: String) -> mpsc::Receiver<Result<AuthTokenResponse, String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(refresh_session(&server, &refresh_token));
+    });
+    rx
+}
+
+/// Log out this device's session on the server (fire-and-forget)
+pub fn revoke_session(server: &str, refresh_token: &str) {
+    let server = server.to_string();
+    let refresh_token = refresh_token.to_string();
+    thread::spawn(move || {
+        if let Err(e) = send(&server, None, &RevokeSession(RefreshTokenRequest { refresh_token })) {
+            eprintln!("Failed to revoke session: {}", e);
+        }
+    });
+}
+
+/// Log out all sessions of the user, on every device
+pub fn logout_all_devices(server: &str, token: &str) -> Result<(), String> {
+    send(server, Some(token), &LogoutAllSessions).map(|_| ())
+}
+
+/// Log out all devices in a background thread
+pub fn start_logout_all_devices(server: String, token: String) -> mpsc::Receiver<Result<(), String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(logout_all_devices(&server, &token));
+    });
+    rx
+}
+
+/// Check if user has data in the cloud
+pub fn check_cloud_status(server: &str, token: &str) -> Result<CloudSyncStatus, String> {
+    send(server, Some(token), &GetSyncStatus)
+}
+
+/// Upload session left unfinished by a failed upload, resumed if the same
+/// payload is uploaded again
+struct PendingUpload {
+    payload_hash: u64,
+    session_id: String,
+}
+
+static PENDING_UPLOAD: Mutex<Option<PendingUpload>> = Mutex::new(None);
+
+/// Attempts per chunk before the upload is given up (and left resumable)
+const CHUNK_ATTEMPTS: u32 = 4;
+
+fn payload_hash(bytes: &[u8]) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Format a network error including its underlying causes
+fn network_error(e: reqwest::Error) -> String {
+    use std::error::Error;
     
-    for param in query.split('&') {
-        if let Some(value) = param.strip_prefix("token=") {
-            token = Some(value.to_string());
-        } else if let Some(value) = param.strip_prefix("steam_id=") {
-            steam_id = Some(value.to_string());
+    let mut msg = format!("Network error: {}", e);
+    if let Some(source) = e.source() {
+        msg.push_str(&format!(" (cause: {})", source));
+        if let Some(inner) = source.source() {
+            msg.push_str(&format!(" (inner: {})", inner));
         }
     }
-    
-    match (token, steam_id) {
-        (Some(t), Some(s)) => Ok(AuthResult { token: t, steam_id: s }),
-        _ => Err("Missing token or steam_id in callback".to_string()),
+    msg
+}
+
+/// Build a request to a REST endpoint of the server at `server` (its base URL)
+fn api_request<E: Endpoint>(client: &reqwest::blocking::Client, server: &str, token: Option<&str>, endpoint: &E) -> reqwest::blocking::RequestBuilder {
+    let url = format!("{}{}", server, endpoint.path());
+    let mut request = match E::METHOD {
+        Method::Get => client.get(url),
+        Method::Post => client.post(url),
+        Method::Put => client.put(url),
+        Method::Delete => client.delete(url),
+    };
+    if let Some(token) = token {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+    if let Some(body) = endpoint.body() {
+        request = request.json(body);
     }
+    request
 }
 
-/// Check if user has data in the cloud
-pub fn check_cloud_status(token: &str) -> Result<CloudSyncStatus, String> {
-    let url = format!("{}/api/sync/status", DEFAULT_SERVER_URL);
-    
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .map_err(|e| format!("Network error: {}", e))?;
-    
+/// Parse a JSON response, using the server's error message when it sends one
+fn parse_response<T: DeserializeOwned>(response: reqwest::blocking::Response) -> Result<T, String> {
     if !response.status().is_success() {
         let status = response.status();
         let body = response.text().unwrap_or_default();
-        return Err(format!("Server error {}: {}", status, body));
+        let message = serde_json::from_str::<ApiError>(&body)
+            .map(|e| e.error)
+            .unwrap_or(body);
+        return Err(format!("Server error {}: {}", status, message));
     }
     
-    response.json::<CloudSyncStatus>()
+    response.json::<T>()
         .map_err(|e| format!("Failed to parse response: {}", e))
 }
 
-/// Upload all local data to cloud (overwrites existing)
-/// The progress callback is called with (bytes_sent, total_bytes)
-pub fn upload_to_cloud<F>(token: &str, data: &CloudSyncData, progress_callback: F) -> Result<(), String> 
+/// Call a REST endpoint with the given client
+fn send_with<E: Endpoint>(client: &reqwest::blocking::Client, server: &str, token: Option<&str>, endpoint: &E) -> Result<E::Response, String> {
+    api_request(client, server, token, endpoint)
+        .send()
+        .map_err(network_error)
+        .and_then(parse_response)
+}
+
+/// Call a REST endpoint; `token` is required by most endpoints and marks the
+/// user's own content on some public ones
+fn send<E: Endpoint>(server: &str, token: Option<&str>, endpoint: &E) -> Result<E::Response, String> {
+    send_with(&reqwest::blocking::Client::new(), server, token, endpoint)
+}
+
+/// HTTP client for large uploads and downloads
+fn transfer_client() -> Result<reqwest::blocking::Client, String> {
+    reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(120)) // 2 minute timeout per request
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+fn encode_payload(encoding: SyncEncoding, json_bytes: &[u8]) -> Result<Vec<u8>, String> {
+    match encoding {
+        SyncEncoding::Identity => Ok(json_bytes.to_vec()),
+        SyncEncoding::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(json_bytes)
+                .and_then(|_| encoder.finish())
+                .map_err(|e| format!("Failed to compress data: {}", e))
+        }
+        SyncEncoding::Zstd => zstd::encode_all(json_bytes, 0)
+            .map_err(|e| format!("Failed to compress data: {}", e)),
+    }
+}
+
+/// Push locally changed rows to the cloud (the server merges them with existing data).
+/// With `dry_run` the server only reports what would change.
+///
+/// The payload is compressed with an encoding negotiated with the server and sent
+/// in chunks through an upload session. Failed chunks are retried from the offset
+/// the server reports, and a failed upload of the same data resumes its session.
+pub fn upload_to_cloud<F>(server: &str, token: &str, delta: &CloudSyncDelta, dry_run: bool, progress_callback: F) -> Result<SyncPushResponse, String> 
 where
-    F: Fn(usize, usize) + Send + 'static,
+    F: Fn(UploadProgress) + Send + 'static,
 {
-    use std::error::Error;
-    
-    let url = format!("{}/api/sync/upload", DEFAULT_SERVER_URL);
-    
-    // Serialize data first to get total size
-    let json_bytes = serde_json::to_vec(data)
+    let json_bytes = serde_json::to_vec(delta)
         .map_err(|e| format!("Failed to serialize data: {}", e))?;
-    let total_bytes = json_bytes.len();
+    let hash = payload_hash(&json_bytes);
+    
+    progress_callback(UploadProgress::default());
+    
+    let client = transfer_client()?;
+    
+    // Resume the session of a previously failed upload of the same data
+    let resumable = if dry_run {
+        None
+    } else {
+        PENDING_UPLOAD.lock().unwrap().as_ref()
+            .filter(|p| p.payload_hash == hash)
+            .map(|p| p.session_id.clone())
+    };
+    let resumed = resumable.and_then(|session_id| {
+        send_with(&client, server, Some(token), &GetUploadSession { session_id }).ok()
+    });
     
-    // Report initial progress (0%)
-    progress_callback(0, total_bytes);
+    let mut session = match resumed {
+        Some(session) => session,
+        None => {
+            let request = UploadSessionRequest {
+                encodings: vec![SyncEncoding::Zstd, SyncEncoding::Gzip],
+                dry_run,
+            };
+            send_with(&client, server, Some(token), &request)?
+        }
+    };
+    
+    let payload = encode_payload(session.encoding, &json_bytes)?;
+    let total_bytes = payload.len();
+    let chunk_size = session.chunk_size.max(1);
+    let chunk_count = total_bytes.div_ceil(chunk_size).max(1);
+    let encoding = session.encoding.label();
+    
+    if !dry_run {
+        *PENDING_UPLOAD.lock().unwrap() = Some(PendingUpload {
+            payload_hash: hash,
+            session_id: session.session_id.clone(),
+        });
+    }
     
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(120)) // 2 minute timeout for uploads
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-    
-    // Use body directly instead of .json() to have serialization control
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("Content-Type", "application/json")
-        .body(json_bytes)
-        .send()
-        .map_err(|e| {
-            let mut msg = format!("Network error: {}", e);
-            if let Some(source) = e.source() {
-                msg.push_str(&format!(" (cause: {})", source));
-                if let Some(inner) = source.source() {
-                    msg.push_str(&format!(" (inner: {})", inner));
+    let report = |bytes_sent: usize| {
+        progress_callback(UploadProgress {
+            bytes_sent,
+            total_bytes,
+            uncompressed_bytes: json_bytes.len(),
+            chunks_sent: bytes_sent.div_ceil(chunk_size),
+            chunk_count,
+            encoding,
+        });
+    };
+    report(session.received_bytes);
+    
+    let mut attempts = 0;
+    while session.received_bytes < total_bytes {
+        let offset = session.received_bytes;
+        let end = (offset + chunk_size).min(total_bytes);
+        // Chunks are raw bytes, the one request without a JSON body
+        let result = client
+            .put(format!("{}{}", server, upload_chunk_path(&session.session_id, offset)))
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/octet-stream")
+            .body(payload[offset..end].to_vec())
+            .send()
+            .map_err(network_error)
+            .and_then(parse_response::<UploadSessionInfo>);
+        
+        match result {
+            Ok(info) => {
+                session = info;
+                attempts = 0;
+                report(session.received_bytes);
+            }
+            Err(e) => {
+                attempts += 1;
+                if attempts >= CHUNK_ATTEMPTS {
+                    return Err(format!("{} (upload can be resumed)", e));
+                }
+                thread::sleep(Duration::from_secs(1 << attempts));
+                // Ask the server where to continue; the chunk may have arrived
+                let status = send_with(&client, server, Some(token), &GetUploadSession { session_id: session.session_id.clone() });
+                if let Ok(info) = status {
+                    session = info;
                 }
             }
-            msg
-        })?;
+        }
+    }
     
-    // Report completion (since blocking client doesn't give us streaming progress)
-    progress_callback(total_bytes, total_bytes);
+    let complete = CompleteUploadSession {
+        session_id: session.session_id.clone(),
+        body: UploadCompleteRequest { total_bytes },
+    };
+    let response = api_request(&client, server, Some(token), &complete)
+        .send()
+        .map_err(network_error)?;
     
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().unwrap_or_default();
-        return Err(format!("Server error {}: {}", status, body));
+    // The session is consumed by the complete request, whatever its outcome
+    if !dry_run {
+        *PENDING_UPLOAD.lock().unwrap() = None;
     }
     
-    Ok(())
+    parse_response(response)
 }
 
-/// Download all data from cloud
-pub fn download_from_cloud(token: &str) -> Result<CloudSyncData, String> {
-    let url = format!("{}/api/sync/download", DEFAULT_SERVER_URL);
-    
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .map_err(|e| format!("Network error: {}", e))?;
-    
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().unwrap_or_default();
-        return Err(format!("Server error {}: {}", status, body));
+/// Pull rows changed in the cloud since `since` (everything if None)
+pub fn download_from_cloud(server: &str, token: &str, since: Option<SyncCursor>) -> Result<CloudSyncDelta, String> {
+    let delta = send(server, Some(token), &PullSyncDelta(PullDeltaQuery { since }))?;
+    if !delta.is_supported() {
+        return Err(format!(
+            "Cloud data uses sync format version {}, this version of Overachiever supports {}. Please update.",
+            delta.version, SYNC_DELTA_VERSION
+        ));
     }
-    
-    response.json::<CloudSyncData>()
-        .map_err(|e| format!("Failed to parse response: {}", e))
+    Ok(delta)
 }
 
-/// Delete all data from cloud
-pub fn delete_from_cloud(token: &str) -> Result<(), String> {
-    let url = format!("{}/api/sync/data", DEFAULT_SERVER_URL);
-    
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .delete(&url)
-        .header("Authorization", format!("Bearer {}", token))
+/// Fetch the encrypted backup, if the cloud has one
+pub fn fetch_encrypted_backup(server: &str, token: &str) -> Result<Option<EncryptedSyncBlob>, String> {
+    send_with(&transfer_client()?, server, Some(token), &GetEncryptedBackup)
+}
+
+/// Store the encrypted backup. `expected_key_check` is the key check of the
+/// backup being replaced; the server refuses the upload if it has changed.
+pub fn store_encrypted_backup(server: &str, token: &str, blob: &EncryptedSyncBlob, expected_key_check: Option<&str>) -> Result<(), String> {
+    let endpoint = StoreEncryptedBackup {
+        query: StoreEncryptedQuery { expected_key_check: expected_key_check.map(str::to_string) },
+        body: blob,
+    };
+    let response = api_request(&transfer_client()?, server, Some(token), &endpoint)
         .send()
-        .map_err(|e| format!("Network error: {}", e))?;
+        .map_err(network_error)?;
     
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().unwrap_or_default();
-        return Err(format!("Server error {}: {}", status, body));
+    if response.status() == reqwest::StatusCode::CONFLICT {
+        return Err(CryptoError::WrongKey.to_string());
     }
-    
-    Ok(())
+    parse_response::<SuccessResponse>(response).map(|_| ())
+}
+
+/// Delete the encrypted backup, keeping the rest of the cloud data
+pub fn delete_encrypted_backup(server: &str, token: &str) -> Result<(), String> {
+    send(server, Some(token), &DeleteEncryptedBackup).map(|_| ())
+}
+
+/// Fetch and decrypt the encrypted backup
+fn open_encrypted_backup(server: &str, token: &str, key: &CloudEncryptionKey) -> Result<Option<(EncryptedSyncBlob, CloudSyncData)>, String> {
+    match fetch_encrypted_backup(server, token)? {
+        Some(blob) => {
+            let data = key.decrypt(&blob).map_err(|e| e.to_string())?;
+            Ok(Some((blob, data)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Contents of a first encrypted backup: the history the cloud holds in
+/// plaintext, which the server deletes once the backup is stored
+fn new_backup_data(server: &str, token: &str, steam_id: &str) -> Result<CloudSyncData, String> {
+    let plaintext = download_from_cloud(server, token, None)?;
+    let mut data = CloudSyncDelta::new(steam_id).into_data();
+    data.run_history = plaintext.run_history;
+    data.achievement_history = plaintext.achievement_history;
+    Ok(data)
+}
+
+/// Merge local data into the encrypted backup and store it re-encrypted.
+/// Returns what the merge added to the backup.
+fn update_encrypted_backup(server: &str, token: &str, backup: &EncryptedBackup, dry_run: bool) -> Result<SyncMergePreview, String> {
+    let (expected_key_check, mut data) = match open_encrypted_backup(server, token, &backup.key)? {
+        Some((blob, data)) => (Some(blob.key_check), data),
+        None => (None, new_backup_data(server, token, &backup.local.steam_id)?),
+    };
+    
+    let merged = data.merge(&backup.local);
+    // An existing backup that gains nothing is left as it is
+    let unchanged = merged.is_empty() && expected_key_check.is_some();
+    if !dry_run && !unchanged {
+        let blob = backup.key.encrypt(&data).map_err(|e| e.to_string())?;
+        store_encrypted_backup(server, token, &blob, expected_key_check.as_deref())?;
+    }
+    Ok(merged)
+}
+
+/// Push local changes; with encryption, history goes into the encrypted backup
+/// and only the library is pushed in plaintext
+fn push_changes<F>(server: &str, token: &str, delta: &CloudSyncDelta, encrypted: Option<&EncryptedBackup>, dry_run: bool, progress_callback: F) -> Result<SyncMergePreview, String>
+where
+    F: Fn(UploadProgress) + Send + 'static,
+{
+    match encrypted {
+        Some(backup) => {
+            let history = update_encrypted_backup(server, token, backup, dry_run)?;
+            let mut merged = upload_to_cloud(server, token, &delta.without_history(), dry_run, progress_callback)?.merged;
+            merged.run_history_added = history.run_history_added;
+            merged.achievement_history_added = history.achievement_history_added;
+            Ok(merged)
+        }
+        None => upload_to_cloud(server, token, delta, dry_run, progress_callback).map(|r| r.merged),
+    }
+}
+
+/// Pull cloud changes; with a key, history from the encrypted backup is included
+fn pull_changes(server: &str, token: &str, since: Option<SyncCursor>, key: Option<&CloudEncryptionKey>) -> Result<CloudSyncDelta, String> {
+    let mut delta = download_from_cloud(server, token, since)?;
+    if let Some(key) = key {
+        if let Some((_, backup)) = open_encrypted_backup(server, token, key)? {
+            delta.run_history.extend(backup.run_history);
+            delta.achievement_history.extend(backup.achievement_history);
+        }
+    }
+    Ok(delta)
+}
+
+/// Delete all data from cloud
+pub fn delete_from_cloud(server: &str, token: &str) -> Result<(), String> {
+    send(server, Some(token), &DeleteSyncData).map(|_| ())
 }
 
 // ============================================================================
@@ -302,32 +627,29 @@ pub fn delete_from_cloud(token: &str) -> Result<(), String> {
 // ============================================================================
 
 /// Start async upload operation with progress reporting
-pub fn start_upload(token: String, data: CloudSyncData) -> mpsc::Receiver<Result<CloudOpResult, String>> {
+pub fn start_upload(server: String, token: String, delta: CloudSyncDelta, encrypted: Option<EncryptedBackup>) -> mpsc::Receiver<Result<CloudOpResult, String>> {
     let (tx, rx) = mpsc::channel();
     
     thread::spawn(move || {
         let tx_progress = tx.clone();
-        let progress_callback = move |bytes_sent: usize, total_bytes: usize| {
-            let _ = tx_progress.send(Ok(CloudOpResult::UploadProgress(UploadProgress {
-                bytes_sent,
-                total_bytes,
-            })));
+        let progress_callback = move |progress: UploadProgress| {
+            let _ = tx_progress.send(Ok(CloudOpResult::UploadProgress(progress)));
         };
         
-        let result = upload_to_cloud(&token, &data, progress_callback)
-            .map(|_| CloudOpResult::UploadSuccess);
+        let result = push_changes(&server, &token, &delta, encrypted.as_ref(), false, progress_callback)
+            .map(|merged| CloudOpResult::UploadSuccess(delta, merged));
         let _ = tx.send(result);
     });
     
     rx
 }
 
-/// Start async download operation
-pub fn start_download(token: String) -> mpsc::Receiver<Result<CloudOpResult, String>> {
+/// Start async download operation, pulling changes since `since`
+pub fn start_download(server: String, token: String, since: Option<SyncCursor>, key: Option<CloudEncryptionKey>) -> mpsc::Receiver<Result<CloudOpResult, String>> {
     let (tx, rx) = mpsc::channel();
     
     thread::spawn(move || {
-        let result = download_from_cloud(&token)
+        let result = pull_changes(&server, &token, since, key.as_ref())
             .map(CloudOpResult::DownloadSuccess);
         let _ = tx.send(result);
     });
@@ -336,11 +658,11 @@ pub fn start_download(token: String) -> mpsc::Receiver<Result<CloudOpResult, Str
 }
 
 /// Start async delete operation
-pub fn start_delete(token: String) -> mpsc::Receiver<Result<CloudOpResult, String>> {
+pub fn start_delete(server: String, token: String) -> mpsc::Receiver<Result<CloudOpResult, String>> {
     let (tx, rx) = mpsc::channel();
     
     thread::spawn(move || {
-        let result = delete_from_cloud(&token)
+        let result = delete_from_cloud(&server, &token)
             .map(|_| CloudOpResult::DeleteSuccess);
         let _ = tx.send(result);
     });
@@ -348,12 +670,93 @@ pub fn start_delete(token: String) -> mpsc::Receiver<Result<CloudOpResult, Strin
     rx
 }
 
+/// Start async fetch of everything needed for a merge preview: the cloud status,
+/// the cloud changes since `since`, and a dry run of pushing the local changes
+pub fn start_preview(server: String, token: String, since: Option<SyncCursor>, local: CloudSyncDelta, encrypted: Option<EncryptedBackup>) -> mpsc::Receiver<Result<CloudOpResult, String>> {
+    let (tx, rx) = mpsc::channel();
+    
+    thread::spawn(move || {
+        let result = check_cloud_status(&server, &token).and_then(|status| {
+            let remote = pull_changes(&server, &token, since, encrypted.as_ref().map(|e| &e.key))?;
+            let upload = push_changes(&server, &token, &local, encrypted.as_ref(), true, |_| {})?;
+            Ok(CloudOpResult::PreviewFetched(status, remote, upload))
+        });
+        let _ = tx.send(result);
+    });
+    
+    rx
+}
+
+/// Start enabling encryption. If the cloud already has an encrypted backup
+/// (made on another device), the passphrase must match it.
+pub fn start_enable_encryption(server: String, token: String, passphrase: String) -> mpsc::Receiver<Result<CloudOpResult, String>> {
+    let (tx, rx) = mpsc::channel();
+    
+    thread::spawn(move || {
+        let result = fetch_encrypted_backup(&server, &token).and_then(|existing| {
+            let key = CloudEncryptionKey::derive(&passphrase, existing.as_ref().map(|b| b.salt.as_str()))
+                .map_err(|e| e.to_string())?;
+            match existing {
+                Some(blob) if blob.key_check != key.key_check => {
+                    Err("Wrong passphrase for the encrypted cloud backup".to_string())
+                }
+                _ => Ok(CloudOpResult::EncryptionKeySet(key)),
+            }
+        });
+        let _ = tx.send(result);
+    });
+    
+    rx
+}
+
+/// Start re-encrypting the backup with a new passphrase
+pub fn start_change_passphrase(server: String, token: String, current: EncryptedBackup, new_passphrase: String) -> mpsc::Receiver<Result<CloudOpResult, String>> {
+    let (tx, rx) = mpsc::channel();
+    
+    thread::spawn(move || {
+        let result = change_passphrase(&server, &token, &current, &new_passphrase)
+            .map(CloudOpResult::PassphraseChanged);
+        let _ = tx.send(result);
+    });
+    
+    rx
+}
+
+/// Start turning encryption off: the backup is decrypted and returned so its
+/// history can be merged locally, then deleted from the cloud
+pub fn start_disable_encryption(server: String, token: String, key: CloudEncryptionKey) -> mpsc::Receiver<Result<CloudOpResult, String>> {
+    let (tx, rx) = mpsc::channel();
+    
+    thread::spawn(move || {
+        let result = open_encrypted_backup(&server, &token, &key).and_then(|backup| {
+            delete_encrypted_backup(&server, &token)?;
+            Ok(CloudOpResult::EncryptionDisabled(backup.map(|(_, data)| data)))
+        });
+        let _ = tx.send(result);
+    });
+    
+    rx
+}
+
+/// Re-encrypt the backup (merged with local data) with a key derived from a new passphrase
+fn change_passphrase(server: &str, token: &str, current: &EncryptedBackup, new_passphrase: &str) -> Result<CloudEncryptionKey, String> {
+    let new_key = CloudEncryptionKey::derive(new_passphrase, None).map_err(|e| e.to_string())?;
+    let (expected_key_check, mut data) = match open_encrypted_backup(server, token, &current.key)? {
+        Some((blob, data)) => (Some(blob.key_check), data),
+        None => (None, new_backup_data(server, token, &current.local.steam_id)?),
+    };
+    data.merge(&current.local);
+    let blob = new_key.encrypt(&data).map_err(|e| e.to_string())?;
+    store_encrypted_backup(server, token, &blob, expected_key_check.as_deref())?;
+    Ok(new_key)
+}
+
 /// Start async status check
-pub fn start_status_check(token: String) -> mpsc::Receiver<Result<CloudOpResult, String>> {
+pub fn start_status_check(server: String, token: String) -> mpsc::Receiver<Result<CloudOpResult, String>> {
     let (tx, rx) = mpsc::channel();
     
     thread::spawn(move || {
-        let result = check_cloud_status(&token)
+        let result = check_cloud_status(&server, &token)
             .map(CloudOpResult::StatusChecked);
         let _ = tx.send(result);
     });
@@ -366,71 +769,195 @@ pub fn start_status_check(token: String) -> mpsc::Receiver<Result<CloudOpResult,
 // ============================================================================
 
 /// Submit an achievement rating to the server (fire-and-forget)
-pub fn submit_achievement_rating(token: &str, appid: u64, apiname: &str, rating: u8) {
-    let url = format!("{}/api/achievement/rating", DEFAULT_SERVER_URL);
+pub fn submit_achievement_rating(server: &str, token: &str, appid: u64, apiname: &str, rating: u8) {
+    let server = server.to_string();
     let token = token.to_string();
-    let apiname = apiname.to_string();
+    let request = AchievementRatingRequest { appid, apiname: apiname.to_string(), rating };
     
     // Fire-and-forget in background thread
     thread::spawn(move || {
-        let client = reqwest::blocking::Client::new();
-        let body = serde_json::json!({
-            "appid": appid,
-            "apiname": apiname,
-            "rating": rating
-        });
-        
-        match client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-        {
-            Ok(resp) if resp.status().is_success() => {
-                // Success - rating submitted
-            }
-            Ok(resp) => {
-                eprintln!("Failed to submit rating: HTTP {}", resp.status());
-            }
-            Err(e) => {
-                eprintln!("Failed to submit rating: {}", e);
-            }
+        if let Err(e) = send(&server, Some(&token), &request) {
+            eprintln!("Failed to submit rating: {}", e);
         }
     });
 }
 
-/// Fetch all achievement ratings for the user from the server
-pub fn fetch_user_achievement_ratings(token: &str) -> Result<Vec<(u64, String, u8)>, String> {
-    let url = format!("{}/api/achievement/ratings", DEFAULT_SERVER_URL);
-    
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .map_err(|e| format!("Network error: {}", e))?;
-    
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().unwrap_or_default();
-        return Err(format!("Server error {}: {}", status, body));
-    }
-    
-    #[derive(serde::Deserialize)]
-    struct RatingItem {
-        appid: u64,
-        apiname: String,
-        rating: u8,
-    }
-    
-    #[derive(serde::Deserialize)]
-    struct RatingsResponse {
-        ratings: Vec<RatingItem>,
-    }
-    
-    let result: RatingsResponse = response.json()
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+/// Submit the user's flags for an achievement to the server (fire-and-forget)
+pub fn submit_achievement_flags(server: &str, token: &str, appid: u64, apiname: &str, flags: AchievementFlags) {
+    let server = server.to_string();
+    let token = token.to_string();
+    let request = AchievementFlagsRequest { appid, apiname: apiname.to_string(), flags };
     
-    Ok(result.ratings.into_iter().map(|r| (r.appid, r.apiname, r.rating)).collect())
+    thread::spawn(move || {
+        if let Err(e) = send(&server, Some(&token), &request) {
+            eprintln!("Failed to submit flags: {}", e);
+        }
+    });
+}
+
+/// Fetch community and Steam unlock rates for a game's achievements (public endpoint)
+pub fn fetch_achievement_rarity(server: &str, appid: u64) -> Result<GameRarity, String> {
+    send(server, None, &GetGameRarity { appid })
+}
+
+/// Fetch community difficulty averages for the achievements of a library (public endpoint)
+pub fn fetch_achievement_averages(server: &str, appids: &[u64]) -> Result<Vec<AchievementDifficulty>, String> {
+    send(server, None, &GetAchievementAverages(AppidsRequest { appids: appids.to_vec() }))
+}
+
+/// Fetch community rating averages of some games (public endpoint)
+pub fn fetch_game_rating_averages(server: &str, appids: &[u64]) -> Result<Vec<GameRatingAverage>, String> {
+    send(server, None, &GetGameRatingAverages(AppidsRequest { appids: appids.to_vec() }))
+}
+
+/// Fetch community-confirmed achievement flags for some games (public endpoint)
+pub fn fetch_achievement_flags(server: &str, appids: &[u64]) -> Result<Vec<AchievementFlagVotes>, String> {
+    send(server, None, &GetAchievementFlags(AppidsRequest { appids: appids.to_vec() }))
+}
+
+/// Fetch the ratings and reviews of a game; with a token, the own rating is marked
+pub fn fetch_game_ratings(server: &str, token: Option<&str>, appid: u64) -> Result<CommunityGameRating, String> {
+    send(server, token, &GetGameRatings { appid })
+}
+
+/// Rate a game with an optional review, replacing an earlier rating. Returns
+/// the game's ratings.
+pub fn submit_game_rating(server: &str, token: &str, appid: u64, rating: u8, review: Option<&str>) -> Result<CommunityGameRating, String> {
+    send(server, Some(token), &SubmitRatingRequest { appid, rating, comment: review.map(str::to_string) })
+}
+
+/// The user's achievement ratings and flags as stored on the server
+pub struct UserAchievementRatings {
+    pub ratings: Vec<(u64, String, u8)>,
+    pub flags: Vec<(u64, String, AchievementFlags)>,
+}
+
+/// Fetch all achievement ratings and flags for the user from the server
+pub fn fetch_user_achievement_ratings(server: &str, token: &str) -> Result<UserAchievementRatings, String> {
+    let result = send(server, Some(token), &GetUserAchievementRatings)?;
+    Ok(UserAchievementRatings {
+        ratings: result.ratings.into_iter().map(|r| (r.appid, r.apiname, r.rating)).collect(),
+        flags: result.flags.into_iter().map(|f| (f.appid, f.apiname, f.flags)).collect(),
+    })
+}
+
+// ============================================================================
+// Follows API
+// ============================================================================
+
+/// Followed users and their recent activity
+#[derive(Debug, Clone)]
+pub struct FriendsUpdate {
+    pub following: Vec<UserProfile>,
+    pub feed: Vec<FeedEntry>,
+}
+
+/// Fetch followed users and the activity feed
+pub fn fetch_friends(server: &str, token: &str) -> Result<FriendsUpdate, String> {
+    let following = send(server, Some(token), &GetFollowing)?;
+    let feed = send(server, Some(token), &GetFeed)?;
+    Ok(FriendsUpdate { following, feed })
+}
+
+/// Follow or unfollow a user by short_id, then reload followed users and the feed
+pub fn change_follow(server: &str, token: &str, short_id: &str, follow: bool) -> Result<FriendsUpdate, String> {
+    let short_id = short_id.to_string();
+    let following = if follow {
+        send(server, Some(token), &FollowUser { short_id })?
+    } else {
+        send(server, Some(token), &UnfollowUser { short_id })?
+    };
+    let feed = send(server, Some(token), &GetFeed)?;
+    Ok(FriendsUpdate { following, feed })
+}
+
+/// Load followed users and the feed in a background thread
+pub fn start_friends_refresh(server: String, token: String) -> mpsc::Receiver<Result<FriendsUpdate, String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(fetch_friends(&server, &token));
+    });
+    rx
+}
+
+/// Follow or unfollow a user in a background thread
+pub fn start_follow_change(server: String, token: String, short_id: String, follow: bool) -> mpsc::Receiver<Result<FriendsUpdate, String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(change_follow(&server, &token, &short_id, follow));
+    });
+    rx
+}
+
+// ============================================================================
+// Leaderboards API
+// ============================================================================
+
+/// Fetch a leaderboard
+pub fn fetch_leaderboard(server: &str, token: &str, metric: LeaderboardMetric, scope: LeaderboardScope) -> Result<Leaderboard, String> {
+    send(server, Some(token), &GetLeaderboard(LeaderboardQuery { metric, scope }))
+}
+
+/// Show or hide the user on global leaderboards
+pub fn set_leaderboard_opt_in(server: &str, token: &str, enabled: bool) -> Result<(), String> {
+    send(server, Some(token), &LeaderboardOptIn { enabled }).map(|_| ())
+}
+
+/// Load a leaderboard in a background thread
+pub fn start_leaderboard_fetch(server: String, token: String, metric: LeaderboardMetric, scope: LeaderboardScope) -> mpsc::Receiver<Result<Leaderboard, String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(fetch_leaderboard(&server, &token, metric, scope));
+    });
+    rx
+}
+
+/// Change the global leaderboard opt-in, then reload the leaderboard
+pub fn start_leaderboard_opt_in(server: String, token: String, enabled: bool, metric: LeaderboardMetric, scope: LeaderboardScope) -> mpsc::Receiver<Result<Leaderboard, String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let result = set_leaderboard_opt_in(&server, &token, enabled)
+            .and_then(|()| fetch_leaderboard(&server, &token, metric, scope));
+        let _ = tx.send(result);
+    });
+    rx
+}
+
+/// Fetch the comments tagging an achievement; with a token, own comments are marked
+pub fn fetch_achievement_comments(server: &str, token: Option<&str>, appid: u64, apiname: &str) -> Result<Vec<AchievementComment>, String> {
+    send(server, token, &GetAchievementComments(AchievementQuery { appid, apiname: apiname.to_string() }))
+}
+
+/// Post a comment tagging one or more achievements
+pub fn post_achievement_comment(server: &str, token: &str, achievements: Vec<(u64, String)>, comment: &str) -> Result<AchievementComment, String> {
+    send(server, Some(token), &AchievementCommentRequest { achievements, comment: comment.to_string() })
+        .map(|r| r.comment)
+}
+
+/// Change the text of an own comment
+pub fn edit_achievement_comment(server: &str, token: &str, id: i64, comment: &str) -> Result<AchievementComment, String> {
+    send(server, Some(token), &EditAchievementComment { id, body: EditCommentRequest { comment: comment.to_string() } })
+}
+
+/// Delete an own comment
+pub fn delete_achievement_comment(server: &str, token: &str, id: i64) -> Result<(), String> {
+    send(server, Some(token), &DeleteAchievementComment { id }).map(|_| ())
+}
+
+/// Fetch the tips for an achievement, highest score first; with a token, own
+/// tips and votes are marked
+pub fn fetch_achievement_tips(server: &str, token: Option<&str>, appid: u64, apiname: &str) -> Result<Vec<AchievementTip>, String> {
+    send(server, token, &GetAchievementTips(AchievementQuery { appid, apiname: apiname.to_string() }))
+}
+
+/// Submit the own tip for an achievement, replacing an earlier one. Returns
+/// the achievement's tips.
+pub fn submit_achievement_tip(server: &str, token: &str, appid: u64, apiname: &str, difficulty: u8, tip: &str) -> Result<Vec<AchievementTip>, String> {
+    send(server, Some(token), &SubmitTipRequest { appid, apiname: apiname.to_string(), difficulty, tip: tip.to_string() })
+}
+
+/// Vote on a tip (1, -1, or 0 to take the vote back). Returns the tips of
+/// the tip's achievement.
+pub fn vote_achievement_tip(server: &str, token: &str, tip_id: i64, vote: i8) -> Result<Vec<AchievementTip>, String> {
+    send(server, Some(token), &VoteAchievementTip { id: tip_id, body: VoteTipRequest { vote } })
 }
@@ -1,12 +1,29 @@
 //! Configuration management using config.toml
 
-use overachiever_core::GdprConsent;
+use chrono::{DateTime, Utc};
+use overachiever_core::{GdprConsent, SyncCursor};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 
+use crate::cloud_crypto::CloudEncryptionKey;
+
 const CONFIG_PATH: &str = "config.toml";
 
+/// Server used when `server_url` is not set
+const DEFAULT_SERVER_URL: &str = "https://overachiever.space";
+
+/// Where the library data comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataMode {
+    /// Local SQLite database, updated with the user's own Steam API key
+    #[default]
+    Local,
+    /// overachiever-server over WebSocket; scans run on the server
+    Remote,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     /// Steam Web API key
@@ -17,7 +34,12 @@ pub struct Config {
     #[serde(default)]
     pub steam_id: String,
     
-    /// Server URL for hybrid/remote modes
+    /// Local or remote data
+    #[serde(default)]
+    pub mode: DataMode,
+    
+    /// Server for login, cloud sync, community features and remote mode
+    /// (empty = the default server)
     #[serde(default)]
     pub server_url: String,
     
@@ -29,6 +51,37 @@ pub struct Config {
     #[serde(default)]
     pub cloud_token: Option<String>,
     
+    /// Token to renew `cloud_token`, replaced on every refresh
+    #[serde(default)]
+    pub cloud_refresh_token: Option<String>,
+    
+    /// When `cloud_token` expires (Unix timestamp)
+    #[serde(default)]
+    pub cloud_token_expires_at: Option<i64>,
+    
+    /// Latest run timestamp known to be in sync with the cloud
+    /// (used to detect uploads made from another device)
+    #[serde(default)]
+    pub last_cloud_sync: Option<DateTime<Utc>>,
+    
+    /// Server-issued cursor of the last delta pulled from the cloud
+    #[serde(default)]
+    pub cloud_sync_cursor: Option<SyncCursor>,
+    
+    /// Key for encrypted cloud backups, derived from the user's passphrase
+    /// (None = backups are not encrypted). Anyone who can read this file can
+    /// decrypt the backups, see [`Config::save`].
+    #[serde(default)]
+    pub cloud_encryption: Option<CloudEncryptionKey>,
+    
+    /// Upload to the cloud automatically after each update or full scan
+    #[serde(default)]
+    pub cloud_auto_sync: bool,
+    
+    /// When data was last uploaded successfully
+    #[serde(default)]
+    pub last_cloud_upload: Option<DateTime<Utc>>,
+    
     /// Debug: output recently played response to file
     #[serde(default)]
     pub debug_recently_played: bool,
@@ -39,9 +92,17 @@ impl Default for Config {
         Self {
             steam_web_api_key: String::new(),
             steam_id: String::new(),
+            mode: DataMode::Local,
             server_url: String::new(),
             gdpr_consent: GdprConsent::Unset,
             cloud_token: None,
+            cloud_refresh_token: None,
+            cloud_token_expires_at: None,
+            last_cloud_sync: None,
+            cloud_sync_cursor: None,
+            cloud_encryption: None,
+            cloud_auto_sync: false,
+            last_cloud_upload: None,
             debug_recently_played: false,
         }
     }
@@ -72,16 +133,21 @@ impl Config {
         config
     }
     
-    /// Save config to file
+    /// Save config to file. It holds the login tokens and the cloud encryption
+    /// key, so on Unix only the user may read it.
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let content = toml::to_string_pretty(self)?;
-        fs::write(CONFIG_PATH, content)?;
+        write_private(Path::new(CONFIG_PATH), &content)?;
         Ok(())
     }
     
-    /// Check if config is valid (steam credentials required)
+    /// Check if config is valid (steam credentials in local mode,
+    /// a linked Steam account in remote mode)
     pub fn is_valid(&self) -> bool {
-        !self.steam_web_api_key.is_empty() && !self.steam_id.is_empty()
+        match self.mode {
+            DataMode::Local => self.has_steam_credentials(),
+            DataMode::Remote => self.cloud_token.is_some(),
+        }
     }
     
     /// Check if local Steam API config is valid
@@ -89,11 +155,44 @@ impl Config {
         !self.steam_web_api_key.is_empty() && !self.steam_id.is_empty()
     }
     
+    /// Base URL of the server, without a trailing slash
+    pub fn server_base_url(&self) -> &str {
+        let url = self.server_url.trim().trim_end_matches('/');
+        if url.is_empty() { DEFAULT_SERVER_URL } else { url }
+    }
+    
+    /// WebSocket endpoint of the server
+    pub fn websocket_url(&self) -> String {
+        let base = self.server_base_url();
+        let base = if let Some(rest) = base.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = base.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            base.to_string()
+        };
+        format!("{}/ws", base)
+    }
+    
     /// Get steam_id as u64 for API calls
     pub fn steam_id_u64(&self) -> Option<u64> {
         self.steam_id.trim().parse().ok()
     }
     
+    /// Store the tokens of a cloud login session
+    pub fn set_cloud_session(&mut self, token: String, refresh_token: String, expires_at: i64) {
+        self.cloud_token = Some(token);
+        self.cloud_refresh_token = Some(refresh_token);
+        self.cloud_token_expires_at = Some(expires_at);
+    }
+    
+    /// Forget the cloud login session
+    pub fn clear_cloud_session(&mut self) {
+        self.cloud_token = None;
+        self.cloud_refresh_token = None;
+        self.cloud_token_expires_at = None;
+    }
+    
     /// Extract short_id from the cloud_token JWT (without verification)
     pub fn get_short_id(&self) -> Option<String> {
         let token = self.cloud_token.as_ref()?;
@@ -115,3 +214,24 @@ impl Config {
         payload.get("short_id")?.as_str().map(String::from)
     }
 }
+
+/// Write a file only its owner can read. Elsewhere than on Unix the file
+/// keeps the permissions of its directory.
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        // The mode only applies to new files; tighten ones written by older versions
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        file.write_all(content.as_bytes())
+    }
+    #[cfg(not(unix))]
+    fs::write(path, content)
+}
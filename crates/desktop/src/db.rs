@@ -2,7 +2,7 @@ use rusqlite::{Connection, Result};
 use overachiever_core::{
     Game, RunHistory, SteamGame, Achievement, AchievementHistory, 
     GameAchievement, AchievementSchema, RecentAchievement, FirstPlay, LogEntry,
-    CloudSyncData, SyncAchievement
+    CloudSyncData, CloudSyncDelta, SyncAchievement, SyncChanges, AchievementFlags
 };
 use chrono::Utc;
 
@@ -139,6 +139,12 @@ fn init_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    // Migration: track rows changed since the last cloud push
+    migrate_add_cloud_dirty(conn)?;
+
+    // Migration: achievement flags next to ratings
+    migrate_add_rating_flags(conn)?;
+
     // Create indexes for common queries
     let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_games_steam_id ON games(steam_id)", []);
     let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_achievements_steam_id ON achievements(steam_id)", []);
@@ -335,6 +341,91 @@ fn migrate_add_unplayed_games_total(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Add cloud_dirty columns (rows changed since the last cloud push) and the
+/// triggers that set them whenever synced values change.
+/// Existing rows start dirty so the first push uploads everything.
+/// Add the flags bitmask to user_achievement_ratings. Flag-only rows store
+/// rating 0 (not rated).
+fn migrate_add_rating_flags(conn: &Connection) -> Result<()> {
+    let has_column: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('user_achievement_ratings') WHERE name = 'flags'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(true);
+
+    if !has_column {
+        conn.execute(
+            "ALTER TABLE user_achievement_ratings ADD COLUMN flags INTEGER NOT NULL DEFAULT 0",
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+fn migrate_add_cloud_dirty(conn: &Connection) -> Result<()> {
+    for table in ["games", "achievements", "run_history", "achievement_history"] {
+        let has_column: bool = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = 'cloud_dirty'", table),
+                [],
+                |row| row.get::<_, i32>(0),
+            )
+            .map(|count| count > 0)
+            .unwrap_or(true);
+
+        if !has_column {
+            let _ = conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN cloud_dirty INTEGER NOT NULL DEFAULT 1", table),
+                [],
+            );
+        }
+    }
+
+    // Only fire for updates that change synced values and don't touch the flag itself
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS games_cloud_dirty AFTER UPDATE ON games
+         WHEN OLD.cloud_dirty = 0 AND NEW.cloud_dirty = 0 AND (
+            OLD.name IS NOT NEW.name
+            OR OLD.playtime_forever IS NOT NEW.playtime_forever
+            OR OLD.rtime_last_played IS NOT NEW.rtime_last_played
+            OR OLD.img_icon_url IS NOT NEW.img_icon_url
+            OR OLD.achievements_total IS NOT NEW.achievements_total
+            OR OLD.achievements_unlocked IS NOT NEW.achievements_unlocked
+            OR OLD.last_achievement_scrape IS NOT NEW.last_achievement_scrape)
+         BEGIN
+            UPDATE games SET cloud_dirty = 1 WHERE steam_id = NEW.steam_id AND appid = NEW.appid;
+         END",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS achievements_cloud_dirty AFTER UPDATE ON achievements
+         WHEN OLD.cloud_dirty = 0 AND NEW.cloud_dirty = 0 AND (
+            OLD.achieved IS NOT NEW.achieved
+            OR OLD.unlocktime IS NOT NEW.unlocktime)
+         BEGIN
+            UPDATE achievements SET cloud_dirty = 1
+            WHERE steam_id = NEW.steam_id AND appid = NEW.appid AND apiname = NEW.apiname;
+         END",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS run_history_cloud_dirty AFTER UPDATE ON run_history
+         WHEN OLD.cloud_dirty = 0 AND NEW.cloud_dirty = 0 AND (
+            OLD.total_games IS NOT NEW.total_games
+            OR OLD.unplayed_games IS NOT NEW.unplayed_games
+            OR OLD.unplayed_games_total IS NOT NEW.unplayed_games_total)
+         BEGIN
+            UPDATE run_history SET cloud_dirty = 1 WHERE id = NEW.id;
+         END",
+        [],
+    )?;
+
+    Ok(())
+}
+
 /// Update migrated data with the actual steam_id
 pub fn finalize_migration(conn: &Connection, steam_id: &str) -> Result<()> {
     conn.execute(
@@ -830,24 +921,37 @@ pub fn get_all_achievements_for_export(conn: &Connection, steam_id: &str) -> Res
     Ok(achievements)
 }
 
-/// Import cloud sync data into local database (overwrites existing data for this user)
-pub fn import_cloud_sync_data(conn: &Connection, data: &CloudSyncData) -> Result<()> {
-    let steam_id = &data.steam_id;
-    
-    // Start transaction
-    conn.execute("BEGIN TRANSACTION", [])?;
-    
-    // Delete existing data for this user
-    conn.execute("DELETE FROM games WHERE steam_id = ?1", [steam_id])?;
-    conn.execute("DELETE FROM achievements WHERE steam_id = ?1", [steam_id])?;
-    conn.execute("DELETE FROM run_history WHERE steam_id = ?1", [steam_id])?;
-    conn.execute("DELETE FROM achievement_history WHERE steam_id = ?1", [steam_id])?;
+/// Gather all local data for a user into a cloud sync bundle
+pub fn get_local_sync_data(conn: &Connection, steam_id: &str) -> Result<CloudSyncData> {
+    Ok(CloudSyncData {
+        steam_id: steam_id.to_string(),
+        games: get_all_games(conn, steam_id)?,
+        achievements: get_all_achievements_for_export(conn, steam_id)?,
+        run_history: get_run_history(conn, steam_id)?,
+        achievement_history: get_achievement_history(conn, steam_id)?,
+        exported_at: Utc::now(),
+    })
+}
+
+/// Apply merged cloud sync changes to the local database.
+/// Only new or changed rows are written; existing local history is kept.
+pub fn import_cloud_sync_data(conn: &Connection, steam_id: &str, changes: &SyncChanges) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
     
-    // Import games
-    for game in &data.games {
-        conn.execute(
+    // Import new or newer games
+    for game in &changes.games {
+        tx.execute(
             "INSERT INTO games (steam_id, appid, name, playtime_forever, rtime_last_played, img_icon_url, added_at, achievements_total, achievements_unlocked, last_achievement_scrape)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT(steam_id, appid) DO UPDATE SET
+             name = excluded.name,
+             playtime_forever = excluded.playtime_forever,
+             rtime_last_played = excluded.rtime_last_played,
+             img_icon_url = excluded.img_icon_url,
+             added_at = excluded.added_at,
+             achievements_total = excluded.achievements_total,
+             achievements_unlocked = excluded.achievements_unlocked,
+             last_achievement_scrape = excluded.last_achievement_scrape",
             rusqlite::params![
                 steam_id,
                 game.appid,
@@ -863,18 +967,15 @@ pub fn import_cloud_sync_data(conn: &Connection, data: &CloudSyncData) -> Result
         )?;
     }
     
-    // Import achievements (lightweight - only sync achieved status, not full metadata)
-    // The metadata (name, description, icons) will be populated by local scrape
-    for ach in &data.achievements {
-        // Use INSERT OR REPLACE to update existing or insert new
-        conn.execute(
-            "INSERT OR REPLACE INTO achievements (steam_id, appid, apiname, name, description, icon, icon_gray, achieved, unlocktime)
-             VALUES (?1, ?2, ?3, 
-                COALESCE((SELECT name FROM achievements WHERE steam_id = ?1 AND appid = ?2 AND apiname = ?3), ''),
-                COALESCE((SELECT description FROM achievements WHERE steam_id = ?1 AND appid = ?2 AND apiname = ?3), NULL),
-                COALESCE((SELECT icon FROM achievements WHERE steam_id = ?1 AND appid = ?2 AND apiname = ?3), ''),
-                COALESCE((SELECT icon_gray FROM achievements WHERE steam_id = ?1 AND appid = ?2 AND apiname = ?3), ''),
-                ?4, ?5)",
+    // Import achievement progress (lightweight - only sync achieved status, not full metadata)
+    // The metadata (name, description, icons) of new rows will be populated by local scrape
+    for ach in &changes.achievements {
+        tx.execute(
+            "INSERT INTO achievements (steam_id, appid, apiname, name, description, icon, icon_gray, achieved, unlocktime)
+             VALUES (?1, ?2, ?3, '', NULL, '', '', ?4, ?5)
+             ON CONFLICT(steam_id, appid, apiname) DO UPDATE SET
+             achieved = excluded.achieved,
+             unlocktime = excluded.unlocktime",
             rusqlite::params![
                 steam_id,
                 ach.appid,
@@ -885,28 +986,26 @@ pub fn import_cloud_sync_data(conn: &Connection, data: &CloudSyncData) -> Result
         )?;
     }
     
-    // Import run history
-    for rh in &data.run_history {
-        let played_games = rh.total_games - rh.unplayed_games;
-        conn.execute(
-            "INSERT INTO run_history (steam_id, recorded_at, total_games, played_games, unplayed_games, unplayed_games_total)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    // Import run history entries we don't have yet
+    for rh in &changes.run_history {
+        tx.execute(
+            "INSERT INTO run_history (steam_id, run_at, total_games, unplayed_games, unplayed_games_total, cloud_dirty)
+             VALUES (?1, ?2, ?3, ?4, ?5, 0)",
             rusqlite::params![
                 steam_id,
                 rh.run_at.to_rfc3339(),
                 rh.total_games,
-                played_games,
                 rh.unplayed_games,
                 rh.unplayed_games_total,
             ],
         )?;
     }
     
-    // Import achievement history
-    for ah in &data.achievement_history {
-        conn.execute(
-            "INSERT INTO achievement_history (steam_id, recorded_at, total_achievements, unlocked_achievements, games_with_achievements, avg_completion)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    // Import achievement history entries we don't have yet
+    for ah in &changes.achievement_history {
+        tx.execute(
+            "INSERT INTO achievement_history (steam_id, recorded_at, total_achievements, unlocked_achievements, games_with_achievements, avg_completion_percent, cloud_dirty)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0)",
             rusqlite::params![
                 steam_id,
                 ah.recorded_at.to_rfc3339(),
@@ -918,9 +1017,119 @@ pub fn import_cloud_sync_data(conn: &Connection, data: &CloudSyncData) -> Result
         )?;
     }
     
-    // Commit transaction
-    conn.execute("COMMIT", [])?;
+    // Rows written from cloud data don't need to be pushed back
+    clear_cloud_dirty_rows(&tx, steam_id, &changes.games, &changes.achievements)?;
+    
+    tx.commit()
+}
+
+/// Gather all rows changed since the last cloud push
+pub fn get_local_sync_delta(conn: &Connection, steam_id: &str) -> Result<CloudSyncDelta> {
+    let mut delta = CloudSyncDelta::new(steam_id);
+    
+    let mut stmt = conn.prepare(
+        "SELECT appid FROM games WHERE steam_id = ?1 AND cloud_dirty = 1"
+    )?;
+    let dirty_games: std::collections::HashSet<u64> = stmt.query_map([steam_id], |row| row.get(0))?
+        .collect::<Result<_>>()?;
+    delta.games = get_all_games(conn, steam_id)?
+        .into_iter()
+        .filter(|g| dirty_games.contains(&g.appid))
+        .collect();
+    
+    let mut stmt = conn.prepare(
+        "SELECT appid, apiname, achieved, unlocktime
+         FROM achievements WHERE steam_id = ?1 AND cloud_dirty = 1"
+    )?;
+    delta.achievements = stmt.query_map([steam_id], |row| {
+        let unlocktime_unix: Option<i64> = row.get(3)?;
+        Ok(SyncAchievement {
+            appid: row.get(0)?,
+            apiname: row.get(1)?,
+            achieved: row.get::<_, i32>(2)? == 1,
+            unlocktime: unlocktime_unix.and_then(|ts| chrono::DateTime::from_timestamp(ts, 0)),
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+    
+    let mut stmt = conn.prepare(
+        "SELECT id FROM run_history WHERE steam_id = ?1 AND cloud_dirty = 1"
+    )?;
+    let dirty_runs: std::collections::HashSet<i64> = stmt.query_map([steam_id], |row| row.get(0))?
+        .collect::<Result<_>>()?;
+    delta.run_history = get_run_history(conn, steam_id)?
+        .into_iter()
+        .filter(|r| dirty_runs.contains(&r.id))
+        .collect();
+    
+    let mut stmt = conn.prepare(
+        "SELECT id FROM achievement_history WHERE steam_id = ?1 AND cloud_dirty = 1"
+    )?;
+    let dirty_history: std::collections::HashSet<i64> = stmt.query_map([steam_id], |row| row.get(0))?
+        .collect::<Result<_>>()?;
+    delta.achievement_history = get_achievement_history(conn, steam_id)?
+        .into_iter()
+        .filter(|a| dirty_history.contains(&a.id))
+        .collect();
     
+    Ok(delta)
+}
+
+/// Mark the rows of a successfully pushed delta as synced.
+/// Rows whose values changed again since the delta was built stay dirty.
+pub fn mark_delta_pushed(conn: &Connection, delta: &CloudSyncDelta) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    clear_cloud_dirty_rows(&tx, &delta.steam_id, &delta.games, &delta.achievements)?;
+    for rh in &delta.run_history {
+        tx.execute(
+            "UPDATE run_history SET cloud_dirty = 0
+             WHERE id = ?1 AND total_games = ?2 AND unplayed_games = ?3 AND unplayed_games_total = ?4",
+            rusqlite::params![rh.id, rh.total_games, rh.unplayed_games, rh.unplayed_games_total],
+        )?;
+    }
+    for ah in &delta.achievement_history {
+        tx.execute("UPDATE achievement_history SET cloud_dirty = 0 WHERE id = ?1", [ah.id])?;
+    }
+    tx.commit()
+}
+
+/// Mark every row as changed, so the next push uploads everything
+/// (used after the cloud copy has been deleted)
+pub fn mark_all_cloud_dirty(conn: &Connection, steam_id: &str) -> Result<()> {
+    for table in ["games", "achievements", "run_history", "achievement_history"] {
+        conn.execute(&format!("UPDATE {} SET cloud_dirty = 1 WHERE steam_id = ?1", table), [steam_id])?;
+    }
+    Ok(())
+}
+
+/// Clear cloud_dirty for games and achievements that still hold the given values
+fn clear_cloud_dirty_rows(conn: &Connection, steam_id: &str, games: &[Game], achievements: &[SyncAchievement]) -> Result<()> {
+    for game in games {
+        conn.execute(
+            "UPDATE games SET cloud_dirty = 0
+             WHERE steam_id = ?1 AND appid = ?2 AND playtime_forever = ?3
+             AND achievements_unlocked IS ?4 AND last_achievement_scrape IS ?5",
+            rusqlite::params![
+                steam_id,
+                game.appid,
+                game.playtime_forever,
+                game.achievements_unlocked,
+                game.last_achievement_scrape.as_ref().map(|d| d.to_rfc3339()),
+            ],
+        )?;
+    }
+    for ach in achievements {
+        conn.execute(
+            "UPDATE achievements SET cloud_dirty = 0
+             WHERE steam_id = ?1 AND appid = ?2 AND apiname = ?3 AND achieved = ?4 AND unlocktime IS ?5",
+            rusqlite::params![
+                steam_id,
+                ach.appid,
+                ach.apiname,
+                if ach.achieved { 1 } else { 0 },
+                ach.unlocktime.map(|t| t.timestamp()),
+            ],
+        )?;
+    }
     Ok(())
 }
 
@@ -942,7 +1151,7 @@ pub fn set_achievement_rating(conn: &Connection, steam_id: &str, appid: u64, api
 #[allow(dead_code)]
 pub fn get_achievement_rating(conn: &Connection, steam_id: &str, appid: u64, apiname: &str) -> Result<Option<u8>> {
     let result = conn.query_row(
-        "SELECT rating FROM user_achievement_ratings WHERE steam_id = ?1 AND appid = ?2 AND apiname = ?3",
+        "SELECT rating FROM user_achievement_ratings WHERE steam_id = ?1 AND appid = ?2 AND apiname = ?3 AND rating > 0",
         rusqlite::params![steam_id, appid, apiname],
         |row| row.get(0),
     );
@@ -957,7 +1166,7 @@ pub fn get_achievement_rating(conn: &Connection, steam_id: &str, appid: u64, api
 /// Get all achievement ratings for a user (for loading into memory)
 pub fn get_all_achievement_ratings(conn: &Connection, steam_id: &str) -> Result<Vec<(u64, String, u8)>> {
     let mut stmt = conn.prepare(
-        "SELECT appid, apiname, rating FROM user_achievement_ratings WHERE steam_id = ?1"
+        "SELECT appid, apiname, rating FROM user_achievement_ratings WHERE steam_id = ?1 AND rating > 0"
     )?;
     
     let ratings = stmt.query_map([steam_id], |row| {
@@ -966,3 +1175,30 @@ pub fn get_all_achievement_ratings(conn: &Connection, steam_id: &str) -> Result<
     
     Ok(ratings)
 }
+
+/// Save or update a user's flags for an achievement, keeping any rating
+pub fn set_achievement_flags(conn: &Connection, steam_id: &str, appid: u64, apiname: &str, flags: AchievementFlags) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO user_achievement_ratings (steam_id, appid, apiname, rating, flags, created_at, updated_at)
+         VALUES (?1, ?2, ?3, 0, ?4, ?5, ?5)
+         ON CONFLICT(steam_id, appid, apiname) DO UPDATE SET
+         flags = excluded.flags,
+         updated_at = excluded.updated_at",
+        rusqlite::params![steam_id, appid, apiname, flags.bits(), now],
+    )?;
+    Ok(())
+}
+
+/// Get all achievement flags a user has set (for loading into memory)
+pub fn get_all_achievement_flags(conn: &Connection, steam_id: &str) -> Result<Vec<(u64, String, AchievementFlags)>> {
+    let mut stmt = conn.prepare(
+        "SELECT appid, apiname, flags FROM user_achievement_ratings WHERE steam_id = ?1 AND flags <> 0"
+    )?;
+    
+    let flags = stmt.query_map([steam_id], |row| {
+        Ok((row.get(0)?, row.get(1)?, AchievementFlags::from_bits(row.get(2)?)))
+    })?.collect::<Result<Vec<_>>>()?;
+    
+    Ok(flags)
+}
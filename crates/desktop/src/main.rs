@@ -2,6 +2,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
+mod cloud_crypto;
 mod cloud_sync;
 mod config;
 mod db;
@@ -9,6 +10,7 @@ mod icon_cache;
 mod steam_api;
 mod steam_library;
 mod ui;
+mod ws_client;
 
 use app::SteamOverachieverApp;
 use eframe::egui;
@@ -0,0 +1,258 @@
+//! WebSocket client for remote mode
+//!
+//! The connection runs on a background thread with its own tokio runtime.
+//! Outgoing messages are queued over a channel and incoming messages are
+//! collected until the UI thread polls them, like the WASM client.
+
+use eframe::egui;
+use futures_util::{SinkExt, StreamExt};
+use overachiever_core::{ClientMessage, ServerMessage};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Keep-alive interval, so proxies don't drop idle connections
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WsState {
+    Connecting,
+    Open,
+    Closed,
+    Error(String),
+}
+
+pub struct WsClient {
+    outgoing: UnboundedSender<ClientMessage>,
+    incoming: Receiver<ServerMessage>,
+    state: Arc<Mutex<WsState>>,
+}
+
+impl WsClient {
+    /// Connect in the background. `ctx` is repainted whenever a message
+    /// arrives or the connection state changes.
+    pub fn connect(url: &str, ctx: egui::Context) -> Self {
+        let (outgoing, outgoing_rx) = unbounded_channel();
+        let (incoming_tx, incoming) = mpsc::channel();
+        let state = Arc::new(Mutex::new(WsState::Connecting));
+
+        let url = url.to_string();
+        let thread_state = state.clone();
+        thread::spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    *thread_state.lock().unwrap() = WsState::Error(e.to_string());
+                    ctx.request_repaint();
+                    return;
+                }
+            };
+            runtime.block_on(run_connection(url, outgoing_rx, incoming_tx, thread_state, ctx));
+        });
+
+        Self { outgoing, incoming, state }
+    }
+
+    pub fn state(&self) -> WsState {
+        self.state.lock().unwrap().clone()
+    }
+
+    pub fn poll_messages(&self) -> Vec<ServerMessage> {
+        self.incoming.try_iter().collect()
+    }
+
+    fn send(&self, msg: ClientMessage) {
+        let _ = self.outgoing.send(msg);
+    }
+
+    pub fn authenticate(&self, token: &str) {
+        self.send(ClientMessage::Authenticate { token: token.to_string() });
+    }
+
+    pub fn fetch_games(&self) {
+        self.send(ClientMessage::FetchGames);
+    }
+
+    pub fn fetch_achievements(&self, appid: u64) {
+        self.send(ClientMessage::FetchAchievements { appid });
+    }
+
+    pub fn sync_from_steam(&self) {
+        self.send(ClientMessage::SyncFromSteam);
+    }
+
+    pub fn full_scan(&self, force: bool) {
+        self.send(ClientMessage::FullScan { force });
+    }
+
+    pub fn refresh_single_game(&self, appid: u64) {
+        self.send(ClientMessage::RefreshSingleGame { appid });
+    }
+
+    pub fn fetch_history(&self) {
+        self.send(ClientMessage::FetchHistory);
+    }
+
+    /// View another user's library by short_id (no authentication required)
+    pub fn view_guest_library(&self, short_id: &str) {
+        self.send(ClientMessage::ViewGuestLibrary { short_id: short_id.to_string() });
+    }
+
+    /// Fetch achievements for a game when viewing as guest
+    pub fn fetch_guest_achievements(&self, short_id: &str, appid: u64) {
+        self.send(ClientMessage::FetchGuestAchievements { short_id: short_id.to_string(), appid });
+    }
+
+    /// Fetch history when viewing as guest
+    pub fn fetch_guest_history(&self, short_id: &str) {
+        self.send(ClientMessage::FetchGuestHistory { short_id: short_id.to_string() });
+    }
+
+    /// Fetch community and Steam unlock rates for a game's achievements
+    pub fn fetch_achievement_rarity(&self, appid: u64) {
+        self.send(ClientMessage::FetchAchievementRarity { appid });
+    }
+    
+    /// Fetch the comments tagging an achievement
+    pub fn fetch_achievement_comments(&self, appid: u64, apiname: &str) {
+        self.send(ClientMessage::FetchAchievementComments { appid, apiname: apiname.to_string() });
+    }
+    
+    /// Post a comment tagging achievements (requires authentication)
+    pub fn submit_achievement_comment(&self, achievements: Vec<(u64, String)>, comment: String) {
+        self.send(ClientMessage::SubmitAchievementComment { achievements, comment });
+    }
+    
+    /// Change the text of an own comment (requires authentication)
+    pub fn edit_achievement_comment(&self, id: i64, comment: String) {
+        self.send(ClientMessage::EditAchievementComment { id, comment });
+    }
+    
+    /// Delete an own comment (requires authentication)
+    pub fn delete_achievement_comment(&self, id: i64) {
+        self.send(ClientMessage::DeleteAchievementComment { id });
+    }
+    
+    /// Fetch the tips for an achievement
+    pub fn fetch_achievement_tips(&self, appid: u64, apiname: &str) {
+        self.send(ClientMessage::GetCommunityTips { appid, apiname: apiname.to_string() });
+    }
+    
+    /// Submit the own tip for an achievement (requires authentication)
+    pub fn submit_achievement_tip(&self, appid: u64, apiname: String, difficulty: u8, tip: String) {
+        self.send(ClientMessage::SubmitAchievementTip { appid, apiname, difficulty, tip });
+    }
+    
+    /// Vote on a tip (requires authentication)
+    pub fn vote_achievement_tip(&self, tip_id: i64, vote: i8) {
+        self.send(ClientMessage::VoteAchievementTip { tip_id, vote });
+    }
+    
+    /// Fetch community difficulty averages for the achievements of some games
+    pub fn fetch_achievement_averages(&self, appids: Vec<u64>) {
+        self.send(ClientMessage::FetchAchievementAverages { appids });
+    }
+    
+    /// Fetch community rating averages of some games
+    pub fn fetch_game_rating_averages(&self, appids: Vec<u64>) {
+        self.send(ClientMessage::FetchGameRatingAverages { appids });
+    }
+    
+    /// Fetch community-confirmed flags for the achievements of some games
+    pub fn fetch_achievement_flags(&self, appids: Vec<u64>) {
+        self.send(ClientMessage::FetchAchievementFlags { appids });
+    }
+    
+    /// Fetch the ratings and reviews of a game
+    pub fn fetch_game_ratings(&self, appid: u64) {
+        self.send(ClientMessage::GetCommunityRatings { appid });
+    }
+    
+    /// Rate a game with an optional review (requires authentication)
+    pub fn submit_game_rating(&self, appid: u64, rating: u8, review: Option<String>) {
+        self.send(ClientMessage::SubmitRating { appid, rating, comment: review });
+    }
+    
+    /// Compare own library with another user's (requires authentication)
+    pub fn compare_libraries(&self, short_id: &str) {
+        self.send(ClientMessage::CompareLibraries { short_id: short_id.to_string() });
+    }
+
+    /// Compare unlocked achievements in a shared game (requires authentication)
+    pub fn compare_game_achievements(&self, short_id: &str, appid: u64) {
+        self.send(ClientMessage::CompareGameAchievements { short_id: short_id.to_string(), appid });
+    }
+}
+
+async fn run_connection(
+    url: String,
+    mut outgoing: UnboundedReceiver<ClientMessage>,
+    incoming: Sender<ServerMessage>,
+    state: Arc<Mutex<WsState>>,
+    ctx: egui::Context,
+) {
+    let set_state = |new_state: WsState| {
+        *state.lock().unwrap() = new_state;
+        ctx.request_repaint();
+    };
+
+    let socket = match tokio_tungstenite::connect_async(url.as_str()).await {
+        Ok((socket, _)) => socket,
+        Err(e) => {
+            set_state(WsState::Error(format!("Could not connect to {}: {}", url, e)));
+            return;
+        }
+    };
+    set_state(WsState::Open);
+
+    let (mut write, mut read) = socket.split();
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    ping.tick().await;
+
+    loop {
+        tokio::select! {
+            msg = outgoing.recv() => {
+                // The client was dropped
+                let Some(msg) = msg else {
+                    let _ = write.close().await;
+                    break;
+                };
+                let Ok(json) = serde_json::to_string(&msg) else {
+                    continue;
+                };
+                if let Err(e) = write.send(Message::Text(json)).await {
+                    set_state(WsState::Error(e.to_string()));
+                    return;
+                }
+            }
+            frame = read.next() => {
+                match frame {
+                    Some(Ok(Message::Text(text))) => {
+                        if let Ok(msg) = serde_json::from_str::<ServerMessage>(&text) {
+                            if incoming.send(msg).is_err() {
+                                break;
+                            }
+                            ctx.request_repaint();
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        set_state(WsState::Error(e.to_string()));
+                        return;
+                    }
+                }
+            }
+            _ = ping.tick() => {
+                if let Ok(json) = serde_json::to_string(&ClientMessage::Ping) {
+                    let _ = write.send(Message::Text(json)).await;
+                }
+            }
+        }
+    }
+
+    set_state(WsState::Closed);
+}
@@ -2,9 +2,9 @@
 
 use eframe::egui;
 use overachiever_core::{
-    Game, GameAchievement, UserProfile, RunHistory, AchievementHistory, 
-    SyncState, LogEntry, GdprConsent, SidebarPanel, SortColumn, SortOrder, TriFilter,
-    sort_games,
+    Game, GameAchievement, GameRarity, CommunityAverages, AchievementComment, AchievementFlags, AchievementTip, CommunityGameRating, GameReviewCache, CommentCache, UserProfile, RunHistory, AchievementHistory, 
+    SyncState, LogEntry, FeedEntry, Leaderboard, LeaderboardViewState, GdprConsent, SidebarPanel, SortColumn, SortOrder, TriFilter,
+    sort_games, AUTH_TOKEN_EXPIRED,
 };
 use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
@@ -12,10 +12,12 @@ use std::cell::RefCell;
 
 use crate::ws_client::WsClient;
 use crate::storage::{
-    get_token_from_url, get_token_from_storage, save_token_to_storage, clear_token_from_storage,
+    get_login_code_from_url, get_token_from_storage, save_token_to_storage,
+    get_refresh_token_from_storage, get_token_expiry_from_storage,
     get_ws_url_from_location, get_gdpr_consent_from_storage, get_short_id_from_url,
 };
 use crate::http_client::BuildInfo;
+use crate::session::SessionUpdate;
 
 // ============================================================================
 // Types
@@ -44,6 +46,16 @@ impl AppState {
     }
 }
 
+/// Result of a comment or tip request, applied on the next frame
+pub enum CommentUpdate {
+    Thread { appid: u64, apiname: String, comments: Vec<AchievementComment> },
+    Posted(AchievementComment),
+    Updated(AchievementComment),
+    Deleted(i64),
+    Tips { appid: u64, apiname: String, tips: Vec<AchievementTip> },
+    Ratings(CommunityGameRating),
+}
+
 // ============================================================================
 // Main App
 // ============================================================================
@@ -71,10 +83,18 @@ pub struct WasmApp {
     pub(crate) expanded_rows: HashSet<u64>,
     pub(crate) achievements_cache: HashMap<u64, Vec<GameAchievement>>,
     pub(crate) user_achievement_ratings: HashMap<(u64, String), u8>, // (appid, apiname) -> rating
-    pub(crate) pending_ratings: Rc<RefCell<Option<Vec<(u64, String, u8)>>>>, // Incoming ratings from server
+    pub(crate) user_achievement_flags: HashMap<(u64, String), AchievementFlags>, // (appid, apiname) -> own flags
+    pub(crate) achievement_rarity: HashMap<u64, GameRarity>,
+    pub(crate) pending_rarity: HashSet<u64>, // Rarity requested this session
+    pub(crate) difficulty: CommunityAverages, // Community difficulty and rating averages
+    pub(crate) comments: CommentCache,
+    pub(crate) reviews: GameReviewCache, // Loaded game ratings and own rating drafts
+    pub(crate) pending_comment_updates: Rc<RefCell<Vec<CommentUpdate>>>, // Finished comment requests
+    pub(crate) pending_ratings: Rc<RefCell<Option<crate::http_client::UserRatings>>>, // Incoming ratings and flags from server
     pub(crate) filter_name: String,
     pub(crate) filter_achievements: TriFilter,
     pub(crate) filter_playtime: TriFilter,
+    pub(crate) filter_missables: TriFilter,
     pub(crate) show_login: bool,
     pub(crate) include_unplayed_in_avg: bool,
     pub(crate) show_stats_panel: bool,
@@ -82,13 +102,33 @@ pub struct WasmApp {
     pub(crate) games_graph_tab: usize,
     pub(crate) achievements_graph_tab: usize,
     
-    // Token from URL or storage
+    // Session tokens from storage (or from the login code in the URL)
     pub(crate) auth_token: Option<String>,
+    pub(crate) refresh_token: Option<String>,
+    pub(crate) token_expires_at: Option<i64>, // Unix timestamp
+    pub(crate) token_refresh_requested: bool, // Server reported an expired token
+    pub(crate) token_refresh_retry_at: Option<i64>, // Unix timestamp
+    pub(crate) session_request_in_flight: bool,
+    pub(crate) pending_session: Rc<RefCell<Option<SessionUpdate>>>, // Finished session request
     
     // Guest viewing mode (viewing another user's library by short_id)
     pub(crate) viewing_short_id: Option<String>,
     pub(crate) viewing_user: Option<UserProfile>,
     
+    // Followed users and their activity (friends panel)
+    pub(crate) following: Vec<UserProfile>,
+    pub(crate) feed_entries: Vec<FeedEntry>,
+    pub(crate) feed_loaded: bool,
+    pub(crate) feed_loading: bool,
+    pub(crate) feed_error: Option<String>,
+    pub(crate) follow_input: String,
+    
+    // Leaderboard panel
+    pub(crate) leaderboard_view: LeaderboardViewState,
+    pub(crate) leaderboard: Option<Leaderboard>,
+    pub(crate) leaderboard_loading: bool,
+    pub(crate) leaderboard_error: Option<String>,
+    
     // GDPR consent status
     pub(crate) gdpr_consent: GdprConsent,
     
@@ -113,11 +153,13 @@ impl WasmApp {
         // Check if we're viewing someone else's library by short_id
         let viewing_short_id = get_short_id_from_url();
         
-        // Try to get token from URL params or localStorage (only if not in guest view mode)
-        let auth_token = if viewing_short_id.is_none() {
-            get_token_from_url().or_else(|| get_token_from_storage())
+        // After a Steam login the URL carries a one-time code to exchange for
+        // tokens; otherwise use the stored session (only if not in guest view mode)
+        let login_code = if viewing_short_id.is_none() { get_login_code_from_url() } else { None };
+        let (auth_token, refresh_token, token_expires_at) = if viewing_short_id.is_none() && login_code.is_none() {
+            (get_token_from_storage(), get_refresh_token_from_storage(), get_token_expiry_from_storage())
         } else {
-            None
+            (None, None, None)
         };
         
         // Auto-detect WebSocket URL from current page location
@@ -157,10 +199,18 @@ impl WasmApp {
             expanded_rows: HashSet::new(),
             achievements_cache: HashMap::new(),
             user_achievement_ratings: HashMap::new(),
+            user_achievement_flags: HashMap::new(),
+            achievement_rarity: HashMap::new(),
+            pending_rarity: HashSet::new(),
+            difficulty: CommunityAverages::default(),
+            comments: CommentCache::default(),
+            reviews: GameReviewCache::default(),
+            pending_comment_updates: Rc::new(RefCell::new(Vec::new())),
             pending_ratings: Rc::new(RefCell::new(None)),
             filter_name: String::new(),
             filter_achievements: TriFilter::All,
             filter_playtime: TriFilter::All,
+            filter_missables: TriFilter::All,
             show_login: false,
             include_unplayed_in_avg: false,
             show_stats_panel,
@@ -168,8 +218,24 @@ impl WasmApp {
             games_graph_tab: 0,
             achievements_graph_tab: 0,
             auth_token,
+            refresh_token,
+            token_expires_at,
+            token_refresh_requested: false,
+            token_refresh_retry_at: None,
+            session_request_in_flight: false,
+            pending_session: Rc::new(RefCell::new(None)),
             viewing_short_id,
             viewing_user: None,
+            following: Vec::new(),
+            feed_entries: Vec::new(),
+            feed_loaded: false,
+            feed_loading: false,
+            feed_error: None,
+            follow_input: String::new(),
+            leaderboard_view: LeaderboardViewState::default(),
+            leaderboard: None,
+            leaderboard_loading: false,
+            leaderboard_error: None,
             gdpr_consent,
             build_info: Rc::new(RefCell::new(None)),
             navigation_target: None,
@@ -181,8 +247,11 @@ impl WasmApp {
         // Fetch build info asynchronously
         app.fetch_build_info();
         
-        // Auto-connect on startup
-        app.connect();
+        // Auto-connect on startup; after a login the page reloads once signed in
+        match login_code {
+            Some(code) => app.exchange_login_code(code),
+            None => app.connect(),
+        }
         app
     }
     
@@ -276,7 +345,7 @@ impl WasmApp {
                     Ok(ratings) => {
                         *pending.borrow_mut() = Some(ratings);
                         web_sys::console::log_1(&format!("Loaded {} achievement ratings from server", 
-                            pending.borrow().as_ref().map_or(0, |r| r.len())).into());
+                            pending.borrow().as_ref().map_or(0, |(ratings, _)| ratings.len())).into());
                     }
                     Err(e) => {
                         web_sys::console::error_1(&format!("Failed to fetch achievement ratings: {}", e).into());
@@ -288,10 +357,33 @@ impl WasmApp {
     
     /// Process any pending ratings from async fetch
     fn process_pending_ratings(&mut self) {
-        if let Some(ratings) = self.pending_ratings.borrow_mut().take() {
+        if let Some((ratings, flags)) = self.pending_ratings.borrow_mut().take() {
             for (appid, apiname, rating) in ratings {
                 self.user_achievement_ratings.insert((appid, apiname), rating);
             }
+            for (appid, apiname, flags) in flags {
+                self.user_achievement_flags.insert((appid, apiname), flags);
+            }
+        }
+    }
+    
+    fn process_pending_comments(&mut self) {
+        let updates: Vec<_> = self.pending_comment_updates.borrow_mut().drain(..).collect();
+        for update in updates {
+            match update {
+                CommentUpdate::Thread { appid, apiname, comments } => self.comments.set_thread(appid, apiname, comments),
+                CommentUpdate::Posted(comment) => self.comments.add(comment),
+                CommentUpdate::Updated(comment) => self.comments.update(comment),
+                CommentUpdate::Deleted(id) => self.comments.remove(id),
+                CommentUpdate::Tips { appid, apiname, tips } => self.comments.set_tips(appid, apiname, tips),
+                CommentUpdate::Ratings(community) => {
+                    self.difficulty.set_game_rating(community.appid, community.avg_rating, community.rating_count);
+                    self.reviews.set(community);
+                    if self.sort_column == SortColumn::Rating {
+                        sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
+                    }
+                }
+            }
         }
     }
     
@@ -305,7 +397,12 @@ impl WasmApp {
         for msg in messages {
             match msg {
                 overachiever_core::ServerMessage::Authenticated { user } => {
+                    // Authenticating again with a refreshed token keeps the loaded data
+                    let reauthenticated = matches!(self.connection_state, ConnectionState::Authenticated(_));
                     self.connection_state = ConnectionState::Authenticated(user.clone());
+                    if reauthenticated {
+                        continue;
+                    }
                     self.status = format!("Logged in as {}", user.display_name);
                     
                     if let Some(token) = &self.auth_token {
@@ -321,20 +418,25 @@ impl WasmApp {
                     // Fetch saved achievement ratings
                     self.fetch_user_ratings();
                 }
+                overachiever_core::ServerMessage::AuthError { reason } if reason == AUTH_TOKEN_EXPIRED && self.refresh_token.is_some() => {
+                    // The connection stays open; it is authenticated again after the refresh
+                    self.connection_state = ConnectionState::Connected;
+                    self.request_token_refresh();
+                }
                 overachiever_core::ServerMessage::AuthError { reason } => {
                     self.connection_state = ConnectionState::Error(reason.clone());
                     self.status = format!("Auth failed: {}", reason);
                     self.show_login = true;
                     // Clear invalid token
-                    self.auth_token = None;
-                    clear_token_from_storage();
+                    self.clear_session();
                 }
                 overachiever_core::ServerMessage::Games { games } => {
                     self.games = games;
                     self.games_loaded = true;
                     self.app_state = AppState::Idle;
                     self.status = format!("Loaded {} games", self.games.len());
-                    sort_games(&mut self.games, self.sort_column, self.sort_order);
+                    sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
+                    self.request_achievement_averages();
                     // Refresh history (run_history may have been recorded on sync)
                     if let Some(client) = &self.ws_client {
                         client.fetch_history();
@@ -343,6 +445,15 @@ impl WasmApp {
                 overachiever_core::ServerMessage::Achievements { appid, achievements } => {
                     self.achievements_cache.insert(appid, achievements);
                 }
+                overachiever_core::ServerMessage::Error { message } if self.feed_loading => {
+                    // A follow or feed request failed
+                    self.feed_loading = false;
+                    self.feed_error = Some(message);
+                }
+                overachiever_core::ServerMessage::Error { message } if self.leaderboard_loading => {
+                    self.leaderboard_loading = false;
+                    self.leaderboard_error = Some(message);
+                }
                 overachiever_core::ServerMessage::Error { message } => {
                     self.app_state = AppState::Idle;
                     self.scan_progress = None;
@@ -375,7 +486,8 @@ impl WasmApp {
                     self.app_state = AppState::Idle;
                     self.scan_progress = None;
                     self.status = format!("Scan complete! Updated {} games, {} achievements", result.games_updated, result.achievements_updated);
-                    sort_games(&mut self.games, self.sort_column, self.sort_order);
+                    sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
+                    self.request_achievement_averages();
                     // Refresh history
                     if let Some(client) = &self.ws_client {
                         client.fetch_history();
@@ -392,7 +504,7 @@ impl WasmApp {
                     self.single_game_refreshing = None;
                     self.status = "Refresh complete!".to_string();
                     // Re-sort games
-                    sort_games(&mut self.games, self.sort_column, self.sort_order);
+                    sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
                 }
                 overachiever_core::ServerMessage::History { run_history, achievement_history, log_entries } => {
                     web_sys::console::log_1(&format!("Received History: {} run_history, {} achievement_history, {} log_entries", 
@@ -407,7 +519,8 @@ impl WasmApp {
                     self.games = games;
                     self.games_loaded = true;
                     self.status = format!("Viewing {}'s library", user.display_name);
-                    sort_games(&mut self.games, self.sort_column, self.sort_order);
+                    sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
+                    self.request_achievement_averages();
                     
                     // Fetch history for guest view
                     if let Some(ref short_id) = self.viewing_short_id.clone() {
@@ -416,6 +529,42 @@ impl WasmApp {
                         }
                     }
                 }
+                overachiever_core::ServerMessage::Following { users } => {
+                    self.following = users;
+                }
+                overachiever_core::ServerMessage::Feed { entries } => {
+                    self.feed_entries = entries;
+                    self.feed_loading = false;
+                    self.feed_error = None;
+                }
+                overachiever_core::ServerMessage::Leaderboard { leaderboard } => {
+                    self.leaderboard = Some(leaderboard);
+                    self.leaderboard_loading = false;
+                    self.leaderboard_error = None;
+                }
+                overachiever_core::ServerMessage::LeaderboardOptIn { enabled } => {
+                    if let Some(leaderboard) = &mut self.leaderboard {
+                        leaderboard.opted_in = enabled;
+                    }
+                }
+                overachiever_core::ServerMessage::GameRatingAverages { averages } => {
+                    self.difficulty.insert_game_ratings(averages);
+                    if self.sort_column == SortColumn::Rating {
+                        sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
+                    }
+                }
+                overachiever_core::ServerMessage::AchievementFlags { flags } => {
+                    self.difficulty.insert_flags(flags);
+                }
+                overachiever_core::ServerMessage::AchievementAverages { averages } => {
+                    self.difficulty.insert(averages);
+                    if self.sort_column == SortColumn::Difficulty {
+                        sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
+                    }
+                }
+                overachiever_core::ServerMessage::AchievementRarity { rarity } => {
+                    self.achievement_rarity.insert(rarity.appid, rarity);
+                }
                 overachiever_core::ServerMessage::GuestNotFound { short_id } => {
                     self.status = format!("User not found: {}", short_id);
                     self.connection_state = ConnectionState::Error(format!("User '{}' not found", short_id));
@@ -429,6 +578,20 @@ impl WasmApp {
     // Actions
     // ========================================================================
     
+    /// Load community difficulty averages and flags for the games with
+    /// achievements and rating averages for all games
+    pub(crate) fn request_achievement_averages(&self) {
+        if let Some(client) = &self.ws_client {
+            let appids: Vec<u64> = self.games.iter()
+                .filter(|g| g.achievements_total.map(|t| t > 0).unwrap_or(false))
+                .map(|g| g.appid)
+                .collect();
+            client.fetch_achievement_averages(appids.clone());
+            client.fetch_achievement_flags(appids);
+            client.fetch_game_rating_averages(self.games.iter().map(|g| g.appid).collect());
+        }
+    }
+    
     pub(crate) fn start_sync(&mut self) {
         if let Some(client) = &self.ws_client {
             self.app_state = AppState::Syncing;
@@ -458,9 +621,14 @@ impl eframe::App for WasmApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.check_ws_state();
         self.check_messages();
+        self.process_pending_session();
+        self.check_token_refresh();
         self.process_pending_ratings();
+        self.process_pending_comments();
         
-        if matches!(self.connection_state, ConnectionState::Disconnected) {
+        // Wait for the login code exchange before connecting
+        let signing_in = self.session_request_in_flight && self.refresh_token.is_none();
+        if matches!(self.connection_state, ConnectionState::Disconnected) && !signing_in {
             self.connect();
         }
         
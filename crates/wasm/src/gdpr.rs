@@ -5,7 +5,7 @@ use egui_phosphor::regular;
 use overachiever_core::GdprConsent;
 
 use crate::app::WasmApp;
-use crate::storage::{save_gdpr_consent_to_storage, clear_token_from_storage};
+use crate::storage::save_gdpr_consent_to_storage;
 
 impl WasmApp {
     /// Render the GDPR consent modal if consent hasn't been given
@@ -94,8 +94,7 @@ impl WasmApp {
                                 self.gdpr_consent = GdprConsent::Declined;
                                 save_gdpr_consent_to_storage(GdprConsent::Declined);
                                 // Clear any existing auth data
-                                self.auth_token = None;
-                                clear_token_from_storage();
+                                self.end_session();
                             }
                         });
                     });
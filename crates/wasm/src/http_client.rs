@@ -1,34 +1,42 @@
-//! HTTP client for REST API calls (ratings, comments)
+//! HTTP client for REST API calls (ratings, reviews, comments, tips, sessions)
 //!
-//! Uses gloo-net for browser fetch API
+//! Uses gloo-net for browser fetch API. Requests and responses are the typed
+//! endpoints of `overachiever_core::api`, shared with the server.
 
 use gloo_net::http::Request;
-use serde::{Deserialize, Serialize};
+use overachiever_core::api::{
+    AchievementCommentRequest, AchievementCommentResponse, AchievementFlagsRequest, AchievementQuery,
+    AchievementRatingRequest, AchievementRatingResponse, DeleteAchievementComment, EditAchievementComment,
+    EditCommentRequest, Endpoint, GetAchievementComments, GetAchievementTips, GetGameRatings,
+    GetUserAchievementRatings, LogoutAllSessions, Method, RefreshSession, RevokeSession, SubmitRatingRequest,
+    SubmitTipRequest, VoteAchievementTip, VoteTipRequest,
+};
+use overachiever_core::{AchievementComment, AchievementFlags, AchievementTip, AuthCodeExchangeRequest, AuthTokenResponse, CommunityGameRating, RefreshTokenRequest};
+use serde::Deserialize;
 
-/// Submit an achievement rating via REST API
-pub async fn submit_achievement_rating(
-    token: &str,
-    appid: u64,
-    apiname: &str,
-    rating: u8,
-) -> Result<AchievementRatingResponse, String> {
+/// Call a REST endpoint of the server this page was served from; `token` is
+/// required by most endpoints and marks the user's own content on some public ones
+async fn send<E: Endpoint>(token: Option<&str>, endpoint: &E) -> Result<E::Response, String> {
     let origin = web_sys::window()
         .and_then(|w| w.location().origin().ok())
         .unwrap_or_default();
     
-    let url = format!("{}/api/achievement/rating", origin);
-    
-    let body = AchievementRatingRequest {
-        appid,
-        apiname: apiname.to_string(),
-        rating,
+    let url = format!("{}{}", origin, endpoint.path());
+    let mut builder = match E::METHOD {
+        Method::Get => Request::get(&url),
+        Method::Post => Request::post(&url),
+        Method::Put => Request::put(&url),
+        Method::Delete => Request::delete(&url),
     };
+    if let Some(token) = token {
+        builder = builder.header("Authorization", &format!("Bearer {}", token));
+    }
     
-    let response = Request::post(&url)
-        .header("Authorization", &format!("Bearer {}", token))
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .map_err(|e| format!("Failed to serialize request: {}", e))?
+    let request = match endpoint.body() {
+        Some(body) => builder.json(body).map_err(|e| format!("Failed to serialize request: {}", e))?,
+        None => builder.build().map_err(|e| format!("Failed to build request: {}", e))?,
+    };
+    let response = request
         .send()
         .await
         .map_err(|e| format!("Failed to send request: {}", e))?;
@@ -40,121 +48,152 @@ pub async fn submit_achievement_rating(
     }
     
     response
-        .json::<AchievementRatingResponse>()
+        .json::<E::Response>()
         .await
         .map_err(|e| format!("Failed to parse response: {}", e))
 }
 
+/// Submit an achievement rating via REST API
+pub async fn submit_achievement_rating(
+    token: &str,
+    appid: u64,
+    apiname: &str,
+    rating: u8,
+) -> Result<AchievementRatingResponse, String> {
+    send(Some(token), &AchievementRatingRequest { appid, apiname: apiname.to_string(), rating }).await
+}
+
+/// Set the user's flags for an achievement via REST API
+pub async fn submit_achievement_flags(
+    token: &str,
+    appid: u64,
+    apiname: &str,
+    flags: AchievementFlags,
+) -> Result<AchievementRatingResponse, String> {
+    send(Some(token), &AchievementFlagsRequest { appid, apiname: apiname.to_string(), flags }).await
+}
+
 /// Submit an achievement comment via REST API
-#[allow(dead_code)]
 pub async fn submit_achievement_comment(
     token: &str,
     achievements: Vec<(u64, String)>,
     comment: &str,
 ) -> Result<AchievementCommentResponse, String> {
-    let origin = web_sys::window()
-        .and_then(|w| w.location().origin().ok())
-        .unwrap_or_default();
-    
-    let url = format!("{}/api/achievement/comment", origin);
-    
-    let body = AchievementCommentRequest {
-        achievements,
-        comment: comment.to_string(),
-    };
-    
-    let response = Request::post(&url)
-        .header("Authorization", &format!("Bearer {}", token))
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .map_err(|e| format!("Failed to serialize request: {}", e))?
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request: {}", e))?;
-    
-    if !response.ok() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Request failed with status {}: {}", status, text));
-    }
-    
-    response
-        .json::<AchievementCommentResponse>()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+    send(Some(token), &AchievementCommentRequest { achievements, comment: comment.to_string() }).await
 }
 
-/// Fetch all achievement ratings for the current user
-pub async fn fetch_user_achievement_ratings(
+/// Fetch the comments tagging an achievement; with a token, own comments are marked
+pub async fn fetch_achievement_comments(
+    token: Option<&str>,
+    appid: u64,
+    apiname: &str,
+) -> Result<Vec<AchievementComment>, String> {
+    send(token, &GetAchievementComments(AchievementQuery { appid, apiname: apiname.to_string() })).await
+}
+
+/// Change the text of an own comment via REST API
+pub async fn edit_achievement_comment(
     token: &str,
-) -> Result<Vec<(u64, String, u8)>, String> {
-    let origin = web_sys::window()
-        .and_then(|w| w.location().origin().ok())
-        .unwrap_or_default();
-    
-    let url = format!("{}/api/achievement/ratings", origin);
-    
-    let response = Request::get(&url)
-        .header("Authorization", &format!("Bearer {}", token))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request: {}", e))?;
-    
-    if !response.ok() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Request failed with status {}: {}", status, text));
-    }
-    
-    let result = response
-        .json::<UserAchievementRatingsResponse>()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
-    
-    Ok(result.ratings.into_iter().map(|r| (r.appid, r.apiname, r.rating)).collect())
+    id: i64,
+    comment: &str,
+) -> Result<AchievementComment, String> {
+    send(Some(token), &EditAchievementComment { id, body: EditCommentRequest { comment: comment.to_string() } }).await
+}
+
+/// Delete an own comment via REST API
+pub async fn delete_achievement_comment(token: &str, id: i64) -> Result<(), String> {
+    send(Some(token), &DeleteAchievementComment { id }).await.map(|_| ())
+}
+
+/// Fetch the tips for an achievement, highest score first; with a token, own
+/// tips and votes are marked
+pub async fn fetch_achievement_tips(
+    token: Option<&str>,
+    appid: u64,
+    apiname: &str,
+) -> Result<Vec<AchievementTip>, String> {
+    send(token, &GetAchievementTips(AchievementQuery { appid, apiname: apiname.to_string() })).await
 }
 
-// Request/Response types (matching backend)
+/// Submit the own tip for an achievement via REST API, replacing an earlier
+/// one. Returns the achievement's tips.
+pub async fn submit_achievement_tip(
+    token: &str,
+    appid: u64,
+    apiname: &str,
+    difficulty: u8,
+    tip: &str,
+) -> Result<Vec<AchievementTip>, String> {
+    let request = SubmitTipRequest {
+        appid,
+        apiname: apiname.to_string(),
+        difficulty,
+        tip: tip.to_string(),
+    };
+    send(Some(token), &request).await
+}
+
+/// Vote on a tip via REST API (1, -1, or 0 to take the vote back). Returns
+/// the tips of the tip's achievement.
+pub async fn vote_achievement_tip(token: &str, tip_id: i64, vote: i8) -> Result<Vec<AchievementTip>, String> {
+    send(Some(token), &VoteAchievementTip { id: tip_id, body: VoteTipRequest { vote } }).await
+}
+
+/// Fetch the ratings and reviews of a game; with a token, the own rating is marked
+pub async fn fetch_game_ratings(token: Option<&str>, appid: u64) -> Result<CommunityGameRating, String> {
+    send(token, &GetGameRatings { appid }).await
+}
 
-#[derive(Serialize)]
-struct AchievementRatingRequest {
+/// Rate a game via REST API with an optional review, replacing an earlier
+/// rating. Returns the game's ratings.
+pub async fn submit_game_rating(
+    token: &str,
     appid: u64,
-    apiname: String,
     rating: u8,
+    review: Option<String>,
+) -> Result<CommunityGameRating, String> {
+    send(Some(token), &SubmitRatingRequest { appid, rating, comment: review }).await
 }
 
-#[derive(Deserialize)]
-#[allow(dead_code)]
-pub struct AchievementRatingResponse {
-    pub success: bool,
-    pub appid: u64,
-    pub apiname: String,
+/// The user's achievement ratings and flags: (appid, apiname, value) each
+pub type UserRatings = (Vec<(u64, String, u8)>, Vec<(u64, String, AchievementFlags)>);
+
+/// Fetch all achievement ratings and flags for the current user
+pub async fn fetch_user_achievement_ratings(
+    token: &str,
+) -> Result<UserRatings, String> {
+    let result = send(Some(token), &GetUserAchievementRatings).await?;
+    
+    Ok((
+        result.ratings.into_iter().map(|r| (r.appid, r.apiname, r.rating)).collect(),
+        result.flags.into_iter().map(|f| (f.appid, f.apiname, f.flags)).collect(),
+    ))
 }
 
-#[derive(Serialize)]
-#[allow(dead_code)]
-struct AchievementCommentRequest {
-    achievements: Vec<(u64, String)>,
-    comment: String,
+// ============================================================================
+// Sessions
+// ============================================================================
+
+/// Exchange the one-time code from the login redirect for session tokens
+pub async fn exchange_login_code(code: &str) -> Result<AuthTokenResponse, String> {
+    send(None, &AuthCodeExchangeRequest { code: code.to_string() }).await
 }
 
-#[derive(Deserialize)]
-#[allow(dead_code)]
-pub struct AchievementCommentResponse {
-    pub success: bool,
-    pub count: usize,
+/// Get a new access token; the refresh token is replaced by the returned one
+pub async fn refresh_session(refresh_token: &str) -> Result<AuthTokenResponse, String> {
+    send(None, &RefreshSession(RefreshTokenRequest { refresh_token: refresh_token.to_string() })).await
 }
 
-#[derive(Deserialize)]
-struct UserAchievementRatingsResponse {
-    ratings: Vec<AchievementRatingEntry>,
+/// Log out the session of a refresh token
+pub async fn revoke_session(refresh_token: &str) -> Result<(), String> {
+    send(None, &RevokeSession(RefreshTokenRequest { refresh_token: refresh_token.to_string() }))
+        .await
+        .map(|_| ())
 }
 
-#[derive(Deserialize)]
-struct AchievementRatingEntry {
-    appid: u64,
-    apiname: String,
-    rating: u8,
+/// Log out all sessions of the user, on every device
+pub async fn logout_all_devices(token: &str) -> Result<(), String> {
+    send(Some(token), &LogoutAllSessions).await.map(|_| ())
 }
 
 // ============================================================================
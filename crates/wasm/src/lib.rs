@@ -8,6 +8,7 @@
 mod app;
 mod gdpr;
 mod panels;
+mod session;
 mod platforms;
 mod steam_images;
 mod storage;
@@ -3,13 +3,13 @@
 use eframe::egui;
 use egui_phosphor::regular;
 use overachiever_core::{
-    GdprConsent, SidebarPanel, StatsPanelConfig,
-    render_stats_content, render_log_content, render_filter_bar, render_games_table,
+    GdprConsent, SidebarPanel, StatsPanelConfig, FeedPanelPlatform,
+    render_stats_content, render_log_content, render_feed_content, render_leaderboard_content, render_filter_bar, render_games_table,
     get_filtered_indices,
 };
 
 use crate::app::{WasmApp, ConnectionState};
-use crate::storage::{get_auth_url, clear_token_from_storage, clear_gdpr_consent_from_storage};
+use crate::storage::{get_auth_url, clear_gdpr_consent_from_storage};
 
 impl WasmApp {
     // ========================================================================
@@ -117,13 +117,19 @@ impl WasmApp {
                         }
                     } else if is_authenticated {
                         if ui.button(format!("{} Logout", regular::SIGN_OUT)).clicked() {
-                            self.auth_token = None;
-                            clear_token_from_storage();
+                            self.end_session();
                             self.connection_state = ConnectionState::Disconnected;
                             self.games.clear();
                             self.games_loaded = false;
                         }
                         
+                        if ui.button(format!("{} Log out all devices", regular::DEVICES))
+                            .on_hover_text("End every session of your account, in all browsers and the desktop app")
+                            .clicked()
+                        {
+                            self.logout_all_devices();
+                        }
+                        
                         // GDPR button - only show if consent has been set
                         if self.gdpr_consent.is_set() {
                             if ui.button(format!("{} Privacy", regular::SHIELD_CHECK))
@@ -178,7 +184,7 @@ impl WasmApp {
             .fill(darker_fill);
         
         if !self.show_stats_panel {
-            // Collapsed sidebar - show two buttons (Stats and Log)
+            // Collapsed sidebar - show buttons for each panel (Stats, Log, Friends and Leaderboards)
             egui::SidePanel::right("stats_panel_collapsed")
                 .exact_width(36.0)
                 .resizable(false)
@@ -201,6 +207,22 @@ impl WasmApp {
                         self.sidebar_panel = SidebarPanel::Log;
                         self.show_stats_panel = true;
                     }
+                    // Friends button
+                    if ui.button(regular::USERS.to_string())
+                        .on_hover_text("Open Friends Panel")
+                        .clicked()
+                    {
+                        self.sidebar_panel = SidebarPanel::Friends;
+                        self.show_stats_panel = true;
+                    }
+                    // Leaderboards button
+                    if ui.button(regular::TROPHY.to_string())
+                        .on_hover_text("Open Leaderboards Panel")
+                        .clicked()
+                    {
+                        self.sidebar_panel = SidebarPanel::Leaderboards;
+                        self.show_stats_panel = true;
+                    }
                 });
             return;
         }
@@ -237,6 +259,8 @@ impl WasmApp {
                     // Panel navigation tabs
                     let stats_selected = self.sidebar_panel == SidebarPanel::Stats;
                     let log_selected = self.sidebar_panel == SidebarPanel::Log;
+                    let friends_selected = self.sidebar_panel == SidebarPanel::Friends;
+                    let leaderboards_selected = self.sidebar_panel == SidebarPanel::Leaderboards;
                     
                     if ui.selectable_label(stats_selected, format!("{} Stats", regular::CHART_LINE)).clicked() {
                         self.sidebar_panel = SidebarPanel::Stats;
@@ -244,6 +268,12 @@ impl WasmApp {
                     if ui.selectable_label(log_selected, format!("{} Log", regular::SCROLL)).clicked() {
                         self.sidebar_panel = SidebarPanel::Log;
                     }
+                    if ui.selectable_label(friends_selected, format!("{} Friends", regular::USERS)).clicked() {
+                        self.sidebar_panel = SidebarPanel::Friends;
+                    }
+                    if ui.selectable_label(leaderboards_selected, format!("{} Leaderboards", regular::TROPHY)).clicked() {
+                        self.sidebar_panel = SidebarPanel::Leaderboards;
+                    }
                 });
                 ui.separator();
                 
@@ -256,6 +286,15 @@ impl WasmApp {
                         SidebarPanel::Log => {
                             render_log_content(ui, self);
                         }
+                        SidebarPanel::Friends => {
+                            if !self.feed_loaded {
+                                self.refresh_feed();
+                            }
+                            render_feed_content(ui, self);
+                        }
+                        SidebarPanel::Leaderboards => {
+                            render_leaderboard_content(ui, self);
+                        }
                     }
                 });
             });
@@ -2,12 +2,13 @@
 
 use eframe::egui;
 use overachiever_core::{
-    Game, GameAchievement, RunHistory, AchievementHistory, LogEntry,
-    StatsPanelPlatform, GamesTablePlatform, SortColumn, SortOrder, TriFilter,
+    Game, GameAchievement, GameRarity, AchievementComment, AchievementTip, AchievementFlag, AchievementFlags, CommentThreadState, GameReviewCache, RunHistory, AchievementHistory, LogEntry, UserProfile, FeedEntry,
+    Leaderboard, LeaderboardViewState,
+    StatsPanelPlatform, GamesTablePlatform, FeedPanelPlatform, LeaderboardPanelPlatform, SortColumn, SortOrder, TriFilter,
     sort_games,
 };
 
-use crate::app::{WasmApp, ConnectionState, AppState};
+use crate::app::{WasmApp, ConnectionState, AppState, CommentUpdate};
 use crate::steam_images::{game_icon_url, proxy_steam_image_url};
 
 // ============================================================================
@@ -79,6 +80,27 @@ impl StatsPanelPlatform for WasmApp {
         self.submit_achievement_rating(appid, apiname, rating);
     }
     
+    fn get_user_achievement_flags(&self, appid: u64, apiname: &str) -> AchievementFlags {
+        self.user_achievement_flags.get(&(appid, apiname.to_string())).copied().unwrap_or_default()
+    }
+    
+    fn set_user_achievement_flags(&mut self, appid: u64, apiname: String, flags: AchievementFlags) {
+        if flags.is_empty() {
+            self.user_achievement_flags.remove(&(appid, apiname.clone()));
+        } else {
+            self.user_achievement_flags.insert((appid, apiname.clone()), flags);
+        }
+        
+        if let Some(token) = &self.auth_token {
+            let token = token.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Err(e) = crate::http_client::submit_achievement_flags(&token, appid, &apiname, flags).await {
+                    web_sys::console::error_1(&format!("Failed to submit flags: {}", e).into());
+                }
+            });
+        }
+    }
+    
     fn achievements_graph_tab(&self) -> usize {
         self.achievements_graph_tab
     }
@@ -99,11 +121,16 @@ impl StatsPanelPlatform for WasmApp {
         self.auth_token.is_some()
     }
     
+    fn get_achievement_avg_rating(&self, appid: u64, apiname: &str) -> Option<(f32, i32)> {
+        self.difficulty.achievement(appid, apiname)
+    }
+    
     fn navigate_to_achievement(&mut self, appid: u64, apiname: String) {
         // Clear filters so the game is visible
         self.filter_name.clear();
         self.filter_achievements = TriFilter::All;
         self.filter_playtime = TriFilter::All;
+        self.filter_missables = TriFilter::All;
         
         // Expand the game row
         self.expanded_rows.insert(appid);
@@ -149,7 +176,7 @@ impl GamesTablePlatform for WasmApp {
             self.sort_column = column;
             self.sort_order = SortOrder::Ascending;
         }
-        sort_games(&mut self.games, self.sort_column, self.sort_order);
+        sort_games(&mut self.games, self.sort_column, self.sort_order, &self.difficulty);
     }
     
     fn filter_name(&self) -> &str {
@@ -176,6 +203,14 @@ impl GamesTablePlatform for WasmApp {
         self.filter_playtime = filter;
     }
     
+    fn filter_missables(&self) -> TriFilter {
+        self.filter_missables
+    }
+    
+    fn set_filter_missables(&mut self, filter: TriFilter) {
+        self.filter_missables = filter;
+    }
+    
     fn is_expanded(&self, appid: u64) -> bool {
         self.expanded_rows.contains(&appid)
     }
@@ -198,6 +233,173 @@ impl GamesTablePlatform for WasmApp {
         }
     }
     
+    fn game_avg_difficulty(&self, appid: u64) -> Option<f32> {
+        self.difficulty.game(appid)
+    }
+    
+    fn game_rating(&self, appid: u64) -> Option<(f32, i32)> {
+        self.difficulty.game_rating(appid)
+    }
+    
+    fn community_achievement_flags(&self, appid: u64, apiname: &str) -> Vec<(AchievementFlag, i32)> {
+        self.difficulty.achievement_flags(appid, apiname).to_vec()
+    }
+    
+    fn game_has_missables(&self, appid: u64) -> bool {
+        self.difficulty.game_has_missables(appid)
+    }
+    
+    fn can_flag_achievements(&self) -> bool {
+        true
+    }
+    
+    fn game_reviews(&mut self) -> Option<&mut GameReviewCache> {
+        Some(&mut self.reviews)
+    }
+    
+    fn request_game_reviews(&mut self, appid: u64) {
+        if !self.reviews.start_request(appid) {
+            return;
+        }
+        let token = self.auth_token.clone();
+        let pending = self.pending_comment_updates.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match crate::http_client::fetch_game_ratings(token.as_deref(), appid).await {
+                Ok(community) => pending.borrow_mut().push(CommentUpdate::Ratings(community)),
+                Err(e) => web_sys::console::error_1(&format!("Failed to fetch ratings: {}", e).into()),
+            }
+        });
+    }
+    
+    fn submit_game_rating(&mut self, appid: u64, rating: u8, review: Option<String>) {
+        let Some(token) = self.auth_token.clone() else { return };
+        let pending = self.pending_comment_updates.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match crate::http_client::submit_game_rating(&token, appid, rating, review).await {
+                Ok(community) => pending.borrow_mut().push(CommentUpdate::Ratings(community)),
+                Err(e) => web_sys::console::error_1(&format!("Failed to submit rating: {}", e).into()),
+            }
+        });
+    }
+    
+    fn comment_thread(&mut self) -> Option<&mut CommentThreadState> {
+        Some(&mut self.comments.thread)
+    }
+    
+    fn achievement_comments(&self, appid: u64, apiname: &str) -> Option<&[AchievementComment]> {
+        self.comments.get(appid, apiname)
+    }
+    
+    fn request_achievement_comments(&mut self, appid: u64, apiname: &str) {
+        if !self.comments.start_request(appid, apiname) {
+            return;
+        }
+        let token = self.auth_token.clone();
+        let apiname = apiname.to_string();
+        let pending = self.pending_comment_updates.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match crate::http_client::fetch_achievement_comments(token.as_deref(), appid, &apiname).await {
+                Ok(comments) => pending.borrow_mut().push(CommentUpdate::Thread { appid, apiname, comments }),
+                Err(e) => web_sys::console::error_1(&format!("Failed to fetch comments: {}", e).into()),
+            }
+        });
+    }
+    
+    fn post_achievement_comment(&mut self, appid: u64, apiname: String, comment: String) {
+        let Some(token) = self.auth_token.clone() else { return };
+        let pending = self.pending_comment_updates.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match crate::http_client::submit_achievement_comment(&token, vec![(appid, apiname)], &comment).await {
+                Ok(resp) => pending.borrow_mut().push(CommentUpdate::Posted(resp.comment)),
+                Err(e) => web_sys::console::error_1(&format!("Failed to post comment: {}", e).into()),
+            }
+        });
+    }
+    
+    fn edit_achievement_comment(&mut self, id: i64, comment: String) {
+        let Some(token) = self.auth_token.clone() else { return };
+        let pending = self.pending_comment_updates.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match crate::http_client::edit_achievement_comment(&token, id, &comment).await {
+                Ok(comment) => pending.borrow_mut().push(CommentUpdate::Updated(comment)),
+                Err(e) => web_sys::console::error_1(&format!("Failed to edit comment: {}", e).into()),
+            }
+        });
+    }
+    
+    fn delete_achievement_comment(&mut self, id: i64) {
+        let Some(token) = self.auth_token.clone() else { return };
+        let pending = self.pending_comment_updates.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match crate::http_client::delete_achievement_comment(&token, id).await {
+                Ok(()) => pending.borrow_mut().push(CommentUpdate::Deleted(id)),
+                Err(e) => web_sys::console::error_1(&format!("Failed to delete comment: {}", e).into()),
+            }
+        });
+    }
+    
+    fn achievement_tips(&self, appid: u64, apiname: &str) -> Option<&[AchievementTip]> {
+        self.comments.tips(appid, apiname)
+    }
+    
+    fn request_achievement_tips(&mut self, appid: u64, apiname: &str) {
+        if !self.comments.start_tips_request(appid, apiname) {
+            return;
+        }
+        let token = self.auth_token.clone();
+        let apiname = apiname.to_string();
+        let pending = self.pending_comment_updates.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match crate::http_client::fetch_achievement_tips(token.as_deref(), appid, &apiname).await {
+                Ok(tips) => pending.borrow_mut().push(CommentUpdate::Tips { appid, apiname, tips }),
+                Err(e) => web_sys::console::error_1(&format!("Failed to fetch tips: {}", e).into()),
+            }
+        });
+    }
+    
+    fn submit_achievement_tip(&mut self, appid: u64, apiname: String, difficulty: u8, tip: String) {
+        let Some(token) = self.auth_token.clone() else { return };
+        let pending = self.pending_comment_updates.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match crate::http_client::submit_achievement_tip(&token, appid, &apiname, difficulty, &tip).await {
+                Ok(tips) => pending.borrow_mut().push(CommentUpdate::Tips { appid, apiname, tips }),
+                Err(e) => web_sys::console::error_1(&format!("Failed to submit tip: {}", e).into()),
+            }
+        });
+    }
+    
+    fn vote_achievement_tip(&mut self, tip_id: i64, vote: i8) {
+        let Some(token) = self.auth_token.clone() else { return };
+        let pending = self.pending_comment_updates.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match crate::http_client::vote_achievement_tip(&token, tip_id, vote).await {
+                // The server replies with the tips of the voted tip's achievement
+                Ok(tips) => {
+                    if let Some((appid, apiname)) = tips.first().map(|t| (t.appid, t.apiname.clone())) {
+                        pending.borrow_mut().push(CommentUpdate::Tips { appid, apiname, tips });
+                    }
+                }
+                Err(e) => web_sys::console::error_1(&format!("Failed to vote on tip: {}", e).into()),
+            }
+        });
+    }
+    
+    fn achievement_rarity(&self, appid: u64) -> Option<&GameRarity> {
+        self.achievement_rarity.get(&appid)
+    }
+    
+    fn request_achievement_rarity(&mut self, appid: u64) {
+        // Wait for the connection, then ask once per session
+        let connected = matches!(self.connection_state, ConnectionState::Connected | ConnectionState::Authenticated(_));
+        if !connected || self.pending_rarity.contains(&appid) {
+            return;
+        }
+        if let Some(client) = &self.ws_client {
+            self.pending_rarity.insert(appid);
+            client.fetch_achievement_rarity(appid);
+        }
+    }
+    
     fn get_navigation_target(&self) -> Option<(u64, String)> {
         self.navigation_target.clone()
     }
@@ -239,3 +441,111 @@ impl GamesTablePlatform for WasmApp {
         self.single_game_refreshing == Some(appid)
     }
 }
+
+// ============================================================================
+// FeedPanelPlatform Implementation
+// ============================================================================
+
+impl FeedPanelPlatform for WasmApp {
+    fn following(&self) -> &[UserProfile] {
+        &self.following
+    }
+    
+    fn feed(&self) -> &[FeedEntry] {
+        &self.feed_entries
+    }
+    
+    fn feed_loading(&self) -> bool {
+        self.feed_loading
+    }
+    
+    fn feed_error(&self) -> Option<&str> {
+        self.feed_error.as_deref()
+    }
+    
+    fn follow_input(&mut self) -> &mut String {
+        &mut self.follow_input
+    }
+    
+    fn follow_user(&mut self, short_id: &str) {
+        if let Some(client) = &self.ws_client {
+            self.feed_loading = true;
+            client.follow_user(short_id);
+            client.fetch_feed();
+        }
+    }
+    
+    fn unfollow_user(&mut self, short_id: &str) {
+        if let Some(client) = &self.ws_client {
+            self.feed_loading = true;
+            client.unfollow_user(short_id);
+            client.fetch_feed();
+        }
+    }
+    
+    fn refresh_feed(&mut self) {
+        // Wait for the server to accept the token
+        if !matches!(self.connection_state, ConnectionState::Authenticated(_)) {
+            return;
+        }
+        if let Some(client) = &self.ws_client {
+            self.feed_loaded = true;
+            self.feed_loading = true;
+            client.fetch_following();
+            client.fetch_feed();
+        }
+    }
+    
+    fn open_profile(&mut self, short_id: &str) {
+        // Shared libraries live at /<short_id>
+        if let Some(window) = web_sys::window() {
+            let _ = window.location().set_href(&format!("/{}", short_id));
+        }
+    }
+}
+
+// ============================================================================
+// LeaderboardPanelPlatform Implementation
+// ============================================================================
+
+impl LeaderboardPanelPlatform for WasmApp {
+    fn leaderboard(&self) -> Option<&Leaderboard> {
+        self.leaderboard.as_ref()
+    }
+    
+    fn leaderboard_loading(&self) -> bool {
+        self.leaderboard_loading
+    }
+    
+    fn leaderboard_error(&self) -> Option<&str> {
+        self.leaderboard_error.as_deref()
+    }
+    
+    fn leaderboard_view(&self) -> LeaderboardViewState {
+        self.leaderboard_view
+    }
+    
+    fn request_leaderboard(&mut self, view: LeaderboardViewState) {
+        self.leaderboard_view = view;
+        // Wait for the server to accept the token
+        if !matches!(self.connection_state, ConnectionState::Authenticated(_)) {
+            return;
+        }
+        if let Some(client) = &self.ws_client {
+            self.leaderboard_loading = true;
+            client.fetch_leaderboard(view.metric, view.scope);
+        }
+    }
+    
+    fn set_leaderboard_opt_in(&mut self, enabled: bool) {
+        if let Some(client) = &self.ws_client {
+            self.leaderboard_loading = true;
+            client.set_leaderboard_opt_in(enabled);
+            client.fetch_leaderboard(self.leaderboard_view.metric, self.leaderboard_view.scope);
+        }
+    }
+    
+    fn open_profile(&mut self, short_id: &str) {
+        FeedPanelPlatform::open_profile(self, short_id);
+    }
+}
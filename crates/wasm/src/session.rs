@@ -0,0 +1,166 @@
+//! Login session: code exchange after the Steam login, access token refresh
+//! and logout
+//!
+//! Access tokens are short-lived. They are refreshed shortly before they
+//! expire, or right away when the server reports an expired token, and the
+//! open WebSocket is authenticated again with the new token.
+
+use overachiever_core::AuthTokenResponse;
+
+use crate::app::{ConnectionState, WasmApp};
+use crate::storage::{clear_token_from_storage, save_session_to_storage};
+
+/// Seconds to wait before retrying a refresh that failed without the server rejecting it
+const REFRESH_RETRY_SECS: i64 = 30;
+
+/// Result of a session request, applied on the next frame
+pub enum SessionUpdate {
+    /// Tokens for the login code from the Steam login redirect
+    LoggedIn(AuthTokenResponse),
+    Refreshed(AuthTokenResponse),
+    /// Exchanging the login code or refreshing failed
+    Failed(String),
+    LoggedOutEverywhere,
+    LogoutAllFailed(String),
+}
+
+impl WasmApp {
+    /// Exchange the login code from the URL for session tokens
+    pub(crate) fn exchange_login_code(&mut self, code: String) {
+        self.session_request_in_flight = true;
+        self.status = "Signing in...".to_string();
+        let pending = self.pending_session.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let update = match crate::http_client::exchange_login_code(&code).await {
+                Ok(tokens) => SessionUpdate::LoggedIn(tokens),
+                Err(e) => SessionUpdate::Failed(e),
+            };
+            *pending.borrow_mut() = Some(update);
+        });
+    }
+
+    /// Refresh the access token as soon as possible
+    pub(crate) fn request_token_refresh(&mut self) {
+        self.token_refresh_requested = true;
+    }
+
+    /// Start a token refresh when one is due; called every frame
+    pub(crate) fn check_token_refresh(&mut self) {
+        if self.session_request_in_flight {
+            return;
+        }
+        // Sessions from before refresh tokens can't be renewed
+        let Some(refresh_token) = self.refresh_token.clone() else {
+            return;
+        };
+        let now = chrono::Utc::now().timestamp();
+        let due = self.token_refresh_requested
+            || AuthTokenResponse::needs_refresh(self.token_expires_at.unwrap_or(0));
+        if !due || self.token_refresh_retry_at.is_some_and(|at| now < at) {
+            return;
+        }
+
+        self.token_refresh_retry_at = None;
+        self.session_request_in_flight = true;
+        let pending = self.pending_session.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let update = match crate::http_client::refresh_session(&refresh_token).await {
+                Ok(tokens) => SessionUpdate::Refreshed(tokens),
+                Err(e) => SessionUpdate::Failed(e),
+            };
+            *pending.borrow_mut() = Some(update);
+        });
+    }
+
+    /// Apply a finished session request
+    pub(crate) fn process_pending_session(&mut self) {
+        let Some(update) = self.pending_session.borrow_mut().take() else {
+            return;
+        };
+        match update {
+            SessionUpdate::LoggedIn(tokens) => {
+                self.session_request_in_flight = false;
+                save_session_to_storage(&tokens);
+                // Reload without the login code in the URL
+                if let Some(window) = web_sys::window() {
+                    let _ = window.location().replace("/");
+                }
+            }
+            SessionUpdate::Refreshed(tokens) => {
+                self.session_request_in_flight = false;
+                self.token_refresh_requested = false;
+                save_session_to_storage(&tokens);
+                // Keep the connection, just authenticate again
+                let connected = matches!(self.connection_state, ConnectionState::Connected | ConnectionState::Authenticated(_));
+                if connected {
+                    if let Some(client) = &self.ws_client {
+                        client.authenticate(&tokens.token);
+                    }
+                }
+                self.auth_token = Some(tokens.token);
+                self.refresh_token = Some(tokens.refresh_token);
+                self.token_expires_at = Some(tokens.expires_at);
+            }
+            SessionUpdate::Failed(e) => {
+                self.session_request_in_flight = false;
+                let signing_in = self.refresh_token.is_none();
+                if signing_in || e.contains("401") {
+                    // Login code expired, or the session ended (e.g. logged out on another device)
+                    self.clear_session();
+                    self.connection_state = ConnectionState::Error(e.clone());
+                    self.status = format!("Auth failed: {}", e);
+                    self.show_login = true;
+                } else {
+                    web_sys::console::error_1(&format!("Failed to refresh session: {}", e).into());
+                    self.token_refresh_retry_at = Some(chrono::Utc::now().timestamp() + REFRESH_RETRY_SECS);
+                }
+            }
+            SessionUpdate::LoggedOutEverywhere => {
+                self.clear_session();
+                self.connection_state = ConnectionState::Disconnected;
+                self.games.clear();
+                self.games_loaded = false;
+                self.status = "Logged out on all devices".to_string();
+            }
+            SessionUpdate::LogoutAllFailed(e) => {
+                self.status = format!("Failed to log out all devices: {}", e);
+            }
+        }
+    }
+
+    /// Forget the session in this browser
+    pub(crate) fn clear_session(&mut self) {
+        self.auth_token = None;
+        self.refresh_token = None;
+        self.token_expires_at = None;
+        self.token_refresh_requested = false;
+        clear_token_from_storage();
+    }
+
+    /// Log out: end the session on the server and forget it here
+    pub(crate) fn end_session(&mut self) {
+        if let Some(refresh_token) = self.refresh_token.clone() {
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Err(e) = crate::http_client::revoke_session(&refresh_token).await {
+                    web_sys::console::error_1(&format!("Failed to revoke session: {}", e).into());
+                }
+            });
+        }
+        self.clear_session();
+    }
+
+    /// End every session of the account, on all devices
+    pub(crate) fn logout_all_devices(&mut self) {
+        let Some(token) = self.auth_token.clone() else {
+            return;
+        };
+        let pending = self.pending_session.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let update = match crate::http_client::logout_all_devices(&token).await {
+                Ok(()) => SessionUpdate::LoggedOutEverywhere,
+                Err(e) => SessionUpdate::LogoutAllFailed(e),
+            };
+            *pending.borrow_mut() = Some(update);
+        });
+    }
+}
@@ -1,23 +1,24 @@
 //! Browser storage helpers for tokens and URL parsing
 
-use overachiever_core::GdprConsent;
+use overachiever_core::{AuthTokenResponse, GdprConsent};
 
 // ============================================================================
 // Token Management
 // ============================================================================
 
-pub fn get_token_from_url() -> Option<String> {
+/// One-time login code the server redirects to after a Steam login
+pub fn get_login_code_from_url() -> Option<String> {
     web_sys::window()
         .and_then(|w| w.location().search().ok())
         .and_then(|search| {
             search.strip_prefix('?')
                 .and_then(|s| {
                     s.split('&')
-                        .find(|p| p.starts_with("token="))
-                        .map(|p| p.strip_prefix("token=").unwrap_or("").to_string())
+                        .find(|p| p.starts_with("code="))
+                        .map(|p| p.strip_prefix("code=").unwrap_or("").to_string())
                 })
         })
-        .filter(|t| !t.is_empty())
+        .filter(|c| !c.is_empty())
 }
 
 /// Get short_id from URL path (e.g., /IHh1wBke -> Some("IHh1wBke"))
@@ -62,12 +63,45 @@ pub fn save_token_to_storage(token: &str) {
     }
 }
 
+/// Store the tokens of a login session
+pub fn save_session_to_storage(tokens: &AuthTokenResponse) {
+    if let Some(storage) = web_sys::window()
+        .and_then(|w| w.local_storage().ok())
+        .flatten()
+    {
+        let _ = storage.set_item("overachiever_token", &tokens.token);
+        let _ = storage.set_item("overachiever_refresh_token", &tokens.refresh_token);
+        let _ = storage.set_item("overachiever_token_expires_at", &tokens.expires_at.to_string());
+    }
+}
+
+pub fn get_refresh_token_from_storage() -> Option<String> {
+    web_sys::window()
+        .and_then(|w| w.local_storage().ok())
+        .flatten()
+        .and_then(|storage| storage.get_item("overachiever_refresh_token").ok())
+        .flatten()
+}
+
+/// When the stored access token expires (Unix timestamp)
+pub fn get_token_expiry_from_storage() -> Option<i64> {
+    web_sys::window()
+        .and_then(|w| w.local_storage().ok())
+        .flatten()
+        .and_then(|storage| storage.get_item("overachiever_token_expires_at").ok())
+        .flatten()
+        .and_then(|s| s.parse().ok())
+}
+
+/// Remove the access token and the session's refresh token
 pub fn clear_token_from_storage() {
     if let Some(storage) = web_sys::window()
         .and_then(|w| w.local_storage().ok())
         .flatten()
     {
         let _ = storage.remove_item("overachiever_token");
+        let _ = storage.remove_item("overachiever_refresh_token");
+        let _ = storage.remove_item("overachiever_token_expires_at");
     }
 }
 
@@ -1,6 +1,6 @@
 //! WebSocket client for WASM
 
-use overachiever_core::{ClientMessage, ServerMessage};
+use overachiever_core::{ClientMessage, ServerMessage, LeaderboardMetric, LeaderboardScope};
 use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
@@ -139,6 +139,53 @@ impl WsClient {
         self.send(&ClientMessage::FetchGuestHistory { short_id: short_id.to_string() });
     }
     
+    /// Follow a user by short_id (requires authentication)
+    pub fn follow_user(&self, short_id: &str) {
+        self.send(&ClientMessage::FollowUser { short_id: short_id.to_string() });
+    }
+    
+    /// Stop following a user by short_id (requires authentication)
+    pub fn unfollow_user(&self, short_id: &str) {
+        self.send(&ClientMessage::UnfollowUser { short_id: short_id.to_string() });
+    }
+    
+    pub fn fetch_following(&self) {
+        self.send(&ClientMessage::FetchFollowing);
+    }
+    
+    pub fn fetch_feed(&self) {
+        self.send(&ClientMessage::FetchFeed);
+    }
+    
+    /// Community and Steam unlock rates for a game (no authentication needed)
+    pub fn fetch_achievement_rarity(&self, appid: u64) {
+        self.send(&ClientMessage::FetchAchievementRarity { appid });
+    }
+    
+    /// Community difficulty averages for the achievements of some games
+    pub fn fetch_achievement_averages(&self, appids: Vec<u64>) {
+        self.send(&ClientMessage::FetchAchievementAverages { appids });
+    }
+    
+    /// Community rating averages of some games
+    pub fn fetch_game_rating_averages(&self, appids: Vec<u64>) {
+        self.send(&ClientMessage::FetchGameRatingAverages { appids });
+    }
+    
+    /// Community-confirmed flags for the achievements of some games
+    pub fn fetch_achievement_flags(&self, appids: Vec<u64>) {
+        self.send(&ClientMessage::FetchAchievementFlags { appids });
+    }
+    
+    pub fn fetch_leaderboard(&self, metric: LeaderboardMetric, scope: LeaderboardScope) {
+        self.send(&ClientMessage::FetchLeaderboard { metric, scope });
+    }
+    
+    /// Show or hide the user on global leaderboards (requires authentication)
+    pub fn set_leaderboard_opt_in(&self, enabled: bool) {
+        self.send(&ClientMessage::SetLeaderboardOptIn { enabled });
+    }
+    
     #[allow(dead_code)]
     pub fn submit_rating(&self, appid: u64, rating: u8, comment: Option<String>) {
         self.send(&ClientMessage::SubmitRating { appid, rating, comment });